@@ -18,7 +18,7 @@ use {
         sync::Arc,
         time::Duration,
     },
-    tokio::sync::{mpsc::Sender, RwLock},
+    tokio::sync::{mpsc::Sender, watch, RwLock},
     tokio_util::sync::CancellationToken,
     yellowstone_grpc_client::{GeyserGrpcBuilder, GeyserGrpcBuilderResult, GeyserGrpcClient},
     yellowstone_grpc_proto::{
@@ -43,6 +43,16 @@ pub struct YellowstoneGrpcGeyserClient {
     pub block_filters: BlockFilters,
     pub account_deletions_tracked: Arc<RwLock<HashSet<Pubkey>>>,
     pub geyser_config: YellowstoneGrpcClientConfig,
+    filter_updates: Option<watch::Receiver<FilterUpdate>>,
+}
+
+/// Account and transaction filters applied to a running subscription.
+///
+/// See [`YellowstoneGrpcGeyserClient::with_filter_updates`].
+#[derive(Default, Debug, Clone)]
+pub struct FilterUpdate {
+    pub account_filters: HashMap<String, SubscribeRequestFilterAccounts>,
+    pub transaction_filters: HashMap<String, SubscribeRequestFilterTransactions>,
 }
 
 #[derive(Debug, Clone)]
@@ -95,8 +105,32 @@ impl YellowstoneGrpcGeyserClient {
             block_filters,
             account_deletions_tracked,
             geyser_config,
+            filter_updates: None,
+        }
+    }
+
+    /// Takes the account and transaction filters from `updates` instead of
+    /// the ones given to [`Self::new`].
+    ///
+    /// The receiver's current value is used when subscribing, and every
+    /// later value is sent over the open stream, so filters change without
+    /// reconnecting. Block filters and the commitment are unchanged.
+    pub fn with_filter_updates(mut self, updates: watch::Receiver<FilterUpdate>) -> Self {
+        self.filter_updates = Some(updates);
+        self
+    }
+}
+
+/// Waits for the next filter update; pending forever without a (live) sender.
+async fn next_filter_update(updates: &mut Option<watch::Receiver<FilterUpdate>>) -> FilterUpdate {
+    if let Some(receiver) = updates {
+        if receiver.changed().await.is_ok() {
+            return receiver.borrow_and_update().clone();
         }
+        // Sender dropped: keep the current filters
+        *updates = None;
     }
+    std::future::pending().await
 }
 
 impl YellowstoneGrpcClientConfig {
@@ -167,6 +201,14 @@ impl Datasource for YellowstoneGrpcGeyserClient {
             failed_transactions: block_failed_transactions,
         } = self.block_filters.clone();
         let retain_block_failed_transactions = block_failed_transactions.unwrap_or(true);
+        let mut filter_updates = self.filter_updates.clone();
+        let (account_filters, transaction_filters) = match filter_updates.as_mut() {
+            Some(updates) => {
+                let current = updates.borrow_and_update().clone();
+                (current.account_filters, current.transaction_filters)
+            }
+            None => (account_filters, transaction_filters),
+        };
 
         let builder = GeyserGrpcClient::build_from_shared(endpoint)
             .map_err(|err| carbon_core::error::Error::FailedToConsumeDatasource(err.to_string()))?
@@ -182,7 +224,7 @@ impl Datasource for YellowstoneGrpcGeyserClient {
             .map_err(|err| carbon_core::error::Error::FailedToConsumeDatasource(err.to_string()))?;
 
         tokio::spawn(async move {
-            let subscribe_request = SubscribeRequest {
+            let mut subscribe_request = SubscribeRequest {
                 slots: HashMap::new(),
                 accounts: account_filters,
                 transactions: transaction_filters,
@@ -207,7 +249,23 @@ impl Datasource for YellowstoneGrpcGeyserClient {
                     result = geyser_client.subscribe_with_request(Some(subscribe_request.clone())) => {
                         match result {
                             Ok((mut subscribe_tx, mut stream)) => {
-                                while let Some(message) = stream.next().await {
+                                loop {
+                                    let message = tokio::select! {
+                                        message = stream.next() => message,
+                                        filters = next_filter_update(&mut filter_updates) => {
+                                            subscribe_request.accounts = filters.account_filters;
+                                            subscribe_request.transactions = filters.transaction_filters;
+                                            if let Err(error) = subscribe_tx.send(subscribe_request.clone()).await {
+                                                log::error!("Failed to send filter update: {error:?}");
+                                                break;
+                                            }
+                                            log::info!("Updated Yellowstone gRPC subscription filters.");
+                                            continue;
+                                        }
+                                    };
+                                    let Some(message) = message else {
+                                        break;
+                                    };
                                     if cancellation_token.is_cancelled() {
                                         break;
                                     }
@@ -42,8 +42,11 @@ function tableExists(db: ReturnType<typeof getDb>, tableName: string): boolean {
   try {
     const result = db.prepare(`
       SELECT name FROM sqlite_master 
-      WHERE type='table' AND name=?
-    `).get(tableName);
+      WHERE type IN ('table', 'view') AND name=?
+      UNION ALL
+      SELECT name FROM sqlite_temp_master
+      WHERE type = 'view' AND name=?
+    `).get(tableName, tableName);
     return result !== undefined;
   } catch {
     return false;
@@ -190,11 +193,12 @@ export async function GET() {
             mint,
             signal_type,
             created_at,
+            source,
             ROW_NUMBER() OVER (PARTITION BY mint ORDER BY created_at DESC) as rn
           FROM token_signals
           WHERE mint IN (${tokenMints.map(() => '?').join(',')})
         )
-        SELECT mint, signal_type, created_at
+        SELECT mint, signal_type, created_at, source
         FROM latest_signals
         WHERE rn = 1
       `;
@@ -204,12 +208,14 @@ export async function GET() {
           mint: string;
           signal_type: string;
           created_at: number;
+          source: string;
         }>;
         
         signalRows.forEach(row => {
           signals[row.mint] = {
             signalType: row.signal_type,
             createdAt: row.created_at,
+            source: row.source,
           };
         });
       } catch (error) {
@@ -0,0 +1,19 @@
+import { NextResponse } from 'next/server';
+import { closePosition } from '@/lib/queries';
+
+export async function POST(request: Request) {
+  try {
+    const { id } = await request.json();
+
+    if (typeof id !== 'number') {
+      return NextResponse.json({ error: 'Invalid parameters' }, { status: 400 });
+    }
+
+    closePosition(id);
+
+    return NextResponse.json({ ok: true });
+  } catch (error) {
+    console.error('Error closing position:', error);
+    return NextResponse.json({ error: 'Failed to close position' }, { status: 500 });
+  }
+}
@@ -0,0 +1,33 @@
+import { NextResponse } from 'next/server';
+import { getOpenPositions, openPosition } from '@/lib/queries';
+
+export async function GET() {
+  try {
+    const positions = getOpenPositions();
+    return NextResponse.json({ positions });
+  } catch (error) {
+    console.error('Error fetching positions:', error);
+    return NextResponse.json({ error: 'Failed to fetch positions' }, { status: 500 });
+  }
+}
+
+export async function POST(request: Request) {
+  try {
+    const { mint, sizeTokens, entryPriceSol, note } = await request.json();
+
+    if (
+      !mint ||
+      typeof sizeTokens !== 'number' || sizeTokens <= 0 ||
+      typeof entryPriceSol !== 'number' || entryPriceSol <= 0
+    ) {
+      return NextResponse.json({ error: 'Invalid parameters' }, { status: 400 });
+    }
+
+    openPosition(mint, sizeTokens, entryPriceSol, typeof note === 'string' && note ? note : null);
+
+    return NextResponse.json({ ok: true });
+  } catch (error) {
+    console.error('Error opening position:', error);
+    return NextResponse.json({ error: 'Failed to open position' }, { status: 500 });
+  }
+}
@@ -0,0 +1,197 @@
+'use client';
+
+import { useState, useEffect } from 'react';
+import { Position, TokenMetadata } from '@/lib/types';
+
+interface PositionsPanelProps {
+  metadata: Record<string, TokenMetadata>;
+}
+
+const EXIT_LABELS: Record<string, string> = {
+  STOP_LOSS: '🛑 Stop loss',
+  SELL_PRESSURE: '📉 Sell pressure',
+  TAKE_PROFIT: '🎯 Take profit',
+};
+
+function formatSol(value: number | null, digits: number = 4): string {
+  return value === null ? '—' : `${value.toFixed(digits)} SOL`;
+}
+
+export default function PositionsPanel({ metadata }: PositionsPanelProps) {
+  const [positions, setPositions] = useState<Position[]>([]);
+  const [mint, setMint] = useState('');
+  const [sizeTokens, setSizeTokens] = useState('');
+  const [entryPriceSol, setEntryPriceSol] = useState('');
+  const [error, setError] = useState<string | null>(null);
+
+  async function fetchPositions() {
+    try {
+      const response = await fetch('/api/positions');
+      if (response.ok) {
+        const data = await response.json();
+        setPositions(data.positions ?? []);
+      }
+    } catch (err) {
+      console.error('Failed to fetch positions:', err);
+    }
+  }
+
+  useEffect(() => {
+    fetchPositions();
+    const interval = setInterval(fetchPositions, 10000);
+    return () => clearInterval(interval);
+  }, []);
+
+  async function handleOpen(event: React.FormEvent) {
+    event.preventDefault();
+    const size = parseFloat(sizeTokens);
+    const price = parseFloat(entryPriceSol);
+    if (!mint.trim() || !(size > 0) || !(price > 0)) {
+      setError('Mint, size and entry price are required');
+      return;
+    }
+
+    try {
+      const response = await fetch('/api/positions', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ mint: mint.trim(), sizeTokens: size, entryPriceSol: price }),
+      });
+      if (response.ok) {
+        setMint('');
+        setSizeTokens('');
+        setEntryPriceSol('');
+        setError(null);
+        fetchPositions();
+      } else {
+        setError('Failed to record position');
+      }
+    } catch (err) {
+      console.error('Failed to open position:', err);
+      setError('Failed to record position');
+    }
+  }
+
+  async function handleClose(id: number) {
+    try {
+      const response = await fetch('/api/positions/close', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ id }),
+      });
+      if (response.ok) {
+        fetchPositions();
+      }
+    } catch (err) {
+      console.error('Failed to close position:', err);
+    }
+  }
+
+  const totalPnl = positions.reduce((sum, p) => sum + (p.unrealizedPnlSol ?? 0), 0);
+
+  return (
+    <div className="mb-6 bg-gray-800 rounded-lg border border-gray-700">
+      <div className="flex items-center justify-between px-4 py-3 border-b border-gray-700">
+        <h2 className="text-sm font-semibold text-gray-200">
+          Positions ({positions.length})
+        </h2>
+        {positions.length > 0 && (
+          <span className={`text-sm font-mono ${totalPnl >= 0 ? 'text-green-400' : 'text-red-400'}`}>
+            {totalPnl >= 0 ? '+' : ''}{totalPnl.toFixed(4)} SOL
+          </span>
+        )}
+      </div>
+
+      {positions.length > 0 && (
+        <table className="w-full text-xs">
+          <thead className="text-gray-500">
+            <tr>
+              <th className="text-left px-4 py-2 font-medium">Token</th>
+              <th className="text-right px-2 py-2 font-medium">Size</th>
+              <th className="text-right px-2 py-2 font-medium">Entry</th>
+              <th className="text-right px-2 py-2 font-medium">Price</th>
+              <th className="text-right px-2 py-2 font-medium">PnL</th>
+              <th className="text-left px-2 py-2 font-medium">Exit</th>
+              <th className="px-4 py-2" />
+            </tr>
+          </thead>
+          <tbody>
+            {positions.map(position => {
+              const meta = metadata[position.mint];
+              const pnlClass = (position.pnlPct ?? 0) >= 0 ? 'text-green-400' : 'text-red-400';
+
+              return (
+                <tr key={position.id} className="border-t border-gray-700/50 hover:bg-gray-700/30">
+                  <td className="px-4 py-1.5">
+                    {meta?.symbol ? (
+                      <span className="font-medium text-gray-100">{meta.symbol}</span>
+                    ) : (
+                      <span className="font-mono text-gray-400">
+                        {position.mint.slice(0, 8)}...{position.mint.slice(-4)}
+                      </span>
+                    )}
+                  </td>
+                  <td className="text-right px-2 py-1.5 font-mono text-gray-300">
+                    {position.sizeTokens.toLocaleString()}
+                  </td>
+                  <td className="text-right px-2 py-1.5 font-mono text-gray-400">
+                    {position.entryPriceSol.toPrecision(4)}
+                  </td>
+                  <td className="text-right px-2 py-1.5 font-mono text-gray-300">
+                    {position.currentPriceSol === null ? '—' : position.currentPriceSol.toPrecision(4)}
+                  </td>
+                  <td className={`text-right px-2 py-1.5 font-mono ${pnlClass}`}>
+                    {position.pnlPct === null
+                      ? '—'
+                      : `${formatSol(position.unrealizedPnlSol)} (${position.pnlPct.toFixed(1)}%)`}
+                  </td>
+                  <td className="px-2 py-1.5 text-amber-400">
+                    {position.exitSignal ? EXIT_LABELS[position.exitSignal] ?? position.exitSignal : ''}
+                  </td>
+                  <td className="px-4 py-1.5 text-right">
+                    <button
+                      onClick={() => handleClose(position.id)}
+                      className="px-2 py-1 bg-gray-700 hover:bg-gray-600 rounded text-xs transition-colors"
+                    >
+                      Close
+                    </button>
+                  </td>
+                </tr>
+              );
+            })}
+          </tbody>
+        </table>
+      )}
+
+      <form onSubmit={handleOpen} className="flex items-center gap-2 px-4 py-3 border-t border-gray-700">
+        <input
+          value={mint}
+          onChange={e => setMint(e.target.value)}
+          placeholder="Mint address"
+          className="flex-1 min-w-0 px-2 py-1 bg-gray-900 border border-gray-700 rounded text-xs font-mono"
+        />
+        <input
+          value={sizeTokens}
+          onChange={e => setSizeTokens(e.target.value)}
+          placeholder="Tokens"
+          inputMode="decimal"
+          className="w-28 px-2 py-1 bg-gray-900 border border-gray-700 rounded text-xs font-mono"
+        />
+        <input
+          value={entryPriceSol}
+          onChange={e => setEntryPriceSol(e.target.value)}
+          placeholder="Entry (SOL/token)"
+          inputMode="decimal"
+          className="w-36 px-2 py-1 bg-gray-900 border border-gray-700 rounded text-xs font-mono"
+        />
+        <button
+          type="submit"
+          className="px-3 py-1 bg-blue-600 hover:bg-blue-700 text-white rounded text-xs transition-colors"
+        >
+          Add
+        </button>
+        {error && <span className="text-red-400 text-xs">{error}</span>}
+      </form>
+    </div>
+  );
+}
@@ -7,6 +7,7 @@ import TokenDashboard from './components/TokenDashboard';
 import BlockedTokensModal from './components/BlockedTokensModal';
 import FollowedTokensModal from './components/FollowedTokensModal';
 import SignalsLegend from './components/SignalsLegend';
+import PositionsPanel from './components/PositionsPanel';
 
 export default function Home() {
   const [dashboardData, setDashboardData] = useState<DashboardData | null>(null);
@@ -70,6 +71,8 @@ export default function Home() {
           </div>
         </header>
 
+        <PositionsPanel metadata={dashboardData?.metadata ?? {}} />
+
         {loading && !dashboardData ? (
           <div className="text-center py-12 text-gray-400">
             Loading dashboard...
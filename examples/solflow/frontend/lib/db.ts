@@ -1,23 +1,44 @@
 import Database from 'better-sqlite3';
 import path from 'path';
+import { openFederatedDb } from './federation';
 
 let db: Database.Database | null = null;
 
-function getDbPath(): string {
-  const dbPath = process.env.DB_PATH || '/var/lib/solflow/solflow.db';
-  
+function resolvePath(dbPath: string): string {
   // For development, allow relative path fallback
   return path.isAbsolute(dbPath) 
     ? dbPath 
     : path.resolve(process.cwd(), dbPath);
 }
 
+function getDbPath(): string {
+  return resolvePath(process.env.DB_PATH || '/var/lib/solflow/solflow.db');
+}
+
+/**
+ * Extra shard databases merged into reads (comma-separated DB_SHARDS).
+ * Writes always go to DB_PATH.
+ */
+function getShardPaths(): string[] {
+  return (process.env.DB_SHARDS || '')
+    .split(',')
+    .map(p => p.trim())
+    .filter(p => p.length > 0)
+    .map(resolvePath);
+}
+
 export function getDb(): Database.Database {
   if (db) {
     return db;
   }
 
   const resolvedPath = getDbPath();
+  const shardPaths = getShardPaths();
+  if (shardPaths.length > 0) {
+    db = openFederatedDb([resolvedPath, ...shardPaths]);
+    return db;
+  }
+
   db = new Database(resolvedPath, { readonly: true });
   
   // Enable WAL mode for consistent reads
@@ -0,0 +1,131 @@
+import Database from 'better-sqlite3';
+import fs from 'fs';
+
+/**
+ * Multi-database read federation
+ *
+ * Sharded deployments (one runtime per venue or region, each with its own
+ * solflow database) still need a single token table. The federated
+ * connection is an in-memory database with every shard ATTACHed read-only
+ * and a TEMP view per table that merges the shards, so the existing queries
+ * run unchanged against `getDb()`.
+ *
+ * Merging per table:
+ * - keyed tables keep one row per key; SUM columns are added across shards,
+ *   every other column comes from the shard row with the latest `latest`
+ *   timestamp (SQLite's bare-column rule for a single MAX aggregate)
+ * - anything else (token_signals, positions, ...) is a plain UNION ALL
+ *
+ * Only columns present in every shard that has the table are exposed. Views
+ * are built once at startup: tables created later need a dashboard restart.
+ * SQLite allows at most 10 attached databases.
+ */
+
+interface MergeRule {
+  key: string[];
+  latest: string;
+  sum?: RegExp;
+}
+
+const MERGE_RULES: Record<string, MergeRule> = {
+  token_aggregates: {
+    key: ['mint'],
+    latest: 'updated_at',
+    sum: /^(net_flow_\d+s_sol|buy_count_|sell_count_|volume_|fees_paid_|priority_fees_|jito_tips_|bot_trades_|dca_buys_)/,
+  },
+  token_metadata: { key: ['mint'], latest: 'updated_at' },
+  token_signal_summary: { key: ['token_address'], latest: 'updated_at' },
+  mint_blocklist: { key: ['mint'], latest: 'created_at' },
+  dca_activity_buckets: {
+    key: ['mint', 'bucket_timestamp'],
+    latest: 'last_slot',
+    sum: /^buy_count$/,
+  },
+  dca_activity_rollups: {
+    key: ['mint', 'bucket_timestamp'],
+    latest: 'last_slot',
+    sum: /^buy_count$/,
+  },
+};
+
+function quote(identifier: string): string {
+  return `"${identifier.replace(/"/g, '""')}"`;
+}
+
+function shardTables(db: Database.Database, alias: string): string[] {
+  const rows = db.prepare(`
+    SELECT name FROM ${alias}.sqlite_master
+    WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+  `).all() as Array<{ name: string }>;
+  return rows.map(row => row.name);
+}
+
+function shardColumns(db: Database.Database, alias: string, table: string): string[] {
+  const rows = db.prepare(`PRAGMA ${alias}.table_info(${quote(table)})`).all() as Array<{ name: string }>;
+  return rows.map(row => row.name);
+}
+
+export function buildMergeView(table: string, aliases: string[], columns: string[]): string {
+  const columnList = columns.map(quote).join(', ');
+  const union = aliases
+    .map(alias => `SELECT ${columnList} FROM ${alias}.${quote(table)}`)
+    .join(' UNION ALL ');
+
+  const rule = MERGE_RULES[table];
+  if (!rule || !rule.key.every(k => columns.includes(k)) || !columns.includes(rule.latest)) {
+    return `CREATE TEMP VIEW ${quote(table)} AS ${union}`;
+  }
+
+  const select = columns.map(column => {
+    if (column === rule.latest) {
+      return `MAX(${quote(column)}) AS ${quote(column)}`;
+    }
+    if (rule.sum?.test(column) && !rule.key.includes(column)) {
+      return `SUM(${quote(column)}) AS ${quote(column)}`;
+    }
+    return quote(column);
+  });
+
+  return `CREATE TEMP VIEW ${quote(table)} AS
+    SELECT ${select.join(', ')}
+    FROM (${union})
+    GROUP BY ${rule.key.map(quote).join(', ')}`;
+}
+
+/**
+ * Open an in-memory connection that merges the given databases (see above)
+ */
+export function openFederatedDb(paths: string[]): Database.Database {
+  const db = new Database(':memory:');
+
+  const aliases = paths.map((dbPath, i) => {
+    // ATTACH would silently create a missing file
+    if (!fs.existsSync(dbPath)) {
+      throw new Error(`Federated database not found: ${dbPath}`);
+    }
+    const alias = `shard${i}`;
+    db.prepare(`ATTACH DATABASE ? AS ${alias}`).run(dbPath);
+    return alias;
+  });
+
+  // table -> aliases of the shards that have it
+  const tables = new Map<string, string[]>();
+  aliases.forEach(alias => {
+    shardTables(db, alias).forEach(table => {
+      tables.set(table, [...(tables.get(table) ?? []), alias]);
+    });
+  });
+
+  tables.forEach((tableAliases, table) => {
+    const columnSets = tableAliases.map(alias => shardColumns(db, alias, table));
+    const columns = columnSets[0].filter(column => columnSets.every(set => set.includes(column)));
+    if (columns.length > 0) {
+      db.exec(buildMergeView(table, tableAliases, columns));
+    }
+  });
+
+  db.pragma('query_only = ON');
+
+  console.log(`Federated dashboard reads across ${paths.length} databases (${tables.size} tables)`);
+  return db;
+}
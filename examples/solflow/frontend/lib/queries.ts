@@ -1,12 +1,15 @@
 import { getDb, getWriteDb } from './db';
-import { TokenMetrics, SparklineDataPoint, DcaSparklineDataPoint, TokenMetadata } from './types';
+import { TokenMetrics, SparklineDataPoint, DcaSparklineDataPoint, TokenMetadata, Position, TokenSignal } from './types';
 
 function tableExists(db: ReturnType<typeof getDb>, tableName: string): boolean {
   try {
     const result = db.prepare(`
       SELECT name FROM sqlite_master 
-      WHERE type='table' AND name=?
-    `).get(tableName);
+      WHERE type IN ('table', 'view') AND name=?
+      UNION ALL
+      SELECT name FROM sqlite_temp_master
+      WHERE type = 'view' AND name=?
+    `).get(tableName, tableName);
     return result !== undefined;
   } catch {
     return false;
@@ -91,41 +94,30 @@ export function getTokens(limit: number = 100): TokenMetrics[] {
 export function getSparklineData(mint: string, limit: number = 30): SparklineDataPoint[] {
   const db = getDb();
   
-  // Try to get historical net flow from token_signals details_json
-  // This extracts net_flow_sol from signal details if available
-  // Note: token_aggregates only stores current state (PRIMARY KEY on mint),
-  // so we can't get historical time series from it
+  // The aggregator stores the last hour of per-minute net flow (from its
+  // minute buckets) as [[minute_start, net_flow_sol], ...]
   const query = `
-    SELECT 
-        created_at as timestamp,
-        CAST(json_extract(details_json, '$.net_flow_sol') AS REAL) as net_flow_sol,
-        CAST(json_extract(details_json, '$.net_flow_300s') AS REAL) as net_flow_300s
-    FROM token_signals
+    SELECT net_flow_sparkline_json
+    FROM token_aggregates
     WHERE mint = ?
-        AND created_at > unixepoch() - 3600
-        AND (
-            json_extract(details_json, '$.net_flow_sol') IS NOT NULL
-            OR json_extract(details_json, '$.net_flow_300s') IS NOT NULL
-        )
-    ORDER BY created_at DESC
-    LIMIT ?
   `;
   
   const stmt = db.prepare(query);
-  const rows = stmt.all(mint, limit) as Array<{
-    timestamp: number;
-    net_flow_sol: number | null;
-    net_flow_300s: number | null;
-  }>;
+  const row = stmt.get(mint) as { net_flow_sparkline_json: string | null } | undefined;
+  if (!row?.net_flow_sparkline_json) {
+    return [];
+  }
   
-  // Reverse to get chronological order and use net_flow_sol or net_flow_300s as fallback
-  return rows
-    .reverse()
-    .map(row => ({
-      timestamp: row.timestamp,
-      netFlowSol: row.net_flow_sol ?? row.net_flow_300s ?? 0,
-    }))
-    .filter(point => point.netFlowSol !== 0 || point.timestamp > 0);
+  let points: Array<[number, number]>;
+  try {
+    points = JSON.parse(row.net_flow_sparkline_json);
+  } catch {
+    return [];
+  }
+  
+  return points
+    .slice(-limit)
+    .map(([timestamp, netFlowSol]) => ({ timestamp, netFlowSol }));
 }
 
 export function blockToken(mint: string, reason: string = 'Blocked via web UI'): void {
@@ -215,11 +207,11 @@ export function getDcaSparklineData(mint: string): DcaSparklineDataPoint[] {
   }
 }
 
-export function getLatestSignal(mint: string): { signalType: string; createdAt: number } | null {
+export function getLatestSignal(mint: string): TokenSignal | null {
   const db = getDb();
   
   const query = `
-    SELECT signal_type, created_at
+    SELECT signal_type, created_at, source
     FROM token_signals
     WHERE mint = ?
     ORDER BY created_at DESC
@@ -227,7 +219,7 @@ export function getLatestSignal(mint: string): { signalType: string; createdAt:
   `;
   
   const stmt = db.prepare(query);
-  const row = stmt.get(mint) as { signal_type: string; created_at: number } | undefined;
+  const row = stmt.get(mint) as { signal_type: string; created_at: number; source: string } | undefined;
   
   if (!row) {
     return null;
@@ -236,6 +228,7 @@ export function getLatestSignal(mint: string): { signalType: string; createdAt:
   return {
     signalType: row.signal_type,
     createdAt: row.created_at,
+    source: row.source,
   };
 }
 
@@ -436,3 +429,72 @@ export function getBlockedCount(): number {
 
 
 
+
+export function getOpenPositions(): Position[] {
+  const db = getDb();
+  if (!tableExists(db, 'positions')) {
+    return [];
+  }
+
+  const rows = db.prepare(`
+    SELECT
+      id, mint, size_tokens, entry_price_sol, note, opened_at,
+      current_price_sol, unrealized_pnl_sol, pnl_pct, exit_signal, updated_at
+    FROM positions
+    WHERE closed_at IS NULL
+    ORDER BY opened_at DESC
+  `).all() as Array<{
+    id: number;
+    mint: string;
+    size_tokens: number;
+    entry_price_sol: number;
+    note: string | null;
+    opened_at: number;
+    current_price_sol: number | null;
+    unrealized_pnl_sol: number | null;
+    pnl_pct: number | null;
+    exit_signal: string | null;
+    updated_at: number | null;
+  }>;
+
+  return rows.map(row => ({
+    id: row.id,
+    mint: row.mint,
+    sizeTokens: row.size_tokens,
+    entryPriceSol: row.entry_price_sol,
+    note: row.note,
+    openedAt: row.opened_at,
+    currentPriceSol: row.current_price_sol,
+    unrealizedPnlSol: row.unrealized_pnl_sol,
+    pnlPct: row.pnl_pct,
+    exitSignal: row.exit_signal,
+    updatedAt: row.updated_at,
+  }));
+}
+
+export function openPosition(mint: string, sizeTokens: number, entryPriceSol: number, note: string | null): void {
+  const writeDb = getWriteDb();
+
+  try {
+    const now = Math.floor(Date.now() / 1000);
+    writeDb.prepare(`
+      INSERT INTO positions (mint, size_tokens, entry_price_sol, note, opened_at)
+      VALUES (?, ?, ?, ?, ?)
+    `).run(mint, sizeTokens, entryPriceSol, note, now);
+  } finally {
+    writeDb.close();
+  }
+}
+
+export function closePosition(id: number): void {
+  const writeDb = getWriteDb();
+
+  try {
+    const now = Math.floor(Date.now() / 1000);
+    writeDb.prepare(`
+      UPDATE positions SET closed_at = ? WHERE id = ? AND closed_at IS NULL
+    `).run(now, id);
+  } finally {
+    writeDb.close();
+  }
+}
@@ -40,6 +40,7 @@ export interface BlockResponse {
 export interface TokenSignal {
   signalType: string;
   createdAt: number;
+  source: string; // 'onchain' or the external submitter's tag
 }
 
 export interface DcaSparklineDataPoint {
@@ -79,3 +80,17 @@ export interface TokenSignalSummary {
   updatedAt: number;             // Unix timestamp
 }
 
+
+export interface Position {
+  id: number;
+  mint: string;
+  sizeTokens: number;
+  entryPriceSol: number;         // SOL per token at entry
+  note: string | null;
+  openedAt: number;              // Unix timestamp
+  currentPriceSol: number | null; // Latest trade-derived price (runtime, POSITIONS_ENABLED)
+  unrealizedPnlSol: number | null;
+  pnlPct: number | null;
+  exitSignal: string | null;     // STOP_LOSS | SELL_PRESSURE | TAKE_PROFIT
+  updatedAt: number | null;
+}
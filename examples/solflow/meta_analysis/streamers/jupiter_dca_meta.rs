@@ -11,9 +11,11 @@ use carbon_yellowstone_grpc_datasource::YellowstoneGrpcGeyserClient;
 use chrono::Utc;
 use dotenv;
 use solflow::meta_analysis::{CaptureMetadata, MetadataCaptureProcessor};
+use solflow::streamer_core::filter_builder::TransactionFilterBuilder;
+use solflow::streamer_core::secrets::secret_from_env;
 use std::{collections::HashMap, env, path::PathBuf, sync::Arc};
 use tokio::sync::RwLock;
-use yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeRequestFilterTransactions};
+use yellowstone_grpc_proto::geyser::CommitmentLevel;
 
 type EmptyDecoderCollection = solflow::empty_decoder::EmptyDecoderCollection;
 
@@ -65,20 +67,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let processor_for_meta = processor.clone();
 
     let geyser_url = env::var("GEYSER_URL").expect("GEYSER_URL must be set");
-    let x_token = env::var("X_TOKEN").ok();
-
-    let mut transaction_filters: HashMap<String, SubscribeRequestFilterTransactions> = HashMap::new();
-    transaction_filters.insert(
-        "jupiter_dca_filter".to_string(),
-        SubscribeRequestFilterTransactions {
-            vote: Some(false),
-            failed: Some(false),
-            account_include: vec![],
-            account_exclude: vec![],
-            account_required: vec![program_id],
-            signature: None,
-        },
-    );
+    let x_token = secret_from_env("X_TOKEN").unwrap_or_else(|e| panic!("{}", e));
+
+    let transaction_filters = TransactionFilterBuilder::new()
+        .program("jupiter_dca", program_id)
+        .build()?;
 
     let client = YellowstoneGrpcGeyserClient::new(
         geyser_url,
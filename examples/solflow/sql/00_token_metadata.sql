@@ -5,8 +5,16 @@ CREATE TABLE IF NOT EXISTS token_metadata (
     decimals            INTEGER NOT NULL,
     launch_platform     TEXT,
     pair_created_at     INTEGER,
+    mint_created_at     INTEGER,
     created_at          INTEGER NOT NULL,
     updated_at          INTEGER NOT NULL,
+    image_url           TEXT,
+    price_usd           REAL,
+    market_cap          REAL,
+    follow_price        INTEGER NOT NULL DEFAULT 0,
+    blocked             INTEGER NOT NULL DEFAULT 0,
+    risk_flags          INTEGER,    -- rug-pull risk bitfield: 1 mint authority, 2 freeze authority, 4 unlocked LP
+    risk_checked_at     INTEGER,    -- last risk check (RISK_CHECK_ENABLED), NULL if never checked
     CHECK (decimals >= 0 AND decimals <= 18)
 );
 
@@ -25,6 +25,26 @@ CREATE TABLE IF NOT EXISTS token_aggregates (
     sell_count_900s         INTEGER,
 
     unique_wallets_300s     INTEGER,
+    unique_clusters_300s    INTEGER, -- unique_wallets_300s with funding-linked wallets counted once
+    new_wallets_300s        INTEGER, -- wallets whose first trade on the mint is in the 300s window
+    fees_paid_300s_sol      REAL,    -- transaction fees paid by trades in the 300s window
+    venue_fees_300s_sol     REAL,    -- venue swap fees (FEE_MODELS) paid by trades in the 300s window
+    fee_adjusted_net_flow_300s_sol REAL, -- net_flow_300s_sol minus venue and transaction fees
+    liquidity_sol           REAL,    -- SOL in the mint's known pool vaults (LIQUIDITY_TRACKING_ENABLED), NULL until seen
+    pool_price_sol          REAL,    -- SOL per token read from the mint's pool state account (POOL_STATE_ENABLED), NULL until seen
+    priority_fees_300s_sol  REAL,    -- priority fees (compute unit price x limit) paid by trades in the 300s window
+    jito_tips_300s_sol      REAL,    -- Jito tips paid by trades in the 300s window
+    program_breakdown_300s_json TEXT, -- per-venue net flow/counts: {"PumpSwap": {"net_flow_sol", "buy_count", "sell_count"}}
+    early_buyers_count      INTEGER, -- first buyers tracked for the mint (up to 20)
+    early_holder_retention  REAL,    -- share of those first buyers still holding (0-1)
+    top10_holder_pct        REAL,    -- share of supply held by the 10 largest wallets (0-100), written by the holder enrichment stage
+    fresh_wallet_ratio_60s  REAL,    -- share of buyers whose wallet is younger than FRESH_WALLET_MAX_AGE_SECS (0-1)
+    fresh_wallet_ratio_300s REAL,
+    fresh_wallet_ratio_900s REAL,
+    unique_wallets_3600s    INTEGER,    -- HLL estimate from minute buckets: exact up to 64 wallets, else ~3.3% std error
+    unique_wallets_7200s    INTEGER,
+    unique_wallets_14400s   INTEGER,
+    net_flow_sparkline_json TEXT,    -- net flow per minute, last 60 minutes: [[minute_start, net_flow_sol], ...] oldest first
     bot_trades_300s         INTEGER,
     bot_wallets_300s        INTEGER,
 
@@ -38,6 +58,28 @@ CREATE TABLE IF NOT EXISTS token_aggregates (
     dca_buys_3600s          INTEGER NOT NULL DEFAULT 0,
     dca_buys_14400s         INTEGER NOT NULL DEFAULT 0,
 
+    -- Final view: finalized trades only (the columns above are the fast view
+    -- and include trades still at Processed/Confirmed commitment)
+    net_flow_60s_final_sol  REAL,
+    net_flow_300s_final_sol REAL,
+    net_flow_900s_final_sol REAL,
+    buy_count_300s_final    INTEGER,
+    sell_count_300s_final   INTEGER,
+    unfinalized_trades_300s INTEGER, -- trades in the 300s window not yet finalized
+
+    -- PIPELINE_WINDOWS metrics without a column above, keyed by generated
+    -- column name: {"net_flow_30s_sol": 1.2, "buy_count_30s": 4, ...}
+    window_metrics_json     TEXT,
+
+    -- Most recent trade (for tracing an aggregate back to a transaction)
+    last_trade_slot         INTEGER,
+    last_trade_signature    TEXT,
+
+    -- Data quality: end time of the latest gap in the live stream the
+    -- windows reach back over (NULL = complete); windows longer than
+    -- updated_at - last_data_gap_at are degraded
+    last_data_gap_at        INTEGER,
+
     updated_at              INTEGER NOT NULL,
     created_at              INTEGER NOT NULL
 );
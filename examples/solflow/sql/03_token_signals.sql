@@ -6,8 +6,10 @@ CREATE TABLE IF NOT EXISTS token_signals (
     window_seconds  INTEGER NOT NULL,
     severity        INTEGER NOT NULL DEFAULT 1,
     score           REAL,
-    details_json    TEXT,
+    details_json    TEXT,                      -- versioned JSON, see SignalDetails (pipeline/signals.rs)
     created_at      INTEGER NOT NULL,
+    source          TEXT NOT NULL DEFAULT 'onchain',  -- 'onchain' or the webhook submitter's tag
+    sample_signatures TEXT,                    -- comma-separated contributing signatures (severity 5 only)
 
     sent_to_discord INTEGER NOT NULL DEFAULT 0,
     seen_in_terminal INTEGER NOT NULL DEFAULT 0
@@ -18,3 +20,7 @@ CREATE INDEX IF NOT EXISTS idx_token_signals_mint_created
 
 CREATE INDEX IF NOT EXISTS idx_token_signals_type_created
     ON token_signals (signal_type, created_at DESC);
+
+-- Detail compaction scans by age (SIGNAL_DETAILS_RETENTION_SECS)
+CREATE INDEX IF NOT EXISTS idx_token_signals_created
+    ON token_signals (created_at);
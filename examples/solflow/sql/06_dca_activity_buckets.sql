@@ -2,15 +2,19 @@
 --
 -- Purpose: Store 1-minute bucketed DCA buy counts for historical sparkline rendering
 --
+-- Granularity: DCA_BUCKET_SECS (default 60s)
 -- Retention: 1 hour of data (60 buckets × 60 seconds)
--- Cleanup: Automatic pruning of buckets older than 2 hours
+-- Cleanup: Automatic pruning of buckets older than DCA_BUCKET_RETENTION_SECS (default 2 hours)
+-- Longer history: 5-minute rollups in dca_activity_rollups (13_dca_activity_rollups.sql)
 --
 -- Phase 7: DCA Sparkline Foundation (feature/dca-sparkline-backend)
 
 CREATE TABLE IF NOT EXISTS dca_activity_buckets (
     mint TEXT NOT NULL,
-    bucket_timestamp INTEGER NOT NULL,  -- Unix timestamp floored to 60s boundary
+    bucket_timestamp INTEGER NOT NULL,  -- Unix timestamp floored to DCA_BUCKET_SECS boundary
     buy_count INTEGER NOT NULL DEFAULT 0,
+    last_slot INTEGER,                  -- Slot of the most recent DCA buy
+    last_signature TEXT,                -- Signature of the most recent DCA buy
     PRIMARY KEY (mint, bucket_timestamp)
 );
 
@@ -0,0 +1,15 @@
+-- Instance Leases: Coordination between runtime instances sharing a database
+--
+-- Purpose: Only the instance holding a lease runs the flush loop, price
+-- enrichment and persistence scoring. Other instances keep ingesting trades
+-- (warm standby) and take over once the holder's lease expires.
+--
+-- The holder renews well before expires_at; a crashed holder is replaced
+-- after at most one TTL.
+
+CREATE TABLE IF NOT EXISTS instance_leases (
+    name TEXT PRIMARY KEY,              -- Lease name (e.g. 'pipeline_runtime')
+    holder TEXT NOT NULL,               -- Instance ID of the current holder
+    acquired_at INTEGER NOT NULL,       -- Unix timestamp the holder first acquired it
+    expires_at INTEGER NOT NULL         -- Unix timestamp after which others may take over
+);
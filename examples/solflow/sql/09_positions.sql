@@ -0,0 +1,29 @@
+-- Positions: User-recorded token holdings with live PnL
+--
+-- Purpose: The user records entries (dashboard Positions panel); the runtime
+-- marks open positions against the latest trade price, stores unrealized PnL
+-- and raises POSITION_EXIT signals when an exit rule trips.
+--
+-- Prices are SOL per token (UI units). A position is open while closed_at
+-- is NULL.
+
+CREATE TABLE IF NOT EXISTS positions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    mint TEXT NOT NULL,
+    size_tokens REAL NOT NULL,          -- Tokens held (UI units)
+    entry_price_sol REAL NOT NULL,      -- SOL per token at entry
+    note TEXT,
+    opened_at INTEGER NOT NULL,         -- Unix timestamp
+    closed_at INTEGER,                  -- Unix timestamp; NULL while open
+
+    -- Written by the runtime (POSITIONS_ENABLED=true)
+    current_price_sol REAL,             -- Latest trade-derived price
+    unrealized_pnl_sol REAL,
+    pnl_pct REAL,
+    exit_signal TEXT,                   -- Active exit rule: STOP_LOSS | SELL_PRESSURE | TAKE_PROFIT
+    exit_signal_at INTEGER,             -- When the last exit rule tripped
+    updated_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_positions_open
+    ON positions (closed_at, mint);
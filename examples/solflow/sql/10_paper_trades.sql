@@ -0,0 +1,29 @@
+-- Paper Trades: Simulated fills from signal-driven strategies
+--
+-- Purpose: The paper-trading executor (PAPER_STRATEGIES) "buys" when a
+-- strategy's entry signal fires and "sells" on its exit signals or price
+-- rules, recording fills at trade-derived prices (SOL per token, slippage
+-- applied) so strategies can be evaluated end-to-end.
+--
+-- A trade is open while closed_at is NULL.
+
+CREATE TABLE IF NOT EXISTS paper_trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    strategy TEXT NOT NULL,             -- Strategy name from PAPER_STRATEGIES
+    mint TEXT NOT NULL,
+    entry_signal_id INTEGER,            -- token_signals.id that triggered the entry
+    entry_signal_type TEXT NOT NULL,
+    size_sol REAL NOT NULL,             -- SOL spent at entry
+    tokens REAL NOT NULL,               -- Tokens received (UI units)
+    entry_price_sol REAL NOT NULL,      -- Fill price incl. slippage
+    opened_at INTEGER NOT NULL,
+
+    exit_price_sol REAL,                -- Fill price incl. slippage
+    exit_reason TEXT,                   -- SIGNAL:<type> | TAKE_PROFIT | STOP_LOSS | MAX_HOLD
+    closed_at INTEGER,
+    pnl_sol REAL,
+    pnl_pct REAL
+);
+
+CREATE INDEX IF NOT EXISTS idx_paper_trades_open
+    ON paper_trades (closed_at, strategy, mint);
@@ -0,0 +1,26 @@
+-- Session Rollups: Per-token and market-wide totals by regional trading session
+--
+-- Purpose: Show flow that concentrates in one region's trading hours, which
+-- uniform rolling windows can't. Sessions hand off at local exchange opens
+-- (DST-aware): ASIA 09:00 Asia/Tokyo, EU 08:00 Europe/London,
+-- US 09:30 America/New_York. Each runs until the next one opens.
+--
+-- One row per (mint, session_date, session), upserted while the session is
+-- open. mint = '*' holds market-wide totals.
+
+CREATE TABLE IF NOT EXISTS session_rollups (
+    mint TEXT NOT NULL,                 -- Token mint, or '*' for market-wide
+    session_date TEXT NOT NULL,         -- Local date the session opened (YYYY-MM-DD)
+    session TEXT NOT NULL,              -- ASIA | EU | US
+    buy_sol REAL NOT NULL DEFAULT 0,
+    sell_sol REAL NOT NULL DEFAULT 0,
+    net_flow_sol REAL NOT NULL DEFAULT 0,
+    buy_count INTEGER NOT NULL DEFAULT 0,
+    sell_count INTEGER NOT NULL DEFAULT 0,
+    unique_wallets INTEGER NOT NULL DEFAULT 0,  -- HyperLogLog estimate (exact up to 64)
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (mint, session_date, session)
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_rollups_date
+    ON session_rollups (session_date, session);
@@ -0,0 +1,33 @@
+-- Audit Trades: Short-retention raw trades for watched mints
+--
+-- Purpose: The pipeline is aggregate-only; this opt-in table
+-- (AUDIT_TRADES_ENABLED) keeps the source trades of a few watched mints for
+-- the last AUDIT_TRADES_RETENTION_MINS minutes so a suspicious aggregate can
+-- be recomputed by hand, e.g. net_flow_300s_sol:
+--
+--   SELECT SUM(CASE direction WHEN 'BUY' THEN sol_amount
+--                             WHEN 'SELL' THEN -sol_amount ELSE 0 END)
+--   FROM audit_trades
+--   WHERE mint = ? AND timestamp > (SELECT updated_at - 300 FROM token_aggregates WHERE mint = ?)
+--
+-- Rows older than the retention (by block time) are deleted on every flush.
+
+CREATE TABLE IF NOT EXISTS audit_trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    mint TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,         -- Block time (unix seconds)
+    direction TEXT NOT NULL,            -- BUY | SELL | UNKNOWN
+    sol_amount REAL NOT NULL,
+    token_amount REAL NOT NULL,
+    user_account TEXT NOT NULL,
+    source_program TEXT NOT NULL,
+    fee_lamports INTEGER NOT NULL,
+    venue_fee_sol REAL,                 -- Venue swap fee from FEE_MODELS
+    net_sol REAL,                       -- Net cost (BUY) or net proceeds (SELL) after all fees
+    recorded_at INTEGER NOT NULL        -- When the pipeline processed the trade
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_trades_mint_time
+    ON audit_trades (mint, timestamp);
@@ -0,0 +1,22 @@
+-- DCA Activity Rollups: 5-minute DCA buckets for long sparkline history
+--
+-- Purpose: dca_activity_buckets keeps fine buckets (DCA_BUCKET_SECS, default
+-- 60s) for DCA_BUCKET_RETENTION_SECS (default 2 hours). This table keeps one
+-- row per mint per 5 minutes for DCA_ROLLUP_RETENTION_SECS (default 24 hours),
+-- so sparklines can reach further back at a fifth of the rows.
+--
+-- buy_count is the highest 1-hour DCA buy count seen in the 5-minute bucket.
+-- Written together with dca_activity_buckets (same DB route), pruned by the
+-- same cleanup task.
+
+CREATE TABLE IF NOT EXISTS dca_activity_rollups (
+    mint TEXT NOT NULL,
+    bucket_timestamp INTEGER NOT NULL,  -- Unix timestamp floored to 300s boundary
+    buy_count INTEGER NOT NULL DEFAULT 0,
+    last_slot INTEGER,                  -- Slot of the most recent DCA buy
+    last_signature TEXT,                -- Signature of the most recent DCA buy
+    PRIMARY KEY (mint, bucket_timestamp)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dca_rollups_timestamp
+    ON dca_activity_rollups (bucket_timestamp);
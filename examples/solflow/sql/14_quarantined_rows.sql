@@ -0,0 +1,23 @@
+-- Quarantined Rows: Corrupt rows moved aside by the startup integrity audit
+--
+-- Purpose: A flush interrupted by a crash can leave rows with negative
+-- counts, timestamps in the future or a NULL mint, which then rank at the top
+-- (or bottom) of every query. With INTEGRITY_AUDIT=quarantine the runtime
+-- moves such rows here at startup instead of deleting them outright, so they
+-- can be inspected or restored by hand:
+--
+--   SELECT source_table, row_key, reason, row_json
+--   FROM quarantined_rows
+--   ORDER BY quarantined_at DESC;
+
+CREATE TABLE IF NOT EXISTS quarantined_rows (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_table TEXT NOT NULL,         -- Table the row was removed from
+    row_key TEXT NOT NULL,              -- Mint, or rowid:<n> when the mint is NULL
+    reason TEXT NOT NULL,               -- Failed check, e.g. "negative buy_count_300s"
+    row_json TEXT NOT NULL,             -- Full row as a JSON object keyed by column
+    quarantined_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_quarantined_rows_table_time
+    ON quarantined_rows (source_table, quarantined_at);
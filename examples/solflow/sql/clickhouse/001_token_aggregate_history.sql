@@ -0,0 +1,49 @@
+-- Token Aggregate History: Minute snapshots of token_aggregates (ClickHouse)
+--
+-- Purpose: token_aggregates is UPSERT-only, so each flush overwrites the
+-- previous values. With CLICKHOUSE_URL set, the runtime also appends one row
+-- per mint per HISTORY_INTERVAL_SECS (default 60) here, keyed by the start
+-- of the interval. A mint re-flushed within the same interval replaces its
+-- row (ReplacingMergeTree on updated_at, query with FINAL for exact results):
+--
+--   SELECT minute, net_flow_300s_sol, unique_wallets_300s
+--   FROM token_aggregate_history FINAL
+--   WHERE mint = '...' AND minute >= now() - INTERVAL 1 DAY
+--   ORDER BY minute;
+--
+-- Applied by the runtime on startup; not read by run_schema_migrations.
+
+CREATE TABLE IF NOT EXISTS token_aggregate_history (
+    mint                    String,
+    minute                  DateTime('UTC'),        -- Start of the snapshot interval
+    source_program          LowCardinality(String),
+
+    price_usd               Nullable(Float64),
+    market_cap_usd          Nullable(Float64),
+
+    net_flow_60s_sol        Nullable(Float64),
+    net_flow_300s_sol       Nullable(Float64),
+    net_flow_900s_sol       Nullable(Float64),
+    net_flow_3600s_sol      Nullable(Float64),
+    net_flow_7200s_sol      Nullable(Float64),
+    net_flow_14400s_sol     Nullable(Float64),
+
+    buy_count_60s           Nullable(Int32),
+    sell_count_60s          Nullable(Int32),
+    buy_count_300s          Nullable(Int32),
+    sell_count_300s         Nullable(Int32),
+    buy_count_900s          Nullable(Int32),
+    sell_count_900s         Nullable(Int32),
+
+    unique_wallets_300s     Nullable(Int32),
+    new_wallets_300s        Nullable(Int32),
+    unique_wallets_3600s    Nullable(Int32),
+    unique_wallets_7200s    Nullable(Int32),
+    unique_wallets_14400s   Nullable(Int32),
+    volume_300s_sol         Nullable(Float64),
+
+    updated_at              DateTime('UTC')         -- Aggregate flush time
+)
+ENGINE = ReplacingMergeTree(updated_at)
+PARTITION BY toYYYYMM(minute)
+ORDER BY (mint, minute)
@@ -0,0 +1,20 @@
+-- Raw trades written by streamer instances (PostgresWriter, --backend postgres)
+-- Mirrors the SQLite `trades` table created by SqliteWriter.
+CREATE TABLE IF NOT EXISTS trades (
+    id              BIGSERIAL PRIMARY KEY,
+    program         TEXT NOT NULL,
+    program_name    TEXT NOT NULL,
+    mint            TEXT NOT NULL,
+    signature       TEXT UNIQUE NOT NULL,
+    action          TEXT NOT NULL,
+    sol_amount      DOUBLE PRECISION NOT NULL,
+    token_amount    DOUBLE PRECISION NOT NULL,
+    token_decimals  INTEGER NOT NULL,
+    user_account    TEXT,
+    discriminator   TEXT NOT NULL,
+    timestamp       BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_trades_mint_timestamp ON trades(mint, timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_trades_timestamp ON trades(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_trades_program ON trades(program, timestamp DESC);
@@ -61,6 +61,9 @@ impl AggregatorWriterBackend for SqliteAggregatorWriter {
             token_decimals: 0,   // Not applicable for aggregated metrics
             user_account: None,
             discriminator: discriminator_json.to_string(),
+            focus_wallet: None,
+            priority_fee_lamports: 0,
+            jito_tip_lamports: 0,
         };
         
         self.sqlite_writer.write(&event).await
@@ -29,6 +29,12 @@ impl AggregatorWriter {
                 let writer = SqliteAggregatorWriter::new(base_path)?;
                 Ok(AggregatorWriter::Sqlite(writer))
             }
+            BackendType::Postgres => Err(AggregatorWriterError::Database(
+                "Postgres backend is only supported by the streamers".to_string(),
+            )),
+            BackendType::Kafka => Err(AggregatorWriterError::Database(
+                "Kafka backend is only supported by the streamers".to_string(),
+            )),
         }
     }
     
@@ -0,0 +1,7 @@
+//! Network APIs for external consumers of pipeline output
+//!
+//! - `rest` - Read-only HTTP queries over aggregates and signals
+//! - `ws_server` - WebSocket push of live aggregates and signals
+
+pub mod rest;
+pub mod ws_server;
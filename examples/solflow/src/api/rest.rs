@@ -0,0 +1,510 @@
+//! HTTP query API over the pipeline database
+//!
+//! Serves `token_aggregates` and `token_signals` straight from the SQLite
+//! file the pipeline writes (WAL mode, so reads don't block the flush).
+//! Every request opens a short-lived read-only connection on the blocking
+//! pool. Blocklisted mints are left out of listings and return 404.
+//!
+//! The only writes are to the gRPC subscription of a unified streamer
+//! running in this process (see `streamer_core::subscription`); they take
+//! effect on the open stream and are not persisted.
+//!
+//! ```text
+//! GET /health                                 → {"status":"ok","last_update":...,"lag_secs":...}
+//! GET /status                                 → configuration, programs, sinks, DB size (see `pipeline::status`)
+//! GET /tokens/top?window=300&limit=20         → top mints by net_flow_<window>s_sol
+//! GET /tokens/{mint}/aggregates               → the mint's token_aggregates row
+//! GET /tokens/{mint}/signals?since=&limit=    → the mint's signals, newest first
+//! GET /subscription                           → {"programs":[[name,id],...],"accounts":[...]}
+//! POST|DELETE /subscription/programs/{id}     → add/remove a program filter: {"changed":bool,"filters":{...}}
+//! POST|DELETE /subscription/accounts/{pubkey} → add/remove an account (mint, pool, wallet)
+//! ```
+//!
+//! Environment variables:
+//! - `API_ADDR`: Listen address, e.g. `127.0.0.1:8790` (unset = disabled)
+//! - `API_TOKEN`: Required bearer token (unset = no auth, localhost use only);
+//!   accepts `file:`/`keyring:` references or `API_TOKEN_FILE`
+
+use crate::pipeline::status::{db_size_bytes, RuntimeStatus};
+use crate::streamer_core::filter_builder::FilterError;
+use crate::streamer_core::secrets::secret_from_env;
+use crate::streamer_core::subscription::{self, SubscriptionControl};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use log::{info, warn};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Row};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Windows `/tokens/top` can rank by (net_flow_<window>s_sol)
+const TOP_WINDOWS: &[u32] = &[60, 300, 900, 3600, 7200, 14400];
+
+/// Largest `limit` accepted by list endpoints
+const MAX_LIMIT: u32 = 500;
+
+type ApiResponse = (StatusCode, Json<Value>);
+
+fn error(status: StatusCode, message: impl Into<String>) -> ApiResponse {
+    (status, Json(json!({"error": message.into()})))
+}
+
+/// Row as a JSON object keyed by column name
+fn row_to_json(row: &Row) -> rusqlite::Result<Value> {
+    let mut object = Map::new();
+    for (index, name) in row.as_ref().column_names().into_iter().enumerate() {
+        let value = match row.get_ref(index)? {
+            ValueRef::Null => Value::Null,
+            ValueRef::Integer(i) => i.into(),
+            ValueRef::Real(f) => serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number),
+            ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned().into(),
+            ValueRef::Blob(_) => Value::Null,
+        };
+        object.insert(name.to_string(), value);
+    }
+    Ok(Value::Object(object))
+}
+
+/// Blocklist condition for a query aliasing token_aggregates/signals as `t`
+const NOT_BLOCKED: &str = "NOT EXISTS (
+    SELECT 1 FROM mint_blocklist b
+    WHERE b.mint = t.mint AND (b.expires_at IS NULL OR b.expires_at > ?1)
+)";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TopQuery {
+    pub window: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SignalsQuery {
+    pub since: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+/// Queries behind the endpoints (separate from axum for testing)
+pub struct ApiQueries {
+    conn: Connection,
+}
+
+impl ApiQueries {
+    pub fn open(db_path: &str) -> rusqlite::Result<Self> {
+        let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        Ok(Self { conn })
+    }
+
+    /// Time of the newest aggregate write (the last flush that wrote rows)
+    pub fn last_update(&self) -> rusqlite::Result<Option<i64>> {
+        self.conn
+            .query_row("SELECT MAX(updated_at) FROM token_aggregates", [], |row| row.get(0))
+    }
+
+    /// Newest aggregate update and how far behind `now` it is
+    pub fn health(&self, now: i64) -> rusqlite::Result<Value> {
+        let last_update = self.last_update()?;
+        Ok(json!({
+            "status": "ok",
+            "last_update": last_update,
+            "lag_secs": last_update.map(|ts| now - ts),
+        }))
+    }
+
+    /// Top mints by net flow over `window` seconds, with metadata when known
+    pub fn top_tokens(&self, window: u32, limit: u32, now: i64) -> Result<Value, String> {
+        if !TOP_WINDOWS.contains(&window) {
+            return Err(format!("window must be one of {:?}", TOP_WINDOWS));
+        }
+        let sql = format!(
+            "SELECT t.mint, m.symbol, m.name, t.net_flow_{window}s_sol AS net_flow_sol,
+                    t.net_flow_60s_sol, t.net_flow_300s_sol, t.net_flow_900s_sol,
+                    t.buy_count_300s, t.sell_count_300s, t.unique_wallets_300s,
+                    t.volume_300s_sol, t.price_usd, t.market_cap_usd, t.updated_at
+             FROM token_aggregates t
+             LEFT JOIN token_metadata m ON m.mint = t.mint
+             WHERE t.net_flow_{window}s_sol IS NOT NULL
+               AND COALESCE(m.blocked, 0) = 0
+               AND {NOT_BLOCKED}
+             ORDER BY t.net_flow_{window}s_sol DESC
+             LIMIT ?2"
+        );
+        let mut stmt = self.conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(rusqlite::params![now, limit], row_to_json)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| e.to_string())?;
+        Ok(json!({"window": window, "tokens": rows}))
+    }
+
+    /// The mint's token_aggregates row (None when missing or blocked)
+    pub fn aggregates(&self, mint: &str, now: i64) -> rusqlite::Result<Option<Value>> {
+        let sql = format!("SELECT t.* FROM token_aggregates t WHERE t.mint = ?2 AND {NOT_BLOCKED}");
+        self.conn
+            .query_row(&sql, rusqlite::params![now, mint], row_to_json)
+            .optional()
+    }
+
+    /// The mint's signals created at or after `since`, newest first
+    pub fn signals(
+        &self,
+        mint: &str,
+        since: i64,
+        limit: u32,
+        now: i64,
+    ) -> rusqlite::Result<Option<Vec<Value>>> {
+        if self.is_blocked(mint, now)? {
+            return Ok(None);
+        }
+        let mut stmt = self.conn.prepare(
+            "SELECT id, mint, signal_type, window_seconds, severity, score, details_json,
+                    created_at, source
+             FROM token_signals
+             WHERE mint = ?1 AND created_at >= ?2
+             ORDER BY created_at DESC, id DESC
+             LIMIT ?3",
+        )?;
+        let rows = stmt
+            .query_map(rusqlite::params![mint, since, limit], row_to_json)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(rows))
+    }
+
+    fn is_blocked(&self, mint: &str, now: i64) -> rusqlite::Result<bool> {
+        self.conn
+            .prepare(
+                "SELECT 1 FROM mint_blocklist
+                 WHERE mint = ?1 AND (expires_at IS NULL OR expires_at > ?2)",
+            )?
+            .exists(rusqlite::params![mint, now])
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    db_path: String,
+    token: Option<String>,
+    status: Option<Arc<RuntimeStatus>>,
+}
+
+fn is_authorized(headers: &HeaderMap, token: Option<&str>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| given == token)
+}
+
+/// Run `query` on a fresh read-only connection on the blocking pool
+async fn with_queries<F>(state: &ApiState, headers: &HeaderMap, query: F) -> ApiResponse
+where
+    F: FnOnce(&ApiQueries, i64) -> Result<ApiResponse, String> + Send + 'static,
+{
+    if !is_authorized(headers, state.token.as_deref()) {
+        return error(StatusCode::UNAUTHORIZED, "unauthorized");
+    }
+    let db_path = state.db_path.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let queries = ApiQueries::open(&db_path).map_err(|e| e.to_string())?;
+        query(&queries, chrono::Utc::now().timestamp())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => {
+            warn!("⚠️  API query failed: {}", e);
+            error(StatusCode::INTERNAL_SERVER_ERROR, e)
+        }
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn health(State(state): State<ApiState>, headers: HeaderMap) -> ApiResponse {
+    with_queries(&state, &headers, |queries, now| {
+        queries
+            .health(now)
+            .map(|body| (StatusCode::OK, Json(body)))
+            .or_else(|e| Ok(error(StatusCode::SERVICE_UNAVAILABLE, e.to_string())))
+    })
+    .await
+}
+
+async fn status(State(state): State<ApiState>, headers: HeaderMap) -> ApiResponse {
+    let Some(status) = state.status.clone() else {
+        return error(StatusCode::NOT_FOUND, "status not available");
+    };
+    let db_path = state.db_path.clone();
+    with_queries(&state, &headers, move |queries, now| {
+        let last_flush_at = queries.last_update().map_err(|e| e.to_string())?;
+        let body = status.to_json(&db_path, db_size_bytes(&db_path), last_flush_at, now);
+        Ok((StatusCode::OK, Json(body)))
+    })
+    .await
+}
+
+async fn top_tokens(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Query(query): Query<TopQuery>,
+) -> ApiResponse {
+    let window = query.window.unwrap_or(300);
+    let limit = query.limit.unwrap_or(20).min(MAX_LIMIT);
+    if is_authorized(&headers, state.token.as_deref()) && !TOP_WINDOWS.contains(&window) {
+        return error(
+            StatusCode::BAD_REQUEST,
+            format!("window must be one of {:?}", TOP_WINDOWS),
+        );
+    }
+    with_queries(&state, &headers, move |queries, now| {
+        queries
+            .top_tokens(window, limit, now)
+            .map(|body| (StatusCode::OK, Json(body)))
+    })
+    .await
+}
+
+async fn token_aggregates(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(mint): Path<String>,
+) -> ApiResponse {
+    with_queries(&state, &headers, move |queries, now| {
+        Ok(match queries.aggregates(&mint, now).map_err(|e| e.to_string())? {
+            Some(row) => (StatusCode::OK, Json(row)),
+            None => error(StatusCode::NOT_FOUND, format!("no aggregates for {}", mint)),
+        })
+    })
+    .await
+}
+
+async fn token_signals(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(mint): Path<String>,
+    Query(query): Query<SignalsQuery>,
+) -> ApiResponse {
+    let since = query.since.unwrap_or(0);
+    let limit = query.limit.unwrap_or(100).min(MAX_LIMIT);
+    with_queries(&state, &headers, move |queries, now| {
+        Ok(match queries.signals(&mint, since, limit, now).map_err(|e| e.to_string())? {
+            Some(signals) => (StatusCode::OK, Json(json!({"mint": mint, "signals": signals}))),
+            None => error(StatusCode::NOT_FOUND, format!("{} is blocked", mint)),
+        })
+    })
+    .await
+}
+
+/// The subscription of the gRPC streamer running in this process
+fn subscription_control(
+    state: &ApiState,
+    headers: &HeaderMap,
+) -> Result<Arc<SubscriptionControl>, ApiResponse> {
+    if !is_authorized(headers, state.token.as_deref()) {
+        return Err(error(StatusCode::UNAUTHORIZED, "unauthorized"));
+    }
+    subscription::installed()
+        .ok_or_else(|| error(StatusCode::SERVICE_UNAVAILABLE, "no gRPC streamer running"))
+}
+
+/// Apply `change` to the subscription and return the new filters
+fn change_subscription<F>(state: &ApiState, headers: &HeaderMap, change: F) -> ApiResponse
+where
+    F: FnOnce(&SubscriptionControl) -> Result<bool, FilterError>,
+{
+    let control = match subscription_control(state, headers) {
+        Ok(control) => control,
+        Err(response) => return response,
+    };
+    match change(&control) {
+        Ok(changed) => (
+            StatusCode::OK,
+            Json(json!({"changed": changed, "filters": control.filters()})),
+        ),
+        Err(e) => error(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+async fn get_subscription(State(state): State<ApiState>, headers: HeaderMap) -> ApiResponse {
+    match subscription_control(&state, &headers) {
+        Ok(control) => (StatusCode::OK, Json(json!(control.filters()))),
+        Err(response) => response,
+    }
+}
+
+async fn add_program(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(program_id): Path<String>,
+) -> ApiResponse {
+    change_subscription(&state, &headers, |control| control.add_program(&program_id))
+}
+
+async fn remove_program(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(program_id): Path<String>,
+) -> ApiResponse {
+    change_subscription(&state, &headers, |control| control.remove_program(&program_id))
+}
+
+async fn add_account(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(account): Path<String>,
+) -> ApiResponse {
+    change_subscription(&state, &headers, |control| control.add_account(&account))
+}
+
+async fn remove_account(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Path(account): Path<String>,
+) -> ApiResponse {
+    change_subscription(&state, &headers, |control| control.remove_account(&account))
+}
+
+/// API server settings
+#[derive(Debug, Clone)]
+pub struct ApiServer {
+    pub addr: String,
+    token: Option<String>,
+    status: Option<Arc<RuntimeStatus>>,
+}
+
+impl ApiServer {
+    /// Create from the environment (see module docs); None unless an address is set
+    pub fn from_env() -> Option<Self> {
+        let addr = std::env::var("API_ADDR")
+            .ok()
+            .filter(|s| !s.trim().is_empty())?;
+        // Fail closed: an unreadable token reference disables the API
+        let token = match secret_from_env("API_TOKEN") {
+            Ok(token) => token,
+            Err(e) => {
+                warn!("⚠️  Query API disabled: {}", e);
+                return None;
+            }
+        };
+        Some(Self {
+            addr,
+            token,
+            status: None,
+        })
+    }
+
+    /// Serve `status` at `/status`
+    pub fn with_status(mut self, status: Arc<RuntimeStatus>) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Bind and serve until the process exits
+    pub async fn serve(self, db_path: String) -> Result<(), std::io::Error> {
+        if self.token.is_none() {
+            warn!("⚠️  API_TOKEN not set: query API is unauthenticated");
+        }
+
+        let app = Router::new()
+            .route("/health", get(health))
+            .route("/status", get(status))
+            .route("/tokens/top", get(top_tokens))
+            .route("/tokens/{mint}/aggregates", get(token_aggregates))
+            .route("/tokens/{mint}/signals", get(token_signals))
+            .route("/subscription", get(get_subscription))
+            .route("/subscription/programs/{program_id}", post(add_program).delete(remove_program))
+            .route("/subscription/accounts/{account}", post(add_account).delete(remove_account))
+            .with_state(ApiState {
+                db_path,
+                token: self.token,
+                status: self.status,
+            });
+
+        let listener = tokio::net::TcpListener::bind(&self.addr).await?;
+        info!("🌐 Query API listening on {}", self.addr);
+        axum::serve(listener, app).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::db::run_schema_migrations;
+    use tempfile::NamedTempFile;
+
+    fn seeded_db() -> NamedTempFile {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut conn = Connection::open(temp_file.path()).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        for (mint, net_flow) in [("alpha", 12.5), ("beta", 3.0), ("blocked", 50.0)] {
+            conn.execute(
+                "INSERT INTO token_aggregates (mint, source_program, net_flow_300s_sol, updated_at, created_at)
+                 VALUES (?1, 'pumpswap', ?2, 1000, 900)",
+                rusqlite::params![mint, net_flow],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO mint_blocklist (mint, reason, blocked_by, created_at) VALUES ('blocked', 'rug', 'test', 0)",
+            [],
+        )
+        .unwrap();
+        for (signal_type, created_at) in [("BREAKOUT", 950), ("SURGE", 990)] {
+            conn.execute(
+                "INSERT INTO token_signals (mint, signal_type, window_seconds, severity, created_at)
+                 VALUES ('alpha', ?1, 60, 3, ?2)",
+                rusqlite::params![signal_type, created_at],
+            )
+            .unwrap();
+        }
+        temp_file
+    }
+
+    #[test]
+    fn test_top_tokens_excludes_blocked() {
+        let db = seeded_db();
+        let queries = ApiQueries::open(db.path().to_str().unwrap()).unwrap();
+
+        let top = queries.top_tokens(300, 10, 1000).unwrap();
+        let mints: Vec<&str> = top["tokens"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["mint"].as_str().unwrap())
+            .collect();
+        assert_eq!(mints, vec!["alpha", "beta"]);
+        assert_eq!(top["tokens"][0]["net_flow_sol"], 12.5);
+        assert!(queries.top_tokens(42, 10, 1000).is_err());
+
+        assert_eq!(queries.health(1030).unwrap()["lag_secs"], 30);
+    }
+
+    #[test]
+    fn test_mint_aggregates_and_signals() {
+        let db = seeded_db();
+        let queries = ApiQueries::open(db.path().to_str().unwrap()).unwrap();
+
+        let row = queries.aggregates("alpha", 1000).unwrap().unwrap();
+        assert_eq!(row["source_program"], "pumpswap");
+        assert!(queries.aggregates("blocked", 1000).unwrap().is_none());
+        assert!(queries.aggregates("missing", 1000).unwrap().is_none());
+
+        let signals = queries.signals("alpha", 960, 10, 1000).unwrap().unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0]["signal_type"], "SURGE");
+        assert!(queries.signals("blocked", 0, 10, 1000).unwrap().is_none());
+    }
+}
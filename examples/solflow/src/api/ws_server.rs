@@ -0,0 +1,506 @@
+//! WebSocket push server for live aggregates and signals
+//!
+//! Dashboards otherwise poll `token_aggregates`/`token_signals`. With
+//! `LIVE_WS_ADDR` set, the runtime wraps its database writer in a
+//! `LiveFeedWriter`: every aggregate flush and every signal that is actually
+//! written (deduplicated by the engine, blocklist-checked by the writer) is
+//! also published on a `LiveFeed` broadcast channel and pushed as a JSON
+//! frame to connected clients. Nothing is serialized while no client is
+//! connected.
+//!
+//! ```text
+//! GET /ws?mints=<mint>,<mint>&types=signal,aggregate&token=<LIVE_WS_TOKEN>
+//!
+//! → {"type":"aggregate","mint":"...","data":{...token_aggregates columns...}}
+//! → {"type":"signal","mint":"...","data":{"signal_type":"BREAKOUT","severity":4,...}}
+//! → {"type":"lagged","skipped":12}
+//! ← {"op":"subscribe","mints":["..."]}
+//! ← {"op":"unsubscribe","mints":["..."]}
+//! ```
+//!
+//! Without `mints` a client receives every mint until it subscribes to
+//! specific ones; without `types` it receives both frame types. Clients that
+//! fall behind the channel get a `lagged` frame and continue from the newest
+//! frames.
+//!
+//! Environment variables:
+//! - `LIVE_WS_ADDR`: Listen address, e.g. `127.0.0.1:8788` (unset = disabled)
+//! - `LIVE_WS_TOKEN`: Required token, as `Authorization: Bearer` or `?token=`
+//!   (unset = no auth, localhost use only); accepts `file:`/`keyring:` references
+//! - `LIVE_WS_BUFFER`: Frames buffered per client before it lags (default: 1024)
+
+use crate::error::SolflowError;
+use crate::pipeline::audit_trades::AuditTrade;
+use crate::pipeline::db::AggregateDbWriter;
+use crate::pipeline::sessions::SessionRollup;
+use crate::pipeline::signals::TokenSignal;
+use crate::pipeline::types::AggregatedTokenState;
+use crate::streamer_core::secrets::secret_from_env;
+use async_trait::async_trait;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use log::{debug, info, warn};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Most mints one client can subscribe to
+const MAX_SUBSCRIBED_MINTS: usize = 1000;
+
+/// Frame categories a client can filter on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameKind {
+    Aggregate,
+    Signal,
+}
+
+impl FrameKind {
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "aggregate" | "aggregates" => Some(Self::Aggregate),
+            "signal" | "signals" => Some(Self::Signal),
+            _ => None,
+        }
+    }
+}
+
+/// One serialized push frame
+#[derive(Debug)]
+pub struct LiveFrame {
+    pub kind: FrameKind,
+    pub mint: String,
+    pub json: String,
+}
+
+impl LiveFrame {
+    pub fn aggregate(aggregate: &AggregatedTokenState) -> Self {
+        let frame = json!({"type": "aggregate", "mint": aggregate.mint, "data": aggregate});
+        Self {
+            kind: FrameKind::Aggregate,
+            mint: aggregate.mint.clone(),
+            json: frame.to_string(),
+        }
+    }
+
+    pub fn signal(signal: &TokenSignal) -> Self {
+        let details = signal
+            .details_json
+            .as_deref()
+            .and_then(|details| serde_json::from_str::<serde_json::Value>(details).ok());
+        let frame = json!({
+            "type": "signal",
+            "mint": signal.mint,
+            "data": {
+                "signal_type": signal.signal_type.as_str(),
+                "window_seconds": signal.window_seconds,
+                "severity": signal.severity,
+                "score": signal.score,
+                "details": details,
+                "created_at": signal.created_at,
+                "source": signal.source,
+            },
+        });
+        Self {
+            kind: FrameKind::Signal,
+            mint: signal.mint.clone(),
+            json: frame.to_string(),
+        }
+    }
+}
+
+/// Broadcast channel between the writer and connected clients; cheap to clone
+#[derive(Clone)]
+pub struct LiveFeed {
+    tx: broadcast::Sender<Arc<LiveFrame>>,
+}
+
+impl LiveFeed {
+    /// `buffer` frames are kept for each client before it lags
+    pub fn new(buffer: usize) -> Self {
+        let (tx, _) = broadcast::channel(buffer.max(1));
+        Self { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveFrame>> {
+        self.tx.subscribe()
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    pub fn publish(&self, frame: LiveFrame) {
+        // Err only means no client is connected
+        let _ = self.tx.send(Arc::new(frame));
+    }
+}
+
+/// `AggregateDbWriter` that publishes what the inner writer accepted
+///
+/// `as_any` forwards to the inner writer so downcasts (DCA bucket cleanup)
+/// keep working through the wrapper.
+pub struct LiveFeedWriter {
+    inner: Arc<dyn AggregateDbWriter + Send + Sync>,
+    feed: LiveFeed,
+}
+
+impl LiveFeedWriter {
+    pub fn new(inner: Arc<dyn AggregateDbWriter + Send + Sync>, feed: LiveFeed) -> Self {
+        Self { inner, feed }
+    }
+}
+
+#[async_trait]
+impl AggregateDbWriter for LiveFeedWriter {
+    async fn write_aggregates(
+        &self,
+        aggregates: Vec<AggregatedTokenState>,
+    ) -> Result<(), SolflowError> {
+        let frames: Vec<LiveFrame> = if self.feed.has_subscribers() {
+            aggregates.iter().map(LiveFrame::aggregate).collect()
+        } else {
+            Vec::new()
+        };
+        self.inner.write_aggregates(aggregates).await?;
+        for frame in frames {
+            self.feed.publish(frame);
+        }
+        Ok(())
+    }
+
+    async fn write_signal(&self, signal: TokenSignal) -> Result<(), SolflowError> {
+        let frame = self.feed.has_subscribers().then(|| LiveFrame::signal(&signal));
+        // Blocklisted signals are rejected here and never pushed
+        self.inner.write_signal(signal).await?;
+        if let Some(frame) = frame {
+            self.feed.publish(frame);
+        }
+        Ok(())
+    }
+
+    async fn write_signals(&self, signals: Vec<TokenSignal>) -> Result<Vec<TokenSignal>, SolflowError> {
+        // Only what the inner writer accepted is pushed
+        let written = self.inner.write_signals(signals).await?;
+        if self.feed.has_subscribers() {
+            for signal in &written {
+                self.feed.publish(LiveFrame::signal(signal));
+            }
+        }
+        Ok(written)
+    }
+
+    async fn write_system_metric(
+        &self,
+        key: &str,
+        value_json: &str,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_system_metric(key, value_json).await
+    }
+
+    async fn write_session_rollups(
+        &self,
+        rollups: Vec<SessionRollup>,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_session_rollups(rollups).await
+    }
+
+    async fn write_audit_trades(
+        &self,
+        trades: Vec<AuditTrade>,
+        prune_before: i64,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_audit_trades(trades, prune_before).await
+    }
+
+    async fn compact_signal_details(
+        &self,
+        before: i64,
+        limit: usize,
+    ) -> Result<usize, SolflowError> {
+        self.inner.compact_signal_details(before, limit).await
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+}
+
+/// Client → server message
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum ClientCommand {
+    Subscribe { mints: Vec<String> },
+    Unsubscribe { mints: Vec<String> },
+}
+
+/// What one client receives
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    /// None = every mint
+    mints: Option<HashSet<String>>,
+    kinds: HashSet<FrameKind>,
+}
+
+impl Subscription {
+    /// From the `mints`/`types` query parameters
+    fn from_query(query: &HashMap<String, String>) -> Result<Self, String> {
+        let mints = query.get("mints").map(|mints| {
+            mints
+                .split(',')
+                .map(|mint| mint.trim().to_string())
+                .filter(|mint| !mint.is_empty())
+                .collect::<HashSet<_>>()
+        });
+        if mints.as_ref().is_some_and(|m| m.len() > MAX_SUBSCRIBED_MINTS) {
+            return Err(format!("at most {} mints per connection", MAX_SUBSCRIBED_MINTS));
+        }
+
+        let kinds = match query.get("types") {
+            Some(types) => types
+                .split(',')
+                .map(|kind| FrameKind::parse(kind).ok_or_else(|| format!("unknown type '{}'", kind)))
+                .collect::<Result<HashSet<_>, _>>()?,
+            None => HashSet::from([FrameKind::Aggregate, FrameKind::Signal]),
+        };
+
+        Ok(Self { mints, kinds })
+    }
+
+    pub fn matches(&self, frame: &LiveFrame) -> bool {
+        self.kinds.contains(&frame.kind)
+            && self.mints.as_ref().is_none_or(|mints| mints.contains(&frame.mint))
+    }
+
+    fn apply(&mut self, command: ClientCommand) -> Result<(), String> {
+        match command {
+            ClientCommand::Subscribe { mints: added } => {
+                let mints = self.mints.get_or_insert_with(HashSet::new);
+                mints.extend(added);
+                if mints.len() > MAX_SUBSCRIBED_MINTS {
+                    return Err(format!("at most {} mints per connection", MAX_SUBSCRIBED_MINTS));
+                }
+            }
+            ClientCommand::Unsubscribe { mints: removed } => {
+                if let Some(mints) = &mut self.mints {
+                    for mint in &removed {
+                        mints.remove(mint);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct WsState {
+    feed: LiveFeed,
+    token: Option<String>,
+}
+
+fn is_authorized(headers: &HeaderMap, query: &HashMap<String, String>, token: Option<&str>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // Browsers can't set headers on a WebSocket handshake
+    bearer.or(query.get("token").map(String::as_str)) == Some(token)
+}
+
+async fn upgrade(
+    State(state): State<WsState>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if !is_authorized(&headers, &query, state.token.as_deref()) {
+        return (StatusCode::UNAUTHORIZED, "unauthorized").into_response();
+    }
+    let subscription = match Subscription::from_query(&query) {
+        Ok(subscription) => subscription,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    let rx = state.feed.subscribe();
+    ws.on_upgrade(move |socket| serve_client(socket, rx, subscription))
+}
+
+async fn serve_client(
+    mut socket: WebSocket,
+    mut rx: broadcast::Receiver<Arc<LiveFrame>>,
+    mut subscription: Subscription,
+) {
+    debug!("🔌 Live WS client connected");
+    loop {
+        tokio::select! {
+            frame = rx.recv() => {
+                let text = match frame {
+                    Ok(frame) if subscription.matches(&frame) => frame.json.clone(),
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        json!({"type": "lagged", "skipped": skipped}).to_string()
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                    Some(Ok(_)) => continue, // ping/pong handled by axum, binary ignored
+                };
+                let result = serde_json::from_str::<ClientCommand>(text.as_str())
+                    .map_err(|e| e.to_string())
+                    .and_then(|command| subscription.apply(command));
+                if let Err(e) = result {
+                    let error = json!({"type": "error", "error": e}).to_string();
+                    if socket.send(Message::Text(error.into())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    debug!("🔌 Live WS client disconnected");
+}
+
+/// Push server settings
+#[derive(Debug, Clone)]
+pub struct LiveWsServer {
+    pub addr: String,
+    pub buffer: usize,
+    token: Option<String>,
+}
+
+impl LiveWsServer {
+    /// Create from the environment (see module docs); None unless an address is set
+    pub fn from_env() -> Option<Self> {
+        let addr = std::env::var("LIVE_WS_ADDR")
+            .ok()
+            .filter(|s| !s.trim().is_empty())?;
+        // Fail closed: an unreadable token reference disables the server
+        let token = match secret_from_env("LIVE_WS_TOKEN") {
+            Ok(token) => token,
+            Err(e) => {
+                warn!("⚠️  Live WS server disabled: {}", e);
+                return None;
+            }
+        };
+        let buffer = std::env::var("LIVE_WS_BUFFER")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1024);
+        Some(Self { addr, buffer, token })
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Bind and serve until the process exits
+    pub async fn serve(self, feed: LiveFeed) -> Result<(), std::io::Error> {
+        if self.token.is_none() {
+            warn!("⚠️  LIVE_WS_TOKEN not set: live WS server accepts unauthenticated clients");
+        }
+
+        let app = Router::new().route("/ws", get(upgrade)).with_state(WsState {
+            feed,
+            token: self.token,
+        });
+
+        let listener = tokio::net::TcpListener::bind(&self.addr).await?;
+        info!("📡 Live WS server listening on {}", self.addr);
+        axum::serve(listener, app).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::db::{run_schema_migrations, SqliteAggregateWriter};
+    use crate::pipeline::signals::SignalType;
+    use rusqlite::Connection;
+    use tempfile::NamedTempFile;
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn signal_frame(mint: &str) -> LiveFrame {
+        LiveFrame::signal(&TokenSignal::new(mint.to_string(), SignalType::Breakout, 60, 1000))
+    }
+
+    #[test]
+    fn test_subscription_filtering() {
+        let all = Subscription::from_query(&query(&[])).unwrap();
+        assert!(all.matches(&signal_frame("a")));
+
+        let mut sub = Subscription::from_query(&query(&[("mints", "a, b"), ("types", "signal")])).unwrap();
+        assert!(sub.matches(&signal_frame("a")));
+        assert!(!sub.matches(&signal_frame("c")));
+
+        sub.apply(serde_json::from_str(r#"{"op":"subscribe","mints":["c"]}"#).unwrap()).unwrap();
+        assert!(sub.matches(&signal_frame("c")));
+        sub.apply(serde_json::from_str(r#"{"op":"unsubscribe","mints":["a"]}"#).unwrap()).unwrap();
+        assert!(!sub.matches(&signal_frame("a")));
+
+        assert!(Subscription::from_query(&query(&[("types", "trades")])).is_err());
+    }
+
+    #[test]
+    fn test_query_token_auth() {
+        let headers = HeaderMap::new();
+        assert!(is_authorized(&headers, &query(&[]), None));
+        assert!(!is_authorized(&headers, &query(&[]), Some("secret")));
+        assert!(is_authorized(&headers, &query(&[("token", "secret")]), Some("secret")));
+        assert!(!is_authorized(&headers, &query(&[("token", "other")]), Some("secret")));
+    }
+
+    #[tokio::test]
+    async fn test_writer_publishes_only_written_signals() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let db_path = temp_file.path().to_str().unwrap();
+        let mut conn = Connection::open(db_path).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        conn.execute(
+            "INSERT INTO mint_blocklist (mint, reason, blocked_by, created_at, expires_at) VALUES ('blocked', 'test', 'test', 0, NULL)",
+            [],
+        )
+        .unwrap();
+
+        let feed = LiveFeed::new(16);
+        let mut rx = feed.subscribe();
+        let writer = LiveFeedWriter::new(Arc::new(SqliteAggregateWriter::new(db_path).unwrap()), feed);
+
+        let blocked = TokenSignal::new("blocked".to_string(), SignalType::Breakout, 60, 1000);
+        assert!(writer.write_signal(blocked).await.is_err());
+        let allowed = TokenSignal::new("allowed".to_string(), SignalType::Surge, 60, 1000).with_severity(4);
+        writer.write_signal(allowed).await.unwrap();
+
+        let frame = rx.try_recv().unwrap();
+        assert_eq!(frame.mint, "allowed");
+        let value: serde_json::Value = serde_json::from_str(&frame.json).unwrap();
+        assert_eq!(value["type"], "signal");
+        assert_eq!(value["data"]["signal_type"], "SURGE");
+        assert_eq!(value["data"]["severity"], 4);
+        assert!(rx.try_recv().is_err());
+
+        // Downcasts see the inner writer
+        assert!(writer.as_any().downcast_ref::<SqliteAggregateWriter>().is_some());
+    }
+}
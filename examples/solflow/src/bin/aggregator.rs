@@ -67,7 +67,7 @@ impl AggregatorConfig {
         
         // Output destination depends on backend flag
         let output_path: PathBuf = match backend {
-            BackendType::Sqlite => db_path.clone(),
+            BackendType::Sqlite | BackendType::Postgres | BackendType::Kafka => db_path.clone(),
             BackendType::Jsonl => std::env::var("AGGREGATES_OUTPUT_PATH")
                 .unwrap_or_else(|_| "streams/aggregates".to_string())
                 .into(),
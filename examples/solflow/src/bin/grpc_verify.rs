@@ -17,13 +17,15 @@ use {
     solana_account_decoder_client_types::token::UiTokenAmount,
     solana_pubkey::Pubkey,
     solana_transaction_status::TransactionStatusMeta,
+    solflow::streamer_core::filter_builder::TransactionFilterBuilder,
+    solflow::streamer_core::secrets::{redact_url, secret_from_env},
     std::{
         collections::HashMap,
         env,
         sync::Arc,
     },
     tokio::sync::RwLock,
-    yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeRequestFilterTransactions},
+    yellowstone_grpc_proto::geyser::CommitmentLevel,
 };
 
 /// Configuration loaded from environment variables
@@ -38,7 +40,7 @@ impl Config {
         let geyser_url = env::var("GEYSER_URL")
             .expect("GEYSER_URL must be set in .env file");
         
-        let x_token = env::var("X_TOKEN").ok();
+        let x_token = secret_from_env("X_TOKEN").unwrap_or_else(|e| panic!("{}", e));
         
         // Parse PROGRAM_FILTERS (comma-separated list of program IDs)
         let program_filters = env::var("PROGRAM_FILTERS")
@@ -474,33 +476,22 @@ pub async fn main() -> CarbonResult<()> {
     
     log::info!("🚀 Starting gRPC Discriminator Verification Script");
     log::info!("📊 Configuration:");
-    log::info!("   GEYSER_URL: {}", config.geyser_url);
+    log::info!("   GEYSER_URL: {}", redact_url(&config.geyser_url));
     log::info!("   PROGRAM_FILTERS: {} program(s)", config.program_filters.len());
     for (idx, program_id) in config.program_filters.iter().enumerate() {
         log::info!("     {}. {}", idx + 1, program_id);
     }
     log::info!("   Detection: Metadata-based (BUY/SELL from SOL flow direction)");
     
-    // Setup transaction filters - create separate filter for each program ID (OR logic)
-    // account_required uses AND logic (all accounts must be present), so we need separate filters
-    // Yellowstone treats multiple filters as OR (any filter can match)
-    let mut transaction_filters: HashMap<String, SubscribeRequestFilterTransactions> = HashMap::new();
-    
-    for (idx, program_id) in config.program_filters.iter().enumerate() {
-        let filter = SubscribeRequestFilterTransactions {
-            vote: Some(false),
-            failed: Some(false),
-            account_include: vec![],
-            account_exclude: vec![],
-            account_required: vec![program_id.clone()],
-            signature: None,
-        };
-        transaction_filters.insert(format!("program_filter_{}", idx), filter);
-    }
+    // Setup transaction filters - one filter per program ID (OR logic)
+    let transaction_filters = TransactionFilterBuilder::new()
+        .programs(config.program_filters.iter().cloned())
+        .build()
+        .unwrap_or_else(|e| panic!("Invalid PROGRAM_FILTERS: {}", e));
     
     log::info!("   Filter logic: OR (transactions matching ANY program will be included)");
     
-    log::info!("🔌 Connecting to Yellowstone gRPC: {}", config.geyser_url);
+    log::info!("🔌 Connecting to Yellowstone gRPC: {}", redact_url(&config.geyser_url));
     let yellowstone_grpc = YellowstoneGrpcGeyserClient::new(
         config.geyser_url.clone(),
         config.x_token.clone(),
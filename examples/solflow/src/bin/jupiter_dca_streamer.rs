@@ -33,6 +33,7 @@
 
 use solflow::streamer_core::{config::StreamerConfig, run};
 use solflow::streamer_core::config::BackendType;
+use solflow::streamer_core::{postgres_writer, writer_backend};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -45,6 +46,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap_or_else(|_| "/var/lib/solflow/solflow.db".to_string()),
         BackendType::Jsonl => std::env::var("JUPITER_DCA_OUTPUT_PATH")
             .unwrap_or_else(|_| "streams/jupiter_dca/events.jsonl".to_string()),
+        BackendType::Postgres => postgres_writer::database_url_from_env()?,
+        BackendType::Kafka => writer_backend::kafka_brokers_from_env()?,
     };
     
     if backend == BackendType::Sqlite {
@@ -61,5 +64,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     config.validate()?;
 
-    run(config).await
+    run(config).await?;
+    Ok(())
 }
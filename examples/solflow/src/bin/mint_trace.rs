@@ -79,6 +79,7 @@ use solflow::streamer_core::{
     balance_extractor::{build_full_account_keys, extract_sol_changes, extract_token_changes},
     config::RuntimeConfig,
     grpc_client::create_single_account_client,
+    secrets::redact_url,
 };
 
 /// Logger helper for writing to console and/or file
@@ -426,7 +427,7 @@ async fn run_with_reconnect(
     let mut retry_count = 0;
 
     loop {
-        log::info!("🔌 Connecting to gRPC endpoint: {}", config.runtime_config.geyser_url);
+        log::info!("🔌 Connecting to gRPC endpoint: {}", redact_url(&config.runtime_config.geyser_url));
         
         let client = match create_single_account_client(
             &config.runtime_config.geyser_url,
@@ -566,7 +567,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("║                          MINT TRACE - Transaction Monitor                     ║");
     println!("╠═══════════════════════════════════════════════════════════════════════════════╣");
     println!("║ Target Mint:  {:<67} ║", config.target_mint);
-    println!("║ Geyser URL:   {:<67} ║", config.runtime_config.geyser_url);
+    println!("║ Geyser URL:   {:<67} ║", redact_url(&config.runtime_config.geyser_url));
     println!("║ Commitment:   {:<67} ║", format!("{:?}", config.runtime_config.commitment_level));
     
     // Auth status (without leaking token value)
@@ -587,7 +588,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!();
 
     log::info!("🎯 Target mint: {}", config.target_mint);
-    log::info!("🔗 Geyser URL: {}", config.runtime_config.geyser_url);
+    log::info!("🔗 Geyser URL: {}", redact_url(&config.runtime_config.geyser_url));
     log::info!("📊 Commitment: {:?}", config.runtime_config.commitment_level);
     
     // Log auth status without exposing token
@@ -12,6 +12,7 @@
 
 use solflow::streamer_core::{run, StreamerConfig};
 use solflow::streamer_core::config::BackendType;
+use solflow::streamer_core::{postgres_writer, writer_backend};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -24,6 +25,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap_or_else(|_| "/var/lib/solflow/solflow.db".to_string()),
         BackendType::Jsonl => std::env::var("MOONSHOT_OUTPUT_PATH")
             .unwrap_or_else(|_| "streams/moonshot/events.jsonl".to_string()),
+        BackendType::Postgres => postgres_writer::database_url_from_env()?,
+        BackendType::Kafka => writer_backend::kafka_brokers_from_env()?,
     };
     
     if backend == BackendType::Sqlite {
@@ -38,5 +41,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         pipeline_tx: None, // Phase 4.2: Set by pipeline_runtime when enabled
     };
 
-    run(config).await
+    run(config).await?;
+    Ok(())
 }
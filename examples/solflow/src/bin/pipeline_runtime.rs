@@ -8,26 +8,220 @@
 //!
 //! Usage:
 //!   cargo run --release --bin pipeline_runtime
+//!   cargo run --release --bin pipeline_runtime -- --replay <events.jsonl> [--speed 1x|10x|max] [--from <time>] [--to <time>]
+//!   cargo run --release --bin pipeline_runtime -- --tune --replay <events.jsonl> [--from <time>] [--to <time>]
+//!   cargo run --release --bin pipeline_runtime -- --status
+//!
+//! Replay mode feeds a streamer JSONL capture into the pipeline instead of
+//! live streamers; the engine clock follows trade time. `--from`/`--to` take
+//! unix seconds or RFC3339. Use a scratch SOLFLOW_DB_PATH.
+//!
+//! `--tune` loads the replay window into memory and opens a prompt for
+//! adjusting signal thresholds and re-running the engine over it (see
+//! `pipeline::tuning`); nothing is written to the database.
+//!
+//! `--status` prints the JSON self-description (configuration, tracked
+//! programs, windows, sinks, DB size, last flush) of the instance serving the
+//! query API at API_ADDR, as returned by its `/status` endpoint.
+//!
+//! This binary is a wrapper around `solflow::Engine` (see `embed`), which
+//! owns the engine shards, ingestion, the unified streamer, engine snapshots,
+//! the event journal and the notifier. The runtime attaches what the
+//! environment enables and runs the database-side services around it.
 //!
 //! Environment variables:
 //!   SOLFLOW_DB_PATH - SQLite database path (default: /var/lib/solflow/solflow.db)
+//!   SQLITE_WRITE_QUEUE_CAPACITY - SQLite writes queued for the writer thread before callers wait (default: 64)
 //!   ENABLE_PIPELINE - Master switch (default: false)
 //!   AGGREGATE_FLUSH_INTERVAL_MS - Flush interval (default: 5000)
+//!   FLUSH_COMPUTE_BUDGET_MS - Per-flush compute budget; the rest is deferred (default: half the interval)
 //!   STREAMER_CHANNEL_BUFFER - Channel size (default: 10000)
+//!   SIGNAL_WARMUP_SECS - Suppress signals after startup (default: 300)
+//!   TOKEN_WARMUP_SECS - Suppress signals after a token cold-starts (default: 0)
+//!   BREAKOUT_WALLET_METRIC - Wallets BREAKOUT counts: any | new (default: any)
+//!   SIGNAL_THRESHOLDS_PATH - Signal thresholds file, TOML (.toml) or JSON (default: built-in thresholds)
+//!   SIGNAL_THRESHOLDS_RELOAD_SECS - Re-read the thresholds file when it changes, checked every N seconds (default: 10, 0 = off)
+//!   SIGNAL_THRESHOLD_<SIGNAL>_<FIELD> - Override one threshold, e.g. SIGNAL_THRESHOLD_BREAKOUT_NET_FLOW_60S_MIN=8
+//!   OUTLIER_MODE - Outlier trades in window metrics: off | cap | exclude (default: off)
+//!   OUTLIER_MAX_STDDEV - Outlier threshold in standard deviations (default: 4.0)
+//!   PIPELINE_WINDOWS - Extra rolling windows, e.g. 30s,1h (default: 60,300,900; up to 4h)
+//!   FEE_MODELS - Venue swap fees in bps for fee-adjusted net flow, e.g. PumpSwap=30,*=25 (default: built-in per venue)
+//!   SOLANA_RPC_URL - RPC endpoint for Jupiter DCA order, wallet age, holder and risk lookups (optional)
+//!   DCA_ORDER_CACHE_TTL_SECS - Reuse decoded DCA orders for N seconds (default: 30)
+//!   DCA_BUCKET_SECS - dca_activity_buckets bucket width (default: 60)
+//!   DCA_BUCKET_RETENTION_SECS - dca_activity_buckets retention (default: 7200)
+//!   DCA_ROLLUP_RETENTION_SECS - 5-minute dca_activity_rollups retention (default: 86400)
+//!   RETENTION_INTERVAL_SECS - Time between retention passes over every database (default: 300)
+//!   RETENTION_SIGNALS_SECS - token_signals retention, 0 = keep forever (default: 604800)
+//!   RETENTION_STALE_AGGREGATES_SECS - Delete token_aggregates rows with no trade for N seconds, 0 = keep (default: 86400)
+//!   RETENTION_BATCH_ROWS - Rows deleted per statement (default: 5000)
+//!   RETENTION_VACUUM_PAGES - Free pages reclaimed per database per pass, 0 = no vacuum (default: 1000)
+//!   SIGNAL_DETAILS_RETENTION_SECS - Drop token_signals details_json after N seconds (default: 604800, 0 = keep)
+//!   SIGNAL_DETAILS_COMPACT_BATCH - Signals compacted per flush (default: 500)
+//!   WALLET_AGE_ENABLED - Resolve buyer wallet ages for fresh-wallet ratios (default: false)
+//!   WALLET_AGE_LOOKUPS_PER_SEC - Wallet age RPC lookups per second (default: 5)
+//!   FRESH_WALLET_MAX_AGE_SECS - Buyer wallets younger than this count as fresh (default: 86400)
+//!   HOLDER_CONCENTRATION_ENABLED - Fetch top-10 holder share for active mints (default: false)
+//!   HOLDER_REFRESH_SECS - Holder concentration refresh interval (default: 300)
+//!   HOLDER_MAX_MINTS - Mints refreshed per holder pass, by 300s volume (default: 20)
+//!   RISK_CHECK_ENABLED - Flag mint/freeze authority and unlocked LP, lowering signal severity (default: false)
+//!   RISK_CHECK_INTERVAL_SECS - Risk check pass interval (default: 60)
+//!   RISK_MAX_MINTS - Mints risk-checked per pass (default: 20)
+//!   RISK_RECHECK_SECS - Re-check flagged mints after N seconds (default: 3600)
+//!   RISK_LP_LOCK_PROGRAMS - Extra comma-separated LP lock program ids (optional)
+//!   WALLET_GRAPH_MAX_WALLETS - Funding-linked wallets kept for unique_clusters_300s before the graph resets (default: 500000, 0 = disabled)
+//!   DATASOURCE - grpc | rpc (default: grpc, or rpc when only RPC_WS_URL is set)
+//!   RPC_WS_URL - RPC websocket for the blockSubscribe fallback (reduced fidelity, unified mode only)
+//!   GRPC_ACCOUNT_INCLUDE - Comma-separated accounts; transactions must touch at least one (optional)
+//!   GRPC_ACCOUNT_EXCLUDE - Comma-separated accounts; transactions touching any are dropped (optional)
+//!   FOCUS_WALLETS - Comma-separated wallets subscribed to in addition to the programs; their trades are wallet-tagged (unified mode, optional)
+//!   FOCUS_WALLETS_ONLY - Subscribe to FOCUS_WALLETS only instead of all venue traffic (default: false)
+//!   BACKFILL_HOURS - Replay the last N hours over RPC before going live (unified mode, default: disabled)
+//!   BACKFILL_START_TIME / BACKFILL_END_TIME - Backfill unix time range instead of BACKFILL_HOURS
+//!   BACKFILL_START_SLOT / BACKFILL_END_SLOT - Backfill slot range (combined with the time range)
+//!   BACKFILL_RPC_URL - Backfill RPC endpoint (default: SOLANA_RPC_URL)
+//!   BACKFILL_CONCURRENCY - Parallel getTransaction requests during backfill (default: 8)
+//!   INSTANCE_LEASE_ENABLED - Only the lease holder flushes, enriches and scores (default: true)
+//!   INSTANCE_LEASE_TTL_SECS - Lease lifetime; standby takeover delay after a crash (default: 30)
+//!   INSTANCE_ID - Identity in instance_leases (default: $HOSTNAME:<pid>)
+//!   METADATA_REFRESH_ACTIVE_SECS - Refresh cadence for tokens traded in the last 5 min (default: 30)
+//!   METADATA_REFRESH_WARM_SECS - Refresh cadence for tokens traded in the last hour (default: 300)
+//!   METADATA_REFRESH_DORMANT_SECS - Refresh cadence for everything else (default: 86400)
+//!   METADATA_REFRESH_MAX_REQUESTS - DexScreener requests per refresh cycle (default: 10)
+//!   METADATA_REFRESH_INTEREST_SECS - Refresh cadence for promoted tokens of interest (default: 10)
+//!   ENRICHMENT_DISABLE - Comma-separated enrichment stages to skip: metadata_refresh, persistence_scoring
+//!   ANOMALY_CAPTURE_ENABLED - Capture full transactions for mints after severe signals (default: false)
+//!   ANOMALY_CAPTURE_MIN_SEVERITY - Lowest signal severity that arms capture (default: 4)
+//!   ANOMALY_CAPTURE_MINUTES - Capture duration per signal (default: 10)
+//!   ANOMALY_CAPTURE_DIR - Capture output directory, one JSONL per mint (default: captures/anomaly)
+//!   POSITIONS_ENABLED - Mark open positions with live PnL and alert on exits (default: false)
+//!   POSITION_REFRESH_SECS - Position marking interval (default: 10)
+//!   INTEGRITY_AUDIT - Startup check for corrupt rows: off | report | quarantine (default: report)
+//!   INTEGRITY_FUTURE_SKEW_SECS - Timestamps further ahead than this count as corrupt (default: 300)
+//!   POSITION_STOP_LOSS_PCT / POSITION_TAKE_PROFIT_PCT - Exit rules in percent (default: 30 / 100)
+//!   POSITION_EXIT_NET_FLOW_SOL - Sell-pressure exit when 300s net flow < -N SOL (default: 5.0)
+//!   PAPER_STRATEGIES - Paper-trading strategies: JSON array or path to a JSON file (default: disabled)
+//!   PAPER_TRADING_INTERVAL_SECS - Paper-trading signal poll interval (default: 5)
+//!   SIGNAL_WEBHOOK_ADDR - Listen address for external signal submissions, e.g. 127.0.0.1:8787 (default: disabled)
+//!   SIGNAL_WEBHOOK_TOKEN - Bearer token required by the signal webhook (default: none)
+//!   LIVE_WS_ADDR - WebSocket push of written aggregates and signals at /ws, e.g. 127.0.0.1:8788 (default: disabled)
+//!   LIVE_WS_TOKEN - Token required by the live WS server, as bearer header or ?token= (default: none)
+//!   LIVE_WS_BUFFER - Frames buffered per live WS client before it lags (default: 1024)
+//!   CLICKHOUSE_URL - Append aggregate snapshots to ClickHouse token_aggregate_history, e.g. http://clickhouse:8123 (default: disabled)
+//!   CLICKHOUSE_DATABASE / CLICKHOUSE_USER / CLICKHOUSE_PASSWORD - ClickHouse database and credentials (default: default / none)
+//!   HISTORY_INTERVAL_SECS - Aggregate history snapshot interval per mint (default: 60)
+//!   ACCOUNT_CACHE_CAPACITY - Pubkeys kept in the shared address/classification LRU (default: 50000)
+//!   KNOWN_POOL_ACCOUNTS - Comma-separated pool/vault accounts classified as non-wallets (default: none)
+//!   KNOWN_FEE_ACCOUNTS - Comma-separated fee recipients classified as non-wallets (default: none)
+//!   API_ADDR - HTTP query API (/health, /status, /tokens/top, /tokens/{mint}/..., /subscription), e.g. 127.0.0.1:8790 (default: disabled)
+//!   API_TOKEN - Bearer token required by the query API (default: none)
+//!   NOTIFY_ROUTES - Signal routing rules and sinks: JSON object or path to a JSON file (default: disabled)
+//!   NOTIFY_INTERVAL_SECS - Notifier signal poll interval (default: 5)
+//!   DISCORD_WEBHOOK_URLS - Comma-separated Discord webhooks receiving every signal when NOTIFY_ROUTES is unset; marks sent_to_discord (default: disabled)
+//!   DISCORD_MIN_SEVERITY / DISCORD_MAX_PER_MINUTE - Severity floor and per-webhook rate limit for DISCORD_WEBHOOK_URLS (default: 1 / 25)
+//!   TELEGRAM_BOT_TOKEN - Telegram bot: alerts to TELEGRAM_CHAT_IDS and /block, /unblock commands on mint_blocklist (default: disabled)
+//!   TELEGRAM_CHAT_IDS - Comma-separated chats that receive alerts and may send commands (required with TELEGRAM_BOT_TOKEN)
+//!   TELEGRAM_MIN_SEVERITY - Lowest severity pushed to TELEGRAM_CHAT_IDS when NOTIFY_ROUTES is unset (default: 4)
+//!   RECONCILE_RPC_URL - RPC endpoint to verify severity-5 signals' sample signatures with getTransaction before notifying (default: disabled)
+//!   RECONCILE_SAMPLE_SIZE / RECONCILE_COMMITMENT - Signatures checked per signal and commitment they must reach (default: 3 / confirmed)
+//!   REPLAY_PATH / REPLAY_SPEED / REPLAY_FROM / REPLAY_TO - Replay mode (CLI flags take precedence)
+//!   CRASH_REPORT_DIR - Crash bundle directory, written on panic or fatal error (default: crash_reports)
+//!   CRASH_REPORT_TRADES - Recent trades included in crash bundles (default: 200)
+//!   FAST_PATH_ENABLED - Aggregate and signal-check watched mints per trade, bypassing the batch flush (unified mode, default: false)
+//!   FAST_PATH_MINTS - Comma-separated mints always on the fast path (optional)
+//!   FAST_PATH_FOLLOWED - Also fast-path followed tokens (follow_price = 1) (default: true)
+//!   FAST_PATH_REFRESH_SECS - Followed token reload interval (default: 30)
+//!   FAST_PATH_CHANNEL_BUFFER - Fast channel size; overflow uses the batch channel (default: 1000)
+//!   INTEREST_ENABLED - Promote busy tokens to a high-fidelity tier: fast path, pinned state, capture, faster enrichment (default: false)
+//!   INTEREST_BUY_COUNT_300S / INTEREST_NET_FLOW_300S_SOL / INTEREST_UNIQUE_WALLETS_300S - Activity that promotes a token, any one suffices (default: 40 / 25.0 / 25)
+//!   INTEREST_DEMOTE_AFTER_SECS / INTEREST_MAX_TOKENS - Quiet time before demotion, promoted tokens at once (default: 900 / 50)
+//!   AUDIT_TRADES_ENABLED - Keep raw trades of watched mints in audit_trades (default: false)
+//!   AUDIT_TRADES_MINTS - Comma-separated mints to audit (optional)
+//!   AUDIT_TRADES_FOLLOWED - Also audit followed tokens (follow_price = 1) (default: true)
+//!   AUDIT_TRADES_RETENTION_MINS - Audit rows kept, by block time (default: 30)
+//!   WALLET_TRACKING_ENABLED - Per-wallet state and SMART_WALLET_ENTRY signals (default: false)
+//!   SMART_WALLET_MIN_CLOSED / SMART_WALLET_MIN_WIN_RATE / SMART_WALLET_MIN_PNL_SOL - Smart-wallet bar (default: 5 / 0.6 / 2.0)
+//!   SMART_WALLET_MIN_ENTRY_SOL - Smallest buy that counts as a smart-wallet entry (default: 0.5)
+//!   WALLET_IDLE_PRUNE_SECS - Forget idle wallets that don't qualify as smart (default: 86400)
+//!   PROMETHEUS_METRICS_ADDR - Serve Prometheus /metrics on this address, e.g. 0.0.0.0:9100 (default: disabled)
+//!   LOG_METRICS_ENABLED - Periodic carbon pipeline metrics report on stderr (default: true)
+//!   COMMITMENT_LEVEL - Ingest commitment; *_final aggregate columns count trades once gRPC slot status finalizes them (default: confirmed)
+//!   REORG_ROLLBACK_ENABLED - Roll back trades from dead/abandoned-fork slots via slot-status updates (gRPC, non-finalized commitment, default: true)
+//!   MINT_WATCHER_ENABLED - Record InitializeMint creations in token_metadata.mint_created_at (gRPC only, default: false)
+//!   MINT_WATCHER_PROGRAMS - Only watch mint creations in transactions invoking one of these programs (default: all)
+//!   GEYSER_URLS - Comma-separated Yellowstone endpoints, primary first; the streamer fails over between them (default: GEYSER_URL)
+//!   GEYSER_HEALTH_INTERVAL_SECS - Time between endpoint health probes (default: 10)
+//!   GEYSER_MAX_SLOT_LAG - Slots behind the best endpoint before failing over (default: 100)
+//!   GEYSER_SECONDARY_URL - Second provider subscribed alongside the first; streams are merged and deduplicated by signature (default: disabled)
+//!   GEYSER_SECONDARY_X_TOKEN - Token for GEYSER_SECONDARY_URL (default: none)
+//!   SLOT_GAP_THRESHOLD - Skipped slots in the live stream reported as a DATA_GAP, e.g. 150; aggregates over a gap get last_data_gap_at (default: 0 = off; skipped with GRPC_ACCOUNT_INCLUDE or FOCUS_WALLETS_ONLY)
+//!   SLOT_GAP_BACKFILL - Backfill DATA_GAP slots over BACKFILL_RPC_URL / SOLANA_RPC_URL (default: false)
+//!   PROGRAMS_CONFIG_PATH - TOML registry of tracked programs and their discriminators, replacing the built-in one (default: built-in)
+//!   PROGRAMS_RELOAD_SECS - Re-read the programs file when it changes, rebuilding the scanner and gRPC filters, checked every N seconds (default: 10, 0 = off)
+//!   LIQUIDITY_TRACKING_ENABLED - Track pool vault balances for liquidity_sol and LIQUIDITY_DRAIN (gRPC only, default: false)
+//!   LIQUIDITY_MAX_VAULTS - Most pool vaults the liquidity tracker subscribes to (default: 2000)
+//!   POOL_STATE_ENABLED - Track bonding-curve/Whirlpool state accounts for an on-chain pool_price_sol (gRPC only, default: false)
+//!   POOL_STATE_MAX_ACCOUNTS - Most pool state accounts the pool price tracker subscribes to (default: 2000)
+//!   SOL_USD_REFRESH_SECS - SOL/USD price refresh for USDC/USDT-quoted trades (default: 60, 0 = never)
+//!   SOL_USD_MAX_AGE_SECS - Oldest SOL/USD price used; stablecoin trades are dropped after (default: 600)
+//!   PIPELINE_SHARDS - Engine shards by mint hash, each with its own ingestion task (default: 1; 1 with wallet tracking)
+//!   ENGINE_SNAPSHOT_PATH - Snapshot rolling windows to this file and restore them on startup (default: disabled; ignored in replay mode)
+//!   ENGINE_SNAPSHOT_INTERVAL_SECS - Engine snapshot interval; a final snapshot is written on shutdown (default: 60)
+//!   ENGINE_SNAPSHOT_MAX_AGE_SECS - Older snapshots are not restored (default: 3600)
+//!   EVENT_JOURNAL_DIR - Journal trades before aggregation and replay unflushed ones on startup, one directory per instance (default: disabled; ignored in replay mode)
+//!   EVENT_JOURNAL_SEGMENT_BYTES - Journal segment size before rotating (default: 67108864)
+//!   TRADE_DEDUP_TTL_SECS - Drop trades re-delivered (same signature + trade index) within N seconds, 0 = off (default: 300)
+//!   TRADE_DEDUP_MAX_ENTRIES - Delivered trades remembered for dedup at most (default: 200000)
+//!   SHUTDOWN_GRACE_SECS - Time Ctrl+C allows the streamer and final flush to finish (default: 10)
+//!   DB_ROUTES - Route tables to separate SQLite files, e.g. "hot=/data/hot.db:token_aggregates,token_signals"
+//!   DB_BACKUP_DIR - Online-backup snapshots of every database, plus <name>-latest.db read replicas (default: disabled)
+//!   DB_BACKUP_INTERVAL_SECS - Snapshot interval (default: 3600)
+//!   DB_BACKUP_KEEP - Timestamped snapshots kept per database (default: 24)
+//!   DB_BACKUP_UPLOAD_CMD - Run per snapshot with {path} substituted, e.g. "aws s3 cp {path} s3://bucket/" (default: none)
 
 use dotenv::dotenv;
 use log::{error, info, warn};
-use rusqlite::Connection;
+use solflow::api::rest::ApiServer;
+use solflow::api::ws_server::{LiveFeed, LiveFeedWriter, LiveWsServer};
+use solflow::embed::{open_writer, Engine, EngineOptions, SnapshotOptions, TradeSource};
+use solflow::meta_analysis::AnomalyCapture;
 use solflow::pipeline::{
+    audit_trades::{AuditTradeLog, AuditTradesConfig},
+    backup::{backup_sources, run_backup_cycle, BackupConfig},
     config::PipelineConfig,
-    db::{run_schema_migrations, AggregateDbWriter, SqliteAggregateWriter},
+    crash_report::CrashReporter,
+    fast_path::{FastPathConfig, WatchedMints},
+    holders::HolderConcentration,
+    risk::RiskChecker,
+    interest::{InterestConfig, TokensOfInterest},
+    db::AggregateDbWriter,
     engine::PipelineEngine,
-    ingestion::start_pipeline_ingestion,
-    types::TradeEvent,
+    enrichment::EnrichmentPipeline,
+    history::{ClickHouseHistoryWriter, HistoryConfig},
+    journal::JournalConfig,
+    lease::{InstanceLease, RUNTIME_LEASE_NAME},
+    metadata_scheduler::{MetadataRefreshScheduler, RefreshSchedule},
+    notifier::{load_notify_config_from_env, Notifier},
+    reconcile::{ReconcileConfig, Reconciler},
+    retention::{RetentionConfig, RetentionManager},
+    telegram_bot::{TelegramBot, TelegramBotConfig},
+    paper_trading::{load_strategies_from_env, PaperFill, PaperTrader},
+    persistence_scorer::PersistenceScorer,
+    positions::{ExitRules, PositionTracker},
+    routing::parse_routes,
+    replay::{load_replay_window, run_replay, ReplayClock, ReplayOptions},
+    status::{fetch_status, RuntimeStatus, TrackedProgram},
+    thresholds::{spawn_thresholds_reload, thresholds_path, SignalThresholdsConfig},
+    tuning::{run_repl, TuningSession},
+    wallet_age::{WalletAgeCache, WalletAgeResolver},
+    wallet_graph,
+    wallets::{WalletTracker, WalletTrackingConfig},
+    webhook::SignalWebhook,
 };
-use solflow::streamer_core::{config::{BackendType, StreamerConfig}, run as run_streamer};
+use solflow::streamer_core::{config::{BackendType, RuntimeConfig, StreamerConfig}, metrics, run as run_streamer, secrets::secret_from_env, mint_watcher::{self, MintCreation}, program_registry::scanner_from_env, slot_status::{self, SlotEvent}};
+use std::collections::HashMap;
 use std::env;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 
 #[tokio::main]
@@ -36,6 +230,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
     env_logger::init();
 
+    // Crash bundles on panic or fatal error (see pipeline::crash_report)
+    let crash_reporter = CrashReporter::from_env();
+    crash_reporter.install_panic_hook();
+
+    let result = run(crash_reporter.clone()).await;
+    if let Err(e) = &result {
+        error!("❌ Fatal error: {}", e);
+        match crash_reporter.write_bundle(&format!("fatal error: {}", e), "") {
+            Ok(path) => error!("💥 Crash report written to {}", path.display()),
+            Err(write_err) => error!("💥 Failed to write crash report: {}", write_err),
+        }
+    }
+    result
+}
+
+async fn run(crash_reporter: CrashReporter) -> Result<(), Box<dyn std::error::Error>> {
     // Initialize rustls crypto provider (required for reqwest with rustls-tls)
     // This must be done before any HTTPS requests are made
     rustls::crypto::aws_lc_rs::default_provider()
@@ -48,6 +258,44 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Load configuration
     let config = PipelineConfig::from_env();
+    crash_reporter.set_config(&config);
+    let replay = ReplayOptions::from_env_and_args()?;
+
+    if env::args().any(|arg| arg == "--status") {
+        let addr = env::var("API_ADDR").map_err(|_| "--status requires API_ADDR of the running instance")?;
+        let token = secret_from_env("API_TOKEN")?;
+        let status = fetch_status(&addr, token.as_deref()).await?;
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    if env::args().any(|arg| arg == "--tune") {
+        let Some(options) = &replay else {
+            return Err("--tune requires --replay <events.jsonl>".into());
+        };
+        let (trades, stats) = load_replay_window(options).await?;
+        info!("🎛️  Tuning thresholds over {} trades from {}", stats.replayed, options.path);
+
+        let (signal_warmup_secs, token_warmup_secs) = (config.signal_warmup_secs, config.token_warmup_secs);
+        let breakout_wallet_metric = config.breakout_wallet_metric;
+        let outlier_policy = config.outlier_policy;
+        let windows = config.windows.clone();
+        let fee_models = config.fee_models.clone();
+        let mut session = TuningSession::new(
+            trades,
+            SignalThresholdsConfig::load_from_env()?,
+            (config.flush_interval_ms / 1000).max(1) as i64,
+            Box::new(move |engine: &mut PipelineEngine| {
+                engine.set_signal_warmup(signal_warmup_secs, token_warmup_secs);
+                engine.set_breakout_wallet_metric(breakout_wallet_metric);
+                engine.set_outlier_policy(outlier_policy);
+                engine.set_window_set(windows.clone());
+                engine.set_fee_models(fee_models.clone());
+            }),
+        );
+        run_repl(&mut session, std::io::stdin().lock(), std::io::stdout())?;
+        return Ok(());
+    }
 
     if !config.enabled {
         info!("⚠️  Pipeline is DISABLED (set ENABLE_PIPELINE=true to activate)");
@@ -56,6 +304,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     info!("✅ Pipeline ENABLED");
+    metrics::init_exporter().await;
     info!("   ├─ Database: {}", config.db_path);
     info!("   ├─ Channel buffer: {} trades", config.channel_buffer);
     info!("   ├─ Flush interval: {}ms", config.flush_interval_ms);
@@ -67,67 +316,436 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("   └─ Integrated streamers: 4 (PumpSwap, BonkSwap, Moonshot, JupiterDCA)");
     }
 
+    // What this instance is doing, served at /status; sinks register as they start
+    let scanner = if replay.is_none() && config.use_unified_streamer {
+        Some(scanner_from_env()?)
+    } else {
+        None
+    };
+    let (mode, programs) = match (&replay, &scanner) {
+        (Some(_), _) => ("replay", Vec::new()),
+        (None, Some(scanner)) => ("unified", scanner.tracked_programs()),
+        (None, None) => (
+            "legacy",
+            vec![
+                ("PumpSwap", "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA".to_string()),
+                ("BonkSwap", "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj".to_string()),
+                ("Moonshot", "MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG".to_string()),
+                ("JupiterDCA", "DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M".to_string()),
+            ],
+        ),
+    };
+    let programs = programs
+        .into_iter()
+        .map(|(name, program_id)| TrackedProgram {
+            name: name.to_string(),
+            program_id,
+        })
+        .collect();
+    let status = Arc::new(RuntimeStatus::new(&config, mode, programs, chrono::Utc::now().timestamp()));
+
     // Initialize database
     info!("🔧 Initializing database...");
-    let mut conn = Connection::open(&config.db_path)?;
+    // Create database writer (routed across multiple databases if DB_ROUTES is set)
+    let routes = match &config.db_routes {
+        Some(spec) => parse_routes(spec)?,
+        None => Vec::new(),
+    };
+    status.add_sink("sqlite");
+    if !routes.is_empty() {
+        status.add_sink(format!("sqlite_routes ({})", routes.len()));
+    }
+    let db_writer = open_writer(&config, &routes)?;
+    // Append minute snapshots to ClickHouse for long-term history (CLICKHOUSE_URL)
+    let db_writer: Arc<dyn AggregateDbWriter + Send + Sync> = match HistoryConfig::from_env()? {
+        Some(history) => {
+            status.add_sink("clickhouse");
+            Arc::new(ClickHouseHistoryWriter::connect(db_writer, history).await?)
+        }
+        None => db_writer,
+    };
+    // Push written aggregates/signals to WebSocket clients (LIVE_WS_ADDR)
+    let live_ws = LiveWsServer::from_env();
+    let live_feed = live_ws.as_ref().map(|server| LiveFeed::new(server.buffer));
+    let live_ws_addr = live_ws.as_ref().map(|server| server.addr.clone());
+    let db_writer: Arc<dyn AggregateDbWriter + Send + Sync> = match &live_feed {
+        Some(feed) => {
+            status.add_sink("live_ws");
+            Arc::new(LiveFeedWriter::new(db_writer, feed.clone()))
+        }
+        None => db_writer,
+    };
+    info!("✅ Database initialized");
 
-    // Run schema migrations (idempotent)
-    run_schema_migrations(&mut conn, "sql")?;
-    drop(conn); // Close temporary connection
+    // Elect a single writer when several instances share the database
+    let lease: Option<Arc<InstanceLease>> = if config.lease_enabled {
+        let lease = Arc::new(InstanceLease::new(
+            &config.db_path,
+            RUNTIME_LEASE_NAME,
+            &config.instance_id,
+            config.lease_ttl_secs,
+        ));
+        if !lease.try_acquire(chrono::Utc::now().timestamp())? {
+            let holder = lease.current_holder()?.map(|(holder, _)| holder);
+            warn!(
+                "⏸️  Lease '{}' held by {} - running as STANDBY (ingest only)",
+                RUNTIME_LEASE_NAME,
+                holder.as_deref().unwrap_or("another instance")
+            );
+        }
+        lease.clone().spawn_renewal();
+        info!("✅ Instance lease: {} (ttl: {}s)", config.instance_id, config.lease_ttl_secs);
+        Some(lease)
+    } else {
+        warn!("⚠️  Instance lease disabled - do not point another runtime at this database");
+        None
+    };
 
-    // Create database writer
-    let db_writer: Arc<dyn AggregateDbWriter + Send + Sync> =
-        Arc::new(SqliteAggregateWriter::new(&config.db_path)?);
-    info!("✅ Database initialized");
+    // Record the active datasource so consumers can flag reduced-fidelity data
+    if let Ok(runtime_config) = RuntimeConfig::from_env() {
+        let datasource = &runtime_config.datasource;
+        if datasource.is_reduced_fidelity() {
+            warn!("⚠️  Datasource '{}' is REDUCED FIDELITY", datasource);
+        }
+        let datasource_json = serde_json::json!({
+            "kind": datasource.to_string(),
+            "reduced_fidelity": datasource.is_reduced_fidelity(),
+            "notes": datasource.fidelity_notes(),
+        });
+        if let Err(e) = db_writer
+            .write_system_metric("datasource", &datasource_json.to_string())
+            .await
+        {
+            warn!("⚠️  Failed to record datasource metric: {}", e);
+        }
+    }
 
-    // Create PipelineEngine
-    let engine = Arc::new(Mutex::new(PipelineEngine::new()));
-    info!("✅ PipelineEngine created");
+    // Engine options: shards (PIPELINE_SHARDS), snapshot, journal, lease and
+    // the subsystems this environment enables
+    let replay_clock = ReplayClock::new();
+    if let Some(options) = &replay {
+        // Start the clock at the seek position so warm-up is relative to it
+        replay_clock.advance_to(options.from.unwrap_or_default());
+    }
+    let wallet_age_cache = WalletAgeCache::new();
+    let signal_thresholds = SignalThresholdsConfig::load_from_env()?;
+    let audit_log = AuditTradesConfig::from_env().map(|audit_config| {
+        let watched = WatchedMints::new(audit_config.pinned_mints.clone());
+        if audit_config.include_followed {
+            watched.spawn_followed_refresh(config.db_path.clone(), 30);
+        }
+        info!(
+            "🔍 Audit trades enabled ({} pinned mints{}, retention: {}s)",
+            audit_config.pinned_mints.len(),
+            if audit_config.include_followed { " + followed tokens" } else { "" },
+            audit_config.retention_secs
+        );
+        AuditTradeLog::new(watched, audit_config.retention_secs)
+    });
+    let anomaly_capture = AnomalyCapture::from_env();
+    let interest = InterestConfig::from_env().map(|interest_config| {
+        info!(
+            "⭐ Tokens of interest enabled (promote at {} buys / {} SOL / {} wallets per 300s, max {})",
+            interest_config.buy_count_300s,
+            interest_config.net_flow_300s_sol,
+            interest_config.unique_wallets_300s,
+            interest_config.max_tokens
+        );
+        TokensOfInterest::new(interest_config)
+    });
+    let wallet_age_resolver = WalletAgeResolver::from_env();
+    let wallet_config = WalletTrackingConfig::from_env();
+    if let Some(wallet_config) = &wallet_config {
+        info!(
+            "👛 Wallet tracking enabled (smart: ≥{} closed, ≥{:.0}% wins, ≥{} SOL PnL)",
+            wallet_config.min_closed_trades,
+            wallet_config.min_win_rate * 100.0,
+            wallet_config.min_realized_pnl_sol
+        );
+    }
+    let wallet_tracking_enabled = wallet_config.is_some();
+    let mut engine_config = config.clone();
+    // Wallet state follows wallets across tokens, so it can't be split by mint
+    if wallet_tracking_enabled && config.engine_shards > 1 {
+        warn!(
+            "⚠️  Wallet tracking needs a single engine shard - ignoring PIPELINE_SHARDS={}",
+            config.engine_shards
+        );
+        engine_config.engine_shards = 1;
+    }
+    let source = if replay.is_none() && scanner.is_some() {
+        TradeSource::Grpc
+    } else {
+        TradeSource::Manual
+    };
+    let mut engine_options = EngineOptions::new(engine_config, signal_thresholds, source);
+    if let Some(secs) = env::var("MINT_PRUNE_THRESHOLD_SECS").ok().and_then(|s| s.parse().ok()) {
+        engine_options.prune_threshold_secs = secs;
+    }
+    let prune_threshold = engine_options.prune_threshold_secs;
+    let setup_interest = interest.clone();
+    let setup_cache = wallet_age_cache.clone();
+    let fresh_max_age_secs = wallet_age_resolver.as_ref().map(|resolver| resolver.fresh_max_age_secs);
+    let mut engine_options = engine_options
+        .with_writer(db_writer)
+        .with_engine_setup(move |pipeline_engine| {
+            pipeline_engine.set_crash_reporter(crash_reporter.clone());
+            if let Some(interest) = &setup_interest {
+                pipeline_engine.set_interest(interest.clone());
+            }
+            if let Some(fresh_max_age_secs) = fresh_max_age_secs {
+                pipeline_engine.set_wallet_age_cache(setup_cache.clone(), fresh_max_age_secs);
+            }
+            if let Some(wallet_config) = &wallet_config {
+                pipeline_engine.set_wallet_tracker(WalletTracker::new(wallet_config.clone()));
+            }
+        });
+    if replay.is_some() {
+        engine_options = engine_options.with_clock(replay_clock.clone());
+    }
+    if let Some(lease) = &lease {
+        engine_options = engine_options.with_lease(lease.clone());
+    }
+    if let Some(audit_log) = audit_log {
+        engine_options = engine_options.with_audit_log(audit_log);
+    }
+    if let Some(capture) = &anomaly_capture {
+        engine_options = engine_options.with_anomaly_capture(capture.clone());
+    }
+    // The legacy streamers record funding edges into the process-wide graph too
+    if replay.is_none() && source == TradeSource::Manual {
+        engine_options = engine_options.with_wallet_graph(wallet_graph::shared());
+    }
+    // Created before the engine so its shards read the flags it writes
+    let risk_checker = RiskChecker::from_env(config.db_path.clone());
+    if let Some(risk_checker) = &risk_checker {
+        engine_options = engine_options.with_risk_flags(risk_checker.flags_cache());
+    }
 
-    // Create trade event channel
-    let (tx, rx) = mpsc::channel::<TradeEvent>(config.channel_buffer);
-    info!("✅ Trade channel created (buffer: {})", config.channel_buffer);
+    // Snapshot and journal restore live state; a replay starts from scratch
+    let snapshot = SnapshotOptions::from_config(&config).filter(|_| replay.is_none());
+    if let Some(snapshot) = &snapshot {
+        engine_options = engine_options.with_snapshot(snapshot.clone());
+    }
+    match (JournalConfig::from_env(), &replay) {
+        (Some(_), Some(_)) => warn!("⚠️  EVENT_JOURNAL_DIR is ignored in replay mode"),
+        (Some(journal_config), None) => engine_options = engine_options.with_journal(journal_config),
+        (None, _) => {}
+    }
+
+    // Fast path: watched mints skip the batch channel (unified streamer only)
+    match FastPathConfig::from_env() {
+        Some(_) if source != TradeSource::Grpc => {
+            warn!("⚠️  Fast path requires the unified live streamer - disabled");
+        }
+        Some(fast_path_config) => {
+            let mut watched = WatchedMints::new(fast_path_config.pinned_mints.clone());
+            if let Some(interest) = &interest {
+                watched = watched.with_interest(interest.clone());
+            }
+            if fast_path_config.include_followed {
+                watched.spawn_followed_refresh(config.db_path.clone(), fast_path_config.refresh_secs);
+            }
+            info!(
+                "⚡ Fast path watching {} pinned mints{}{}",
+                fast_path_config.pinned_mints.len(),
+                if fast_path_config.include_followed { " + followed tokens" } else { "" },
+                if interest.is_some() { " + tokens of interest" } else { "" }
+            );
+            engine_options = engine_options.with_fast_path(watched, fast_path_config.channel_buffer);
+        }
+        None => {}
+    }
+
+    // Signal notifications (routing rules → sinks), polled by the engine
+    let notify_config = load_notify_config_from_env()?;
+    let notifier_enabled = notify_config.is_some();
+    if let Some(notify_config) = notify_config {
+        let mut notifier = Notifier::new(&config.db_path, notify_config)?;
+        for (name, sink) in &notifier.config().sinks {
+            status.add_sink(format!("notifier:{} ({})", name, sink.target.kind()));
+        }
+        if let Some(reconcile_config) = ReconcileConfig::from_env()? {
+            info!(
+                "🔎 Severity-5 signals reconciled over RPC ({} signatures at {})",
+                reconcile_config.sample_size, reconcile_config.commitment
+            );
+            notifier.set_reconciler(Reconciler::new(reconcile_config)?);
+        }
+        let interval_secs: u64 = env::var("NOTIFY_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(5);
+        info!(
+            "🔔 Notifier enabled ({} sinks, {} rules, {}s interval)",
+            notifier.config().sinks.len(),
+            notifier.config().rules.len(),
+            interval_secs
+        );
+        engine_options = engine_options.with_notifier(notifier, interval_secs);
+    } else {
+        info!("⏭️  Notifier disabled (NOTIFY_ROUTES, DISCORD_WEBHOOK_URLS and TELEGRAM_BOT_TOKEN unset)");
+    }
+
+    // The engine owns the unified streamer; replay and the legacy streamers
+    // feed its sender
+    if let Some(scanner) = scanner {
+        engine_options = engine_options.with_scanner(scanner);
+    }
+
+    let pipeline = Engine::start(engine_options).await?;
+    let db_writer = pipeline.writer();
+    let engine = pipeline.shards().clone();
+    let thresholds_reload_secs: u64 = env::var("SIGNAL_THRESHOLDS_RELOAD_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(10);
+    info!("✅ PipelineEngine created ({} shards)", engine.shard_count());
+    info!(
+        "   ├─ Signal warm-up: {}s after startup, {}s per cold-started token",
+        config.signal_warmup_secs, config.token_warmup_secs
+    );
+    info!("   ├─ BREAKOUT wallet metric: {:?}", config.breakout_wallet_metric);
+    info!(
+        "   ├─ Outlier trades: {:?} (> {} stddev)",
+        config.outlier_policy.mode, config.outlier_policy.max_stddev
+    );
+    info!(
+        "   ├─ Windows: {}",
+        config
+            .windows
+            .secs()
+            .map(|secs| format!("{}s", secs))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    match thresholds_path() {
+        Some(path) => {
+            info!(
+                "   ├─ Signal thresholds: {} (reload every {}s)",
+                path.display(),
+                thresholds_reload_secs
+            );
+            if thresholds_reload_secs > 0 {
+                spawn_thresholds_reload(engine.clone(), path, thresholds_reload_secs);
+            }
+        }
+        None => info!("   ├─ Signal thresholds: built-in defaults (plus SIGNAL_THRESHOLD_* overrides)"),
+    }
+    match &anomaly_capture {
+        Some(capture) => info!(
+            "   ├─ Anomaly capture: severity >= {} for {}s -> {}",
+            capture.min_severity(),
+            capture.duration_secs(),
+            capture.output_dir().display()
+        ),
+        None => info!("   ├─ Anomaly capture: disabled"),
+    }
+    match wallet_age_resolver {
+        Some(resolver) => {
+            info!(
+                "   └─ Wallet ages: enabled (fresh < {}s)",
+                resolver.fresh_max_age_secs
+            );
+            resolver.spawn(wallet_age_cache);
+        }
+        None => info!("   └─ Wallet ages: disabled"),
+    }
+
+    // Slot status: upgrade trade confirmations (final view) and roll back
+    // trades from dead or abandoned slots
+    let slot_status_stream = match RuntimeConfig::from_env() {
+        Ok(runtime_config) if replay.is_none() && slot_status::stream_enabled(&runtime_config) => {
+            let rollback = slot_status::rollback_enabled();
+            info!(
+                "🍴 Slot status stream enabled (ingest: {:?}, rollback: {})",
+                runtime_config.commitment_level, rollback
+            );
+            let (slot_tx, mut slot_rx) = mpsc::channel::<SlotEvent>(256);
+            tokio::spawn(slot_status::run_slot_status_stream(runtime_config, rollback, slot_tx));
+            let engine_slots = engine.clone();
+            tokio::spawn(async move {
+                while let Some(event) = slot_rx.recv().await {
+                    match event {
+                        SlotEvent::RolledBack(slots) => {
+                            let now = engine_slots.now();
+                            engine_slots.rollback_slots(slots, now);
+                        }
+                        SlotEvent::Reached { slot, confirmation } => {
+                            engine_slots.confirm_through_slot(slot, confirmation);
+                        }
+                    }
+                }
+            });
+            Some(rollback)
+        }
+        _ => None,
+    };
+
+    // Mint watcher: on-chain creation time for token_metadata before DexScreener lists the pair
+    if let Ok(runtime_config) = RuntimeConfig::from_env() {
+        if replay.is_none() && mint_watcher::watcher_enabled(&runtime_config) {
+            let (mint_tx, mut mint_rx) = mpsc::channel::<MintCreation>(1024);
+            tokio::spawn(mint_watcher::run_mint_watcher(runtime_config, mint_tx));
+            let db_path = config.db_path.clone();
+            let lease_mints = lease.clone();
+            tokio::spawn(async move {
+                let conn = match rusqlite::Connection::open(&db_path) {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        error!("❌ Mint watcher could not open {}: {}", db_path, e);
+                        return;
+                    }
+                };
+                while let Some(creation) = mint_rx.recv().await {
+                    if lease_mints.as_ref().is_some_and(|l| !l.is_held()) {
+                        continue;
+                    }
+                    if let Err(e) = mint_watcher::record_mint_creation(&conn, &creation) {
+                        warn!("⚠️  Failed to record mint {}: {}", creation.mint, e);
+                    }
+                }
+            });
+            info!("🪙 Mint watcher enabled");
+        }
+    }
+
+    let mut replay_task = None;
 
     // Phase 4.2b: Spawn streamers with pipeline integration
     info!("🚀 Spawning streamers...");
     
-    if config.use_unified_streamer {
-        // UNIFIED MODE: Single streamer with InstructionScanner
-        info!("   Mode: UNIFIED (5 programs via InstructionScanner)");
-        
-        let tx_unified = tx.clone();
-        tokio::spawn(async move {
-            info!("   └─ Starting unified streamer with pipeline connected");
-            
-            use solflow::instruction_scanner::InstructionScanner;
-            use solflow::streamer_core::run_unified;
-            
-            // Initialize scanner
-            let scanner = InstructionScanner::new();
-            
-            // Create streamer config with pipeline channel
-            let streamer_config = StreamerConfig {
-                program_id: "11111111111111111111111111111111".to_string(), // Placeholder (scanner handles filtering)
-                program_name: "Unified".to_string(),
-                output_path: env::var("UNIFIED_OUTPUT_PATH")
-                    .unwrap_or_else(|_| "streams/unified/events.jsonl".to_string()),
-                backend: BackendType::Jsonl, // Ignored (pipeline mode uses channel only)
-                pipeline_tx: Some(tx_unified), // ← CRITICAL: Connect to pipeline
-            };
-            
-            if let Err(e) = run_unified(streamer_config, scanner).await {
-                error!("❌ Unified streamer failed: {}", e);
+    if let Some(options) = replay {
+        // REPLAY MODE: Feed a JSONL capture instead of live streamers
+        info!(
+            "   Mode: REPLAY ({} at {}, from: {:?}, to: {:?})",
+            options.path, options.speed, options.from, options.to
+        );
+
+        let tx_replay = pipeline.sender();
+        replay_task = Some(tokio::spawn(async move {
+            match run_replay(&options, tx_replay, replay_clock).await {
+                Ok(stats) => info!(
+                    "✅ Replay complete: {} replayed, {} out of range, {} malformed ({} read)",
+                    stats.replayed, stats.out_of_range, stats.malformed, stats.read
+                ),
+                Err(e) => error!("❌ Replay failed: {}", e),
             }
-        });
-        
+        }));
+    } else if source == TradeSource::Grpc {
+        // UNIFIED MODE: Single streamer with InstructionScanner, run by the engine
+        info!("   Mode: UNIFIED (5 programs via InstructionScanner)");
         info!("✅ Unified streamer spawned and connected to pipeline");
     } else {
         // LEGACY MODE: 4 separate program streamers
         info!("   Mode: LEGACY (4 separate streamers)");
         info!("   ⚠️  WARNING: Legacy mode is deprecated. Set USE_UNIFIED_STREAMER=true.");
+        if anomaly_capture.is_some() {
+            warn!("   ⚠️  Anomaly capture requires the unified streamer - mints are armed but not captured");
+        }
         
         // Streamer 1: PumpSwap
-        let tx_pump = tx.clone();
+        let tx_pump = pipeline.sender();
         tokio::spawn(async move {
             info!("   ├─ Starting PumpSwap streamer with pipeline connected");
             let streamer_config = StreamerConfig {
@@ -144,7 +762,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         });
         
         // Streamer 2: BonkSwap
-        let tx_bonk = tx.clone();
+        let tx_bonk = pipeline.sender();
         tokio::spawn(async move {
             info!("   ├─ Starting BonkSwap streamer with pipeline connected");
             let streamer_config = StreamerConfig {
@@ -161,7 +779,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         });
         
         // Streamer 3: Moonshot
-        let tx_moon = tx.clone();
+        let tx_moon = pipeline.sender();
         tokio::spawn(async move {
             info!("   ├─ Starting Moonshot streamer with pipeline connected");
             let streamer_config = StreamerConfig {
@@ -178,7 +796,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         });
         
         // Streamer 4: Jupiter DCA
-        let tx_jup = tx.clone();
+        let tx_jup = pipeline.sender();
         tokio::spawn(async move {
             info!("   └─ Starting JupiterDCA streamer with pipeline connected");
             let streamer_config = StreamerConfig {
@@ -200,220 +818,368 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Spawn background tasks
     info!("🚀 Spawning background tasks...");
 
-    // Task 1: Ingestion (processes trades from channel + unified flush loop)
-    let engine_ingestion = engine.clone();
-    let db_writer_ingestion = db_writer.clone();
-    let flush_interval = config.flush_interval_ms;
-    tokio::spawn(async move {
-        start_pipeline_ingestion(rx, engine_ingestion, db_writer_ingestion, flush_interval).await;
-    });
+    // Ingestion, pruning, session rollups, audit trades, engine snapshots
+    // and the notifier run inside the engine
     info!("   ├─ ✅ Ingestion task spawned (includes unified flush loop)");
+    info!("   ├─ ✅ Pruning task spawned (threshold: {}s)", prune_threshold);
+    info!("   ├─ ✅ Session rollup task spawned (interval: 60s)");
+    if let Some(snapshot) = &snapshot {
+        info!("   ├─ ✅ Engine snapshot task spawned (interval: {}s)", snapshot.interval_secs);
+    }
+
+    // Task 2b: Retention (per-table TTLs + incremental vacuum, RETENTION_* env vars)
+    // Replaces the Phase 7 DCA bucket cleanup; the bucket TTLs still come from
+    // DCA_BUCKET_RETENTION_SECS / DCA_ROLLUP_RETENTION_SECS
+    let retention = RetentionManager::new(
+        RetentionConfig::from_env(),
+        backup_sources(&config.db_path, &routes),
+    );
+    let retention_interval = retention.config().interval_secs;
+    let engine_retention = engine.clone();
+    retention.spawn(lease.clone(), move || engine_retention.now());
+    info!("   ├─ ✅ Retention task spawned (interval: {}s)", retention_interval);
 
-    // Task 2: Pruning (removes inactive mints every 60 seconds)
-    let engine_prune = engine.clone();
-    let prune_threshold = env::var("MINT_PRUNE_THRESHOLD_SECS")
+    // Task 2e: Database snapshots (online backup API, DB_BACKUP_DIR)
+    let backup_config = BackupConfig::from_env();
+    if let Some(backup_config) = backup_config.clone() {
+        status.add_sink("backup");
+        let sources = backup_sources(&config.db_path, &routes);
+        let engine_backup = engine.clone();
+        let lease_backup = lease.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_secs(backup_config.interval_secs));
+            loop {
+                interval.tick().await;
+                // Standby instances share the database; one copy is enough
+                if lease_backup.as_ref().is_some_and(|l| !l.is_held()) {
+                    continue;
+                }
+
+                let now = engine_backup.now();
+                let cycle_config = backup_config.clone();
+                let cycle_sources = sources.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    run_backup_cycle(&cycle_config, &cycle_sources, now).map_err(|e| e.to_string())
+                })
+                .await;
+                match result {
+                    Ok(Ok(paths)) => info!("💾 Database snapshot written ({} files)", paths.len()),
+                    Ok(Err(e)) => error!("❌ Database snapshot failed: {}", e),
+                    Err(e) => error!("❌ Database snapshot task panicked: {}", e),
+                }
+            }
+        });
+        info!("   ├─ ✅ Backup task spawned (interval: {}s)", backup_config.interval_secs);
+    }
+
+    // Task 3: Enrichment pipeline (ordered stages, each on its own schedule)
+    let mut enrichment = EnrichmentPipeline::from_env();
+
+    // Stage: metadata/price refresh (tiered by trading activity)
+    let refresh_schedule = RefreshSchedule::from_env();
+    info!(
+        "   ├─ Refresh tiers: active {}s, warm {}s, dormant {}s (max {} requests/cycle)",
+        refresh_schedule.active_secs,
+        refresh_schedule.warm_secs,
+        refresh_schedule.dormant_secs,
+        refresh_schedule.max_requests_per_cycle
+    );
+    let mut metadata_scheduler = MetadataRefreshScheduler::new(config.db_path.clone(), refresh_schedule);
+    if let Some(interest) = &interest {
+        metadata_scheduler = metadata_scheduler.with_interest(interest.clone());
+    }
+    enrichment.push(metadata_scheduler);
+
+    // Stage: rug-pull risk flags (read back by the engine to lower signal severity)
+    if let Some(risk_checker) = risk_checker {
+        enrichment.push(risk_checker);
+    }
+
+    // Stage: holder concentration (feeds the persistence scorer's penalty)
+    if let Some(holders) = HolderConcentration::from_env(config.db_path.clone()) {
+        enrichment.push(holders);
+    }
+
+    // Stage: persistence scoring (Phase 2 - every 60s)
+    enrichment.push(PersistenceScorer::new(config.db_path.clone()));
+
+    let enrichment_stages = enrichment
+        .stages()
+        .iter()
+        .map(|(name, interval)| format!("{} {}s", name, interval.as_secs()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    enrichment.spawn(lease.clone());
+    info!("   ├─ ✅ Enrichment pipeline spawned ({})", enrichment_stages);
+
+    // Task 5: Position marking (live PnL + exit alerts for held tokens)
+    let positions_enabled = env::var("POSITIONS_ENABLED")
         .ok()
         .and_then(|s| s.parse().ok())
-        .unwrap_or(7200); // Default: 2 hours
-    
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
-        loop {
-            interval.tick().await;
-            
-            let now = chrono::Utc::now().timestamp();
-            let mut engine_guard = engine_prune.lock().unwrap();
-            engine_guard.prune_inactive_mints(now, prune_threshold);
-        }
-    });
-    info!("   ├─ ✅ Pruning task spawned (threshold: {}s)", prune_threshold);
+        .unwrap_or(false);
+    if positions_enabled {
+        let tracker = PositionTracker::new(&config.db_path, ExitRules::from_env())?;
+        let refresh_secs: u64 = env::var("POSITION_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(10);
+        let engine_positions = engine.clone();
+        let db_writer_positions = db_writer.clone();
+        let lease_positions = lease.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(refresh_secs));
+
+            loop {
+                interval.tick().await;
+                if lease_positions.as_ref().is_some_and(|l| !l.is_held()) {
+                    continue;
+                }
 
-    // Task 2b: DCA Bucket Cleanup (every 5 minutes, removes buckets older than 2 hours)
-    // Phase 7: DCA Sparkline Foundation
-    let db_writer_cleanup = db_writer.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300));
-        loop {
-            interval.tick().await;
-            
-            // Downcast Arc<dyn AggregateDbWriter> to SqliteAggregateWriter
-            // This is safe because we know we created a SqliteAggregateWriter above
-            if let Some(sqlite_writer) = db_writer_cleanup
-                .as_any()
-                .downcast_ref::<solflow::pipeline::db::SqliteAggregateWriter>()
-            {
-                match sqlite_writer.cleanup_old_dca_buckets() {
-                    Ok(deleted) if deleted > 0 => {
-                        info!("🧹 DCA bucket cleanup: removed {} old buckets", deleted);
+                let positions = match tracker.open_positions() {
+                    Ok(positions) if positions.is_empty() => continue,
+                    Ok(positions) => positions,
+                    Err(e) => {
+                        error!("❌ Failed to load positions: {}", e);
+                        continue;
                     }
+                };
+
+                let (prices, now) = {
+                    let prices: HashMap<String, f64> = positions
+                        .iter()
+                        .filter_map(|p| engine_positions.latest_price_sol(&p.mint).map(|price| (p.mint.clone(), price)))
+                        .collect();
+                    (prices, engine_positions.now())
+                };
+
+                let signals = match tracker.update(&positions, &prices, now) {
+                    Ok(signals) => signals,
                     Err(e) => {
-                        error!("❌ DCA bucket cleanup failed: {}", e);
+                        error!("❌ Position marking failed: {}", e);
+                        continue;
+                    }
+                };
+
+                for signal in signals {
+                    warn!(
+                        "🚪 POSITION_EXIT {} (severity {}, PnL {:.1}%)",
+                        signal.mint,
+                        signal.severity,
+                        signal.score.unwrap_or_default()
+                    );
+                    if let Err(e) = db_writer_positions.write_signal(signal).await {
+                        warn!("⚠️  Failed to write POSITION_EXIT signal: {}", e);
                     }
-                    _ => {} // No buckets deleted, skip log
                 }
-            } else {
-                warn!("⚠️  Cannot downcast db_writer to SqliteAggregateWriter for cleanup");
             }
-        }
-    });
-    info!("   ├─ ✅ DCA bucket cleanup task spawned (interval: 300s)");
+        });
+        info!("   ├─ ✅ Position marking task spawned ({}s interval)", refresh_secs);
+    } else {
+        info!("   ├─ ⏭️  Position marking disabled (POSITIONS_ENABLED=false)");
+    }
 
-    // Task 3: Price Update Task (every 60s with rate limiting)
-    let db_path_price = config.db_path.clone();
-    tokio::spawn(async move {
-        use solflow::pipeline::dexscreener;
-        use rusqlite::Connection;
-        
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
-        
-        loop {
-            interval.tick().await;
-            
-            // Query tokens with follow_price = 1 and check staleness (in separate scope to drop connection)
-            let mints_with_staleness: Vec<(String, i64)> = {
-                let conn = match Connection::open(&db_path_price) {
-                    Ok(c) => c,
+    // Task 6: Paper trading (simulated fills on signal events)
+    let paper_strategies = load_strategies_from_env()?;
+    let paper_trading_enabled = paper_strategies.is_some();
+    if let Some(strategies) = paper_strategies {
+        let mut trader = PaperTrader::new(&config.db_path, strategies)?;
+        let interval_secs: u64 = env::var("PAPER_TRADING_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(5);
+        let strategy_count = trader.strategies().len();
+        let engine_paper = engine.clone();
+        let db_writer_paper = db_writer.clone();
+        let lease_paper = lease.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
+
+            loop {
+                interval.tick().await;
+                if lease_paper.as_ref().is_some_and(|l| !l.is_held()) {
+                    continue;
+                }
+
+                let signals = match trader.poll_signals() {
+                    Ok(signals) => signals,
                     Err(e) => {
-                        error!("❌ Failed to open DB for price update: {}", e);
+                        error!("❌ Paper trading: failed to poll signals: {}", e);
                         continue;
                     }
                 };
-                
-                let mut stmt = match conn.prepare(
-                    "SELECT mint, updated_at FROM token_metadata WHERE follow_price = 1"
-                ) {
-                    Ok(s) => s,
+                let open = match trader.open_trades() {
+                    Ok(open) if signals.is_empty() && open.is_empty() => continue,
+                    Ok(open) => open,
                     Err(e) => {
-                        error!("❌ Failed to prepare price query: {}", e);
+                        error!("❌ Paper trading: failed to load open trades: {}", e);
                         continue;
                     }
                 };
-                
-                match stmt
-                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
-                    .and_then(|rows| rows.collect::<Result<Vec<(String, i64)>, _>>()) 
-                {
-                    Ok(m) => m,
+
+                let (prices, now) = {
+                    let prices: HashMap<String, f64> = PaperTrader::mints_of_interest(&signals, &open)
+                        .into_iter()
+                        .filter_map(|mint| engine_paper.latest_price_sol(&mint).map(|price| (mint, price)))
+                        .collect();
+                    (prices, engine_paper.now())
+                };
+
+                let fills = match trader.apply(&signals, open, &prices, now) {
+                    Ok(fills) => fills,
                     Err(e) => {
-                        error!("❌ Failed to fetch follow_price tokens: {}", e);
+                        error!("❌ Paper trading failed: {}", e);
                         continue;
                     }
-                }
-            }; // Connection dropped here
-            
-            if mints_with_staleness.is_empty() {
-                continue;
-            }
-            
-            // Filter for stale tokens (updated_at older than 120 seconds)
-            let now = chrono::Utc::now().timestamp();
-            let stale_mints: Vec<String> = mints_with_staleness
-                .into_iter()
-                .filter(|(_, updated_at)| (now - updated_at) > 120)
-                .map(|(mint, _)| mint)
-                .collect();
-            
-            let total_tracked = stale_mints.len();
-            if total_tracked == 0 {
-                continue;
-            }
-            
-            info!("🔄 Price update: {} tokens tracked", total_tracked);
-            
-            let mut updated_count = 0;
-            let mut error_count = 0;
-            
-            // Stagger requests: 300-600ms between calls (2-3 req/sec)
-            for mint in stale_mints {
-                // Check if row exists before attempting update
-                let exists = {
-                    let conn = match Connection::open(&db_path_price) {
-                        Ok(c) => c,
-                        Err(e) => {
-                            error!("❌ Failed to open DB for existence check: {}", e);
-                            continue;
-                        }
-                    };
-                    dexscreener::row_exists(&conn, &mint)
                 };
-                
-                if !exists {
-                    // Skip silently - invalid mint or not followed
+                if fills.is_empty() {
                     continue;
                 }
-                
-                // Fetch price data only (no metadata)
-                let price = match dexscreener::fetch_token_price(&mint).await {
-                    Ok(p) => p,
-                    Err(e) => {
-                        warn!("⚠️  Failed to update price for {}: {} (skipping)", mint, e);
-                        error_count += 1;
-                        continue;
+
+                for fill in &fills {
+                    match fill {
+                        PaperFill::Open { strategy, mint, price_sol } => {
+                            info!("📝 [{}] BUY {} @ {:.3e} SOL", strategy, mint, price_sol);
+                        }
+                        PaperFill::Close { strategy, mint, price_sol, reason, pnl_sol } => {
+                            info!(
+                                "📝 [{}] SELL {} @ {:.3e} SOL ({}, PnL {:+.4} SOL)",
+                                strategy, mint, price_sol, reason, pnl_sol
+                            );
+                        }
                     }
-                };
-                
-                // Update database with price only (in separate scope)
-                {
-                    let conn = match Connection::open(&db_path_price) {
-                        Ok(c) => c,
-                        Err(e) => {
-                            error!("❌ Failed to open DB for price update: {}", e);
-                            continue;
+                }
+
+                match trader.summary().map(|summary| serde_json::to_string(&summary)) {
+                    Ok(Ok(json)) => {
+                        if let Err(e) = db_writer_paper.write_system_metric("paper_trading", &json).await {
+                            warn!("⚠️  Failed to write paper trading summary: {}", e);
                         }
-                    };
-                    
-                    if let Err(e) = dexscreener::upsert_price(&conn, &price) {
-                        warn!("⚠️  Failed to write price for {}: {}", mint, e);
-                        error_count += 1;
-                    } else {
-                        updated_count += 1;
                     }
-                } // Connection dropped here
-                
-                // Rate limiting: sleep 300-600ms
-                let sleep_ms = 300 + (rand::random::<u64>() % 300);
-                tokio::time::sleep(tokio::time::Duration::from_millis(sleep_ms)).await;
+                    Ok(Err(e)) => warn!("⚠️  Failed to serialize paper trading summary: {}", e),
+                    Err(e) => warn!("⚠️  Failed to summarize paper trades: {}", e),
+                }
+            }
+        });
+        info!(
+            "   ├─ ✅ Paper trading task spawned ({} strategies, {}s interval)",
+            strategy_count, interval_secs
+        );
+    } else {
+        info!("   ├─ ⏭️  Paper trading disabled (PAPER_STRATEGIES unset)");
+    }
+
+    // Task 7: External signal webhook
+    let signal_webhook = SignalWebhook::from_env();
+    let webhook_addr = signal_webhook.as_ref().map(|w| w.addr.clone());
+    if let Some(webhook) = signal_webhook {
+        let auth = if webhook.has_token() { "bearer token" } else { "NO AUTH" };
+        let db_writer_webhook = db_writer.clone();
+        info!("   ├─ ✅ Signal webhook listening on {} ({})", webhook.addr, auth);
+        tokio::spawn(async move {
+            if let Err(e) = webhook.serve(db_writer_webhook).await {
+                error!("❌ Signal webhook failed: {}", e);
             }
-            
-            if updated_count > 0 || error_count > 0 {
-                info!("📊 Price update cycle complete: {} updated, {} errors", updated_count, error_count);
+        });
+    } else {
+        info!("   ├─ ⏭️  Signal webhook disabled (SIGNAL_WEBHOOK_ADDR unset)");
+    }
+
+    // Task 7b: Live aggregate/signal push over WebSocket
+    if let (Some(server), Some(feed)) = (live_ws, live_feed) {
+        let auth = if server.has_token() { "token" } else { "NO AUTH" };
+        info!("   ├─ ✅ Live WS server on {}/ws ({})", server.addr, auth);
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(feed).await {
+                error!("❌ Live WS server failed: {}", e);
             }
+        });
+    } else {
+        info!("   ├─ ⏭️  Live WS server disabled (LIVE_WS_ADDR unset)");
+    }
+
+    // Task 7c: Read-only HTTP query API over the same database
+    let api_addr = match ApiServer::from_env() {
+        Some(server) => {
+            let server = server.with_status(status.clone());
+            let addr = server.addr.clone();
+            let auth = if server.has_token() { "token" } else { "NO AUTH" };
+            info!("   ├─ ✅ Query API on {} ({})", addr, auth);
+            let db_path_api = config.db_path.clone();
+            tokio::spawn(async move {
+                if let Err(e) = server.serve(db_path_api).await {
+                    error!("❌ Query API failed: {}", e);
+                }
+            });
+            Some(addr)
         }
-    });
-    info!("   ├─ ✅ Price update task spawned (60s interval)");
+        None => {
+            info!("   ├─ ⏭️  Query API disabled (API_ADDR unset)");
+            None
+        }
+    };
 
-    // Task 4: Persistence Scoring Engine (Phase 2 - every 60s)
-    let db_path_scorer = config.db_path.clone();
-    tokio::spawn(async move {
-        use solflow::pipeline::persistence_scorer::PersistenceScorer;
-        
-        let scorer = PersistenceScorer::new(db_path_scorer);
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
-        
-        loop {
-            interval.tick().await;
-            
-            info!("🧮 Running persistence scoring cycle...");
-            
-            match scorer.run_scoring_cycle() {
-                Ok(count) => {
-                    info!("✅ Persistence scoring: updated {} tokens", count);
+    // Task 8b: Telegram bot commands (/block, /unblock → mint_blocklist)
+    if let Some(telegram_config) = TelegramBotConfig::from_env()? {
+        let mut bot = TelegramBot::new(&config.db_path, telegram_config)?;
+        let chat_count = bot.config().chat_ids.len();
+        let lease_bot = lease.clone();
+        tokio::spawn(async move {
+            loop {
+                // Telegram rejects concurrent getUpdates pollers: only the lease holder polls
+                if lease_bot.as_ref().is_some_and(|l| !l.is_held()) {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                    continue;
                 }
-                Err(e) => {
-                    error!("❌ Persistence scoring failed: {}", e);
+                if let Err(e) = bot.poll_once().await {
+                    warn!("⚠️  Telegram bot: {}", e);
+                    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
                 }
             }
-        }
-    });
-    info!("   └─ ✅ Persistence scoring task spawned (60s interval)");
+        });
+        status.add_sink("telegram_bot");
+        info!("   └─ ✅ Telegram bot commands enabled ({} chats)", chat_count);
+    } else {
+        info!("   └─ ⏭️  Telegram bot disabled (TELEGRAM_BOT_TOKEN unset)");
+    }
 
     info!("✅ All background tasks running");
     info!("");
     info!("📊 Pipeline Status:");
     info!("   ├─ Ingestion: READY (unified flush every {}ms)", config.flush_interval_ms);
     info!("   ├─ Pruning: READY (threshold: {}s)", prune_threshold);
-    info!("   ├─ Price Monitoring: READY (60s interval)");
+    info!("   ├─ Metadata Refresh: READY ({}s cycle, tiered)", refresh_interval_secs);
     info!("   ├─ Persistence Scoring: READY (60s interval)");
+    info!("   ├─ Session Rollups: READY (Asia/EU/US, 60s interval)");
+    if positions_enabled {
+        info!("   ├─ Positions: READY (live PnL + exit alerts)");
+    }
+    if paper_trading_enabled {
+        info!("   ├─ Paper Trading: READY (results in paper_trades)");
+    }
+    if let Some(addr) = &webhook_addr {
+        info!("   ├─ Signal Webhook: READY (POST http://{}/signals)", addr);
+    }
+    if let Some(addr) = &live_ws_addr {
+        info!("   ├─ Live WS: READY (ws://{}/ws)", addr);
+    }
+    if let Some(addr) = &api_addr {
+        info!("   ├─ Query API: READY (http://{}/tokens/top)", addr);
+    }
+    if notifier_enabled {
+        info!("   ├─ Notifier: READY (signals routed to sinks)");
+    }
+    if wallet_tracking_enabled {
+        info!("   ├─ Wallet Tracking: READY (SMART_WALLET_ENTRY signals)");
+    }
+    if let Some(backup_config) = &backup_config {
+        info!("   ├─ Backups: READY ({}, every {}s)", backup_config.dir.display(), backup_config.interval_secs);
+    }
+    match slot_status_stream {
+        Some(true) => info!("   ├─ Slot Status: READY (final view + dead/abandoned slots evicted)"),
+        Some(false) => info!("   ├─ Slot Status: READY (final view, rollback disabled)"),
+        None => {}
+    }
     if config.use_unified_streamer {
         info!("   └─ Streamers: 1 unified (PumpFun, PumpSwap, BonkSwap, Moonshot, JupiterDCA)");
     } else {
@@ -433,11 +1199,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
-    // Cleanup: Drop tx to close channel
-    drop(tx);
+    // Coordinated shutdown: the streamer stops ingesting and flushes its
+    // writer, then ingestion drains the channel, does a final flush and the
+    // engine writes its final snapshot
+    if let Some(task) = replay_task {
+        task.abort();
+    }
+    pipeline.shutdown().await;
 
-    // Give tasks time to finish
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    // Hand the lease to a standby instance without waiting for expiry
+    if let Some(lease) = &lease {
+        if let Err(e) = lease.release() {
+            warn!("⚠️  Failed to release instance lease: {}", e);
+        }
+    }
 
     info!("✅ Pipeline runtime stopped");
     Ok(())
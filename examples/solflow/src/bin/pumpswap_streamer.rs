@@ -12,6 +12,7 @@
 
 use solflow::streamer_core::{run, StreamerConfig};
 use solflow::streamer_core::config::BackendType;
+use solflow::streamer_core::{postgres_writer, writer_backend};
 use dotenv;
 
 #[tokio::main]
@@ -25,6 +26,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap_or_else(|_| "/var/lib/solflow/solflow.db".to_string()),
         BackendType::Jsonl => std::env::var("PUMPSWAP_OUTPUT_PATH")
             .unwrap_or_else(|_| "streams/pumpswap/events.jsonl".to_string()),
+        BackendType::Postgres => postgres_writer::database_url_from_env()?,
+        BackendType::Kafka => writer_backend::kafka_brokers_from_env()?,
     };
     
     if backend == BackendType::Sqlite {
@@ -39,5 +42,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         pipeline_tx: None, // Phase 4.2: Set by pipeline_runtime when enabled
     };
 
-    run(config).await
+    run(config).await?;
+    Ok(())
 }
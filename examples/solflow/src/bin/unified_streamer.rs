@@ -28,8 +28,10 @@
 //! - Detects all tracked program interactions
 //! - Provides complete coverage including nested program calls
 
-use solflow::instruction_scanner::InstructionScanner;
+use solflow::streamer_core::program_registry::scanner_from_env;
 use solflow::streamer_core::config::BackendType;
+use solflow::streamer_core::{postgres_writer, writer_backend};
+use solflow::streamer_core::secrets::redact_url;
 use solflow::streamer_core::{run_unified, RuntimeConfig, StreamerConfig};
 use dotenv;
 
@@ -57,7 +59,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     log::info!("   Tracked Programs: 5 (PumpFun, PumpSwap, BonkSwap, Moonshot, Jupiter DCA)");
     log::info!("   gRPC Filter: Multi-program subscription");
     log::info!("   Coverage: Outer + Inner (CPI) instructions");
-    log::info!("   Geyser URL: {}", runtime_config.geyser_url);
+    log::info!("   Geyser URL: {}", redact_url(&runtime_config.geyser_url));
     log::info!("   Commitment: {:?}", runtime_config.commitment_level);
 
     // Parse backend type from command line
@@ -68,16 +70,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap_or_else(|_| "/var/lib/solflow/solflow.db".to_string()),
         BackendType::Jsonl => std::env::var("UNIFIED_OUTPUT_PATH")
             .unwrap_or_else(|_| "streams/unified/events.jsonl".to_string()),
+        BackendType::Postgres => postgres_writer::database_url_from_env()?,
+        BackendType::Kafka => writer_backend::kafka_brokers_from_env()?,
     };
 
-    if backend == BackendType::Sqlite {
-        log::info!("💾 SQLite backend: {}", output_path);
-    } else {
-        log::info!("📝 JSONL backend: {}", output_path);
+    match backend {
+        BackendType::Sqlite => log::info!("💾 SQLite backend: {}", output_path),
+        BackendType::Jsonl => log::info!("📝 JSONL backend: {}", output_path),
+        BackendType::Postgres => log::info!("🐘 Postgres backend: {}", redact_url(&output_path)),
+        BackendType::Kafka => log::info!("📨 Kafka backend: {}", output_path),
     }
 
     // Initialize the instruction scanner
-    let scanner = InstructionScanner::new();
+    let scanner = scanner_from_env()?;
 
     // Create a config with placeholder program_id (validation requires valid base58)
     // The actual program filtering happens in the scanner
@@ -90,5 +95,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     // Run the unified streamer with the scanner
-    run_unified(config, scanner).await
+    run_unified(config, scanner).await?;
+    Ok(())
 }
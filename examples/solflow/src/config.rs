@@ -17,7 +17,9 @@ impl Config {
         let geyser_url = env::var("GEYSER_URL")
             .expect("GEYSER_URL must be set in .env file");
         
-        let x_token = env::var("X_TOKEN").ok();
+        // X_TOKEN may be a file:/keyring: reference (see streamer_core::secrets)
+        let x_token = crate::streamer_core::secrets::secret_from_env("X_TOKEN")
+            .unwrap_or_else(|e| panic!("{}", e));
         
         // Optional program filters (comma-separated list)
         // If not set, processes all transactions (unfiltered baseline)
@@ -0,0 +1,960 @@
+//! Library facade for embedding the pipeline
+//!
+//! `pipeline_runtime` wires a dozen optional subsystems (API, notifier,
+//! fast path, lease, backups, ...) around the core pipeline. Applications
+//! that only want trades in and aggregates/signals out can start that core
+//! in-process with `Engine` instead of shelling out to the binary:
+//!
+//! ```no_run
+//! use solflow::{Engine, EngineEvent, EngineOptions};
+//!
+//! # async fn run() -> Result<(), solflow::error::SolflowError> {
+//! let engine = Engine::start(EngineOptions::from_env()?).await?;
+//! let mut events = engine.subscribe();
+//! while let Ok(event) = events.recv().await {
+//!     if let EngineEvent::Signal(signal) = event {
+//!         println!("{} {}", signal.mint, signal.signal_type.as_str());
+//!     }
+//! }
+//! engine.shutdown().await;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! `Engine` owns the engine shards, the ingestion/flush loop, mint pruning,
+//! session rollups and, with `TradeSource::Grpc`, the unified streamer.
+//! Aggregates and signals are written to the configured SQLite database
+//! exactly as the runtime writes them, then published to subscribers.
+//!
+//! Optional subsystems attach through `EngineOptions::with_*`: engine
+//! snapshots, the event journal, the instance lease, the fast path, audit
+//! trades and the notifier. `pipeline_runtime` is itself a wrapper around
+//! `Engine` that attaches what its environment enables and runs the
+//! services that only read the database (API, enrichment, backups, ...).
+
+use crate::error::{codes, SolflowError};
+use crate::instruction_scanner::InstructionScanner;
+use crate::meta_analysis::AnomalyCapture;
+use crate::pipeline::audit_trades::{AuditTrade, AuditTradeLog};
+use crate::pipeline::db::{run_schema_migrations, AggregateDbWriter, SqliteAggregateWriter};
+use crate::pipeline::fast_path::{start_fast_path, FastPathRoute, WatchedMints};
+use crate::pipeline::ingestion::start_journaled_ingestion_until;
+use crate::pipeline::integrity::{run_integrity_audit, IntegrityConfig};
+use crate::pipeline::journal::{EventJournal, JournalConfig};
+use crate::pipeline::lease::InstanceLease;
+use crate::pipeline::notifier::Notifier;
+use crate::pipeline::replay::ReplayClock;
+use crate::pipeline::risk::RiskFlagsCache;
+use crate::pipeline::routing::{parse_routes, DbRoute, RoutedAggregateWriter};
+use crate::pipeline::schema_check::verify_schema;
+use crate::pipeline::sessions::SessionRollup;
+use crate::pipeline::shards::ShardedEngine;
+use crate::pipeline::shutdown::{join_until, Shutdown, ShutdownSignal};
+use crate::pipeline::signals::TokenSignal;
+use crate::pipeline::snapshot::{read_snapshot, write_snapshot};
+use crate::pipeline::state::RollingMetrics;
+use crate::pipeline::thresholds::SignalThresholdsConfig;
+use crate::pipeline::types::{AggregatedTokenState, TokenMigration, TradeEvent};
+use crate::pipeline::wallet_graph::{self, WalletGraph};
+use crate::pipeline::{PipelineConfig, PipelineEngine};
+use crate::streamer_core::config::{BackendType, RuntimeConfig, StreamerConfig};
+use crate::streamer_core::liquidity::{self, LiquidityUpdate, PoolVault};
+use crate::streamer_core::pool_state::{self, PoolAccount, PoolPriceUpdate};
+use crate::streamer_core::slot_gaps::{self, DataGaps};
+use crate::streamer_core::run_unified_with_capture;
+use async_trait::async_trait;
+use log::{error, info, warn};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+
+/// Events buffered per subscriber before it lags
+const DEFAULT_EVENT_BUFFER: usize = 1024;
+
+/// Where the engine's trades come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeSource {
+    /// The unified gRPC streamer (`GEYSER_URL`, see `streamer_core::config`)
+    Grpc,
+    /// Only trades the application passes to `Engine::ingest`
+    Manual,
+}
+
+/// Applied to every engine shard after the pipeline settings
+pub type EngineSetup = Box<dyn Fn(&mut PipelineEngine) + Send>;
+
+/// Periodic engine state snapshots, restored on start (see `pipeline::snapshot`)
+#[derive(Debug, Clone)]
+pub struct SnapshotOptions {
+    pub path: PathBuf,
+    /// Older snapshots are not restored
+    pub max_age_secs: i64,
+    /// Time between snapshots; a final one is written on shutdown
+    pub interval_secs: u64,
+}
+
+impl SnapshotOptions {
+    /// From `ENGINE_SNAPSHOT_*`; None when `ENGINE_SNAPSHOT_PATH` is unset
+    pub fn from_config(config: &PipelineConfig) -> Option<Self> {
+        config.snapshot_path.as_ref().map(|path| Self {
+            path: PathBuf::from(path),
+            max_age_secs: config.snapshot_max_age_secs,
+            interval_secs: config.snapshot_interval_secs,
+        })
+    }
+}
+
+/// Everything `Engine::start` needs
+pub struct EngineOptions {
+    pub pipeline: PipelineConfig,
+    pub thresholds: SignalThresholdsConfig,
+    pub source: TradeSource,
+    /// Events buffered per subscriber before it lags
+    pub event_buffer: usize,
+    /// Mints without a trade for this long are pruned every minute
+    pub prune_threshold_secs: i64,
+    writer: Option<Arc<dyn AggregateDbWriter + Send + Sync>>,
+    setup: Option<EngineSetup>,
+    clock: Option<ReplayClock>,
+    lease: Option<Arc<InstanceLease>>,
+    snapshot: Option<SnapshotOptions>,
+    journal: Option<JournalConfig>,
+    scanner: Option<InstructionScanner>,
+    anomaly_capture: Option<AnomalyCapture>,
+    fast_path: Option<(WatchedMints, usize)>,
+    audit_log: Option<AuditTradeLog>,
+    notifier: Option<(Notifier, u64)>,
+    wallet_graph: Option<Arc<WalletGraph>>,
+    risk_flags: Option<Arc<RiskFlagsCache>>,
+    data_gaps: Option<Arc<DataGaps>>,
+}
+
+impl EngineOptions {
+    /// Core pipeline only; attach optional subsystems with the `with_*` methods
+    pub fn new(pipeline: PipelineConfig, thresholds: SignalThresholdsConfig, source: TradeSource) -> Self {
+        Self {
+            pipeline,
+            thresholds,
+            source,
+            event_buffer: DEFAULT_EVENT_BUFFER,
+            prune_threshold_secs: 7200,
+            writer: None,
+            setup: None,
+            clock: None,
+            lease: None,
+            snapshot: None,
+            journal: None,
+            scanner: None,
+            anomaly_capture: None,
+            fast_path: None,
+            audit_log: None,
+            notifier: None,
+            wallet_graph: None,
+            risk_flags: None,
+            data_gaps: None,
+        }
+    }
+
+    /// Pipeline settings and thresholds from env, trades from gRPC
+    pub fn from_env() -> Result<Self, SolflowError> {
+        let mut options = Self::new(
+            PipelineConfig::from_env(),
+            SignalThresholdsConfig::load_from_env()
+                .map_err(|e| SolflowError::config(codes::CONFIG_INVALID, e))?,
+            TradeSource::Grpc,
+        );
+        if let Some(secs) = env::var("MINT_PRUNE_THRESHOLD_SECS").ok().and_then(|s| s.parse().ok()) {
+            options.prune_threshold_secs = secs;
+        }
+        Ok(options)
+    }
+
+    /// Write through `writer` instead of opening `pipeline.db_path`
+    ///
+    /// The caller is responsible for migrating the database (see `open_writer`).
+    pub fn with_writer(mut self, writer: Arc<dyn AggregateDbWriter + Send + Sync>) -> Self {
+        self.writer = Some(writer);
+        self
+    }
+
+    /// Customize every engine shard (crash reporter, wallet tracker, ...)
+    pub fn with_engine_setup(mut self, setup: impl Fn(&mut PipelineEngine) + Send + 'static) -> Self {
+        self.setup = Some(Box::new(setup));
+        self
+    }
+
+    /// Follow `clock` instead of wall time (replay)
+    pub fn with_clock(mut self, clock: ReplayClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Only write while `lease` is held; standby instances ingest only
+    pub fn with_lease(mut self, lease: Arc<InstanceLease>) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    pub fn with_snapshot(mut self, snapshot: SnapshotOptions) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
+    /// Journal trades before aggregation and replay unflushed ones on start
+    pub fn with_journal(mut self, journal: JournalConfig) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Programs the unified streamer decodes (default: the built-in registry)
+    pub fn with_scanner(mut self, scanner: InstructionScanner) -> Self {
+        self.scanner = Some(scanner);
+        self
+    }
+
+    /// Arm capture on severe signals; only the unified streamer captures
+    pub fn with_anomaly_capture(mut self, capture: AnomalyCapture) -> Self {
+        self.anomaly_capture = Some(capture);
+        self
+    }
+
+    /// Aggregate `watched` mints per trade (`TradeSource::Grpc` only)
+    pub fn with_fast_path(mut self, watched: WatchedMints, channel_buffer: usize) -> Self {
+        self.fast_path = Some((watched, channel_buffer));
+        self
+    }
+
+    /// Keep raw trades of the log's mints in audit_trades, written every 5s
+    pub fn with_audit_log(mut self, audit_log: AuditTradeLog) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Deliver written signals to the notifier's sinks every `interval_secs`
+    pub fn with_notifier(mut self, notifier: Notifier, interval_secs: u64) -> Self {
+        self.notifier = Some((notifier, interval_secs));
+        self
+    }
+
+    /// Count funding-linked wallets once, reading clusters from `graph`
+    ///
+    /// With `TradeSource::Grpc` the default is the process-wide graph the
+    /// unified streamer records funding into.
+    pub fn with_wallet_graph(mut self, graph: Arc<WalletGraph>) -> Self {
+        self.wallet_graph = Some(graph);
+        self
+    }
+
+    /// Downgrade signals of mints flagged in `cache` (see `RiskChecker::flags_cache`)
+    pub fn with_risk_flags(mut self, cache: Arc<RiskFlagsCache>) -> Self {
+        self.risk_flags = Some(cache);
+        self
+    }
+
+    /// Mark aggregates whose windows cover a gap in `gaps`
+    ///
+    /// With `TradeSource::Grpc` the default is the process-wide registry the
+    /// unified streamer records slot gaps into.
+    pub fn with_data_gaps(mut self, gaps: Arc<DataGaps>) -> Self {
+        self.data_gaps = Some(gaps);
+        self
+    }
+}
+
+impl fmt::Debug for EngineOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EngineOptions")
+            .field("pipeline", &self.pipeline)
+            .field("thresholds", &self.thresholds)
+            .field("source", &self.source)
+            .field("event_buffer", &self.event_buffer)
+            .field("prune_threshold_secs", &self.prune_threshold_secs)
+            .field("snapshot", &self.snapshot)
+            .finish_non_exhaustive()
+    }
+}
+
+/// What subscribers receive, after it was written
+#[derive(Debug, Clone)]
+pub enum EngineEvent {
+    Aggregate(AggregatedTokenState),
+    /// Deduplicated by the engine and blocklist-checked by the writer
+    Signal(TokenSignal),
+}
+
+/// `AggregateDbWriter` that publishes what the inner writer accepted
+struct EventWriter {
+    inner: Arc<dyn AggregateDbWriter + Send + Sync>,
+    events: broadcast::Sender<EngineEvent>,
+}
+
+#[async_trait]
+impl AggregateDbWriter for EventWriter {
+    async fn write_aggregates(
+        &self,
+        aggregates: Vec<AggregatedTokenState>,
+    ) -> Result<(), SolflowError> {
+        let published = (self.events.receiver_count() > 0).then(|| aggregates.clone());
+        self.inner.write_aggregates(aggregates).await?;
+        for aggregate in published.into_iter().flatten() {
+            // Err only means nobody is subscribed
+            let _ = self.events.send(EngineEvent::Aggregate(aggregate));
+        }
+        Ok(())
+    }
+
+    async fn write_signal(&self, signal: TokenSignal) -> Result<(), SolflowError> {
+        let published = (self.events.receiver_count() > 0).then(|| signal.clone());
+        self.inner.write_signal(signal).await?;
+        if let Some(signal) = published {
+            let _ = self.events.send(EngineEvent::Signal(signal));
+        }
+        Ok(())
+    }
+
+    async fn write_signals(&self, signals: Vec<TokenSignal>) -> Result<Vec<TokenSignal>, SolflowError> {
+        let written = self.inner.write_signals(signals).await?;
+        if self.events.receiver_count() > 0 {
+            for signal in &written {
+                let _ = self.events.send(EngineEvent::Signal(signal.clone()));
+            }
+        }
+        Ok(written)
+    }
+
+    async fn write_system_metric(
+        &self,
+        key: &str,
+        value_json: &str,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_system_metric(key, value_json).await
+    }
+
+    async fn write_session_rollups(
+        &self,
+        rollups: Vec<SessionRollup>,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_session_rollups(rollups).await
+    }
+
+    async fn write_audit_trades(
+        &self,
+        trades: Vec<AuditTrade>,
+        prune_before: i64,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_audit_trades(trades, prune_before).await
+    }
+
+    async fn compact_signal_details(
+        &self,
+        before: i64,
+        limit: usize,
+    ) -> Result<usize, SolflowError> {
+        self.inner.compact_signal_details(before, limit).await
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+}
+
+/// Migrate, verify and audit the database, then open its writer
+///
+/// With `routes` (`DB_ROUTES`) the writer spreads tables across several
+/// databases, each audited once it exists.
+pub fn open_writer(
+    config: &PipelineConfig,
+    routes: &[DbRoute],
+) -> Result<Arc<dyn AggregateDbWriter + Send + Sync>, SolflowError> {
+    let mut conn = Connection::open(&config.db_path)?;
+
+    // Run schema migrations (idempotent)
+    run_schema_migrations(&mut conn, "sql")?;
+    // Fail fast with the full diff instead of a "no such column" mid-flush
+    verify_schema(&conn)?;
+    // Report (or quarantine) rows a crashed flush left corrupt
+    let integrity = IntegrityConfig::from_env();
+    run_integrity_audit(&mut conn, &integrity, chrono::Utc::now().timestamp())?;
+    drop(conn); // Close temporary connection
+
+    if routes.is_empty() {
+        return Ok(Arc::new(SqliteAggregateWriter::new(&config.db_path)?));
+    }
+
+    info!("🔀 DB routing enabled ({} routes)", routes.len());
+    let writer = RoutedAggregateWriter::new(&config.db_path, routes.to_vec(), "sql")?;
+    // Routed databases are migrated by the writer; audit them once they exist
+    let mut audited = vec![config.db_path.clone()];
+    for route in routes {
+        if !audited.contains(&route.db_path) {
+            let mut route_conn = Connection::open(&route.db_path)?;
+            run_integrity_audit(&mut route_conn, &integrity, chrono::Utc::now().timestamp())?;
+            audited.push(route.db_path.clone());
+        }
+    }
+    Ok(Arc::new(writer))
+}
+
+/// Spawn the unified streamer feeding `tx`
+///
+/// pump.fun curve migrations bypass the trade channel and go straight to
+/// their engine shard, as do pool liquidity updates when
+/// `LIQUIDITY_TRACKING_ENABLED` is set (see `streamer_core::liquidity`) and
+/// on-chain pool prices when `POOL_STATE_ENABLED` is set (see
+/// `streamer_core::pool_state`).
+pub fn spawn_unified_streamer(
+    engine: Arc<ShardedEngine>,
+    tx: mpsc::Sender<TradeEvent>,
+    scanner: InstructionScanner,
+    anomaly_capture: Option<AnomalyCapture>,
+    fast_path: Option<FastPathRoute>,
+    shutdown: ShutdownSignal,
+) -> JoinHandle<()> {
+    let (migration_tx, mut migration_rx) = mpsc::channel::<TokenMigration>(256);
+    let engine_migrations = engine.clone();
+    tokio::spawn(async move {
+        while let Some(migration) = migration_rx.recv().await {
+            engine_migrations.process_migration(migration);
+        }
+    });
+
+    let runtime_config = RuntimeConfig::from_env().ok();
+
+    let vault_tx = match runtime_config.clone() {
+        Some(runtime_config) if liquidity::tracking_enabled(&runtime_config) => {
+            let (vault_tx, vault_rx) = mpsc::channel::<PoolVault>(1024);
+            let (liquidity_tx, mut liquidity_rx) = mpsc::channel::<LiquidityUpdate>(1024);
+            tokio::spawn(liquidity::run_liquidity_stream(runtime_config, vault_rx, liquidity_tx));
+            let engine = engine.clone();
+            tokio::spawn(async move {
+                while let Some(update) = liquidity_rx.recv().await {
+                    engine.process_liquidity(&update.mint, update.liquidity_sol);
+                }
+            });
+            info!("💧 Liquidity tracking enabled");
+            Some(vault_tx)
+        }
+        _ => None,
+    };
+
+    let pool_tx = match runtime_config {
+        Some(runtime_config) if pool_state::tracking_enabled(&runtime_config) => {
+            let (pool_tx, pool_rx) = mpsc::channel::<PoolAccount>(1024);
+            let (price_tx, mut price_rx) = mpsc::channel::<PoolPriceUpdate>(1024);
+            tokio::spawn(pool_state::run_pool_state_stream(runtime_config, pool_rx, price_tx));
+            tokio::spawn(async move {
+                while let Some(update) = price_rx.recv().await {
+                    engine.process_pool_price(&update.mint, update.price_sol);
+                }
+            });
+            info!("📈 Pool state tracking enabled");
+            Some(pool_tx)
+        }
+        _ => None,
+    };
+
+    tokio::spawn(async move {
+        info!("   └─ Starting unified streamer with pipeline connected");
+
+        // Create streamer config with pipeline channel
+        let streamer_config = StreamerConfig {
+            program_id: "11111111111111111111111111111111".to_string(), // Placeholder (scanner handles filtering)
+            program_name: "Unified".to_string(),
+            output_path: env::var("UNIFIED_OUTPUT_PATH")
+                .unwrap_or_else(|_| "streams/unified/events.jsonl".to_string()),
+            backend: BackendType::Jsonl, // Ignored (pipeline mode uses channel only)
+            pipeline_tx: Some(tx), // ← CRITICAL: Connect to pipeline
+        };
+
+        if let Err(e) = run_unified_with_capture(
+            streamer_config,
+            scanner,
+            anomaly_capture,
+            fast_path,
+            Some(shutdown),
+            Some(migration_tx),
+            vault_tx,
+            pool_tx,
+        )
+        .await
+        {
+            error!("❌ Unified streamer failed: {}", e);
+        }
+    })
+}
+
+/// The core pipeline running in-process
+///
+/// Dropping it without `shutdown` abandons the tasks' final flush.
+pub struct Engine {
+    engine: Arc<ShardedEngine>,
+    db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
+    trade_tx: mpsc::Sender<TradeEvent>,
+    events: broadcast::Sender<EngineEvent>,
+    shutdown: Shutdown,
+    ingestion: JoinHandle<()>,
+    streamer: Option<JoinHandle<()>>,
+    /// Interval tasks, aborted on shutdown
+    tasks: Vec<JoinHandle<()>>,
+    snapshot: Option<SnapshotOptions>,
+}
+
+impl Engine {
+    /// Open the database, create the engine shards and start ingesting
+    ///
+    /// With a snapshot attached the shards resume from it, then the journal
+    /// re-feeds the trades received after it.
+    pub async fn start(options: EngineOptions) -> Result<Self, SolflowError> {
+        let config = &options.pipeline;
+        let (events, _) = broadcast::channel(options.event_buffer.max(1));
+        let inner = match options.writer {
+            Some(writer) => writer,
+            None => {
+                let routes = match &config.db_routes {
+                    Some(spec) => parse_routes(spec).map_err(|e| SolflowError::config(codes::CONFIG_INVALID, e))?,
+                    None => Vec::new(),
+                };
+                open_writer(config, &routes)?
+            }
+        };
+        let db_writer: Arc<dyn AggregateDbWriter + Send + Sync> = Arc::new(EventWriter {
+            inner,
+            events: events.clone(),
+        });
+
+        let graph = options
+            .wallet_graph
+            .clone()
+            .or_else(|| (options.source == TradeSource::Grpc).then(wallet_graph::shared));
+        let data_gaps = options
+            .data_gaps
+            .clone()
+            .or_else(|| (options.source == TradeSource::Grpc).then(slot_gaps::shared));
+        let engine = Arc::new(ShardedEngine::new(config.engine_shards, |_| {
+            let mut pipeline_engine = match &options.clock {
+                Some(clock) => PipelineEngine::new_with_timestamp_fn(clock.now_fn()),
+                None => PipelineEngine::new(),
+            };
+            pipeline_engine.set_signal_warmup(config.signal_warmup_secs, config.token_warmup_secs);
+            pipeline_engine.set_breakout_wallet_metric(config.breakout_wallet_metric);
+            pipeline_engine.set_outlier_policy(config.outlier_policy);
+            pipeline_engine.set_window_set(config.windows.clone());
+            pipeline_engine.set_fee_models(config.fee_models.clone());
+            pipeline_engine.set_signal_thresholds(options.thresholds.clone());
+            if let Some(audit_log) = &options.audit_log {
+                pipeline_engine.set_audit_log(audit_log.clone());
+            }
+            if let Some(capture) = &options.anomaly_capture {
+                pipeline_engine.set_anomaly_capture(capture.clone());
+            }
+            if let Some(graph) = &graph {
+                pipeline_engine.set_wallet_graph(graph.clone());
+            }
+            if let Some(cache) = &options.risk_flags {
+                pipeline_engine.set_risk_flags(cache.clone());
+            }
+            if let Some(gaps) = &data_gaps {
+                pipeline_engine.set_data_gaps(gaps.clone());
+            }
+            if let Some(setup) = &options.setup {
+                setup(&mut pipeline_engine);
+            }
+            pipeline_engine
+        }));
+        info!("✅ Embedded engine created ({} shards)", engine.shard_count());
+
+        // Restore rolling windows and signal state from the last snapshot
+        let mut restored_trades = HashSet::new();
+        if let Some(snapshot) = &options.snapshot {
+            let now = engine.now();
+            match read_snapshot(&snapshot.path, now, snapshot.max_age_secs) {
+                Ok(Some(snapshot)) => {
+                    let age = now - snapshot.taken_at;
+                    restored_trades = snapshot.trade_keys();
+                    let restored = engine.restore(snapshot, now);
+                    info!("♻️  Restored {} mints from engine snapshot ({}s old)", restored, age);
+                }
+                Ok(None) => info!("♻️  No engine snapshot to restore at {}", snapshot.path.display()),
+                Err(e) => warn!("⚠️  Engine snapshot not restored: {}", e),
+            }
+        }
+
+        // Re-feed trades received after the last flush before a crash, except
+        // those the snapshot already restored
+        let journal = options.journal.and_then(|journal_config| {
+            let dir = journal_config.dir.clone();
+            match EventJournal::open(journal_config) {
+                Ok(journal) => {
+                    let mut skipped = 0;
+                    let replay = journal.replay(|trade| {
+                        if restored_trades.contains(&(trade.signature.clone(), trade.trade_index)) {
+                            skipped += 1;
+                        } else {
+                            engine.process_trade(trade);
+                        }
+                    });
+                    match replay {
+                        Ok(replayed) => info!(
+                            "📼 Event journal: {} (replayed {} unflushed trades, {} already in snapshot)",
+                            dir.display(),
+                            replayed - skipped,
+                            skipped
+                        ),
+                        Err(e) => warn!("⚠️  Event journal replay failed: {}", e),
+                    }
+                    Some(journal)
+                }
+                Err(e) => {
+                    warn!("⚠️  Event journal disabled: {}", e);
+                    None
+                }
+            }
+        });
+
+        // Fast path: watched mints skip the batch channel (unified streamer only)
+        let fast_path = match (options.fast_path, options.source) {
+            (Some((watched, channel_buffer)), TradeSource::Grpc) => {
+                let (fast_tx, fast_rx) = mpsc::channel::<TradeEvent>(channel_buffer);
+                tokio::spawn(start_fast_path(
+                    fast_rx,
+                    engine.clone(),
+                    db_writer.clone(),
+                    options.lease.clone(),
+                ));
+                info!("⚡ Fast path enabled (buffer: {})", channel_buffer);
+                Some(FastPathRoute::new(watched, fast_tx))
+            }
+            (Some(_), TradeSource::Manual) => {
+                warn!("⚠️  Fast path requires the unified streamer - disabled");
+                None
+            }
+            (None, _) => None,
+        };
+
+        let shutdown = Shutdown::from_env();
+        let (trade_tx, rx) = mpsc::channel::<TradeEvent>(config.channel_buffer);
+        let streamer = match options.source {
+            TradeSource::Grpc => Some(spawn_unified_streamer(
+                engine.clone(),
+                trade_tx.clone(),
+                options.scanner.unwrap_or_default(),
+                options.anomaly_capture,
+                fast_path,
+                shutdown.signal(),
+            )),
+            TradeSource::Manual => None,
+        };
+        let ingestion = tokio::spawn(start_journaled_ingestion_until(
+            rx,
+            engine.clone(),
+            db_writer.clone(),
+            config.flush_interval_ms,
+            options.lease.clone(),
+            Some(shutdown.signal()),
+            journal,
+        ));
+
+        let mut tasks = Vec::new();
+
+        // Pruning (removes inactive mints every 60 seconds, by engine time so
+        // a replay of an old capture keeps its mints)
+        let engine_prune = engine.clone();
+        let prune_threshold = options.prune_threshold_secs;
+        tasks.push(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                engine_prune.prune_inactive_mints(engine_prune.now(), prune_threshold);
+            }
+        }));
+
+        // Trading-session rollups (Asia/EU/US totals, every 60s)
+        let engine_sessions = engine.clone();
+        let db_writer_sessions = db_writer.clone();
+        let lease_sessions = options.lease.clone();
+        tasks.push(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+
+                // Drain even without the lease so standby instances don't accumulate
+                let rollups = engine_sessions.drain_session_rollups(engine_sessions.now());
+                if rollups.is_empty() || lease_sessions.as_ref().is_some_and(|l| !l.is_held()) {
+                    continue;
+                }
+
+                if let Err(e) = db_writer_sessions.write_session_rollups(rollups).await {
+                    error!("❌ Session rollup write failed: {}", e);
+                }
+            }
+        }));
+
+        // Audit trades (raw trades for watched mints, every 5s)
+        if let Some(audit_log) = options.audit_log {
+            let engine_audit = engine.clone();
+            let db_writer_audit = db_writer.clone();
+            let lease_audit = options.lease.clone();
+            tasks.push(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(5));
+                loop {
+                    interval.tick().await;
+
+                    // Drain even without the lease so standby instances don't accumulate
+                    let trades = audit_log.drain();
+                    if lease_audit.as_ref().is_some_and(|l| !l.is_held()) {
+                        continue;
+                    }
+
+                    let prune_before = engine_audit.now() - audit_log.retention_secs();
+                    if let Err(e) = db_writer_audit.write_audit_trades(trades, prune_before).await {
+                        error!("❌ Audit trade write failed: {}", e);
+                    }
+                }
+            }));
+        }
+
+        // Engine state snapshots
+        if let Some(snapshot) = &options.snapshot {
+            let engine_snapshot = engine.clone();
+            let path = snapshot.path.clone();
+            let interval_secs = snapshot.interval_secs;
+            tasks.push(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+                interval.tick().await; // Nothing worth saving yet
+                loop {
+                    interval.tick().await;
+                    let snapshot = engine_snapshot.snapshot(engine_snapshot.now());
+                    let path = path.clone();
+                    match tokio::task::spawn_blocking(move || write_snapshot(&path, &snapshot)).await {
+                        Ok(Ok(())) => {}
+                        Ok(Err(e)) => error!("❌ Engine snapshot failed: {}", e),
+                        Err(e) => error!("❌ Engine snapshot task panicked: {}", e),
+                    }
+                }
+            }));
+        }
+
+        // Signal notifications (routing rules → sinks)
+        if let Some((mut notifier, interval_secs)) = options.notifier {
+            let lease_notify = options.lease.clone();
+            tasks.push(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+                loop {
+                    interval.tick().await;
+                    if lease_notify.as_ref().is_some_and(|l| !l.is_held()) {
+                        continue;
+                    }
+
+                    let signals = match notifier.poll_signals() {
+                        Ok(signals) => signals,
+                        Err(e) => {
+                            error!("❌ Notifier: failed to poll signals: {}", e);
+                            continue;
+                        }
+                    };
+                    // Runs even without new signals so pending digests go out on time
+                    let now = chrono::Utc::now().timestamp();
+                    for (sink, e) in notifier.dispatch(&signals, now).await {
+                        warn!("⚠️  Notifier: delivery to {} failed: {}", sink, e);
+                    }
+                }
+            }));
+        }
+
+        Ok(Self {
+            engine,
+            db_writer,
+            trade_tx,
+            events,
+            shutdown,
+            ingestion,
+            streamer,
+            tasks,
+            snapshot: options.snapshot,
+        })
+    }
+
+    /// Aggregates and signals as they are written
+    ///
+    /// Subscribers that fall more than `event_buffer` events behind get
+    /// `RecvError::Lagged` and continue from the newest events.
+    pub fn subscribe(&self) -> broadcast::Receiver<EngineEvent> {
+        self.events.subscribe()
+    }
+
+    /// Queue a trade for the engine (any `TradeSource`)
+    pub async fn ingest(&self, trade: TradeEvent) -> Result<(), SolflowError> {
+        self.trade_tx
+            .send(trade)
+            .await
+            .map_err(|_| SolflowError::datasource(codes::DATASOURCE_STREAM, "engine ingestion has stopped"))
+    }
+
+    /// A sender for trade producers the engine doesn't own (replay, legacy streamers)
+    ///
+    /// Ingestion stops at `shutdown` even while clones are still alive.
+    pub fn sender(&self) -> mpsc::Sender<TradeEvent> {
+        self.trade_tx.clone()
+    }
+
+    /// The engine's writer; signals written through it reach subscribers too
+    pub fn writer(&self) -> Arc<dyn AggregateDbWriter + Send + Sync> {
+        self.db_writer.clone()
+    }
+
+    /// Current rolling metrics for a mint, None if it isn't tracked
+    pub fn rolling_metrics(&self, mint: &str) -> Option<RollingMetrics> {
+        self.engine.rolling_metrics(mint)
+    }
+
+    /// Latest trade-derived price for a mint, in SOL per token
+    pub fn latest_price_sol(&self, mint: &str) -> Option<f64> {
+        self.engine.latest_price_sol(mint)
+    }
+
+    /// Mints the engine holds rolling state for
+    pub fn active_mints(&self) -> Vec<String> {
+        self.engine.active_mints()
+    }
+
+    /// The engine shards, for anything the facade doesn't cover
+    pub fn shards(&self) -> &Arc<ShardedEngine> {
+        &self.engine
+    }
+
+    /// Stop the streamer, drain queued trades and write a final flush
+    ///
+    /// Waits at most `SHUTDOWN_GRACE_SECS` (see `pipeline::shutdown`), then
+    /// writes the final engine snapshot if one is attached.
+    pub async fn shutdown(self) {
+        let deadline = self.shutdown.trigger();
+        info!("⏳ Waiting up to {}s for in-flight work", self.shutdown.grace_period().as_secs());
+        for task in &self.tasks {
+            task.abort();
+        }
+        if let Some(streamer) = self.streamer {
+            join_until("Unified streamer", streamer, deadline).await;
+        }
+        drop(self.trade_tx);
+        join_until("Ingestion", self.ingestion, deadline).await;
+
+        // Final engine snapshot so the next start resumes from here
+        if let Some(options) = &self.snapshot {
+            let snapshot = self.engine.snapshot(self.engine.now());
+            let mints = snapshot.mints.len();
+            match write_snapshot(&options.path, &snapshot) {
+                Ok(()) => info!("💾 Engine snapshot written ({} mints)", mints),
+                Err(e) => error!("❌ Final engine snapshot failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_manual_engine_publishes_aggregates() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut pipeline = PipelineConfig::from_env();
+        pipeline.db_path = dir.path().join("embedded.db").to_string_lossy().into_owned();
+        pipeline.db_routes = None;
+        pipeline.flush_interval_ms = 50;
+        let mut options = EngineOptions::new(pipeline, SignalThresholdsConfig::default(), TradeSource::Manual);
+        options.event_buffer = 16;
+        let engine = Engine::start(options).await.unwrap();
+        let mut events = engine.subscribe();
+
+        engine
+            .ingest(TradeEvent::test_buy("embedded_mint", chrono::Utc::now().timestamp()))
+            .await
+            .unwrap();
+
+        let event = tokio::time::timeout(std::time::Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(event, EngineEvent::Aggregate(aggregate) if aggregate.mint == "embedded_mint"));
+        assert!(engine.active_mints().contains(&"embedded_mint".to_string()));
+        assert_eq!(engine.rolling_metrics("embedded_mint").unwrap().buy_count_60s, 1);
+
+        engine.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_replayed_mints_survive_pruning() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut pipeline = PipelineConfig::from_env();
+        pipeline.db_path = dir.path().join("embedded.db").to_string_lossy().into_owned();
+        pipeline.db_routes = None;
+        pipeline.flush_interval_ms = 50;
+        // A capture from three hours ago, past the default prune threshold
+        let replayed_at = chrono::Utc::now().timestamp() - 3 * 3600;
+        let clock = ReplayClock::new();
+        clock.advance_to(replayed_at);
+        let options = EngineOptions::new(pipeline, SignalThresholdsConfig::default(), TradeSource::Manual)
+            .with_clock(clock);
+        let prune_threshold = options.prune_threshold_secs;
+        let engine = Engine::start(options).await.unwrap();
+        let mut events = engine.subscribe();
+
+        engine
+            .ingest(TradeEvent::test_buy("replayed_mint", replayed_at))
+            .await
+            .unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+
+        // What the prune task does every minute
+        let shards = engine.shards();
+        shards.prune_inactive_mints(shards.now(), prune_threshold);
+        assert!(engine.active_mints().contains(&"replayed_mint".to_string()));
+        // By wall time the mint is long idle
+        shards.prune_inactive_mints(chrono::Utc::now().timestamp(), prune_threshold);
+        assert!(engine.active_mints().is_empty());
+
+        engine.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restores_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut pipeline = PipelineConfig::from_env();
+        pipeline.db_path = dir.path().join("embedded.db").to_string_lossy().into_owned();
+        pipeline.db_routes = None;
+        pipeline.flush_interval_ms = 50;
+        let snapshot = SnapshotOptions {
+            path: dir.path().join("engine.snapshot"),
+            max_age_secs: 3600,
+            interval_secs: 3600,
+        };
+        let options = |pipeline: PipelineConfig| {
+            EngineOptions::new(pipeline, SignalThresholdsConfig::default(), TradeSource::Manual)
+                .with_snapshot(snapshot.clone())
+        };
+
+        let engine = Engine::start(options(pipeline.clone())).await.unwrap();
+        engine
+            .ingest(TradeEvent {
+                signature: "sig".to_string(),
+                ..TradeEvent::test_buy("restored_mint", chrono::Utc::now().timestamp())
+            })
+            .await
+            .unwrap();
+        engine.shutdown().await;
+        assert!(snapshot.path.exists());
+
+        let engine = Engine::start(options(pipeline)).await.unwrap();
+        assert_eq!(engine.rolling_metrics("restored_mint").unwrap().buy_count_60s, 1);
+        engine.shutdown().await;
+    }
+}
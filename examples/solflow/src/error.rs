@@ -0,0 +1,251 @@
+//! Unified error type for the streamer, pipeline and database layers
+//!
+//! Every `SolflowError` belongs to one category and carries a stable
+//! `category.reason` code (see `codes`), so callers can decide what to do
+//! without matching on message text:
+//!
+//! - `Datasource`: gRPC/RPC connection and stream failures (reconnect)
+//! - `Db`: SQLite/Postgres/file writes and reads (retry when busy, alert otherwise)
+//! - `Enrichment`: external APIs such as DexScreener (back off, never fatal)
+//! - `Config`: bad or missing settings (fail fast, retrying won't help)
+//!
+//! Errors from the lower-level types (`rusqlite`, `reqwest`, `ConfigError`,
+//! `ClientError`, `WriterError`, ...) convert with `?`.
+
+use crate::streamer_core::config::ConfigError;
+use crate::streamer_core::error_handler::MaxRetriesExceeded;
+use crate::streamer_core::grpc_client::ClientError;
+use crate::streamer_core::writer_backend::WriterError;
+use std::fmt;
+
+/// Stable error codes, `<category>.<reason>`
+pub mod codes {
+    pub const DATASOURCE_CONNECT: &str = "datasource.connect";
+    pub const DATASOURCE_STREAM: &str = "datasource.stream";
+    pub const DATASOURCE_RETRIES_EXHAUSTED: &str = "datasource.retries_exhausted";
+    pub const DATASOURCE_RPC: &str = "datasource.rpc";
+    pub const DATASOURCE_DECODE: &str = "datasource.decode";
+    pub const DATASOURCE_UNKNOWN_MINT: &str = "datasource.unknown_mint";
+
+    pub const DB_SQLITE: &str = "db.sqlite";
+    pub const DB_BUSY: &str = "db.busy";
+    pub const DB_IO: &str = "db.io";
+    pub const DB_WRITER: &str = "db.writer";
+    pub const DB_SCHEMA: &str = "db.schema";
+    pub const DB_INVALID_DATA: &str = "db.invalid_data";
+    pub const DB_MINT_BLOCKED: &str = "db.mint_blocked";
+
+    pub const ENRICHMENT_HTTP: &str = "enrichment.http";
+    pub const ENRICHMENT_TIMEOUT: &str = "enrichment.timeout";
+    pub const ENRICHMENT_RATE_LIMITED: &str = "enrichment.rate_limited";
+    pub const ENRICHMENT_RESPONSE: &str = "enrichment.response";
+
+    pub const CONFIG_MISSING: &str = "config.missing";
+    pub const CONFIG_INVALID: &str = "config.invalid";
+}
+
+/// Top-level error category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    Datasource,
+    Db,
+    Enrichment,
+    Config,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::Datasource => "datasource",
+            ErrorCategory::Db => "db",
+            ErrorCategory::Enrichment => "enrichment",
+            ErrorCategory::Config => "config",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SolflowError {
+    Datasource { code: &'static str, message: String },
+    Db { code: &'static str, message: String },
+    Enrichment { code: &'static str, message: String },
+    Config { code: &'static str, message: String },
+}
+
+impl SolflowError {
+    pub fn datasource(code: &'static str, message: impl fmt::Display) -> Self {
+        SolflowError::Datasource { code, message: message.to_string() }
+    }
+
+    pub fn db(code: &'static str, message: impl fmt::Display) -> Self {
+        SolflowError::Db { code, message: message.to_string() }
+    }
+
+    pub fn enrichment(code: &'static str, message: impl fmt::Display) -> Self {
+        SolflowError::Enrichment { code, message: message.to_string() }
+    }
+
+    pub fn config(code: &'static str, message: impl fmt::Display) -> Self {
+        SolflowError::Config { code, message: message.to_string() }
+    }
+
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            SolflowError::Datasource { .. } => ErrorCategory::Datasource,
+            SolflowError::Db { .. } => ErrorCategory::Db,
+            SolflowError::Enrichment { .. } => ErrorCategory::Enrichment,
+            SolflowError::Config { .. } => ErrorCategory::Config,
+        }
+    }
+
+    /// Stable `category.reason` code, e.g. `db.busy`
+    pub fn code(&self) -> &'static str {
+        match self {
+            SolflowError::Datasource { code, .. }
+            | SolflowError::Db { code, .. }
+            | SolflowError::Enrichment { code, .. }
+            | SolflowError::Config { code, .. } => code,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            SolflowError::Datasource { message, .. }
+            | SolflowError::Db { message, .. }
+            | SolflowError::Enrichment { message, .. }
+            | SolflowError::Config { message, .. } => message,
+        }
+    }
+
+    /// Whether the same operation may succeed if tried again later
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SolflowError::Datasource { code, .. } => *code != codes::DATASOURCE_RETRIES_EXHAUSTED,
+            SolflowError::Db { code, .. } => matches!(*code, codes::DB_BUSY | codes::DB_IO | codes::DB_WRITER),
+            SolflowError::Enrichment { code, .. } => *code != codes::ENRICHMENT_RESPONSE,
+            SolflowError::Config { .. } => false,
+        }
+    }
+
+    /// Whether this is the expected refusal to write a signal for a blocklisted mint
+    pub fn is_mint_blocked(&self) -> bool {
+        self.code() == codes::DB_MINT_BLOCKED
+    }
+}
+
+impl fmt::Display for SolflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.message(), self.code())
+    }
+}
+
+impl std::error::Error for SolflowError {}
+
+impl From<rusqlite::Error> for SolflowError {
+    fn from(err: rusqlite::Error) -> Self {
+        let busy = matches!(
+            err.sqlite_error_code(),
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+        );
+        let code = if busy { codes::DB_BUSY } else { codes::DB_SQLITE };
+        SolflowError::db(code, err)
+    }
+}
+
+impl From<std::io::Error> for SolflowError {
+    fn from(err: std::io::Error) -> Self {
+        SolflowError::db(codes::DB_IO, err)
+    }
+}
+
+impl From<reqwest::Error> for SolflowError {
+    fn from(err: reqwest::Error) -> Self {
+        let code = if err.is_timeout() {
+            codes::ENRICHMENT_TIMEOUT
+        } else if err.status() == Some(reqwest::StatusCode::TOO_MANY_REQUESTS) {
+            codes::ENRICHMENT_RATE_LIMITED
+        } else if err.is_decode() {
+            codes::ENRICHMENT_RESPONSE
+        } else {
+            codes::ENRICHMENT_HTTP
+        };
+        SolflowError::enrichment(code, err)
+    }
+}
+
+impl From<ConfigError> for SolflowError {
+    fn from(err: ConfigError) -> Self {
+        let code = match err {
+            ConfigError::MissingVariable(_) => codes::CONFIG_MISSING,
+            ConfigError::InvalidValue(_) => codes::CONFIG_INVALID,
+        };
+        SolflowError::config(code, err)
+    }
+}
+
+impl From<ClientError> for SolflowError {
+    fn from(err: ClientError) -> Self {
+        match err {
+            ClientError::InvalidFilter(_) => SolflowError::config(codes::CONFIG_INVALID, err),
+            ClientError::MaxRetries => {
+                SolflowError::datasource(codes::DATASOURCE_RETRIES_EXHAUSTED, err)
+            }
+            ClientError::Connection(_) => SolflowError::datasource(codes::DATASOURCE_CONNECT, err),
+        }
+    }
+}
+
+impl From<MaxRetriesExceeded> for SolflowError {
+    fn from(err: MaxRetriesExceeded) -> Self {
+        SolflowError::datasource(codes::DATASOURCE_RETRIES_EXHAUSTED, err)
+    }
+}
+
+impl From<WriterError> for SolflowError {
+    fn from(err: WriterError) -> Self {
+        match err {
+            WriterError::Io(_) => SolflowError::db(codes::DB_IO, err),
+            WriterError::Serialization(_) => SolflowError::db(codes::DB_INVALID_DATA, err),
+            WriterError::Database(_) | WriterError::Publish(_) => {
+                SolflowError::db(codes::DB_WRITER, err)
+            }
+        }
+    }
+}
+
+impl From<carbon_core::error::Error> for SolflowError {
+    fn from(err: carbon_core::error::Error) -> Self {
+        SolflowError::datasource(codes::DATASOURCE_STREAM, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions_pick_category_and_code() {
+        let busy: SolflowError = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        )
+        .into();
+        assert_eq!(busy.category(), ErrorCategory::Db);
+        assert_eq!(busy.code(), codes::DB_BUSY);
+        assert!(busy.is_retryable());
+
+        let missing: SolflowError = ConfigError::MissingVariable("GEYSER_URL".to_string()).into();
+        assert_eq!(missing.category(), ErrorCategory::Config);
+        assert_eq!(missing.code(), "config.missing");
+        assert!(!missing.is_retryable());
+
+        let exhausted: SolflowError = ClientError::MaxRetries.into();
+        assert_eq!(exhausted.category(), ErrorCategory::Datasource);
+        assert!(!exhausted.is_retryable());
+
+        let blocked = SolflowError::db(codes::DB_MINT_BLOCKED, "Mint abc is blocked");
+        assert!(blocked.is_mint_blocked());
+        assert_eq!(blocked.to_string(), "Mint abc is blocked [db.mint_blocked]");
+        assert_eq!(blocked.category().as_str(), "db");
+    }
+}
@@ -4,21 +4,102 @@
 //! matching program IDs in both outer and inner (CPI) instructions. It replaces
 //! per-program gRPC filtering with a unified scanning approach that ensures
 //! complete coverage including nested program calls.
+//!
+//! Programs that do much more than swap (Raydium's, Orca's and Meteora's
+//! AMMs also handle liquidity, pool creation and fee collection) have a
+//! discriminator table: only their swap instructions count as a match.
+//! Instructions listed as buys or sells also tell the trade detector the
+//! direction, and liquidity instructions never match (see
+//! `streamer_core::program_registry`).
+//!
+//! The registry is built in unless `PROGRAMS_CONFIG_PATH` points at a file
+//! (see `streamer_core::program_registry`).
 
 use {
-    crate::streamer_core::balance_extractor::build_full_account_keys,
+    crate::streamer_core::{
+        balance_extractor::build_full_account_keys, program_registry::ProgramRegistry,
+        trade_detector::InstructionAction,
+    },
     carbon_core::transaction::TransactionMetadata,
     solana_pubkey::Pubkey,
     std::collections::{HashMap, HashSet},
     std::str::FromStr,
-    std::sync::Arc,
+    std::sync::{Arc, Mutex, OnceLock},
 };
 
+/// Raydium AMM v4 swap instructions (one-byte tags)
+pub(crate) const RAYDIUM_AMM_V4_SWAPS: &[&[u8]] = &[
+    &[0x09], // swap_base_in
+    &[0x0b], // swap_base_out
+    &[0x10], // swap_base_in_v2
+    &[0x11], // swap_base_out_v2
+];
+
+/// Raydium CLMM swap instructions (Anchor discriminators)
+pub(crate) const RAYDIUM_CLMM_SWAPS: &[&[u8]] = &[
+    &[0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8], // swap
+    &[0x2b, 0x04, 0xed, 0x0b, 0x1a, 0xc9, 0x1e, 0x62], // swap_v2
+    &[0x45, 0x7d, 0x73, 0xda, 0xf5, 0xba, 0xf2, 0xc4], // swap_router_base_in
+];
+
+/// Orca Whirlpool swap instructions (Anchor discriminators)
+pub(crate) const ORCA_WHIRLPOOL_SWAPS: &[&[u8]] = &[
+    &[0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8], // swap
+    &[0x2b, 0x04, 0xed, 0x0b, 0x1a, 0xc9, 0x1e, 0x62], // swap_v2
+    &[0xc3, 0x60, 0xed, 0x6c, 0x44, 0xa2, 0xdb, 0xe6], // two_hop_swap
+    &[0xba, 0x8f, 0xd1, 0x1d, 0xfe, 0x02, 0xc2, 0x75], // two_hop_swap_v2
+];
+
+/// Meteora DLMM swap instructions (Anchor discriminators)
+pub(crate) const METEORA_DLMM_SWAPS: &[&[u8]] = &[
+    &[0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8], // swap
+    &[0x41, 0x4b, 0x3f, 0x4c, 0xeb, 0x5b, 0x5b, 0x88], // swap2
+    &[0xfa, 0x49, 0x65, 0x21, 0x26, 0xcf, 0x4b, 0xb8], // swap_exact_out
+    &[0x2b, 0xd7, 0xf7, 0x84, 0x89, 0x3c, 0xf3, 0x51], // swap_exact_out2
+    &[0x38, 0xad, 0xe6, 0xd0, 0xad, 0xe4, 0x9c, 0xcd], // swap_with_price_impact
+    &[0x4a, 0x62, 0xc0, 0xd6, 0xb1, 0x33, 0x4b, 0x33], // swap_with_price_impact2
+];
+
+/// Meteora dynamic AMM (v1 pools) swap instruction (Anchor discriminator)
+pub(crate) const METEORA_DAMM_SWAPS: &[&[u8]] = &[
+    &[0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8], // swap
+];
+
+/// PumpSwap instructions (Anchor discriminators)
+pub(crate) const PUMPSWAP_BUY: &[u8] = &[0x66, 0x06, 0x3d, 0x12, 0x01, 0xda, 0xeb, 0xea];
+pub(crate) const PUMPSWAP_SELL: &[u8] = &[0x33, 0xe6, 0x85, 0xa4, 0x01, 0x7f, 0x83, 0xad];
+pub(crate) const PUMPSWAP_DEPOSIT: &[u8] = &[0xf2, 0x23, 0xc6, 0x89, 0x52, 0xe1, 0xf2, 0xb6];
+pub(crate) const PUMPSWAP_WITHDRAW: &[u8] = &[0xb7, 0x12, 0x46, 0x9c, 0x94, 0x6d, 0xa1, 0x22];
+
+/// Meteora DAMM v2 swap instructions (Anchor discriminators)
+pub(crate) const METEORA_DAMM_V2_SWAPS: &[&[u8]] = &[
+    &[0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8], // swap
+    &[0x41, 0x4b, 0x3f, 0x4c, 0xeb, 0x5b, 0x5b, 0x88], // swap2
+];
+
 /// Registry of tracked programs with scanning capabilities
 #[derive(Clone)]
 pub struct InstructionScanner {
     tracked_programs: HashSet<Pubkey>,
     program_names: HashMap<Pubkey, &'static str>,
+    program_categories: HashMap<Pubkey, &'static str>,
+    /// Discriminator table per program (instruction prefix → action)
+    actions: HashMap<Pubkey, Vec<(Vec<u8>, InstructionAction)>>,
+    /// Programs that only match their swap, buy and sell instructions
+    swap_only: HashSet<Pubkey>,
+}
+
+/// Registry names as `&'static str`; each distinct name is leaked once,
+/// however often the registry is reloaded
+fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut names = NAMES.get_or_init(Default::default).lock().unwrap();
+    if let Some(interned) = names.get(name) {
+        return *interned;
+    }
+    let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+    names.insert(interned);
+    interned
 }
 
 /// Result when a tracked program is found in a transaction
@@ -26,6 +107,8 @@ pub struct InstructionScanner {
 pub struct InstructionMatch {
     pub program_id: Pubkey,
     pub program_name: &'static str,
+    /// Action of the matched instruction, if it's in the discriminator table
+    pub action: Option<InstructionAction>,
     pub instruction_path: InstructionPath,
 }
 
@@ -42,54 +125,87 @@ pub enum InstructionPath {
 }
 
 impl InstructionScanner {
-    /// Create a new instruction scanner with the tracked program registry
+    /// Create a new instruction scanner with the built-in program registry
     ///
-    /// The registry includes 5 programs:
+    /// The registry includes 11 programs:
     /// - PumpFun: Token minting and bonding curve protocol
     /// - PumpSwap: Swap protocol for pump tokens
     /// - BonkSwap: LetsBonk launchpad swaps
     /// - Moonshot: Moonshot DEX
     /// - Jupiter DCA: Jupiter DCA protocol
+    /// - RaydiumAMM: Raydium AMM v4 (swap instructions only)
+    /// - RaydiumCLMM: Raydium concentrated liquidity (swap instructions only)
+    /// - OrcaWhirlpool: Orca concentrated liquidity (swap and two-hop swap instructions only)
+    /// - MeteoraDLMM: Meteora dynamic liquidity market maker (swap instructions only)
+    /// - MeteoraDAMM: Meteora dynamic AMM v1 pools (swap instructions only)
+    /// - MeteoraDAMMv2: Meteora dynamic AMM v2 (swap instructions only)
     pub fn new() -> Self {
-        let mut program_names = HashMap::new();
+        Self::from_registry(&ProgramRegistry::builtin()).expect("built-in program registry is valid")
+    }
 
-        // CRITICAL: All 5 programs must be included
-        let pumpfun =
-            Pubkey::from_str("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P").unwrap();
-        let pumpswap =
-            Pubkey::from_str("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA").unwrap();
-        let bonkswap =
-            Pubkey::from_str("LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj").unwrap();
-        let moonshot =
-            Pubkey::from_str("MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG").unwrap();
-        let jupiter_dca =
-            Pubkey::from_str("DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M").unwrap();
-
-        program_names.insert(pumpfun, "PumpFun");
-        program_names.insert(pumpswap, "PumpSwap");
-        program_names.insert(bonkswap, "BonkSwap");
-        program_names.insert(moonshot, "Moonshot");
-        program_names.insert(jupiter_dca, "JupiterDCA");
+    /// Create a scanner for the programs in `registry` (see `program_registry`)
+    pub fn from_registry(registry: &ProgramRegistry) -> Result<Self, String> {
+        registry.validate()?;
 
+        let mut program_names = HashMap::new();
+        let mut program_categories = HashMap::new();
+        let mut actions = HashMap::new();
+        let mut swap_only = HashSet::new();
+        for entry in &registry.programs {
+            let program_id = Pubkey::from_str(&entry.program_id)
+                .map_err(|_| format!("'{}' is not a base58 pubkey", entry.program_id))?;
+            program_names.insert(program_id, intern(&entry.name));
+            program_categories.insert(program_id, intern(&entry.category));
+            let table = entry.actions();
+            if !table.is_empty() {
+                actions.insert(program_id, table);
+            }
+            if entry.swap_only() {
+                swap_only.insert(program_id);
+            }
+        }
         let tracked_programs = program_names.keys().copied().collect();
 
-        log::info!("📋 InstructionScanner initialized with {} programs", program_names.len());
-        log::info!("   ├─ PumpFun: 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
-        log::info!("   ├─ PumpSwap: pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
-        log::info!("   ├─ BonkSwap: LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj");
-        log::info!("   ├─ Moonshot: MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG");
-        log::info!("   └─ JupiterDCA: DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M");
+        log::info!("📋 InstructionScanner initialized with {} programs", registry.programs.len());
+        for (index, entry) in registry.programs.iter().enumerate() {
+            let branch = if index + 1 == registry.programs.len() { "└─" } else { "├─" };
+            let filtered = if entry.swap_only() { " (swaps)" } else { "" };
+            log::info!("   {} {}: {}{}", branch, entry.name, entry.program_id, filtered);
+        }
 
-        Self {
+        Ok(Self {
             tracked_programs,
             program_names,
+            program_categories,
+            actions,
+            swap_only,
+        })
+    }
+
+    /// Action of an instruction of `program_id` with `data` (None if not in the table)
+    pub fn instruction_action(&self, program_id: &Pubkey, data: &[u8]) -> Option<InstructionAction> {
+        self.actions
+            .get(program_id)?
+            .iter()
+            .find(|(discriminator, _)| data.starts_with(discriminator))
+            .map(|(_, action)| *action)
+    }
+
+    /// Whether an instruction of `program_id` with `data` counts as a match
+    fn matches_instruction(&self, program_id: &Pubkey, data: &[u8]) -> bool {
+        if !self.tracked_programs.contains(program_id) {
+            return false;
+        }
+        match self.instruction_action(program_id, data) {
+            Some(action) => !action.is_liquidity(),
+            None => !self.swap_only.contains(program_id),
         }
     }
 
     /// Check if a transaction is "pump-relevant" (contains any tracked program)
     ///
     /// This is a convenience method that returns a boolean indicating whether
-    /// the transaction involves any of the 5 tracked programs. It's used as
+    /// the transaction involves any of the 11 tracked programs. It's used as
     /// the unified detection mechanism for pump-ecosystem transactions.
     ///
     /// # Unified Detection
@@ -100,6 +216,9 @@ impl InstructionScanner {
     /// - BonkSwap (LetsBonk launchpad)
     /// - Moonshot (Moonshot DEX)
     /// - Jupiter DCA (DCA protocol)
+    /// - Raydium AMM v4 / CLMM swaps
+    /// - Orca Whirlpool swaps (including two-hop swaps)
+    /// - Meteora DLMM / DAMM v1 / DAMM v2 swaps
     ///
     /// This includes matches in both outer (top-level) and inner (CPI) instructions.
     ///
@@ -159,10 +278,11 @@ impl InstructionScanner {
             let program_id_index = instruction.program_id_index as usize;
             
             if let Some(program_id) = account_keys.get(program_id_index) {
-                if self.tracked_programs.contains(program_id) {
+                if self.matches_instruction(program_id, &instruction.data) {
                     return Some(InstructionMatch {
                         program_id: *program_id,
                         program_name: self.program_names.get(program_id).unwrap(),
+                        action: self.instruction_action(program_id, &instruction.data),
                         instruction_path: InstructionPath::Outer { index: idx },
                     });
                 }
@@ -178,10 +298,11 @@ impl InstructionScanner {
                     let program_id_index = inner.instruction.program_id_index as usize;
                     
                     if let Some(program_id) = account_keys.get(program_id_index) {
-                        if self.tracked_programs.contains(program_id) {
+                        if self.matches_instruction(program_id, &inner.instruction.data) {
                             return Some(InstructionMatch {
                                 program_id: *program_id,
                                 program_name: self.program_names.get(program_id).unwrap(),
+                                action: self.instruction_action(program_id, &inner.instruction.data),
                                 instruction_path: InstructionPath::Inner {
                                     outer_index,
                                     inner_path: vec![inner_idx],
@@ -209,6 +330,27 @@ impl InstructionScanner {
             .map(|pk| pk.to_string())
             .collect()
     }
+
+    /// Name of a tracked program (None if it isn't tracked)
+    pub fn program_name(&self, program_id: &Pubkey) -> Option<&'static str> {
+        self.program_names.get(program_id).copied()
+    }
+
+    /// Category of a tracked program from the registry (launchpad, amm, ...)
+    pub fn program_category(&self, program_id: &Pubkey) -> Option<&'static str> {
+        self.program_categories.get(program_id).copied()
+    }
+
+    /// Tracked programs as (name, program ID) pairs, sorted by name
+    pub fn tracked_programs(&self) -> Vec<(&'static str, String)> {
+        let mut programs: Vec<(&'static str, String)> = self
+            .program_names
+            .iter()
+            .map(|(pk, name)| (*name, pk.to_string()))
+            .collect();
+        programs.sort();
+        programs
+    }
 }
 
 impl Default for InstructionScanner {
@@ -224,7 +366,7 @@ mod tests {
     #[test]
     fn test_scanner_initialization() {
         let scanner = InstructionScanner::new();
-        assert_eq!(scanner.program_count(), 5);
+        assert_eq!(scanner.program_count(), 11);
         
         let program_ids = scanner.tracked_program_ids();
         assert!(program_ids.contains(&"6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string()));
@@ -232,6 +374,43 @@ mod tests {
         assert!(program_ids.contains(&"LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj".to_string()));
         assert!(program_ids.contains(&"MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG".to_string()));
         assert!(program_ids.contains(&"DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M".to_string()));
+        assert!(program_ids.contains(&"675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8".to_string()));
+        assert!(program_ids.contains(&"CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK".to_string()));
+        assert!(program_ids.contains(&"whirLbMiicVdio4qvUfM5KaG6Ct8VwpYzGff3uctyCc".to_string()));
+        assert!(program_ids.contains(&"LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo".to_string()));
+        assert!(program_ids.contains(&"Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB".to_string()));
+        assert!(program_ids.contains(&"cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG".to_string()));
+    }
+
+    #[test]
+    fn test_raydium_matches_swaps_only() {
+        let scanner = InstructionScanner::new();
+        let amm = Pubkey::from_str("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8").unwrap();
+        let clmm = Pubkey::from_str("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK").unwrap();
+        let pumpswap = Pubkey::from_str("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA").unwrap();
+
+        // swap_base_in vs. deposit (tag 3)
+        assert!(scanner.matches_instruction(&amm, &[0x09, 1, 2, 3]));
+        assert!(!scanner.matches_instruction(&amm, &[0x03, 1, 2, 3]));
+        assert!(scanner.matches_instruction(&clmm, &[0x2b, 0x04, 0xed, 0x0b, 0x1a, 0xc9, 0x1e, 0x62, 0]));
+        assert!(!scanner.matches_instruction(&clmm, &[0x2b, 0x04]));
+        // Programs without swap entries match any instruction but liquidity ones
+        assert!(scanner.matches_instruction(&pumpswap, &[]));
+        assert!(!scanner.matches_instruction(&pumpswap, PUMPSWAP_DEPOSIT));
+        assert_eq!(scanner.instruction_action(&pumpswap, PUMPSWAP_SELL), Some(InstructionAction::Sell));
+        assert_eq!(scanner.instruction_action(&amm, &[0x09]), Some(InstructionAction::Swap));
+        assert_eq!(scanner.program_name(&clmm), Some("RaydiumCLMM"));
+
+        // Orca: two_hop_swap vs. increase_liquidity
+        let whirlpool = Pubkey::from_str("whirLbMiicVdio4qvUfM5KaG6Ct8VwpYzGff3uctyCc").unwrap();
+        assert!(scanner.matches_instruction(&whirlpool, &[0xc3, 0x60, 0xed, 0x6c, 0x44, 0xa2, 0xdb, 0xe6, 0]));
+        assert!(!scanner.matches_instruction(&whirlpool, &[0x2e, 0x9c, 0xf3, 0x76, 0x0d, 0xcd, 0xfb, 0xb2]));
+
+        // Meteora DLMM: swap2 vs. add_liquidity
+        let dlmm = Pubkey::from_str("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo").unwrap();
+        assert!(scanner.matches_instruction(&dlmm, &[0x41, 0x4b, 0x3f, 0x4c, 0xeb, 0x5b, 0x5b, 0x88, 0]));
+        assert!(!scanner.matches_instruction(&dlmm, &[0xb5, 0x9d, 0x59, 0x43, 0x8f, 0xb6, 0x34, 0x48]));
+        assert_eq!(scanner.program_name(&dlmm), Some("MeteoraDLMM"));
     }
 
     #[test]
@@ -4,7 +4,9 @@ mod tests;
 pub mod aggregator_core;
 mod aggregator;
 mod config;
+pub mod embed;
 pub mod empty_decoder;
+pub mod error;
 pub mod instruction_scanner;
 mod persistence;
 mod state;
@@ -15,6 +17,9 @@ mod ui;
 pub mod streamer_core;
 pub mod pipeline;
 pub mod meta_analysis;
+pub mod api;
+
+pub use embed::{Engine, EngineEvent, EngineOptions, SnapshotOptions, TradeSource};
 
 use {
     async_trait::async_trait,
@@ -33,9 +38,10 @@ use {
         collections::HashMap,
         sync::Arc,
     },
+    streamer_core::filter_builder::TransactionFilterBuilder,
     tokio::sync::{mpsc, RwLock},
     trade_extractor::{build_full_account_keys, extract_sol_changes, extract_token_changes, extract_user_volumes},
-    yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeRequestFilterTransactions},
+    yellowstone_grpc_proto::geyser::CommitmentLevel,
 };
 
 #[tokio::main]
@@ -69,7 +75,7 @@ pub async fn main() -> CarbonResult<()> {
     // Log startup information (before UI starts to avoid overlay)
     log::info!("🚀 Starting SolFlow...");
     log::info!("📊 Configuration:");
-    log::info!("   GEYSER_URL: {}", config.geyser_url);
+    log::info!("   GEYSER_URL: {}", streamer_core::secrets::redact_url(&config.geyser_url));
     let filters_str = if config.program_filters.is_empty() {
         "None (processing all transactions)".to_string()
     } else {
@@ -77,21 +83,11 @@ pub async fn main() -> CarbonResult<()> {
     };
     log::info!("   Program Filters: {}", filters_str);
     
-    // Setup transaction filter (match pattern from jupiter-swap-alerts)
-    let mut transaction_filters: HashMap<String, SubscribeRequestFilterTransactions> = HashMap::new();
-    
-    if !config.program_filters.is_empty() {
-        let transaction_filter = SubscribeRequestFilterTransactions {
-            vote: Some(false),
-            failed: Some(false),
-            account_include: vec![],
-            account_exclude: vec![],
-            account_required: config.program_filters.clone(),
-            signature: None,
-        };
-        
-        transaction_filters.insert("transaction_filter".to_string(), transaction_filter);
-    }
+    // One filter per program (OR logic); no programs = no transaction filter
+    let transaction_filters = TransactionFilterBuilder::new()
+        .programs(config.program_filters.iter().cloned())
+        .build()
+        .unwrap_or_else(|e| panic!("Invalid PROGRAM_FILTERS: {}", e));
     
     // Create bounded channel for state messages (backpressure handling)
     let (tx, rx) = mpsc::channel::<StateMessage>(1000);
@@ -121,7 +117,7 @@ pub async fn main() -> CarbonResult<()> {
         persistence::persistence_task(state_for_persistence, persistence::PersistenceConfig::default()).await;
     });
     
-    log::info!("🔌 Connecting to Yellowstone gRPC: {}", config.geyser_url);
+    log::info!("🔌 Connecting to Yellowstone gRPC: {}", streamer_core::secrets::redact_url(&config.geyser_url));
     let yellowstone_grpc = YellowstoneGrpcGeyserClient::new(
         config.geyser_url,
         config.x_token,
@@ -138,18 +134,22 @@ pub async fn main() -> CarbonResult<()> {
     log::info!("✅ Pipeline configured, starting data stream...");
     
     // Spawn UI task (needed for terminal interface)
+    let shutdown = pipeline::shutdown::Shutdown::from_env();
     let state_for_ui = state.clone();
-    let ui_handle = tokio::spawn(async move {
-        if let Err(e) = ui::run_ui(state_for_ui).await {
+    let ui_shutdown = shutdown.signal();
+    let mut ui_handle = tokio::spawn(async move {
+        if let Err(e) = ui::run_ui(state_for_ui, ui_shutdown).await {
             log::error!("UI error: {}", e);
         }
     });
+    let mut ui_exited = false;
     
     // Run pipeline directly (matching jupiter-swap-alerts pattern)
     // Use tokio::select to run both UI and pipeline concurrently
     tokio::select! {
-        _ = ui_handle => {
+        _ = &mut ui_handle => {
             log::info!("UI exited");
+            ui_exited = true;
         }
         result = async {
             log::info!("📡 Starting pipeline...");
@@ -169,6 +169,16 @@ pub async fn main() -> CarbonResult<()> {
             }
         }
     }
+
+    // Stop the UI (restores the terminal) and keep the latest trades
+    let deadline = shutdown.trigger();
+    if !ui_exited {
+        pipeline::shutdown::join_until("UI", ui_handle, deadline).await;
+    }
+    let trades = state.read().await.get_recent_trades().to_vec();
+    if let Err(e) = persistence::save_snapshot(&trades, &persistence::PersistenceConfig::default().file_path) {
+        log::warn!("Failed to save snapshot on shutdown: {}", e);
+    }
     
     Ok(())
 }
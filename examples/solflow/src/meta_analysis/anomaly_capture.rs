@@ -0,0 +1,157 @@
+//! Capture-on-anomaly: full transaction capture for mints with fresh signals
+//!
+//! Capturing every transaction is too much data, but the raw evidence behind
+//! an interesting signal is exactly what post-mortems need. When a signal at
+//! or above `ANOMALY_CAPTURE_MIN_SEVERITY` fires, the engine arms capture for
+//! that mint; for the next `ANOMALY_CAPTURE_MINUTES` the unified streamer
+//! writes every transaction trading it as a `TransactionCapture` (the same
+//! record the meta-analysis streamers produce) to
+//! `<ANOMALY_CAPTURE_DIR>/<mint>.jsonl`.
+//!
+//! Environment variables:
+//! - `ANOMALY_CAPTURE_ENABLED`: Arm capture on high-severity signals (default: false)
+//! - `ANOMALY_CAPTURE_MIN_SEVERITY`: Lowest signal severity that arms capture (default: 4)
+//! - `ANOMALY_CAPTURE_MINUTES`: How long capture stays armed per signal (default: 10)
+//! - `ANOMALY_CAPTURE_DIR`: Output directory (default: captures/anomaly)
+
+use crate::meta_analysis::types::TransactionCapture;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncWriteExt;
+
+/// Shared set of mints currently armed for capture
+///
+/// Cloned into both the engine (which arms) and the streamer (which captures).
+#[derive(Debug, Clone)]
+pub struct AnomalyCapture {
+    /// Mint → capture expiry (unix seconds)
+    armed: Arc<Mutex<HashMap<String, i64>>>,
+    min_severity: i32,
+    duration_secs: i64,
+    output_dir: PathBuf,
+}
+
+impl AnomalyCapture {
+    pub fn new(min_severity: i32, duration_secs: i64, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            armed: Arc::new(Mutex::new(HashMap::new())),
+            min_severity,
+            duration_secs,
+            output_dir: output_dir.into(),
+        }
+    }
+
+    /// Create from the environment (see module docs); None unless enabled
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("ANOMALY_CAPTURE_ENABLED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let min_severity = std::env::var("ANOMALY_CAPTURE_MIN_SEVERITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(4);
+        let minutes: i64 = std::env::var("ANOMALY_CAPTURE_MINUTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(10);
+        let output_dir = std::env::var("ANOMALY_CAPTURE_DIR")
+            .unwrap_or_else(|_| "captures/anomaly".to_string());
+
+        Some(Self::new(min_severity, minutes * 60, output_dir))
+    }
+
+    pub fn min_severity(&self) -> i32 {
+        self.min_severity
+    }
+
+    pub fn duration_secs(&self) -> i64 {
+        self.duration_secs
+    }
+
+    pub fn output_dir(&self) -> &PathBuf {
+        &self.output_dir
+    }
+
+    /// Arm (or extend) capture for a mint if the signal is severe enough
+    ///
+    /// Returns true when the mint was not already armed.
+    pub fn arm(&self, mint: &str, severity: i32, now: i64) -> bool {
+        if severity < self.min_severity {
+            return false;
+        }
+        self.arm_mint(mint, now)
+    }
+
+    /// Arm (or extend) capture for a mint regardless of severity
+    ///
+    /// Used for tokens of interest (see `pipeline::interest`). Returns true
+    /// when the mint was not already armed.
+    pub fn arm_mint(&self, mint: &str, now: i64) -> bool {
+        let mut armed = self.armed.lock().unwrap();
+        armed.retain(|_, expires_at| *expires_at > now);
+        armed
+            .insert(mint.to_string(), now + self.duration_secs)
+            .is_none()
+    }
+
+    /// Check whether a mint is currently armed
+    pub fn is_armed(&self, mint: &str, now: i64) -> bool {
+        self.armed
+            .lock()
+            .unwrap()
+            .get(mint)
+            .is_some_and(|expires_at| *expires_at > now)
+    }
+
+    fn output_path(&self, mint: &str) -> PathBuf {
+        self.output_dir.join(format!("{}.jsonl", mint))
+    }
+
+    /// Append a capture record to the mint's capture file
+    pub async fn write(&self, mint: &str, capture: &TransactionCapture) -> Result<(), std::io::Error> {
+        let json_line = serde_json::to_string(capture)?;
+
+        tokio::fs::create_dir_all(&self.output_dir).await?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.output_path(mint))
+            .await?;
+
+        file.write_all(json_line.as_bytes()).await?;
+        file.write_all(b"\n").await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arm_respects_severity_and_expiry() {
+        let capture = AnomalyCapture::new(4, 600, "captures/test");
+
+        assert!(!capture.arm("mint_a", 3, 1000));
+        assert!(!capture.is_armed("mint_a", 1000));
+
+        assert!(capture.arm("mint_a", 5, 1000));
+        assert!(capture.is_armed("mint_a", 1599));
+        assert!(!capture.is_armed("mint_a", 1600));
+
+        // Re-firing while armed extends the window
+        assert!(!capture.arm("mint_a", 4, 1500));
+        assert!(capture.is_armed("mint_a", 2000));
+
+        // Expired entries re-arm as new
+        assert!(capture.arm("mint_a", 4, 5000));
+    }
+}
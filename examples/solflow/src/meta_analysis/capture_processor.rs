@@ -122,46 +122,86 @@ impl MetadataCaptureProcessor {
         
         Ok(())
     }
+}
 
-    fn extract_inner_instructions(
-        &self,
-        metadata: &carbon_core::transaction::TransactionMetadata,
-        account_keys: &[solana_pubkey::Pubkey],
-    ) -> Vec<InnerInstructionRecord> {
-        metadata
-            .meta
-            .inner_instructions
-            .as_ref()
-            .map(|inner_groups| {
-                inner_groups
-                    .iter()
-                    .flat_map(|inner_group| {
-                        inner_group.instructions.iter().map(|inner| {
-                            let program_id_index = inner.instruction.program_id_index;
-                            let program_id = account_keys
-                                .get(program_id_index as usize)
-                                .map(|pk| pk.to_string())
-                                .unwrap_or_else(|| "INVALID_INDEX".to_string());
+fn extract_inner_instructions(
+    metadata: &carbon_core::transaction::TransactionMetadata,
+    account_keys: &[solana_pubkey::Pubkey],
+) -> Vec<InnerInstructionRecord> {
+    metadata
+        .meta
+        .inner_instructions
+        .as_ref()
+        .map(|inner_groups| {
+            inner_groups
+                .iter()
+                .flat_map(|inner_group| {
+                    inner_group.instructions.iter().map(|inner| {
+                        let program_id_index = inner.instruction.program_id_index;
+                        let program_id = account_keys
+                            .get(program_id_index as usize)
+                            .map(|pk| pk.to_string())
+                            .unwrap_or_else(|| "INVALID_INDEX".to_string());
 
-                            let data_len = inner.instruction.data.len();
-                            let data_hex_prefix = hex::encode(
-                                &inner.instruction.data[..data_len.min(16)]
-                            );
+                        let data_len = inner.instruction.data.len();
+                        let data_hex_prefix = hex::encode(
+                            &inner.instruction.data[..data_len.min(16)]
+                        );
 
-                            InnerInstructionRecord {
-                                top_level_index: inner_group.index,
-                                stack_height: inner.stack_height,
-                                program_id_index,
-                                program_id,
-                                accounts: inner.instruction.accounts.clone(),
-                                data_length: data_len,
-                                data_hex_prefix,
-                            }
-                        })
+                        InnerInstructionRecord {
+                            top_level_index: inner_group.index,
+                            stack_height: inner.stack_height,
+                            program_id_index,
+                            program_id,
+                            accounts: inner.instruction.accounts.clone(),
+                            data_length: data_len,
+                            data_hex_prefix,
+                        }
                     })
-                    .collect()
-            })
-            .unwrap_or_default()
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build a full capture record for a transaction
+///
+/// Shared by the meta-analysis streamers and anomaly capture.
+pub fn build_transaction_capture(
+    metadata: &carbon_core::transaction::TransactionMetadata,
+    capture_metadata: CaptureMetadata,
+) -> TransactionCapture {
+    let account_keys = build_full_account_keys(metadata, &metadata.meta);
+    let sol_deltas = extract_sol_changes(&metadata.meta, &account_keys);
+    let token_deltas = extract_token_changes(&metadata.meta, &account_keys);
+
+    // Refinement #1: Extract inner instructions with program IDs
+    let inner_instructions = extract_inner_instructions(metadata, &account_keys);
+
+    TransactionCapture {
+        capture_metadata,
+        slot: metadata.slot,
+        signature: metadata.signature.to_string(),
+        block_time: metadata.block_time,
+        fee_payer: metadata.fee_payer.to_string(),
+        account_keys: account_keys.iter().map(|k| k.to_string()).collect(),
+        static_key_count: metadata.message.static_account_keys().len(),
+        pre_balances: metadata.meta.pre_balances.clone(),
+        post_balances: metadata.meta.post_balances.clone(),
+        pre_token_balances: convert_token_balances(&metadata.meta.pre_token_balances),
+        post_token_balances: convert_token_balances(&metadata.meta.post_token_balances),
+        sol_deltas: sol_deltas
+            .iter()
+            .map(BalanceDeltaRecord::from_balance_delta)
+            .collect(),
+        token_deltas: token_deltas
+            .iter()
+            .map(BalanceDeltaRecord::from_balance_delta)
+            .collect(),
+        inner_instructions,
+        fee: metadata.meta.fee,
+        rewards: metadata.meta.rewards.clone(),
+        account_classifications: vec![],
     }
 }
 
@@ -187,57 +227,22 @@ impl Processor for MetadataCaptureProcessor {
             ));
         }
 
-        // Build full account keys
-        let account_keys = build_full_account_keys(&metadata, &metadata.meta);
-
-        // Extract balance deltas
-        let sol_deltas = extract_sol_changes(&metadata.meta, &account_keys);
-        let token_deltas = extract_token_changes(&metadata.meta, &account_keys);
-
-        // Refinement #1: Extract inner instructions with program IDs
-        let inner_instructions = self.extract_inner_instructions(&metadata, &account_keys);
+        let capture = build_transaction_capture(&metadata, self.capture_metadata.clone());
 
         // Track inner instruction stats
-        if !inner_instructions.is_empty() {
+        if !capture.inner_instructions.is_empty() {
             self.transactions_with_inner.fetch_add(1, Ordering::SeqCst);
             self.inner_instruction_count
-                .fetch_add(inner_instructions.len(), Ordering::SeqCst);
+                .fetch_add(capture.inner_instructions.len(), Ordering::SeqCst);
 
             let mut tracker = self.inner_program_tracker.lock().await;
-            for inner in &inner_instructions {
+            for inner in &capture.inner_instructions {
                 if !tracker.contains(&inner.program_id) {
                     tracker.push(inner.program_id.clone());
                 }
             }
         }
 
-        // Build capture record
-        let capture = TransactionCapture {
-            capture_metadata: self.capture_metadata.clone(),
-            slot: metadata.slot,
-            signature: metadata.signature.to_string(),
-            block_time: metadata.block_time,
-            fee_payer: metadata.fee_payer.to_string(),
-            account_keys: account_keys.iter().map(|k| k.to_string()).collect(),
-            static_key_count: metadata.message.static_account_keys().len(),
-            pre_balances: metadata.meta.pre_balances.clone(),
-            post_balances: metadata.meta.post_balances.clone(),
-            pre_token_balances: convert_token_balances(&metadata.meta.pre_token_balances),
-            post_token_balances: convert_token_balances(&metadata.meta.post_token_balances),
-            sol_deltas: sol_deltas
-                .iter()
-                .map(BalanceDeltaRecord::from_balance_delta)
-                .collect(),
-            token_deltas: token_deltas
-                .iter()
-                .map(BalanceDeltaRecord::from_balance_delta)
-                .collect(),
-            inner_instructions,
-            fee: metadata.meta.fee,
-            rewards: metadata.meta.rewards.clone(),
-            account_classifications: vec![],
-        };
-
         // Write to JSONL
         self.write_jsonl(&capture).await.map_err(|e| {
             CarbonError::Custom(format!("Failed to write JSONL: {}", e))
@@ -248,9 +253,9 @@ impl Processor for MetadataCaptureProcessor {
             count + 1,
             self.max_transactions,
             metadata.signature,
-            account_keys.len(),
-            sol_deltas.len(),
-            token_deltas.len(),
+            capture.account_keys.len(),
+            capture.sol_deltas.len(),
+            capture.token_deltas.len(),
             capture.inner_instructions.len()
         );
 
@@ -1,7 +1,9 @@
+pub mod anomaly_capture;
 pub mod capture_processor;
 pub mod types;
 
-pub use capture_processor::MetadataCaptureProcessor;
+pub use anomaly_capture::AnomalyCapture;
+pub use capture_processor::{build_transaction_capture, MetadataCaptureProcessor};
 pub use types::{
     BalanceDeltaRecord, CaptureMetadata, InnerInstructionRecord, SessionMetadata,
     TransactionCapture,
@@ -0,0 +1,162 @@
+//! Opt-in raw trade audit log for watched mints
+//!
+//! The pipeline never persists raw trades. When an aggregate looks wrong
+//! that makes it hard to check, so this keeps a short, rolling window of
+//! source trades for a few watched mints in `audit_trades` (see
+//! `/sql/12_audit_trades.sql`) to recompute it against.
+//!
+//! The engine hands every processed trade to `record`; trades for watched
+//! mints are buffered and written by the runtime every few seconds, which
+//! also deletes rows older than the retention. Watched mints are
+//! `AUDIT_TRADES_MINTS` plus, unless disabled, followed tokens
+//! (`follow_price = 1`), the same set the fast path uses.
+
+use super::fast_path::WatchedMints;
+use super::fees::TradeFees;
+use super::types::{TradeDirection, TradeEvent};
+use std::env;
+use std::sync::{Arc, Mutex};
+
+/// Audit log configuration (AUDIT_TRADES_* env vars)
+#[derive(Debug, Clone)]
+pub struct AuditTradesConfig {
+    /// Mints always audited (AUDIT_TRADES_MINTS, comma-separated)
+    pub pinned_mints: Vec<String>,
+    /// Also audit followed tokens (AUDIT_TRADES_FOLLOWED, default: true)
+    pub include_followed: bool,
+    /// Rows kept per mint, by block time (AUDIT_TRADES_RETENTION_MINS, default: 30)
+    pub retention_secs: i64,
+}
+
+impl AuditTradesConfig {
+    /// Load from env; None when AUDIT_TRADES_ENABLED is not true
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("AUDIT_TRADES_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let pinned_mints = env::var("AUDIT_TRADES_MINTS")
+            .map(|v| {
+                v.split(',')
+                    .map(|mint| mint.trim().to_string())
+                    .filter(|mint| !mint.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let retention_mins: i64 = env::var("AUDIT_TRADES_RETENTION_MINS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(30);
+
+        Some(Self {
+            pinned_mints,
+            include_followed: env::var("AUDIT_TRADES_FOLLOWED")
+                .map(|v| !v.eq_ignore_ascii_case("false") && v != "0")
+                .unwrap_or(true),
+            retention_secs: retention_mins.max(1) * 60,
+        })
+    }
+}
+
+/// A buffered trade, its fees and when the engine processed it
+#[derive(Debug, Clone)]
+pub struct AuditTrade {
+    pub trade: TradeEvent,
+    pub fees: TradeFees,
+    pub recorded_at: i64,
+}
+
+impl AuditTrade {
+    /// Value stored in `audit_trades.direction`
+    pub fn direction(&self) -> &'static str {
+        match self.trade.direction {
+            TradeDirection::Buy => "BUY",
+            TradeDirection::Sell => "SELL",
+            TradeDirection::Unknown => "UNKNOWN",
+        }
+    }
+
+    /// Value stored in `audit_trades.net_sol`
+    pub fn net_sol(&self) -> f64 {
+        self.fees.net_sol(&self.trade)
+    }
+}
+
+/// Buffers trades for watched mints until the runtime writes them; cheap to clone
+#[derive(Clone)]
+pub struct AuditTradeLog {
+    watched: WatchedMints,
+    pending: Arc<Mutex<Vec<AuditTrade>>>,
+    retention_secs: i64,
+}
+
+impl AuditTradeLog {
+    pub fn new(watched: WatchedMints, retention_secs: i64) -> Self {
+        Self {
+            watched,
+            pending: Arc::default(),
+            retention_secs,
+        }
+    }
+
+    pub fn watched(&self) -> &WatchedMints {
+        &self.watched
+    }
+
+    pub fn retention_secs(&self) -> i64 {
+        self.retention_secs
+    }
+
+    /// Buffer `trade` if its mint is watched
+    pub fn record(&self, trade: &TradeEvent, fees: TradeFees, now: i64) {
+        if self.watched.contains(&trade.mint) {
+            self.pending.lock().unwrap().push(AuditTrade {
+                trade: trade.clone(),
+                fees,
+                recorded_at: now,
+            });
+        }
+    }
+
+    /// Take everything buffered since the last drain
+    pub fn drain(&self) -> Vec<AuditTrade> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn make_trade(mint: &str, direction: TradeDirection) -> TradeEvent {
+        TradeEvent {
+            direction,
+            ..TradeEvent::test_buy(mint, 1_700_000_000)
+        }
+    }
+
+    #[test]
+    fn test_records_only_watched_mints() {
+        let watched = WatchedMints::new(vec!["pinned".to_string()]);
+        let log = AuditTradeLog::new(watched.clone(), 1800);
+
+        let fees = TradeFees::default();
+        log.record(&make_trade("pinned", TradeDirection::Buy), fees, 10);
+        log.record(&make_trade("other", TradeDirection::Buy), fees, 11);
+        watched.set_followed(HashSet::from(["followed".to_string()]));
+        log.record(&make_trade("followed", TradeDirection::Sell), fees, 12);
+
+        let drained = log.drain();
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].direction(), "BUY");
+        assert_eq!(drained[0].recorded_at, 10);
+        assert_eq!(drained[1].trade.mint, "followed");
+        assert_eq!(drained[1].direction(), "SELL");
+        assert!(log.drain().is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+//! Periodic online snapshots of the pipeline databases
+//!
+//! Copies the live database (and every `DB_ROUTES` file) with SQLite's online
+//! backup API, a few pages at a time so the flush is never blocked for long.
+//! Each snapshot is a consistent point-in-time copy:
+//!
+//! - `<DB_BACKUP_DIR>/<name>-<UTC time>.db`, the newest `DB_BACKUP_KEEP` kept
+//! - `<DB_BACKUP_DIR>/<name>-latest.db`, atomically replaced each cycle, for
+//!   analytics readers that should stay off the hot file
+//!
+//! `DB_BACKUP_UPLOAD_CMD` is run after each snapshot with `{path}` replaced by
+//! the snapshot path, e.g. `aws s3 cp {path} s3://bucket/solflow/`, for
+//! off-host copies.
+
+use super::routing::DbRoute;
+use crate::error::SolflowError;
+use rusqlite::{backup::Backup, Connection, OpenFlags};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Pages copied per backup step
+const PAGES_PER_STEP: std::os::raw::c_int = 1024;
+
+/// Pause between backup steps, letting writers in
+const STEP_PAUSE: Duration = Duration::from_millis(20);
+
+/// Backup configuration (DB_BACKUP_* env vars)
+#[derive(Debug, Clone)]
+pub struct BackupConfig {
+    /// Snapshot directory (DB_BACKUP_DIR)
+    pub dir: PathBuf,
+    /// Seconds between snapshots (DB_BACKUP_INTERVAL_SECS, default: 3600)
+    pub interval_secs: u64,
+    /// Timestamped snapshots kept per database (DB_BACKUP_KEEP, default: 24)
+    pub keep: usize,
+    /// Command run per snapshot, `{path}` substituted (DB_BACKUP_UPLOAD_CMD)
+    pub upload_cmd: Option<String>,
+}
+
+impl BackupConfig {
+    /// Load from env; None when DB_BACKUP_DIR is not set
+    pub fn from_env() -> Option<Self> {
+        let dir = env::var("DB_BACKUP_DIR").ok().filter(|d| !d.trim().is_empty())?;
+        Some(Self {
+            dir: PathBuf::from(dir),
+            interval_secs: env::var("DB_BACKUP_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3600u64)
+                .max(60),
+            keep: env::var("DB_BACKUP_KEEP")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(24usize)
+                .max(1),
+            upload_cmd: env::var("DB_BACKUP_UPLOAD_CMD")
+                .ok()
+                .filter(|c| !c.trim().is_empty()),
+        })
+    }
+}
+
+/// One database to snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupSource {
+    /// Snapshot file prefix
+    pub name: String,
+    pub db_path: String,
+}
+
+/// The primary database ("solflow") plus each routed database (by route name)
+pub fn backup_sources(primary_db_path: &str, routes: &[DbRoute]) -> Vec<BackupSource> {
+    let mut sources = vec![BackupSource {
+        name: "solflow".to_string(),
+        db_path: primary_db_path.to_string(),
+    }];
+    for route in routes {
+        if sources.iter().all(|s| s.db_path != route.db_path) {
+            sources.push(BackupSource {
+                name: route.name.clone(),
+                db_path: route.db_path.clone(),
+            });
+        }
+    }
+    sources
+}
+
+/// Copy `source` to `dest` with the online backup API
+///
+/// `dest` is written through a temporary file and renamed into place, so a
+/// reader never sees a partial copy.
+pub fn snapshot(source: &str, dest: &Path) -> Result<(), SolflowError> {
+    let src = Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let tmp = dest.with_extension("db.tmp");
+    let _ = std::fs::remove_file(&tmp);
+
+    {
+        let mut dst = Connection::open(&tmp)?;
+        Backup::new(&src, &mut dst)?.run_to_completion(PAGES_PER_STEP, STEP_PAUSE, None)?;
+        // The live file is in WAL mode; make the snapshot self-contained
+        dst.pragma_update(None, "journal_mode", "DELETE")?;
+    }
+
+    std::fs::rename(&tmp, dest)?;
+    Ok(())
+}
+
+/// Remove all but the newest `keep` timestamped snapshots of `name`
+fn prune_snapshots(dir: &Path, name: &str, keep: usize) -> std::io::Result<usize> {
+    let prefix = format!("{}-", name);
+    let latest = format!("{}-latest.db", name);
+    let mut snapshots: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|f| f.to_str())
+                .is_some_and(|f| f.starts_with(&prefix) && f.ends_with(".db") && f != latest)
+        })
+        .collect();
+
+    // Timestamps sort lexically, so the oldest come first
+    snapshots.sort();
+    let excess = snapshots.len().saturating_sub(keep);
+    for path in &snapshots[..excess] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
+
+/// Snapshot every source once; returns the timestamped snapshot paths
+///
+/// Blocking; run it off the async runtime.
+pub fn run_backup_cycle(
+    config: &BackupConfig,
+    sources: &[BackupSource],
+    now: i64,
+) -> Result<Vec<PathBuf>, SolflowError> {
+    std::fs::create_dir_all(&config.dir)?;
+    let stamp = chrono::DateTime::<chrono::Utc>::from_timestamp(now, 0)
+        .map(|dt| dt.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_else(|| now.to_string());
+
+    let mut written = Vec::new();
+    for source in sources {
+        let path = config.dir.join(format!("{}-{}.db", source.name, stamp));
+        snapshot(&source.db_path, &path)?;
+
+        let latest = config.dir.join(format!("{}-latest.db", source.name));
+        let latest_tmp = latest.with_extension("db.tmp");
+        std::fs::copy(&path, &latest_tmp)?;
+        std::fs::rename(&latest_tmp, &latest)?;
+
+        prune_snapshots(&config.dir, &source.name, config.keep)?;
+
+        if let Some(cmd) = &config.upload_cmd {
+            upload(cmd, &path);
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Run the upload command for one snapshot; failures are logged, not fatal
+fn upload(cmd: &str, path: &Path) {
+    let cmd = cmd.replace("{path}", &path.display().to_string());
+    match std::process::Command::new("sh").arg("-c").arg(&cmd).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => log::warn!("⚠️  Backup upload exited with {}: {}", status, path.display()),
+        Err(e) => log::warn!("⚠️  Backup upload failed to start: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_cycle_snapshots_and_prunes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("live.db");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.pragma_update(None, "journal_mode", "WAL").unwrap();
+        conn.execute_batch("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1), (2);")
+            .unwrap();
+
+        let config = BackupConfig {
+            dir: dir.path().join("backups"),
+            interval_secs: 3600,
+            keep: 2,
+            upload_cmd: None,
+        };
+        let sources = backup_sources(db_path.to_str().unwrap(), &[]);
+
+        for now in [1_700_000_000, 1_700_003_600, 1_700_007_200] {
+            run_backup_cycle(&config, &sources, now).unwrap();
+        }
+
+        let mut names: Vec<String> = std::fs::read_dir(&config.dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "solflow-20231114T231320Z.db",
+                "solflow-20231115T001320Z.db",
+                "solflow-latest.db",
+            ]
+        );
+
+        let copy = Connection::open(config.dir.join("solflow-latest.db")).unwrap();
+        let count: i64 = copy.query_row("SELECT COUNT(*) FROM t", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 2);
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! Phase 1: Trait definition only (no SQLite implementation)
 
+use crate::error::SolflowError;
 use async_trait::async_trait;
 
 /// Trait for checking if a mint is blocked
@@ -42,7 +43,7 @@ pub trait BlocklistProvider {
         &self,
         mint: &str,
         now: i64,
-    ) -> Result<bool, Box<dyn std::error::Error>>;
+    ) -> Result<bool, SolflowError>;
 }
 
 // TODO: Phase 2 - Implement SqliteBlocklistProvider:
@@ -2,6 +2,10 @@
 //!
 //! Phase 4: Configuration management for pipeline runtime
 
+use super::fees::FeeModels;
+use super::lease::default_instance_id;
+use super::state::{OutlierMode, OutlierPolicy, WalletGrowthMetric};
+use super::window_set::WindowSet;
 use std::env;
 
 /// Configuration for pipeline runtime
@@ -29,6 +33,48 @@ pub struct PipelineConfig {
     
     /// Toggle between legacy (4 streamers) and unified (1 streamer with InstructionScanner)
     pub use_unified_streamer: bool,
+
+    /// Suppress signals for this many seconds after startup (windows still filling)
+    pub signal_warmup_secs: i64,
+
+    /// Suppress a token's signals for this many seconds after its state is cold-started
+    pub token_warmup_secs: i64,
+
+    /// Optional multi-database routing spec (see `routing` module)
+    pub db_routes: Option<String>,
+
+    /// Wallet count BREAKOUT uses: any active wallet or only new wallets
+    pub breakout_wallet_metric: WalletGrowthMetric,
+
+    /// Outlier-trade handling in window metrics
+    pub outlier_policy: OutlierPolicy,
+
+    /// Rolling windows computed per token (see `window_set` module)
+    pub windows: WindowSet,
+
+    /// Venue fee per source program (see `fees` module)
+    pub fee_models: FeeModels,
+
+    /// Elect a single writer among instances sharing the database
+    pub lease_enabled: bool,
+
+    /// Lease lifetime without renewal (standby takeover delay after a crash)
+    pub lease_ttl_secs: i64,
+
+    /// This instance's identity in `instance_leases`
+    pub instance_id: String,
+
+    /// Engine shards, each with its own ingestion task (see `shards` module)
+    pub engine_shards: usize,
+
+    /// Engine state snapshot file, restored on startup (see `snapshot` module)
+    pub snapshot_path: Option<String>,
+
+    /// Seconds between engine snapshots
+    pub snapshot_interval_secs: u64,
+
+    /// Snapshots older than this are not restored
+    pub snapshot_max_age_secs: i64,
 }
 
 impl PipelineConfig {
@@ -42,6 +88,20 @@ impl PipelineConfig {
     /// - `METADATA_UPDATE_INTERVAL_MS` (default: 60000)
     /// - `ENABLE_PIPELINE` (default: false)
     /// - `USE_UNIFIED_STREAMER` (default: true)
+    /// - `SIGNAL_WARMUP_SECS` (default: 300)
+    /// - `TOKEN_WARMUP_SECS` (default: 0)
+    /// - `DB_ROUTES` (default: unset, all writes go to SOLFLOW_DB_PATH)
+    /// - `BREAKOUT_WALLET_METRIC` (default: any; `new` counts only first-time wallets)
+    /// - `OUTLIER_MODE` (default: off; `cap` or `exclude` trades above the threshold)
+    /// - `OUTLIER_MAX_STDDEV` (default: 4.0 standard deviations above mean trade size)
+    /// - `PIPELINE_WINDOWS` (default: 60,300,900; extra windows such as `30s,1h`, core windows always kept)
+    /// - `FEE_MODELS` (default: built-in venue fees; overrides such as `PumpSwap=30,*=25` in bps)
+    /// - `INSTANCE_LEASE_ENABLED` (default: true)
+    /// - `INSTANCE_LEASE_TTL_SECS` (default: 30)
+    /// - `INSTANCE_ID` (default: `$HOSTNAME:<pid>`)
+    /// - `ENGINE_SNAPSHOT_PATH` (default: unset, no snapshots)
+    /// - `ENGINE_SNAPSHOT_INTERVAL_SECS` (default: 60)
+    /// - `ENGINE_SNAPSHOT_MAX_AGE_SECS` (default: 3600)
     pub fn from_env() -> Self {
         Self {
             db_path: env::var("SOLFLOW_DB_PATH")
@@ -76,6 +136,83 @@ impl PipelineConfig {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(true),
+            
+            signal_warmup_secs: env::var("SIGNAL_WARMUP_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300),
+            
+            token_warmup_secs: env::var("TOKEN_WARMUP_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            
+            db_routes: env::var("DB_ROUTES").ok().filter(|s| !s.trim().is_empty()),
+            
+            breakout_wallet_metric: env::var("BREAKOUT_WALLET_METRIC")
+                .ok()
+                .and_then(|s| WalletGrowthMetric::parse(&s))
+                .unwrap_or_default(),
+            
+            outlier_policy: OutlierPolicy {
+                mode: env::var("OUTLIER_MODE")
+                    .ok()
+                    .and_then(|s| OutlierMode::parse(&s))
+                    .unwrap_or_default(),
+                max_stddev: env::var("OUTLIER_MAX_STDDEV")
+                    .ok()
+                    .and_then(|s| s.parse().ok())
+                    .filter(|n: &f64| *n > 0.0)
+                    .unwrap_or(OutlierPolicy::default().max_stddev),
+            },
+            
+            windows: env::var("PIPELINE_WINDOWS")
+                .ok()
+                .and_then(|s| {
+                    WindowSet::parse(&s)
+                        .map_err(|e| log::warn!("⚠️  Ignoring PIPELINE_WINDOWS: {}", e))
+                        .ok()
+                })
+                .unwrap_or_default(),
+            
+            fee_models: FeeModels::from_env(),
+            
+            lease_enabled: env::var("INSTANCE_LEASE_ENABLED")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(true),
+            
+            lease_ttl_secs: env::var("INSTANCE_LEASE_TTL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|n: &i64| *n > 0)
+                .unwrap_or(30),
+            
+            instance_id: env::var("INSTANCE_ID")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(default_instance_id),
+            
+            engine_shards: env::var("PIPELINE_SHARDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|n: &usize| *n > 0)
+                .unwrap_or(1),
+            
+            snapshot_path: env::var("ENGINE_SNAPSHOT_PATH")
+                .ok()
+                .filter(|s| !s.trim().is_empty()),
+            
+            snapshot_interval_secs: env::var("ENGINE_SNAPSHOT_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|n: &u64| *n > 0)
+                .unwrap_or(60),
+            
+            snapshot_max_age_secs: env::var("ENGINE_SNAPSHOT_MAX_AGE_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3600),
         }
     }
 }
@@ -100,6 +237,13 @@ mod tests {
         assert_eq!(config.price_interval_ms, 10_000);
         assert_eq!(config.metadata_interval_ms, 60_000);
         assert_eq!(config.enabled, false);
+        assert_eq!(config.signal_warmup_secs, 300);
+        assert_eq!(config.token_warmup_secs, 0);
+        assert_eq!(config.breakout_wallet_metric, WalletGrowthMetric::AnyActivity);
+        assert_eq!(config.outlier_policy, OutlierPolicy::default());
+        assert_eq!(config.windows, WindowSet::default());
+        assert!(config.lease_enabled);
+        assert_eq!(config.lease_ttl_secs, 30);
     }
     
     #[test]
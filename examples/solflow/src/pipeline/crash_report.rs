@@ -0,0 +1,199 @@
+//! Crash report bundles
+//!
+//! A panic or fatal error used to leave only a stack trace. The runtime now
+//! writes a JSON bundle to `CRASH_REPORT_DIR` with:
+//! - the panic message / error and a backtrace
+//! - the last `CRASH_REPORT_TRADES` trades the engine processed
+//! - engine summary stats as of the last trade
+//! - the pipeline config and environment, with secrets redacted (see
+//!   `streamer_core::secrets`)
+//!
+//! The reporter keeps its own copies of trades and stats: the panic hook
+//! must not touch the engine, whose mutex may be held (or poisoned) by the
+//! panicking thread.
+//!
+//! Environment variables:
+//! - `CRASH_REPORT_DIR`: Bundle output directory (default: crash_reports)
+//! - `CRASH_REPORT_TRADES`: Recent trades kept for the bundle (default: 200, 0 = disabled)
+
+use super::types::TradeEvent;
+use crate::streamer_core::secrets::redact_env_value;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Engine counters copied into the reporter on every trade
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EngineStats {
+    pub trades_processed: u64,
+    pub tracked_mints: usize,
+    pub touched_mints: usize,
+    pub started_at: i64,
+    pub last_trade_at: i64,
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    recent_trades: VecDeque<TradeEvent>,
+    stats: EngineStats,
+}
+
+fn trade_json(trade: &TradeEvent) -> serde_json::Value {
+    json!({
+        "timestamp": trade.timestamp,
+        "mint": trade.mint,
+        "direction": format!("{:?}", trade.direction),
+        "sol_amount": trade.sol_amount,
+        "token_amount": trade.token_amount,
+        "user_account": trade.user_account,
+        "source_program": trade.source_program,
+        "signature": trade.signature,
+        "slot": trade.slot,
+    })
+}
+
+/// Collects crash context and writes bundles; cheap to clone
+#[derive(Debug, Clone)]
+pub struct CrashReporter {
+    snapshot: Arc<Mutex<Snapshot>>,
+    /// Debug dump of the pipeline config, set once it is loaded
+    config: Arc<Mutex<Option<String>>>,
+    max_trades: usize,
+    output_dir: PathBuf,
+}
+
+impl CrashReporter {
+    pub fn new(max_trades: usize, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            snapshot: Arc::new(Mutex::new(Snapshot::default())),
+            config: Arc::new(Mutex::new(None)),
+            max_trades,
+            output_dir: output_dir.into(),
+        }
+    }
+
+    /// Create from the environment (see module docs)
+    pub fn from_env() -> Self {
+        let max_trades = std::env::var("CRASH_REPORT_TRADES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(200);
+        let output_dir =
+            std::env::var("CRASH_REPORT_DIR").unwrap_or_else(|_| "crash_reports".to_string());
+        Self::new(max_trades, output_dir)
+    }
+
+    pub fn output_dir(&self) -> &PathBuf {
+        &self.output_dir
+    }
+
+    pub fn set_config(&self, config: &impl std::fmt::Debug) {
+        *self.config.lock().unwrap() = Some(format!("{:#?}", config));
+    }
+
+    /// Remember a processed trade and the engine stats after it
+    pub fn record_trade(&self, trade: &TradeEvent, stats: EngineStats) {
+        let mut snapshot = self.snapshot.lock().unwrap();
+        if self.max_trades > 0 {
+            if snapshot.recent_trades.len() == self.max_trades {
+                snapshot.recent_trades.pop_front();
+            }
+            snapshot.recent_trades.push_back(trade.clone());
+        }
+        snapshot.stats = stats;
+    }
+
+    fn bundle(&self, reason: &str, backtrace: &str, written_at: i64) -> serde_json::Value {
+        // try_lock: the panicking thread may hold these
+        let (stats, trades) = match self.snapshot.try_lock() {
+            Ok(snapshot) => (
+                json!(snapshot.stats),
+                snapshot.recent_trades.iter().map(trade_json).collect::<Vec<_>>(),
+            ),
+            Err(_) => (json!(null), Vec::new()),
+        };
+        let config = self.config.try_lock().ok().and_then(|c| c.clone());
+        let mut environment: Vec<(String, String)> = std::env::vars()
+            .map(|(name, value)| {
+                let value = redact_env_value(&name, &value);
+                (name, value)
+            })
+            .collect();
+        environment.sort();
+
+        json!({
+            "reason": reason,
+            "written_at": written_at,
+            "version": env!("CARGO_PKG_VERSION"),
+            "backtrace": backtrace,
+            "engine_stats": stats,
+            "recent_trades": trades,
+            "pipeline_config": config,
+            "environment": environment.into_iter().collect::<serde_json::Map<_, _>>(),
+        })
+    }
+
+    /// Write a bundle; returns its path
+    pub fn write_bundle(&self, reason: &str, backtrace: &str) -> std::io::Result<PathBuf> {
+        let written_at = chrono::Utc::now().timestamp();
+        let json = serde_json::to_string_pretty(&self.bundle(reason, backtrace, written_at))?;
+        std::fs::create_dir_all(&self.output_dir)?;
+        let path = self
+            .output_dir
+            .join(format!("crash-{}-{}.json", written_at, std::process::id()));
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// Write a bundle on panic, then run the previous hook
+    pub fn install_panic_hook(&self) {
+        let reporter = self.clone();
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+            match reporter.write_bundle(&format!("panic: {}", info), &backtrace) {
+                Ok(path) => eprintln!("💥 Crash report written to {}", path.display()),
+                Err(e) => eprintln!("💥 Failed to write crash report: {}", e),
+            }
+            previous(info);
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(timestamp: i64) -> TradeEvent {
+        TradeEvent {
+            signature: format!("sig{}", timestamp),
+            ..TradeEvent::test_buy("mint1", timestamp)
+        }
+    }
+
+    #[test]
+    fn test_bundle_keeps_last_trades() {
+        let dir = tempfile::tempdir().unwrap();
+        let reporter = CrashReporter::new(3, dir.path());
+        reporter.set_config(&"PipelineConfig { db_path: \"x.db\" }");
+        for i in 0..5 {
+            let stats = EngineStats {
+                trades_processed: i + 1,
+                ..Default::default()
+            };
+            reporter.record_trade(&trade(1000 + i as i64), stats);
+        }
+
+        let path = reporter.write_bundle("fatal error: test", "").unwrap();
+        let bundle: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(bundle["reason"], "fatal error: test");
+        assert_eq!(bundle["engine_stats"]["trades_processed"], 5);
+        let trades = bundle["recent_trades"].as_array().unwrap();
+        assert_eq!(trades.len(), 3);
+        assert_eq!(trades[0]["signature"], "sig1002");
+        assert!(bundle["pipeline_config"].as_str().unwrap().contains("x.db"));
+    }
+}
@@ -2,14 +2,18 @@
 //!
 //! Phase 3-C: SQLite implementation with rusqlite
 //! Phase 4: Schema migration loader added
+//! Writes run on a dedicated thread behind a bounded queue (see `SqliteAggregateWriter`)
 
-// TODO: Phase 4 - Add connection pooling for concurrent writes
-
+use super::audit_trades::AuditTrade;
+use super::sessions::SessionRollup;
 use super::signals::TokenSignal;
 use super::types::AggregatedTokenState;
+use crate::error::{codes, SolflowError};
+use crate::streamer_core::metrics;
 use async_trait::async_trait;
 use rusqlite::Connection;
 use std::fs;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 
@@ -32,7 +36,7 @@ pub trait AggregateDbWriter: Send + Sync {
     async fn write_aggregates(
         &self,
         aggregates: Vec<AggregatedTokenState>,
-    ) -> Result<(), Box<dyn std::error::Error>>;
+    ) -> Result<(), SolflowError>;
 
     /// Write signal event to token_signals table
     ///
@@ -49,7 +53,62 @@ pub trait AggregateDbWriter: Send + Sync {
     async fn write_signal(
         &self,
         signal: TokenSignal,
-    ) -> Result<(), Box<dyn std::error::Error>>;
+    ) -> Result<(), SolflowError>;
+
+    /// Write a batch of signal events to token_signals in one transaction
+    ///
+    /// SQL reference: `/sql/03_token_signals.sql`
+    ///
+    /// Loads `mint_blocklist` once and drops signals for blocked mints (not
+    /// an error, unlike `write_signal`). Returns the signals written.
+    async fn write_signals(
+        &self,
+        signals: Vec<TokenSignal>,
+    ) -> Result<Vec<TokenSignal>, SolflowError>;
+
+    /// Write a key/value metric to the system_metrics table
+    ///
+    /// SQL reference: `/sql/04_system_metrics.sql`
+    ///
+    /// Operation: UPSERT on key (value_json must be valid JSON)
+    async fn write_system_metric(
+        &self,
+        key: &str,
+        value_json: &str,
+    ) -> Result<(), SolflowError>;
+
+    /// Write trading-session totals to the session_rollups table
+    ///
+    /// SQL reference: `/sql/11_session_rollups.sql`
+    ///
+    /// Operation: UPSERT on (mint, session_date, session)
+    async fn write_session_rollups(
+        &self,
+        rollups: Vec<SessionRollup>,
+    ) -> Result<(), SolflowError>;
+
+    /// Append raw trades to the audit_trades table and drop expired rows
+    ///
+    /// SQL reference: `/sql/12_audit_trades.sql`
+    ///
+    /// Operation: INSERT, then DELETE rows with `timestamp < prune_before`
+    async fn write_audit_trades(
+        &self,
+        trades: Vec<AuditTrade>,
+        prune_before: i64,
+    ) -> Result<(), SolflowError>;
+
+    /// Drop `details_json` from up to `limit` signals created before `before`
+    ///
+    /// SQL reference: `/sql/03_token_signals.sql`
+    ///
+    /// Operation: UPDATE, oldest first; severity and score are kept.
+    /// Returns the number of signals compacted.
+    async fn compact_signal_details(
+        &self,
+        before: i64,
+        limit: usize,
+    ) -> Result<usize, SolflowError>;
 
     /// Downcast helper for accessing concrete implementation
     ///
@@ -78,13 +137,20 @@ pub trait AggregateDbWriter: Send + Sync {
 pub fn run_schema_migrations(
     conn: &mut Connection,
     schema_dir: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), SolflowError> {
     let schema_path = Path::new(schema_dir);
     
     if !schema_path.exists() {
-        return Err(format!("Schema directory not found: {}", schema_dir).into());
+        return Err(SolflowError::db(
+            codes::DB_SCHEMA,
+            format!("Schema directory not found: {}", schema_dir),
+        ));
     }
 
+    // Only takes effect before the first table is created; lets the retention
+    // manager hand freed pages back with PRAGMA incremental_vacuum
+    conn.pragma_update(None, "auto_vacuum", "INCREMENTAL")?;
+
     // Enable WAL mode for better concurrency (Phase 4 requirement)
     // Note: PRAGMA journal_mode returns results, so we use execute instead of query
     conn.pragma_update(None, "journal_mode", "WAL")?;
@@ -109,6 +175,9 @@ pub fn run_schema_migrations(
         log::info!("   ├─ Executing: {}", filename);
         
         let sql_content = fs::read_to_string(&path)?;
+
+        // Backfill before each file so its indexes can reference added columns
+        backfill_added_columns(conn)?;
         
         // Execute the SQL file (expects IF NOT EXISTS clauses)
         conn.execute_batch(&sql_content)?;
@@ -116,17 +185,190 @@ pub fn run_schema_migrations(
         log::info!("   └─ ✅ Success: {}", filename);
     }
 
+    // CREATE TABLE IF NOT EXISTS leaves older tables untouched, so columns
+    // added to the DDL after a table was created are backfilled here
+    backfill_added_columns(conn)?;
+
     log::info!("✅ All schema migrations completed successfully");
     
     Ok(())
 }
 
+/// Columns added to existing tables after their initial DDL
+///
+/// Each entry mirrors a column declared in `/sql/`: (table, column, declaration).
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("token_aggregates", "new_wallets_300s", "INTEGER"),
+    ("token_aggregates", "fees_paid_300s_sol", "REAL"),
+    ("token_aggregates", "program_breakdown_300s_json", "TEXT"),
+    ("token_aggregates", "early_buyers_count", "INTEGER"),
+    ("token_aggregates", "early_holder_retention", "REAL"),
+    ("token_aggregates", "fresh_wallet_ratio_60s", "REAL"),
+    ("token_aggregates", "fresh_wallet_ratio_300s", "REAL"),
+    ("token_aggregates", "fresh_wallet_ratio_900s", "REAL"),
+    ("token_aggregates", "unique_wallets_3600s", "INTEGER"),
+    ("token_aggregates", "unique_wallets_7200s", "INTEGER"),
+    ("token_aggregates", "unique_wallets_14400s", "INTEGER"),
+    ("token_aggregates", "net_flow_sparkline_json", "TEXT"),
+    ("token_aggregates", "last_trade_slot", "INTEGER"),
+    ("token_aggregates", "last_trade_signature", "TEXT"),
+    ("token_aggregates", "net_flow_60s_final_sol", "REAL"),
+    ("token_aggregates", "net_flow_300s_final_sol", "REAL"),
+    ("token_aggregates", "net_flow_900s_final_sol", "REAL"),
+    ("token_aggregates", "buy_count_300s_final", "INTEGER"),
+    ("token_aggregates", "sell_count_300s_final", "INTEGER"),
+    ("token_aggregates", "unfinalized_trades_300s", "INTEGER"),
+    ("token_aggregates", "window_metrics_json", "TEXT"),
+    ("token_aggregates", "venue_fees_300s_sol", "REAL"),
+    ("token_aggregates", "fee_adjusted_net_flow_300s_sol", "REAL"),
+    ("token_aggregates", "liquidity_sol", "REAL"),
+    ("token_aggregates", "priority_fees_300s_sol", "REAL"),
+    ("token_aggregates", "jito_tips_300s_sol", "REAL"),
+    ("token_aggregates", "unique_clusters_300s", "INTEGER"),
+    ("token_aggregates", "top10_holder_pct", "REAL"),
+    ("token_aggregates", "last_data_gap_at", "INTEGER"),
+    ("token_aggregates", "pool_price_sol", "REAL"),
+    ("audit_trades", "venue_fee_sol", "REAL"),
+    ("audit_trades", "net_sol", "REAL"),
+    ("dca_activity_buckets", "last_slot", "INTEGER"),
+    ("dca_activity_buckets", "last_signature", "TEXT"),
+    ("token_signals", "source", "TEXT NOT NULL DEFAULT 'onchain'"),
+    ("token_signals", "sample_signatures", "TEXT"),
+    ("token_metadata", "image_url", "TEXT"),
+    ("token_metadata", "price_usd", "REAL"),
+    ("token_metadata", "market_cap", "REAL"),
+    ("token_metadata", "follow_price", "INTEGER NOT NULL DEFAULT 0"),
+    ("token_metadata", "blocked", "INTEGER NOT NULL DEFAULT 0"),
+    ("token_metadata", "mint_created_at", "INTEGER"),
+    ("token_metadata", "risk_flags", "INTEGER"),
+    ("token_metadata", "risk_checked_at", "INTEGER"),
+];
+
+/// Add every `ADDED_COLUMNS` entry whose table already exists
+fn backfill_added_columns(conn: &Connection) -> Result<(), SolflowError> {
+    for (table, column, decl) in ADDED_COLUMNS {
+        add_column_if_missing(conn, table, column, decl)?;
+    }
+    Ok(())
+}
+
+/// Add a column to a table unless it already exists
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    decl: &str,
+) -> Result<(), SolflowError> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns: Vec<String> = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .collect();
+
+    // Table not created yet: its DDL will include the column
+    if columns.is_empty() {
+        return Ok(());
+    }
+
+    if !columns.iter().any(|name| name == column) {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl), [])?;
+        log::info!("   ├─ Added column {}.{}", table, column);
+    }
+
+    Ok(())
+}
+
+/// Width of the buckets in `dca_activity_rollups`
+pub const DCA_ROLLUP_SECS: i64 = 300;
+
+/// DCA sparkline bucket settings (DCA_BUCKET_* env vars)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DcaBucketConfig {
+    /// Bucket width in `dca_activity_buckets` (DCA_BUCKET_SECS, default: 60)
+    pub bucket_secs: i64,
+    /// How long fine buckets are kept (DCA_BUCKET_RETENTION_SECS, default: 7200)
+    pub retention_secs: i64,
+    /// How long 5-minute rollups are kept (DCA_ROLLUP_RETENTION_SECS, default: 86400)
+    pub rollup_retention_secs: i64,
+}
+
+impl Default for DcaBucketConfig {
+    fn default() -> Self {
+        Self {
+            bucket_secs: 60,
+            retention_secs: 7200,
+            rollup_retention_secs: 86_400,
+        }
+    }
+}
+
+impl DcaBucketConfig {
+    /// Load from env, falling back to the defaults
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str, default: i64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok())
+                .filter(|secs| *secs > 0)
+                .unwrap_or(default)
+        };
+        Self {
+            bucket_secs: var("DCA_BUCKET_SECS", defaults.bucket_secs),
+            retention_secs: var("DCA_BUCKET_RETENTION_SECS", defaults.retention_secs),
+            rollup_retention_secs: var("DCA_ROLLUP_RETENTION_SECS", defaults.rollup_retention_secs),
+        }
+    }
+}
+
+/// `mint_blocklist` entries loaded once for a batch of signals
+#[derive(Debug, Clone, Default)]
+pub struct SignalBlocklist {
+    /// Mint → expiry (None = permanent)
+    entries: HashMap<String, Option<i64>>,
+}
+
+impl SignalBlocklist {
+    /// Entries that can still block signals created at or after `since`
+    pub fn load(conn: &Connection, since: i64) -> Result<Self, SolflowError> {
+        let mut stmt = conn.prepare(
+            "SELECT mint, expires_at FROM mint_blocklist
+             WHERE expires_at IS NULL OR expires_at > ?",
+        )?;
+        let entries = stmt
+            .query_map([since], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Same rule as `check_blocklist`, at the signal's creation time
+    pub fn blocks(&self, signal: &TokenSignal) -> bool {
+        self.entries
+            .get(&signal.mint)
+            .is_some_and(|expires_at| expires_at.is_none_or(|expires_at| expires_at > signal.created_at))
+    }
+}
+
+/// Default `SQLITE_WRITE_QUEUE_CAPACITY`
+const DEFAULT_WRITE_QUEUE_CAPACITY: usize = 64;
+
+/// Work run on the writer thread with the connection to itself
+type WriteJob = Box<dyn FnOnce(&mut Connection) + Send>;
+
 /// SQLite implementation of AggregateDbWriter
 ///
-/// Phase 3-C: Basic implementation without pooling or WAL mode
-/// Phase 4: Will add connection pooling and WAL mode
+/// Trait writes are queued to a dedicated `sqlite-writer` thread, so large
+/// batch UPSERTs never run on (and stall) the async executor. The queue is
+/// bounded (`SQLITE_WRITE_QUEUE_CAPACITY`, default 64 jobs): when it is full,
+/// callers wait asynchronously and `solflow_sqlite_write_queue_full` counts
+/// it; `solflow_sqlite_write_queue_depth` samples the backlog per write.
+///
+/// The synchronous helpers (`write_aggregates_batched`, cleanup, blocklist
+/// lookups) share the same connection and run on the calling thread.
 pub struct SqliteAggregateWriter {
     conn: Arc<Mutex<Connection>>,
+    dca_buckets: DcaBucketConfig,
+    jobs: tokio::sync::mpsc::Sender<WriteJob>,
 }
 
 impl SqliteAggregateWriter {
@@ -137,18 +379,81 @@ impl SqliteAggregateWriter {
     ///
     /// Note: Does NOT create database or schema. Caller must ensure database
     /// exists and has schema from `/sql/*.sql` files.
-    pub fn new(db_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(db_path: &str) -> Result<Self, SolflowError> {
+        let queue_capacity = std::env::var("SQLITE_WRITE_QUEUE_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|capacity: &usize| *capacity > 0)
+            .unwrap_or(DEFAULT_WRITE_QUEUE_CAPACITY);
+        Self::with_queue_capacity(db_path, queue_capacity)
+    }
+
+    /// Create a writer whose write queue holds up to `queue_capacity` jobs
+    pub fn with_queue_capacity(db_path: &str, queue_capacity: usize) -> Result<Self, SolflowError> {
         let conn = Connection::open(db_path)?;
         
         // Enable WAL mode for better write concurrency
         conn.pragma_update(None, "journal_mode", "WAL")?;
         log::info!("📘 SQLite: WAL mode enabled");
+
+        let conn = Arc::new(Mutex::new(conn));
+        let (jobs, mut queue) = tokio::sync::mpsc::channel::<WriteJob>(queue_capacity.max(1));
+        let writer_conn = conn.clone();
+        std::thread::Builder::new()
+            .name("sqlite-writer".to_string())
+            .spawn(move || {
+                // Ends once the writer (every sender) is dropped
+                while let Some(job) = queue.blocking_recv() {
+                    let mut conn = writer_conn.lock().unwrap();
+                    job(&mut conn);
+                }
+            })?;
         
         Ok(Self {
-            conn: Arc::new(Mutex::new(conn)),
+            conn,
+            dca_buckets: DcaBucketConfig::from_env(),
+            jobs,
         })
     }
 
+    /// Write jobs waiting for the writer thread
+    pub fn queue_depth(&self) -> usize {
+        self.jobs.max_capacity() - self.jobs.capacity()
+    }
+
+    /// Run `job` on the writer thread and wait for its result
+    ///
+    /// Waits without blocking the executor, including while the queue is full.
+    async fn run<T, F>(&self, job: F) -> Result<T, SolflowError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, SolflowError> + Send + 'static,
+    {
+        let writer_stopped = || SolflowError::db(codes::DB_WRITER, "SQLite writer thread stopped");
+
+        let (done, result) = tokio::sync::oneshot::channel();
+        let job: WriteJob = Box::new(move |conn| {
+            let _ = done.send(job(conn));
+        });
+        match self.jobs.try_send(job) {
+            Ok(()) => {}
+            Err(tokio::sync::mpsc::error::TrySendError::Full(job)) => {
+                metrics::increment_counter(metrics::SQLITE_WRITE_QUEUE_FULL, 1).await;
+                self.jobs.send(job).await.map_err(|_| writer_stopped())?;
+            }
+            Err(tokio::sync::mpsc::error::TrySendError::Closed(_)) => return Err(writer_stopped()),
+        }
+        metrics::update_gauge(metrics::SQLITE_WRITE_QUEUE_DEPTH, self.queue_depth() as f64).await;
+
+        result.await.map_err(|_| writer_stopped())?
+    }
+
+    /// Override the DCA bucket settings read from env by `new`
+    pub fn with_dca_bucket_config(mut self, config: DcaBucketConfig) -> Self {
+        self.dca_buckets = config;
+        self
+    }
+
     /// Check if a mint is in the blocklist
     ///
     /// Returns: true if mint is blocked, false if allowed
@@ -156,7 +461,7 @@ impl SqliteAggregateWriter {
         conn: &Connection,
         mint: &str,
         now: i64,
-    ) -> Result<bool, Box<dyn std::error::Error>> {
+    ) -> Result<bool, SolflowError> {
         let mut stmt = conn.prepare(
             "SELECT mint FROM mint_blocklist 
              WHERE mint = ? AND (expires_at IS NULL OR expires_at > ?)",
@@ -170,68 +475,57 @@ impl SqliteAggregateWriter {
     ///
     /// Phase 7: DCA Sparkline Foundation (feature/dca-sparkline-backend)
     ///
-    /// Computes 1-minute bucket timestamp and writes DCA buy count.
-    /// Uses UPSERT (INSERT OR REPLACE) for idempotency.
+    /// Computes the `bucket_secs` bucket timestamp and writes DCA buy count.
+    /// Uses UPSERT (INSERT OR REPLACE) for idempotency. The 5-minute rollup
+    /// row keeps the highest count seen in its bucket.
     ///
     /// Arguments:
     /// - `tx`: Active transaction (for batch atomicity)
     /// - `mint`: Token mint address
-    /// - `timestamp`: Current timestamp (will be floored to minute boundary)
+    /// - `timestamp`: Current timestamp (will be floored to the bucket boundary)
+    /// - `bucket_secs`: Bucket width (DCA_BUCKET_SECS)
     /// - `buy_count`: Number of DCA buys in this bucket
+    /// - `last_slot` / `last_signature`: Most recent DCA buy (for tracing)
     ///
     /// Note: This is called within write_aggregates transaction for atomic writes.
     fn write_dca_buckets(
         tx: &rusqlite::Transaction,
         mint: &str,
         timestamp: i64,
+        bucket_secs: i64,
         buy_count: i32,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Floor timestamp to 60-second boundary
-        let bucket_timestamp = (timestamp / 60) * 60;
+        last_slot: Option<i64>,
+        last_signature: Option<&str>,
+    ) -> Result<(), SolflowError> {
+        // Floor timestamp to the bucket boundary
+        let bucket_timestamp = (timestamp / bucket_secs) * bucket_secs;
 
         tx.execute(
             r#"
             INSERT OR REPLACE INTO dca_activity_buckets (
-                mint, bucket_timestamp, buy_count
-            ) VALUES (?, ?, ?)
+                mint, bucket_timestamp, buy_count, last_slot, last_signature
+            ) VALUES (?, ?, ?, ?, ?)
             "#,
-            rusqlite::params![mint, bucket_timestamp, buy_count],
+            rusqlite::params![mint, bucket_timestamp, buy_count, last_slot, last_signature],
         )?;
 
-        Ok(())
-    }
-
-    /// Clean up old DCA activity buckets
-    ///
-    /// Phase 7: DCA Sparkline Foundation
-    ///
-    /// Deletes buckets older than 2 hours (7200 seconds) to prevent unbounded growth.
-    /// Should be called periodically (every 5 minutes recommended).
-    ///
-    /// Returns: Number of rows deleted
-    pub fn cleanup_old_dca_buckets(&self) -> Result<usize, Box<dyn std::error::Error>> {
-        let conn = self.conn.lock().unwrap();
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() as i64;
-        
-        let cutoff = now - 7200; // 2 hours
-
-        let deleted = conn.execute(
-            "DELETE FROM dca_activity_buckets WHERE bucket_timestamp < ?",
-            rusqlite::params![cutoff],
+        let rollup_timestamp = (timestamp / DCA_ROLLUP_SECS) * DCA_ROLLUP_SECS;
+        tx.execute(
+            r#"
+            INSERT INTO dca_activity_rollups (
+                mint, bucket_timestamp, buy_count, last_slot, last_signature
+            ) VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(mint, bucket_timestamp) DO UPDATE SET
+                buy_count = MAX(buy_count, excluded.buy_count),
+                last_slot = COALESCE(excluded.last_slot, last_slot),
+                last_signature = COALESCE(excluded.last_signature, last_signature)
+            "#,
+            rusqlite::params![mint, rollup_timestamp, buy_count, last_slot, last_signature],
         )?;
 
-        if deleted > 0 {
-            log::debug!("🧹 Cleaned up {} old DCA buckets (older than {})", deleted, cutoff);
-        }
-
-        Ok(deleted)
+        Ok(())
     }
-}
 
-#[async_trait]
-impl AggregateDbWriter for SqliteAggregateWriter {
     /// Write aggregate metrics to token_aggregates table
     ///
     /// Phase 5: Batched writes to reduce lock duration
@@ -242,10 +536,37 @@ impl AggregateDbWriter for SqliteAggregateWriter {
     ///
     /// Writes are batched (default: 500 mints per transaction) to avoid long-running
     /// monolithic transactions that block for multiple seconds.
-    async fn write_aggregates(
+    ///
+    /// `write_buckets` controls whether DCA activity buckets are written in the
+    /// same transaction (disabled when buckets are routed to another database).
+    ///
+    /// Runs on the calling thread; async callers use `write_aggregates_queued`.
+    pub fn write_aggregates_batched(
+        &self,
+        aggregates: &[AggregatedTokenState],
+        write_buckets: bool,
+    ) -> Result<(), SolflowError> {
+        let mut conn = self.conn.lock().unwrap();
+        Self::upsert_aggregates(&mut conn, aggregates, write_buckets, &self.dca_buckets)
+    }
+
+    /// `write_aggregates_batched` on the writer thread
+    pub async fn write_aggregates_queued(
         &self,
         aggregates: Vec<AggregatedTokenState>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        write_buckets: bool,
+    ) -> Result<(), SolflowError> {
+        let dca_buckets = self.dca_buckets;
+        self.run(move |conn| Self::upsert_aggregates(conn, &aggregates, write_buckets, &dca_buckets))
+            .await
+    }
+
+    fn upsert_aggregates(
+        conn: &mut Connection,
+        aggregates: &[AggregatedTokenState],
+        write_buckets: bool,
+        dca_buckets: &DcaBucketConfig,
+    ) -> Result<(), SolflowError> {
         // Early return if nothing to write
         if aggregates.is_empty() {
             return Ok(());
@@ -267,8 +588,6 @@ impl AggregateDbWriter for SqliteAggregateWriter {
             batch_size
         );
 
-        let mut conn = self.conn.lock().unwrap();
-
         // Phase 5: Process in batches
         for (batch_idx, chunk) in aggregates.chunks(batch_size).enumerate() {
             let batch_start = std::time::Instant::now();
@@ -286,12 +605,24 @@ impl AggregateDbWriter for SqliteAggregateWriter {
                         buy_count_60s, sell_count_60s,
                         buy_count_300s, sell_count_300s,
                         buy_count_900s, sell_count_900s,
-                        unique_wallets_300s, bot_trades_300s, bot_wallets_300s,
+                        unique_wallets_300s, unique_clusters_300s, new_wallets_300s, fees_paid_300s_sol,
+                        venue_fees_300s_sol, fee_adjusted_net_flow_300s_sol, liquidity_sol, pool_price_sol,
+                        priority_fees_300s_sol, jito_tips_300s_sol,
+                        program_breakdown_300s_json,
+                        early_buyers_count, early_holder_retention,
+                        fresh_wallet_ratio_60s, fresh_wallet_ratio_300s, fresh_wallet_ratio_900s,
+                        unique_wallets_3600s, unique_wallets_7200s, unique_wallets_14400s,
+                        net_flow_sparkline_json,
+                        bot_trades_300s, bot_wallets_300s,
                         avg_trade_size_300s_sol, volume_300s_sol,
                         dca_buys_60s, dca_buys_300s, dca_buys_900s, dca_buys_3600s, dca_buys_14400s,
+                        net_flow_60s_final_sol, net_flow_300s_final_sol, net_flow_900s_final_sol,
+                        buy_count_300s_final, sell_count_300s_final, unfinalized_trades_300s,
+                        window_metrics_json,
                         price_usd, price_sol, market_cap_usd,
+                        last_trade_slot, last_trade_signature, last_data_gap_at,
                         updated_at, created_at
-                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                     ON CONFLICT(mint) DO UPDATE SET
                         source_program = excluded.source_program,
                         last_trade_timestamp = excluded.last_trade_timestamp,
@@ -308,6 +639,25 @@ impl AggregateDbWriter for SqliteAggregateWriter {
                         buy_count_900s = excluded.buy_count_900s,
                         sell_count_900s = excluded.sell_count_900s,
                         unique_wallets_300s = excluded.unique_wallets_300s,
+                        unique_clusters_300s = excluded.unique_clusters_300s,
+                        new_wallets_300s = excluded.new_wallets_300s,
+                        fees_paid_300s_sol = excluded.fees_paid_300s_sol,
+                        venue_fees_300s_sol = excluded.venue_fees_300s_sol,
+                        fee_adjusted_net_flow_300s_sol = excluded.fee_adjusted_net_flow_300s_sol,
+                        liquidity_sol = excluded.liquidity_sol,
+                        pool_price_sol = excluded.pool_price_sol,
+                        priority_fees_300s_sol = excluded.priority_fees_300s_sol,
+                        jito_tips_300s_sol = excluded.jito_tips_300s_sol,
+                        program_breakdown_300s_json = excluded.program_breakdown_300s_json,
+                        early_buyers_count = excluded.early_buyers_count,
+                        early_holder_retention = excluded.early_holder_retention,
+                        fresh_wallet_ratio_60s = excluded.fresh_wallet_ratio_60s,
+                        fresh_wallet_ratio_300s = excluded.fresh_wallet_ratio_300s,
+                        fresh_wallet_ratio_900s = excluded.fresh_wallet_ratio_900s,
+                        unique_wallets_3600s = excluded.unique_wallets_3600s,
+                        unique_wallets_7200s = excluded.unique_wallets_7200s,
+                        unique_wallets_14400s = excluded.unique_wallets_14400s,
+                        net_flow_sparkline_json = excluded.net_flow_sparkline_json,
                         bot_trades_300s = excluded.bot_trades_300s,
                         bot_wallets_300s = excluded.bot_wallets_300s,
                         avg_trade_size_300s_sol = excluded.avg_trade_size_300s_sol,
@@ -317,9 +667,19 @@ impl AggregateDbWriter for SqliteAggregateWriter {
                         dca_buys_900s = excluded.dca_buys_900s,
                         dca_buys_3600s = excluded.dca_buys_3600s,
                         dca_buys_14400s = excluded.dca_buys_14400s,
+                        net_flow_60s_final_sol = excluded.net_flow_60s_final_sol,
+                        net_flow_300s_final_sol = excluded.net_flow_300s_final_sol,
+                        net_flow_900s_final_sol = excluded.net_flow_900s_final_sol,
+                        buy_count_300s_final = excluded.buy_count_300s_final,
+                        sell_count_300s_final = excluded.sell_count_300s_final,
+                        unfinalized_trades_300s = excluded.unfinalized_trades_300s,
+                        window_metrics_json = excluded.window_metrics_json,
                         price_usd = excluded.price_usd,
                         price_sol = excluded.price_sol,
                         market_cap_usd = excluded.market_cap_usd,
+                        last_trade_slot = excluded.last_trade_slot,
+                        last_trade_signature = excluded.last_trade_signature,
+                        last_data_gap_at = excluded.last_data_gap_at,
                         updated_at = excluded.updated_at
                     "#,
                     rusqlite::params![
@@ -339,6 +699,25 @@ impl AggregateDbWriter for SqliteAggregateWriter {
                         agg.buy_count_900s,
                         agg.sell_count_900s,
                         agg.unique_wallets_300s,
+                        agg.unique_clusters_300s,
+                        agg.new_wallets_300s,
+                        agg.fees_paid_300s_sol,
+                        agg.venue_fees_300s_sol,
+                        agg.fee_adjusted_net_flow_300s_sol,
+                        agg.liquidity_sol,
+                        agg.pool_price_sol,
+                        agg.priority_fees_300s_sol,
+                        agg.jito_tips_300s_sol,
+                        agg.program_breakdown_300s_json,
+                        agg.early_buyers_count,
+                        agg.early_holder_retention,
+                        agg.fresh_wallet_ratio_60s,
+                        agg.fresh_wallet_ratio_300s,
+                        agg.fresh_wallet_ratio_900s,
+                        agg.unique_wallets_3600s,
+                        agg.unique_wallets_7200s,
+                        agg.unique_wallets_14400s,
+                        agg.net_flow_sparkline_json,
                         agg.bot_trades_300s,
                         agg.bot_wallets_300s,
                         agg.avg_trade_size_300s_sol,
@@ -348,9 +727,19 @@ impl AggregateDbWriter for SqliteAggregateWriter {
                         agg.dca_buys_900s,
                         agg.dca_buys_3600s,
                         agg.dca_buys_14400s,
+                        agg.net_flow_60s_final_sol,
+                        agg.net_flow_300s_final_sol,
+                        agg.net_flow_900s_final_sol,
+                        agg.buy_count_300s_final,
+                        agg.sell_count_300s_final,
+                        agg.unfinalized_trades_300s,
+                        agg.window_metrics_json,
                         agg.price_usd,
                         agg.price_sol,
                         agg.market_cap_usd,
+                        agg.last_trade_slot,
+                        agg.last_trade_signature,
+                        agg.last_data_gap_at,
                         agg.updated_at,
                         agg.created_at,
                     ],
@@ -359,13 +748,8 @@ impl AggregateDbWriter for SqliteAggregateWriter {
 
             // Phase 7: Write DCA activity buckets for sparkline visualization
             // Process DCA buckets for each aggregate in this batch
-            for agg in chunk {
-                if let Some(dca_3600s) = agg.dca_buys_3600s {
-                    // Only write buckets if there's DCA activity in the 1-hour window
-                    if dca_3600s > 0 {
-                        Self::write_dca_buckets(&tx, &agg.mint, agg.updated_at, dca_3600s)?;
-                    }
-                }
+            if write_buckets {
+                Self::write_dca_buckets_for(&tx, chunk, dca_buckets)?;
             }
 
             tx.commit()?;
@@ -388,53 +772,382 @@ impl AggregateDbWriter for SqliteAggregateWriter {
         Ok(())
     }
 
+    /// Write DCA activity buckets for every aggregate with 1-hour DCA activity
+    fn write_dca_buckets_for(
+        tx: &rusqlite::Transaction,
+        aggregates: &[AggregatedTokenState],
+        config: &DcaBucketConfig,
+    ) -> Result<(), SolflowError> {
+        for agg in aggregates {
+            if let Some(dca_3600s) = agg.dca_buys_3600s {
+                // Only write buckets if there's DCA activity in the 1-hour window
+                if dca_3600s > 0 {
+                    Self::write_dca_buckets(
+                        tx,
+                        &agg.mint,
+                        agg.updated_at,
+                        config.bucket_secs,
+                        dca_3600s,
+                        agg.last_dca_slot,
+                        agg.last_dca_signature.as_deref(),
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write only the DCA activity buckets for a set of aggregates
+    ///
+    /// Used when `dca_activity_buckets` is routed to a different database than
+    /// `token_aggregates` (see `routing`).
+    pub fn write_dca_activity(
+        &self,
+        aggregates: &[AggregatedTokenState],
+    ) -> Result<(), SolflowError> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        Self::write_dca_buckets_for(&tx, aggregates, &self.dca_buckets)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// `write_dca_activity` on the writer thread
+    pub async fn write_dca_activity_queued(
+        &self,
+        aggregates: Vec<AggregatedTokenState>,
+    ) -> Result<(), SolflowError> {
+        let dca_buckets = self.dca_buckets;
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            Self::write_dca_buckets_for(&tx, &aggregates, &dca_buckets)?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Load this database's mint_blocklist for signals created from `since`
+    pub async fn signal_blocklist(&self, since: i64) -> Result<SignalBlocklist, SolflowError> {
+        self.run(move |conn| SignalBlocklist::load(conn, since)).await
+    }
+
+    /// Check whether a mint is blocked in this database's mint_blocklist
+    pub async fn is_mint_blocked(&self, mint: &str, now: i64) -> Result<bool, SolflowError> {
+        let mint = mint.to_string();
+        self.run(move |conn| Self::check_blocklist(conn, &mint, now)).await
+    }
+
+    /// Clean up old DCA activity buckets and rollups
+    ///
+    /// Phase 7: DCA Sparkline Foundation
+    ///
+    /// Deletes buckets older than DCA_BUCKET_RETENTION_SECS (default 2 hours) and
+    /// rollups older than DCA_ROLLUP_RETENTION_SECS to prevent unbounded growth.
+    /// Should be called periodically (every 5 minutes recommended).
+    ///
+    /// Returns: Number of rows deleted
+    pub async fn cleanup_old_dca_buckets(&self) -> Result<usize, SolflowError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default();
+        self.cleanup_dca_buckets_at(now).await
+    }
+
+    /// Runs on the writer thread so it never waits out a batch on the executor
+    async fn cleanup_dca_buckets_at(&self, now: i64) -> Result<usize, SolflowError> {
+        let dca_buckets = self.dca_buckets;
+        self.run(move |conn| {
+            let cutoff = now - dca_buckets.retention_secs;
+
+            let mut deleted = conn.execute(
+                "DELETE FROM dca_activity_buckets WHERE bucket_timestamp < ?",
+                rusqlite::params![cutoff],
+            )?;
+            deleted += conn.execute(
+                "DELETE FROM dca_activity_rollups WHERE bucket_timestamp < ?",
+                rusqlite::params![now - dca_buckets.rollup_retention_secs],
+            )?;
+
+            if deleted > 0 {
+                log::debug!("🧹 Cleaned up {} old DCA buckets (older than {})", deleted, cutoff);
+            }
+
+            Ok(deleted)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl AggregateDbWriter for SqliteAggregateWriter {
+    /// Write aggregate metrics to token_aggregates table
+    ///
+    /// Queues `write_aggregates_batched`, including DCA activity buckets, on
+    /// the writer thread; the flush loop's task yields instead of blocking.
+    async fn write_aggregates(
+        &self,
+        aggregates: Vec<AggregatedTokenState>,
+    ) -> Result<(), SolflowError> {
+        self.write_aggregates_queued(aggregates, true).await
+    }
+
     /// Write signal event to token_signals table
     ///
     /// Checks mint_blocklist first, then inserts signal if allowed.
     ///
-    /// Note: For batch signal writes, consider collecting multiple signals
-    /// and calling this within a transaction loop externally.
+    /// Note: For batches, `write_signals` reads the blocklist once and uses a
+    /// single transaction.
     async fn write_signal(
         &self,
         signal: TokenSignal,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut conn = self.conn.lock().unwrap();
-
+    ) -> Result<(), SolflowError> {
         // Validate JSON if present
         if let Some(ref json) = signal.details_json {
             validate_json(json)?;
         }
 
-        // Use transaction for atomic blocklist check + insert
-        let tx = conn.transaction()?;
+        self.run(move |conn| {
+            // Use transaction for atomic blocklist check + insert
+            let tx = conn.transaction()?;
 
-        // Check blocklist
-        let blocked = Self::check_blocklist(&tx, &signal.mint, signal.created_at)?;
-        if blocked {
-            return Err(format!("Mint {} is blocked, signal not written", signal.mint).into());
-        }
+            // Check blocklist
+            let blocked = Self::check_blocklist(&tx, &signal.mint, signal.created_at)?;
+            if blocked {
+                return Err(SolflowError::db(
+                    codes::DB_MINT_BLOCKED,
+                    format!("Mint {} is blocked, signal not written", signal.mint),
+                ));
+            }
 
-        // Insert signal
-        tx.execute(
-            r#"
-            INSERT INTO token_signals (
-                mint, signal_type, window_seconds, severity, score, details_json, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?)
-            "#,
-            rusqlite::params![
-                signal.mint,
-                signal.signal_type.as_str(),
-                signal.window_seconds,
-                signal.severity,
-                signal.score,
-                signal.details_json,
-                signal.created_at,
-            ],
-        )?;
+            // Insert signal
+            tx.execute(
+                r#"
+                INSERT INTO token_signals (
+                    mint, signal_type, window_seconds, severity, score, details_json, created_at, source,
+                    sample_signatures
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                rusqlite::params![
+                    signal.mint,
+                    signal.signal_type.as_str(),
+                    signal.window_seconds,
+                    signal.severity,
+                    signal.score,
+                    signal.details_json,
+                    signal.created_at,
+                    signal.source,
+                    (!signal.sample_signatures.is_empty()).then(|| signal.sample_signatures.join(",")),
+                ],
+            )?;
 
-        tx.commit()?;
+            tx.commit()?;
 
-        Ok(())
+            Ok(())
+        })
+        .await
+    }
+
+    /// Write a batch of signals in one transaction
+    ///
+    /// The blocklist is read once inside the transaction and applied in
+    /// memory; signals with malformed details are skipped with a warning.
+    async fn write_signals(
+        &self,
+        signals: Vec<TokenSignal>,
+    ) -> Result<Vec<TokenSignal>, SolflowError> {
+        let signals: Vec<TokenSignal> = signals
+            .into_iter()
+            .filter(|signal| match signal.details_json.as_deref().map(validate_json) {
+                Some(Err(e)) => {
+                    log::warn!("⚠️  Signal not written (mint: {}, type: {:?}): {}", signal.mint, signal.signal_type, e);
+                    false
+                }
+                _ => true,
+            })
+            .collect();
+        let Some(since) = signals.iter().map(|signal| signal.created_at).min() else {
+            return Ok(Vec::new());
+        };
+
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            let blocklist = SignalBlocklist::load(&tx, since)?;
+            let allowed: Vec<TokenSignal> =
+                signals.into_iter().filter(|signal| !blocklist.blocks(signal)).collect();
+            {
+                let mut stmt = tx.prepare_cached(
+                    r#"
+                    INSERT INTO token_signals (
+                        mint, signal_type, window_seconds, severity, score, details_json, created_at, source,
+                        sample_signatures
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )?;
+                for signal in &allowed {
+                    stmt.execute(rusqlite::params![
+                        signal.mint,
+                        signal.signal_type.as_str(),
+                        signal.window_seconds,
+                        signal.severity,
+                        signal.score,
+                        signal.details_json,
+                        signal.created_at,
+                        signal.source,
+                        (!signal.sample_signatures.is_empty()).then(|| signal.sample_signatures.join(",")),
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(allowed)
+        })
+        .await
+    }
+
+    /// Write a key/value metric to the system_metrics table
+    ///
+    /// Validates JSON, then UPSERTs on key with the current timestamp.
+    async fn write_system_metric(
+        &self,
+        key: &str,
+        value_json: &str,
+    ) -> Result<(), SolflowError> {
+        validate_json(value_json)?;
+
+        let (key, value_json) = (key.to_string(), value_json.to_string());
+        self.run(move |conn| {
+            let now = chrono::Utc::now().timestamp();
+
+            conn.execute(
+                r#"
+                INSERT INTO system_metrics (key, value_json, updated_at)
+                VALUES (?, ?, ?)
+                ON CONFLICT(key) DO UPDATE SET
+                    value_json = excluded.value_json,
+                    updated_at = excluded.updated_at
+                "#,
+                rusqlite::params![key, value_json, now],
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Write trading-session totals to the session_rollups table
+    ///
+    /// Totals are cumulative for the session, so rows are overwritten.
+    async fn write_session_rollups(
+        &self,
+        rollups: Vec<SessionRollup>,
+    ) -> Result<(), SolflowError> {
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    r#"
+                    INSERT INTO session_rollups (
+                        mint, session_date, session, buy_sol, sell_sol, net_flow_sol,
+                        buy_count, sell_count, unique_wallets, updated_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    ON CONFLICT(mint, session_date, session) DO UPDATE SET
+                        buy_sol = excluded.buy_sol,
+                        sell_sol = excluded.sell_sol,
+                        net_flow_sol = excluded.net_flow_sol,
+                        buy_count = excluded.buy_count,
+                        sell_count = excluded.sell_count,
+                        unique_wallets = excluded.unique_wallets,
+                        updated_at = excluded.updated_at
+                    "#,
+                )?;
+                for rollup in &rollups {
+                    stmt.execute(rusqlite::params![
+                        rollup.mint,
+                        rollup.session_date,
+                        rollup.session,
+                        rollup.buy_sol,
+                        rollup.sell_sol,
+                        rollup.buy_sol - rollup.sell_sol,
+                        rollup.buy_count,
+                        rollup.sell_count,
+                        rollup.unique_wallets,
+                        rollup.updated_at,
+                    ])?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Append audit trades and enforce the retention in one transaction
+    async fn write_audit_trades(
+        &self,
+        trades: Vec<AuditTrade>,
+        prune_before: i64,
+    ) -> Result<(), SolflowError> {
+        self.run(move |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    r#"
+                    INSERT INTO audit_trades (
+                        mint, signature, slot, timestamp, direction, sol_amount,
+                        token_amount, user_account, source_program, fee_lamports,
+                        venue_fee_sol, net_sol, recorded_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )?;
+                for audit in &trades {
+                    let trade = &audit.trade;
+                    stmt.execute(rusqlite::params![
+                        trade.mint,
+                        trade.signature,
+                        trade.slot as i64,
+                        trade.timestamp,
+                        audit.direction(),
+                        trade.sol_amount,
+                        trade.token_amount,
+                        trade.user_account,
+                        trade.source_program,
+                        trade.fee_lamports as i64,
+                        audit.fees.venue_fee_sol,
+                        audit.net_sol(),
+                        audit.recorded_at,
+                    ])?;
+                }
+            }
+            tx.execute("DELETE FROM audit_trades WHERE timestamp < ?", [prune_before])?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Null out old detail blobs in bounded batches (they dominate table growth)
+    async fn compact_signal_details(
+        &self,
+        before: i64,
+        limit: usize,
+    ) -> Result<usize, SolflowError> {
+        self.run(move |conn| {
+            let compacted = conn.execute(
+                r#"
+                UPDATE token_signals SET details_json = NULL
+                WHERE id IN (
+                    SELECT id FROM token_signals
+                    WHERE created_at < ? AND details_json IS NOT NULL
+                    ORDER BY created_at
+                    LIMIT ?
+                )
+                "#,
+                rusqlite::params![before, limit as i64],
+            )?;
+            Ok(compacted)
+        })
+        .await
     }
 
     /// Downcast helper for accessing concrete implementation
@@ -447,8 +1160,9 @@ impl AggregateDbWriter for SqliteAggregateWriter {
 ///
 /// Ensures JSON is well-formed before storing in database.
 /// Returns error if JSON is malformed.
-fn validate_json(json: &str) -> Result<(), Box<dyn std::error::Error>> {
-    serde_json::from_str::<serde_json::Value>(json)?;
+fn validate_json(json: &str) -> Result<(), SolflowError> {
+    serde_json::from_str::<serde_json::Value>(json)
+        .map_err(|e| SolflowError::db(codes::DB_INVALID_DATA, format!("invalid JSON: {}", e)))?;
     Ok(())
 }
 
@@ -505,10 +1219,45 @@ mod tests {
                 buy_count_900s          INTEGER,
                 sell_count_900s         INTEGER,
                 unique_wallets_300s     INTEGER,
+                unique_clusters_300s    INTEGER,
+                new_wallets_300s        INTEGER,
+                fees_paid_300s_sol      REAL,
+                venue_fees_300s_sol     REAL,
+                fee_adjusted_net_flow_300s_sol REAL,
+                liquidity_sol           REAL,
+                pool_price_sol          REAL,
+                priority_fees_300s_sol  REAL,
+                jito_tips_300s_sol      REAL,
+                program_breakdown_300s_json TEXT,
+                early_buyers_count      INTEGER,
+                early_holder_retention  REAL,
+                top10_holder_pct        REAL,
+                fresh_wallet_ratio_60s  REAL,
+                fresh_wallet_ratio_300s REAL,
+                fresh_wallet_ratio_900s REAL,
+                unique_wallets_3600s    INTEGER,
+                unique_wallets_7200s    INTEGER,
+                unique_wallets_14400s   INTEGER,
+                net_flow_sparkline_json TEXT,
                 bot_trades_300s         INTEGER,
                 bot_wallets_300s        INTEGER,
                 avg_trade_size_300s_sol REAL,
                 volume_300s_sol         REAL,
+                dca_buys_60s            INTEGER NOT NULL DEFAULT 0,
+                dca_buys_300s           INTEGER NOT NULL DEFAULT 0,
+                dca_buys_900s           INTEGER NOT NULL DEFAULT 0,
+                dca_buys_3600s          INTEGER NOT NULL DEFAULT 0,
+                dca_buys_14400s         INTEGER NOT NULL DEFAULT 0,
+                net_flow_60s_final_sol  REAL,
+                net_flow_300s_final_sol REAL,
+                net_flow_900s_final_sol REAL,
+                buy_count_300s_final    INTEGER,
+                sell_count_300s_final   INTEGER,
+                unfinalized_trades_300s INTEGER,
+                window_metrics_json     TEXT,
+                last_trade_slot         INTEGER,
+                last_trade_signature    TEXT,
+                last_data_gap_at        INTEGER,
                 updated_at              INTEGER NOT NULL,
                 created_at              INTEGER NOT NULL
             )
@@ -528,6 +1277,8 @@ mod tests {
                 score           REAL,
                 details_json    TEXT,
                 created_at      INTEGER NOT NULL,
+                source          TEXT NOT NULL DEFAULT 'onchain',
+                sample_signatures TEXT,
                 sent_to_discord INTEGER NOT NULL DEFAULT 0,
                 seen_in_terminal INTEGER NOT NULL DEFAULT 0
             )
@@ -542,12 +1293,84 @@ mod tests {
                 mint TEXT NOT NULL,
                 bucket_timestamp INTEGER NOT NULL,
                 buy_count INTEGER NOT NULL DEFAULT 0,
+                last_slot INTEGER,
+                last_signature TEXT,
+                PRIMARY KEY (mint, bucket_timestamp)
+            )
+            "#,
+            [],
+        )?;
+
+        // Schema from /sql/13_dca_activity_rollups.sql
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS dca_activity_rollups (
+                mint TEXT NOT NULL,
+                bucket_timestamp INTEGER NOT NULL,
+                buy_count INTEGER NOT NULL DEFAULT 0,
+                last_slot INTEGER,
+                last_signature TEXT,
                 PRIMARY KEY (mint, bucket_timestamp)
             )
             "#,
             [],
         )?;
 
+        // Schema from /sql/04_system_metrics.sql
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS system_metrics (
+                key         TEXT PRIMARY KEY,
+                value_json  TEXT NOT NULL,
+                updated_at  INTEGER NOT NULL
+            )
+            "#,
+            [],
+        )?;
+
+        // Schema from /sql/11_session_rollups.sql
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS session_rollups (
+                mint            TEXT NOT NULL,
+                session_date    TEXT NOT NULL,
+                session         TEXT NOT NULL,
+                buy_sol         REAL NOT NULL DEFAULT 0,
+                sell_sol        REAL NOT NULL DEFAULT 0,
+                net_flow_sol    REAL NOT NULL DEFAULT 0,
+                buy_count       INTEGER NOT NULL DEFAULT 0,
+                sell_count      INTEGER NOT NULL DEFAULT 0,
+                unique_wallets  INTEGER NOT NULL DEFAULT 0,
+                updated_at      INTEGER NOT NULL,
+                PRIMARY KEY (mint, session_date, session)
+            )
+            "#,
+            [],
+        )?;
+
+        // Schema from /sql/12_audit_trades.sql
+        conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS audit_trades (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                mint TEXT NOT NULL,
+                signature TEXT NOT NULL,
+                slot INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL,
+                direction TEXT NOT NULL,
+                sol_amount REAL NOT NULL,
+                token_amount REAL NOT NULL,
+                user_account TEXT NOT NULL,
+                source_program TEXT NOT NULL,
+                fee_lamports INTEGER NOT NULL,
+                venue_fee_sol REAL,
+                net_sol REAL,
+                recorded_at INTEGER NOT NULL
+            )
+            "#,
+            [],
+        )?;
+
         drop(conn); // Close connection before creating writer
 
         let writer = SqliteAggregateWriter::new(db_path)?;
@@ -576,6 +1399,25 @@ mod tests {
             buy_count_900s: Some(50),
             sell_count_900s: Some(30),
             unique_wallets_300s: Some(10),
+            unique_clusters_300s: Some(10),
+            new_wallets_300s: Some(4),
+            fees_paid_300s_sol: Some(0.001),
+            venue_fees_300s_sol: Some(0.05),
+            fee_adjusted_net_flow_300s_sol: Some(net_flow_300s - 0.051),
+            liquidity_sol: None,
+            pool_price_sol: None,
+            priority_fees_300s_sol: Some(0.0),
+            jito_tips_300s_sol: Some(0.0),
+            early_buyers_count: Some(20),
+            early_holder_retention: Some(0.75),
+            fresh_wallet_ratio_60s: None,
+            fresh_wallet_ratio_300s: Some(0.4),
+            fresh_wallet_ratio_900s: Some(0.25),
+            unique_wallets_3600s: Some(40),
+            unique_wallets_7200s: Some(55),
+            unique_wallets_14400s: Some(70),
+            net_flow_sparkline_json: Some("[[1700000000,1.5],[1700000060,-0.5]]".to_string()),
+            program_breakdown_300s_json: Some(r#"{"PumpSwap":{"net_flow_sol":5.0,"buy_count":20,"sell_count":10}}"#.to_string()),
             bot_trades_300s: Some(3),
             bot_wallets_300s: Some(2),
             avg_trade_size_300s_sol: Some(0.5),
@@ -586,6 +1428,18 @@ mod tests {
             dca_buys_900s: Some(7),
             dca_buys_3600s: Some(15),
             dca_buys_14400s: Some(25),
+            net_flow_60s_final_sol: Some(0.5),
+            net_flow_300s_final_sol: Some(net_flow_300s),
+            net_flow_900s_final_sol: Some(9.0),
+            buy_count_300s_final: Some(18),
+            sell_count_300s_final: Some(10),
+            unfinalized_trades_300s: Some(2),
+            window_metrics_json: Some(r#"{"net_flow_30s_sol":0.25}"#.to_string()),
+            last_trade_slot: Some(250_000_000),
+            last_trade_signature: Some(format!("sig_{}", mint)),
+            last_dca_slot: None,
+            last_dca_signature: None,
+            last_data_gap_at: None,
             updated_at,
             created_at: updated_at - 1000,
         }
@@ -614,6 +1468,17 @@ mod tests {
         assert_eq!(result.0, "mint_new");
         assert_eq!(result.1, 5.0);
         assert_eq!(result.2, agg.created_at);
+
+        // Trace references persisted
+        let (slot, signature): (i64, String) = conn
+            .query_row(
+                "SELECT last_trade_slot, last_trade_signature FROM token_aggregates WHERE mint = ?",
+                ["mint_new"],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(slot, 250_000_000);
+        assert_eq!(signature, "sig_mint_new");
     }
 
     #[tokio::test]
@@ -705,10 +1570,9 @@ mod tests {
         // Attempt to write signal (should fail due to blocklist)
         let result = writer.write_signal(signal).await;
         assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("mint_blocked is blocked"));
+        let err = result.unwrap_err();
+        assert!(err.is_mint_blocked());
+        assert!(err.to_string().contains("mint_blocked is blocked"));
 
         // Verify signal was NOT inserted
         let conn = writer.conn.lock().unwrap();
@@ -794,6 +1658,35 @@ mod tests {
         assert_eq!(parsed["extra"]["nested"], "value");
     }
 
+    #[tokio::test]
+    async fn test_compact_signal_details() {
+        let (_temp, writer) = create_test_db().unwrap();
+        for (mint, created_at) in [("old_a", 1000), ("old_b", 2000), ("new", 9000)] {
+            let signal = TokenSignal::new(mint.to_string(), SignalType::Surge, 60, created_at)
+                .with_severity(4)
+                .with_score(0.8)
+                .with_details(r#"{"buy_count":15}"#.to_string());
+            writer.write_signal(signal).await.unwrap();
+        }
+
+        // Bounded per call, oldest first
+        assert_eq!(writer.compact_signal_details(5000, 1).await.unwrap(), 1);
+        assert_eq!(writer.compact_signal_details(5000, 10).await.unwrap(), 1);
+        assert_eq!(writer.compact_signal_details(5000, 10).await.unwrap(), 0);
+
+        let conn = writer.conn.lock().unwrap();
+        let rows: Vec<(String, Option<String>, i32, f64)> = conn
+            .prepare("SELECT mint, details_json, severity, score FROM token_signals ORDER BY created_at")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rows[0], ("old_a".to_string(), None, 4, 0.8));
+        assert_eq!(rows[1].1, None);
+        assert_eq!(rows[2].1.as_deref(), Some(r#"{"buy_count":15}"#));
+    }
+
     #[tokio::test]
     async fn test_null_optional_fields() {
         let (_temp, writer) = create_test_db().unwrap();
@@ -883,4 +1776,246 @@ mod tests {
 
         assert_eq!(count, 1);
     }
+
+    #[tokio::test]
+    async fn test_write_signals_filters_blocked_mints() {
+        let (_temp, writer) = create_test_db().unwrap();
+        let now = 1_000_000;
+        {
+            let conn = writer.conn.lock().unwrap();
+            for (mint, expires_at) in [("mint_blocked", None), ("mint_expired", Some(now - 100))] {
+                conn.execute(
+                    "INSERT INTO mint_blocklist (mint, reason, blocked_by, created_at, expires_at) VALUES (?, ?, ?, ?, ?)",
+                    rusqlite::params![mint, "test", "admin", now - 2000, expires_at],
+                )
+                .unwrap();
+            }
+        }
+
+        let signals = ["mint_ok", "mint_blocked", "mint_expired", "mint_ok"]
+            .iter()
+            .map(|mint| TokenSignal::new(mint.to_string(), SignalType::Breakout, 60, now))
+            .collect();
+        let written = writer.write_signals(signals).await.unwrap();
+
+        let mints: Vec<&str> = written.iter().map(|s| s.mint.as_str()).collect();
+        assert_eq!(mints, vec!["mint_ok", "mint_expired", "mint_ok"]);
+        let count: i32 = writer
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM token_signals", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 3);
+        assert!(writer.write_signals(Vec::new()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_system_metric_upsert() {
+        let (_temp, writer) = create_test_db().unwrap();
+
+        writer.write_system_metric("trade_latency_sla", r#"{"p50_ms":800}"#).await.unwrap();
+        writer.write_system_metric("trade_latency_sla", r#"{"p50_ms":1200}"#).await.unwrap();
+
+        // Invalid JSON rejected
+        assert!(writer.write_system_metric("trade_latency_sla", "{not json").await.is_err());
+
+        let conn = writer.conn.lock().unwrap();
+        let (count, value): (i32, String) = conn
+            .query_row(
+                "SELECT COUNT(*), MAX(value_json) FROM system_metrics WHERE key = ?",
+                ["trade_latency_sla"],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(value, r#"{"p50_ms":1200}"#);
+    }
+
+    #[tokio::test]
+    async fn test_session_rollup_upsert() {
+        let (_temp, writer) = create_test_db().unwrap();
+        let rollup = |buy_sol: f64, updated_at: i64| SessionRollup {
+            mint: "mint1".to_string(),
+            session_date: "2025-01-15".to_string(),
+            session: "EU".to_string(),
+            buy_sol,
+            sell_sol: 1.0,
+            buy_count: 3,
+            sell_count: 1,
+            unique_wallets: 4,
+            updated_at,
+        };
+
+        writer.write_session_rollups(vec![rollup(2.0, 100)]).await.unwrap();
+        writer.write_session_rollups(vec![rollup(5.0, 200)]).await.unwrap();
+
+        let conn = writer.conn.lock().unwrap();
+        let (count, net_flow, updated_at): (i32, f64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), MAX(net_flow_sol), MAX(updated_at) FROM session_rollups",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+
+        assert_eq!(count, 1);
+        assert!((net_flow - 4.0).abs() < 1e-9);
+        assert_eq!(updated_at, 200);
+    }
+
+    #[tokio::test]
+    async fn test_audit_trades_retention() {
+        use crate::pipeline::fees::TradeFees;
+        use crate::pipeline::types::{TradeDirection, TradeEvent};
+
+        let (_temp, writer) = create_test_db().unwrap();
+        let audit = |timestamp: i64, direction: TradeDirection| AuditTrade {
+            trade: TradeEvent {
+                direction,
+                sol_amount: 2.0,
+                signature: format!("sig{}", timestamp),
+                slot: timestamp as u64,
+                ..TradeEvent::test_buy("mint1", timestamp)
+            },
+            fees: TradeFees { venue_fee_sol: 0.02, network_fee_sol: 0.000005 },
+            recorded_at: timestamp,
+        };
+
+        writer
+            .write_audit_trades(vec![audit(100, TradeDirection::Buy), audit(200, TradeDirection::Sell)], 0)
+            .await
+            .unwrap();
+        // Next flush: 100 falls out of the retention window
+        writer
+            .write_audit_trades(vec![audit(300, TradeDirection::Buy)], 150)
+            .await
+            .unwrap();
+
+        let conn = writer.conn.lock().unwrap();
+        let (count, net_flow): (i32, f64) = conn
+            .query_row(
+                "SELECT COUNT(*), SUM(CASE direction WHEN 'BUY' THEN sol_amount ELSE -sol_amount END)
+                 FROM audit_trades WHERE mint = 'mint1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+
+        assert_eq!(count, 2);
+        assert!(net_flow.abs() < 1e-9);
+
+        // Net proceeds of the sell: 2.0 minus venue and transaction fees
+        let net_sol: f64 = conn
+            .query_row("SELECT net_sol FROM audit_trades WHERE direction = 'SELL'", [], |row| row.get(0))
+            .unwrap();
+        assert!((net_sol - 1.979995).abs() < 1e-9);
+    }
+    #[tokio::test]
+    async fn test_dca_bucket_granularity_and_rollups() {
+        let (_temp, writer) = create_test_db().unwrap();
+        let writer = writer.with_dca_bucket_config(DcaBucketConfig {
+            bucket_secs: 120,
+            retention_secs: 600,
+            rollup_retention_secs: 3600,
+        });
+
+        // 970 and 1050 share a 120s bucket and a 300s rollup; 1250 starts new ones
+        for (updated_at, dca_3600s) in [(970, 6), (1050, 4), (1250, 5)] {
+            let mut agg = make_aggregate("dca_mint", 1.0, updated_at);
+            agg.dca_buys_3600s = Some(dca_3600s);
+            writer.write_aggregates(vec![agg]).await.unwrap();
+        }
+
+        let rows = |table: &str| -> Vec<(i64, i32)> {
+            let conn = writer.conn.lock().unwrap();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT bucket_timestamp, buy_count FROM {} WHERE mint = 'dca_mint' ORDER BY bucket_timestamp",
+                    table
+                ))
+                .unwrap();
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        // Buckets keep the latest count, rollups the highest
+        assert_eq!(rows("dca_activity_buckets"), vec![(960, 4), (1200, 5)]);
+        assert_eq!(rows("dca_activity_rollups"), vec![(900, 6), (1200, 5)]);
+
+        // Fine buckets expire after 600s, rollups are kept for 3600s
+        writer.cleanup_dca_buckets_at(1700).await.unwrap();
+        assert_eq!(rows("dca_activity_buckets"), vec![(1200, 5)]);
+        assert_eq!(rows("dca_activity_rollups").len(), 2);
+        writer.cleanup_dca_buckets_at(5000).await.unwrap();
+        assert!(rows("dca_activity_rollups").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_queued_writes_apply_backpressure() {
+        let (temp, writer) = create_test_db().unwrap();
+        let db_path = temp.path().to_str().unwrap().to_string();
+        drop(writer);
+        let writer = Arc::new(SqliteAggregateWriter::with_queue_capacity(&db_path, 1).unwrap());
+
+        // Far more concurrent flushes than queue slots: every one waits its turn and lands
+        let flushes: Vec<_> = (0..20)
+            .map(|i| {
+                let writer = writer.clone();
+                tokio::spawn(async move {
+                    writer
+                        .write_aggregates(vec![make_aggregate(&format!("mint{}", i), 1.0, 1000 + i)])
+                        .await
+                })
+            })
+            .collect();
+        for flush in flushes {
+            flush.await.unwrap().unwrap();
+        }
+        assert_eq!(writer.queue_depth(), 0);
+
+        let count: i64 = writer
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM token_aggregates", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 20);
+    }
+
+    #[test]
+    fn test_migrations_backfill_added_columns() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut conn = Connection::open(temp_file.path()).unwrap();
+
+        // Table created before new_wallets_300s existed
+        conn.execute(
+            "CREATE TABLE token_aggregates (
+                mint TEXT PRIMARY KEY,
+                source_program TEXT NOT NULL,
+                net_flow_300s_sol REAL,
+                unique_wallets_300s INTEGER,
+                dca_buys_3600s INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .unwrap();
+
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        // Idempotent on re-run
+        run_schema_migrations(&mut conn, "sql").unwrap();
+
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('token_aggregates') WHERE name = 'new_wallets_300s'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
 }
@@ -0,0 +1,130 @@
+//! Trade de-duplication by (signature, trade index)
+//!
+//! A gRPC reconnect that resumes from an earlier slot, or two streamers
+//! covering the same program, deliver the same transaction twice; counted
+//! twice, its trades inflate net flow and wallet counts. The ingestion loop
+//! drops any trade whose (signature, trade index) it has already seen
+//! within `TRADE_DEDUP_TTL_SECS`.
+//!
+//! Keys are stored as 64-bit hashes in insertion order, so memory is bounded
+//! by `TRADE_DEDUP_MAX_ENTRIES` (the oldest keys are forgotten first). Trades
+//! without a signature (manual or synthetic input) are never deduplicated,
+//! and fast-path trades bypass the ingestion loop.
+//!
+//! Environment variables:
+//! - `TRADE_DEDUP_TTL_SECS`: How long a delivered trade is remembered (default: 300, 0 = off)
+//! - `TRADE_DEDUP_MAX_ENTRIES`: Keys remembered at most (default: 200000)
+
+use super::types::TradeEvent;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashSet, VecDeque};
+use std::env;
+use std::hash::BuildHasher;
+use std::time::{Duration, Instant};
+
+/// Bounded TTL cache of delivered trades
+pub struct TradeDedup {
+    ttl: Duration,
+    max_entries: usize,
+    hasher: RandomState,
+    seen: HashSet<u64>,
+    /// Keys by first delivery, oldest first
+    order: VecDeque<(Instant, u64)>,
+}
+
+impl TradeDedup {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries: max_entries.max(1),
+            hasher: RandomState::new(),
+            seen: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Create from the environment; None when TRADE_DEDUP_TTL_SECS is 0
+    pub fn from_env() -> Option<Self> {
+        let ttl_secs = env::var("TRADE_DEDUP_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(300u64);
+        if ttl_secs == 0 {
+            return None;
+        }
+        let max_entries = env::var("TRADE_DEDUP_MAX_ENTRIES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(200_000);
+        Some(Self::new(Duration::from_secs(ttl_secs), max_entries))
+    }
+
+    /// Whether `trade` was already delivered; remembers it otherwise
+    pub fn is_duplicate(&mut self, trade: &TradeEvent, now: Instant) -> bool {
+        if trade.signature.is_empty() {
+            return false;
+        }
+
+        while let Some(&(first_seen, key)) = self.order.front() {
+            if now.duration_since(first_seen) < self.ttl && self.order.len() < self.max_entries {
+                break;
+            }
+            self.order.pop_front();
+            self.seen.remove(&key);
+        }
+
+        let key = self.hasher.hash_one((trade.signature.as_str(), trade.trade_index));
+        if !self.seen.insert(key) {
+            return true;
+        }
+        self.order.push_back((now, key));
+        false
+    }
+
+    /// Keys currently remembered
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(signature: &str, trade_index: u16) -> TradeEvent {
+        TradeEvent {
+            signature: signature.to_string(),
+            trade_index,
+            ..TradeEvent::test_buy("mint", 1_700_000_000)
+        }
+    }
+
+    #[test]
+    fn test_drops_redelivered_trades_within_ttl() {
+        let mut dedup = TradeDedup::new(Duration::from_secs(60), 3);
+        let start = Instant::now();
+
+        assert!(!dedup.is_duplicate(&trade("sig1", 0), start));
+        // Another leg of the same transaction is a different trade
+        assert!(!dedup.is_duplicate(&trade("sig1", 1), start));
+        assert!(dedup.is_duplicate(&trade("sig1", 0), start + Duration::from_secs(30)));
+        // Unsigned trades always pass
+        assert!(!dedup.is_duplicate(&trade("", 0), start));
+        assert!(!dedup.is_duplicate(&trade("", 0), start));
+
+        // Forgotten after the TTL
+        assert!(!dedup.is_duplicate(&trade("sig1", 0), start + Duration::from_secs(61)));
+
+        // Bounded: the oldest key is evicted at capacity
+        let later = start + Duration::from_secs(120);
+        for sig in ["a", "b", "c", "d"] {
+            assert!(!dedup.is_duplicate(&trade(sig, 0), later));
+        }
+        assert_eq!(dedup.len(), 3);
+        assert!(!dedup.is_duplicate(&trade("a", 0), later));
+    }
+}
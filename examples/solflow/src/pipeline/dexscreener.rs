@@ -11,6 +11,9 @@
 //! Endpoint: https://api.dexscreener.com/token-pairs/v1/solana/{mint}
 //! Returns: Array of trading pairs for the token
 //!
+//! Batch endpoint: https://api.dexscreener.com/tokens/v1/solana/{mint1,mint2,...}
+//! Returns: Array of trading pairs for up to 30 tokens (grouped by baseToken.address)
+//!
 //! ## Usage
 //!
 //! ```rust
@@ -20,11 +23,19 @@
 //! dexscreener::upsert_metadata(&conn, &metadata).await?;
 //! ```
 
+use crate::error::{codes, SolflowError};
+use crate::streamer_core::balance_extractor::set_sol_usd_price;
 use reqwest;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::time::Duration;
 
+/// Maximum mints accepted by the batch `tokens/v1` endpoint per request
+pub const MAX_TOKENS_PER_REQUEST: usize = 30;
+
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
 /// DexScreener pair response structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DexScreenerPair {
@@ -43,6 +54,8 @@ pub struct DexScreenerPair {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BaseToken {
+    #[serde(default)]
+    pub address: String,
     pub name: String,
     pub symbol: String,
 }
@@ -97,7 +110,7 @@ pub struct TokenPrice {
 /// let metadata = fetch_token_metadata("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").await?;
 /// println!("Token: {} ({})", metadata.name, metadata.symbol);
 /// ```
-pub async fn fetch_token_metadata(mint: &str) -> Result<TokenMetadata, Box<dyn std::error::Error>> {
+pub async fn fetch_token_metadata(mint: &str) -> Result<TokenMetadata, SolflowError> {
     let url = format!("https://api.dexscreener.com/token-pairs/v1/solana/{}", mint);
     
     let client = reqwest::Client::builder()
@@ -107,7 +120,7 @@ pub async fn fetch_token_metadata(mint: &str) -> Result<TokenMetadata, Box<dyn s
     let response = client.get(&url).send().await?;
     
     if !response.status().is_success() {
-        return Err(format!("DexScreener API error: {}", response.status()).into());
+        return Err(status_error(response.status()));
     }
     
     let pairs: Vec<DexScreenerPair> = response.json().await?;
@@ -115,7 +128,7 @@ pub async fn fetch_token_metadata(mint: &str) -> Result<TokenMetadata, Box<dyn s
     // Find first pair with SOL quote token
     let pair = pairs.iter()
         .find(|p| p.quote_token.symbol == "SOL")
-        .ok_or("No SOL pair found")?;
+        .ok_or_else(|| response_error("No SOL pair found"))?;
     
     Ok(TokenMetadata {
         mint: mint.to_string(),
@@ -150,7 +163,7 @@ pub async fn fetch_token_metadata(mint: &str) -> Result<TokenMetadata, Box<dyn s
 /// let price = fetch_token_price("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").await?;
 /// println!("Price: ${}", price.price_usd);
 /// ```
-pub async fn fetch_token_price(mint: &str) -> Result<TokenPrice, Box<dyn std::error::Error>> {
+pub async fn fetch_token_price(mint: &str) -> Result<TokenPrice, SolflowError> {
     let url = format!("https://api.dexscreener.com/token-pairs/v1/solana/{}", mint);
     
     let client = reqwest::Client::builder()
@@ -160,24 +173,105 @@ pub async fn fetch_token_price(mint: &str) -> Result<TokenPrice, Box<dyn std::er
     let response = client.get(&url).send().await?;
     
     if !response.status().is_success() {
-        return Err(format!("DexScreener API error: {}", response.status()).into());
+        return Err(status_error(response.status()));
     }
     
     // Parse response as flexible JSON to handle heterogeneous pair data
     let json: serde_json::Value = response.json().await?;
     let pairs = json.as_array()
-        .ok_or("Response is not an array")?;
+        .ok_or_else(|| response_error("Response is not an array"))?;
     
-    // Collect valid SOL pairs with their liquidity for ranking
-    let mut valid_sol_pairs: Vec<(f64, Option<f64>, Option<f64>)> = Vec::new();
+    let (price_usd, market_cap) = select_best_sol_price(pairs.iter())
+        .ok_or_else(|| response_error("No valid SOL pair found with price data"))?;
+    
+    Ok(TokenPrice {
+        mint: mint.to_string(),
+        price_usd,
+        market_cap,
+    })
+}
+
+/// Fetch SOL's USD price (most liquid SOL/USDC or SOL/USDT pair)
+pub async fn fetch_sol_usd_price() -> Result<f64, SolflowError> {
+    let url = format!("https://api.dexscreener.com/token-pairs/v1/solana/{}", WSOL_MINT);
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let response = client.get(&url).send().await?;
+
+    if !response.status().is_success() {
+        return Err(status_error(response.status()));
+    }
+
+    let json: serde_json::Value = response.json().await?;
+    let pairs = json.as_array()
+        .ok_or_else(|| response_error("Response is not an array"))?;
+
+    select_sol_usd_price(pairs.iter())
+        .ok_or_else(|| response_error("No SOL/USD pair found with price data"))
+}
+
+/// Keep the streamer's SOL/USD price fresh (stablecoin-quoted trades, see `balance_extractor`)
+///
+/// Runs until the process exits; failed fetches keep the previous price,
+/// which expires after `SOL_USD_MAX_AGE_SECS`.
+pub async fn run_sol_usd_refresh(interval: Duration) {
+    loop {
+        match fetch_sol_usd_price().await {
+            Ok(price) => {
+                set_sol_usd_price(price, chrono::Utc::now().timestamp());
+                log::debug!("💵 SOL/USD: {:.2}", price);
+            }
+            Err(e) => log::warn!("⚠️  SOL/USD price refresh failed: {}", e),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Pick price data from the most liquid valid SOL pair
+///
+/// Skips non-SOL pairs and pairs without a positive priceUsd. Pairs with
+/// liquidity data rank above pairs without it.
+///
+/// Returns: (price_usd, market_cap) or None if no valid SOL pair exists
+fn select_best_sol_price<'a>(
+    pairs: impl Iterator<Item = &'a serde_json::Value>,
+) -> Option<(f64, Option<f64>)> {
+    select_best_price(pairs, |_, quote| quote == "SOL")
+}
+
+/// SOL's USD price from the most liquid SOL/USDC or SOL/USDT pair
+///
+/// `token-pairs` for WSOL also lists pairs where SOL is the quote token
+/// (whose priceUsd is the other token's), so the base must be WSOL.
+fn select_sol_usd_price<'a>(pairs: impl Iterator<Item = &'a serde_json::Value>) -> Option<f64> {
+    select_best_price(pairs, |base, quote| {
+        base == WSOL_MINT && (quote == "USDC" || quote == "USDT")
+    })
+    .map(|(price_usd, _)| price_usd)
+}
+
+/// Price data from the most liquid pair accepted by `accept(base address, quote symbol)`
+fn select_best_price<'a>(
+    pairs: impl Iterator<Item = &'a serde_json::Value>,
+    accept: impl Fn(&str, &str) -> bool,
+) -> Option<(f64, Option<f64>)> {
+    // Collect accepted pairs with their liquidity for ranking
+    let mut valid_pairs: Vec<(f64, Option<f64>, Option<f64>)> = Vec::new();
     
     for pair in pairs {
-        // Skip pairs without SOL quote token
+        let base_address = pair.get("baseToken")
+            .and_then(|bt| bt.get("address"))
+            .and_then(|s| s.as_str())
+            .unwrap_or_default();
         let quote_symbol = pair.get("quoteToken")
             .and_then(|qt| qt.get("symbol"))
-            .and_then(|s| s.as_str());
+            .and_then(|s| s.as_str())
+            .unwrap_or_default();
         
-        if quote_symbol != Some("SOL") {
+        if !accept(base_address, quote_symbol) {
             continue;
         }
         
@@ -199,11 +293,11 @@ pub async fn fetch_token_price(mint: &str) -> Result<TokenPrice, Box<dyn std::er
             .and_then(|l| l.get("usd"))
             .and_then(|u| u.as_f64());
         
-        valid_sol_pairs.push((price_usd, market_cap, liquidity));
+        valid_pairs.push((price_usd, market_cap, liquidity));
     }
     
     // Select best pair: highest liquidity, or first if liquidity missing
-    let best_pair = valid_sol_pairs.into_iter()
+    valid_pairs.into_iter()
         .max_by(|a, b| {
             match (a.2, b.2) {
                 (Some(liq_a), Some(liq_b)) => liq_a.partial_cmp(&liq_b).unwrap_or(std::cmp::Ordering::Equal),
@@ -212,13 +306,184 @@ pub async fn fetch_token_price(mint: &str) -> Result<TokenPrice, Box<dyn std::er
                 (None, None) => std::cmp::Ordering::Equal,
             }
         })
-        .ok_or("No valid SOL pair found with price data")?;
+        .map(|(price_usd, market_cap, _)| (price_usd, market_cap))
+}
+
+/// Fetch raw pairs for up to 30 mints from the batch endpoint
+async fn fetch_pairs_batch(mints: &[String]) -> Result<Vec<serde_json::Value>, SolflowError> {
+    if mints.len() > MAX_TOKENS_PER_REQUEST {
+        return Err(SolflowError::config(
+            codes::CONFIG_INVALID,
+            format!(
+                "DexScreener batch limited to {} mints, got {}",
+                MAX_TOKENS_PER_REQUEST,
+                mints.len()
+            ),
+        ));
+    }
     
-    Ok(TokenPrice {
-        mint: mint.to_string(),
-        price_usd: best_pair.0,
-        market_cap: best_pair.1,
-    })
+    let url = format!("https://api.dexscreener.com/tokens/v1/solana/{}", mints.join(","));
+    
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    
+    let response = client.get(&url).send().await?;
+    
+    if !response.status().is_success() {
+        return Err(status_error(response.status()));
+    }
+    
+    let json: serde_json::Value = response.json().await?;
+    match json {
+        serde_json::Value::Array(pairs) => Ok(pairs),
+        _ => Err(response_error("Response is not an array")),
+    }
+}
+
+/// Non-success HTTP status from DexScreener (429 is reported as rate limiting)
+fn status_error(status: reqwest::StatusCode) -> SolflowError {
+    let code = if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        codes::ENRICHMENT_RATE_LIMITED
+    } else {
+        codes::ENRICHMENT_HTTP
+    };
+    SolflowError::enrichment(code, format!("DexScreener API error: {}", status))
+}
+
+/// Well-formed response that doesn't contain what was asked for
+fn response_error(message: &str) -> SolflowError {
+    SolflowError::enrichment(codes::ENRICHMENT_RESPONSE, message)
+}
+
+/// Group raw pairs by base token mint
+fn group_pairs_by_mint(pairs: &[serde_json::Value]) -> HashMap<&str, Vec<&serde_json::Value>> {
+    let mut grouped: HashMap<&str, Vec<&serde_json::Value>> = HashMap::new();
+    for pair in pairs {
+        if let Some(mint) = pair
+            .get("baseToken")
+            .and_then(|bt| bt.get("address"))
+            .and_then(|a| a.as_str())
+        {
+            grouped.entry(mint).or_default().push(pair);
+        }
+    }
+    grouped
+}
+
+/// Fetch prices for up to 30 mints in a single request
+///
+/// Uses the same pair selection as `fetch_token_price`. Mints without a valid
+/// SOL pair are simply absent from the result.
+///
+/// # Example
+/// ```rust
+/// let prices = fetch_token_prices_batch(&mints[..30]).await?;
+/// ```
+pub async fn fetch_token_prices_batch(
+    mints: &[String],
+) -> Result<Vec<TokenPrice>, SolflowError> {
+    let pairs = fetch_pairs_batch(mints).await?;
+    Ok(prices_from_pairs(mints, &pairs))
+}
+
+/// Extract per-mint prices from a batch response
+fn prices_from_pairs(mints: &[String], pairs: &[serde_json::Value]) -> Vec<TokenPrice> {
+    let grouped = group_pairs_by_mint(pairs);
+    
+    mints
+        .iter()
+        .filter_map(|mint| {
+            let (price_usd, market_cap) =
+                select_best_sol_price(grouped.get(mint.as_str())?.iter().copied())?;
+            Some(TokenPrice {
+                mint: mint.clone(),
+                price_usd,
+                market_cap,
+            })
+        })
+        .collect()
+}
+
+/// Fetch metadata for up to 30 mints in a single request
+///
+/// Like `fetch_token_metadata`, uses the first SOL-quoted pair per mint.
+/// Mints without a SOL pair are absent from the result.
+pub async fn fetch_token_metadata_batch(
+    mints: &[String],
+) -> Result<Vec<TokenMetadata>, SolflowError> {
+    let pairs = fetch_pairs_batch(mints).await?;
+    Ok(metadata_from_pairs(mints, &pairs))
+}
+
+/// Extract per-mint metadata from a batch response
+fn metadata_from_pairs(mints: &[String], pairs: &[serde_json::Value]) -> Vec<TokenMetadata> {
+    let grouped = group_pairs_by_mint(pairs);
+    
+    mints
+        .iter()
+        .filter_map(|mint| {
+            let pair = grouped
+                .get(mint.as_str())?
+                .iter()
+                .filter_map(|p| serde_json::from_value::<DexScreenerPair>((*p).clone()).ok())
+                .find(|p| p.quote_token.symbol == "SOL")?;
+            
+            Some(TokenMetadata {
+                mint: mint.clone(),
+                name: pair.base_token.name.clone(),
+                symbol: pair.base_token.symbol.clone(),
+                image_url: pair.info.as_ref().and_then(|i| i.image_url.clone()),
+                price_usd: pair.price_usd.parse().unwrap_or(0.0),
+                market_cap: pair.market_cap,
+                pair_created_at: pair.pair_created_at.map(|ms| ms / 1000),
+            })
+        })
+        .collect()
+}
+
+/// Coalescing queue of mints awaiting enrichment
+///
+/// Requests for the same mint are merged while it is queued, and mints are
+/// drained in batches sized for the `tokens/v1` endpoint, so a launch wave
+/// of hundreds of mints costs one request per 30 instead of one per mint.
+#[derive(Debug, Default)]
+pub struct EnrichmentQueue {
+    pending: VecDeque<String>,
+    queued: HashSet<String>,
+}
+
+impl EnrichmentQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a mint; returns false if it was already pending
+    pub fn push(&mut self, mint: String) -> bool {
+        if !self.queued.insert(mint.clone()) {
+            return false;
+        }
+        self.pending.push_back(mint);
+        true
+    }
+
+    /// Take up to `MAX_TOKENS_PER_REQUEST` mints in FIFO order
+    pub fn next_batch(&mut self) -> Vec<String> {
+        let count = self.pending.len().min(MAX_TOKENS_PER_REQUEST);
+        let batch: Vec<String> = self.pending.drain(..count).collect();
+        for mint in &batch {
+            self.queued.remove(mint);
+        }
+        batch
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
 }
 
 /// Upsert metadata into token_metadata table
@@ -242,7 +507,7 @@ pub async fn fetch_token_price(mint: &str) -> Result<TokenPrice, Box<dyn std::er
 pub fn upsert_metadata(
     conn: &Connection,
     metadata: &TokenMetadata,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), SolflowError> {
     let now = chrono::Utc::now().timestamp();
     
     conn.execute(
@@ -296,7 +561,7 @@ pub fn upsert_metadata(
 pub fn upsert_price(
     conn: &Connection,
     price: &TokenPrice,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), SolflowError> {
     let now = chrono::Utc::now().timestamp();
     
     conn.execute(
@@ -405,7 +670,7 @@ mod tests {
         let pairs = json.as_array().unwrap();
 
         // Simulate the logic from fetch_token_price
-        let mut valid_sol_pairs: Vec<(f64, Option<f64>, Option<f64>)> = Vec::new();
+        let mut valid_pairs: Vec<(f64, Option<f64>, Option<f64>)> = Vec::new();
 
         for pair in pairs {
             let quote_symbol = pair.get("quoteToken")
@@ -429,14 +694,14 @@ mod tests {
                 .and_then(|l| l.get("usd"))
                 .and_then(|u| u.as_f64());
 
-            valid_sol_pairs.push((price_usd, market_cap, liquidity));
+            valid_pairs.push((price_usd, market_cap, liquidity));
         }
 
         // Should have found 2 valid SOL pairs (skipped malformed and USDC)
-        assert_eq!(valid_sol_pairs.len(), 2);
+        assert_eq!(valid_pairs.len(), 2);
 
         // Select best pair by liquidity
-        let best_pair = valid_sol_pairs.into_iter()
+        let best_pair = valid_pairs.into_iter()
             .max_by(|a, b| {
                 match (a.2, b.2) {
                     (Some(liq_a), Some(liq_b)) => liq_a.partial_cmp(&liq_b).unwrap_or(std::cmp::Ordering::Equal),
@@ -474,7 +739,7 @@ mod tests {
         let json: serde_json::Value = serde_json::from_str(json_response).unwrap();
         let pairs = json.as_array().unwrap();
 
-        let mut valid_sol_pairs: Vec<(f64, Option<f64>, Option<f64>)> = Vec::new();
+        let mut valid_pairs: Vec<(f64, Option<f64>, Option<f64>)> = Vec::new();
 
         for pair in pairs {
             let quote_symbol = pair.get("quoteToken")
@@ -498,13 +763,13 @@ mod tests {
                 .and_then(|l| l.get("usd"))
                 .and_then(|u| u.as_f64());
 
-            valid_sol_pairs.push((price_usd, market_cap, liquidity));
+            valid_pairs.push((price_usd, market_cap, liquidity));
         }
 
-        assert_eq!(valid_sol_pairs.len(), 2);
+        assert_eq!(valid_pairs.len(), 2);
 
         // When no liquidity, max_by returns last pair when all equal
-        let best_pair = valid_sol_pairs.into_iter()
+        let best_pair = valid_pairs.into_iter()
             .max_by(|a, b| {
                 match (a.2, b.2) {
                     (Some(liq_a), Some(liq_b)) => liq_a.partial_cmp(&liq_b).unwrap_or(std::cmp::Ordering::Equal),
@@ -519,4 +784,74 @@ mod tests {
         assert_eq!(best_pair.0, 1.55);
         assert_eq!(best_pair.1, Some(110000.0));
     }
+
+    #[test]
+    fn test_batch_response_grouped_by_mint() {
+        let json_response = r#"[
+            {
+                "baseToken": {"address": "mint_a", "name": "Alpha", "symbol": "A"},
+                "quoteToken": {"symbol": "SOL"},
+                "priceUsd": "0.10",
+                "marketCap": 1000,
+                "liquidity": {"usd": 500}
+            },
+            {
+                "baseToken": {"address": "mint_a", "name": "Alpha", "symbol": "A"},
+                "quoteToken": {"symbol": "SOL"},
+                "priceUsd": "0.11",
+                "marketCap": 1100,
+                "liquidity": {"usd": 900}
+            },
+            {
+                "baseToken": {"address": "mint_b", "name": "Beta", "symbol": "B"},
+                "quoteToken": {"symbol": "SOL"},
+                "priceUsd": "2.50",
+                "info": {"imageUrl": "https://example.com/b.png"}
+            },
+            {
+                "baseToken": {"address": "mint_c", "name": "Gamma", "symbol": "C"},
+                "quoteToken": {"symbol": "USDC"},
+                "priceUsd": "1.00"
+            }
+        ]"#;
+
+        let pairs: Vec<serde_json::Value> = serde_json::from_str(json_response).unwrap();
+        let mints: Vec<String> = ["mint_a", "mint_b", "mint_c"].iter().map(|m| m.to_string()).collect();
+
+        let prices = prices_from_pairs(&mints, &pairs);
+        assert_eq!(prices.len(), 2); // mint_c has no SOL pair
+        assert_eq!(prices[0].mint, "mint_a");
+        assert_eq!(prices[0].price_usd, 0.11); // most liquid pair
+        assert_eq!(prices[1].mint, "mint_b");
+        assert_eq!(prices[1].market_cap, None);
+
+        let metadata = metadata_from_pairs(&mints, &pairs);
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata[1].symbol, "B");
+        assert_eq!(metadata[1].image_url.as_deref(), Some("https://example.com/b.png"));
+    }
+
+    #[test]
+    fn test_enrichment_queue_coalesces_and_batches() {
+        let mut queue = EnrichmentQueue::new();
+
+        for i in 0..45 {
+            assert!(queue.push(format!("mint_{}", i)));
+        }
+        // Duplicate requests while pending are merged
+        assert!(!queue.push("mint_3".to_string()));
+        assert_eq!(queue.len(), 45);
+
+        let first = queue.next_batch();
+        assert_eq!(first.len(), MAX_TOKENS_PER_REQUEST);
+        assert_eq!(first[0], "mint_0");
+
+        // Drained mints can be queued again
+        assert!(queue.push("mint_3".to_string()));
+
+        let second = queue.next_batch();
+        assert_eq!(second.len(), 16);
+        assert_eq!(second.last().unwrap(), "mint_3");
+        assert!(queue.is_empty());
+    }
 }
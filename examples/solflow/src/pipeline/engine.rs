@@ -39,11 +39,34 @@
 //! 3. Add price/supply enrichment pipeline
 //! 4. Schedule periodic flush_to_db() for buffered results
 
+use super::audit_trades::AuditTradeLog;
+use super::crash_report::{CrashReporter, EngineStats};
 use super::db::AggregateDbWriter;
-use super::signals::{SignalType, TokenSignal};
-use super::state::{RollingMetrics, TokenRollingState};
-use super::types::{AggregatedTokenState, TokenMetadata, TradeEvent};
+use super::fees::FeeModels;
+use super::interest::{InterestChange, TokensOfInterest};
+use super::latency::{LatencySummary, LatencyTracker};
+use super::reconcile::{MAX_SAMPLE_SIGNATURES, RECONCILED_SEVERITY};
+use super::sessions::{SessionRollup, SessionTracker};
+use super::signals::{SignalDetails, SignalType, TokenMigratedDetails, TokenSignal};
+use super::snapshot::MintSnapshot;
+use super::state::{
+    OutlierPolicy, RollingMetrics, SignalDetector, SignalDetectorRegistry, TokenRollingState,
+    WalletGrowthMetric,
+};
+use super::thresholds::SignalThresholdsConfig;
+use super::types::{
+    AggregatedTokenState, Confirmation, TokenMetadata, TokenMigration, TradeDirection, TradeEvent,
+};
+use super::wallet_age::WalletAgeCache;
+use super::risk::{self, RiskFlagsCache};
+use super::wallet_graph::WalletGraph;
+use super::wallets::WalletTracker;
+use super::window_set::WindowSet;
+use crate::error::{codes, SolflowError};
+use crate::meta_analysis::AnomalyCapture;
+use crate::streamer_core::slot_gaps::{self, DataGaps};
 use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 use std::sync::Arc;
 
 /// Pipeline engine orchestrating the aggregate-only architecture
@@ -82,6 +105,112 @@ pub struct PipelineEngine {
     /// Phase 5: Delta flush optimization
     /// Tracks mints that received trades since last flush (for incremental flush)
     touched_mints: HashSet<String>,
+
+    /// End-to-end latency tracking (block_time → flush visibility)
+    latency: LatencyTracker,
+
+    /// Engine start timestamp (for startup warm-up)
+    started_at: i64,
+
+    /// Signals are suppressed until this many seconds after engine start
+    startup_warmup_secs: i64,
+
+    /// Signals are suppressed until this many seconds after a token's first trade
+    token_warmup_secs: i64,
+
+    /// Wallet count used by BREAKOUT's wallet-growth check
+    breakout_wallet_metric: WalletGrowthMetric,
+
+    /// Outlier-trade handling applied to window metrics
+    outlier_policy: OutlierPolicy,
+
+    /// Rolling windows computed per token (core 60s/300s/900s plus PIPELINE_WINDOWS)
+    window_set: WindowSet,
+
+    /// Venue fee per source program, for fee-adjusted flow and audit net cost
+    fee_models: FeeModels,
+
+    /// Buyer wallet ages (None = wallet-age enrichment disabled)
+    wallet_ages: Option<WalletAgeCache>,
+
+    /// Buyer wallets younger than this count as fresh
+    fresh_wallet_max_age_secs: i64,
+
+    /// Mints armed for full transaction capture after severe signals
+    anomaly_capture: Option<AnomalyCapture>,
+
+    /// Per-token and market-wide Asia/EU/US session totals
+    sessions: SessionTracker,
+
+    /// Record session totals per trade (off for shards; see `shards`)
+    track_sessions: bool,
+
+    /// Trades handed to `process_trade` since start
+    trades_processed: u64,
+
+    /// Block time of the last trade handed to `process_trade` (0 = none yet)
+    last_trade_at: i64,
+
+    /// Recent trades and stats for crash bundles (None = not reporting)
+    crash_reporter: Option<CrashReporter>,
+
+    /// Raw trades kept for watched mints (None = audit log disabled)
+    audit_log: Option<AuditTradeLog>,
+
+    /// Signal detectors run by `compute_metrics` (built-ins by default)
+    detectors: SignalDetectorRegistry,
+
+    /// Thresholds for the built-in detectors (hot-reloadable)
+    signal_thresholds: SignalThresholdsConfig,
+
+    /// Per-wallet state for wallet-level signals (None = wallet tracking disabled)
+    wallet_tracker: Option<WalletTracker>,
+
+    /// Last trade timestamp of pruned mints, so a mint that resumes trading
+    /// is recognised as waking up rather than new (REACTIVATION)
+    dormant_mints: HashMap<String, i64>,
+
+    /// Promoted tokens of interest (None = promotion disabled)
+    interest: Option<TokensOfInterest>,
+
+    /// Funding-linked wallet clusters (None = clusters not computed)
+    wallet_graph: Option<Arc<WalletGraph>>,
+
+    /// Rug-pull risk flags lowering signal severity (None = no downgrade)
+    risk_flags: Option<Arc<RiskFlagsCache>>,
+
+    /// Open datasource gaps stamped on aggregates (None = never stamped)
+    data_gaps: Option<Arc<DataGaps>>,
+
+    /// TOKEN_MIGRATED signals waiting for their mint's next flush
+    migrations: HashMap<String, TokenSignal>,
+}
+
+/// How long pruned mints are remembered as dormant
+const DORMANT_MEMORY_SECS: i64 = 7 * 86_400;
+
+/// Launch platform stamped on mints first seen trading on a launchpad venue
+pub(crate) fn launch_platform(source_program: &str) -> Option<&'static str> {
+    match source_program {
+        "PumpFun" => Some("pumpfun"),
+        "BonkSwap" => Some("bonkswap"),
+        "Moonshot" => Some("moonshot"),
+        _ => None,
+    }
+}
+
+/// Most recent distinct signatures among `trades` (oldest first), newest first
+fn sample_signatures(trades: &[TradeEvent]) -> Vec<String> {
+    let mut signatures: Vec<String> = Vec::new();
+    for trade in trades.iter().rev() {
+        if signatures.len() == MAX_SAMPLE_SIGNATURES {
+            break;
+        }
+        if !trade.signature.is_empty() && !signatures.contains(&trade.signature) {
+            signatures.push(trade.signature.clone());
+        }
+    }
+    signatures
 }
 
 impl PipelineEngine {
@@ -102,6 +231,7 @@ impl PipelineEngine {
     /// # Arguments
     /// * `now_fn` - Function returning Unix timestamp (for testing)
     pub fn new_with_timestamp_fn(now_fn: Box<dyn Fn() -> i64 + Send + Sync>) -> Self {
+        let started_at = now_fn();
         Self {
             states: HashMap::new(),
             last_bot_counts: HashMap::new(),
@@ -110,9 +240,212 @@ impl PipelineEngine {
             metadata_cache: HashMap::new(),
             now_fn,
             touched_mints: HashSet::new(), // Phase 5: Delta flush optimization
+            latency: LatencyTracker::new(),
+            started_at,
+            startup_warmup_secs: 0,
+            token_warmup_secs: 0,
+            breakout_wallet_metric: WalletGrowthMetric::default(),
+            outlier_policy: OutlierPolicy::default(),
+            window_set: WindowSet::default(),
+            fee_models: FeeModels::default(),
+            wallet_ages: None,
+            fresh_wallet_max_age_secs: 86_400,
+            anomaly_capture: None,
+            sessions: SessionTracker::new(),
+            track_sessions: true,
+            trades_processed: 0,
+            last_trade_at: 0,
+            crash_reporter: None,
+            audit_log: None,
+            detectors: SignalDetectorRegistry::with_defaults(),
+            signal_thresholds: SignalThresholdsConfig::default(),
+            wallet_tracker: None,
+            dormant_mints: HashMap::new(),
+            interest: None,
+            wallet_graph: None,
+            risk_flags: None,
+            data_gaps: None,
+            migrations: HashMap::new(),
         }
     }
 
+    /// Configure signal warm-up periods
+    ///
+    /// Half-filled windows right after a restart (or after a token's state is
+    /// cold-started) produce bogus BREAKOUTs. During warm-up, signals are still
+    /// computed but neither returned for writing nor recorded in dedup state, so
+    /// a condition that persists past warm-up is emitted once it ends.
+    ///
+    /// # Arguments
+    /// * `startup_secs` - Suppress all signals for this long after engine start (0 = off)
+    /// * `token_secs` - Suppress a token's signals for this long after its first trade (0 = off)
+    pub fn set_signal_warmup(&mut self, startup_secs: i64, token_secs: i64) {
+        self.startup_warmup_secs = startup_secs;
+        self.token_warmup_secs = token_secs;
+    }
+
+    /// Choose which wallet count BREAKOUT uses
+    ///
+    /// `AnyActivity` counts every wallet trading in the 300s window;
+    /// `NewWallets` counts only wallets whose first trade on the mint is in it.
+    pub fn set_breakout_wallet_metric(&mut self, metric: WalletGrowthMetric) {
+        self.breakout_wallet_metric = metric;
+    }
+
+    /// Configure outlier-trade handling for metrics and signals
+    ///
+    /// With `OutlierMode::Cap` or `OutlierMode::Exclude`, trades far above the
+    /// token's typical size are clamped or dropped before net flow is summed.
+    pub fn set_outlier_policy(&mut self, policy: OutlierPolicy) {
+        self.outlier_policy = policy;
+    }
+
+    /// Configure the rolling windows computed for every token
+    ///
+    /// Windows beyond the core 60s/300s/900s are written to
+    /// `window_metrics_json` unless they map onto a fixed column.
+    pub fn set_window_set(&mut self, windows: WindowSet) {
+        self.window_set = windows;
+    }
+
+    /// Configure the per-venue fee models
+    ///
+    /// Used for `venue_fees_300s_sol`, `fee_adjusted_net_flow_300s_sol` and
+    /// the net cost/proceeds of audited trades.
+    pub fn set_fee_models(&mut self, models: FeeModels) {
+        self.fee_models = models;
+    }
+
+    /// Enable buyer wallet-age cohorts
+    ///
+    /// Unknown buyers are queued on `cache` for background resolution; the
+    /// fresh-wallet ratios cover only buyers whose age is already known.
+    pub fn set_wallet_age_cache(&mut self, cache: WalletAgeCache, fresh_max_age_secs: i64) {
+        self.wallet_ages = Some(cache);
+        self.fresh_wallet_max_age_secs = fresh_max_age_secs;
+    }
+
+    /// Count funding-linked wallets once in `unique_clusters_300s`
+    ///
+    /// The graph is filled by whoever sees the transactions (the streamers'
+    /// funding edges, see `streamer_core::funding`); shards share one graph.
+    pub fn set_wallet_graph(&mut self, graph: Arc<WalletGraph>) {
+        self.wallet_graph = Some(graph);
+    }
+
+    /// Lower a risky mint's signal severity by one level per flag in `cache`
+    ///
+    /// The cache is filled by the `RiskChecker` enrichment stage (see
+    /// `RiskChecker::flags_cache`).
+    pub fn set_risk_flags(&mut self, cache: Arc<RiskFlagsCache>) {
+        self.risk_flags = Some(cache);
+    }
+
+    /// Stamp aggregates whose windows reach back over an open gap in `gaps`
+    ///
+    /// The streamer detecting the gaps records them (see `slot_gaps`).
+    pub fn set_data_gaps(&mut self, gaps: Arc<DataGaps>) {
+        self.data_gaps = Some(gaps);
+    }
+
+    /// Arm full transaction capture for mints whose emitted signals are severe enough
+    ///
+    /// The same `AnomalyCapture` must be handed to the unified streamer, which
+    /// does the capturing.
+    pub fn set_anomaly_capture(&mut self, capture: AnomalyCapture) {
+        self.anomaly_capture = Some(capture);
+    }
+
+    /// Promote/demote tokens of interest on each computation (shared across shards)
+    pub fn set_interest(&mut self, interest: TokensOfInterest) {
+        self.interest = Some(interest);
+    }
+
+    /// Feed processed trades and engine stats to a crash reporter
+    pub fn set_crash_reporter(&mut self, reporter: CrashReporter) {
+        self.crash_reporter = Some(reporter);
+    }
+
+    /// Add a signal detector (replaces any registered for the same signal type)
+    ///
+    /// Register custom detectors before trades are processed, e.g.
+    /// `engine.register_detector(WhaleEntryDetector::new(50.0))`.
+    pub fn register_detector(&mut self, detector: impl SignalDetector + 'static) {
+        self.detectors.register(detector);
+    }
+
+    /// Replace the whole detector registry (e.g. to drop built-ins)
+    pub fn set_signal_detectors(&mut self, detectors: SignalDetectorRegistry) {
+        self.detectors = detectors;
+    }
+
+    /// Replace the built-in detector thresholds; applies from the next compute
+    pub fn set_signal_thresholds(&mut self, thresholds: SignalThresholdsConfig) {
+        self.signal_thresholds = thresholds;
+    }
+
+    pub fn signal_thresholds(&self) -> &SignalThresholdsConfig {
+        &self.signal_thresholds
+    }
+
+    /// Track per-wallet state and emit SMART_WALLET_ENTRY signals
+    pub fn set_wallet_tracker(&mut self, tracker: WalletTracker) {
+        self.wallet_tracker = Some(tracker);
+    }
+
+    pub fn wallet_tracker(&self) -> Option<&WalletTracker> {
+        self.wallet_tracker.as_ref()
+    }
+
+    /// Keep raw trades for the audit log's watched mints (see `audit_trades`)
+    pub fn set_audit_log(&mut self, audit_log: AuditTradeLog) {
+        self.audit_log = Some(audit_log);
+    }
+
+    /// Enable or disable per-trade session totals
+    ///
+    /// Sharded engines see only part of the market, so the market-wide
+    /// session rows are kept by the `ShardedEngine` instead.
+    pub fn set_session_tracking(&mut self, enabled: bool) {
+        self.track_sessions = enabled;
+    }
+
+    /// Summary counters (also copied into crash bundles)
+    pub fn stats(&self) -> EngineStats {
+        EngineStats {
+            trades_processed: self.trades_processed,
+            tracked_mints: self.states.len(),
+            touched_mints: self.touched_mints.len(),
+            started_at: self.started_at,
+            last_trade_at: self.last_trade_at,
+        }
+    }
+
+    /// Latest trade-derived price for a token, in SOL per token
+    ///
+    /// Taken from the most recent trade with both legs non-zero.
+    pub fn latest_price_sol(&self, mint: &str) -> Option<f64> {
+        let state = self.states.get(mint)?;
+        state
+            .trades_900s()
+            .iter()
+            .rev()
+            .find(|t| t.sol_amount > 0.0 && t.token_amount > 0.0)
+            .map(|t| t.sol_amount / t.token_amount)
+    }
+
+    /// Current engine time (wall clock, or replayed trade time during replay)
+    pub fn now(&self) -> i64 {
+        (self.now_fn)()
+    }
+
+    /// Check whether signals for a token are currently suppressed by warm-up
+    fn in_warmup(&self, state: &TokenRollingState, now: i64) -> bool {
+        let startup_warm = now - self.started_at < self.startup_warmup_secs;
+        let token_warm = now - state.first_seen_ts < self.token_warmup_secs;
+        startup_warm || token_warm
+    }
+
     /// Process a trade event through the pipeline
     ///
     /// Updates rolling state for the token:
@@ -134,11 +467,45 @@ impl PipelineEngine {
         // Phase 5: Mark mint as touched (for delta flush)
         self.touched_mints.insert(mint.clone());
 
+        // Latency SLA: remember block time until this mint is flushed
+        self.latency.record_trade(&mint, trade.timestamp);
+
+        if self.track_sessions {
+            self.sessions.record(&trade);
+        }
+
+        self.trades_processed += 1;
+        self.last_trade_at = trade.timestamp;
+        if let Some(reporter) = &self.crash_reporter {
+            reporter.record_trade(&trade, self.stats());
+        }
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(&trade, self.fee_models.trade_fees(&trade), now);
+        }
+
+        // Queue buyer for wallet-age lookup (no-op once resolved)
+        if let Some(cache) = &self.wallet_ages {
+            if trade.direction == TradeDirection::Buy {
+                cache.request(&trade.user_account);
+            }
+        }
+
+        if let Some(tracker) = &mut self.wallet_tracker {
+            tracker.record(&trade, now);
+        }
+
+        if let Some(platform) = launch_platform(&trade.source_program) {
+            self.stamp_launch_platform(&mint, platform, trade.token_decimals, trade.timestamp, now);
+        }
+
         // Get or create rolling state for this token
-        let state = self
-            .states
-            .entry(mint)
-            .or_insert_with(|| TokenRollingState::new(trade.mint.clone()));
+        let dormant_since = self.dormant_mints.remove(&mint);
+        let state = self.states.entry(mint).or_insert_with(|| {
+            let mut state = TokenRollingState::new(trade.mint.clone());
+            // Resume from the last trade before pruning so the gap counts as a wake-up
+            state.last_seen_ts = dormant_since.unwrap_or(0);
+            state
+        });
 
         // Add trade to rolling windows
         state.add_trade(trade);
@@ -147,6 +514,97 @@ impl PipelineEngine {
         state.evict_old_trades(now);
     }
 
+    /// Queue a TOKEN_MIGRATED signal for the mint's next flush
+    ///
+    /// The migration also stamps the launch platform, in case the mint's
+    /// bonding-curve trades were never seen.
+    pub fn process_migration(&mut self, migration: TokenMigration) {
+        let now = (self.now_fn)();
+        let mint = migration.mint.clone();
+        log::info!(
+            "🎓 {} migrated from {} to {} (pool {}, {:.2} SOL)",
+            mint,
+            migration.launch_platform,
+            migration.destination,
+            migration.pool,
+            migration.sol_amount
+        );
+
+        self.stamp_launch_platform(
+            &mint,
+            &migration.launch_platform,
+            migration.token_decimals,
+            migration.timestamp,
+            now,
+        );
+        self.touched_mints.insert(mint.clone());
+        self.states.entry(mint.clone()).or_insert_with(|| {
+            let mut state = TokenRollingState::new(mint.clone());
+            state.last_seen_ts = migration.timestamp;
+            state
+        });
+
+        let signal = TokenSignal::new(mint.clone(), SignalType::TokenMigrated, 0, migration.timestamp)
+            .with_severity(3)
+            .with_typed_details(SignalDetails::TokenMigrated(TokenMigratedDetails {
+                launch_platform: migration.launch_platform,
+                destination: migration.destination,
+                pool: migration.pool,
+                sol_amount: migration.sol_amount,
+                signature: migration.signature,
+            }));
+        self.migrations.insert(mint, signal);
+    }
+
+    /// Record the SOL in a mint's pools (liquidity tracker)
+    ///
+    /// Only mints with trading state are updated: a pool the tracker still
+    /// watches after its mint was pruned doesn't bring the mint back.
+    pub fn process_liquidity(&mut self, mint: &str, liquidity_sol: f64) {
+        let now = (self.now_fn)();
+        let Some(state) = self.states.get_mut(mint) else {
+            return;
+        };
+        state.record_liquidity(now, liquidity_sol);
+        self.touched_mints.insert(mint.to_string());
+    }
+
+    /// Record a mint's price read from its pool state (pool state tracker)
+    ///
+    /// Like pooled SOL, only mints with trading state are updated.
+    pub fn process_pool_price(&mut self, mint: &str, price_sol: f64) {
+        let Some(state) = self.states.get_mut(mint) else {
+            return;
+        };
+        state.pool_price_sol = Some(price_sol);
+        self.touched_mints.insert(mint.to_string());
+    }
+
+    /// Record the launch platform of a mint unless one is already known
+    fn stamp_launch_platform(&mut self, mint: &str, platform: &str, decimals: u8, seen_at: i64, now: i64) {
+        let metadata = self
+            .metadata_cache
+            .entry(mint.to_string())
+            .or_insert_with(|| TokenMetadata {
+                mint: mint.to_string(),
+                symbol: None,
+                name: None,
+                decimals,
+                launch_platform: None,
+                created_at: seen_at,
+                updated_at: now,
+            });
+        if metadata.launch_platform.is_none() {
+            metadata.launch_platform = Some(platform.to_string());
+            metadata.updated_at = now;
+        }
+    }
+
+    /// Session rollup rows changed since the last call (see `sessions`)
+    pub fn drain_session_rollups(&mut self, now: i64) -> Vec<SessionRollup> {
+        self.sessions.drain(now)
+    }
+
     /// Compute metrics and signals for a token
     ///
     /// Returns full pipeline output:
@@ -170,38 +628,152 @@ impl PipelineEngine {
         &mut self,
         mint: &str,
         now: i64,
-    ) -> Result<(RollingMetrics, Vec<TokenSignal>, AggregatedTokenState), Box<dyn std::error::Error>>
+    ) -> Result<(RollingMetrics, Vec<TokenSignal>, AggregatedTokenState), SolflowError>
     {
+        // Re-classify only wallets whose window changed since the last flush
+        if let Some(state) = self.states.get_mut(mint) {
+            state.refresh_bot_cache(now);
+        }
+
         // Get state for this token
         let state = self
             .states
             .get(mint)
-            .ok_or_else(|| format!("No state for mint: {}", mint))?;
+            .ok_or_else(|| {
+                SolflowError::datasource(
+                    codes::DATASOURCE_UNKNOWN_MINT,
+                    format!("No state for mint: {}", mint),
+                )
+            })?;
+
+        // Compute rolling metrics (outlier policy applies to signals too)
+        let mut metrics = state.compute_rolling_metrics_for(self.outlier_policy, &self.window_set);
+
+        // Buyer wallet-age cohorts
+        if let Some(cache) = &self.wallet_ages {
+            let max_age = self.fresh_wallet_max_age_secs;
+            metrics.fresh_wallet_ratio_60s = cache.fresh_buyer_ratio(state.trades_60s(), now, max_age);
+            metrics.fresh_wallet_ratio_300s = cache.fresh_buyer_ratio(state.trades_300s(), now, max_age);
+            metrics.fresh_wallet_ratio_900s = cache.fresh_buyer_ratio(state.trades_900s(), now, max_age);
+        }
 
-        // Compute rolling metrics
-        let metrics = state.compute_rolling_metrics();
+        // Funding-linked wallets count once
+        if let Some(graph) = self.wallet_graph.as_ref().filter(|graph| graph.is_enabled()) {
+            metrics.unique_clusters_300s = graph.cluster_count(&state.unique_wallets_300s);
+        }
 
         // Detect signals (with bot history for BOT_DROPOFF)
         let previous_bot_count = self.last_bot_counts.get(mint).copied();
-        let signals = state.detect_signals(now, previous_bot_count);
+        let signals = state.detect_signals_with(
+            &self.detectors,
+            &self.signal_thresholds,
+            &metrics,
+            now,
+            previous_bot_count,
+            self.breakout_wallet_metric,
+        );
 
         // Get metadata for enrichment (if available)
         let metadata = self.metadata_cache.get(mint);
 
-        // Find last trade timestamp
-        let last_trade_ts = state
-            .trades_60s
-            .last()
-            .or(state.trades_300s.last())
-            .or(state.trades_900s.last())
-            .map(|t| t.timestamp)
-            .unwrap_or(now);
+        // Find last trade (timestamp + trace references)
+        let last_trade = state.trades_900s().last();
+        let last_trade_ts = last_trade.map(|t| t.timestamp).unwrap_or(now);
 
         // Build AggregatedTokenState from metrics + metadata
-        let aggregate = AggregatedTokenState::from_metrics(mint, &metrics, metadata, last_trade_ts, now);
+        let aggregate = AggregatedTokenState::from_metrics(mint, &metrics, metadata, last_trade_ts, now)
+            .with_trade_refs(last_trade, state.last_dca_trade.as_ref())
+            .with_window_fees(self.fee_models.window_fees(state.trades_300s()))
+            .with_liquidity(state.liquidity_sol())
+            .with_pool_price(state.pool_price_sol)
+            .with_data_gap(
+                self.data_gaps
+                    .as_ref()
+                    .and_then(|gaps| gaps.latest_since(now - slot_gaps::GAP_MEMORY_SECS)),
+            );
+
+        let in_warmup = self.in_warmup(state, now);
+
+        // Deduplicate signals before returning. Warm-up skips dedup so a
+        // suppressed signal is not recorded as active and still fires later.
+        let mut deduplicated_signals = if in_warmup {
+            signals
+        } else {
+            self.deduplicate_signals(mint, signals)
+        };
+
+        // Wallet-level signals are per entry, so they skip state dedup
+        if let Some(tracker) = &mut self.wallet_tracker {
+            deduplicated_signals.extend(tracker.drain_signals(mint));
+        }
 
-        // Deduplicate signals before returning
-        let deduplicated_signals = self.deduplicate_signals(mint, signals);
+        // Warm-up: signals are computed but not emitted
+        if in_warmup && !deduplicated_signals.is_empty() {
+            log::debug!(
+                "🔇 Suppressed {} signals for {} during warm-up",
+                deduplicated_signals.len(),
+                mint
+            );
+            deduplicated_signals.clear();
+        }
+
+        // Migrations are on-chain events, not window state: never deduplicated or suppressed
+        if let Some(signal) = self.migrations.remove(mint) {
+            deduplicated_signals.push(signal);
+        }
+
+        // Rug-pull risk: one severity level per flag, before severity-gated steps below
+        let risk_flags = self.risk_flags.as_ref().map_or(0, |cache| cache.flags(mint));
+        if risk_flags != 0 {
+            for signal in &mut deduplicated_signals {
+                signal.severity = risk::downgrade_severity(signal.severity, risk_flags);
+            }
+        }
+
+        // Severe signals carry their latest trades for RPC reconciliation
+        if let Some(state) = self.states.get(mint) {
+            for signal in deduplicated_signals
+                .iter_mut()
+                .filter(|s| s.severity >= RECONCILED_SEVERITY && s.sample_signatures.is_empty())
+            {
+                let trades = match signal.window_seconds {
+                    ..=60 => state.trades_60s(),
+                    61..=300 => state.trades_300s(),
+                    _ => state.trades_900s(),
+                };
+                signal.sample_signatures = sample_signatures(trades);
+            }
+        }
+
+        // Capture-on-anomaly: keep the raw transactions behind severe signals
+        if let Some(capture) = &self.anomaly_capture {
+            for signal in &deduplicated_signals {
+                if capture.arm(mint, signal.severity, now) {
+                    log::info!(
+                        "🎥 Anomaly capture armed for {} ({} severity {}, {}s)",
+                        mint,
+                        signal.signal_type.as_str(),
+                        signal.severity,
+                        capture.duration_secs()
+                    );
+                }
+            }
+        }
+
+        // Tokens of interest: promote or demote on the latest activity
+        if let Some(interest) = &self.interest {
+            match interest.observe(mint, &metrics, now) {
+                Some(InterestChange::Promoted) => {
+                    log::info!("⭐ Promoted {} to token of interest ({} promoted)", mint, interest.len())
+                }
+                Some(InterestChange::Demoted) => log::info!("💤 Demoted {} from tokens of interest", mint),
+                None => {}
+            }
+            // Capture stays armed while promoted (and for the capture duration after)
+            if let Some(capture) = self.anomaly_capture.as_ref().filter(|_| interest.contains(mint)) {
+                capture.arm_mint(mint, now);
+            }
+        }
 
         Ok((metrics, deduplicated_signals, aggregate))
     }
@@ -255,16 +827,9 @@ impl PipelineEngine {
 
         // Update state: set undetected signals to false (signal ended)
         // This allows the same signal to be emitted again later
-        let all_signal_types = [
-            SignalType::Breakout,
-            SignalType::Focused,
-            SignalType::Surge,
-            SignalType::BotDropoff,
-            SignalType::DcaConviction,
-        ];
-        for signal_type in &all_signal_types {
-            if !active_types.contains_key(signal_type) {
-                signal_state.insert(*signal_type, false);
+        for signal_type in self.detectors.signal_types() {
+            if !active_types.contains_key(&signal_type) {
+                signal_state.insert(signal_type, false);
             }
         }
 
@@ -310,6 +875,61 @@ impl PipelineEngine {
         self.states.keys().cloned().collect()
     }
 
+    /// Whether the engine still holds rolling state for a mint
+    pub fn is_tracking(&self, mint: &str) -> bool {
+        self.states.contains_key(mint)
+    }
+
+    /// Current rolling metrics for a mint without flushing it
+    pub fn rolling_metrics(&self, mint: &str) -> Option<RollingMetrics> {
+        let state = self.states.get(mint)?;
+        Some(state.compute_rolling_metrics_for(self.outlier_policy, &self.window_set))
+    }
+
+    /// Upgrade trades in `slot` and its ancestors to `confirmation`
+    ///
+    /// Mints whose trades reached Finalized are marked touched so the final
+    /// view is rewritten on the next flush. Returns the number of trades
+    /// upgraded.
+    pub fn confirm_through_slot(&mut self, slot: u64, confirmation: Confirmation) -> usize {
+        let mut upgraded = 0;
+        for (mint, state) in self.states.iter_mut() {
+            let count = state.confirm_through_slot(slot, confirmation);
+            if count > 0 {
+                if confirmation == Confirmation::Finalized {
+                    self.touched_mints.insert(mint.clone());
+                }
+                upgraded += count;
+            }
+        }
+        upgraded
+    }
+
+    /// Remove trades from rolled-back slots across all mints
+    ///
+    /// Affected mints are marked touched so the next flush rewrites their
+    /// aggregates. Wallet tracker history is not rewound. Returns the number
+    /// of trades removed.
+    pub fn rollback_slots(&mut self, slots: RangeInclusive<u64>, now: i64) -> usize {
+        let mut removed = 0;
+        for (mint, state) in self.states.iter_mut() {
+            let count = state.evict_trades_in_slots(slots.clone(), now);
+            if count > 0 {
+                self.touched_mints.insert(mint.clone());
+                removed += count;
+            }
+        }
+        if removed > 0 {
+            log::warn!(
+                "🍴 Rolled back {} trades from slots {}..={}",
+                removed,
+                slots.start(),
+                slots.end()
+            );
+        }
+        removed
+    }
+
     /// Get list of mints that received trades since last flush (delta flush)
     ///
     /// Phase 5: Delta flush optimization
@@ -334,6 +954,39 @@ impl PipelineEngine {
         self.touched_mints.clear();
     }
 
+    /// Record flush completion for latency SLA tracking
+    ///
+    /// Call after aggregates for `mints` have been committed to the database.
+    /// Each trade received since the mint's previous flush yields one
+    /// block_time → visibility latency sample.
+    ///
+    /// # Arguments
+    /// * `mints` - Mints whose aggregates were just written
+    /// * `flushed_at_ms` - Unix timestamp (milliseconds) of flush completion
+    pub fn record_flush_latency(&mut self, mints: &[String], flushed_at_ms: i64) {
+        self.latency.record_flush(mints, flushed_at_ms);
+    }
+
+    /// Latency percentiles (p50/p99) for a single token
+    pub fn latency_summary(&self, mint: &str) -> Option<LatencySummary> {
+        self.latency.summary(mint)
+    }
+
+    /// Latency percentiles across all tokens
+    pub fn global_latency_summary(&self) -> Option<LatencySummary> {
+        self.latency.global_summary()
+    }
+
+    /// Latency percentiles for every token with samples
+    pub fn per_mint_latency_summaries(&self) -> HashMap<String, LatencySummary> {
+        self.latency.per_mint_summaries()
+    }
+
+    /// Raw latency samples (ms) across all tokens, for merging shard summaries
+    pub fn latency_samples(&self) -> Vec<i64> {
+        self.latency.all_samples()
+    }
+
     /// Prune mints with no activity in last N seconds
     ///
     /// Phase 5: Mint pruning to prevent unbounded state growth
@@ -355,10 +1008,11 @@ impl PipelineEngine {
     pub fn prune_inactive_mints(&mut self, now: i64, threshold_secs: i64) {
         let cutoff = now - threshold_secs;
         let before_count = self.states.len();
+        let interest = self.interest.clone();
 
-        // Remove mints with last_seen_ts < cutoff
+        // Remove mints with last_seen_ts < cutoff (tokens of interest are pinned)
         self.states.retain(|mint, state| {
-            let keep = state.last_seen_ts >= cutoff;
+            let keep = state.last_seen_ts >= cutoff || interest.as_ref().is_some_and(|i| i.contains(mint));
 
             if !keep {
                 log::debug!(
@@ -367,15 +1021,27 @@ impl PipelineEngine {
                     now - state.last_seen_ts
                 );
 
+                self.dormant_mints.insert(mint.clone(), state.last_seen_ts);
+
                 // Also remove from auxiliary structures
                 self.last_bot_counts.remove(mint);
                 self.last_signal_state.remove(mint);
                 self.touched_mints.remove(mint);
+                self.latency.remove(mint);
+                if let Some(tracker) = &mut self.wallet_tracker {
+                    tracker.forget_mint(mint);
+                }
             }
 
             keep
         });
 
+        if let Some(tracker) = &mut self.wallet_tracker {
+            tracker.prune(now);
+        }
+        self.dormant_mints
+            .retain(|_, last_seen| *last_seen >= now - DORMANT_MEMORY_SECS);
+
         let pruned = before_count - self.states.len();
 
         if pruned > 0 {
@@ -388,8 +1054,64 @@ impl PipelineEngine {
         }
     }
 
+    /// Per-mint state for an engine snapshot (see `snapshot`)
+    pub fn snapshot_mints(&self) -> Vec<MintSnapshot> {
+        self.states
+            .iter()
+            .map(|(mint, state)| MintSnapshot {
+                state: state.clone(),
+                last_bot_count: self.last_bot_counts.get(mint).copied(),
+                active_signals: self
+                    .last_signal_state
+                    .get(mint)
+                    .map(|signals| {
+                        signals
+                            .iter()
+                            .filter(|(_, active)| **active)
+                            .map(|(signal_type, _)| signal_type.as_str().to_string())
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Restore snapshotted mints, evicting trades that expired since the snapshot
+    ///
+    /// Mints idle for longer than the longest window are skipped, as are
+    /// active signals no registered detector emits. Returns the number of
+    /// mints restored.
+    pub fn restore_mints(&mut self, mints: Vec<MintSnapshot>, now: i64) -> usize {
+        let signal_types = self.detectors.signal_types();
+        let mut restored = 0;
+        for snapshot in mints {
+            if !snapshot.is_live(now) {
+                continue;
+            }
+            let mut state = snapshot.state;
+            state.evict_old_trades(now);
+            let mint = state.mint.clone();
+
+            if let Some(bot_count) = snapshot.last_bot_count {
+                self.last_bot_counts.insert(mint.clone(), bot_count);
+            }
+            let active: HashMap<SignalType, bool> = signal_types
+                .iter()
+                .filter(|signal_type| snapshot.active_signals.iter().any(|name| name == signal_type.as_str()))
+                .map(|signal_type| (*signal_type, true))
+                .collect();
+            if !active.is_empty() {
+                self.last_signal_state.insert(mint.clone(), active);
+            }
+            self.touched_mints.insert(mint.clone());
+            self.states.insert(mint, state);
+            restored += 1;
+        }
+        restored
+    }
+
     // TODO: Phase 4 - Add database write methods
-    // pub async fn flush_aggregates(&self) -> Result<(), Box<dyn std::error::Error>> {
+    // pub async fn flush_aggregates(&self) -> Result<(), SolflowError> {
     //     if let Some(writer) = &self.db_writer {
     //         let aggregates = self.build_all_aggregates();
     //         writer.write_aggregates(aggregates).await?;
@@ -397,7 +1119,7 @@ impl PipelineEngine {
     //     Ok(())
     // }
     //
-    // pub async fn flush_signals(&self, signals: Vec<TokenSignal>) -> Result<(), Box<dyn std::error::Error>> {
+    // pub async fn flush_signals(&self, signals: Vec<TokenSignal>) -> Result<(), SolflowError> {
     //     if let Some(writer) = &self.db_writer {
     //         for signal in signals {
     //             writer.write_signal(signal).await?;
@@ -448,7 +1170,6 @@ impl Default for PipelineEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pipeline::types::TradeDirection;
 
     /// Helper to create a test trade event
     fn make_trade(
@@ -467,6 +1188,7 @@ mod tests {
             token_decimals: 6,
             user_account: user_account.to_string(),
             source_program: "test_program".to_string(),
+            ..Default::default()
         }
     }
 
@@ -483,6 +1205,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wallet_graph_is_per_engine() {
+        let base_time = 10000;
+        let graph = Arc::new(WalletGraph::new(100));
+        graph.link("funder", "wallet_1");
+        graph.link("funder", "wallet_2");
+        let mut linked = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+        linked.set_wallet_graph(graph);
+        let mut unlinked = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+
+        for engine in [&mut linked, &mut unlinked] {
+            engine.process_trade(make_trade(base_time, "mint", TradeDirection::Buy, 1.0, "wallet_1"));
+            engine.process_trade(make_trade(base_time, "mint", TradeDirection::Buy, 1.0, "wallet_2"));
+        }
+
+        let (metrics, _, _) = linked.compute_metrics("mint", base_time).unwrap();
+        assert_eq!(metrics.unique_clusters_300s, 1);
+        // Without a graph every wallet is its own cluster
+        let (metrics, _, _) = unlinked.compute_metrics("mint", base_time).unwrap();
+        assert_eq!(metrics.unique_clusters_300s, 2);
+    }
+
+    #[test]
+    fn test_data_gaps_are_per_engine() {
+        let base_time = 10000;
+        let gaps = Arc::new(DataGaps::new());
+        gaps.record(slot_gaps::SlotGap {
+            start_slot: 100,
+            end_slot: 400,
+            start_time: base_time - 120,
+            end_time: base_time - 60,
+        });
+        let mut gapped = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+        gapped.set_data_gaps(gaps);
+        let mut clean = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+
+        for engine in [&mut gapped, &mut clean] {
+            engine.process_trade(make_trade(base_time, "mint", TradeDirection::Buy, 1.0, "wallet_1"));
+        }
+
+        let (_, _, aggregate) = gapped.compute_metrics("mint", base_time).unwrap();
+        assert_eq!(aggregate.last_data_gap_at, Some(base_time - 60));
+        let (_, _, aggregate) = clean.compute_metrics("mint", base_time).unwrap();
+        assert_eq!(aggregate.last_data_gap_at, None);
+    }
+
+    #[test]
+    fn test_stats_report_last_trade_time() {
+        let base_time = 10000;
+        let mut engine = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time + 500));
+        assert_eq!(engine.stats().last_trade_at, 0);
+
+        engine.process_trade(make_trade(base_time, "mint", TradeDirection::Buy, 1.0, "wallet_1"));
+        let stats = engine.stats();
+        assert_eq!(stats.trades_processed, 1);
+        assert_eq!(stats.last_trade_at, base_time);
+    }
+
     #[test]
     fn test_process_trade_updates_state() {
         // Test: process_trade() creates state and adds trades
@@ -500,9 +1280,9 @@ mod tests {
 
         // Verify trade was added (check 60s window has 1 trade)
         let state = engine.states.get(mint).unwrap();
-        assert_eq!(state.trades_60s.len(), 1);
-        assert_eq!(state.trades_300s.len(), 1);
-        assert_eq!(state.trades_900s.len(), 1);
+        assert_eq!(state.trades_60s().len(), 1);
+        assert_eq!(state.trades_300s().len(), 1);
+        assert_eq!(state.trades_900s().len(), 1);
 
         // Process second trade
         let trade2 = make_trade(base_time + 30, mint, TradeDirection::Sell, 0.8, "wallet_2");
@@ -510,8 +1290,8 @@ mod tests {
 
         // Verify both trades present
         let state = engine.states.get(mint).unwrap();
-        assert_eq!(state.trades_60s.len(), 2);
-        assert_eq!(state.trades_300s.len(), 2);
+        assert_eq!(state.trades_60s().len(), 2);
+        assert_eq!(state.trades_300s().len(), 2);
         assert_eq!(state.unique_wallets_300s.len(), 2);
     }
 
@@ -766,6 +1546,70 @@ mod tests {
         assert_ne!(agg1.net_flow_60s_sol, agg2.net_flow_60s_sol);
     }
 
+    #[test]
+    fn test_migration_emits_token_migrated_once() {
+        // Test: a curve migration is a TOKEN_MIGRATED signal on the mint's next flush
+        let base_time = 10000;
+        let mut engine = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+
+        let mint = "migrated_mint";
+        engine.process_migration(TokenMigration {
+            mint: mint.to_string(),
+            launch_platform: "pumpfun".to_string(),
+            destination: "pumpswap".to_string(),
+            pool: "pool".to_string(),
+            sol_amount: 85.0,
+            token_decimals: 6,
+            signature: "sig".to_string(),
+            timestamp: base_time,
+        });
+
+        let (_m1, signals1, agg1) = engine.compute_metrics(mint, base_time + 1).unwrap();
+        let migrated: Vec<_> = signals1
+            .iter()
+            .filter(|s| s.signal_type == SignalType::TokenMigrated)
+            .collect();
+        assert_eq!(migrated.len(), 1);
+        assert_eq!(migrated[0].severity, 3);
+        assert_eq!(agg1.source_program, "pumpfun"); // Stamped by the migration
+
+        // Emitted once, not on every flush
+        let (_m2, signals2, _agg2) = engine.compute_metrics(mint, base_time + 2).unwrap();
+        assert!(!signals2.iter().any(|s| s.signal_type == SignalType::TokenMigrated));
+    }
+
+    #[test]
+    fn test_risk_flags_downgrade_severity() {
+        let base_time = 10000;
+        let mut engine = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+        let cache = Arc::new(RiskFlagsCache::new());
+        cache.set("risky_mint", risk::RISK_MINT_AUTHORITY);
+        engine.set_risk_flags(cache);
+
+        for mint in ["risky_mint", "clean_mint"] {
+            engine.process_migration(TokenMigration {
+                mint: mint.to_string(),
+                launch_platform: "pumpfun".to_string(),
+                destination: "pumpswap".to_string(),
+                pool: "pool".to_string(),
+                sol_amount: 85.0,
+                token_decimals: 6,
+                signature: format!("sig_{}", mint),
+                timestamp: base_time,
+            });
+        }
+
+        let severity = |engine: &mut PipelineEngine, mint: &str| {
+            let (_, signals, _) = engine.compute_metrics(mint, base_time + 1).unwrap();
+            signals
+                .iter()
+                .find(|s| s.signal_type == SignalType::TokenMigrated)
+                .map(|s| s.severity)
+        };
+        assert_eq!(severity(&mut engine, "risky_mint"), Some(2));
+        assert_eq!(severity(&mut engine, "clean_mint"), Some(3));
+    }
+
     #[test]
     fn test_dedup_breakout_persists() {
         // Test: BREAKOUT signal is written once, then deduplicated on subsequent calls
@@ -1023,4 +1867,189 @@ mod tests {
         assert!(engine.last_signal_state.contains_key(mint_b));
         assert_eq!(engine.last_signal_state.len(), 2);
     }
+
+    #[test]
+    fn test_startup_warmup_suppresses_signals() {
+        // Test: Signals inside the startup warm-up are computed but not returned
+        let base_time = 10000;
+        let mut engine = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+        engine.set_signal_warmup(300, 0);
+
+        let mint = "warmup_mint";
+        for i in 0..20 {
+            let trade = make_trade(
+                base_time + i * 3,
+                mint,
+                TradeDirection::Buy,
+                0.5 + (i as f64 * 0.05),
+                &format!("wallet_{}", i % 8),
+            );
+            engine.process_trade(trade);
+        }
+
+        // Inside warm-up: BREAKOUT conditions met but nothing emitted
+        let (_m, signals, _agg) = engine.compute_metrics(mint, base_time + 60).unwrap();
+        assert!(signals.is_empty(), "Signals should be suppressed during warm-up");
+
+        // Suppressed signals are not recorded as active
+        assert!(!engine.last_signal_state.contains_key(mint));
+
+        // Once warm-up is over, the persisting BREAKOUT is emitted
+        engine.set_signal_warmup(0, 0);
+        let (_m, signals, _agg) = engine.compute_metrics(mint, base_time + 60).unwrap();
+        assert!(signals.iter().any(|s| s.signal_type == SignalType::Breakout));
+    }
+
+    #[test]
+    fn test_token_warmup_applies_per_cold_start() {
+        // Test: Per-token warm-up is measured from the token's first trade
+        let base_time = 10000;
+        let mut engine = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+        engine.set_signal_warmup(0, 120);
+
+        let mint = "cold_start_mint";
+        for i in 0..20 {
+            let trade = make_trade(
+                base_time + i * 3,
+                mint,
+                TradeDirection::Buy,
+                0.5 + (i as f64 * 0.05),
+                &format!("wallet_{}", i % 8),
+            );
+            engine.process_trade(trade);
+        }
+
+        let state = engine.states.get(mint).unwrap();
+        assert_eq!(state.first_seen_ts, base_time);
+        assert!(engine.in_warmup(state, base_time + 60));
+        assert!(!engine.in_warmup(state, base_time + 120));
+    }
+
+    /// Custom detector: any single buy of at least `min_sol` in the 60s window
+    struct WhaleEntryDetector {
+        min_sol: f64,
+    }
+
+    impl SignalDetector for WhaleEntryDetector {
+        fn signal_type(&self) -> SignalType {
+            SignalType::Custom("WHALE_ENTRY")
+        }
+
+        fn detect(&self, ctx: &crate::pipeline::state::DetectionContext) -> Option<TokenSignal> {
+            let largest = ctx
+                .state
+                .trades_60s()
+                .iter()
+                .filter(|t| t.direction == TradeDirection::Buy)
+                .map(|t| t.sol_amount)
+                .fold(0.0, f64::max);
+            (largest >= self.min_sol).then(|| {
+                TokenSignal::new(ctx.mint.to_string(), self.signal_type(), 60, ctx.now)
+                    .with_severity(4)
+                    .with_score(largest)
+            })
+        }
+    }
+
+    #[test]
+    fn test_custom_detector_registered_and_deduplicated() {
+        let base_time = 10000;
+        let mut engine = PipelineEngine::new_with_timestamp_fn(Box::new(move || base_time));
+        engine.register_detector(WhaleEntryDetector { min_sol: 50.0 });
+
+        let mint = "whale_mint";
+        engine.process_trade(make_trade(base_time, mint, TradeDirection::Buy, 1.0, "small"));
+        let (_m, signals, _agg) = engine.compute_metrics(mint, base_time).unwrap();
+        assert!(signals.is_empty());
+
+        engine.process_trade(make_trade(base_time + 1, mint, TradeDirection::Buy, 75.0, "whale"));
+        let (_m, signals, _agg) = engine.compute_metrics(mint, base_time + 1).unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type.as_str(), "WHALE_ENTRY");
+        assert_eq!(signals[0].severity, 4);
+
+        // Still active on the next flush: deduplicated
+        let (_m, signals, _agg) = engine.compute_metrics(mint, base_time + 2).unwrap();
+        assert!(signals.is_empty());
+
+        // A registry without built-ins runs only what is registered
+        let mut detectors = SignalDetectorRegistry::new();
+        detectors.register(WhaleEntryDetector { min_sol: 50.0 });
+        assert_eq!(detectors.signal_types(), vec![SignalType::Custom("WHALE_ENTRY")]);
+        detectors.unregister(SignalType::Custom("WHALE_ENTRY"));
+        assert!(detectors.signal_types().is_empty());
+    }
+
+    #[test]
+    fn test_liquidity_drain_from_pool_balances() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+        let t0 = 100_000;
+        let clock = Arc::new(AtomicI64::new(t0));
+        let now_fn = clock.clone();
+        let mut engine =
+            PipelineEngine::new_with_timestamp_fn(Box::new(move || now_fn.load(Ordering::SeqCst)));
+
+        // Pools of untracked mints are ignored
+        engine.process_liquidity("unknown_mint", 40.0);
+        assert!(!engine.is_tracking("unknown_mint"));
+
+        let mint = "pooled_mint";
+        engine.process_trade(make_trade(t0, mint, TradeDirection::Buy, 1.0, "buyer"));
+        engine.process_liquidity(mint, 80.0);
+        let (_m, signals, agg) = engine.compute_metrics(mint, t0).unwrap();
+        assert_eq!(agg.liquidity_sol, Some(80.0));
+        assert!(!signals.iter().any(|s| s.signal_type == SignalType::LiquidityDrain));
+
+        // LP pulled: 80 → 12 SOL within 300s
+        clock.store(t0 + 120, Ordering::SeqCst);
+        engine.process_liquidity(mint, 12.0);
+        let (_m, signals, agg) = engine.compute_metrics(mint, t0 + 120).unwrap();
+        assert_eq!(agg.liquidity_sol, Some(12.0));
+        let drain = signals
+            .iter()
+            .find(|s| s.signal_type == SignalType::LiquidityDrain)
+            .expect("LIQUIDITY_DRAIN");
+        assert_eq!(drain.severity, 4);
+
+        // Once the peak ages out of the window the lower level is the baseline
+        let state = engine.states.get_mut(mint).unwrap();
+        state.record_liquidity(t0 + 500, 12.0);
+        assert_eq!(state.peak_liquidity_300s(t0 + 500), Some(12.0));
+    }
+
+    #[test]
+    fn test_reactivation_after_dormant_prune() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+        let t0 = 100_000;
+        let clock = Arc::new(AtomicI64::new(t0));
+        let now_fn = clock.clone();
+        let mut engine =
+            PipelineEngine::new_with_timestamp_fn(Box::new(move || now_fn.load(Ordering::SeqCst)));
+
+        let mint = "sleepy_mint";
+        engine.process_trade(make_trade(t0, mint, TradeDirection::Buy, 0.5, "early"));
+        engine.prune_inactive_mints(t0 + 7201, 7200);
+        assert!(!engine.states.contains_key(mint));
+
+        // Eight hours later a burst of buys arrives, on this mint and on a new one
+        let t1 = t0 + 8 * 3600;
+        clock.store(t1, Ordering::SeqCst);
+        for i in 0..6 {
+            let wallet = format!("buyer_{}", i);
+            engine.process_trade(make_trade(t1 + i, mint, TradeDirection::Buy, 1.0, &wallet));
+            engine.process_trade(make_trade(t1 + i, "fresh_mint", TradeDirection::Buy, 1.0, &wallet));
+        }
+
+        let state = engine.states.get(mint).unwrap();
+        assert_eq!(state.wake_up.map(|w| w.idle_secs), Some(8 * 3600));
+        let (_m, signals, _agg) = engine.compute_metrics(mint, t1 + 5).unwrap();
+        assert!(signals.iter().any(|s| s.signal_type == SignalType::Reactivation));
+        let (_m, signals, _agg) = engine.compute_metrics("fresh_mint", t1 + 5).unwrap();
+        assert!(!signals.iter().any(|s| s.signal_type == SignalType::Reactivation));
+
+        // Outside the 300s after trading resumed the burst no longer counts
+        engine.process_trade(make_trade(t1 + 400, mint, TradeDirection::Buy, 1.0, "late"));
+        let (_m, signals, _agg) = engine.compute_metrics(mint, t1 + 400).unwrap();
+        assert!(!signals.iter().any(|s| s.signal_type == SignalType::Reactivation));
+    }
 }
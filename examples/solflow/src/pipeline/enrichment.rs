@@ -0,0 +1,271 @@
+//! Ordered enrichment pipeline
+//!
+//! Enrichment sources (DexScreener metadata/prices, persistence scoring, ...)
+//! implement `Enricher` and are pushed onto one `EnrichmentPipeline`, which
+//! runs them in order from a single task, each on its own interval:
+//!
+//! - a stage that fails (error, panic or timeout) is logged and backed off
+//!   exponentially (up to 15 minutes) without delaying the other stages;
+//!   errors that retrying can't fix (`SolflowError::is_retryable`, e.g. bad
+//!   config) go straight to the longest backoff
+//! - nothing runs while another instance holds the lease
+//! - `ENRICHMENT_DISABLE` lists stage names to skip, e.g. `persistence_scoring`
+//!
+//! A new source is one `Enricher` impl plus a `push` in the runtime.
+
+use super::lease::InstanceLease;
+use crate::error::SolflowError;
+use async_trait::async_trait;
+use futures::FutureExt;
+use std::collections::HashSet;
+use std::env;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Longest delay a failing stage is backed off to
+const MAX_BACKOFF: Duration = Duration::from_secs(900);
+
+/// Doublings after which a failing stage sits at `MAX_BACKOFF`
+const MAX_BACKOFF_STEPS: u32 = 6;
+
+/// How often the pipeline task checks for due stages
+const TICK: Duration = Duration::from_secs(1);
+
+/// One enrichment source
+#[async_trait]
+pub trait Enricher: Send {
+    /// Stage name for logs and `ENRICHMENT_DISABLE`
+    fn name(&self) -> &'static str;
+
+    /// Time between runs
+    fn interval(&self) -> Duration;
+
+    /// A run taking longer than this counts as a failure
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(300)
+    }
+
+    /// One enrichment pass
+    async fn enrich(&mut self, now: i64) -> Result<(), SolflowError>;
+}
+
+struct Stage {
+    enricher: Box<dyn Enricher>,
+    next_run: Option<Instant>,
+    consecutive_failures: u32,
+}
+
+impl Stage {
+    /// Delay before the next run, doubled per consecutive failure
+    fn delay(&self) -> Duration {
+        let interval = self.enricher.interval();
+        if self.consecutive_failures == 0 {
+            return interval;
+        }
+        let factor = 1u32 << self.consecutive_failures.min(MAX_BACKOFF_STEPS);
+        interval.saturating_mul(factor).min(MAX_BACKOFF.max(interval))
+    }
+}
+
+/// Enrichers run in push order, each on its own schedule
+#[derive(Default)]
+pub struct EnrichmentPipeline {
+    stages: Vec<Stage>,
+    disabled: HashSet<String>,
+}
+
+impl EnrichmentPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pipeline with `ENRICHMENT_DISABLE` applied
+    pub fn from_env() -> Self {
+        let disabled = env::var("ENRICHMENT_DISABLE")
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        Self {
+            stages: Vec::new(),
+            disabled,
+        }
+    }
+
+    /// Append a stage; returns false if it is disabled
+    pub fn push(&mut self, enricher: impl Enricher + 'static) -> bool {
+        if self.disabled.contains(enricher.name()) {
+            log::info!("⏭️  Enrichment stage '{}' disabled", enricher.name());
+            return false;
+        }
+        self.stages.push(Stage {
+            enricher: Box::new(enricher),
+            next_run: None,
+            consecutive_failures: 0,
+        });
+        true
+    }
+
+    /// (name, interval) of each stage, in run order
+    pub fn stages(&self) -> Vec<(&'static str, Duration)> {
+        self.stages
+            .iter()
+            .map(|stage| (stage.enricher.name(), stage.enricher.interval()))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Run every stage due at `at`, in order; returns the number of stages run
+    ///
+    /// A failing stage never stops the stages after it.
+    pub async fn run_due(&mut self, at: Instant, now: i64) -> usize {
+        let mut ran = 0;
+        for stage in &mut self.stages {
+            if stage.next_run.is_some_and(|next| next > at) {
+                continue;
+            }
+            ran += 1;
+
+            let name = stage.enricher.name();
+            let timeout = stage.enricher.timeout();
+            let run = AssertUnwindSafe(stage.enricher.enrich(now)).catch_unwind();
+            // (reason, whether a retry could succeed)
+            let failure = match tokio::time::timeout(timeout, run).await {
+                Ok(Ok(Ok(()))) => None,
+                Ok(Ok(Err(e))) => Some((e.to_string(), e.is_retryable())),
+                Ok(Err(_)) => Some(("panicked".to_string(), true)),
+                Err(_) => Some((format!("timed out after {}s", timeout.as_secs()), true)),
+            };
+
+            match failure {
+                None => {
+                    if stage.consecutive_failures > 0 {
+                        log::info!("✅ Enrichment stage '{}' recovered", name);
+                    }
+                    stage.consecutive_failures = 0;
+                }
+                Some((reason, retryable)) => {
+                    stage.consecutive_failures += 1;
+                    if !retryable {
+                        stage.consecutive_failures = stage.consecutive_failures.max(MAX_BACKOFF_STEPS);
+                    }
+                    log::error!(
+                        "❌ Enrichment stage '{}' failed ({} in a row, retry in {}s): {}",
+                        name,
+                        stage.consecutive_failures,
+                        stage.delay().as_secs(),
+                        reason
+                    );
+                }
+            }
+            stage.next_run = Some(at + stage.delay());
+        }
+        ran
+    }
+
+    /// Run the pipeline until the runtime exits, skipped while the lease is held elsewhere
+    pub fn spawn(mut self, lease: Option<Arc<InstanceLease>>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TICK);
+            loop {
+                interval.tick().await;
+                if lease.as_ref().is_some_and(|l| !l.is_held()) {
+                    continue;
+                }
+                self.run_due(Instant::now(), chrono::Utc::now().timestamp())
+                    .await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::codes;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct TestStage {
+        name: &'static str,
+        runs: Arc<AtomicUsize>,
+        behavior: fn(usize) -> Result<(), SolflowError>,
+    }
+
+    #[async_trait]
+    impl Enricher for TestStage {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn interval(&self) -> Duration {
+            Duration::from_secs(10)
+        }
+
+        async fn enrich(&mut self, _now: i64) -> Result<(), SolflowError> {
+            let run = self.runs.fetch_add(1, Ordering::SeqCst);
+            (self.behavior)(run)
+        }
+    }
+
+    fn stage(
+        name: &'static str,
+        behavior: fn(usize) -> Result<(), SolflowError>,
+    ) -> (TestStage, Arc<AtomicUsize>) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let stage = TestStage {
+            name,
+            runs: runs.clone(),
+            behavior,
+        };
+        (stage, runs)
+    }
+
+    #[tokio::test]
+    async fn test_failing_stages_back_off_without_blocking_others() {
+        let (failing, failing_runs) = stage("failing", |_| {
+            Err(SolflowError::enrichment(codes::ENRICHMENT_HTTP, "upstream down"))
+        });
+        let (panicking, panicking_runs) = stage("panicking", |run| {
+            if run == 0 {
+                panic!("bad payload");
+            }
+            Ok(())
+        });
+        let (healthy, healthy_runs) = stage("healthy", |_| Ok(()));
+        let (skipped, skipped_runs) = stage("skipped", |_| Ok(()));
+
+        let mut pipeline = EnrichmentPipeline::new();
+        pipeline.disabled.insert("skipped".to_string());
+        assert!(pipeline.push(failing));
+        assert!(pipeline.push(panicking));
+        assert!(pipeline.push(healthy));
+        assert!(!pipeline.push(skipped));
+        assert_eq!(
+            pipeline.stages().iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            vec!["failing", "panicking", "healthy"]
+        );
+
+        let start = Instant::now();
+        assert_eq!(pipeline.run_due(start, 0).await, 3);
+        assert_eq!(pipeline.run_due(start + Duration::from_secs(5), 0).await, 0);
+
+        // Healthy stage keeps its interval; failed stages wait twice as long
+        assert_eq!(pipeline.run_due(start + Duration::from_secs(10), 0).await, 1);
+        assert_eq!(pipeline.run_due(start + Duration::from_secs(20), 0).await, 3);
+
+        assert_eq!(healthy_runs.load(Ordering::SeqCst), 3);
+        assert_eq!(failing_runs.load(Ordering::SeqCst), 2);
+        assert_eq!(panicking_runs.load(Ordering::SeqCst), 2);
+        assert_eq!(skipped_runs.load(Ordering::SeqCst), 0);
+
+        // The panicking stage recovered; the failing one backs off further
+        assert_eq!(pipeline.stages[1].consecutive_failures, 0);
+        assert_eq!(pipeline.stages[0].consecutive_failures, 2);
+        assert_eq!(pipeline.stages[0].delay(), Duration::from_secs(40));
+    }
+}
@@ -0,0 +1,310 @@
+//! Latency-optimized fast path for watched mints
+//!
+//! Trades normally wait in the batch channel and are only aggregated and
+//! signal-checked on the next flush (`AGGREGATE_FLUSH_INTERVAL_MS`). For the
+//! handful of mints someone is actively watching that is too slow, so the
+//! unified streamer routes their trades to a separate, small channel. Each
+//! fast-path trade is applied to the shared engine and its mint is
+//! recomputed and written immediately; everything else keeps going through
+//! the batched flush.
+//!
+//! Watched mints are `FAST_PATH_MINTS` plus followed tokens
+//! (`token_metadata.follow_price = 1`), re-read every
+//! `FAST_PATH_REFRESH_SECS`, plus promoted tokens of interest (see
+//! `interest`). When the fast channel is full a trade falls back
+//! to the batch channel rather than being dropped.
+//!
+//! The batched flush still covers fast-path mints (they stay "touched"), and
+//! signal deduplication in the engine keeps a signal from being written twice.
+//! Bot history for BOT_DROPOFF is only advanced by the batched flush.
+
+use super::db::AggregateDbWriter;
+use super::engine::PipelineEngine;
+use super::interest::TokensOfInterest;
+use super::lease::InstanceLease;
+use super::shards::ShardedEngine;
+use super::signals::TokenSignal;
+use super::types::{AggregatedTokenState, TradeEvent};
+use crate::error::SolflowError;
+use rusqlite::{Connection, OpenFlags};
+use std::collections::HashSet;
+use std::env;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Fast path configuration (FAST_PATH_* env vars)
+#[derive(Debug, Clone)]
+pub struct FastPathConfig {
+    /// Mints always on the fast path (FAST_PATH_MINTS, comma-separated)
+    pub pinned_mints: Vec<String>,
+    /// Also route followed tokens (FAST_PATH_FOLLOWED, default: true)
+    pub include_followed: bool,
+    /// How often followed tokens are re-read (FAST_PATH_REFRESH_SECS, default: 30)
+    pub refresh_secs: u64,
+    /// Fast channel capacity (FAST_PATH_CHANNEL_BUFFER, default: 1000)
+    pub channel_buffer: usize,
+}
+
+impl FastPathConfig {
+    /// Load from env; None when FAST_PATH_ENABLED is not true
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("FAST_PATH_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let pinned_mints = env::var("FAST_PATH_MINTS")
+            .map(|v| {
+                v.split(',')
+                    .map(|mint| mint.trim().to_string())
+                    .filter(|mint| !mint.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            pinned_mints,
+            include_followed: env::var("FAST_PATH_FOLLOWED")
+                .map(|v| !v.eq_ignore_ascii_case("false") && v != "0")
+                .unwrap_or(true),
+            refresh_secs: env::var("FAST_PATH_REFRESH_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            channel_buffer: env::var("FAST_PATH_CHANNEL_BUFFER")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1000),
+        })
+    }
+}
+
+/// Shared set of mints on the fast path
+///
+/// Pinned mints are fixed; followed mints are replaced on every refresh;
+/// tokens of interest come and go as the engine promotes and demotes them.
+#[derive(Clone, Default)]
+pub struct WatchedMints {
+    pinned: Arc<HashSet<String>>,
+    followed: Arc<RwLock<HashSet<String>>>,
+    interest: Option<TokensOfInterest>,
+}
+
+impl WatchedMints {
+    pub fn new(pinned: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            pinned: Arc::new(pinned.into_iter().collect()),
+            followed: Arc::default(),
+            interest: None,
+        }
+    }
+
+    /// Also watch promoted tokens of interest
+    pub fn with_interest(mut self, interest: TokensOfInterest) -> Self {
+        self.interest = Some(interest);
+        self
+    }
+
+    pub fn contains(&self, mint: &str) -> bool {
+        self.pinned.contains(mint)
+            || self.followed.read().unwrap().contains(mint)
+            || self.interest.as_ref().is_some_and(|interest| interest.contains(mint))
+    }
+
+    /// Replace the followed set; returns the number of followed mints
+    pub fn set_followed(&self, mints: HashSet<String>) -> usize {
+        let count = mints.len();
+        *self.followed.write().unwrap() = mints;
+        count
+    }
+
+    /// Re-read followed tokens from `db_path` every `refresh_secs`
+    pub fn spawn_followed_refresh(&self, db_path: String, refresh_secs: u64) {
+        let watched = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(refresh_secs.max(1)));
+            loop {
+                interval.tick().await;
+                let path = db_path.clone();
+                match tokio::task::spawn_blocking(move || load_followed_mints(&path)).await {
+                    Ok(Ok(mints)) => {
+                        let count = watched.set_followed(mints);
+                        log::debug!("⚡ Fast path: {} followed mints", count);
+                    }
+                    Ok(Err(e)) => log::warn!("⚠️  Fast path: failed to load followed mints: {}", e),
+                    Err(e) => log::warn!("⚠️  Fast path: followed mint refresh panicked: {}", e),
+                }
+            }
+        });
+    }
+}
+
+/// Mints with `follow_price = 1` in token_metadata
+pub fn load_followed_mints(db_path: &str) -> Result<HashSet<String>, rusqlite::Error> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    let mut stmt = conn.prepare("SELECT mint FROM token_metadata WHERE follow_price = 1")?;
+    let mints = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<HashSet<_>, _>>()?;
+    Ok(mints)
+}
+
+/// Streamer-side router: sends watched mints to the fast channel
+#[derive(Clone)]
+pub struct FastPathRoute {
+    watched: WatchedMints,
+    tx: mpsc::Sender<TradeEvent>,
+}
+
+impl FastPathRoute {
+    pub fn new(watched: WatchedMints, tx: mpsc::Sender<TradeEvent>) -> Self {
+        Self { watched, tx }
+    }
+
+    /// Try the fast channel; gives the trade back if the mint isn't watched
+    /// or the channel is full/closed, so the caller can use the batch channel
+    pub fn try_route(&self, trade: TradeEvent) -> Result<(), TradeEvent> {
+        if !self.watched.contains(&trade.mint) {
+            return Err(trade);
+        }
+        self.tx.try_send(trade).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(trade) => {
+                log::debug!("⚡ Fast path channel full, using batch channel for {}", trade.mint);
+                trade
+            }
+            mpsc::error::TrySendError::Closed(trade) => trade,
+        })
+    }
+}
+
+/// Apply one trade and recompute its mint (single lock acquisition)
+pub fn process_watched_trade(
+    engine: &Mutex<PipelineEngine>,
+    trade: TradeEvent,
+) -> Result<(AggregatedTokenState, Vec<TokenSignal>), SolflowError> {
+    let mint = trade.mint.clone();
+    let mut engine_guard = engine.lock().unwrap();
+    engine_guard.process_trade(trade);
+    let now = engine_guard.now();
+    let (_metrics, signals, aggregate) = engine_guard.compute_metrics(&mint, now)?;
+    Ok((aggregate, signals))
+}
+
+/// Consume the fast channel until it closes
+///
+/// Without the lease (standby) trades are still applied to the engine, but
+/// nothing is written, matching the batched ingestion loop.
+pub async fn start_fast_path(
+    mut rx: mpsc::Receiver<TradeEvent>,
+    engine: Arc<ShardedEngine>,
+    db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
+    lease: Option<Arc<InstanceLease>>,
+) {
+    log::info!("⚡ Fast path ingestion started");
+
+    while let Some(trade) = rx.recv().await {
+        let started = Instant::now();
+        let mint = trade.mint.clone();
+
+        engine.record_session(&trade);
+        let (aggregate, signals) = match process_watched_trade(engine.shard(&mint), trade) {
+            Ok(result) => result,
+            Err(e) => {
+                log::warn!("⚠️  Fast path: failed to compute metrics for {}: {}", mint, e);
+                continue;
+            }
+        };
+
+        if lease.as_ref().is_some_and(|l| !l.is_held()) {
+            continue;
+        }
+
+        match db_writer.write_aggregates(vec![aggregate]).await {
+            Ok(_) => {
+                let flushed_at_ms = chrono::Utc::now().timestamp_millis();
+                engine.record_flush_latency(std::slice::from_ref(&mint), flushed_at_ms);
+            }
+            Err(e) => log::error!("❌ Fast path: failed to write aggregate for {}: {}", mint, e),
+        }
+
+        for signal in signals {
+            match db_writer.write_signal(signal.clone()).await {
+                Ok(_) => log::info!(
+                    "🚨⚡ {} signal for {} ({}ms after receipt)",
+                    signal.signal_type.as_str(),
+                    mint,
+                    started.elapsed().as_millis()
+                ),
+                // Blocklisted mint - this is expected
+                Err(e) if e.is_mint_blocked() => log::debug!(
+                    "⚠️  Fast path signal not written (mint: {}, type: {:?}): {}",
+                    mint,
+                    signal.signal_type,
+                    e
+                ),
+                Err(e) => log::warn!(
+                    "⚠️  Fast path: failed to write signal (mint: {}, type: {:?}): {}",
+                    mint,
+                    signal.signal_type,
+                    e
+                ),
+            }
+        }
+
+        log::debug!("⚡ Fast path: {} in {}ms", mint, started.elapsed().as_millis());
+    }
+
+    log::info!("✅ Fast path ingestion stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_trade(mint: &str, timestamp: i64, sol_amount: f64) -> TradeEvent {
+        TradeEvent {
+            sol_amount,
+            user_account: format!("wallet_{}", timestamp),
+            ..TradeEvent::test_buy(mint, timestamp)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_route_only_watched_mints() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let watched = WatchedMints::new(vec!["pinned".to_string()]);
+        let route = FastPathRoute::new(watched.clone(), tx);
+
+        assert!(route.try_route(make_trade("other", 1, 1.0)).is_err());
+        assert!(route.try_route(make_trade("pinned", 1, 1.0)).is_ok());
+        // Full channel hands the trade back for the batch channel
+        let returned = route.try_route(make_trade("pinned", 2, 1.0)).unwrap_err();
+        assert_eq!(returned.timestamp, 2);
+        assert_eq!(rx.recv().await.unwrap().mint, "pinned");
+
+        watched.set_followed(HashSet::from(["followed".to_string()]));
+        assert!(route.try_route(make_trade("followed", 3, 1.0)).is_ok());
+        watched.set_followed(HashSet::new());
+        assert!(route.try_route(make_trade("followed", 4, 1.0)).is_err());
+    }
+
+    #[test]
+    fn test_watched_trade_aggregated_immediately() {
+        let now = 1_700_000_000;
+        let engine = Mutex::new(PipelineEngine::new_with_timestamp_fn(Box::new(move || now)));
+
+        let (aggregate, _signals) =
+            process_watched_trade(&engine, make_trade("watched", now, 2.5)).unwrap();
+        assert_eq!(aggregate.mint, "watched");
+        assert_eq!(aggregate.buy_count_60s, Some(1));
+
+        let (aggregate, _signals) =
+            process_watched_trade(&engine, make_trade("watched", now, 1.5)).unwrap();
+        assert_eq!(aggregate.buy_count_60s, Some(2));
+        assert!((aggregate.net_flow_60s_sol.unwrap() - 4.0).abs() < 1e-9);
+    }
+}
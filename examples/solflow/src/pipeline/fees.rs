@@ -0,0 +1,209 @@
+//! Per-venue fee models for net cost and fee-adjusted flow
+//!
+//! `sol_amount` is the swap amount before fees, so a round trip on a 1% venue
+//! shows zero net flow while traders lost 2% of it. Each venue
+//! (`source_program`) gets a swap fee in basis points; together with the
+//! transaction fee (`fee_lamports`, base + priority fee) this gives:
+//!
+//! - per trade: net cost of a buy (`sol_amount` + fees) or net proceeds of a
+//!   sell (`sol_amount` - fees)
+//! - per window: venue fees paid and fee-adjusted net flow (net flow minus
+//!   every fee paid, since fees leave the market on both sides)
+//!
+//! Built-in venue fees (bps): PumpFun 100, PumpSwap 25 (LP + protocol),
+//! BonkSwap 100, Moonshot 100, JupiterDCA 10, RaydiumAMM 25, anything else 0
+//! (Raydium CLMM, Orca Whirlpool and Meteora fees depend on the pool).
+//! `FEE_MODELS` overrides them, e.g. `PumpSwap=30,pump.fun=125,*=25` (`*` =
+//! unlisted venues). Venue names match case-insensitively, ignoring
+//! punctuation.
+
+use super::types::{TradeDirection, TradeEvent};
+use std::collections::HashMap;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Highest accepted venue fee (50%)
+const MAX_FEE_BPS: u32 = 5_000;
+
+const BUILTIN_FEE_BPS: &[(&str, u32)] = &[
+    ("pumpfun", 100),
+    ("pumpswap", 25),
+    ("bonkswap", 100),
+    ("moonshot", 100),
+    ("jupiterdca", 10),
+    ("raydiumamm", 25),
+];
+
+/// Fees paid by one trade, in SOL
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TradeFees {
+    /// Venue swap fee (LP + protocol/creator)
+    pub venue_fee_sol: f64,
+    /// Transaction fee, base + priority
+    pub network_fee_sol: f64,
+}
+
+impl TradeFees {
+    pub fn total_sol(&self) -> f64 {
+        self.venue_fee_sol + self.network_fee_sol
+    }
+
+    /// Net cost of a buy or net proceeds of a sell (sol_amount for unknown direction)
+    pub fn net_sol(&self, trade: &TradeEvent) -> f64 {
+        match trade.direction {
+            TradeDirection::Buy => trade.sol_amount + self.total_sol(),
+            TradeDirection::Sell => (trade.sol_amount - self.total_sol()).max(0.0),
+            TradeDirection::Unknown => trade.sol_amount,
+        }
+    }
+}
+
+/// Fee totals for a window of trades, in SOL
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WindowFees {
+    pub venue_fees_sol: f64,
+    pub network_fees_sol: f64,
+    /// Net flow minus every fee paid by the window's buys and sells
+    pub fee_adjusted_net_flow_sol: f64,
+}
+
+/// Venue fee (bps) per `source_program`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeModels {
+    venue_bps: HashMap<String, u32>,
+    default_bps: u32,
+}
+
+impl Default for FeeModels {
+    fn default() -> Self {
+        Self {
+            venue_bps: BUILTIN_FEE_BPS
+                .iter()
+                .map(|(venue, bps)| (venue.to_string(), *bps))
+                .collect(),
+            default_bps: 0,
+        }
+    }
+}
+
+impl FeeModels {
+    /// Built-in models with `FEE_MODELS` applied (invalid specs are logged and ignored)
+    pub fn from_env() -> Self {
+        match std::env::var("FEE_MODELS") {
+            Ok(spec) => Self::parse(&spec).unwrap_or_else(|e| {
+                log::warn!("⚠️  Ignoring FEE_MODELS: {}", e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Built-in models overridden by `venue=bps` pairs; `*` sets unlisted venues
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut models = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (venue, bps) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected venue=bps, got '{}'", entry))?;
+            let bps: u32 = bps
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid fee '{}' for {}", bps.trim(), venue.trim()))?;
+            if bps > MAX_FEE_BPS {
+                return Err(format!("fee {} bps for {} is above {}", bps, venue.trim(), MAX_FEE_BPS));
+            }
+            match venue.trim() {
+                "*" => models.default_bps = bps,
+                venue => {
+                    models.venue_bps.insert(normalize_venue(venue), bps);
+                }
+            }
+        }
+        Ok(models)
+    }
+
+    /// Swap fee for `source_program`, in basis points
+    pub fn venue_fee_bps(&self, source_program: &str) -> u32 {
+        self.venue_bps
+            .get(&normalize_venue(source_program))
+            .copied()
+            .unwrap_or(self.default_bps)
+    }
+
+    pub fn trade_fees(&self, trade: &TradeEvent) -> TradeFees {
+        let bps = self.venue_fee_bps(&trade.source_program);
+        TradeFees {
+            venue_fee_sol: trade.sol_amount * bps as f64 / 10_000.0,
+            network_fee_sol: trade.fee_lamports as f64 / LAMPORTS_PER_SOL,
+        }
+    }
+
+    /// Fee totals and fee-adjusted net flow for `trades`
+    pub fn window_fees(&self, trades: &[TradeEvent]) -> WindowFees {
+        let mut window = WindowFees::default();
+        for trade in trades {
+            let fees = self.trade_fees(trade);
+            window.venue_fees_sol += fees.venue_fee_sol;
+            window.network_fees_sol += fees.network_fee_sol;
+            let flow = match trade.direction {
+                TradeDirection::Buy => trade.sol_amount,
+                TradeDirection::Sell => -trade.sol_amount,
+                TradeDirection::Unknown => 0.0,
+            };
+            window.fee_adjusted_net_flow_sol += flow - fees.total_sol();
+        }
+        window
+    }
+}
+
+/// `Pump.fun`, `pump_fun` and `PumpFun` all map to `pumpfun`
+fn normalize_venue(venue: &str) -> String {
+    venue
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(program: &str, direction: TradeDirection, sol_amount: f64) -> TradeEvent {
+        TradeEvent {
+            direction,
+            sol_amount,
+            source_program: program.to_string(),
+            fee_lamports: 10_000_000,
+            ..TradeEvent::test_buy("mint", 1000)
+        }
+    }
+
+    #[test]
+    fn test_fee_models_net_cost_and_adjusted_flow() {
+        let models = FeeModels::parse("PumpSwap=30, *=5").unwrap();
+        assert_eq!(models.venue_fee_bps("pump.fun"), 100);
+        assert_eq!(models.venue_fee_bps("pumpswap"), 30);
+        assert_eq!(models.venue_fee_bps("SomeDex"), 5);
+        assert_eq!(FeeModels::default().venue_fee_bps("SomeDex"), 0);
+        assert!(FeeModels::parse("PumpSwap").is_err());
+        assert!(FeeModels::parse("PumpSwap=abc").is_err());
+        assert!(FeeModels::parse("PumpSwap=6000").is_err());
+
+        // 1% venue fee + 0.01 SOL transaction fee
+        let buy = trade("PumpFun", TradeDirection::Buy, 2.0);
+        let fees = models.trade_fees(&buy);
+        assert!((fees.venue_fee_sol - 0.02).abs() < 1e-9);
+        assert!((fees.network_fee_sol - 0.01).abs() < 1e-9);
+        assert!((fees.net_sol(&buy) - 2.03).abs() < 1e-9);
+
+        let sell = trade("PumpFun", TradeDirection::Sell, 2.0);
+        assert!((models.trade_fees(&sell).net_sol(&sell) - 1.97).abs() < 1e-9);
+
+        // Round trip: zero net flow, but the fees left the market
+        let window = models.window_fees(&[buy, sell]);
+        assert!((window.venue_fees_sol - 0.04).abs() < 1e-9);
+        assert!((window.network_fees_sol - 0.02).abs() < 1e-9);
+        assert!((window.fee_adjusted_net_flow_sol + 0.06).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,413 @@
+//! Long-term aggregate history in ClickHouse
+//!
+//! `token_aggregates` is UPSERT-only, so every flush overwrites the previous
+//! values. With `CLICKHOUSE_URL` set, the runtime wraps its database writer in
+//! a `ClickHouseHistoryWriter`: aggregates are written to SQLite as before,
+//! then the first snapshot of each mint in every `HISTORY_INTERVAL_SECS`
+//! interval is appended to `token_aggregate_history`
+//! (`/sql/clickhouse/001_token_aggregate_history.sql`).
+//!
+//! Inserts run on a background task over the ClickHouse HTTP interface
+//! (`FORMAT JSONEachRow`). They are best effort: a failed or backed-up insert
+//! is logged and dropped, never failing or delaying the SQLite write.
+//!
+//! Environment variables:
+//! - `CLICKHOUSE_URL`: HTTP endpoint, e.g. `http://clickhouse:8123` (unset = disabled);
+//!   accepts `file:`/`keyring:` references
+//! - `CLICKHOUSE_DATABASE`: Database (default: default)
+//! - `CLICKHOUSE_USER` / `CLICKHOUSE_PASSWORD`: Credentials (default: none)
+//! - `HISTORY_INTERVAL_SECS`: Snapshot interval per mint (default: 60)
+
+use crate::error::{codes, SolflowError};
+use crate::pipeline::audit_trades::AuditTrade;
+use crate::pipeline::db::AggregateDbWriter;
+use crate::pipeline::sessions::SessionRollup;
+use crate::pipeline::signals::TokenSignal;
+use crate::pipeline::types::AggregatedTokenState;
+use crate::streamer_core::secrets::{redact_url, secret_from_env};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Applied on startup; ClickHouse schema is not covered by `run_schema_migrations`
+const HISTORY_DDL: &str = include_str!("../../sql/clickhouse/001_token_aggregate_history.sql");
+
+/// Insert batches queued for the background task before new ones are dropped
+const QUEUE_CAPACITY: usize = 64;
+
+/// Tracked mints above which mints not seen in the last interval are forgotten
+const MAX_TRACKED_MINTS: usize = 50_000;
+
+#[derive(Debug, Clone)]
+pub struct HistoryConfig {
+    pub url: String,
+    pub database: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub interval_secs: i64,
+}
+
+impl HistoryConfig {
+    /// None unless `CLICKHOUSE_URL` is set
+    pub fn from_env() -> Result<Option<Self>, SolflowError> {
+        let secret = |name: &str| {
+            secret_from_env(name).map_err(|e| SolflowError::config(codes::CONFIG_INVALID, e))
+        };
+        let Some(url) = secret("CLICKHOUSE_URL")? else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            url: url.trim_end_matches('/').to_string(),
+            database: env::var("CLICKHOUSE_DATABASE")
+                .ok()
+                .filter(|d| !d.trim().is_empty())
+                .unwrap_or_else(|| "default".to_string()),
+            user: env::var("CLICKHOUSE_USER").ok().filter(|u| !u.is_empty()),
+            password: secret("CLICKHOUSE_PASSWORD")?,
+            interval_secs: env::var("HISTORY_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60i64)
+                .max(1),
+        }))
+    }
+}
+
+/// One `token_aggregate_history` row
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryRow {
+    pub mint: String,
+    pub minute: String,
+    pub source_program: String,
+    pub price_usd: Option<f64>,
+    pub market_cap_usd: Option<f64>,
+    pub net_flow_60s_sol: Option<f64>,
+    pub net_flow_300s_sol: Option<f64>,
+    pub net_flow_900s_sol: Option<f64>,
+    pub net_flow_3600s_sol: Option<f64>,
+    pub net_flow_7200s_sol: Option<f64>,
+    pub net_flow_14400s_sol: Option<f64>,
+    pub buy_count_60s: Option<i32>,
+    pub sell_count_60s: Option<i32>,
+    pub buy_count_300s: Option<i32>,
+    pub sell_count_300s: Option<i32>,
+    pub buy_count_900s: Option<i32>,
+    pub sell_count_900s: Option<i32>,
+    pub unique_wallets_300s: Option<i32>,
+    pub new_wallets_300s: Option<i32>,
+    pub unique_wallets_3600s: Option<i32>,
+    pub unique_wallets_7200s: Option<i32>,
+    pub unique_wallets_14400s: Option<i32>,
+    pub volume_300s_sol: Option<f64>,
+    pub updated_at: String,
+}
+
+impl HistoryRow {
+    /// Snapshot of `aggregate` for the interval starting at `interval_start`
+    pub fn from_aggregate(aggregate: &AggregatedTokenState, interval_start: i64) -> Self {
+        Self {
+            mint: aggregate.mint.clone(),
+            minute: clickhouse_datetime(interval_start),
+            source_program: aggregate.source_program.clone(),
+            price_usd: aggregate.price_usd,
+            market_cap_usd: aggregate.market_cap_usd,
+            net_flow_60s_sol: aggregate.net_flow_60s_sol,
+            net_flow_300s_sol: aggregate.net_flow_300s_sol,
+            net_flow_900s_sol: aggregate.net_flow_900s_sol,
+            net_flow_3600s_sol: aggregate.net_flow_3600s_sol,
+            net_flow_7200s_sol: aggregate.net_flow_7200s_sol,
+            net_flow_14400s_sol: aggregate.net_flow_14400s_sol,
+            buy_count_60s: aggregate.buy_count_60s,
+            sell_count_60s: aggregate.sell_count_60s,
+            buy_count_300s: aggregate.buy_count_300s,
+            sell_count_300s: aggregate.sell_count_300s,
+            buy_count_900s: aggregate.buy_count_900s,
+            sell_count_900s: aggregate.sell_count_900s,
+            unique_wallets_300s: aggregate.unique_wallets_300s,
+            new_wallets_300s: aggregate.new_wallets_300s,
+            unique_wallets_3600s: aggregate.unique_wallets_3600s,
+            unique_wallets_7200s: aggregate.unique_wallets_7200s,
+            unique_wallets_14400s: aggregate.unique_wallets_14400s,
+            volume_300s_sol: aggregate.volume_300s_sol,
+            updated_at: clickhouse_datetime(aggregate.updated_at),
+        }
+    }
+}
+
+/// `YYYY-MM-DD HH:MM:SS` (UTC), the default ClickHouse `DateTime` input format
+fn clickhouse_datetime(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// Picks the first snapshot of each mint in every interval
+#[derive(Debug)]
+struct SnapshotSchedule {
+    interval_secs: i64,
+    /// mint → start of the last interval it was snapshotted in
+    last_interval: HashMap<String, i64>,
+}
+
+impl SnapshotSchedule {
+    fn new(interval_secs: i64) -> Self {
+        Self {
+            interval_secs: interval_secs.max(1),
+            last_interval: HashMap::new(),
+        }
+    }
+
+    /// Rows for the aggregates whose mint has no snapshot in its current interval yet
+    fn due(&mut self, aggregates: &[AggregatedTokenState]) -> Vec<HistoryRow> {
+        let mut rows = Vec::new();
+        let mut latest = i64::MIN;
+        for aggregate in aggregates {
+            let updated_at = aggregate.updated_at;
+            let interval_start = updated_at - updated_at.rem_euclid(self.interval_secs);
+            latest = latest.max(interval_start);
+            let last = self.last_interval.get(&aggregate.mint).copied();
+            if last.is_some_and(|last| last >= interval_start) {
+                continue;
+            }
+            self.last_interval.insert(aggregate.mint.clone(), interval_start);
+            rows.push(HistoryRow::from_aggregate(aggregate, interval_start));
+        }
+
+        if self.last_interval.len() > MAX_TRACKED_MINTS {
+            let cutoff = latest - self.interval_secs;
+            self.last_interval.retain(|_, last| *last >= cutoff);
+        }
+        rows
+    }
+}
+
+/// Minimal ClickHouse HTTP client
+#[derive(Clone)]
+struct ClickHouseClient {
+    http: reqwest::Client,
+    config: HistoryConfig,
+}
+
+impl ClickHouseClient {
+    fn new(config: HistoryConfig) -> Result<Self, SolflowError> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| SolflowError::config(codes::CONFIG_INVALID, e))?;
+        Ok(Self { http, config })
+    }
+
+    /// Run `query`, with `body` as its input data (or as the query itself when `query` is None)
+    async fn execute(&self, query: Option<&str>, body: String) -> Result<(), SolflowError> {
+        let mut params = vec![("database", self.config.database.as_str())];
+        if let Some(query) = query {
+            params.push(("query", query));
+        }
+        let mut request = self.http.post(&self.config.url).query(&params).body(body);
+        if let Some(user) = &self.config.user {
+            request = request.header("X-ClickHouse-User", user);
+        }
+        if let Some(password) = &self.config.password {
+            request = request.header("X-ClickHouse-Key", password);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| SolflowError::db(codes::DB_WRITER, format!("ClickHouse request failed: {}", e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(SolflowError::db(
+                codes::DB_WRITER,
+                format!("ClickHouse returned {}: {}", status, text.trim()),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn insert(&self, rows: &[HistoryRow]) -> Result<(), SolflowError> {
+        let mut body = String::new();
+        for row in rows {
+            body.push_str(
+                &serde_json::to_string(row)
+                    .map_err(|e| SolflowError::db(codes::DB_INVALID_DATA, e))?,
+            );
+            body.push('\n');
+        }
+        self.execute(Some("INSERT INTO token_aggregate_history FORMAT JSONEachRow"), body)
+            .await
+    }
+}
+
+/// `AggregateDbWriter` that also appends aggregate snapshots to ClickHouse
+///
+/// `as_any` forwards to the inner writer so downcasts (DCA bucket cleanup)
+/// keep working through the wrapper.
+pub struct ClickHouseHistoryWriter {
+    inner: Arc<dyn AggregateDbWriter + Send + Sync>,
+    schedule: Mutex<SnapshotSchedule>,
+    tx: mpsc::Sender<Vec<HistoryRow>>,
+}
+
+impl ClickHouseHistoryWriter {
+    /// Create the history table and start the insert task
+    ///
+    /// Fails if ClickHouse can't be reached at startup; later outages only
+    /// drop history rows.
+    pub async fn connect(
+        inner: Arc<dyn AggregateDbWriter + Send + Sync>,
+        config: HistoryConfig,
+    ) -> Result<Self, SolflowError> {
+        let interval_secs = config.interval_secs;
+        let url = redact_url(&config.url);
+        let client = ClickHouseClient::new(config)?;
+        client
+            .execute(None, HISTORY_DDL.to_string())
+            .await
+            .map_err(|e| SolflowError::db(codes::DB_SCHEMA, format!("ClickHouse history table: {}", e)))?;
+        log::info!("✅ ClickHouse history ready: {} (every {}s)", url, interval_secs);
+
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(run_inserts(client, rx));
+        Ok(Self {
+            inner,
+            schedule: Mutex::new(SnapshotSchedule::new(interval_secs)),
+            tx,
+        })
+    }
+}
+
+async fn run_inserts(client: ClickHouseClient, mut rx: mpsc::Receiver<Vec<HistoryRow>>) {
+    let mut failing = false;
+    while let Some(rows) = rx.recv().await {
+        match client.insert(&rows).await {
+            Ok(()) => {
+                if failing {
+                    log::info!("✅ ClickHouse history inserts recovered");
+                }
+                failing = false;
+            }
+            Err(e) => {
+                // Warn once per outage, the rest at debug
+                if failing {
+                    log::debug!("ClickHouse history insert failed ({} rows dropped): {}", rows.len(), e);
+                } else {
+                    log::warn!("⚠️  ClickHouse history insert failed ({} rows dropped): {}", rows.len(), e);
+                }
+                failing = true;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AggregateDbWriter for ClickHouseHistoryWriter {
+    async fn write_aggregates(
+        &self,
+        aggregates: Vec<AggregatedTokenState>,
+    ) -> Result<(), SolflowError> {
+        let rows = self.schedule.lock().unwrap().due(&aggregates);
+        self.inner.write_aggregates(aggregates).await?;
+        if !rows.is_empty() {
+            if let Err(mpsc::error::TrySendError::Full(rows)) = self.tx.try_send(rows) {
+                log::warn!("⚠️  ClickHouse history queue full, dropping {} rows", rows.len());
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_signal(&self, signal: TokenSignal) -> Result<(), SolflowError> {
+        self.inner.write_signal(signal).await
+    }
+
+    async fn write_signals(&self, signals: Vec<TokenSignal>) -> Result<Vec<TokenSignal>, SolflowError> {
+        self.inner.write_signals(signals).await
+    }
+
+    async fn write_system_metric(
+        &self,
+        key: &str,
+        value_json: &str,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_system_metric(key, value_json).await
+    }
+
+    async fn write_session_rollups(
+        &self,
+        rollups: Vec<SessionRollup>,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_session_rollups(rollups).await
+    }
+
+    async fn write_audit_trades(
+        &self,
+        trades: Vec<AuditTrade>,
+        prune_before: i64,
+    ) -> Result<(), SolflowError> {
+        self.inner.write_audit_trades(trades, prune_before).await
+    }
+
+    async fn compact_signal_details(
+        &self,
+        before: i64,
+        limit: usize,
+    ) -> Result<usize, SolflowError> {
+        self.inner.compact_signal_details(before, limit).await
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.inner.as_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::state::TokenRollingState;
+
+    fn aggregate(mint: &str, updated_at: i64, net_flow_300s: f64) -> AggregatedTokenState {
+        let metrics = TokenRollingState::new(mint.to_string()).compute_rolling_metrics();
+        let mut aggregate =
+            AggregatedTokenState::from_metrics(mint, &metrics, None, updated_at, updated_at);
+        aggregate.net_flow_300s_sol = Some(net_flow_300s);
+        aggregate.unique_wallets_300s = Some(4);
+        aggregate
+    }
+
+    #[test]
+    fn test_one_snapshot_per_mint_per_interval() {
+        let mut schedule = SnapshotSchedule::new(60);
+
+        let rows = schedule.due(&[
+            aggregate("a", 1_700_000_010, 1.0),
+            aggregate("b", 1_700_000_010, 2.0),
+        ]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].minute, "2023-11-14 22:13:00");
+        assert_eq!(rows[0].updated_at, "2023-11-14 22:13:30");
+        assert_eq!(rows[0].net_flow_300s_sol, Some(1.0));
+        assert_eq!(rows[0].unique_wallets_300s, Some(4));
+
+        // Same minute: already snapshotted
+        assert!(schedule.due(&[aggregate("a", 1_700_000_030, 3.0)]).is_empty());
+
+        // Next minute for "a", first sighting of "c"
+        let rows = schedule.due(&[
+            aggregate("a", 1_700_000_045, 4.0),
+            aggregate("c", 1_700_000_020, 5.0),
+        ]);
+        let mints: Vec<&str> = rows.iter().map(|row| row.mint.as_str()).collect();
+        assert_eq!(mints, vec!["a", "c"]);
+        assert_eq!(rows[0].minute, "2023-11-14 22:14:00");
+
+        let json = serde_json::to_value(&rows[1]).unwrap();
+        assert_eq!(json["mint"], "c");
+        assert_eq!(json["price_usd"], serde_json::Value::Null);
+    }
+}
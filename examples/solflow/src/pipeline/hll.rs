@@ -0,0 +1,191 @@
+//! HyperLogLog distinct counter for wallets
+//!
+//! Minute buckets need a mergeable unique-wallet count that doesn't grow
+//! with the number of wallets. Small sets (the common case: most minutes see
+//! a handful of wallets) are kept as exact hash lists; past
+//! `SPARSE_LIMIT` hashes the sketch switches to `REGISTERS` one-byte
+//! registers.
+//!
+//! Accuracy: exact while sparse; dense estimates have a standard error of
+//! 1.04 / sqrt(REGISTERS) ≈ 3.3% (within ±6.5% for ~95% of estimates).
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Register index bits
+const PRECISION: u32 = 10;
+
+/// Number of dense registers (1 KiB per dense sketch)
+pub const REGISTERS: usize = 1 << PRECISION;
+
+/// Distinct hashes kept exactly before switching to registers
+const SPARSE_LIMIT: usize = 64;
+
+/// Relative standard error of a dense estimate
+pub const STANDARD_ERROR: f64 = 1.04 / 32.0; // 1.04 / sqrt(REGISTERS)
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+enum Repr {
+    Sparse(Vec<u64>),
+    Dense(Box<[u8]>),
+}
+
+/// Mergeable approximate distinct counter
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HyperLogLog {
+    repr: Repr,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn hash_of(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self {
+            repr: Repr::Sparse(Vec::new()),
+        }
+    }
+
+    /// Whether the count is still exact (sparse)
+    pub fn is_exact(&self) -> bool {
+        matches!(self.repr, Repr::Sparse(_))
+    }
+
+    pub fn insert(&mut self, value: &str) {
+        self.insert_hash(hash_of(value));
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        let overflowed = match &mut self.repr {
+            Repr::Sparse(hashes) => {
+                if !hashes.contains(&hash) {
+                    hashes.push(hash);
+                }
+                hashes.len() > SPARSE_LIMIT
+            }
+            Repr::Dense(registers) => {
+                Self::update_register(registers, hash);
+                false
+            }
+        };
+        if overflowed {
+            self.densify();
+        }
+    }
+
+    fn update_register(registers: &mut [u8], hash: u64) {
+        let index = (hash >> (64 - PRECISION)) as usize;
+        let rank = ((hash << PRECISION).leading_zeros() + 1).min(64 - PRECISION + 1) as u8;
+        if rank > registers[index] {
+            registers[index] = rank;
+        }
+    }
+
+    fn densify(&mut self) {
+        if let Repr::Sparse(hashes) = &self.repr {
+            let mut registers = vec![0u8; REGISTERS].into_boxed_slice();
+            for &hash in hashes {
+                Self::update_register(&mut registers, hash);
+            }
+            self.repr = Repr::Dense(registers);
+        }
+    }
+
+    /// Fold another sketch into this one (set union)
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        match &other.repr {
+            Repr::Sparse(hashes) => {
+                for &hash in hashes {
+                    self.insert_hash(hash);
+                }
+            }
+            Repr::Dense(other_registers) => {
+                self.densify();
+                if let Repr::Dense(registers) = &mut self.repr {
+                    for (register, &other) in registers.iter_mut().zip(other_registers.iter()) {
+                        *register = (*register).max(other);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Estimated number of distinct values
+    pub fn estimate(&self) -> u64 {
+        let registers = match &self.repr {
+            Repr::Sparse(hashes) => return hashes.len() as u64,
+            Repr::Dense(registers) => registers,
+        };
+
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        // Small-range correction (linear counting)
+        let zeros = registers.iter().filter(|&&r| r == 0).count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_while_sparse() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..50 {
+            hll.insert(&format!("wallet_{}", i % 25));
+        }
+        assert!(hll.is_exact());
+        assert_eq!(hll.estimate(), 25);
+    }
+
+    #[test]
+    fn test_dense_estimate_within_bounds() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..20_000 {
+            hll.insert(&format!("wallet_{}", i));
+        }
+        assert!(!hll.is_exact());
+        let error = (hll.estimate() as f64 - 20_000.0).abs() / 20_000.0;
+        assert!(error < 4.0 * STANDARD_ERROR, "error {}", error);
+    }
+
+    #[test]
+    fn test_merge_is_union() {
+        let mut a = HyperLogLog::new();
+        let mut b = HyperLogLog::new();
+        for i in 0..1000 {
+            a.insert(&format!("wallet_{}", i));
+            b.insert(&format!("wallet_{}", i + 500));
+        }
+        let mut small = HyperLogLog::new();
+        small.insert("wallet_1");
+        small.insert("someone_else");
+
+        a.merge(&b);
+        a.merge(&small);
+        let error = (a.estimate() as f64 - 1501.0).abs() / 1501.0;
+        assert!(error < 4.0 * STANDARD_ERROR, "error {}", error);
+
+        let mut exact = HyperLogLog::new();
+        exact.merge(&small);
+        assert_eq!(exact.estimate(), 2);
+    }
+}
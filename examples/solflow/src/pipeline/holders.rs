@@ -0,0 +1,327 @@
+//! Holder concentration enrichment
+//!
+//! Tokens whose supply sits in a handful of insider wallets can be dumped at
+//! any moment, however healthy their flows look. This enrichment stage
+//! periodically fetches the largest token accounts of actively traded mints
+//! (`getTokenLargestAccounts`, the top 20) and stores the share of supply
+//! held by the 10 largest wallets as `token_aggregates.top10_holder_pct`
+//! (0-100). `persistence_scorer` lowers confidence for concentrated tokens.
+//!
+//! Only accounts owned by wallets (system-owned owner accounts) count:
+//! bonding curves, pool vaults and other program-controlled accounts are
+//! liquidity, not insiders. Each mint costs four RPC calls (largest
+//! accounts, supply, token account owners, owner accounts).
+//!
+//! Environment variables:
+//! - `HOLDER_CONCENTRATION_ENABLED`: Run the stage (default: false)
+//! - `SOLANA_RPC_URL`: RPC endpoint used for lookups (required when enabled)
+//! - `HOLDER_REFRESH_SECS`: Time between passes (default: 300)
+//! - `HOLDER_MAX_MINTS`: Mints refreshed per pass, by 300s volume (default: 20)
+
+use super::enrichment::Enricher;
+use crate::error::{codes, SolflowError};
+use async_trait::async_trait;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+
+/// Holders summed into `top10_holder_pct`
+const TOP_HOLDERS: usize = 10;
+
+/// Mints traded within this many seconds count as active
+const ACTIVE_WINDOW_SECS: i64 = 300;
+
+/// JSON-RPC request, with RPC-level errors surfaced as `ENRICHMENT_HTTP`
+pub(crate) async fn rpc_call(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, SolflowError> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    let response: serde_json::Value = client.post(rpc_url).json(&request).send().await?.json().await?;
+
+    if let Some(error) = response.get("error") {
+        return Err(SolflowError::enrichment(
+            codes::ENRICHMENT_HTTP,
+            format!("RPC error: {}", error),
+        ));
+    }
+    Ok(response)
+}
+
+/// Share of supply (percent) held by the largest wallets
+pub fn top_holder_pct(mut wallet_amounts: Vec<u128>, supply: u128) -> Option<f64> {
+    if supply == 0 {
+        return None;
+    }
+    wallet_amounts.sort_unstable_by(|a, b| b.cmp(a));
+    let top: u128 = wallet_amounts.iter().take(TOP_HOLDERS).sum();
+    Some((top as f64 / supply as f64 * 100.0).min(100.0))
+}
+
+/// (token account, raw amount) from `getTokenLargestAccounts`
+pub(crate) fn parse_largest_accounts(response: &serde_json::Value) -> Vec<(String, u128)> {
+    response
+        .pointer("/result/value")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|account| {
+            let address = account.get("address")?.as_str()?.to_string();
+            let amount = account.get("amount")?.as_str()?.parse().ok()?;
+            Some((address, amount))
+        })
+        .collect()
+}
+
+/// Raw supply from `getTokenSupply`
+pub(crate) fn parse_supply(response: &serde_json::Value) -> Option<u128> {
+    response.pointer("/result/value/amount")?.as_str()?.parse().ok()
+}
+
+/// Owner of each token account from `getMultipleAccounts` (jsonParsed)
+pub(crate) fn parse_token_owners(response: &serde_json::Value) -> Vec<Option<String>> {
+    response
+        .pointer("/result/value")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|account| {
+            account
+                .pointer("/data/parsed/info/owner")
+                .and_then(|owner| owner.as_str())
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Owning program of each account from `getMultipleAccounts`
+pub(crate) fn parse_account_programs(response: &serde_json::Value) -> Vec<Option<String>> {
+    response
+        .pointer("/result/value")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|account| account.get("owner").and_then(|o| o.as_str()).map(str::to_string))
+        .collect()
+}
+
+/// Enrichment stage writing `top10_holder_pct` for active mints
+pub struct HolderConcentration {
+    db_path: String,
+    rpc_url: String,
+    client: reqwest::Client,
+    refresh: Duration,
+    max_mints: usize,
+}
+
+impl HolderConcentration {
+    /// Create the stage from the environment (see module docs)
+    ///
+    /// Returns None unless enabled and an RPC endpoint is configured.
+    pub fn from_env(db_path: String) -> Option<Self> {
+        let enabled = std::env::var("HOLDER_CONCENTRATION_ENABLED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let Ok(rpc_url) = std::env::var("SOLANA_RPC_URL") else {
+            log::warn!("⚠️  HOLDER_CONCENTRATION_ENABLED is set but SOLANA_RPC_URL is not; holder concentration disabled");
+            return None;
+        };
+
+        let refresh_secs = std::env::var("HOLDER_REFRESH_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|n: &u64| *n > 0)
+            .unwrap_or(300);
+        let max_mints = std::env::var("HOLDER_MAX_MINTS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(20);
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .ok()?;
+
+        Some(Self {
+            db_path,
+            rpc_url,
+            client,
+            refresh: Duration::from_secs(refresh_secs),
+            max_mints,
+        })
+    }
+
+    async fn rpc(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, SolflowError> {
+        rpc_call(&self.client, &self.rpc_url, method, params).await
+    }
+
+    /// Top-10 wallet share of a mint's supply
+    async fn fetch_top10_pct(&self, mint: &str) -> Result<Option<f64>, SolflowError> {
+        let largest = parse_largest_accounts(
+            &self.rpc("getTokenLargestAccounts", serde_json::json!([mint])).await?,
+        );
+        let Some(supply) = parse_supply(&self.rpc("getTokenSupply", serde_json::json!([mint])).await?)
+        else {
+            return Err(SolflowError::enrichment(
+                codes::ENRICHMENT_RESPONSE,
+                format!("no supply for {}", mint),
+            ));
+        };
+        if largest.is_empty() {
+            return Ok(None);
+        }
+
+        let addresses: Vec<&str> = largest.iter().map(|(address, _)| address.as_str()).collect();
+        let owners = parse_token_owners(
+            &self
+                .rpc("getMultipleAccounts", serde_json::json!([addresses, {"encoding": "jsonParsed"}]))
+                .await?,
+        );
+
+        let mut distinct_owners: Vec<&str> = owners.iter().flatten().map(String::as_str).collect();
+        distinct_owners.sort_unstable();
+        distinct_owners.dedup();
+        let wallets: HashMap<&str, bool> = distinct_owners
+            .iter()
+            .copied()
+            .zip(parse_account_programs(
+                &self
+                    .rpc(
+                        "getMultipleAccounts",
+                        serde_json::json!([
+                            distinct_owners,
+                            {"encoding": "base64", "dataSlice": {"offset": 0, "length": 0}}
+                        ]),
+                    )
+                    .await?,
+            )
+            .into_iter()
+            .map(|program| program.as_deref() == Some(SYSTEM_PROGRAM_ID)))
+            .collect();
+
+        // A wallet holding several accounts counts once
+        let mut per_wallet: HashMap<&str, u128> = HashMap::new();
+        for ((_, amount), owner) in largest.iter().zip(&owners) {
+            if let Some(owner) = owner.as_deref().filter(|o| wallets.get(o) == Some(&true)) {
+                *per_wallet.entry(owner).or_default() += amount;
+            }
+        }
+
+        Ok(top_holder_pct(per_wallet.into_values().collect(), supply))
+    }
+
+    /// Active, unblocked mints by 300s volume
+    fn active_mints(&self, conn: &Connection, now: i64) -> rusqlite::Result<Vec<String>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT ta.mint
+            FROM token_aggregates ta
+            LEFT JOIN token_metadata tm ON ta.mint = tm.mint
+            WHERE ta.last_trade_timestamp >= ?
+              AND (tm.blocked IS NULL OR tm.blocked = 0)
+            ORDER BY ta.volume_300s_sol DESC
+            LIMIT ?
+            "#,
+        )?;
+        let mints = stmt
+            .query_map(rusqlite::params![now - ACTIVE_WINDOW_SECS, self.max_mints as i64], |row| {
+                row.get(0)
+            })?
+            .collect();
+        mints
+    }
+}
+
+#[async_trait]
+impl Enricher for HolderConcentration {
+    fn name(&self) -> &'static str {
+        "holder_concentration"
+    }
+
+    fn interval(&self) -> Duration {
+        self.refresh
+    }
+
+    async fn enrich(&mut self, now: i64) -> Result<(), SolflowError> {
+        let mints = {
+            let conn = Connection::open(&self.db_path)?;
+            self.active_mints(&conn, now)?
+        };
+
+        let mut results = Vec::with_capacity(mints.len());
+        for mint in &mints {
+            match self.fetch_top10_pct(mint).await {
+                Ok(Some(pct)) => results.push((mint, pct)),
+                Ok(None) => {}
+                Err(e) => log::debug!("⚠️  Holder lookup failed for {}: {}", mint, e),
+            }
+        }
+
+        let conn = Connection::open(&self.db_path)?;
+        for (mint, pct) in &results {
+            conn.execute(
+                "UPDATE token_aggregates SET top10_holder_pct = ? WHERE mint = ?",
+                rusqlite::params![pct, mint],
+            )?;
+        }
+        log::debug!("👥 Holder concentration: updated {}/{} mints", results.len(), mints.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top10_pct_counts_wallets_only() {
+        let largest = serde_json::json!({"result": {"value": [
+            {"address": "CurveVault", "amount": "800000", "decimals": 6},
+            {"address": "InsiderAta", "amount": "120000", "decimals": 6},
+            {"address": "InsiderAta2", "amount": "30000", "decimals": 6},
+        ]}});
+        let owners = serde_json::json!({"result": {"value": [
+            {"data": {"parsed": {"info": {"owner": "BondingCurvePda"}}}},
+            {"data": {"parsed": {"info": {"owner": "Insider"}}}},
+            {"data": {"parsed": {"info": {"owner": "Insider"}}}},
+        ]}});
+        // Owners sorted: BondingCurvePda (program-owned), Insider (wallet)
+        let owner_accounts = serde_json::json!({"result": {"value": [
+            {"owner": "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", "lamports": 1},
+            {"owner": SYSTEM_PROGRAM_ID, "lamports": 1},
+        ]}});
+
+        let largest = parse_largest_accounts(&largest);
+        assert_eq!(largest.len(), 3);
+        assert_eq!(parse_token_owners(&owners)[1].as_deref(), Some("Insider"));
+        assert_eq!(
+            parse_account_programs(&owner_accounts)[1].as_deref(),
+            Some(SYSTEM_PROGRAM_ID)
+        );
+        assert_eq!(
+            parse_supply(&serde_json::json!({"result": {"value": {"amount": "1000000"}}})),
+            Some(1_000_000)
+        );
+
+        // Insider's two accounts make 15% of supply; the curve is ignored
+        assert_eq!(top_holder_pct(vec![150_000], 1_000_000), Some(15.0));
+        // Only the 10 largest count
+        assert_eq!(top_holder_pct(vec![10; 12], 1_000), Some(10.0));
+        assert_eq!(top_holder_pct(vec![1], 0), None);
+    }
+}
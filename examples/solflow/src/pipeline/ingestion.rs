@@ -2,16 +2,43 @@
 //!
 //! Phase 4: Live trade ingestion from streamers
 //! Phase 4.3: Unified flush loop with single lock acquisition
+//! Sharding: per-shard ingestion tasks, flush computed per shard and merged
 
 use super::db::AggregateDbWriter;
+use super::dedup::TradeDedup;
 use super::engine::PipelineEngine;
-use super::types::TradeEvent;
+use super::journal::{EventJournal, JournalPosition};
+use super::lease::InstanceLease;
+use super::shards::ShardedEngine;
+use super::shutdown::ShutdownSignal;
+use super::signals::TokenSignal;
+use super::types::{AggregatedTokenState, TradeEvent};
+use crate::streamer_core::metrics;
+use std::collections::HashSet;
 use std::env;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
 
+/// Order a flush's mints so those deferred last cycle go first
+///
+/// Deferred mints are included even on a delta flush (they may not have been
+/// touched again), minus any the engine has since pruned.
+fn prioritize_deferred(
+    mints: Vec<String>,
+    deferred: Vec<String>,
+    is_tracked: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let mut seen: HashSet<String> = HashSet::with_capacity(mints.len() + deferred.len());
+    deferred
+        .into_iter()
+        .filter(|mint| is_tracked(mint))
+        .chain(mints)
+        .filter(|mint| seen.insert(mint.clone()))
+        .collect()
+}
+
 /// Start pipeline ingestion from trade event channel
 ///
 /// This is the ONLY flush mechanism in the entire pipeline.
@@ -26,6 +53,12 @@ use tokio::time::{interval, Duration};
 /// - Compute all metrics while holding lock
 /// - Release lock BEFORE database writes
 /// - Log channel utilization for monitoring
+/// - Stop computing once the per-flush time budget is spent; skipped mints
+///   are logged and computed first next cycle (`FLUSH_COMPUTE_BUDGET_MS`,
+///   default: half the flush interval)
+/// - Drop `details_json` from signals older than `SIGNAL_DETAILS_RETENTION_SECS`
+///   (default: 7 days, 0 = keep), at most `SIGNAL_DETAILS_COMPACT_BATCH`
+///   (default: 500) per flush so the write never stalls the loop
 ///
 /// Arguments:
 /// - `rx`: Receiver end of trade event channel
@@ -35,13 +68,229 @@ use tokio::time::{interval, Duration};
 ///
 /// This function runs indefinitely until the channel is closed (streamer shutdown).
 pub async fn start_pipeline_ingestion(
-    mut rx: mpsc::Receiver<TradeEvent>,
+    rx: mpsc::Receiver<TradeEvent>,
+    engine: Arc<Mutex<PipelineEngine>>,
+    db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
+    flush_interval_ms: u64,
+) {
+    start_pipeline_ingestion_with_lease(rx, engine, db_writer, flush_interval_ms, None).await;
+}
+
+/// Start pipeline ingestion, flushing only while `lease` is held
+///
+/// Without the lease the instance is a warm standby: trades are still
+/// processed so its windows are ready on takeover, but aggregates and
+/// signals are left to the instance holding the lease.
+pub async fn start_pipeline_ingestion_with_lease(
+    rx: mpsc::Receiver<TradeEvent>,
     engine: Arc<Mutex<PipelineEngine>>,
     db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
     flush_interval_ms: u64,
+    lease: Option<Arc<InstanceLease>>,
+) {
+    let engine = Arc::new(ShardedEngine::single(engine));
+    start_sharded_ingestion(rx, engine, db_writer, flush_interval_ms, lease).await;
+}
+
+/// One shard's share of a flush
+#[derive(Default)]
+struct ShardFlush {
+    aggregates: Vec<AggregatedTokenState>,
+    signals: Vec<TokenSignal>,
+    /// Mints computed this cycle
+    flushed: usize,
+    /// Mints skipped by the compute budget, computed first next cycle
+    deferred: Vec<String>,
+}
+
+/// Compute one shard's aggregates and signals (engine locked throughout)
+///
+/// Mints deferred last cycle go first; computing stops once `compute_budget`
+/// has elapsed since `flush_start`. Clears the shard's touched set.
+fn flush_shard(
+    engine_guard: &mut PipelineEngine,
+    is_full_flush: bool,
+    deferred: Vec<String>,
+    now: i64,
+    flush_start: Instant,
+    compute_budget: Duration,
+) -> ShardFlush {
+    // Phase 5: Get mints to flush (delta or full)
+    let mints_to_flush = if is_full_flush {
+        engine_guard.get_active_mints() // Full flush: all mints
+    } else {
+        engine_guard.get_touched_mints() // Delta flush: only touched mints
+    };
+
+    // Mints skipped by last cycle's budget go first
+    let mints_to_flush = prioritize_deferred(mints_to_flush, deferred, |mint| {
+        engine_guard.is_tracking(mint)
+    });
+
+    let mut flush = ShardFlush::default();
+
+    // Compute metrics for selected mints while holding lock
+    for (index, mint) in mints_to_flush.iter().enumerate() {
+        if flush_start.elapsed() >= compute_budget {
+            // Budget spent: flush what's done, the rest go first next cycle
+            flush.deferred = mints_to_flush[index..].to_vec();
+            log::warn!(
+                "⏱️  Flush budget ({}ms) exceeded after {} mints; deferring {}: {}",
+                compute_budget.as_millis(),
+                index,
+                flush.deferred.len(),
+                flush.deferred.iter().take(10).cloned().collect::<Vec<_>>().join(", ")
+            );
+            break;
+        }
+
+        let mint_start = Instant::now();
+        match engine_guard.compute_metrics(mint, now) {
+            Ok((metrics, signals, aggregate)) => {
+                flush.aggregates.push(aggregate);
+                flush.signals.extend(signals);
+
+                // Update bot history for BOT_DROPOFF detection
+                engine_guard.update_bot_history(mint, metrics.bot_trades_count_300s);
+            }
+            Err(e) => {
+                log::warn!("⚠️  Failed to compute metrics for {}: {}", mint, e);
+            }
+        }
+
+        if mint_start.elapsed() >= compute_budget / 4 {
+            log::warn!(
+                "🐢 Slow metrics for {}: {}ms",
+                mint,
+                mint_start.elapsed().as_millis()
+            );
+        }
+    }
+
+    // Phase 5: Clear touched set after processing (for next delta flush)
+    // Deferred mints are carried separately by the caller
+    engine_guard.clear_touched_mints();
+
+    flush.flushed = mints_to_flush.len() - flush.deferred.len();
+    flush
+}
+
+/// Flush every shard in parallel and merge the results
+///
+/// Each shard is computed on the blocking pool under its own lock, so shards
+/// keep ingesting while the others compute.
+async fn flush_shards(
+    engine: &ShardedEngine,
+    is_full_flush: bool,
+    deferred: &mut [Vec<String>],
+    now: i64,
+    flush_start: Instant,
+    compute_budget: Duration,
+) -> ShardFlush {
+    let tasks = engine.shards().iter().zip(deferred.iter_mut()).map(|(shard, deferred)| {
+        let shard = shard.clone();
+        let deferred = std::mem::take(deferred);
+        tokio::task::spawn_blocking(move || {
+            let mut engine_guard = shard.lock().unwrap();
+            flush_shard(&mut engine_guard, is_full_flush, deferred, now, flush_start, compute_budget)
+        })
+    });
+    let results = futures::future::join_all(tasks).await;
+
+    // Merge step: one write batch across shards
+    let mut merged = ShardFlush::default();
+    for (result, deferred) in results.into_iter().zip(deferred.iter_mut()) {
+        match result {
+            Ok(flush) => {
+                merged.aggregates.extend(flush.aggregates);
+                merged.signals.extend(flush.signals);
+                merged.flushed += flush.flushed;
+                merged.deferred.extend(flush.deferred.iter().cloned());
+                *deferred = flush.deferred;
+            }
+            Err(e) => log::error!("❌ Shard flush task failed: {}", e),
+        }
+    }
+    merged
+}
+
+/// Spawn a shard's ingestion task; returns its channel and task handle
+fn spawn_shard_worker(
+    shard: Arc<Mutex<PipelineEngine>>,
+    capacity: usize,
+) -> (mpsc::Sender<TradeEvent>, tokio::task::JoinHandle<()>) {
+    let (tx, mut rx) = mpsc::channel::<TradeEvent>(capacity);
+    let handle = tokio::spawn(async move {
+        while let Some(trade) = rx.recv().await {
+            shard.lock().unwrap().process_trade(trade);
+        }
+    });
+    (tx, handle)
+}
+
+/// Start pipeline ingestion over a sharded engine
+///
+/// With one shard, trades are processed inline as before. With more, this
+/// loop only routes each trade to its shard's task (hash of the mint) and
+/// runs the flush; shards process trades in parallel, and the flush computes
+/// them in parallel and merges the results into one write batch. Flushes
+/// only while `lease` is held (see `start_pipeline_ingestion_with_lease`).
+pub async fn start_sharded_ingestion(
+    rx: mpsc::Receiver<TradeEvent>,
+    engine: Arc<ShardedEngine>,
+    db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
+    flush_interval_ms: u64,
+    lease: Option<Arc<InstanceLease>>,
 ) {
+    start_sharded_ingestion_until(rx, engine, db_writer, flush_interval_ms, lease, None).await;
+}
+
+/// Start sharded ingestion that also stops on `shutdown`
+///
+/// On shutdown the channel is closed to new trades, the trades already
+/// queued are processed, and a final full flush is written before returning
+/// (see `shutdown`). Without a signal it runs until every sender is dropped.
+pub async fn start_sharded_ingestion_until(
+    rx: mpsc::Receiver<TradeEvent>,
+    engine: Arc<ShardedEngine>,
+    db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
+    flush_interval_ms: u64,
+    lease: Option<Arc<InstanceLease>>,
+    shutdown: Option<ShutdownSignal>,
+) {
+    start_journaled_ingestion_until(rx, engine, db_writer, flush_interval_ms, lease, shutdown, None).await;
+}
+
+/// Move the journal checkpoint, logging failures (the flush already happened)
+fn checkpoint_journal(journal: &mut Option<EventJournal>, position: Option<JournalPosition>) {
+    if let (Some(journal), Some(position)) = (journal.as_mut(), position) {
+        if let Err(e) = journal.checkpoint(position) {
+            log::error!("❌ Event journal checkpoint failed: {}", e);
+        }
+    }
+}
+
+/// Start sharded ingestion, appending each trade to `journal` first
+///
+/// Trades already delivered are dropped (see `dedup`); every other received
+/// trade is journaled before it reaches the engine. After a
+/// successful flush (or a skipped standby flush) the checkpoint moves to the
+/// position of the previous flush, so trades still queued for a shard stay
+/// replayable; the final flush checkpoints everything (see `journal`).
+pub async fn start_journaled_ingestion_until(
+    mut rx: mpsc::Receiver<TradeEvent>,
+    engine: Arc<ShardedEngine>,
+    db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
+    flush_interval_ms: u64,
+    lease: Option<Arc<InstanceLease>>,
+    mut shutdown: Option<ShutdownSignal>,
+    mut journal: Option<EventJournal>,
+) {
+    let is_standby = || lease.as_ref().is_some_and(|l| !l.is_held());
+
     log::info!("🚀 Starting pipeline ingestion (UNIFIED FLUSH LOOP)");
     log::info!("   ├─ Flush interval: {}ms", flush_interval_ms);
+    log::info!("   ├─ Engine shards: {}", engine.shard_count());
     log::info!("   └─ Waiting for trades...");
 
     // Phase 5: Load back-pressure watermark thresholds
@@ -68,19 +317,91 @@ pub async fn start_pipeline_ingestion(
     log::info!("   ├─ High watermark: {} ({}%)", high_watermark, high_watermark_pct);
     log::info!("   └─ Critical watermark: {} ({}%)", critical_watermark, critical_watermark_pct);
 
+    // Per-flush compute budget: a pathological token can't stall the loop
+    let compute_budget = Duration::from_millis(
+        env::var("FLUSH_COMPUTE_BUDGET_MS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or((flush_interval_ms / 2).max(1)),
+    );
+    log::info!("⏱️  Flush compute budget: {}ms", compute_budget.as_millis());
+
+    // Detail blobs are the bulk of token_signals; scores and severity are kept
+    let details_retention_secs: i64 = env::var("SIGNAL_DETAILS_RETENTION_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(7 * 86_400);
+    let details_compact_batch: usize = env::var("SIGNAL_DETAILS_COMPACT_BATCH")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(500);
+    let mut deferred_mints: Vec<Vec<String>> = vec![Vec::new(); engine.shard_count()];
+
+    // One task per shard; a single shard is processed inline
+    let (mut shard_txs, mut shard_workers): (Vec<_>, Vec<_>) = if engine.shard_count() > 1 {
+        let shard_capacity = (channel_capacity / engine.shard_count()).max(1000);
+        engine
+            .shards()
+            .iter()
+            .map(|shard| spawn_shard_worker(shard.clone(), shard_capacity))
+            .unzip()
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
     let mut flush_timer = interval(Duration::from_millis(flush_interval_ms));
     let mut trade_count = 0u64;
     let mut last_log_time = Instant::now();
     let mut last_full_flush = Instant::now(); // Phase 5: Track full flush timing
+    let mut closing = false;
+    // Reconnect replays and redundant streamers re-deliver trades
+    let mut dedup = TradeDedup::from_env();
+    // Journal position at the previous flush tick, checkpointed after this one
+    let mut journal_mark = journal.as_ref().map(|j| j.position());
 
     loop {
         tokio::select! {
+            // Shutdown requested: stop accepting trades, drain what's queued
+            _ = async {
+                match shutdown.as_mut() {
+                    Some(signal) => signal.recv().await,
+                    None => std::future::pending().await,
+                }
+            }, if !closing => {
+                log::info!("🛑 Shutdown requested, draining {} queued trades", rx.len());
+                closing = true;
+                rx.close();
+            }
+
             // Receive trade from channel
-            Some(trade) = rx.recv() => {
-                // Process trade through engine (single lock acquisition)
-                {
-                    let mut engine_guard = engine.lock().unwrap();
-                    engine_guard.process_trade(trade);
+            received = rx.recv() => {
+                let Some(trade) = received else {
+                    // Channel closed (streamer shutdown) and drained
+                    final_flush(&engine, &db_writer, &mut deferred_mints, &mut shard_txs, &mut shard_workers, &is_standby, &mut journal).await;
+                    break;
+                };
+
+                if dedup.as_mut().is_some_and(|d| d.is_duplicate(&trade, Instant::now())) {
+                    metrics::increment_counter(metrics::TRADES_DEDUPLICATED, 1).await;
+                    continue;
+                }
+
+                // Write-ahead: journaled before the engine sees it
+                if let Some(journal) = journal.as_mut() {
+                    if let Err(e) = journal.append(&trade) {
+                        log::error!("❌ Event journal append failed: {}", e);
+                    }
+                }
+
+                if shard_txs.is_empty() {
+                    // Process trade through engine (single lock acquisition)
+                    engine.shards()[0].lock().unwrap().process_trade(trade);
+                } else {
+                    engine.record_session(&trade);
+                    let shard_tx = &shard_txs[engine.shard_index(&trade.mint)];
+                    if shard_tx.send(trade).await.is_err() {
+                        log::error!("❌ Shard task stopped; trade dropped");
+                    }
                 }
                 
                 trade_count += 1;
@@ -96,7 +417,17 @@ pub async fn start_pipeline_ingestion(
             
             // Periodic flush timer - ONLY FLUSH MECHANISM
             _ = flush_timer.tick() => {
-                let now = chrono::Utc::now().timestamp();
+                let flush_mark = journal.as_ref().map(|j| j.position());
+
+                if is_standby() {
+                    // Another instance holds the lease and does the writing
+                    engine.clear_touched_mints();
+                    checkpoint_journal(&mut journal, std::mem::replace(&mut journal_mark, flush_mark));
+                    log::debug!("⏸️  Standby: flush skipped (lease held elsewhere)");
+                    continue;
+                }
+
+                let now = engine.now();
                 let flush_start = Instant::now();
                 
                 // Phase 5: Determine flush type (delta vs full)
@@ -108,81 +439,81 @@ pub async fn start_pipeline_ingestion(
                     "DELTA"
                 };
                 
-                // 1. Lock engine ONCE and compute metrics
-                let (aggregates, all_signals, _mint_count, flush_label) = {
-                    let mut engine_guard = engine.lock().unwrap();
-                    
-                    // Phase 5: Get mints to flush (delta or full)
-                    let mints_to_flush = if is_full_flush {
-                        engine_guard.get_active_mints() // Full flush: all mints
-                    } else {
-                        engine_guard.get_touched_mints() // Delta flush: only touched mints
-                    };
-                    
-                    if mints_to_flush.is_empty() {
-                        // No mints to process, skip flush
-                        (Vec::new(), Vec::new(), 0, format!("{} (0 mints)", flush_type))
-                    } else {
-                        let mut aggregates = Vec::new();
-                        let mut all_signals = Vec::new();
-                        
-                        // Compute metrics for selected mints while holding lock
-                        for mint in &mints_to_flush {
-                            match engine_guard.compute_metrics(mint, now) {
-                                Ok((metrics, signals, aggregate)) => {
-                                    aggregates.push(aggregate);
-                                    all_signals.extend(signals);
-                                    
-                                    // Update bot history for BOT_DROPOFF detection
-                                    engine_guard.update_bot_history(mint, metrics.bot_trades_count_300s);
-                                }
-                                Err(e) => {
-                                    log::warn!("⚠️  Failed to compute metrics for {}: {}", mint, e);
-                                }
-                            }
-                        }
-                        
-                        // Phase 5: Clear touched set after processing (for next delta flush)
-                        engine_guard.clear_touched_mints();
-                        
-                        let count = mints_to_flush.len();
-                        (aggregates, all_signals, count, format!("{} ({} mints)", flush_type, count))
-                    }
-                }; // Lock released here
+                // 1. Compute metrics, each shard locked once (in parallel)
+                let ShardFlush { aggregates, signals: all_signals, flushed: mint_count, deferred } =
+                    flush_shards(&engine, is_full_flush, &mut deferred_mints, now, flush_start, compute_budget).await;
+                let flush_label = if deferred.is_empty() {
+                    format!("{} ({} mints)", flush_type, mint_count)
+                } else {
+                    format!("{} ({} mints, {} deferred)", flush_type, mint_count, deferred.len())
+                };
                 
                 // 2. Database writes (engine unlocked - no blocking)
+                let mut flush_ok = true;
                 if !aggregates.is_empty() {
                     match db_writer.write_aggregates(aggregates.clone()).await {
                         Ok(_) => {
                             log::debug!("✅ Wrote {} aggregates to database", aggregates.len());
+
+                            // Latency SLA: aggregates for these mints are now visible
+                            let flushed_mints: Vec<String> =
+                                aggregates.iter().map(|a| a.mint.clone()).collect();
+                            let flushed_at_ms = chrono::Utc::now().timestamp_millis();
+                            engine.record_flush_latency(&flushed_mints, flushed_at_ms);
                         }
                         Err(e) => {
                             log::error!("❌ Failed to write aggregates: {}", e);
+                            flush_ok = false;
                         }
                     }
                 }
                 
-                // Write signals to database
-                let mut signals_written = 0;
-                for signal in all_signals {
-                    match db_writer.write_signal(signal.clone()).await {
-                        Ok(_) => signals_written += 1,
-                        Err(e) => {
-                            // May fail due to blocklist - this is expected
-                            log::debug!("⚠️  Signal not written (mint: {}, type: {:?}): {}", 
-                                signal.mint, signal.signal_type, e);
+                // Write signals to database (one transaction, blocklist read once)
+                let signal_count = all_signals.len();
+                let signals_written = match db_writer.write_signals(all_signals).await {
+                    Ok(written) => {
+                        // Blocklisted mints - this is expected
+                        if written.len() < signal_count {
+                            log::debug!("⚠️  {} signals not written (blocked mints)", signal_count - written.len());
                         }
+                        written.len()
+                    }
+                    Err(e) => {
+                        log::warn!("⚠️  Failed to write {} signals: {}", signal_count, e);
+                        flush_ok = false;
+                        0
                     }
+                };
+
+                // Trades journaled before the previous tick are now written
+                if flush_ok {
+                    checkpoint_journal(&mut journal, std::mem::replace(&mut journal_mark, flush_mark));
                 }
                 
                 if signals_written > 0 {
                     log::info!("🚨 Detected {} signals", signals_written);
                 }
+
+                if details_retention_secs > 0 {
+                    let before = now - details_retention_secs;
+                    match db_writer.compact_signal_details(before, details_compact_batch).await {
+                        Ok(compacted) if compacted > 0 => {
+                            log::debug!("🗜️  Compacted details of {} old signals", compacted);
+                        }
+                        Ok(_) => {}
+                        Err(e) => log::warn!("⚠️  Signal details compaction failed: {}", e),
+                    }
+                }
                 
                 // 3. Log channel health and flush performance with back-pressure warnings
                 let channel_usage = rx.len();
                 let flush_duration = flush_start.elapsed();
                 let utilization_pct = (channel_usage * 100) / channel_capacity;
+
+                metrics::update_gauge(metrics::CHANNEL_DEPTH, channel_usage as f64).await;
+                metrics::record_histogram(metrics::FLUSH_LATENCY_MS, flush_duration.as_millis() as f64).await;
+                metrics::increment_counter(metrics::FLUSH_MINTS, mint_count as u64).await;
+                metrics::increment_counter(metrics::SIGNALS_WRITTEN, signals_written as u64).await;
                 
                 log::info!("📊 Flush complete: {} | {} signals | channel: {}/{} ({}%) | {}ms", 
                     flush_label,
@@ -206,53 +537,112 @@ pub async fn start_pipeline_ingestion(
                         utilization_pct, channel_usage, channel_capacity
                     );
                 }
-            }
-            
-            // Channel closed (streamer shutdown)
-            else => {
-                log::warn!("⚠️  Trade channel closed, stopping ingestion");
-                
-                // Final flush before exit
-                log::info!("🔄 Performing final flush...");
-                let now = chrono::Utc::now().timestamp();
-                
-                let (aggregates, all_signals, _) = {
-                    let mut engine_guard = engine.lock().unwrap();
-                    let active_mints = engine_guard.get_active_mints();
-                    
-                    let mut aggregates = Vec::new();
-                    let mut all_signals = Vec::new();
-                    
-                    for mint in &active_mints {
-                        if let Ok((metrics, signals, aggregate)) = engine_guard.compute_metrics(mint, now) {
-                            aggregates.push(aggregate);
-                            all_signals.extend(signals);
-                            engine_guard.update_bot_history(mint, metrics.bot_trades_count_300s);
-                        }
-                    }
-                    
-                    (aggregates, all_signals, active_mints.len())
-                };
-                
-                if !aggregates.is_empty() {
-                    if let Err(e) = db_writer.write_aggregates(aggregates).await {
-                        log::error!("❌ Failed final aggregate flush: {}", e);
-                    }
-                }
-                
-                for signal in all_signals {
-                    let _ = db_writer.write_signal(signal).await;
+
+                // Latency SLA report once per full flush cycle (~60s)
+                if is_full_flush {
+                    report_latency_sla(&engine, &db_writer).await;
                 }
-                
-                log::info!("✅ Final flush complete");
-                break;
             }
+            
         }
     }
 
     log::info!("✅ Pipeline ingestion stopped");
 }
 
+/// Drain the shard tasks and write one last full flush (skipped on standby)
+async fn final_flush(
+    engine: &ShardedEngine,
+    db_writer: &Arc<dyn AggregateDbWriter + Send + Sync>,
+    deferred_mints: &mut [Vec<String>],
+    shard_txs: &mut Vec<mpsc::Sender<TradeEvent>>,
+    shard_workers: &mut Vec<tokio::task::JoinHandle<()>>,
+    is_standby: &impl Fn() -> bool,
+    journal: &mut Option<EventJournal>,
+) {
+    log::warn!("⚠️  Trade channel closed, stopping ingestion");
+
+    // Let shard tasks drain their queues
+    shard_txs.clear();
+    for worker in shard_workers.drain(..) {
+        let _ = worker.await;
+    }
+
+    // Shards are drained: every journaled trade is in the engine
+    let journal_end = journal.as_ref().map(|j| j.position());
+
+    if is_standby() {
+        checkpoint_journal(journal, journal_end);
+        return;
+    }
+
+    // Final flush before exit
+    log::info!("🔄 Performing final flush...");
+    let now = engine.now();
+
+    let ShardFlush { aggregates, signals: all_signals, .. } =
+        flush_shards(engine, true, deferred_mints, now, Instant::now(), Duration::MAX).await;
+
+    let aggregate_count = aggregates.len();
+    let mut flush_ok = true;
+    if !aggregates.is_empty() {
+        if let Err(e) = db_writer.write_aggregates(aggregates).await {
+            log::error!("❌ Failed final aggregate flush: {}", e);
+            flush_ok = false;
+        }
+    }
+
+    if let Err(e) = db_writer.write_signals(all_signals).await {
+        log::error!("❌ Failed final signal flush: {}", e);
+        flush_ok = false;
+    }
+
+    if flush_ok {
+        checkpoint_journal(journal, journal_end);
+    }
+
+    log::info!("✅ Final flush complete ({} aggregates)", aggregate_count);
+}
+
+/// Log and persist end-to-end latency percentiles
+///
+/// Writes `trade_latency_sla` to system_metrics:
+/// `{"global": {p50_ms, p99_ms, max_ms, samples}, "per_mint": {mint: {...}}}`
+///
+/// Engine locks are released before the database write.
+async fn report_latency_sla(
+    engine: &ShardedEngine,
+    db_writer: &Arc<dyn AggregateDbWriter + Send + Sync>,
+) {
+    let (global, per_mint) = (
+        engine.global_latency_summary(),
+        engine.per_mint_latency_summaries(),
+    );
+
+    let Some(global) = global else {
+        return;
+    };
+
+    log::info!(
+        "⏱️  Trade latency (block_time → visible): p50={}ms p99={}ms max={}ms ({} samples, {} mints)",
+        global.p50_ms,
+        global.p99_ms,
+        global.max_ms,
+        global.samples,
+        per_mint.len()
+    );
+
+    let value_json = serde_json::json!({
+        "global": global,
+        "per_mint": per_mint,
+    })
+    .to_string();
+
+    if let Err(e) = db_writer.write_system_metric("trade_latency_sla", &value_json).await {
+        log::warn!("⚠️  Failed to write latency SLA metric: {}", e);
+    }
+}
+
 // Flush logic is now integrated directly into the tokio::select! loop above.
 // This eliminates the need for a separate function and allows better control
 // over lock acquisition timing.
@@ -276,6 +666,7 @@ mod tests {
             token_decimals: 6,
             user_account: "test_wallet".to_string(),
             source_program: "pumpswap".to_string(),
+            ..Default::default()
         }
     }
     
@@ -378,4 +769,121 @@ mod tests {
         
         // Verify no errors (actual database verification would require exposing connection)
     }
+
+    #[tokio::test]
+    async fn test_standby_instance_does_not_flush() {
+        use crate::pipeline::lease::{InstanceLease, RUNTIME_LEASE_NAME};
+
+        let (tx, rx) = mpsc::channel(100);
+        let engine = Arc::new(Mutex::new(PipelineEngine::new()));
+        let (temp, db_writer) = create_test_db();
+        let db_path = temp.path().to_str().unwrap().to_string();
+
+        // Another instance holds the lease
+        let now = chrono::Utc::now().timestamp();
+        let leader = InstanceLease::new(&db_path, RUNTIME_LEASE_NAME, "leader", 60);
+        assert!(leader.try_acquire(now).unwrap());
+        let standby = Arc::new(InstanceLease::new(&db_path, RUNTIME_LEASE_NAME, "standby", 60));
+        assert!(!standby.try_acquire(now).unwrap());
+
+        let engine_clone = engine.clone();
+        let ingestion_handle = tokio::spawn(async move {
+            start_pipeline_ingestion_with_lease(rx, engine_clone, db_writer, 50, Some(standby)).await;
+        });
+
+        for i in 0..5 {
+            tx.send(make_test_trade(now + i, "standby_mint", 1.0)).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        drop(tx);
+        let _ = tokio::time::timeout(Duration::from_secs(1), ingestion_handle).await;
+
+        // Trades were ingested, but nothing was written
+        assert!(engine.lock().unwrap().get_active_mints().contains(&"standby_mint".to_string()));
+        let conn = Connection::open(&db_path).unwrap();
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM token_aggregates", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    #[tokio::test]
+    async fn test_sharded_ingestion_merges_shard_flushes() {
+        let (tx, rx) = mpsc::channel(100);
+        let engine = Arc::new(ShardedEngine::new(3, |_| PipelineEngine::new()));
+        let (temp, db_writer) = create_test_db();
+        let db_path = temp.path().to_str().unwrap().to_string();
+
+        let engine_clone = engine.clone();
+        let ingestion_handle = tokio::spawn(async move {
+            start_sharded_ingestion(rx, engine_clone, db_writer, 50, None).await;
+        });
+
+        let now = chrono::Utc::now().timestamp();
+        let mints: Vec<String> = (0..12).map(|i| format!("shard_mint_{}", i)).collect();
+        for mint in &mints {
+            tx.send(make_test_trade(now, mint, 1.0)).await.unwrap();
+        }
+
+        // Shard tasks process the trades; the next flush merges every shard
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        drop(tx);
+        let _ = tokio::time::timeout(Duration::from_secs(1), ingestion_handle).await;
+
+        for mint in &mints {
+            assert!(engine.is_tracking(mint));
+        }
+        let conn = Connection::open(&db_path).unwrap();
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM token_aggregates", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, mints.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_and_flushes_with_open_senders() {
+        use crate::pipeline::shutdown::Shutdown;
+
+        let (tx, rx) = mpsc::channel(100);
+        let engine = Arc::new(ShardedEngine::new(1, |_| PipelineEngine::new()));
+        let (temp, db_writer) = create_test_db();
+        let db_path = temp.path().to_str().unwrap().to_string();
+        let shutdown = Shutdown::new(Duration::from_secs(1));
+
+        let engine_clone = engine.clone();
+        let signal = shutdown.signal();
+        let ingestion_handle = tokio::spawn(async move {
+            start_sharded_ingestion_until(rx, engine_clone, db_writer, 60_000, None, Some(signal)).await;
+        });
+
+        let now = chrono::Utc::now().timestamp();
+        tx.send(make_test_trade(now, "shutdown_mint", 1.0)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // A streamer still holds `tx`; the signal alone stops ingestion
+        let deadline = shutdown.trigger();
+        tokio::time::timeout_at(deadline, ingestion_handle).await.unwrap().unwrap();
+        assert!(tx.send(make_test_trade(now, "late_mint", 1.0)).await.is_err());
+
+        let conn = Connection::open(&db_path).unwrap();
+        let rows: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM token_aggregates WHERE mint = 'shutdown_mint'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[test]
+    fn test_prioritize_deferred_mints() {
+        let mints = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let deferred = vec!["c".to_string(), "pruned".to_string(), "d".to_string()];
+
+        let ordered = prioritize_deferred(mints, deferred, |mint| mint != "pruned");
+
+        // Deferred first (pruned dropped), then the rest without duplicates
+        assert_eq!(ordered, vec!["c", "d", "a", "b"]);
+    }
 }
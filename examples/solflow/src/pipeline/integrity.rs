@@ -0,0 +1,361 @@
+//! Startup data integrity audit
+//!
+//! A flush interrupted by a crash has left rows that poison ranking queries:
+//! negative counts, timestamps far in the future, NULL mints. The runtime runs
+//! `run_integrity_audit` once after the schema check (for every routed
+//! database too). `INTEGRITY_AUDIT` decides what happens to bad rows:
+//!
+//! - `off` - skip the audit
+//! - `report` (default) - log each finding with a few sample mints
+//! - `quarantine` - also move the rows into `quarantined_rows` as JSON, with
+//!   the failed check, and delete them from their table
+//!
+//! A timestamp is "in the future" when it is more than
+//! `INTEGRITY_FUTURE_SKEW_SECS` (default: 300) ahead of the wall clock.
+
+use crate::error::SolflowError;
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection, Row};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::env;
+
+/// Sample keys kept per finding for the log
+const SAMPLE_KEYS: usize = 5;
+
+/// Count and timestamp columns checked per table
+struct TableChecks {
+    table: &'static str,
+    counts: &'static [&'static str],
+    timestamps: &'static [&'static str],
+}
+
+/// Tables audited at startup; every one has a `mint` column
+const CHECKED_TABLES: &[TableChecks] = &[
+    TableChecks {
+        table: "token_aggregates",
+        counts: &[
+            "buy_count_60s",
+            "sell_count_60s",
+            "buy_count_300s",
+            "sell_count_300s",
+            "buy_count_900s",
+            "sell_count_900s",
+            "unique_wallets_300s",
+            "new_wallets_300s",
+            "early_buyers_count",
+            "unique_wallets_3600s",
+            "unique_wallets_7200s",
+            "unique_wallets_14400s",
+            "bot_trades_300s",
+            "bot_wallets_300s",
+            "dca_buys_60s",
+            "dca_buys_300s",
+            "dca_buys_900s",
+            "dca_buys_3600s",
+            "dca_buys_14400s",
+            "buy_count_300s_final",
+            "sell_count_300s_final",
+            "unfinalized_trades_300s",
+        ],
+        timestamps: &["last_trade_timestamp", "updated_at", "created_at"],
+    },
+    TableChecks {
+        table: "token_signals",
+        counts: &[],
+        timestamps: &["created_at"],
+    },
+    TableChecks {
+        table: "token_metadata",
+        counts: &[],
+        timestamps: &["updated_at", "created_at"],
+    },
+    TableChecks {
+        table: "session_rollups",
+        counts: &["buy_count", "sell_count", "unique_wallets"],
+        timestamps: &["updated_at"],
+    },
+    TableChecks {
+        table: "dca_activity_buckets",
+        counts: &["buy_count"],
+        timestamps: &["bucket_timestamp"],
+    },
+    TableChecks {
+        table: "dca_activity_rollups",
+        counts: &["buy_count"],
+        timestamps: &["bucket_timestamp"],
+    },
+];
+
+/// What the audit does with corrupt rows (INTEGRITY_AUDIT)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityMode {
+    Off,
+    Report,
+    Quarantine,
+}
+
+impl IntegrityMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" | "false" | "0" => Some(Self::Off),
+            "report" => Some(Self::Report),
+            "quarantine" => Some(Self::Quarantine),
+            _ => None,
+        }
+    }
+}
+
+/// Integrity audit configuration (INTEGRITY_* env vars)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntegrityConfig {
+    /// INTEGRITY_AUDIT: off | report | quarantine (default: report)
+    pub mode: IntegrityMode,
+    /// Allowed clock skew before a timestamp counts as future (INTEGRITY_FUTURE_SKEW_SECS, default: 300)
+    pub future_skew_secs: i64,
+}
+
+impl Default for IntegrityConfig {
+    fn default() -> Self {
+        Self {
+            mode: IntegrityMode::Report,
+            future_skew_secs: 300,
+        }
+    }
+}
+
+impl IntegrityConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let mode = match env::var("INTEGRITY_AUDIT") {
+            Ok(value) => IntegrityMode::parse(&value).unwrap_or_else(|| {
+                log::warn!("⚠️  Unknown INTEGRITY_AUDIT '{}', using report", value);
+                defaults.mode
+            }),
+            Err(_) => defaults.mode,
+        };
+        Self {
+            mode,
+            future_skew_secs: env::var("INTEGRITY_FUTURE_SKEW_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(defaults.future_skew_secs)
+                .max(0),
+        }
+    }
+}
+
+/// Rows of one table failing one check
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegrityFinding {
+    pub table: String,
+    /// Failed check, e.g. "negative buy_count_300s"
+    pub reason: String,
+    pub rows: usize,
+    /// Up to `SAMPLE_KEYS` mints (rowid:<n> for NULL mints)
+    pub sample_keys: Vec<String>,
+    /// Rows moved to `quarantined_rows`
+    pub quarantined: bool,
+}
+
+/// (reason, WHERE clause) for every check that applies to the table as it exists
+fn table_conditions(
+    conn: &Connection,
+    checks: &TableChecks,
+    future_cutoff: i64,
+) -> Result<Vec<(String, String)>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+    let existing = stmt
+        .query_map([checks.table], |row| row.get::<_, String>(0))?
+        .collect::<Result<HashSet<_>, _>>()?;
+    if !existing.contains("mint") {
+        return Ok(Vec::new());
+    }
+
+    let mut conditions = vec![("NULL mint".to_string(), "mint IS NULL OR mint = ''".to_string())];
+    for column in checks.counts.iter().filter(|c| existing.contains(**c)) {
+        conditions.push((format!("negative {}", column), format!("{} < 0", column)));
+    }
+    for column in checks.timestamps.iter().filter(|c| existing.contains(**c)) {
+        conditions.push((
+            format!("{} in the future", column),
+            format!("{} > {}", column, future_cutoff),
+        ));
+    }
+    Ok(conditions)
+}
+
+/// Row as a JSON object keyed by column name
+fn row_to_json(row: &Row, skip: usize) -> rusqlite::Result<Value> {
+    let mut object = Map::new();
+    for (index, name) in row.as_ref().column_names().into_iter().enumerate().skip(skip) {
+        let value = match row.get_ref(index)? {
+            ValueRef::Null => Value::Null,
+            ValueRef::Integer(i) => i.into(),
+            ValueRef::Real(f) => serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number),
+            ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned().into(),
+            ValueRef::Blob(_) => Value::Null,
+        };
+        object.insert(name.to_string(), value);
+    }
+    Ok(Value::Object(object))
+}
+
+/// Find (and with `quarantine`, move aside) the rows failing one check
+fn check_rows(
+    conn: &Connection,
+    table: &str,
+    reason: &str,
+    condition: &str,
+    quarantine: bool,
+    now: i64,
+) -> Result<Option<IntegrityFinding>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT rowid, COALESCE(NULLIF(mint, ''), 'rowid:' || rowid), * FROM {} WHERE {}",
+        table, condition
+    ))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row_to_json(row, 2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    if quarantine {
+        let mut insert = conn.prepare(
+            "INSERT INTO quarantined_rows (source_table, row_key, reason, row_json, quarantined_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let mut delete = conn.prepare(&format!("DELETE FROM {} WHERE rowid = ?1", table))?;
+        for (rowid, key, json) in &rows {
+            insert.execute(params![table, key, reason, json.to_string(), now])?;
+            delete.execute([rowid])?;
+        }
+    }
+
+    Ok(Some(IntegrityFinding {
+        table: table.to_string(),
+        reason: reason.to_string(),
+        rows: rows.len(),
+        sample_keys: rows.iter().take(SAMPLE_KEYS).map(|(_, key, _)| key.clone()).collect(),
+        quarantined: quarantine,
+    }))
+}
+
+/// Audit every checked table; quarantines in one transaction when enabled
+///
+/// A row failing several checks is reported (and quarantined) under the
+/// first one only.
+pub fn run_integrity_audit(
+    conn: &mut Connection,
+    config: &IntegrityConfig,
+    now: i64,
+) -> Result<Vec<IntegrityFinding>, SolflowError> {
+    if config.mode == IntegrityMode::Off {
+        return Ok(Vec::new());
+    }
+    let quarantine = config.mode == IntegrityMode::Quarantine;
+    let future_cutoff = now.saturating_add(config.future_skew_secs);
+
+    let tx = conn.transaction()?;
+    let mut findings = Vec::new();
+    for checks in CHECKED_TABLES {
+        for (reason, condition) in table_conditions(&tx, checks, future_cutoff)? {
+            if let Some(finding) = check_rows(&tx, checks.table, &reason, &condition, quarantine, now)? {
+                findings.push(finding);
+            }
+        }
+    }
+    tx.commit()?;
+
+    if findings.is_empty() {
+        log::info!("✅ Integrity audit passed ({} tables)", CHECKED_TABLES.len());
+    }
+    for finding in &findings {
+        log::warn!(
+            "⚠️  Integrity: {} {} row(s) in {} ({}){}",
+            finding.rows,
+            finding.reason,
+            finding.table,
+            finding.sample_keys.join(", "),
+            if finding.quarantined { " - quarantined" } else { "" }
+        );
+    }
+    Ok(findings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::db::run_schema_migrations;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_audit_reports_then_quarantines_corrupt_rows() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut conn = Connection::open(temp_file.path()).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+
+        let now = 1_700_000_000;
+        conn.execute_batch(&format!(
+            "INSERT INTO token_aggregates (mint, source_program, buy_count_300s, updated_at, created_at)
+             VALUES ('good', 'pumpswap', 4, {now}, {now}),
+                    ('negative', 'pumpswap', -3, {now}, {now}),
+                    (NULL, 'pumpswap', 1, {now}, {now}),
+                    ('future', 'pumpswap', 1, {future}, {now});",
+            now = now,
+            future = now + 86_400,
+        ))
+        .unwrap();
+
+        let report = IntegrityConfig::default();
+        let findings = run_integrity_audit(&mut conn, &report, now).unwrap();
+        let reasons: Vec<&str> = findings.iter().map(|f| f.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            vec!["NULL mint", "negative buy_count_300s", "updated_at in the future"]
+        );
+        assert!(findings[0].sample_keys[0].starts_with("rowid:"));
+        assert_eq!(findings[1].sample_keys, vec!["negative".to_string()]);
+
+        // Report mode leaves the rows in place
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM token_aggregates", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, 4);
+
+        let quarantine = IntegrityConfig {
+            mode: IntegrityMode::Quarantine,
+            ..report
+        };
+        assert_eq!(run_integrity_audit(&mut conn, &quarantine, now).unwrap().len(), 3);
+
+        let remaining: Vec<String> = conn
+            .prepare("SELECT mint FROM token_aggregates")
+            .unwrap()
+            .query_map([], |r| r.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(remaining, vec!["good".to_string()]);
+
+        let (key, reason, json): (String, String, String) = conn
+            .query_row(
+                "SELECT row_key, reason, row_json FROM quarantined_rows WHERE row_key = 'negative'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((key.as_str(), reason.as_str()), ("negative", "negative buy_count_300s"));
+        let row: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(row["buy_count_300s"], -3);
+
+        assert!(run_integrity_audit(&mut conn, &quarantine, now).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+//! Tokens of interest: automatic promotion to a high-fidelity tier
+//!
+//! A handful of tokens deserve more than the default treatment, and each
+//! part of that used to be its own manual knob (`FAST_PATH_MINTS`,
+//! `follow_price`, capture arming by signal severity). With
+//! `INTEREST_ENABLED=true` the engine promotes a mint as soon as its 300s
+//! activity crosses any promotion threshold, and demotes it once activity
+//! has stayed below half of every threshold for `INTEREST_DEMOTE_AFTER_SECS`.
+//! While promoted a mint:
+//!
+//! - is enriched on the interest tier (`METADATA_REFRESH_INTEREST_SECS`)
+//! - keeps its engine state (never pruned as inactive)
+//! - takes the fast path, i.e. is flushed on every trade (with `FAST_PATH_ENABLED`)
+//! - has anomaly capture armed (with `ANOMALY_CAPTURE_ENABLED`)
+//!
+//! At most `INTEREST_MAX_TOKENS` mints are promoted at once; further
+//! candidates are promoted when a slot frees up.
+//!
+//! Environment variables:
+//! - `INTEREST_ENABLED`: Enable automatic promotion (default: false)
+//! - `INTEREST_BUY_COUNT_300S`: Buys in 300s that promote a mint (default: 40)
+//! - `INTEREST_NET_FLOW_300S_SOL`: Net inflow in 300s that promotes a mint (default: 25.0)
+//! - `INTEREST_UNIQUE_WALLETS_300S`: Unique wallets in 300s that promote a mint (default: 25)
+//! - `INTEREST_DEMOTE_AFTER_SECS`: Quiet time before demotion (default: 900)
+//! - `INTEREST_MAX_TOKENS`: Mints promoted at once (default: 50)
+
+use super::state::RollingMetrics;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, RwLock};
+
+/// Promotion thresholds (any one promotes) and demotion timing
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterestConfig {
+    pub buy_count_300s: i32,
+    pub net_flow_300s_sol: f64,
+    pub unique_wallets_300s: i32,
+    pub demote_after_secs: i64,
+    pub max_tokens: usize,
+}
+
+impl Default for InterestConfig {
+    fn default() -> Self {
+        Self {
+            buy_count_300s: 40,
+            net_flow_300s_sol: 25.0,
+            unique_wallets_300s: 25,
+            demote_after_secs: 900,
+            max_tokens: 50,
+        }
+    }
+}
+
+impl InterestConfig {
+    /// Load from env; None unless INTEREST_ENABLED is true
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("INTEREST_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let defaults = Self::default();
+        fn read<T: std::str::FromStr>(key: &str, default: T) -> T {
+            env::var(key).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        }
+        Some(Self {
+            buy_count_300s: read("INTEREST_BUY_COUNT_300S", defaults.buy_count_300s),
+            net_flow_300s_sol: read("INTEREST_NET_FLOW_300S_SOL", defaults.net_flow_300s_sol),
+            unique_wallets_300s: read("INTEREST_UNIQUE_WALLETS_300S", defaults.unique_wallets_300s),
+            demote_after_secs: read("INTEREST_DEMOTE_AFTER_SECS", defaults.demote_after_secs),
+            max_tokens: read("INTEREST_MAX_TOKENS", defaults.max_tokens),
+        })
+    }
+
+    /// Whether activity reaches `fraction` of any promotion threshold
+    fn reaches(&self, metrics: &RollingMetrics, fraction: f64) -> bool {
+        metrics.buy_count_300s as f64 >= self.buy_count_300s as f64 * fraction
+            || metrics.net_flow_300s_sol >= self.net_flow_300s_sol * fraction
+            || metrics.unique_wallets_300s as f64 >= self.unique_wallets_300s as f64 * fraction
+    }
+}
+
+/// A mint's tier change from one observation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterestChange {
+    Promoted,
+    Demoted,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Promotion {
+    promoted_at: i64,
+    /// Last observation at or above half a promotion threshold
+    last_active: i64,
+}
+
+/// Shared set of promoted mints
+///
+/// Cloned into every engine shard (which promote and demote), the fast path
+/// and the metadata scheduler (which read it).
+#[derive(Debug, Clone)]
+pub struct TokensOfInterest {
+    config: Arc<InterestConfig>,
+    promoted: Arc<RwLock<HashMap<String, Promotion>>>,
+}
+
+impl TokensOfInterest {
+    pub fn new(config: InterestConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            promoted: Arc::default(),
+        }
+    }
+
+    pub fn config(&self) -> &InterestConfig {
+        &self.config
+    }
+
+    pub fn contains(&self, mint: &str) -> bool {
+        self.promoted.read().unwrap().contains_key(mint)
+    }
+
+    pub fn len(&self) -> usize {
+        self.promoted.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Promoted mints with their promotion time, oldest first
+    pub fn promoted(&self) -> Vec<(String, i64)> {
+        let mut mints: Vec<(String, i64)> = self
+            .promoted
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(mint, promotion)| (mint.clone(), promotion.promoted_at))
+            .collect();
+        mints.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        mints
+    }
+
+    /// Promote or demote a mint from its latest rolling metrics
+    pub fn observe(&self, mint: &str, metrics: &RollingMetrics, now: i64) -> Option<InterestChange> {
+        let mut promoted = self.promoted.write().unwrap();
+        match promoted.get_mut(mint) {
+            Some(promotion) => {
+                if self.config.reaches(metrics, 0.5) {
+                    promotion.last_active = now;
+                    None
+                } else if now - promotion.last_active >= self.config.demote_after_secs {
+                    promoted.remove(mint);
+                    Some(InterestChange::Demoted)
+                } else {
+                    None
+                }
+            }
+            None if promoted.len() < self.config.max_tokens && self.config.reaches(metrics, 1.0) => {
+                promoted.insert(
+                    mint.to_string(),
+                    Promotion {
+                        promoted_at: now,
+                        last_active: now,
+                    },
+                );
+                Some(InterestChange::Promoted)
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::state::TokenRollingState;
+
+    fn metrics(buy_count_300s: i32) -> RollingMetrics {
+        let mut metrics = TokenRollingState::new("mint".to_string()).compute_rolling_metrics();
+        metrics.buy_count_300s = buy_count_300s;
+        metrics
+    }
+
+    #[test]
+    fn test_promotion_hysteresis_and_cap() {
+        let interest = TokensOfInterest::new(InterestConfig {
+            max_tokens: 2,
+            ..Default::default()
+        });
+
+        assert_eq!(interest.observe("a", &metrics(39), 0), None);
+        assert_eq!(interest.observe("a", &metrics(40), 10), Some(InterestChange::Promoted));
+        assert!(interest.contains("a"));
+
+        // Half the threshold keeps it promoted; quiet for 900s demotes it
+        assert_eq!(interest.observe("a", &metrics(20), 500), None);
+        assert_eq!(interest.observe("a", &metrics(5), 1000), None);
+        assert_eq!(interest.observe("a", &metrics(5), 1400), Some(InterestChange::Demoted));
+        assert!(!interest.contains("a"));
+
+        // No more than max_tokens at once
+        interest.observe("b", &metrics(50), 2000);
+        interest.observe("c", &metrics(50), 2001);
+        assert_eq!(interest.observe("d", &metrics(50), 2002), None);
+        assert_eq!(interest.promoted(), vec![("b".to_string(), 2000), ("c".to_string(), 2001)]);
+    }
+}
@@ -0,0 +1,215 @@
+//! End-to-end trade latency tracking
+//!
+//! Measures the time between a trade's on-chain `block_time` and the moment its
+//! aggregate becomes visible in SQLite (flush completion). Samples are kept per
+//! token in a bounded buffer so p50/p99 can be reported without unbounded growth.
+//!
+//! Note: `block_time` has 1-second resolution, so individual samples carry up to
+//! ~1s of quantization error. Percentiles over many samples are still meaningful
+//! for judging whether signals land inside an execution window.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Maximum latency samples retained per token (oldest samples are dropped first)
+const MAX_SAMPLES_PER_MINT: usize = 256;
+
+/// Latency percentiles for a token (or the whole pipeline)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencySummary {
+    pub p50_ms: i64,
+    pub p99_ms: i64,
+    pub max_ms: i64,
+    pub samples: usize,
+}
+
+/// Tracks block_time → flush-visibility latency per token
+#[derive(Debug, Clone, Default)]
+pub struct LatencyTracker {
+    /// Block times (seconds) of trades received since the last flush of each mint,
+    /// bounded per mint so failing flushes cannot grow it without limit
+    pending: HashMap<String, VecDeque<i64>>,
+
+    /// Completed latency samples (milliseconds), bounded per mint
+    samples: HashMap<String, VecDeque<i64>>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a trade with the given block time entered the engine
+    ///
+    /// Only the newest `MAX_SAMPLES_PER_MINT` pending trades are kept: a flush
+    /// would evict anything older from the sample buffer anyway.
+    pub fn record_trade(&mut self, mint: &str, block_time: i64) {
+        let pending = self.pending.entry(mint.to_string()).or_default();
+        if pending.len() == MAX_SAMPLES_PER_MINT {
+            pending.pop_front();
+        }
+        pending.push_back(block_time);
+    }
+
+    /// Record flush completion for the given mints
+    ///
+    /// Every pending trade for each mint becomes visible at `flushed_at_ms`,
+    /// so one latency sample is produced per pending trade.
+    pub fn record_flush(&mut self, mints: &[String], flushed_at_ms: i64) {
+        for mint in mints {
+            let Some(block_times) = self.pending.remove(mint) else {
+                continue;
+            };
+
+            let samples = self.samples.entry(mint.clone()).or_default();
+            for block_time in block_times {
+                // Clock skew between validator and host can make this negative
+                let latency_ms = (flushed_at_ms - block_time * 1000).max(0);
+                if samples.len() == MAX_SAMPLES_PER_MINT {
+                    samples.pop_front();
+                }
+                samples.push_back(latency_ms);
+            }
+        }
+    }
+
+    /// Latency percentiles for a single token
+    pub fn summary(&self, mint: &str) -> Option<LatencySummary> {
+        let samples = self.samples.get(mint)?;
+        summarize(samples.iter().copied().collect())
+    }
+
+    /// Latency percentiles across all tokens
+    pub fn global_summary(&self) -> Option<LatencySummary> {
+        summarize(self.all_samples())
+    }
+
+    /// Every retained sample across all tokens
+    pub fn all_samples(&self) -> Vec<i64> {
+        self.samples.values().flatten().copied().collect()
+    }
+
+    /// Per-token latency percentiles for every token with samples
+    pub fn per_mint_summaries(&self) -> HashMap<String, LatencySummary> {
+        self.samples
+            .keys()
+            .filter_map(|mint| self.summary(mint).map(|s| (mint.clone(), s)))
+            .collect()
+    }
+
+    /// Drop all latency state for a mint (called when the mint is pruned)
+    pub fn remove(&mut self, mint: &str) {
+        self.pending.remove(mint);
+        self.samples.remove(mint);
+    }
+}
+
+/// Compute p50/p99/max using nearest-rank percentiles
+pub fn summarize(mut samples: Vec<i64>) -> Option<LatencySummary> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    samples.sort_unstable();
+
+    Some(LatencySummary {
+        p50_ms: percentile(&samples, 0.50),
+        p99_ms: percentile(&samples, 0.99),
+        max_ms: *samples.last().unwrap(),
+        samples: samples.len(),
+    })
+}
+
+/// Nearest-rank percentile over sorted samples
+fn percentile(sorted: &[i64], pct: f64) -> i64 {
+    let rank = (pct * sorted.len() as f64).ceil() as usize;
+    sorted[rank.saturating_sub(1).min(sorted.len() - 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flush_produces_samples_per_pending_trade() {
+        let mut tracker = LatencyTracker::new();
+        tracker.record_trade("mint_a", 1000);
+        tracker.record_trade("mint_a", 1001);
+        tracker.record_trade("mint_b", 1000);
+
+        tracker.record_flush(&["mint_a".to_string()], 1_002_000);
+
+        let summary = tracker.summary("mint_a").unwrap();
+        assert_eq!(summary.samples, 2);
+        assert_eq!(summary.p50_ms, 1000);
+        assert_eq!(summary.p99_ms, 2000);
+
+        // mint_b not flushed yet
+        assert!(tracker.summary("mint_b").is_none());
+    }
+
+    #[test]
+    fn test_pending_cleared_after_flush() {
+        let mut tracker = LatencyTracker::new();
+        tracker.record_trade("mint_a", 1000);
+        tracker.record_flush(&["mint_a".to_string()], 1_001_000);
+        tracker.record_flush(&["mint_a".to_string()], 1_005_000);
+
+        // Second flush has no pending trades, so no new samples
+        assert_eq!(tracker.summary("mint_a").unwrap().samples, 1);
+    }
+
+    #[test]
+    fn test_samples_bounded_per_mint() {
+        let mut tracker = LatencyTracker::new();
+        for i in 0..(MAX_SAMPLES_PER_MINT as i64 + 50) {
+            tracker.record_trade("mint_a", i);
+        }
+        tracker.record_flush(&["mint_a".to_string()], 10_000_000);
+
+        assert_eq!(tracker.summary("mint_a").unwrap().samples, MAX_SAMPLES_PER_MINT);
+    }
+
+    #[test]
+    fn test_pending_bounded_without_flush() {
+        let mut tracker = LatencyTracker::new();
+        for i in 0..(MAX_SAMPLES_PER_MINT as i64 * 4) {
+            tracker.record_trade("mint_a", i);
+        }
+
+        let pending = &tracker.pending["mint_a"];
+        assert_eq!(pending.len(), MAX_SAMPLES_PER_MINT);
+        // Newest trades are the ones kept
+        assert_eq!(*pending.back().unwrap(), MAX_SAMPLES_PER_MINT as i64 * 4 - 1);
+    }
+
+    #[test]
+    fn test_percentiles_nearest_rank() {
+        let samples: Vec<i64> = (1..=100).collect();
+        let summary = summarize(samples).unwrap();
+
+        assert_eq!(summary.p50_ms, 50);
+        assert_eq!(summary.p99_ms, 99);
+        assert_eq!(summary.max_ms, 100);
+    }
+
+    #[test]
+    fn test_negative_latency_clamped() {
+        let mut tracker = LatencyTracker::new();
+        tracker.record_trade("mint_a", 2000);
+        tracker.record_flush(&["mint_a".to_string()], 1_000_000);
+
+        assert_eq!(tracker.summary("mint_a").unwrap().p50_ms, 0);
+    }
+
+    #[test]
+    fn test_remove_drops_state() {
+        let mut tracker = LatencyTracker::new();
+        tracker.record_trade("mint_a", 1000);
+        tracker.record_flush(&["mint_a".to_string()], 1_001_000);
+        tracker.remove("mint_a");
+
+        assert!(tracker.summary("mint_a").is_none());
+        assert!(tracker.global_summary().is_none());
+    }
+}
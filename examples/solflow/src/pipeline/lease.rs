@@ -0,0 +1,185 @@
+//! Multi-instance coordination via SQLite leases
+//!
+//! Two runtimes pointed at the same database would otherwise both run the
+//! flush loop, price enrichment and persistence scoring, contending for the
+//! write lock and writing every signal twice.
+//!
+//! Each instance tries to take a named lease in `instance_leases`
+//! (`/sql/08_instance_leases.sql`). The holder renews it every `ttl / 3`;
+//! the others keep ingesting trades so their windows stay warm, and take over
+//! once the holder's lease expires (crash) or is released (shutdown).
+
+use crate::error::SolflowError;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Lease taken by `pipeline_runtime` for its write-side tasks
+pub const RUNTIME_LEASE_NAME: &str = "pipeline_runtime";
+
+/// Default instance ID: `$HOSTNAME:<pid>`
+pub fn default_instance_id() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string());
+    format!("{}:{}", host, std::process::id())
+}
+
+/// A named lease held (or wanted) by this instance
+#[derive(Debug)]
+pub struct InstanceLease {
+    db_path: String,
+    name: String,
+    holder: String,
+    ttl_secs: i64,
+    held: AtomicBool,
+}
+
+impl InstanceLease {
+    pub fn new(db_path: &str, name: &str, holder: &str, ttl_secs: i64) -> Self {
+        Self {
+            db_path: db_path.to_string(),
+            name: name.to_string(),
+            holder: holder.to_string(),
+            ttl_secs: ttl_secs.max(1),
+            held: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether this instance held the lease at the last acquire/renew attempt
+    pub fn is_held(&self) -> bool {
+        self.held.load(Ordering::Relaxed)
+    }
+
+    pub fn holder(&self) -> &str {
+        &self.holder
+    }
+
+    /// Acquire or renew the lease
+    ///
+    /// Succeeds if the lease is free, expired, or already ours. The upsert
+    /// is a single statement, so two instances racing for an expired lease
+    /// can't both win.
+    pub fn try_acquire(&self, now: i64) -> Result<bool, SolflowError> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+
+        let changed = conn.execute(
+            "INSERT INTO instance_leases (name, holder, acquired_at, expires_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(name) DO UPDATE SET
+                 acquired_at = CASE WHEN instance_leases.holder = excluded.holder
+                                    THEN instance_leases.acquired_at
+                                    ELSE excluded.acquired_at END,
+                 holder = excluded.holder,
+                 expires_at = excluded.expires_at
+             WHERE instance_leases.holder = excluded.holder
+                OR instance_leases.expires_at <= ?3",
+            params![self.name, self.holder, now, now + self.ttl_secs],
+        )?;
+
+        let acquired = changed > 0;
+        let was_held = self.held.swap(acquired, Ordering::Relaxed);
+        if acquired && !was_held {
+            log::info!("👑 Acquired lease '{}' as {}", self.name, self.holder);
+        } else if !acquired && was_held {
+            log::warn!("⚠️  Lost lease '{}' (now held by another instance)", self.name);
+        }
+
+        Ok(acquired)
+    }
+
+    /// Release the lease if we hold it, letting a standby take over immediately
+    pub fn release(&self) -> Result<(), SolflowError> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.execute(
+            "DELETE FROM instance_leases WHERE name = ?1 AND holder = ?2",
+            params![self.name, self.holder],
+        )?;
+        self.held.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Current holder and expiry of the lease, if any
+    pub fn current_holder(&self) -> Result<Option<(String, i64)>, SolflowError> {
+        let conn = Connection::open(&self.db_path)?;
+        let row = conn
+            .query_row(
+                "SELECT holder, expires_at FROM instance_leases WHERE name = ?1",
+                params![self.name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(row)
+    }
+
+    /// Keep acquiring/renewing the lease every `ttl / 3` seconds
+    ///
+    /// Renewal errors count as losing the lease: better to pause writes than
+    /// to keep writing alongside an instance that took over.
+    pub fn spawn_renewal(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let period = Duration::from_secs((self.ttl_secs / 3).max(1) as u64);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                let now = chrono::Utc::now().timestamp();
+                if let Err(e) = self.try_acquire(now) {
+                    log::error!("❌ Lease '{}' renewal failed: {}", self.name, e);
+                    self.held.store(false, Ordering::Relaxed);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::db::run_schema_migrations;
+    use tempfile::NamedTempFile;
+
+    fn create_test_db() -> NamedTempFile {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut conn = Connection::open(temp_file.path()).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        temp_file
+    }
+
+    #[test]
+    fn test_second_instance_waits_for_expiry() {
+        let db = create_test_db();
+        let path = db.path().to_str().unwrap();
+        let a = InstanceLease::new(path, RUNTIME_LEASE_NAME, "a", 30);
+        let b = InstanceLease::new(path, RUNTIME_LEASE_NAME, "b", 30);
+
+        assert!(a.try_acquire(1000).unwrap());
+        assert!(!b.try_acquire(1001).unwrap());
+        assert!(!b.is_held());
+
+        // Renewal by the holder extends the lease
+        assert!(a.try_acquire(1020).unwrap());
+        assert!(!b.try_acquire(1040).unwrap());
+
+        // Holder stops renewing: standby takes over after expiry
+        assert!(b.try_acquire(1050).unwrap());
+        assert_eq!(a.current_holder().unwrap(), Some(("b".to_string(), 1080)));
+        assert!(!a.try_acquire(1051).unwrap());
+        assert!(!a.is_held());
+    }
+
+    #[test]
+    fn test_release_hands_over_immediately() {
+        let db = create_test_db();
+        let path = db.path().to_str().unwrap();
+        let a = InstanceLease::new(path, RUNTIME_LEASE_NAME, "a", 30);
+        let b = InstanceLease::new(path, RUNTIME_LEASE_NAME, "b", 30);
+
+        assert!(a.try_acquire(1000).unwrap());
+        // Releasing a lease we don't hold is a no-op
+        b.release().unwrap();
+        assert!(!b.try_acquire(1001).unwrap());
+
+        a.release().unwrap();
+        assert!(b.try_acquire(1002).unwrap());
+    }
+}
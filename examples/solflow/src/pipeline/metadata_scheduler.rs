@@ -0,0 +1,518 @@
+//! Tiered metadata/price refresh scheduler
+//!
+//! Decides which mints to enrich from DexScreener each cycle, based on how
+//! recently they traded (`token_aggregates.last_trade_timestamp`):
+//!
+//! | Tier    | Last trade      | Refreshed every |
+//! |---------|-----------------|-----------------|
+//! | Active  | within 5 min    | 30s             |
+//! | Warm    | within 1 hour   | 5 min           |
+//! | Dormant | older / never   | daily           |
+//!
+//! Followed tokens (`follow_price = 1`) are refreshed at least every 120s
+//! regardless of tier. Promoted tokens of interest (see `interest`) sit in
+//! an Interest tier above Active, refreshed every 10s by default. Mints that trade but have no `token_metadata` row yet
+//! get a full metadata fetch (which creates the row); everything else gets a
+//! price-only update. Each cycle issues at most `max_requests_per_cycle`
+//! batch requests, most urgent tier first, so a busy market degrades to
+//! slower dormant refreshes instead of blowing the API budget. A rate-limited
+//! response ends the cycle early.
+//!
+//! Environment variables:
+//! - `METADATA_REFRESH_ACTIVE_SECS`: Active-tier cadence (default: 30)
+//! - `METADATA_REFRESH_WARM_SECS`: Warm-tier cadence (default: 300)
+//! - `METADATA_REFRESH_DORMANT_SECS`: Dormant-tier cadence (default: 86400)
+//! - `METADATA_REFRESH_MAX_REQUESTS`: DexScreener requests per cycle (default: 10)
+//! - `METADATA_REFRESH_INTEREST_SECS`: Tokens-of-interest cadence (default: 10)
+
+use super::dexscreener::{self, MAX_TOKENS_PER_REQUEST};
+use super::enrichment::Enricher;
+use super::interest::TokensOfInterest;
+use crate::error::{codes, SolflowError};
+use async_trait::async_trait;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::env;
+
+/// Trades within this window put a mint in the active tier
+const ACTIVE_WINDOW_SECS: i64 = 300;
+
+/// Trades within this window put a mint in the warm tier
+const WARM_WINDOW_SECS: i64 = 3600;
+
+/// Followed tokens are never left staler than this
+const FOLLOWED_MAX_STALENESS_SECS: i64 = 120;
+
+/// Refresh tier derived from trading activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RefreshTier {
+    /// Promoted token of interest (never derived from activity alone)
+    Interest,
+    Active,
+    Warm,
+    Dormant,
+}
+
+impl RefreshTier {
+    /// Classify a mint by its last trade timestamp
+    pub fn classify(last_activity: Option<i64>, now: i64) -> Self {
+        match last_activity.map(|ts| now - ts) {
+            Some(age) if age <= ACTIVE_WINDOW_SECS => Self::Active,
+            Some(age) if age <= WARM_WINDOW_SECS => Self::Warm,
+            _ => Self::Dormant,
+        }
+    }
+}
+
+/// Refresh cadences and per-cycle API budget
+#[derive(Debug, Clone)]
+pub struct RefreshSchedule {
+    pub interest_secs: i64,
+    pub active_secs: i64,
+    pub warm_secs: i64,
+    pub dormant_secs: i64,
+    pub max_requests_per_cycle: usize,
+}
+
+impl Default for RefreshSchedule {
+    fn default() -> Self {
+        Self {
+            interest_secs: 10,
+            active_secs: 30,
+            warm_secs: 300,
+            dormant_secs: 86_400,
+            max_requests_per_cycle: 10,
+        }
+    }
+}
+
+impl RefreshSchedule {
+    /// Load cadences from environment variables (see module docs)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |key: &str, default: i64| {
+            env::var(key)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|n: &i64| *n > 0)
+                .unwrap_or(default)
+        };
+
+        Self {
+            interest_secs: read("METADATA_REFRESH_INTEREST_SECS", defaults.interest_secs),
+            active_secs: read("METADATA_REFRESH_ACTIVE_SECS", defaults.active_secs),
+            warm_secs: read("METADATA_REFRESH_WARM_SECS", defaults.warm_secs),
+            dormant_secs: read("METADATA_REFRESH_DORMANT_SECS", defaults.dormant_secs),
+            max_requests_per_cycle: read(
+                "METADATA_REFRESH_MAX_REQUESTS",
+                defaults.max_requests_per_cycle as i64,
+            ) as usize,
+        }
+    }
+
+    /// Seconds between refreshes for a tier
+    pub fn interval_for(&self, tier: RefreshTier, followed: bool) -> i64 {
+        let interval = match tier {
+            RefreshTier::Interest => self.interest_secs,
+            RefreshTier::Active => self.active_secs,
+            RefreshTier::Warm => self.warm_secs,
+            RefreshTier::Dormant => self.dormant_secs,
+        };
+        if followed {
+            interval.min(FOLLOWED_MAX_STALENESS_SECS)
+        } else {
+            interval
+        }
+    }
+}
+
+/// A mint that may need enrichment
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefreshCandidate {
+    pub mint: String,
+    /// Last trade seen by the aggregator
+    pub last_activity: Option<i64>,
+    /// `token_metadata.updated_at` (None if the row doesn't exist)
+    pub last_refreshed: Option<i64>,
+    pub followed: bool,
+    /// Promoted token of interest (set by the scheduler, not stored)
+    pub promoted: bool,
+    /// Whether name/symbol are already known (price-only refresh suffices)
+    pub has_metadata: bool,
+}
+
+/// Load refresh candidates, skipping blocked mints
+///
+/// Candidates are every non-blocked `token_metadata` row, plus mints traded
+/// within the warm window that have no metadata row yet.
+pub fn load_candidates(
+    conn: &Connection,
+    now: i64,
+) -> Result<Vec<RefreshCandidate>, SolflowError> {
+    let mut stmt = conn.prepare(
+        r#"
+        SELECT m.mint,
+               COALESCE(a.last_trade_timestamp, a.updated_at),
+               m.updated_at,
+               m.follow_price,
+               m.name IS NOT NULL
+        FROM token_metadata m
+        LEFT JOIN token_aggregates a ON a.mint = m.mint
+        WHERE m.blocked = 0
+          AND NOT EXISTS (
+              SELECT 1 FROM mint_blocklist b
+              WHERE b.mint = m.mint AND (b.expires_at IS NULL OR b.expires_at > ?1)
+          )
+        UNION ALL
+        SELECT a.mint,
+               COALESCE(a.last_trade_timestamp, a.updated_at),
+               NULL,
+               0,
+               0
+        FROM token_aggregates a
+        WHERE COALESCE(a.last_trade_timestamp, a.updated_at) >= ?2
+          AND NOT EXISTS (SELECT 1 FROM token_metadata m WHERE m.mint = a.mint)
+          AND NOT EXISTS (
+              SELECT 1 FROM mint_blocklist b
+              WHERE b.mint = a.mint AND (b.expires_at IS NULL OR b.expires_at > ?1)
+          )
+        "#,
+    )?;
+
+    let candidates = stmt
+        .query_map(params![now, now - WARM_WINDOW_SECS], |row| {
+            Ok(RefreshCandidate {
+                mint: row.get(0)?,
+                last_activity: row.get(1)?,
+                last_refreshed: row.get(2)?,
+                followed: row.get::<_, i64>(3)? != 0,
+                promoted: false,
+                has_metadata: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(candidates)
+}
+
+/// Pick candidates due for refresh, most urgent first
+///
+/// `last_attempts` holds the last attempt per mint, so mints DexScreener
+/// doesn't know about are retried on their tier cadence rather than every
+/// cycle. Ordered by tier, then by how overdue the refresh is.
+pub fn due_refreshes(
+    candidates: Vec<RefreshCandidate>,
+    schedule: &RefreshSchedule,
+    last_attempts: &HashMap<String, i64>,
+    now: i64,
+) -> Vec<RefreshCandidate> {
+    let mut due: Vec<(RefreshTier, i64, RefreshCandidate)> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let tier = if candidate.promoted {
+                RefreshTier::Interest
+            } else {
+                RefreshTier::classify(candidate.last_activity, now)
+            };
+            let interval = schedule.interval_for(tier, candidate.followed);
+            let last = candidate
+                .last_refreshed
+                .max(last_attempts.get(&candidate.mint).copied());
+
+            let overdue = match last {
+                Some(last) => now - last - interval,
+                // Never enriched: as urgent as it gets within its tier
+                None => i64::MAX,
+            };
+            (overdue >= 0).then_some((tier, overdue, candidate))
+        })
+        .collect();
+
+    due.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+    due.into_iter().map(|(_, _, candidate)| candidate).collect()
+}
+
+/// Counts from one refresh cycle
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshStats {
+    pub due: usize,
+    pub requests: usize,
+    pub metadata_updated: usize,
+    pub prices_updated: usize,
+    pub errors: usize,
+}
+
+/// Runs refresh cycles against a database, remembering recent attempts
+pub struct MetadataRefreshScheduler {
+    db_path: String,
+    schedule: RefreshSchedule,
+    last_attempts: HashMap<String, i64>,
+    interest: Option<TokensOfInterest>,
+}
+
+impl MetadataRefreshScheduler {
+    pub fn new(db_path: String, schedule: RefreshSchedule) -> Self {
+        Self {
+            db_path,
+            schedule,
+            last_attempts: HashMap::new(),
+            interest: None,
+        }
+    }
+
+    /// Refresh promoted tokens of interest on the Interest tier
+    pub fn with_interest(mut self, interest: TokensOfInterest) -> Self {
+        self.interest = Some(interest);
+        self
+    }
+
+    /// How often `run_cycle` should be called (the fastest tier's cadence)
+    pub fn cycle_interval_secs(&self) -> u64 {
+        match self.interest {
+            Some(_) => self.schedule.interest_secs.min(self.schedule.active_secs) as u64,
+            None => self.schedule.active_secs as u64,
+        }
+    }
+
+    /// Refresh whichever mints are due, within the per-cycle request budget
+    pub async fn run_cycle(&mut self, now: i64) -> Result<RefreshStats, SolflowError> {
+        let due = {
+            let conn = Connection::open(&self.db_path)?;
+            let mut candidates = load_candidates(&conn, now)?;
+            if let Some(interest) = &self.interest {
+                for candidate in &mut candidates {
+                    candidate.promoted = interest.contains(&candidate.mint);
+                }
+            }
+            due_refreshes(candidates, &self.schedule, &self.last_attempts, now)
+        }; // Connection dropped here
+
+        let mut stats = RefreshStats {
+            due: due.len(),
+            ..Default::default()
+        };
+
+        // Full metadata for unknown mints, price-only for the rest
+        let (needs_metadata, needs_price): (Vec<_>, Vec<_>) =
+            due.into_iter().partition(|c| !c.has_metadata);
+        let needs_metadata: Vec<String> = needs_metadata.into_iter().map(|c| c.mint).collect();
+        let needs_price: Vec<String> = needs_price.into_iter().map(|c| c.mint).collect();
+
+        let batches = needs_metadata
+            .chunks(MAX_TOKENS_PER_REQUEST)
+            .map(|batch| (true, batch))
+            .chain(needs_price.chunks(MAX_TOKENS_PER_REQUEST).map(|batch| (false, batch)))
+            .take(self.schedule.max_requests_per_cycle);
+
+        for (full_metadata, batch) in batches {
+            if stats.requests > 0 {
+                // Rate limiting: stagger requests 300-600ms apart
+                let sleep_ms = 300 + (rand::random::<u64>() % 300);
+                tokio::time::sleep(tokio::time::Duration::from_millis(sleep_ms)).await;
+            }
+
+            stats.requests += 1;
+            for mint in batch {
+                self.last_attempts.insert(mint.clone(), now);
+            }
+
+            if full_metadata {
+                match dexscreener::fetch_token_metadata_batch(batch).await {
+                    Ok(found) => {
+                        stats.errors += batch.len() - found.len();
+                        let conn = Connection::open(&self.db_path)?;
+                        for metadata in &found {
+                            match dexscreener::upsert_metadata(&conn, metadata) {
+                                Ok(()) => stats.metadata_updated += 1,
+                                Err(e) => {
+                                    log::warn!("⚠️  Failed to write metadata for {}: {}", metadata.mint, e);
+                                    stats.errors += 1;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("⚠️  Metadata fetch failed for {} tokens: {}", batch.len(), e);
+                        stats.errors += batch.len();
+                        // Later requests this cycle would be throttled too
+                        if e.code() == codes::ENRICHMENT_RATE_LIMITED {
+                            break;
+                        }
+                    }
+                }
+            } else {
+                match dexscreener::fetch_token_prices_batch(batch).await {
+                    Ok(found) => {
+                        stats.errors += batch.len() - found.len();
+                        let conn = Connection::open(&self.db_path)?;
+                        for price in &found {
+                            match dexscreener::upsert_price(&conn, price) {
+                                Ok(()) => stats.prices_updated += 1,
+                                Err(e) => {
+                                    log::warn!("⚠️  Failed to write price for {}: {}", price.mint, e);
+                                    stats.errors += 1;
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("⚠️  Price fetch failed for {} tokens: {}", batch.len(), e);
+                        stats.errors += batch.len();
+                        // Later requests this cycle would be throttled too
+                        if e.code() == codes::ENRICHMENT_RATE_LIMITED {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Attempts older than the slowest cadence no longer affect scheduling
+        let horizon = now - self.schedule.dormant_secs;
+        self.last_attempts.retain(|_, attempted_at| *attempted_at > horizon);
+
+        Ok(stats)
+    }
+}
+
+#[async_trait]
+impl Enricher for MetadataRefreshScheduler {
+    fn name(&self) -> &'static str {
+        "metadata_refresh"
+    }
+
+    fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.cycle_interval_secs())
+    }
+
+    async fn enrich(&mut self, now: i64) -> Result<(), SolflowError> {
+        let stats = self.run_cycle(now).await?;
+        if stats.due > 0 {
+            log::info!(
+                "📊 Refresh cycle: {} due, {} requests, {} metadata, {} prices, {} errors",
+                stats.due,
+                stats.requests,
+                stats.metadata_updated,
+                stats.prices_updated,
+                stats.errors
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::db::run_schema_migrations;
+    use tempfile::NamedTempFile;
+
+    fn candidate(mint: &str, last_activity: Option<i64>, last_refreshed: Option<i64>) -> RefreshCandidate {
+        RefreshCandidate {
+            mint: mint.to_string(),
+            last_activity,
+            last_refreshed,
+            followed: false,
+            promoted: false,
+            has_metadata: true,
+        }
+    }
+
+    #[test]
+    fn test_tier_classification() {
+        let now = 100_000;
+        assert_eq!(RefreshTier::classify(Some(now - 10), now), RefreshTier::Active);
+        assert_eq!(RefreshTier::classify(Some(now - 1800), now), RefreshTier::Warm);
+        assert_eq!(RefreshTier::classify(Some(now - 7200), now), RefreshTier::Dormant);
+        assert_eq!(RefreshTier::classify(None, now), RefreshTier::Dormant);
+    }
+
+    #[test]
+    fn test_due_refreshes_by_tier_cadence() {
+        let now = 100_000;
+        let schedule = RefreshSchedule::default();
+        let candidates = vec![
+            candidate("active_fresh", Some(now - 5), Some(now - 10)),
+            candidate("active_stale", Some(now - 5), Some(now - 40)),
+            candidate("warm_fresh", Some(now - 1800), Some(now - 200)),
+            candidate("dormant_stale", None, Some(now - 90_000)),
+            candidate("dormant_fresh", None, Some(now - 3600)),
+        ];
+
+        let due: Vec<String> = due_refreshes(candidates, &schedule, &HashMap::new(), now)
+            .into_iter()
+            .map(|c| c.mint)
+            .collect();
+        assert_eq!(due, vec!["active_stale", "dormant_stale"]);
+    }
+
+    #[test]
+    fn test_followed_and_attempted_mints() {
+        let now = 100_000;
+        let schedule = RefreshSchedule::default();
+
+        // Followed dormant token still refreshes every 120s
+        let mut followed = candidate("followed", None, Some(now - 150));
+        followed.followed = true;
+        // Never-enriched mint was attempted recently (unknown to DexScreener)
+        let unknown = candidate("unknown", Some(now - 5), None);
+
+        let attempts = HashMap::from([("unknown".to_string(), now - 10)]);
+        let due = due_refreshes(vec![followed, unknown], &schedule, &attempts, now);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].mint, "followed");
+
+        // Tokens of interest refresh every 10s and go ahead of the active tier
+        let mut promoted = candidate("promoted", Some(now - 5), Some(now - 15));
+        promoted.promoted = true;
+        let active = candidate("active", Some(now - 5), Some(now - 60));
+        let due = due_refreshes(vec![active, promoted], &schedule, &HashMap::new(), now);
+        assert_eq!(due.iter().map(|c| c.mint.as_str()).collect::<Vec<_>>(), vec!["promoted", "active"]);
+    }
+
+    #[test]
+    fn test_load_candidates_includes_untracked_active_mints() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut conn = Connection::open(temp_file.path()).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        let now = 100_000;
+
+        conn.execute(
+            "INSERT INTO token_metadata (mint, name, decimals, created_at, updated_at, follow_price)
+             VALUES ('known', 'Known', 6, 0, ?1, 1)",
+            params![now - 60],
+        )
+        .unwrap();
+        for (mint, last_trade) in [("known", now - 10), ("new_active", now - 20), ("old_untracked", now - 10_000)] {
+            conn.execute(
+                "INSERT INTO token_aggregates (mint, source_program, last_trade_timestamp, updated_at, created_at)
+                 VALUES (?1, 'test', ?2, ?2, 0)",
+                params![mint, last_trade],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO mint_blocklist (mint, reason, blocked_by, created_at, expires_at)
+             VALUES ('blocked', 'test', 'test', 0, NULL)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO token_metadata (mint, name, decimals, created_at, updated_at)
+             VALUES ('blocked', 'Blocked', 6, 0, 0)",
+            [],
+        )
+        .unwrap();
+
+        let mut candidates = load_candidates(&conn, now).unwrap();
+        candidates.sort_by(|a, b| a.mint.cmp(&b.mint));
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].mint, "known");
+        assert!(candidates[0].followed && candidates[0].has_metadata);
+        assert_eq!(candidates[0].last_activity, Some(now - 10));
+        assert_eq!(candidates[1].mint, "new_active");
+        assert!(!candidates[1].has_metadata);
+        assert_eq!(candidates[1].last_refreshed, None);
+    }
+}
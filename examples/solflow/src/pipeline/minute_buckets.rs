@@ -0,0 +1,390 @@
+//! Minute-bucket pre-aggregation of trades
+//!
+//! Keeping every trade for four hours made a busy token's state grow with
+//! its trade rate (three raw buffers of up to tens of thousands of trades
+//! each). Trades are folded into one bucket per minute instead: at most
+//! `MAX_BUCKET_AGE_SECS / 60` buckets per mint regardless of volume.
+//!
+//! Each bucket holds counts, buy/sell SOL and a unique-wallet sketch
+//! (`hll`), so any window up to four hours can be summarized by merging
+//! buckets (`summary`). The long (1h/2h/4h) flows and the per-minute net flow
+//! sparkline (`flow_series`) read from here.
+//!
+//! Trade-off: windows have minute resolution. A window includes every bucket
+//! that overlaps it, so a "3600s" flow covers between 3600 and 3659 seconds.
+//!
+//! Each bucket also keeps the sum and sum of squares of trade sizes (the
+//! outlier baseline) and its `LARGEST_TRADES_PER_BUCKET` largest trades, so
+//! outlier capping/exclusion still applies. A minute with more outliers than
+//! that is only partially corrected.
+
+use super::hll::HyperLogLog;
+use super::types::{TradeDirection, TradeEvent};
+use std::collections::VecDeque;
+
+/// Bucket width in seconds
+pub const BUCKET_SECS: i64 = 60;
+
+/// Buckets older than this are evicted (the longest window)
+pub const MAX_BUCKET_AGE_SECS: i64 = 14400;
+
+/// Largest trades remembered per bucket for outlier correction
+const LARGEST_TRADES_PER_BUCKET: usize = 4;
+
+/// Aggregates for the trades of one minute
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct MinuteBucket {
+    /// Unix timestamp of the minute's first second
+    pub minute_start: i64,
+    pub buy_sol: f64,
+    pub sell_sol: f64,
+    pub buy_count: i32,
+    pub sell_count: i32,
+    /// All trades, including unknown direction
+    pub trade_count: i32,
+    pub sol_sum: f64,
+    pub sol_sum_sq: f64,
+    /// Largest trades as (sol_amount, direction), largest first
+    pub largest: Vec<(f64, TradeDirection)>,
+    /// Distinct wallets that traded in the minute
+    pub wallets: HyperLogLog,
+}
+
+impl MinuteBucket {
+    fn new(minute_start: i64) -> Self {
+        Self {
+            minute_start,
+            ..Default::default()
+        }
+    }
+
+    fn record(&mut self, trade: &TradeEvent) {
+        match trade.direction {
+            TradeDirection::Buy => {
+                self.buy_sol += trade.sol_amount;
+                self.buy_count += 1;
+            }
+            TradeDirection::Sell => {
+                self.sell_sol += trade.sol_amount;
+                self.sell_count += 1;
+            }
+            TradeDirection::Unknown => {}
+        }
+        self.trade_count += 1;
+        self.sol_sum += trade.sol_amount;
+        self.sol_sum_sq += trade.sol_amount * trade.sol_amount;
+
+        self.largest.push((trade.sol_amount, trade.direction));
+        self.largest.sort_by(|a, b| b.0.total_cmp(&a.0));
+        self.largest.truncate(LARGEST_TRADES_PER_BUCKET);
+
+        self.wallets.insert(&trade.user_account);
+    }
+
+    /// Undo `record` for a rolled-back trade
+    ///
+    /// The wallet sketch can't forget a wallet, so the minute's unique-wallet
+    /// estimate may stay one too high.
+    fn unrecord(&mut self, trade: &TradeEvent) {
+        match trade.direction {
+            TradeDirection::Buy => {
+                self.buy_sol -= trade.sol_amount;
+                self.buy_count -= 1;
+            }
+            TradeDirection::Sell => {
+                self.sell_sol -= trade.sol_amount;
+                self.sell_count -= 1;
+            }
+            TradeDirection::Unknown => {}
+        }
+        self.trade_count -= 1;
+        self.sol_sum -= trade.sol_amount;
+        self.sol_sum_sq -= trade.sol_amount * trade.sol_amount;
+
+        if let Some(i) = self
+            .largest
+            .iter()
+            .position(|&(sol, direction)| sol == trade.sol_amount && direction == trade.direction)
+        {
+            self.largest.remove(i);
+        }
+    }
+}
+
+/// Net flow and directional counts over a window
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WindowTotals {
+    pub net_flow_sol: f64,
+    pub buy_count: i32,
+    pub sell_count: i32,
+}
+
+/// Everything a window of buckets can answer (no outlier policy applied)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WindowSummary {
+    pub net_flow_sol: f64,
+    pub volume_sol: f64,
+    pub buy_count: i32,
+    pub sell_count: i32,
+    pub trade_count: i32,
+    /// Estimated distinct wallets (exact for small windows, see `hll`)
+    pub unique_wallets: u64,
+}
+
+/// Per-mint minute buckets, oldest first
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct MinuteBuckets {
+    buckets: VecDeque<MinuteBucket>,
+    /// Timestamp of the last eviction; windows end here (0 = not yet evicted)
+    as_of: i64,
+}
+
+impl MinuteBuckets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Fold a trade into its minute's bucket
+    ///
+    /// Late trades land in their own (older) minute; buckets stay ordered.
+    pub fn record(&mut self, trade: &TradeEvent) {
+        let minute_start = trade.timestamp - trade.timestamp.rem_euclid(BUCKET_SECS);
+
+        let position = self
+            .buckets
+            .iter()
+            .rposition(|bucket| bucket.minute_start <= minute_start);
+        let index = match position {
+            Some(i) if self.buckets[i].minute_start == minute_start => i,
+            Some(i) => {
+                self.buckets.insert(i + 1, MinuteBucket::new(minute_start));
+                i + 1
+            }
+            None => {
+                self.buckets.push_front(MinuteBucket::new(minute_start));
+                0
+            }
+        };
+        self.buckets[index].record(trade);
+    }
+
+    /// Remove a previously recorded trade (slot rollback)
+    ///
+    /// Empty buckets are dropped; trades whose bucket is gone are ignored.
+    pub fn remove(&mut self, trade: &TradeEvent) {
+        let minute_start = trade.timestamp - trade.timestamp.rem_euclid(BUCKET_SECS);
+        let Some(index) = self
+            .buckets
+            .iter()
+            .rposition(|bucket| bucket.minute_start == minute_start)
+        else {
+            return;
+        };
+        self.buckets[index].unrecord(trade);
+        if self.buckets[index].trade_count <= 0 {
+            self.buckets.remove(index);
+        }
+    }
+
+    /// Drop buckets entirely older than the longest window
+    pub fn evict(&mut self, now: i64) {
+        self.as_of = now;
+        let cutoff = now - MAX_BUCKET_AGE_SECS;
+        while self
+            .buckets
+            .front()
+            .is_some_and(|bucket| bucket.minute_start + BUCKET_SECS <= cutoff)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Buckets overlapping the last `window_secs`
+    fn window(&self, window_secs: i64) -> impl Iterator<Item = &MinuteBucket> {
+        let cutoff = self.as_of - window_secs;
+        self.buckets
+            .iter()
+            .rev()
+            .take_while(move |bucket| bucket.minute_start + BUCKET_SECS > cutoff)
+    }
+
+    /// Summarize an arbitrary window (minute resolution, up to 4 hours)
+    pub fn summary(&self, window_secs: i64) -> WindowSummary {
+        let mut summary = WindowSummary::default();
+        let mut wallets = HyperLogLog::new();
+        for bucket in self.window(window_secs) {
+            summary.net_flow_sol += bucket.buy_sol - bucket.sell_sol;
+            summary.volume_sol += bucket.buy_sol + bucket.sell_sol;
+            summary.buy_count += bucket.buy_count;
+            summary.sell_count += bucket.sell_count;
+            summary.trade_count += bucket.trade_count;
+            wallets.merge(&bucket.wallets);
+        }
+        summary.unique_wallets = wallets.estimate();
+        summary
+    }
+
+    /// Net flow per minute for the last `minutes` minutes, oldest first
+    ///
+    /// Minutes without trades are included as 0.0 so the series is evenly
+    /// spaced; the last entry is the current (partial) minute.
+    pub fn flow_series(&self, minutes: usize) -> Vec<(i64, f64)> {
+        let Some(last) = self.buckets.back() else {
+            return Vec::new();
+        };
+        let end = last.minute_start.max(self.as_of - self.as_of.rem_euclid(BUCKET_SECS));
+        let start = end - (minutes as i64 - 1) * BUCKET_SECS;
+
+        let mut series: Vec<(i64, f64)> = (0..minutes as i64)
+            .map(|i| (start + i * BUCKET_SECS, 0.0))
+            .collect();
+        for bucket in self.buckets.iter().rev() {
+            if bucket.minute_start < start {
+                break;
+            }
+            let index = ((bucket.minute_start - start) / BUCKET_SECS) as usize;
+            series[index].1 = bucket.buy_sol - bucket.sell_sol;
+        }
+        series
+    }
+
+    /// Trade count, mean and population variance of trade sizes in a window
+    pub fn size_stats(&self, window_secs: i64) -> (usize, f64, f64) {
+        let (count, sum, sum_sq) = self.window(window_secs).fold((0, 0.0, 0.0), |acc, b| {
+            (acc.0 + b.trade_count as usize, acc.1 + b.sol_sum, acc.2 + b.sol_sum_sq)
+        });
+        if count == 0 {
+            return (0, 0.0, 0.0);
+        }
+        let mean = sum / count as f64;
+        let variance = (sum_sq / count as f64 - mean * mean).max(0.0);
+        (count, mean, variance)
+    }
+
+    /// Net flow and counts over a window
+    ///
+    /// `adjust` maps a trade's SOL amount to the amount to count (None =
+    /// exclude the trade); it is applied to each bucket's largest trades.
+    pub fn totals(&self, window_secs: i64, adjust: impl Fn(f64) -> Option<f64>) -> WindowTotals {
+        let mut totals = WindowTotals::default();
+        for bucket in self.window(window_secs) {
+            totals.net_flow_sol += bucket.buy_sol - bucket.sell_sol;
+            totals.buy_count += bucket.buy_count;
+            totals.sell_count += bucket.sell_count;
+
+            for &(amount, direction) in &bucket.largest {
+                let adjusted = adjust(amount);
+                if adjusted == Some(amount) {
+                    continue;
+                }
+                let removed = amount - adjusted.unwrap_or(0.0);
+                match direction {
+                    TradeDirection::Buy => {
+                        totals.net_flow_sol -= removed;
+                        totals.buy_count -= i32::from(adjusted.is_none());
+                    }
+                    TradeDirection::Sell => {
+                        totals.net_flow_sol += removed;
+                        totals.sell_count -= i32::from(adjusted.is_none());
+                    }
+                    TradeDirection::Unknown => {}
+                }
+            }
+        }
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(timestamp: i64, direction: TradeDirection, sol_amount: f64) -> TradeEvent {
+        TradeEvent {
+            direction,
+            sol_amount,
+            ..TradeEvent::test_buy("bucket_mint", timestamp)
+        }
+    }
+
+    #[test]
+    fn test_windows_and_eviction() {
+        let mut buckets = MinuteBuckets::new();
+        let now = 100_000 - 100_000 % BUCKET_SECS;
+
+        buckets.record(&trade(now - 7000, TradeDirection::Buy, 4.0));
+        buckets.record(&trade(now - 3000, TradeDirection::Sell, 1.0));
+        buckets.record(&trade(now - 10, TradeDirection::Buy, 2.0));
+        // Late trade goes into its own minute, keeping order
+        buckets.record(&trade(now - 5000, TradeDirection::Buy, 1.5));
+        buckets.evict(now);
+        assert_eq!(buckets.len(), 4);
+
+        let flow = |b: &MinuteBuckets, secs| b.totals(secs, Some).net_flow_sol;
+        assert!((flow(&buckets, 3600) - 1.0).abs() < 1e-9);
+        assert!((flow(&buckets, 7200) - 6.5).abs() < 1e-9);
+        assert_eq!(buckets.totals(14400, Some).buy_count, 3);
+
+        buckets.evict(now + 7500);
+        assert_eq!(buckets.len(), 3);
+        buckets.evict(now + 14350);
+        assert_eq!(buckets.len(), 1);
+    }
+
+    #[test]
+    fn test_summary_and_flow_series() {
+        let mut buckets = MinuteBuckets::new();
+        let now = 60_000;
+        for i in 0..30 {
+            let mut t = trade(now - 1800 + i * 60, TradeDirection::Buy, 1.0);
+            t.user_account = format!("wallet_{}", i % 10);
+            buckets.record(&t);
+        }
+        buckets.record(&trade(now - 30, TradeDirection::Sell, 2.5));
+        buckets.evict(now);
+
+        let summary = buckets.summary(3600);
+        assert_eq!(summary.trade_count, 31);
+        assert_eq!(summary.buy_count, 30);
+        assert_eq!(summary.unique_wallets, 11);
+        assert!((summary.net_flow_sol - 27.5).abs() < 1e-9);
+        assert!((summary.volume_sol - 32.5).abs() < 1e-9);
+        assert_eq!(buckets.summary(600).trade_count, 11);
+
+        let series = buckets.flow_series(5);
+        assert_eq!(series.len(), 5);
+        assert_eq!(series[4].0, now);
+        assert!((series[3].1 + 1.5).abs() < 1e-9);
+        assert_eq!(series[4].1, 0.0);
+        assert!(MinuteBuckets::new().flow_series(5).is_empty());
+    }
+
+    #[test]
+    fn test_outlier_adjustment_and_stats() {
+        let mut buckets = MinuteBuckets::new();
+        for i in 0..10 {
+            buckets.record(&trade(1000 + i, TradeDirection::Buy, 1.0));
+        }
+        buckets.record(&trade(1030, TradeDirection::Buy, 50.0));
+        buckets.evict(1060);
+
+        let (count, mean, variance) = buckets.size_stats(3600);
+        assert_eq!(count, 11);
+        assert!((mean - 60.0 / 11.0).abs() < 1e-9);
+        assert!(variance > 0.0);
+
+        let capped = buckets.totals(3600, |amount| Some(amount.min(5.0)));
+        assert!((capped.net_flow_sol - 15.0).abs() < 1e-9);
+        let excluded = buckets.totals(3600, |amount| (amount <= 5.0).then_some(amount));
+        assert!((excluded.net_flow_sol - 10.0).abs() < 1e-9);
+        assert_eq!(excluded.buy_count, 10);
+    }
+}
@@ -61,13 +61,55 @@
 //!
 //! - `types` - Core data structures (TradeEvent, AggregatedTokenState)
 //! - `state` - Per-token rolling state container
+//! - `minute_buckets` - Per-minute trade aggregates backing the 1h/2h/4h windows and sparklines
+//! - `hll` - HyperLogLog unique-wallet sketches for minute buckets
 //! - `windows` - Rolling window trait definitions
 //! - `db` - Database writer trait
 //! - `signals` - Signal type definitions
 //! - `blocklist` - Blocklist checking trait
+//! - `latency` - End-to-end trade latency (block_time → flush) percentiles
+//! - `routing` - Per-table write routing across multiple SQLite databases
+//! - `lease` - Single-writer election between instances sharing a database
+//! - `metadata_scheduler` - Activity-tiered DexScreener metadata/price refresh
+//! - `wallet_age` - Buyer wallet age lookups for fresh-wallet cohort ratios
+//! - `replay` - JSONL capture replay with speed control and time seek
+//! - `positions` - User positions with live PnL and exit alerts
+//! - `paper_trading` - Signal-driven paper-trading strategies
+//! - `webhook` - HTTP input for source-tagged external signals
+//! - `notifier` - Rule-based signal routing to Discord/Telegram/webhook sinks
+//! - `templates` - Handlebars-style per-signal payload templates for notifier sinks
+//! - `sessions` - Asia/EU/US trading-session rollups
+//! - `crash_report` - Crash bundles (recent trades, stats, redacted config) on panic
+//! - `fast_path` - Immediate aggregation and signal checks for watched mints
+//! - `audit_trades` - Opt-in short-retention raw trades for watched mints
+//! - `schema_check` - Startup diff of the database against the columns the pipeline uses
+//! - `thresholds` - Signal thresholds from TOML/JSON and env, hot-reloaded
+//! - `wallets` - Per-wallet rolling state and SMART_WALLET_ENTRY signals
+//! - `backup` - Periodic online-backup snapshots and a read replica of the databases
+//! - `shards` - PipelineEngine split across per-shard ingestion tasks by mint
+//! - `integrity` - Startup audit that reports or quarantines corrupt rows
+//! - `window_set` - Configurable rolling-window sizes and their generated columns
+//! - `enrichment` - Ordered `Enricher` stages with per-stage scheduling and failure backoff
+//! - `fees` - Per-venue fee models for net cost/proceeds and fee-adjusted net flow
+//! - `history` - Minute aggregate snapshots appended to ClickHouse for long-term history
+//! - `snapshot` - Periodic on-disk snapshots of engine state, restored on startup
+//! - `shutdown` - Broadcast shutdown request with a shared grace period for final flushes
+//! - `tuning` - Interactive threshold tuning over an in-memory replay window
+//! - `status` - Self-description of a running instance served at /status
+//! - `telegram_bot` - Telegram alerts and /block, /unblock commands on mint_blocklist
+//! - `reconcile` - RPC check of severity-5 signals' sample signatures before notifying
+//! - `interest` - Automatic promotion of active tokens to a high-fidelity tier
+//! - `wallet_graph` - Funding-linked wallet clusters (union-find) for sybil-resistant wallet counts
+//! - `holders` - Top-10 holder concentration enrichment for active mints
+//! - `risk` - Rug-pull risk flags (mint/freeze authority, LP lock) and severity downgrade
+//! - `retention` - Per-table TTL deletes and incremental vacuum
+//! - `journal` - Write-ahead trade journal replayed on startup for crash recovery
+//! - `dedup` - Drops re-delivered trades by (signature, trade index)
 
 pub mod types;
 pub mod state;
+pub mod hll;
+pub mod minute_buckets;
 pub mod windows;
 pub mod db;
 pub mod signals;
@@ -77,12 +119,50 @@ pub mod config;
 pub mod ingestion;
 pub mod dexscreener;
 pub mod persistence_scorer;
+pub mod latency;
+pub mod routing;
+pub mod lease;
+pub mod metadata_scheduler;
+pub mod wallet_age;
+pub mod replay;
+pub mod positions;
+pub mod paper_trading;
+pub mod webhook;
+pub mod notifier;
+pub mod templates;
+pub mod sessions;
+pub mod crash_report;
+pub mod fast_path;
+pub mod audit_trades;
+pub mod schema_check;
+pub mod thresholds;
+pub mod wallets;
+pub mod backup;
+pub mod shards;
+pub mod integrity;
+pub mod window_set;
+pub mod enrichment;
+pub mod history;
+pub mod fees;
+pub mod snapshot;
+pub mod shutdown;
+pub mod tuning;
+pub mod status;
+pub mod telegram_bot;
+pub mod reconcile;
+pub mod interest;
+pub mod wallet_graph;
+pub mod holders;
+pub mod risk;
+pub mod retention;
+pub mod journal;
+pub mod dedup;
 // Note: scheduler module removed in Phase 4.3 - unified flush loop now handles all periodic tasks
 
 // Re-export commonly used types
-pub use types::{TradeEvent, TradeDirection, AggregatedTokenState};
-pub use signals::{SignalType, TokenSignal};
-pub use state::TokenRollingState;
+pub use types::{TradeEvent, TradeDirection, AggregatedTokenState, DcaOrderInfo};
+pub use signals::{SignalDetails, SignalType, TokenSignal};
+pub use state::{OutlierMode, OutlierPolicy, TokenRollingState, WalletGrowthMetric};
 pub use windows::{RollingWindow, WindowManager};
 pub use db::AggregateDbWriter;
 pub use blocklist::BlocklistProvider;
@@ -0,0 +1,869 @@
+//! Signal notifier with routing rules
+//!
+//! Polls new rows in `token_signals` and fans them out to named sinks
+//! (Discord webhooks, Telegram chats, generic JSON webhooks). Routing rules
+//! match on signal type, severity, the token's persistence pattern tag
+//! (`token_signal_summary.pattern_tag`) and a market-cap band
+//! (`token_metadata.market_cap`), so one pipeline can feed several audiences
+//! with different noise tolerances. A signal goes to every sink of every
+//! matching rule, once per sink.
+//!
+//! Each sink can be throttled so market-wide surges don't flood a channel:
+//! - `max_per_minute`: messages per rolling minute; signals over the limit
+//!   are held for the next digest instead of being dropped
+//! - `digest_below_severity`: signals under this severity are never sent on
+//!   their own but batched into one digest message every
+//!   `digest_interval_secs` (default: 300)
+//!
+//! Config is JSON, either inline or in a file:
+//!
+//! ```json
+//! {"sinks": {
+//!    "alpha": {"type": "discord", "webhook_url": "https://discord.com/api/webhooks/...",
+//!              "max_per_minute": 20, "digest_below_severity": 3},
+//!    "phone": {"type": "telegram", "bot_token": "123:abc", "chat_id": "-100123"},
+//!    "bot":   {"type": "webhook", "url": "http://127.0.0.1:9000/signal"}},
+//!  "rules": [
+//!    {"sinks": ["alpha"], "min_severity": 3},
+//!    {"sinks": ["phone"], "signal_types": ["BREAKOUT", "SURGE"], "min_severity": 4,
+//!     "pattern_tags": ["ACCUMULATION", "MOMENTUM"], "max_market_cap_usd": 2000000},
+//!    {"sinks": ["bot"], "signal_types": ["BREAKOUT"]}]}
+//! ```
+//!
+//! Any sink can also take `templates`, keyed by signal type (or `"*"`), to
+//! replace the default message with a handlebars-style payload (see
+//! `templates`). On webhook sinks the rendered template is posted as the
+//! raw JSON body.
+//!
+//! Sink secrets (`webhook_url`, `bot_token`, `url`) may be `file:` or
+//! `keyring:` references instead of literal values (see
+//! `streamer_core::secrets`).
+//!
+//! For the common case of posting everything to Discord, `DISCORD_WEBHOOK_URLS`
+//! stands in for a routing config: one throttled Discord sink per webhook and
+//! a single rule sending them every signal at or above `DISCORD_MIN_SEVERITY`.
+//! `TELEGRAM_BOT_TOKEN`/`TELEGRAM_CHAT_IDS` likewise add one Telegram sink per
+//! chat for signals at or above `TELEGRAM_MIN_SEVERITY` (see `telegram_bot`).
+//! Signals delivered to any Discord sink are marked `sent_to_discord = 1`.
+//!
+//! Environment variables:
+//! - `NOTIFY_ROUTES`: Routing JSON object, or a path to a JSON file (unset = disabled)
+//! - `NOTIFY_INTERVAL_SECS`: Signal polling interval (default: 5)
+//! - `DISCORD_WEBHOOK_URLS`: Comma-separated Discord webhooks, used when `NOTIFY_ROUTES` is unset
+//! - `DISCORD_MIN_SEVERITY`: Lowest severity posted to `DISCORD_WEBHOOK_URLS` (default: 1)
+//! - `DISCORD_MAX_PER_MINUTE`: Messages per webhook per minute; the rest go out as digests (default: 25)
+//! - `RECONCILE_RPC_URL`: Verify severity-5 signals over RPC before sending (see `reconcile`)
+
+use super::reconcile::{Reconciler, Verdict, RECONCILED_SEVERITY};
+use super::telegram_bot::TelegramBotConfig;
+use super::templates::{PayloadTemplate, FALLBACK_TEMPLATE_KEY};
+use crate::streamer_core::secrets::resolve_secret;
+use crate::error::{codes, SolflowError};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Where a notification is delivered
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Sink {
+    Discord { webhook_url: String },
+    Telegram { bot_token: String, chat_id: String },
+    /// Generic HTTP endpoint receiving the signal as JSON
+    Webhook { url: String },
+}
+
+impl Sink {
+    /// Sink type as written in the routing config
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Sink::Discord { .. } => "discord",
+            Sink::Telegram { .. } => "telegram",
+            Sink::Webhook { .. } => "webhook",
+        }
+    }
+
+    /// Replace `file:` / `keyring:` references with the secret values
+    fn resolve_secrets(&mut self) -> Result<(), String> {
+        let secret = match self {
+            Sink::Discord { webhook_url } => webhook_url,
+            Sink::Telegram { bot_token, .. } => bot_token,
+            Sink::Webhook { url } => url,
+        };
+        *secret = resolve_secret(secret)?;
+        Ok(())
+    }
+}
+
+fn default_digest_interval_secs() -> i64 {
+    300
+}
+
+/// A sink plus its throttling settings
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SinkConfig {
+    #[serde(flatten)]
+    pub target: Sink,
+    /// Messages per rolling minute (unset = unlimited)
+    #[serde(default)]
+    pub max_per_minute: Option<usize>,
+    /// Severities below this only go out in digests (unset = no digesting)
+    #[serde(default)]
+    pub digest_below_severity: Option<i32>,
+    #[serde(default = "default_digest_interval_secs")]
+    pub digest_interval_secs: i64,
+    /// Payload templates by signal type, `"*"` for the rest (unset = default format)
+    #[serde(default)]
+    pub templates: HashMap<String, PayloadTemplate>,
+}
+
+impl SinkConfig {
+    /// Template for a signal type, falling back to `"*"`
+    pub fn template_for(&self, signal_type: &str) -> Option<&PayloadTemplate> {
+        self.templates
+            .get(signal_type)
+            .or_else(|| self.templates.get(FALLBACK_TEMPLATE_KEY))
+    }
+
+    /// Message rendered through the sink's template, if one applies
+    ///
+    /// Webhook bodies are JSON, so their string fields are JSON-escaped.
+    pub fn render(&self, message: &Message) -> Option<String> {
+        let Message::Single(signal) = message else {
+            return None;
+        };
+        let escape_json = matches!(self.target, Sink::Webhook { .. });
+        self.template_for(&signal.signal_type)
+            .map(|template| template.render(signal, escape_json))
+    }
+}
+
+/// Maps matching signals to sinks; unset fields match everything
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RouteRule {
+    pub sinks: Vec<String>,
+    #[serde(default)]
+    pub signal_types: Option<Vec<String>>,
+    #[serde(default)]
+    pub min_severity: Option<i32>,
+    #[serde(default)]
+    pub max_severity: Option<i32>,
+    /// Persistence pattern tags (ACCUMULATION, MOMENTUM, ...); untagged tokens never match
+    #[serde(default)]
+    pub pattern_tags: Option<Vec<String>>,
+    /// Market-cap band in USD; tokens without a known market cap never match a band
+    #[serde(default)]
+    pub min_market_cap_usd: Option<f64>,
+    #[serde(default)]
+    pub max_market_cap_usd: Option<f64>,
+}
+
+impl RouteRule {
+    pub fn matches(&self, signal: &NotifySignal) -> bool {
+        let in_list = |list: &Option<Vec<String>>, value: Option<&str>| match list {
+            None => true,
+            Some(list) => value.is_some_and(|v| list.iter().any(|item| item == v)),
+        };
+        let has_band = self.min_market_cap_usd.is_some() || self.max_market_cap_usd.is_some();
+
+        in_list(&self.signal_types, Some(signal.signal_type.as_str()))
+            && self.min_severity.is_none_or(|min| signal.severity >= min)
+            && self.max_severity.is_none_or(|max| signal.severity <= max)
+            && in_list(&self.pattern_tags, signal.pattern_tag.as_deref())
+            && (!has_band
+                || signal.market_cap_usd.is_some_and(|cap| {
+                    self.min_market_cap_usd.is_none_or(|min| cap >= min)
+                        && self.max_market_cap_usd.is_none_or(|max| cap <= max)
+                }))
+    }
+}
+
+/// Named sinks plus the rules routing signals to them
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NotifyConfig {
+    pub sinks: HashMap<String, SinkConfig>,
+    pub rules: Vec<RouteRule>,
+}
+
+impl NotifyConfig {
+    /// Sink names for a signal, deduplicated, in rule order
+    pub fn route(&self, signal: &NotifySignal) -> Vec<&str> {
+        let mut sinks: Vec<&str> = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.matches(signal)) {
+            for sink in &rule.sinks {
+                if !sinks.contains(&sink.as_str()) {
+                    sinks.push(sink);
+                }
+            }
+        }
+        sinks
+    }
+}
+
+/// Parse routing config from inline JSON or a JSON file path
+pub fn parse_notify_config(spec: &str) -> Result<NotifyConfig, String> {
+    let spec = spec.trim();
+    let json = if spec.starts_with('{') {
+        spec.to_string()
+    } else {
+        std::fs::read_to_string(spec)
+            .map_err(|e| format!("Failed to read notify routes file {}: {}", spec, e))?
+    };
+
+    let mut config: NotifyConfig =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid notify routes JSON: {}", e))?;
+
+    for (name, sink) in config.sinks.iter_mut() {
+        sink.target
+            .resolve_secrets()
+            .map_err(|e| format!("Sink '{}': {}", name, e))?;
+    }
+    for (name, sink) in &config.sinks {
+        if sink.max_per_minute == Some(0) || sink.digest_interval_secs <= 0 {
+            return Err(format!(
+                "Sink '{}': max_per_minute and digest_interval_secs must be positive",
+                name
+            ));
+        }
+    }
+    for (i, rule) in config.rules.iter().enumerate() {
+        if rule.sinks.is_empty() {
+            return Err(format!("Notify rule {} has no sinks", i));
+        }
+        if let Some(unknown) = rule.sinks.iter().find(|s| !config.sinks.contains_key(*s)) {
+            return Err(format!("Notify rule {} references unknown sink '{}'", i, unknown));
+        }
+    }
+    Ok(config)
+}
+
+/// Discord allows 30 requests per minute per webhook; stay under it
+const DEFAULT_DISCORD_MAX_PER_MINUTE: usize = 25;
+
+/// Sinks set up by the DISCORD_* / TELEGRAM_* variables instead of `NOTIFY_ROUTES`
+#[derive(Debug, Clone, Default)]
+pub struct ShortcutSinks {
+    pub discord_webhook_urls: Vec<String>,
+    pub discord_min_severity: i32,
+    pub discord_max_per_minute: usize,
+    pub telegram: Option<TelegramBotConfig>,
+}
+
+impl ShortcutSinks {
+    /// Routing config with one sink per webhook/chat and one rule per service;
+    /// None when nothing is configured
+    pub fn to_config(&self) -> Result<Option<NotifyConfig>, String> {
+        let mut sinks = serde_json::Map::new();
+        let mut rules = Vec::new();
+
+        let names: Vec<String> = (1..=self.discord_webhook_urls.len())
+            .map(|i| format!("discord_{}", i))
+            .collect();
+        for (name, url) in names.iter().zip(&self.discord_webhook_urls) {
+            let sink = json!({"type": "discord", "webhook_url": url,
+                              "max_per_minute": self.discord_max_per_minute});
+            sinks.insert(name.clone(), sink);
+        }
+        if !names.is_empty() {
+            rules.push(json!({"sinks": names, "min_severity": self.discord_min_severity}));
+        }
+
+        if let Some(telegram) = &self.telegram {
+            let names: Vec<String> = (1..=telegram.chat_ids.len())
+                .map(|i| format!("telegram_{}", i))
+                .collect();
+            for (name, chat_id) in names.iter().zip(&telegram.chat_ids) {
+                let sink = json!({"type": "telegram", "bot_token": telegram.bot_token, "chat_id": chat_id});
+                sinks.insert(name.clone(), sink);
+            }
+            rules.push(json!({"sinks": names, "min_severity": telegram.min_severity}));
+        }
+
+        if rules.is_empty() {
+            return Ok(None);
+        }
+        parse_notify_config(&json!({"sinks": sinks, "rules": rules}).to_string()).map(Some)
+    }
+}
+
+/// Load routing config from `NOTIFY_ROUTES`, else the DISCORD_* / TELEGRAM_*
+/// shortcuts; None when none are set
+pub fn load_notify_config_from_env() -> Result<Option<NotifyConfig>, String> {
+    if let Ok(spec) = std::env::var("NOTIFY_ROUTES") {
+        if !spec.trim().is_empty() {
+            return parse_notify_config(&spec).map(Some);
+        }
+    }
+
+    let shortcuts = ShortcutSinks {
+        discord_webhook_urls: std::env::var("DISCORD_WEBHOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty())
+            .collect(),
+        discord_min_severity: std::env::var("DISCORD_MIN_SEVERITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1),
+        discord_max_per_minute: std::env::var("DISCORD_MAX_PER_MINUTE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_DISCORD_MAX_PER_MINUTE),
+        telegram: TelegramBotConfig::from_env()?,
+    };
+    shortcuts.to_config()
+}
+
+/// A new signal with the token context routing rules look at
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NotifySignal {
+    pub id: i64,
+    pub mint: String,
+    pub signal_type: String,
+    pub severity: i32,
+    pub score: Option<f64>,
+    pub window_seconds: i32,
+    pub source: String,
+    pub created_at: i64,
+    pub symbol: Option<String>,
+    pub pattern_tag: Option<String>,
+    pub market_cap_usd: Option<f64>,
+    /// Contributing signatures to reconcile against RPC (severity 5 only)
+    #[serde(skip)]
+    pub sample_signatures: Vec<String>,
+}
+
+impl NotifySignal {
+    /// One-line human-readable message (Discord / Telegram)
+    pub fn text(&self) -> String {
+        let token = match &self.symbol {
+            Some(symbol) => format!("${}", symbol),
+            None => self.mint.clone(),
+        };
+        format!(
+            "{} {} {} (severity {}) https://dexscreener.com/solana/{}",
+            severity_emoji(self.severity),
+            self.signal_type,
+            token,
+            self.severity,
+            self.mint
+        )
+    }
+}
+
+/// Marker prefixed to messages so severe signals stand out in a channel
+pub fn severity_emoji(severity: i32) -> &'static str {
+    match severity {
+        5.. => "🚨",
+        4 => "🔥",
+        3 => "⚠️",
+        2 => "📈",
+        _ => "ℹ️",
+    }
+}
+
+/// Lines listed in a digest message before it is cut short
+const MAX_DIGEST_LINES: usize = 20;
+
+/// What gets posted to a sink
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Single(NotifySignal),
+    /// Batched low-severity / rate-limited signals, oldest first
+    Digest(Vec<NotifySignal>),
+}
+
+impl Message {
+    fn text(&self) -> String {
+        match self {
+            Message::Single(signal) => signal.text(),
+            Message::Digest(signals) => {
+                let mut lines = vec![format!("Digest: {} signals", signals.len())];
+                lines.extend(signals.iter().take(MAX_DIGEST_LINES).map(|s| s.text()));
+                if signals.len() > MAX_DIGEST_LINES {
+                    lines.push(format!("...and {} more", signals.len() - MAX_DIGEST_LINES));
+                }
+                lines.join("\n")
+            }
+        }
+    }
+
+    fn signal_ids(&self) -> Vec<i64> {
+        match self {
+            Message::Single(signal) => vec![signal.id],
+            Message::Digest(signals) => signals.iter().map(|s| s.id).collect(),
+        }
+    }
+
+    fn payload(&self) -> serde_json::Value {
+        match self {
+            Message::Single(signal) => json!(signal),
+            Message::Digest(signals) => json!({"digest": signals}),
+        }
+    }
+}
+
+/// A message bound for a named sink
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivery {
+    pub sink: String,
+    pub message: Message,
+}
+
+/// Per-sink throttling state
+#[derive(Debug, Default)]
+struct SinkState {
+    /// Send times within the last minute
+    sent_at: VecDeque<i64>,
+    /// Signals waiting for the next digest
+    pending: Vec<NotifySignal>,
+    last_digest: i64,
+}
+
+impl SinkState {
+    fn has_capacity(&mut self, max_per_minute: Option<usize>, now: i64) -> bool {
+        while self.sent_at.front().is_some_and(|&t| t <= now - 60) {
+            self.sent_at.pop_front();
+        }
+        max_per_minute.is_none_or(|max| self.sent_at.len() < max)
+    }
+}
+
+/// Polls signals and delivers them to routed sinks
+pub struct Notifier {
+    conn: Mutex<Connection>,
+    config: NotifyConfig,
+    client: reqwest::Client,
+    /// Highest token_signals.id already processed
+    cursor: i64,
+    sink_states: HashMap<String, SinkState>,
+    /// Verifies severe signals before they are sent (None = send as is)
+    reconciler: Option<Reconciler>,
+}
+
+impl Notifier {
+    /// Create a notifier; signals written before startup are not sent
+    pub fn new(db_path: &str, config: NotifyConfig) -> Result<Self, SolflowError> {
+        let conn = Connection::open(db_path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        let cursor: i64 =
+            conn.query_row("SELECT COALESCE(MAX(id), 0) FROM token_signals", [], |row| row.get(0))?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| SolflowError::config(codes::CONFIG_INVALID, e))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            config,
+            client,
+            cursor,
+            sink_states: HashMap::new(),
+            reconciler: None,
+        })
+    }
+
+    pub fn config(&self) -> &NotifyConfig {
+        &self.config
+    }
+
+    /// Check severe signals' sample signatures over RPC before sending them
+    pub fn set_reconciler(&mut self, reconciler: Reconciler) {
+        self.reconciler = Some(reconciler);
+    }
+
+    /// Signals written since the last poll (advances the cursor)
+    pub fn poll_signals(&mut self) -> Result<Vec<NotifySignal>, rusqlite::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT s.id, s.mint, s.signal_type, s.severity, s.score, s.window_seconds,
+                    s.source, s.created_at, m.symbol, p.pattern_tag, m.market_cap,
+                    s.sample_signatures
+             FROM token_signals s
+             LEFT JOIN token_metadata m ON m.mint = s.mint
+             LEFT JOIN token_signal_summary p ON p.token_address = s.mint
+             WHERE s.id > ?
+             ORDER BY s.id",
+        )?;
+        let signals = stmt
+            .query_map([self.cursor], |row| {
+                Ok(NotifySignal {
+                    id: row.get(0)?,
+                    mint: row.get(1)?,
+                    signal_type: row.get(2)?,
+                    severity: row.get(3)?,
+                    score: row.get(4)?,
+                    window_seconds: row.get(5)?,
+                    source: row.get(6)?,
+                    created_at: row.get(7)?,
+                    symbol: row.get(8)?,
+                    pattern_tag: row.get(9)?,
+                    market_cap_usd: row.get(10)?,
+                    sample_signatures: row
+                        .get::<_, Option<String>>(11)?
+                        .map(|s| s.split(',').map(str::to_string).collect())
+                        .unwrap_or_default(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(last) = signals.last() {
+            self.cursor = last.id;
+        }
+        Ok(signals)
+    }
+
+    /// Route signals and apply throttling, returning what to send now
+    ///
+    /// Digests go out once a sink has pending signals and its digest
+    /// interval has passed; they are exempt from `max_per_minute` but count
+    /// towards it.
+    pub fn plan(&mut self, signals: &[NotifySignal], now: i64) -> Vec<Delivery> {
+        let mut deliveries = Vec::new();
+        for signal in signals {
+            for name in self.config.route(signal) {
+                let sink = &self.config.sinks[name];
+                let state = self
+                    .sink_states
+                    .entry(name.to_string())
+                    .or_insert_with(|| SinkState {
+                        last_digest: now,
+                        ..Default::default()
+                    });
+                let digested = sink.digest_below_severity.is_some_and(|min| signal.severity < min);
+                if digested || !state.has_capacity(sink.max_per_minute, now) {
+                    state.pending.push(signal.clone());
+                    continue;
+                }
+                state.sent_at.push_back(now);
+                deliveries.push(Delivery {
+                    sink: name.to_string(),
+                    message: Message::Single(signal.clone()),
+                });
+            }
+        }
+
+        for (name, state) in self.sink_states.iter_mut() {
+            let interval = self.config.sinks[name].digest_interval_secs;
+            if state.pending.is_empty() || now - state.last_digest < interval {
+                continue;
+            }
+            state.last_digest = now;
+            state.sent_at.push_back(now);
+            deliveries.push(Delivery {
+                sink: name.clone(),
+                message: Message::Digest(std::mem::take(&mut state.pending)),
+            });
+        }
+        deliveries
+    }
+
+    /// Plan and send; returns (sink, error) for failed deliveries
+    pub async fn dispatch(&mut self, signals: &[NotifySignal], now: i64) -> Vec<(String, String)> {
+        let signals = self.drop_phantoms(signals).await;
+        let mut failures = Vec::new();
+        let mut sent_to_discord = Vec::new();
+        for delivery in self.plan(&signals, now) {
+            let sink = &self.config.sinks[&delivery.sink];
+            match self.send(sink, &delivery.message).await {
+                Ok(()) if matches!(sink.target, Sink::Discord { .. }) => {
+                    sent_to_discord.extend(delivery.message.signal_ids());
+                }
+                Ok(()) => {}
+                Err(e) => failures.push((delivery.sink, e.to_string())),
+            }
+        }
+        if let Err(e) = self.mark_sent_to_discord(&sent_to_discord) {
+            log::warn!("⚠️  Notifier: failed to mark signals sent_to_discord: {}", e);
+        }
+        failures
+    }
+
+    /// Signals minus severe ones whose sample signatures did not land
+    async fn drop_phantoms(&self, signals: &[NotifySignal]) -> Vec<NotifySignal> {
+        let Some(reconciler) = &self.reconciler else {
+            return signals.to_vec();
+        };
+        let mut kept = Vec::with_capacity(signals.len());
+        for signal in signals {
+            if signal.severity >= RECONCILED_SEVERITY && !signal.sample_signatures.is_empty() {
+                if let Verdict::Phantom { bad, checked } =
+                    reconciler.reconcile(&signal.sample_signatures).await
+                {
+                    log::warn!(
+                        "👻 Notifier: dropped {} for {} ({}/{} sampled signatures not landed at {})",
+                        signal.signal_type,
+                        signal.mint,
+                        bad,
+                        checked,
+                        reconciler.config().commitment
+                    );
+                    continue;
+                }
+            }
+            kept.push(signal.clone());
+        }
+        kept
+    }
+
+    /// Set `token_signals.sent_to_discord` for delivered signals
+    pub fn mark_sent_to_discord(&self, ids: &[i64]) -> Result<usize, rusqlite::Error> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let mut updated = 0;
+        {
+            let mut stmt =
+                tx.prepare_cached("UPDATE token_signals SET sent_to_discord = 1 WHERE id = ?")?;
+            for id in ids {
+                updated += stmt.execute([id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(updated)
+    }
+
+    async fn send(&self, sink: &SinkConfig, message: &Message) -> Result<(), reqwest::Error> {
+        let rendered = sink.render(message);
+        let text = || rendered.clone().unwrap_or_else(|| message.text());
+        let request = match &sink.target {
+            Sink::Discord { webhook_url } => self
+                .client
+                .post(webhook_url)
+                .json(&json!({"content": text()})),
+            Sink::Telegram { bot_token, chat_id } => self
+                .client
+                .post(format!("https://api.telegram.org/bot{}/sendMessage", bot_token))
+                .json(&json!({"chat_id": chat_id, "text": text()})),
+            Sink::Webhook { url } => match rendered {
+                Some(body) => self
+                    .client
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body),
+                None => self.client.post(url).json(&message.payload()),
+            },
+        };
+        // Webhook URLs and bot tokens are secrets: keep them out of error messages
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.without_url())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(signal_type: &str, severity: i32) -> NotifySignal {
+        NotifySignal {
+            id: 1,
+            mint: "mint1".to_string(),
+            signal_type: signal_type.to_string(),
+            severity,
+            score: None,
+            window_seconds: 300,
+            source: "onchain".to_string(),
+            created_at: 1000,
+            symbol: Some("TEST".to_string()),
+            pattern_tag: Some("MOMENTUM".to_string()),
+            market_cap_usd: Some(500_000.0),
+            sample_signatures: Vec::new(),
+        }
+    }
+
+    const CONFIG: &str = r#"{
+        "sinks": {
+            "alpha": {"type": "discord", "webhook_url": "http://localhost/discord"},
+            "phone": {"type": "telegram", "bot_token": "t", "chat_id": "1"},
+            "bot": {"type": "webhook", "url": "http://localhost/bot"}
+        },
+        "rules": [
+            {"sinks": ["alpha"], "min_severity": 3},
+            {"sinks": ["phone", "alpha"], "signal_types": ["BREAKOUT"], "min_severity": 4,
+             "pattern_tags": ["MOMENTUM"], "max_market_cap_usd": 1000000},
+            {"sinks": ["bot"], "signal_types": ["SURGE"], "min_market_cap_usd": 1000000}
+        ]
+    }"#;
+
+    #[test]
+    fn test_route_by_rules() {
+        let config = parse_notify_config(CONFIG).unwrap();
+        assert_eq!(config.sinks.len(), 3);
+
+        assert_eq!(config.route(&signal("BREAKOUT", 5)), vec!["alpha", "phone"]);
+        assert_eq!(config.route(&signal("BREAKOUT", 3)), vec!["alpha"]);
+        assert!(config.route(&signal("BREAKOUT", 2)).is_empty());
+
+        let mut untagged = signal("BREAKOUT", 5);
+        untagged.pattern_tag = None;
+        assert_eq!(config.route(&untagged), vec!["alpha"]);
+
+        // Market-cap band: unknown or out-of-band caps don't match
+        let mut surge = signal("SURGE", 1);
+        assert!(config.route(&surge).is_empty());
+        surge.market_cap_usd = Some(5_000_000.0);
+        assert_eq!(config.route(&surge), vec!["bot"]);
+        surge.market_cap_usd = None;
+        assert!(config.route(&surge).is_empty());
+    }
+
+    #[test]
+    fn test_rate_limit_and_digest() {
+        let config = r#"{
+            "sinks": {"alpha": {"type": "webhook", "url": "http://localhost/a",
+                                "max_per_minute": 2, "digest_below_severity": 3,
+                                "digest_interval_secs": 300}},
+            "rules": [{"sinks": ["alpha"]}]
+        }"#;
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("notify.db");
+        let db_path = db_path.to_str().unwrap();
+        Connection::open(db_path)
+            .unwrap()
+            .execute_batch("CREATE TABLE token_signals (id INTEGER PRIMARY KEY)")
+            .unwrap();
+        let mut notifier = Notifier::new(db_path, parse_notify_config(config).unwrap()).unwrap();
+
+        let signals = vec![
+            signal("BREAKOUT", 5),
+            signal("SURGE", 1),
+            signal("BREAKOUT", 4),
+            signal("BREAKOUT", 4),
+        ];
+        let deliveries = notifier.plan(&signals, 1000);
+        assert_eq!(deliveries.len(), 2);
+        assert!(deliveries.iter().all(|d| matches!(d.message, Message::Single(_))));
+
+        // Capacity frees up after a minute; the digest waits for its interval
+        let deliveries = notifier.plan(&[signal("BREAKOUT", 4)], 1061);
+        assert_eq!(deliveries.len(), 1);
+        assert!(notifier.plan(&[], 1200).is_empty());
+
+        // Low-severity and rate-limited signals arrive together in one digest
+        let deliveries = notifier.plan(&[], 1300);
+        assert_eq!(deliveries.len(), 1);
+        match &deliveries[0].message {
+            Message::Digest(batched) => {
+                assert_eq!(batched.len(), 2);
+                assert_eq!(batched[0].signal_type, "SURGE");
+            }
+            other => panic!("expected digest, got {:?}", other),
+        }
+        assert!(notifier.plan(&[], 1700).is_empty());
+    }
+
+    #[test]
+    fn test_sink_templates_by_signal_type() {
+        let config = r#"{
+            "sinks": {
+                "slack": {"type": "webhook", "url": "http://localhost/slack",
+                          "templates": {"BREAKOUT": "{\"text\": \"{{symbol}} broke out\"}",
+                                        "*": "{\"text\": \"{{text}}\"}"}},
+                "alpha": {"type": "discord", "webhook_url": "http://localhost/discord",
+                          "templates": {"SURGE": "surge on {{mint}}"}}
+            },
+            "rules": [{"sinks": ["slack", "alpha"]}]
+        }"#;
+        let config = parse_notify_config(config).unwrap();
+        let slack = &config.sinks["slack"];
+        let alpha = &config.sinks["alpha"];
+
+        let breakout = Message::Single(signal("BREAKOUT", 5));
+        assert_eq!(slack.render(&breakout).unwrap(), r#"{"text": "TEST broke out"}"#);
+        let surge = Message::Single(signal("SURGE", 2));
+        assert!(slack.render(&surge).unwrap().contains("SURGE $TEST (severity 2)"));
+        assert_eq!(alpha.render(&surge).unwrap(), "surge on mint1");
+        assert!(alpha.render(&breakout).is_none());
+        assert!(slack.render(&Message::Digest(vec![signal("SURGE", 2)])).is_none());
+
+        let bad = r#"{"sinks": {"x": {"type": "webhook", "url": "http://localhost",
+                      "templates": {"*": "{{nope}}"}}}, "rules": []}"#;
+        assert!(parse_notify_config(bad).unwrap_err().contains("nope"));
+    }
+
+    #[test]
+    fn test_rejects_unknown_sinks() {
+        let config = r#"{"sinks": {}, "rules": [{"sinks": ["missing"]}]}"#;
+        assert!(parse_notify_config(config).unwrap_err().contains("missing"));
+
+        let config = r#"{"sinks": {"x": {"type": "carrier_pigeon"}}, "rules": []}"#;
+        assert!(parse_notify_config(config).is_err());
+    }
+
+    #[test]
+    fn test_poll_joins_token_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("notify.db");
+        let db_path = db_path.to_str().unwrap();
+        let conn = Connection::open(db_path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE token_signals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, mint TEXT, signal_type TEXT,
+                window_seconds INTEGER, severity INTEGER, score REAL,
+                source TEXT NOT NULL DEFAULT 'onchain', created_at INTEGER,
+                sample_signatures TEXT, sent_to_discord INTEGER NOT NULL DEFAULT 0);
+             CREATE TABLE token_metadata (mint TEXT PRIMARY KEY, symbol TEXT, market_cap REAL);
+             CREATE TABLE token_signal_summary (token_address TEXT PRIMARY KEY, pattern_tag TEXT);
+             INSERT INTO token_signals (mint, signal_type, window_seconds, severity, created_at)
+                VALUES ('old', 'SURGE', 60, 5, 1);",
+        )
+        .unwrap();
+
+        let mut notifier = Notifier::new(db_path, parse_notify_config(CONFIG).unwrap()).unwrap();
+        conn.execute_batch(
+            "INSERT INTO token_signals (mint, signal_type, window_seconds, severity, created_at, sample_signatures)
+                VALUES ('mint1', 'BREAKOUT', 300, 4, 1000, 'sigA,sigB');
+             INSERT INTO token_metadata VALUES ('mint1', 'TEST', 750000.0);
+             INSERT INTO token_signal_summary VALUES ('mint1', 'MOMENTUM');",
+        )
+        .unwrap();
+
+        let signals = notifier.poll_signals().unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].symbol.as_deref(), Some("TEST"));
+        assert_eq!(signals[0].pattern_tag.as_deref(), Some("MOMENTUM"));
+        assert_eq!(signals[0].sample_signatures, vec!["sigA", "sigB"]);
+        assert_eq!(notifier.config().route(&signals[0]), vec!["alpha", "phone"]);
+        assert!(notifier.poll_signals().unwrap().is_empty());
+
+        assert_eq!(notifier.mark_sent_to_discord(&[signals[0].id]).unwrap(), 1);
+        let sent: i64 = conn
+            .query_row("SELECT SUM(sent_to_discord) FROM token_signals WHERE mint = 'mint1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(sent, 1);
+    }
+
+    #[test]
+    fn test_shortcut_sinks_config() {
+        let mut shortcuts = ShortcutSinks {
+            discord_webhook_urls: vec!["http://localhost/a".to_string(), "http://localhost/b".to_string()],
+            discord_min_severity: 3,
+            discord_max_per_minute: 25,
+            telegram: None,
+        };
+        let config = shortcuts.to_config().unwrap().unwrap();
+        assert_eq!(config.sinks["discord_2"].max_per_minute, Some(25));
+        assert_eq!(config.route(&signal("SURGE", 3)), vec!["discord_1", "discord_2"]);
+        assert!(config.route(&signal("SURGE", 2)).is_empty());
+        assert!(signal("SURGE", 5).text().starts_with("🚨 SURGE $TEST"));
+
+        shortcuts.telegram = Some(TelegramBotConfig {
+            bot_token: "123:abc".to_string(),
+            chat_ids: vec!["-100123".to_string()],
+            min_severity: 4,
+        });
+        let config = shortcuts.to_config().unwrap().unwrap();
+        assert_eq!(config.route(&signal("SURGE", 4)), vec!["discord_1", "discord_2", "telegram_1"]);
+        assert_eq!(config.route(&signal("SURGE", 3)), vec!["discord_1", "discord_2"]);
+
+        assert!(ShortcutSinks::default().to_config().unwrap().is_none());
+    }
+}
@@ -0,0 +1,509 @@
+//! Paper-trading executor driven by signals
+//!
+//! Strategies "buy" a fixed SOL size when their entry signal is written to
+//! `token_signals` and "sell" on exit signals or price rules. Fills use the
+//! engine's latest trade-derived price with slippage applied, and every
+//! trade is recorded in `paper_trades` so strategies can be compared on
+//! realized PnL.
+//!
+//! Strategies are JSON, either inline or in a file:
+//!
+//! ```json
+//! [{"name": "breakout_4", "entry_signal": "BREAKOUT", "min_severity": 4,
+//!   "size_sol": 0.5, "exit_signals": ["POSITION_EXIT", "BOT_DROPOFF"],
+//!   "take_profit_pct": 100, "stop_loss_pct": 30, "max_hold_secs": 3600}]
+//! ```
+//!
+//! Environment variables:
+//! - `PAPER_STRATEGIES`: Strategy JSON array, or a path to a JSON file (unset = disabled)
+//! - `PAPER_TRADING_INTERVAL_SECS`: Signal polling / price check interval (default: 5)
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+fn default_min_severity() -> i32 {
+    1
+}
+
+fn default_slippage_bps() -> f64 {
+    100.0
+}
+
+/// A signal-driven strategy
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Strategy {
+    pub name: String,
+    /// Signal type that opens a trade (e.g. "BREAKOUT")
+    pub entry_signal: String,
+    #[serde(default = "default_min_severity")]
+    pub min_severity: i32,
+    /// SOL spent per entry
+    pub size_sol: f64,
+    /// Signal types that close an open trade on the same mint
+    #[serde(default)]
+    pub exit_signals: Vec<String>,
+    #[serde(default)]
+    pub take_profit_pct: Option<f64>,
+    #[serde(default)]
+    pub stop_loss_pct: Option<f64>,
+    #[serde(default)]
+    pub max_hold_secs: Option<i64>,
+    /// Adverse slippage applied to both fills, in basis points
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: f64,
+}
+
+impl Strategy {
+    fn enters_on(&self, signal: &SignalEvent) -> bool {
+        signal.signal_type == self.entry_signal && signal.severity >= self.min_severity
+    }
+
+    fn exits_on(&self, signal: &SignalEvent) -> bool {
+        self.exit_signals.iter().any(|s| *s == signal.signal_type)
+    }
+
+    fn buy_fill(&self, price_sol: f64) -> f64 {
+        price_sol * (1.0 + self.slippage_bps / 10_000.0)
+    }
+
+    fn sell_fill(&self, price_sol: f64) -> f64 {
+        price_sol * (1.0 - self.slippage_bps / 10_000.0)
+    }
+
+    /// Price/time exit for an open trade, if any rule trips
+    fn price_exit(&self, trade: &PaperTrade, price_sol: f64, now: i64) -> Option<String> {
+        let pnl_pct = (self.sell_fill(price_sol) / trade.entry_price_sol - 1.0) * 100.0;
+        if self.stop_loss_pct.is_some_and(|sl| pnl_pct <= -sl) {
+            Some("STOP_LOSS".to_string())
+        } else if self.take_profit_pct.is_some_and(|tp| pnl_pct >= tp) {
+            Some("TAKE_PROFIT".to_string())
+        } else if self.max_hold_secs.is_some_and(|max| now - trade.opened_at >= max) {
+            Some("MAX_HOLD".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Parse strategies from inline JSON or a JSON file path
+pub fn parse_strategies(spec: &str) -> Result<Vec<Strategy>, String> {
+    let spec = spec.trim();
+    let json = if spec.starts_with('[') {
+        spec.to_string()
+    } else {
+        std::fs::read_to_string(spec)
+            .map_err(|e| format!("Failed to read strategies file {}: {}", spec, e))?
+    };
+
+    let strategies: Vec<Strategy> =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid strategy JSON: {}", e))?;
+
+    let mut names = HashSet::new();
+    for strategy in &strategies {
+        if !names.insert(strategy.name.as_str()) {
+            return Err(format!("Duplicate strategy name '{}'", strategy.name));
+        }
+        if strategy.size_sol <= 0.0 {
+            return Err(format!("Strategy '{}': size_sol must be positive", strategy.name));
+        }
+    }
+    Ok(strategies)
+}
+
+/// Load strategies from `PAPER_STRATEGIES`; None when unset
+pub fn load_strategies_from_env() -> Result<Option<Vec<Strategy>>, String> {
+    match std::env::var("PAPER_STRATEGIES") {
+        Ok(spec) if !spec.trim().is_empty() => parse_strategies(&spec).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// A signal row read from `token_signals`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalEvent {
+    pub id: i64,
+    pub mint: String,
+    pub signal_type: String,
+    pub severity: i32,
+}
+
+/// An open paper trade
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaperTrade {
+    pub id: i64,
+    pub strategy: String,
+    pub mint: String,
+    pub size_sol: f64,
+    pub tokens: f64,
+    pub entry_price_sol: f64,
+    pub opened_at: i64,
+}
+
+/// A simulated fill, for logging
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaperFill {
+    Open {
+        strategy: String,
+        mint: String,
+        price_sol: f64,
+    },
+    Close {
+        strategy: String,
+        mint: String,
+        price_sol: f64,
+        reason: String,
+        pnl_sol: f64,
+    },
+}
+
+/// Per-strategy results
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategySummary {
+    pub strategy: String,
+    pub open: i64,
+    pub closed: i64,
+    pub wins: i64,
+    pub realized_pnl_sol: f64,
+}
+
+pub struct PaperTrader {
+    conn: Mutex<Connection>,
+    strategies: Vec<Strategy>,
+    /// Highest token_signals.id already processed
+    cursor: i64,
+}
+
+impl PaperTrader {
+    /// Create a trader; signals written before startup are not traded
+    pub fn new(db_path: &str, strategies: Vec<Strategy>) -> Result<Self, rusqlite::Error> {
+        let conn = Connection::open(db_path)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        let cursor: i64 =
+            conn.query_row("SELECT COALESCE(MAX(id), 0) FROM token_signals", [], |row| row.get(0))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            strategies,
+            cursor,
+        })
+    }
+
+    pub fn strategies(&self) -> &[Strategy] {
+        &self.strategies
+    }
+
+    /// Signals written since the last poll (advances the cursor)
+    pub fn poll_signals(&mut self) -> Result<Vec<SignalEvent>, rusqlite::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, mint, signal_type, severity FROM token_signals
+             WHERE id > ? ORDER BY id",
+        )?;
+        let signals = stmt
+            .query_map([self.cursor], |row| {
+                Ok(SignalEvent {
+                    id: row.get(0)?,
+                    mint: row.get(1)?,
+                    signal_type: row.get(2)?,
+                    severity: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(last) = signals.last() {
+            self.cursor = last.id;
+        }
+        Ok(signals)
+    }
+
+    pub fn open_trades(&self) -> Result<Vec<PaperTrade>, rusqlite::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, strategy, mint, size_sol, tokens, entry_price_sol, opened_at
+             FROM paper_trades WHERE closed_at IS NULL",
+        )?;
+        let trades = stmt
+            .query_map([], |row| {
+                Ok(PaperTrade {
+                    id: row.get(0)?,
+                    strategy: row.get(1)?,
+                    mint: row.get(2)?,
+                    size_sol: row.get(3)?,
+                    tokens: row.get(4)?,
+                    entry_price_sol: row.get(5)?,
+                    opened_at: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(trades)
+    }
+
+    /// Mints that need a price this cycle (signal mints + open trades)
+    pub fn mints_of_interest(signals: &[SignalEvent], open: &[PaperTrade]) -> HashSet<String> {
+        signals
+            .iter()
+            .map(|s| s.mint.clone())
+            .chain(open.iter().map(|t| t.mint.clone()))
+            .collect()
+    }
+
+    /// Apply signals, then price rules, recording fills
+    ///
+    /// Mints without a price are skipped: no fill can be derived for them.
+    pub fn apply(
+        &self,
+        signals: &[SignalEvent],
+        open: Vec<PaperTrade>,
+        prices: &HashMap<String, f64>,
+        now: i64,
+    ) -> Result<Vec<PaperFill>, rusqlite::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut open: HashMap<(String, String), PaperTrade> = open
+            .into_iter()
+            .map(|t| ((t.strategy.clone(), t.mint.clone()), t))
+            .collect();
+        let mut fills = Vec::new();
+
+        for signal in signals {
+            let Some(&price) = prices.get(&signal.mint) else {
+                continue;
+            };
+            for strategy in &self.strategies {
+                let key = (strategy.name.clone(), signal.mint.clone());
+                if strategy.exits_on(signal) {
+                    if let Some(trade) = open.remove(&key) {
+                        let reason = format!("SIGNAL:{}", signal.signal_type);
+                        fills.push(Self::close(&conn, strategy, &trade, price, &reason, now)?);
+                    }
+                } else if strategy.enters_on(signal) && !open.contains_key(&key) {
+                    let trade = Self::open(&conn, strategy, signal, price, now)?;
+                    fills.push(PaperFill::Open {
+                        strategy: strategy.name.clone(),
+                        mint: signal.mint.clone(),
+                        price_sol: trade.entry_price_sol,
+                    });
+                    open.insert(key, trade);
+                }
+            }
+        }
+
+        for trade in open.values() {
+            let Some(strategy) = self.strategies.iter().find(|s| s.name == trade.strategy) else {
+                continue; // Strategy removed from config: leave the trade as-is
+            };
+            let Some(&price) = prices.get(&trade.mint) else {
+                continue;
+            };
+            if let Some(reason) = strategy.price_exit(trade, price, now) {
+                fills.push(Self::close(&conn, strategy, trade, price, &reason, now)?);
+            }
+        }
+
+        Ok(fills)
+    }
+
+    fn open(
+        conn: &Connection,
+        strategy: &Strategy,
+        signal: &SignalEvent,
+        price_sol: f64,
+        now: i64,
+    ) -> Result<PaperTrade, rusqlite::Error> {
+        let entry_price_sol = strategy.buy_fill(price_sol);
+        let tokens = strategy.size_sol / entry_price_sol;
+        conn.execute(
+            "INSERT INTO paper_trades (
+                strategy, mint, entry_signal_id, entry_signal_type,
+                size_sol, tokens, entry_price_sol, opened_at
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            rusqlite::params![
+                strategy.name,
+                signal.mint,
+                signal.id,
+                signal.signal_type,
+                strategy.size_sol,
+                tokens,
+                entry_price_sol,
+                now,
+            ],
+        )?;
+        Ok(PaperTrade {
+            id: conn.last_insert_rowid(),
+            strategy: strategy.name.clone(),
+            mint: signal.mint.clone(),
+            size_sol: strategy.size_sol,
+            tokens,
+            entry_price_sol,
+            opened_at: now,
+        })
+    }
+
+    fn close(
+        conn: &Connection,
+        strategy: &Strategy,
+        trade: &PaperTrade,
+        price_sol: f64,
+        reason: &str,
+        now: i64,
+    ) -> Result<PaperFill, rusqlite::Error> {
+        let exit_price_sol = strategy.sell_fill(price_sol);
+        let pnl_sol = trade.tokens * exit_price_sol - trade.size_sol;
+        let pnl_pct = pnl_sol / trade.size_sol * 100.0;
+        conn.execute(
+            "UPDATE paper_trades SET
+                exit_price_sol = ?, exit_reason = ?, closed_at = ?, pnl_sol = ?, pnl_pct = ?
+             WHERE id = ?",
+            rusqlite::params![exit_price_sol, reason, now, pnl_sol, pnl_pct, trade.id],
+        )?;
+        Ok(PaperFill::Close {
+            strategy: trade.strategy.clone(),
+            mint: trade.mint.clone(),
+            price_sol: exit_price_sol,
+            reason: reason.to_string(),
+            pnl_sol,
+        })
+    }
+
+    /// Realized results per strategy (all time)
+    pub fn summary(&self) -> Result<Vec<StrategySummary>, rusqlite::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT strategy,
+                    SUM(closed_at IS NULL),
+                    SUM(closed_at IS NOT NULL),
+                    SUM(pnl_sol > 0),
+                    COALESCE(SUM(pnl_sol), 0.0)
+             FROM paper_trades
+             GROUP BY strategy
+             ORDER BY strategy",
+        )?;
+        let summaries = stmt
+            .query_map([], |row| {
+                Ok(StrategySummary {
+                    strategy: row.get(0)?,
+                    open: row.get(1)?,
+                    closed: row.get(2)?,
+                    wins: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+                    realized_pnl_sol: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(summaries)
+    }
+
+    /// Latest open trade for a strategy/mint (for inspection and tests)
+    pub fn open_trade(&self, strategy: &str, mint: &str) -> Result<Option<PaperTrade>, rusqlite::Error> {
+        Ok(self
+            .open_trades()?
+            .into_iter()
+            .find(|t| t.strategy == strategy && t.mint == mint))
+    }
+
+    /// Exit reason of the most recently closed trade for a mint
+    pub fn last_exit_reason(&self, mint: &str) -> Result<Option<String>, rusqlite::Error> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT exit_reason FROM paper_trades
+             WHERE mint = ? AND closed_at IS NOT NULL
+             ORDER BY closed_at DESC, id DESC LIMIT 1",
+            [mint],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRATEGIES: &str = r#"[{
+        "name": "breakout_4",
+        "entry_signal": "BREAKOUT",
+        "min_severity": 4,
+        "size_sol": 0.5,
+        "exit_signals": ["POSITION_EXIT"],
+        "take_profit_pct": 50,
+        "slippage_bps": 0
+    }]"#;
+
+    fn setup() -> (tempfile::TempDir, Connection, PaperTrader) {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("paper.db");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(include_str!("../../sql/03_token_signals.sql")).unwrap();
+        conn.execute_batch(include_str!("../../sql/10_paper_trades.sql")).unwrap();
+        let trader =
+            PaperTrader::new(db_path.to_str().unwrap(), parse_strategies(STRATEGIES).unwrap()).unwrap();
+        (dir, conn, trader)
+    }
+
+    fn insert_signal(conn: &Connection, mint: &str, signal_type: &str, severity: i32) {
+        conn.execute(
+            "INSERT INTO token_signals (mint, signal_type, window_seconds, severity, created_at)
+             VALUES (?, ?, 60, ?, 0)",
+            rusqlite::params![mint, signal_type, severity],
+        )
+        .unwrap();
+    }
+
+    fn cycle(trader: &mut PaperTrader, prices: &HashMap<String, f64>, now: i64) -> Vec<PaperFill> {
+        let signals = trader.poll_signals().unwrap();
+        let open = trader.open_trades().unwrap();
+        trader.apply(&signals, open, prices, now).unwrap()
+    }
+
+    #[test]
+    fn test_parse_strategies() {
+        let strategies = parse_strategies(STRATEGIES).unwrap();
+        assert_eq!(strategies[0].min_severity, 4);
+        assert_eq!(strategies[0].stop_loss_pct, None);
+
+        let dup = r#"[{"name":"a","entry_signal":"SURGE","size_sol":1},{"name":"a","entry_signal":"SURGE","size_sol":1}]"#;
+        assert!(parse_strategies(dup).is_err());
+        assert_eq!(parse_strategies(r#"[{"name":"b","entry_signal":"SURGE","size_sol":1}]"#).unwrap()[0].slippage_bps, 100.0);
+    }
+
+    #[test]
+    fn test_signal_entry_and_exit() {
+        let (_dir, conn, mut trader) = setup();
+        let prices = HashMap::from([("mint_a".to_string(), 0.001)]);
+
+        // Below min severity: no entry
+        insert_signal(&conn, "mint_a", "BREAKOUT", 3);
+        assert!(cycle(&mut trader, &prices, 100).is_empty());
+
+        insert_signal(&conn, "mint_a", "BREAKOUT", 5);
+        insert_signal(&conn, "mint_a", "BREAKOUT", 5); // Already open: ignored
+        let fills = cycle(&mut trader, &prices, 110);
+        assert_eq!(fills.len(), 1);
+        let trade = trader.open_trade("breakout_4", "mint_a").unwrap().unwrap();
+        assert!((trade.tokens - 500.0).abs() < 1e-9);
+
+        let exit_prices = HashMap::from([("mint_a".to_string(), 0.0012)]);
+        insert_signal(&conn, "mint_a", "POSITION_EXIT", 4);
+        let fills = cycle(&mut trader, &exit_prices, 120);
+        match &fills[..] {
+            [PaperFill::Close { reason, pnl_sol, .. }] => {
+                assert_eq!(reason, "SIGNAL:POSITION_EXIT");
+                assert!((pnl_sol - 0.1).abs() < 1e-9);
+            }
+            other => panic!("unexpected fills: {:?}", other),
+        }
+
+        let summary = trader.summary().unwrap();
+        assert_eq!(summary[0].closed, 1);
+        assert_eq!(summary[0].wins, 1);
+    }
+
+    #[test]
+    fn test_take_profit_exit() {
+        let (_dir, conn, mut trader) = setup();
+        insert_signal(&conn, "mint_b", "BREAKOUT", 4);
+        cycle(&mut trader, &HashMap::from([("mint_b".to_string(), 0.001)]), 100);
+
+        cycle(&mut trader, &HashMap::from([("mint_b".to_string(), 0.0016)]), 200);
+        assert_eq!(trader.last_exit_reason("mint_b").unwrap().as_deref(), Some("TAKE_PROFIT"));
+        assert!(trader.open_trades().unwrap().is_empty());
+    }
+}
@@ -13,18 +13,35 @@
 //! - Bot penalty (10%): Penalize excessive bot activity
 //!
 //! **pattern_tag**:
-//! - ACCUMULATION: High DCA overlap + positive net flow
-//! - MOMENTUM: Strong uptrend score + increasing velocity
+//! - ACCUMULATION: High DCA overlap or early buyers still holding + positive net flow
+//! - MOMENTUM: Strong uptrend score + increasing velocity (not while early buyers churn out)
 //! - DISTRIBUTION: Negative net flow + high sell pressure
 //! - WASHOUT: Declining across all metrics
 //! - NOISE: Inconsistent or low-quality signals
 //!
 //! **confidence** (LOW/MEDIUM/HIGH):
 //! - Based on data richness, consistency, token lifetime, and bot interference
+//! - Penalized when the 10 largest wallets hold much of the supply
+//!   (`top10_holder_pct`, see `holders`)
 
+use super::enrichment::Enricher;
+use crate::error::SolflowError;
+use async_trait::async_trait;
 use rusqlite::{Connection, Result as SqliteResult};
 use std::collections::HashMap;
 
+/// Early-holder retention at or above which buying counts as accumulation
+const EARLY_RETENTION_ACCUMULATION: f64 = 0.7;
+
+/// Early-holder retention below which inflows are treated as churn
+const EARLY_RETENTION_CHURN: f64 = 0.3;
+
+/// Top-10 holder share (percent) at which a token counts as insider-held
+const INSIDER_HELD_PCT: f64 = 50.0;
+
+/// Top-10 holder share (percent) at which a token counts as concentrated
+const CONCENTRATED_HOLDERS_PCT: f64 = 30.0;
+
 /// Token metrics snapshot from database
 #[derive(Debug, Clone)]
 pub struct TokenSnapshot {
@@ -43,7 +60,12 @@ pub struct TokenSnapshot {
     pub volume_300s_sol: f64,
     pub updated_at: i64,
     pub created_at: i64,
+    /// DexScreener pair creation, else on-chain mint creation (mint watcher)
     pub pair_created_at: Option<i64>,
+    /// Share of the mint's first buyers still holding (None if unknown)
+    pub early_holder_retention: Option<f64>,
+    /// Share of supply held by the 10 largest wallets, 0-100 (None if not fetched)
+    pub top10_holder_pct: Option<f64>,
 }
 
 /// Signal summary for appearance tracking
@@ -104,7 +126,9 @@ impl PersistenceScorer {
                 ta.volume_300s_sol,
                 ta.updated_at,
                 ta.created_at,
-                tm.pair_created_at
+                COALESCE(tm.pair_created_at, tm.mint_created_at),
+                ta.early_holder_retention,
+                ta.top10_holder_pct
             FROM token_aggregates ta
             LEFT JOIN token_metadata tm ON ta.mint = tm.mint
             WHERE (ta.dca_buys_3600s > 0 OR ta.net_flow_300s_sol > 10.0)
@@ -133,6 +157,8 @@ impl PersistenceScorer {
                     updated_at: row.get(13).unwrap_or(0),
                     created_at: row.get(14).unwrap_or(0),
                     pair_created_at: row.get(15).ok(),
+                    early_holder_retention: row.get(16).ok().flatten(),
+                    top10_holder_pct: row.get(17).ok().flatten(),
                 })
             })?
             .collect::<SqliteResult<Vec<_>>>()?;
@@ -270,7 +296,24 @@ impl PersistenceScorer {
         }
     }
 
+    /// Calculate holder concentration multiplier
+    ///
+    /// - Top 10 wallets hold >=50%: 0.6 (insider-held, can never reach HIGH)
+    /// - Top 10 wallets hold >=30%: 0.8
+    /// - Otherwise or unknown: 1.0
+    fn compute_holder_multiplier(&self, top10_holder_pct: Option<f64>) -> f64 {
+        match top10_holder_pct {
+            Some(pct) if pct >= INSIDER_HELD_PCT => 0.6,
+            Some(pct) if pct >= CONCENTRATED_HOLDERS_PCT => 0.8,
+            _ => 1.0,
+        }
+    }
+
     /// Classify pattern tag
+    ///
+    /// Early-holder retention separates accumulation from churn: most first
+    /// buyers still holding counts like DCA overlap, while most of them having
+    /// sold means inflows are rotation rather than momentum.
     fn classify_pattern(&self, token: &TokenSnapshot, dca_overlap: bool) -> String {
         let total_trades = token.buy_count_300s + token.sell_count_300s;
         let buy_ratio = if total_trades > 0 {
@@ -281,9 +324,16 @@ impl PersistenceScorer {
 
         let avg_net_flow = (token.net_flow_300s + token.net_flow_900s + token.net_flow_3600s) / 3.0;
 
-        if dca_overlap && avg_net_flow > 0.0 && buy_ratio > 0.6 {
+        let early_holders_accumulating = token
+            .early_holder_retention
+            .is_some_and(|r| r >= EARLY_RETENTION_ACCUMULATION);
+        let early_holders_churning = token
+            .early_holder_retention
+            .is_some_and(|r| r < EARLY_RETENTION_CHURN);
+
+        if (dca_overlap || early_holders_accumulating) && avg_net_flow > 0.0 && buy_ratio > 0.6 {
             "ACCUMULATION".to_string()
-        } else if avg_net_flow > 5.0 && buy_ratio > 0.7 {
+        } else if avg_net_flow > 5.0 && buy_ratio > 0.7 && !early_holders_churning {
             "MOMENTUM".to_string()
         } else if avg_net_flow < -2.0 && buy_ratio < 0.4 {
             "DISTRIBUTION".to_string()
@@ -305,6 +355,9 @@ impl PersistenceScorer {
     /// - Multiplies base score by age-based factor (0.5 to 1.3)
     /// - Reduces confidence for very new tokens (<1h, <24h)
     /// - Boosts confidence for mature tokens (>7d, >30d)
+    ///
+    /// Holder concentration is applied after clamping, so a heavily
+    /// insider-held token is capped however rich its data is.
     fn compute_confidence(&self, token: &TokenSnapshot, lifetime_hours: f64, bot_ratio: f64, now: i64) -> String {
         let total_trades = token.buy_count_300s + token.sell_count_300s;
         let data_richness = total_trades as f64 / 50.0;
@@ -319,7 +372,8 @@ impl PersistenceScorer {
         let adjusted_confidence_score = base_confidence_score * age_multiplier;
 
         // Clamp to [0, 1] range after adjustment
-        let final_score = adjusted_confidence_score.clamp(0.0, 1.0);
+        let final_score = adjusted_confidence_score.clamp(0.0, 1.0)
+            * self.compute_holder_multiplier(token.top10_holder_pct);
 
         // Thresholds (unchanged)
         if final_score > 0.7 {
@@ -332,7 +386,7 @@ impl PersistenceScorer {
     }
 
     /// Run scoring engine and write results to database
-    pub fn run_scoring_cycle(&self) -> Result<usize, Box<dyn std::error::Error>> {
+    pub fn run_scoring_cycle(&self) -> Result<usize, SolflowError> {
         let conn = Connection::open(&self.db_path)?;
 
         // Fetch data
@@ -437,6 +491,24 @@ impl PersistenceScorer {
     }
 }
 
+#[async_trait]
+impl Enricher for PersistenceScorer {
+    fn name(&self) -> &'static str {
+        "persistence_scoring"
+    }
+
+    fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(60)
+    }
+
+    async fn enrich(&mut self, _now: i64) -> Result<(), SolflowError> {
+        log::info!("🧮 Running persistence scoring cycle...");
+        let count = self.run_scoring_cycle()?;
+        log::info!("✅ Persistence scoring: updated {} tokens", count);
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -462,6 +534,8 @@ mod tests {
             updated_at: 1000000,
             created_at: 999000,
             pair_created_at: None,
+            early_holder_retention: None,
+            top10_holder_pct: None,
         };
 
         let lifetime_hours = 1000.0 / 3600.0;
@@ -493,12 +567,49 @@ mod tests {
             updated_at: 1000,
             created_at: 900,
             pair_created_at: None,
+            early_holder_retention: None,
+            top10_holder_pct: None,
         };
 
         let pattern = scorer.classify_pattern(&accumulation_token, true);
         assert_eq!(pattern, "ACCUMULATION");
     }
 
+    #[test]
+    fn test_pattern_uses_early_holder_retention() {
+        let scorer = PersistenceScorer::new(":memory:".to_string());
+
+        let mut token = TokenSnapshot {
+            mint: "test".to_string(),
+            net_flow_60s: 2.0,
+            net_flow_300s: 6.0,
+            net_flow_900s: 8.0,
+            net_flow_3600s: 10.0,
+            net_flow_7200s: 0.0,
+            net_flow_14400s: 0.0,
+            unique_wallets_300s: 10,
+            bot_trades_300s: 0,
+            buy_count_300s: 16,
+            sell_count_300s: 4,
+            dca_buys_3600s: 0,
+            volume_300s_sol: 8.0,
+            updated_at: 1000,
+            created_at: 900,
+            pair_created_at: None,
+            early_holder_retention: None,
+            top10_holder_pct: None,
+        };
+        assert_eq!(scorer.classify_pattern(&token, false), "MOMENTUM");
+
+        // Early buyers still holding: accumulation even without DCA overlap
+        token.early_holder_retention = Some(0.85);
+        assert_eq!(scorer.classify_pattern(&token, false), "ACCUMULATION");
+
+        // Early buyers dumped: inflows are churn, not momentum
+        token.early_holder_retention = Some(0.1);
+        assert_eq!(scorer.classify_pattern(&token, false), "NOISE");
+    }
+
     #[test]
     fn test_age_multiplier_very_new_token() {
         let scorer = PersistenceScorer::new(":memory:".to_string());
@@ -581,6 +692,8 @@ mod tests {
             updated_at: 1000000,
             created_at: 900000,
             pair_created_at: Some(now - (45 * 86400)),
+            early_holder_retention: None,
+            top10_holder_pct: None,
         };
 
         let lifetime_hours = 100000.0 / 3600.0;
@@ -615,6 +728,8 @@ mod tests {
             updated_at: 1000000,
             created_at: 999000,
             pair_created_at: Some(now - 1800), // 30 min ago
+            early_holder_retention: None,
+            top10_holder_pct: None,
         };
 
         let lifetime_hours = 1000.0 / 3600.0;
@@ -625,4 +740,44 @@ mod tests {
         // With age penalty (0.5x), even high base score becomes LOW/MEDIUM
         assert!(confidence == "LOW" || confidence == "MEDIUM");
     }
+
+    #[test]
+    fn test_confidence_penalizes_insider_held_tokens() {
+        let scorer = PersistenceScorer::new(":memory:".to_string());
+        let now = 1000000;
+
+        let mut token = TokenSnapshot {
+            mint: "test".to_string(),
+            net_flow_60s: 0.0,
+            net_flow_300s: 0.0,
+            net_flow_900s: 0.0,
+            net_flow_3600s: 0.0,
+            net_flow_7200s: 0.0,
+            net_flow_14400s: 0.0,
+            unique_wallets_300s: 40,
+            bot_trades_300s: 5,
+            buy_count_300s: 40,
+            sell_count_300s: 40,
+            dca_buys_3600s: 0,
+            volume_300s_sol: 10.0,
+            updated_at: 1000000,
+            created_at: 900000,
+            pair_created_at: Some(now - (45 * 86400)),
+            early_holder_retention: None,
+            top10_holder_pct: Some(12.0),
+        };
+        let lifetime_hours = 100000.0 / 3600.0;
+        let bot_ratio = 5.0 / 80.0;
+
+        // Spread-out holders: no penalty
+        assert_eq!(scorer.compute_confidence(&token, lifetime_hours, bot_ratio, now), "HIGH");
+
+        // Top 10 wallets hold 35%: still HIGH at full data richness
+        token.top10_holder_pct = Some(35.0);
+        assert_eq!(scorer.compute_confidence(&token, lifetime_hours, bot_ratio, now), "HIGH");
+
+        // Insider-held: capped below HIGH
+        token.top10_holder_pct = Some(62.0);
+        assert_eq!(scorer.compute_confidence(&token, lifetime_hours, bot_ratio, now), "MEDIUM");
+    }
 }
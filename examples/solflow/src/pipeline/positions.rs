@@ -0,0 +1,318 @@
+//! Position tracking: live PnL and exit alerts for held tokens
+//!
+//! The user records entries in the `positions` table (dashboard Positions
+//! panel). While enabled, the runtime periodically marks every open position
+//! against the engine's latest trade price, stores unrealized PnL on the row,
+//! and emits a POSITION_EXIT signal when an exit rule trips for a held token.
+//! A rule re-alerts only after it has cleared.
+//!
+//! Environment variables:
+//! - `POSITIONS_ENABLED`: Mark open positions and alert on exits (default: false)
+//! - `POSITION_REFRESH_SECS`: Marking interval (default: 10)
+//! - `POSITION_STOP_LOSS_PCT`: Exit when PnL falls to -N% (default: 30)
+//! - `POSITION_TAKE_PROFIT_PCT`: Exit when PnL reaches +N% (default: 100)
+//! - `POSITION_EXIT_NET_FLOW_SOL`: Exit when 300s net flow is below -N SOL (default: 5.0)
+
+use super::signals::{PositionExitDetails, SignalDetails, SignalType, TokenSignal};
+use rusqlite::{Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Why a held token should be exited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    StopLoss,
+    SellPressure,
+    TakeProfit,
+}
+
+impl ExitReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExitReason::StopLoss => "STOP_LOSS",
+            ExitReason::SellPressure => "SELL_PRESSURE",
+            ExitReason::TakeProfit => "TAKE_PROFIT",
+        }
+    }
+
+    /// Severity of the POSITION_EXIT signal
+    pub fn severity(&self) -> i32 {
+        match self {
+            ExitReason::StopLoss => 5,
+            ExitReason::SellPressure => 4,
+            ExitReason::TakeProfit => 3,
+        }
+    }
+}
+
+/// Exit thresholds applied to every open position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExitRules {
+    pub stop_loss_pct: f64,
+    pub take_profit_pct: f64,
+    /// Sell pressure: 300s net flow at or below minus this
+    pub sell_pressure_net_flow_sol: f64,
+}
+
+impl Default for ExitRules {
+    fn default() -> Self {
+        Self {
+            stop_loss_pct: 30.0,
+            take_profit_pct: 100.0,
+            sell_pressure_net_flow_sol: 5.0,
+        }
+    }
+}
+
+impl ExitRules {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let read = |var: &str, default: f64| {
+            std::env::var(var)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(default)
+        };
+        Self {
+            stop_loss_pct: read("POSITION_STOP_LOSS_PCT", defaults.stop_loss_pct),
+            take_profit_pct: read("POSITION_TAKE_PROFIT_PCT", defaults.take_profit_pct),
+            sell_pressure_net_flow_sol: read(
+                "POSITION_EXIT_NET_FLOW_SOL",
+                defaults.sell_pressure_net_flow_sol,
+            ),
+        }
+    }
+
+    /// First tripped rule, most urgent first
+    pub fn evaluate(&self, pnl_pct: f64, net_flow_300s_sol: Option<f64>) -> Option<ExitReason> {
+        if pnl_pct <= -self.stop_loss_pct {
+            Some(ExitReason::StopLoss)
+        } else if net_flow_300s_sol.is_some_and(|flow| flow <= -self.sell_pressure_net_flow_sol) {
+            Some(ExitReason::SellPressure)
+        } else if pnl_pct >= self.take_profit_pct {
+            Some(ExitReason::TakeProfit)
+        } else {
+            None
+        }
+    }
+}
+
+/// An open position row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub id: i64,
+    pub mint: String,
+    pub size_tokens: f64,
+    pub entry_price_sol: f64,
+    /// Exit rule active at the last mark (None = no rule tripped)
+    pub exit_signal: Option<String>,
+}
+
+/// Position valued at a price
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionMark {
+    pub price_sol: f64,
+    pub unrealized_pnl_sol: f64,
+    pub pnl_pct: f64,
+}
+
+impl Position {
+    pub fn mark(&self, price_sol: f64) -> PositionMark {
+        let unrealized_pnl_sol = (price_sol - self.entry_price_sol) * self.size_tokens;
+        let pnl_pct = if self.entry_price_sol > 0.0 {
+            (price_sol / self.entry_price_sol - 1.0) * 100.0
+        } else {
+            0.0
+        };
+        PositionMark {
+            price_sol,
+            unrealized_pnl_sol,
+            pnl_pct,
+        }
+    }
+}
+
+/// Marks open positions and raises exit signals
+pub struct PositionTracker {
+    conn: Mutex<Connection>,
+    rules: ExitRules,
+}
+
+impl PositionTracker {
+    pub fn new(db_path: &str, rules: ExitRules) -> Result<Self, rusqlite::Error> {
+        let conn = Connection::open(db_path)?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            rules,
+        })
+    }
+
+    pub fn rules(&self) -> ExitRules {
+        self.rules
+    }
+
+    pub fn open_positions(&self) -> Result<Vec<Position>, rusqlite::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare_cached(
+            "SELECT id, mint, size_tokens, entry_price_sol, exit_signal
+             FROM positions
+             WHERE closed_at IS NULL",
+        )?;
+        let positions = stmt
+            .query_map([], |row| {
+                Ok(Position {
+                    id: row.get(0)?,
+                    mint: row.get(1)?,
+                    size_tokens: row.get(2)?,
+                    entry_price_sol: row.get(3)?,
+                    exit_signal: row.get(4)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(positions)
+    }
+
+    /// Mark positions at the given prices and store PnL
+    ///
+    /// Positions without a price (no recent trades) are left untouched.
+    /// Returns POSITION_EXIT signals for rules that newly tripped; the caller
+    /// writes them through the usual blocklist-checked signal path.
+    pub fn update(
+        &self,
+        positions: &[Position],
+        prices: &HashMap<String, f64>,
+        now: i64,
+    ) -> Result<Vec<TokenSignal>, rusqlite::Error> {
+        let conn = self.conn.lock().unwrap();
+        let mut signals = Vec::new();
+
+        for position in positions {
+            let Some(&price_sol) = prices.get(&position.mint) else {
+                continue;
+            };
+            let mark = position.mark(price_sol);
+
+            let net_flow_300s: Option<f64> = conn
+                .query_row(
+                    "SELECT net_flow_300s_sol FROM token_aggregates WHERE mint = ?",
+                    [&position.mint],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .flatten();
+
+            let reason = self.rules.evaluate(mark.pnl_pct, net_flow_300s);
+            let newly_tripped =
+                reason.is_some_and(|r| position.exit_signal.as_deref() != Some(r.as_str()));
+
+            conn.execute(
+                "UPDATE positions SET
+                    current_price_sol = ?1,
+                    unrealized_pnl_sol = ?2,
+                    pnl_pct = ?3,
+                    exit_signal = ?4,
+                    exit_signal_at = CASE WHEN ?5 THEN ?6 ELSE exit_signal_at END,
+                    updated_at = ?6
+                 WHERE id = ?7",
+                rusqlite::params![
+                    mark.price_sol,
+                    mark.unrealized_pnl_sol,
+                    mark.pnl_pct,
+                    reason.map(|r| r.as_str()),
+                    newly_tripped,
+                    now,
+                    position.id,
+                ],
+            )?;
+
+            if let (true, Some(reason)) = (newly_tripped, reason) {
+                signals.push(
+                    TokenSignal::new(position.mint.clone(), SignalType::PositionExit, 300, now)
+                        .with_severity(reason.severity())
+                        .with_score(mark.pnl_pct)
+                        .with_typed_details(SignalDetails::PositionExit(PositionExitDetails {
+                            position_id: position.id,
+                            reason: reason.as_str().to_string(),
+                            entry_price_sol: position.entry_price_sol,
+                            price_sol: mark.price_sol,
+                            pnl_pct: mark.pnl_pct,
+                            net_flow_300s,
+                        })),
+                );
+            }
+        }
+
+        Ok(signals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, PositionTracker) {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("positions.db");
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute_batch(include_str!("../../sql/09_positions.sql")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE token_aggregates (mint TEXT PRIMARY KEY, net_flow_300s_sol REAL);",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO positions (mint, size_tokens, entry_price_sol, opened_at)
+             VALUES ('held_mint', 1000.0, 0.001, 0)",
+            [],
+        )
+        .unwrap();
+        let tracker = PositionTracker::new(db_path.to_str().unwrap(), ExitRules::default()).unwrap();
+        (dir, tracker)
+    }
+
+    #[test]
+    fn test_exit_rules() {
+        let rules = ExitRules::default();
+        assert_eq!(rules.evaluate(-35.0, Some(10.0)), Some(ExitReason::StopLoss));
+        assert_eq!(rules.evaluate(10.0, Some(-6.0)), Some(ExitReason::SellPressure));
+        assert_eq!(rules.evaluate(150.0, None), Some(ExitReason::TakeProfit));
+        assert_eq!(rules.evaluate(10.0, Some(-1.0)), None);
+    }
+
+    #[test]
+    fn test_update_marks_and_alerts_once() {
+        let (_dir, tracker) = setup();
+        let prices = HashMap::from([("held_mint".to_string(), 0.0005)]);
+
+        // -50%: stop loss trips once
+        let positions = tracker.open_positions().unwrap();
+        let signals = tracker.update(&positions, &prices, 100).unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type, SignalType::PositionExit);
+        assert_eq!(signals[0].severity, 5);
+
+        let positions = tracker.open_positions().unwrap();
+        assert_eq!(positions[0].exit_signal.as_deref(), Some("STOP_LOSS"));
+        assert!(tracker.update(&positions, &prices, 110).unwrap().is_empty());
+
+        let (pnl_sol, pnl_pct): (f64, f64) = tracker
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT unrealized_pnl_sol, pnl_pct FROM positions WHERE mint = 'held_mint'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!((pnl_sol + 0.5).abs() < 1e-9);
+        assert!((pnl_pct + 50.0).abs() < 1e-9);
+
+        // Recovery clears the rule so a later drop alerts again
+        let recovered = HashMap::from([("held_mint".to_string(), 0.0011)]);
+        assert!(tracker.update(&positions, &recovered, 120).unwrap().is_empty());
+        let positions = tracker.open_positions().unwrap();
+        assert_eq!(positions[0].exit_signal, None);
+        assert_eq!(tracker.update(&positions, &prices, 130).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,202 @@
+//! RPC reconciliation of severity-5 signals before they are notified
+//!
+//! A provider glitch (replayed or forked-off transactions in the stream) can
+//! produce volume that never landed, and the loudest signals are the ones
+//! that hurt most when phantom. The engine attaches the most recent
+//! signatures in the signal's window to severity-5 signals
+//! (`token_signals.sample_signatures`); with `RECONCILE_RPC_URL` set, the
+//! notifier looks a few of them up with `getTransaction` at the configured
+//! commitment and drops the signal when most are missing or failed.
+//!
+//! Reconciliation fails open: if the RPC node can't be reached for any of
+//! the sampled signatures, the signal is sent as usual.
+//!
+//! Environment variables:
+//! - `RECONCILE_RPC_URL`: Solana JSON-RPC endpoint, or a `file:`/`keyring:` reference (unset = disabled)
+//! - `RECONCILE_SAMPLE_SIZE`: Signatures checked per signal (default: 3, max: 8)
+//! - `RECONCILE_COMMITMENT`: `confirmed` or `finalized` (default: confirmed).
+//!   Finalized trails the stream by ~30 slots, so very recent samples may
+//!   not be found yet.
+
+use crate::error::{codes, SolflowError};
+use crate::streamer_core::secrets::secret_from_env;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Signals at or above this severity carry sample signatures and are reconciled
+pub const RECONCILED_SEVERITY: i32 = 5;
+
+/// Signatures the engine attaches to a reconciled signal
+pub const MAX_SAMPLE_SIGNATURES: usize = 8;
+
+/// RPC endpoint and how much of each signal to check
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconcileConfig {
+    pub rpc_url: String,
+    pub sample_size: usize,
+    /// `confirmed` or `finalized`
+    pub commitment: String,
+}
+
+impl ReconcileConfig {
+    /// Load from the environment (see module docs); None when no RPC URL is set
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(rpc_url) = secret_from_env("RECONCILE_RPC_URL")? else {
+            return Ok(None);
+        };
+        let sample_size = std::env::var("RECONCILE_SAMPLE_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3usize)
+            .clamp(1, MAX_SAMPLE_SIGNATURES);
+        let commitment = std::env::var("RECONCILE_COMMITMENT").unwrap_or_else(|_| "confirmed".to_string());
+        if commitment != "confirmed" && commitment != "finalized" {
+            return Err(format!(
+                "RECONCILE_COMMITMENT must be confirmed or finalized, got '{}'",
+                commitment
+            ));
+        }
+        Ok(Some(Self {
+            rpc_url,
+            sample_size,
+            commitment,
+        }))
+    }
+}
+
+/// What the RPC node reports for one signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Landed and succeeded at the requested commitment
+    Landed,
+    /// Landed but the transaction failed (no balance changes happened)
+    Failed,
+    /// Unknown to the node at the requested commitment
+    Missing,
+}
+
+/// Outcome of reconciling one signal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Verified,
+    /// Most sampled signatures did not land
+    Phantom { bad: usize, checked: usize },
+    /// No signature could be checked (RPC unavailable); send anyway
+    Unchecked,
+}
+
+/// A signal is phantom when more than half of its checked signatures are
+/// missing or failed
+pub fn verdict(statuses: &[TxStatus]) -> Verdict {
+    if statuses.is_empty() {
+        return Verdict::Unchecked;
+    }
+    let bad = statuses.iter().filter(|s| **s != TxStatus::Landed).count();
+    if bad * 2 > statuses.len() {
+        Verdict::Phantom {
+            bad,
+            checked: statuses.len(),
+        }
+    } else {
+        Verdict::Verified
+    }
+}
+
+/// Interpret a `getTransaction` JSON-RPC response
+pub fn parse_status(response: &Value) -> Result<TxStatus, String> {
+    if let Some(error) = response.get("error") {
+        return Err(error["message"].as_str().unwrap_or("RPC error").to_string());
+    }
+    match response.get("result") {
+        None => Err("response has no result".to_string()),
+        Some(Value::Null) => Ok(TxStatus::Missing),
+        Some(result) if !result["meta"]["err"].is_null() => Ok(TxStatus::Failed),
+        Some(_) => Ok(TxStatus::Landed),
+    }
+}
+
+/// Looks up sampled signatures over JSON-RPC
+pub struct Reconciler {
+    config: ReconcileConfig,
+    client: reqwest::Client,
+}
+
+impl Reconciler {
+    pub fn new(config: ReconcileConfig) -> Result<Self, SolflowError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| SolflowError::config(codes::CONFIG_INVALID, e))?;
+        Ok(Self { config, client })
+    }
+
+    pub fn config(&self) -> &ReconcileConfig {
+        &self.config
+    }
+
+    /// Check the first `sample_size` signatures (newest first)
+    ///
+    /// Signatures the node couldn't be asked about are left out of the verdict.
+    pub async fn reconcile(&self, signatures: &[String]) -> Verdict {
+        let mut statuses = Vec::new();
+        for signature in signatures.iter().take(self.config.sample_size) {
+            match self.status(signature).await {
+                Ok(status) => statuses.push(status),
+                Err(e) => log::warn!("⚠️  Reconcile: getTransaction {} failed: {}", signature, e),
+            }
+        }
+        verdict(&statuses)
+    }
+
+    async fn status(&self, signature: &str) -> Result<TxStatus, String> {
+        // RPC URLs usually embed an API key: keep them out of error messages
+        let response: Value = self
+            .client
+            .post(&self.config.rpc_url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "getTransaction",
+                "params": [signature, {
+                    "commitment": self.config.commitment,
+                    "encoding": "json",
+                    "maxSupportedTransactionVersion": 0,
+                }],
+            }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.without_url().to_string())?
+            .json()
+            .await
+            .map_err(|e| e.without_url().to_string())?;
+        parse_status(&response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phantom_when_most_samples_did_not_land() {
+        let landed = json!({"jsonrpc": "2.0", "id": 1, "result": {"slot": 1, "meta": {"err": null}}});
+        let failed = json!({"jsonrpc": "2.0", "id": 1, "result": {"slot": 1, "meta": {"err": {"InstructionError": [0, "Custom"]}}}});
+        let missing = json!({"jsonrpc": "2.0", "id": 1, "result": null});
+        let error = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32005, "message": "Node is behind"}});
+        assert_eq!(parse_status(&landed), Ok(TxStatus::Landed));
+        assert_eq!(parse_status(&failed), Ok(TxStatus::Failed));
+        assert_eq!(parse_status(&missing), Ok(TxStatus::Missing));
+        assert_eq!(parse_status(&error), Err("Node is behind".to_string()));
+
+        use TxStatus::*;
+        assert_eq!(verdict(&[Landed, Missing, Landed]), Verdict::Verified);
+        // Half bad is not enough to drop a signal
+        assert_eq!(verdict(&[Landed, Failed]), Verdict::Verified);
+        assert_eq!(
+            verdict(&[Missing, Failed, Landed]),
+            Verdict::Phantom { bad: 2, checked: 3 }
+        );
+        assert_eq!(verdict(&[]), Verdict::Unchecked);
+    }
+}
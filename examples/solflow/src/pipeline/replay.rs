@@ -0,0 +1,415 @@
+//! Trade replay from streamer JSONL captures
+//!
+//! Feeds `streams/*/events.jsonl` files (written by the streamers when
+//! `ENABLE_JSONL=true`) back into the pipeline channel instead of live
+//! streamers, so a missed signal can be debugged against the exact trades.
+//!
+//! The engine clock follows replayed trade time (see `ReplayClock`), so
+//! rolling windows, eviction and signals behave as they did live regardless
+//! of playback speed. Point `SOLFLOW_DB_PATH` at a scratch database.
+//!
+//! Configuration (CLI flags override environment variables):
+//! - `--replay <path>` / `REPLAY_PATH`: JSONL capture to replay (enables replay mode)
+//! - `--speed <1x|10x|max>` / `REPLAY_SPEED`: Playback speed (default: 1x)
+//! - `--from <time>` / `REPLAY_FROM`: Skip trades before this time (unix seconds or RFC3339)
+//! - `--to <time>` / `REPLAY_TO`: Skip trades after this time (unix seconds or RFC3339)
+
+use super::types::{Confirmation, TradeDirection, TradeEvent};
+use crate::error::{codes, SolflowError};
+use crate::streamer_core::output_writer::TradeEvent as CapturedTradeEvent;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncBufReadExt;
+use tokio::sync::mpsc;
+
+/// Longest wall-clock wait between two trades, in trade seconds
+///
+/// Quiet stretches in a capture are skipped rather than sat through; the
+/// engine clock still jumps by the full gap.
+const MAX_REPLAY_GAP_SECS: i64 = 60;
+
+/// Playback speed relative to the original trade timing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Wait `gap / multiplier` between trades (1.0 = real time)
+    Multiplier(f64),
+    /// No waiting: replay as fast as the channel accepts
+    Max,
+}
+
+impl Default for ReplaySpeed {
+    fn default() -> Self {
+        ReplaySpeed::Multiplier(1.0)
+    }
+}
+
+impl ReplaySpeed {
+    /// Parse `max`, `10x` or `10` (multiplier must be positive)
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        if value == "max" {
+            return Some(ReplaySpeed::Max);
+        }
+        value
+            .strip_suffix('x')
+            .unwrap_or(&value)
+            .parse::<f64>()
+            .ok()
+            .filter(|m| *m > 0.0 && m.is_finite())
+            .map(ReplaySpeed::Multiplier)
+    }
+
+    /// Wall-clock wait before replaying a trade `gap_secs` after the previous one
+    pub fn delay(&self, gap_secs: i64) -> Option<Duration> {
+        match self {
+            ReplaySpeed::Max => None,
+            ReplaySpeed::Multiplier(m) if gap_secs > 0 => Some(Duration::from_secs_f64(
+                gap_secs.min(MAX_REPLAY_GAP_SECS) as f64 / m,
+            )),
+            ReplaySpeed::Multiplier(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ReplaySpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplaySpeed::Multiplier(m) => write!(f, "{}x", m),
+            ReplaySpeed::Max => write!(f, "max"),
+        }
+    }
+}
+
+/// Parse a seek bound: unix seconds or RFC3339 (`2025-01-15T14:00:00Z`)
+pub fn parse_replay_time(value: &str) -> Option<i64> {
+    let value = value.trim();
+    value.parse::<i64>().ok().or_else(|| {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|dt| dt.timestamp())
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayOptions {
+    pub path: String,
+    pub speed: ReplaySpeed,
+    /// Inclusive lower bound on trade timestamp
+    pub from: Option<i64>,
+    /// Inclusive upper bound on trade timestamp
+    pub to: Option<i64>,
+}
+
+impl ReplayOptions {
+    /// Read replay options from CLI flags, falling back to environment variables
+    ///
+    /// Returns Ok(None) when no capture path is configured (live mode).
+    pub fn from_env_and_args() -> Result<Option<Self>, String> {
+        let args: Vec<String> = std::env::args().collect();
+        let lookup = |flag: &str, var: &str| {
+            args.windows(2)
+                .find(|w| w[0] == flag)
+                .map(|w| w[1].clone())
+                .or_else(|| std::env::var(var).ok())
+        };
+
+        let Some(path) = lookup("--replay", "REPLAY_PATH") else {
+            return Ok(None);
+        };
+
+        let speed = match lookup("--speed", "REPLAY_SPEED") {
+            Some(value) => ReplaySpeed::parse(&value)
+                .ok_or_else(|| format!("Invalid replay speed '{}' (expected 1x, 10x or max)", value))?,
+            None => ReplaySpeed::default(),
+        };
+
+        let parse_bound = |flag: &str, var: &str| -> Result<Option<i64>, String> {
+            lookup(flag, var)
+                .map(|value| {
+                    parse_replay_time(&value).ok_or_else(|| {
+                        format!("Invalid {} '{}' (expected unix seconds or RFC3339)", flag, value)
+                    })
+                })
+                .transpose()
+        };
+        let from = parse_bound("--from", "REPLAY_FROM")?;
+        let to = parse_bound("--to", "REPLAY_TO")?;
+
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(format!("--from ({}) is after --to ({})", from, to));
+            }
+        }
+
+        Ok(Some(Self { path, speed, from, to }))
+    }
+
+    /// True when a trade timestamp falls inside the seek range
+    pub fn in_range(&self, timestamp: i64) -> bool {
+        self.from.map_or(true, |from| timestamp >= from) && self.to.map_or(true, |to| timestamp <= to)
+    }
+}
+
+/// Engine clock driven by replayed trade time
+///
+/// Pass `now_fn()` to `PipelineEngine::new_with_timestamp_fn` so windows
+/// evict relative to the capture rather than the wall clock.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayClock(Arc<AtomicI64>);
+
+impl ReplayClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn now(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Move the clock forward (never backwards: captures interleave streamers)
+    pub fn advance_to(&self, timestamp: i64) {
+        self.0.fetch_max(timestamp, Ordering::Relaxed);
+    }
+
+    pub fn now_fn(&self) -> Box<dyn Fn() -> i64 + Send + Sync> {
+        let clock = self.clone();
+        Box::new(move || clock.now())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayStats {
+    /// Trades parsed from the capture
+    pub read: u64,
+    /// Trades sent to the pipeline
+    pub replayed: u64,
+    /// Trades outside the --from/--to range
+    pub out_of_range: u64,
+    /// Lines that were not valid trade events
+    pub malformed: u64,
+}
+
+/// Trade index of captured trades
+///
+/// Streamers write a transaction's trades consecutively, so a trade's index
+/// is the number of trades directly before it with the same signature.
+#[derive(Default)]
+struct TradeIndex {
+    signature: String,
+    next: u16,
+}
+
+impl TradeIndex {
+    fn next(&mut self, signature: &str) -> u16 {
+        if self.signature != signature {
+            self.signature = signature.to_string();
+            self.next = 0;
+        }
+        let index = self.next;
+        self.next = self.next.saturating_add(1);
+        index
+    }
+}
+
+/// Convert a captured streamer trade to a pipeline trade
+///
+/// Captures carry no slot, base fee or DCA order context; those stay empty.
+/// Replayed history counts as finalized.
+fn to_pipeline_event(event: CapturedTradeEvent, trade_index: u16) -> TradeEvent {
+    TradeEvent {
+        timestamp: event.timestamp,
+        mint: event.mint,
+        direction: match event.action.as_str() {
+            "BUY" => TradeDirection::Buy,
+            "SELL" => TradeDirection::Sell,
+            _ => TradeDirection::Unknown,
+        },
+        sol_amount: event.sol_amount,
+        token_amount: event.token_amount,
+        token_decimals: event.token_decimals,
+        user_account: event.user_account.unwrap_or_default(),
+        source_program: event.program_name,
+        signature: event.signature,
+        trade_index,
+        slot: 0,
+        fee_lamports: 0,
+        priority_fee_lamports: event.priority_fee_lamports,
+        jito_tip_lamports: event.jito_tip_lamports,
+        confirmation: Confirmation::Finalized,
+        dca_order: None,
+    }
+}
+
+/// Replay a JSONL capture into the pipeline channel
+///
+/// Stops early if the channel closes. The clock is advanced before each
+/// trade is sent so the engine sees it as current.
+pub async fn run_replay(
+    options: &ReplayOptions,
+    tx: mpsc::Sender<TradeEvent>,
+    clock: ReplayClock,
+) -> Result<ReplayStats, SolflowError> {
+    let read_error = |e: std::io::Error| {
+        SolflowError::datasource(codes::DATASOURCE_STREAM, format!("{}: {}", options.path, e))
+    };
+    let file = tokio::fs::File::open(&options.path).await.map_err(read_error)?;
+    let mut lines = tokio::io::BufReader::new(file).lines();
+    let mut stats = ReplayStats::default();
+    let mut last_ts: Option<i64> = None;
+    let mut indexes = TradeIndex::default();
+
+    while let Some(line) = lines.next_line().await.map_err(read_error)? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: CapturedTradeEvent = match serde_json::from_str(&line) {
+            Ok(event) => event,
+            Err(_) => {
+                stats.malformed += 1;
+                continue;
+            }
+        };
+        stats.read += 1;
+        let trade_index = indexes.next(&event.signature);
+
+        if !options.in_range(event.timestamp) {
+            stats.out_of_range += 1;
+            continue;
+        }
+
+        if let Some(delay) = last_ts.and_then(|prev| options.speed.delay(event.timestamp - prev)) {
+            tokio::time::sleep(delay).await;
+        }
+        last_ts = Some(last_ts.map_or(event.timestamp, |prev| prev.max(event.timestamp)));
+
+        clock.advance_to(event.timestamp);
+        if tx.send(to_pipeline_event(event, trade_index)).await.is_err() {
+            log::warn!("⚠️  Pipeline channel closed - stopping replay");
+            break;
+        }
+        stats.replayed += 1;
+
+        if stats.replayed % 10_000 == 0 {
+            log::info!("⏩ Replayed {} trades (clock: {})", stats.replayed, clock.now());
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Load a capture's in-range trades into memory, ordered by timestamp
+///
+/// Used by threshold tuning (see `tuning`), which replays the same window
+/// many times. Playback speed is ignored.
+pub async fn load_replay_window(
+    options: &ReplayOptions,
+) -> Result<(Vec<TradeEvent>, ReplayStats), SolflowError> {
+    let read_error = |e: std::io::Error| {
+        SolflowError::datasource(codes::DATASOURCE_STREAM, format!("{}: {}", options.path, e))
+    };
+    let file = tokio::fs::File::open(&options.path).await.map_err(read_error)?;
+    let mut lines = tokio::io::BufReader::new(file).lines();
+    let mut stats = ReplayStats::default();
+    let mut trades = Vec::new();
+    let mut indexes = TradeIndex::default();
+
+    while let Some(line) = lines.next_line().await.map_err(read_error)? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(event) = serde_json::from_str::<CapturedTradeEvent>(&line) else {
+            stats.malformed += 1;
+            continue;
+        };
+        stats.read += 1;
+        let trade_index = indexes.next(&event.signature);
+        if !options.in_range(event.timestamp) {
+            stats.out_of_range += 1;
+            continue;
+        }
+        trades.push(to_pipeline_event(event, trade_index));
+    }
+
+    // Captures interleave streamers; keep file order within a second
+    trades.sort_by_key(|trade| trade.timestamp);
+    stats.replayed = trades.len() as u64;
+    Ok((trades, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn captured(timestamp: i64, action: &str) -> String {
+        serde_json::to_string(&CapturedTradeEvent {
+            timestamp,
+            signature: format!("sig_{}", timestamp),
+            program_id: "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA".to_string(),
+            program_name: "PumpSwap".to_string(),
+            action: action.to_string(),
+            mint: "replay_mint".to_string(),
+            sol_amount: 1.0,
+            token_amount: 1000.0,
+            token_decimals: 6,
+            user_account: Some("wallet".to_string()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!(ReplaySpeed::parse("1x"), Some(ReplaySpeed::Multiplier(1.0)));
+        assert_eq!(ReplaySpeed::parse("10X"), Some(ReplaySpeed::Multiplier(10.0)));
+        assert_eq!(ReplaySpeed::parse("2.5"), Some(ReplaySpeed::Multiplier(2.5)));
+        assert_eq!(ReplaySpeed::parse("max"), Some(ReplaySpeed::Max));
+        assert_eq!(ReplaySpeed::parse("0x"), None);
+        assert_eq!(ReplaySpeed::parse("fast"), None);
+
+        assert_eq!(ReplaySpeed::Multiplier(10.0).delay(5), Some(Duration::from_millis(500)));
+        assert_eq!(ReplaySpeed::Multiplier(1.0).delay(3600), Some(Duration::from_secs(60)));
+        assert_eq!(ReplaySpeed::Multiplier(1.0).delay(0), None);
+        assert_eq!(ReplaySpeed::Max.delay(5), None);
+    }
+
+    #[test]
+    fn test_parse_replay_time() {
+        assert_eq!(parse_replay_time("1700000000"), Some(1_700_000_000));
+        assert_eq!(parse_replay_time("2023-11-14T22:13:20Z"), Some(1_700_000_000));
+        assert_eq!(parse_replay_time("yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn test_replay_seeks_and_drives_clock() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "{}", captured(100, "BUY")).unwrap();
+        writeln!(file, "{}", captured(200, "SELL")).unwrap();
+        writeln!(file, "not json").unwrap();
+        writeln!(file, "{}", captured(300, "BUY")).unwrap();
+        writeln!(file, "{}", captured(400, "BUY")).unwrap();
+
+        let options = ReplayOptions {
+            path: file.path().to_string_lossy().to_string(),
+            speed: ReplaySpeed::Max,
+            from: Some(200),
+            to: Some(300),
+        };
+        let (tx, mut rx) = mpsc::channel(16);
+        let clock = ReplayClock::new();
+
+        let stats = run_replay(&options, tx, clock.clone()).await.unwrap();
+        assert_eq!(
+            stats,
+            ReplayStats { read: 4, replayed: 2, out_of_range: 2, malformed: 1 }
+        );
+        assert_eq!(clock.now(), 300);
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(first.timestamp, 200);
+        assert_eq!(first.direction, TradeDirection::Sell);
+        assert_eq!(rx.recv().await.unwrap().timestamp, 300);
+        assert!(rx.recv().await.is_none());
+    }
+}
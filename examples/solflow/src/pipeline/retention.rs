@@ -0,0 +1,297 @@
+//! Retention: per-table TTLs, periodic deletes and incremental vacuum
+//!
+//! Without it `token_signals`, the DCA buckets and rows for tokens that
+//! stopped trading grow forever. Every `RETENTION_INTERVAL_SECS` the
+//! `RetentionManager` deletes expired rows from each database (the primary
+//! plus every `DB_ROUTES` file; tables a database doesn't hold are skipped),
+//! in bounded batches so writers are never locked out for long, then hands
+//! freed pages back to the filesystem with `PRAGMA incremental_vacuum`.
+//!
+//! | table                  | age column                                | TTL (env)                                       |
+//! |------------------------|-------------------------------------------|-------------------------------------------------|
+//! | `token_signals`        | `created_at`                              | `RETENTION_SIGNALS_SECS` (7d)                   |
+//! | `dca_activity_buckets` | `bucket_timestamp`                        | `DCA_BUCKET_RETENTION_SECS` (2h)                |
+//! | `dca_activity_rollups` | `bucket_timestamp`                        | `DCA_ROLLUP_RETENTION_SECS` (24h)               |
+//! | `token_aggregates`     | `last_trade_timestamp` (else `updated_at`) | `RETENTION_STALE_AGGREGATES_SECS` (24h inactive) |
+//!
+//! A TTL of 0 keeps the table. Incremental vacuum needs `auto_vacuum =
+//! INCREMENTAL`, which schema migrations set on new databases; older files
+//! need one manual `VACUUM` after switching the pragma, until then the
+//! vacuum step is skipped.
+//!
+//! Environment variables:
+//! - `RETENTION_INTERVAL_SECS`: Time between cycles (default: 300)
+//! - `RETENTION_SIGNALS_SECS`: token_signals TTL (default: 604800, 0 = keep)
+//! - `RETENTION_STALE_AGGREGATES_SECS`: token_aggregates TTL since last trade (default: 86400, 0 = keep)
+//! - `RETENTION_BATCH_ROWS`: Rows deleted per statement (default: 5000)
+//! - `RETENTION_VACUUM_PAGES`: Pages reclaimed per database per cycle (default: 1000, 0 = off)
+
+use super::backup::BackupSource;
+use super::db::DcaBucketConfig;
+use super::lease::InstanceLease;
+use crate::error::SolflowError;
+use crate::streamer_core::metrics;
+use rusqlite::Connection;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `auto_vacuum` value for INCREMENTAL
+const AUTO_VACUUM_INCREMENTAL: i64 = 2;
+
+/// Retention configuration (RETENTION_* and DCA_*_RETENTION_SECS env vars)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionConfig {
+    pub interval_secs: u64,
+    pub signals_secs: i64,
+    pub dca_buckets: DcaBucketConfig,
+    pub stale_aggregates_secs: i64,
+    pub batch_rows: usize,
+    pub vacuum_pages: u32,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            signals_secs: 7 * 86_400,
+            dca_buckets: DcaBucketConfig::default(),
+            stale_aggregates_secs: 86_400,
+            batch_rows: 5000,
+            vacuum_pages: 1000,
+        }
+    }
+}
+
+impl RetentionConfig {
+    /// Load from env, falling back to the defaults
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        fn var<T: std::str::FromStr>(name: &str, default: T) -> T {
+            env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        }
+        Self {
+            interval_secs: var("RETENTION_INTERVAL_SECS", defaults.interval_secs).max(10),
+            signals_secs: var("RETENTION_SIGNALS_SECS", defaults.signals_secs),
+            dca_buckets: DcaBucketConfig::from_env(),
+            stale_aggregates_secs: var("RETENTION_STALE_AGGREGATES_SECS", defaults.stale_aggregates_secs),
+            batch_rows: var("RETENTION_BATCH_ROWS", defaults.batch_rows).max(1),
+            vacuum_pages: var("RETENTION_VACUUM_PAGES", defaults.vacuum_pages),
+        }
+    }
+
+    /// (table, age expression, TTL) for every table with retention enabled
+    fn rules(&self) -> Vec<(&'static str, &'static str, i64)> {
+        [
+            ("token_signals", "created_at", self.signals_secs),
+            ("dca_activity_buckets", "bucket_timestamp", self.dca_buckets.retention_secs),
+            ("dca_activity_rollups", "bucket_timestamp", self.dca_buckets.rollup_retention_secs),
+            (
+                "token_aggregates",
+                "COALESCE(last_trade_timestamp, updated_at)",
+                self.stale_aggregates_secs,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, _, ttl)| *ttl > 0)
+        .collect()
+    }
+}
+
+/// What one cycle removed from one database
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetentionReport {
+    pub database: String,
+    /// (table, rows deleted), tables with deletions only
+    pub deleted: Vec<(&'static str, usize)>,
+    pub pages_reclaimed: i64,
+}
+
+impl RetentionReport {
+    pub fn rows_deleted(&self) -> usize {
+        self.deleted.iter().map(|(_, rows)| rows).sum()
+    }
+}
+
+/// Apply retention to every database on a schedule
+pub struct RetentionManager {
+    config: RetentionConfig,
+    sources: Vec<BackupSource>,
+}
+
+impl RetentionManager {
+    /// `sources` are the databases to clean (see `backup::backup_sources`)
+    pub fn new(config: RetentionConfig, sources: Vec<BackupSource>) -> Self {
+        Self { config, sources }
+    }
+
+    pub fn config(&self) -> &RetentionConfig {
+        &self.config
+    }
+
+    /// One retention pass over every database
+    pub fn run_cycle(&self, now: i64) -> Result<Vec<RetentionReport>, SolflowError> {
+        self.sources
+            .iter()
+            .map(|source| {
+                let conn = Connection::open(&source.db_path)?;
+                conn.busy_timeout(Duration::from_secs(5))?;
+                let mut report = apply_retention(&conn, &self.config, now)?;
+                report.database = source.name.clone();
+                Ok(report)
+            })
+            .collect()
+    }
+
+    /// Run cycles forever on a blocking thread per cycle
+    ///
+    /// `now` supplies the clock (engine time, so replays age rows by trade
+    /// time); standby instances skip cycles while `lease` is held elsewhere.
+    pub fn spawn(
+        self,
+        lease: Option<Arc<InstanceLease>>,
+        now: impl Fn() -> i64 + Send + 'static,
+    ) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::new(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(manager.config.interval_secs));
+            loop {
+                interval.tick().await;
+                if lease.as_ref().is_some_and(|l| !l.is_held()) {
+                    continue;
+                }
+
+                let cycle_now = now();
+                let cycle_manager = manager.clone();
+                let result =
+                    tokio::task::spawn_blocking(move || cycle_manager.run_cycle(cycle_now).map_err(|e| e.to_string()))
+                        .await;
+                match result {
+                    Ok(Ok(reports)) => {
+                        for report in reports {
+                            let rows = report.rows_deleted();
+                            metrics::increment_counter(metrics::RETENTION_ROWS_DELETED, rows as u64).await;
+                            metrics::increment_counter(
+                                metrics::RETENTION_PAGES_RECLAIMED,
+                                report.pages_reclaimed.max(0) as u64,
+                            )
+                            .await;
+                            if rows > 0 || report.pages_reclaimed > 0 {
+                                log::info!(
+                                    "🧹 Retention ({}): {:?}, {} pages reclaimed",
+                                    report.database,
+                                    report.deleted,
+                                    report.pages_reclaimed
+                                );
+                            }
+                        }
+                    }
+                    Ok(Err(e)) => log::error!("❌ Retention cycle failed: {}", e),
+                    Err(e) => log::error!("❌ Retention task panicked: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// Delete expired rows from one database, then vacuum freed pages
+pub fn apply_retention(
+    conn: &Connection,
+    config: &RetentionConfig,
+    now: i64,
+) -> Result<RetentionReport, SolflowError> {
+    let mut report = RetentionReport::default();
+
+    for (table, age, ttl) in config.rules() {
+        if !table_exists(conn, table)? {
+            continue;
+        }
+        let sql = format!(
+            "DELETE FROM {table} WHERE rowid IN (SELECT rowid FROM {table} WHERE {age} < ? LIMIT ?)"
+        );
+        let mut deleted = 0;
+        loop {
+            let batch = conn.execute(&sql, rusqlite::params![now - ttl, config.batch_rows as i64])?;
+            deleted += batch;
+            if batch < config.batch_rows {
+                break;
+            }
+        }
+        if deleted > 0 {
+            report.deleted.push((table, deleted));
+        }
+    }
+
+    if config.vacuum_pages > 0 {
+        let auto_vacuum: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+        if auto_vacuum == AUTO_VACUUM_INCREMENTAL {
+            let free_before: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+            conn.execute_batch(&format!("PRAGMA incremental_vacuum({})", config.vacuum_pages))?;
+            let free_after: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+            report.pages_reclaimed = free_before - free_after;
+        }
+    }
+
+    Ok(report)
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool, SolflowError> {
+    let mut stmt = conn.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?")?;
+    Ok(stmt.exists([table])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::db::run_schema_migrations;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_retention_deletes_expired_rows_and_vacuums() {
+        let temp = NamedTempFile::new().unwrap();
+        let mut conn = Connection::open(temp.path()).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        let now = 10_000_000;
+
+        for (i, created_at) in [now - 8 * 86_400, now - 86_400, now - 60].iter().enumerate() {
+            conn.execute(
+                "INSERT INTO token_signals (mint, signal_type, window_seconds, created_at, details_json)
+                 VALUES (?, 'BREAKOUT', 60, ?, ?)",
+                rusqlite::params![format!("mint{}", i), created_at, "x".repeat(20_000)],
+            )
+            .unwrap();
+        }
+        for (mint, last_trade) in [("stale", now - 2 * 86_400), ("active", now - 30)] {
+            conn.execute(
+                "INSERT INTO token_aggregates (mint, source_program, last_trade_timestamp, updated_at, created_at)
+                 VALUES (?, 'test', ?, ?, 0)",
+                rusqlite::params![mint, last_trade, last_trade],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO dca_activity_buckets (mint, bucket_timestamp, buy_count) VALUES ('m', ?, 1), ('m', ?, 1)",
+            rusqlite::params![now - 3 * 3600, now - 60],
+        )
+        .unwrap();
+
+        let config = RetentionConfig { batch_rows: 1, ..RetentionConfig::default() };
+        let report = apply_retention(&conn, &config, now).unwrap();
+
+        assert_eq!(
+            report.deleted,
+            vec![("token_signals", 1), ("dca_activity_buckets", 1), ("token_aggregates", 1)]
+        );
+        // New databases are created with auto_vacuum = INCREMENTAL
+        assert!(report.pages_reclaimed > 0);
+
+        let remaining: String = conn
+            .query_row("SELECT group_concat(mint) FROM token_aggregates", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, "active");
+
+        // TTL 0 keeps the table
+        let keep = RetentionConfig { signals_secs: 0, ..RetentionConfig::default() };
+        assert!(keep.rules().iter().all(|(table, _, _)| *table != "token_signals"));
+    }
+}
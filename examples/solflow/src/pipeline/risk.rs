@@ -0,0 +1,547 @@
+//! Rug-pull risk checks
+//!
+//! Three things let a deployer take holders' money after the fact:
+//! - a mint authority that was never revoked (more supply can be minted)
+//! - a freeze authority that was never revoked (holders can be frozen, a honeypot)
+//! - pool LP that is neither burned nor locked (liquidity can be pulled)
+//!
+//! `RiskChecker` is an enrichment stage that checks each newly traded mint
+//! once and writes a `risk_flags` bitfield (`RISK_*`) with `risk_checked_at`
+//! into `token_metadata`. Deployers often revoke and burn shortly after
+//! launch, so flagged mints are checked again every `RISK_RECHECK_SECS`;
+//! mints that pass are not.
+//!
+//! Flags are also published to the checker's `RiskFlagsCache`; an engine
+//! handed that cache (`PipelineEngine::set_risk_flags`) lowers a risky mint's
+//! signal severity by one level per flag (never below 1).
+//!
+//! Pools are found with `getProgramAccounts` over the PumpSwap and Raydium
+//! AMM v4 pool layouts. LP counts as safe when its supply is zero (fully
+//! burned) or most of it sits with the incinerator or a lock program.
+//! Tokens still on a bonding curve have no pool and are never LP-flagged.
+//!
+//! Environment variables:
+//! - `RISK_CHECK_ENABLED`: Run the stage (default: false)
+//! - `SOLANA_RPC_URL`: RPC endpoint used for lookups (required when enabled)
+//! - `RISK_CHECK_INTERVAL_SECS`: Time between passes (default: 60)
+//! - `RISK_MAX_MINTS`: Mints checked per pass, unchecked ones first (default: 20)
+//! - `RISK_RECHECK_SECS`: Re-check flagged mints after this long (default: 3600)
+//! - `RISK_LP_LOCK_PROGRAMS`: Extra comma-separated LP lock program ids
+
+use super::enrichment::Enricher;
+use super::holders::{
+    parse_account_programs, parse_largest_accounts, parse_supply, parse_token_owners, rpc_call,
+};
+use crate::error::{codes, SolflowError};
+use async_trait::async_trait;
+use base64::Engine;
+use rusqlite::Connection;
+use solana_pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Mint authority not revoked
+pub const RISK_MINT_AUTHORITY: u32 = 1;
+/// Freeze authority not revoked
+pub const RISK_FREEZE_AUTHORITY: u32 = 1 << 1;
+/// No pool of the mint has burned or locked LP
+pub const RISK_LP_UNLOCKED: u32 = 1 << 2;
+
+/// Mints traded within this many seconds are checked
+const ACTIVE_WINDOW_SECS: i64 = 900;
+
+/// Share of LP supply that must be burned or locked
+const LP_SAFE_SHARE: f64 = 0.9;
+
+/// Token accounts sent here are burned for good
+const INCINERATOR: &str = "1nc1nerator11111111111111111111111111111111";
+
+/// Streamflow and Raydium Burn & Earn lockers
+const LP_LOCK_PROGRAMS: &[&str] = &[
+    "strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m",
+    "LockrWmn6K5twhz3y9w1dQERbmgSaRkfnTeTKbpofwE",
+];
+
+/// Where a pool account stores the traded mint and its LP mint
+struct PoolLayout {
+    program: &'static str,
+    mint_offset: usize,
+    lp_mint_offset: usize,
+    data_size: Option<u64>,
+}
+
+const POOL_LAYOUTS: &[PoolLayout] = &[
+    // PumpSwap: discriminator, bump, index, creator, base_mint, quote_mint, lp_mint
+    PoolLayout {
+        program: "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA",
+        mint_offset: 43,
+        lp_mint_offset: 107,
+        data_size: None,
+    },
+    // Raydium AMM v4, mint as coin
+    PoolLayout {
+        program: "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+        mint_offset: 400,
+        lp_mint_offset: 464,
+        data_size: Some(752),
+    },
+    // Raydium AMM v4, mint as pc
+    PoolLayout {
+        program: "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+        mint_offset: 432,
+        lp_mint_offset: 464,
+        data_size: Some(752),
+    },
+];
+
+/// Human-readable names of the set flags
+pub fn flag_names(flags: u32) -> Vec<&'static str> {
+    [
+        (RISK_MINT_AUTHORITY, "mint authority"),
+        (RISK_FREEZE_AUTHORITY, "freeze authority"),
+        (RISK_LP_UNLOCKED, "unlocked LP"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| flags & flag != 0)
+    .map(|(_, name)| name)
+    .collect()
+}
+
+/// Signal severity after the risk downgrade (one level per flag, at least 1)
+pub fn downgrade_severity(severity: i32, flags: u32) -> i32 {
+    if flags == 0 {
+        return severity;
+    }
+    (severity - flags.count_ones() as i32).max(1)
+}
+
+/// Risk flags of checked mints (only risky mints are kept)
+#[derive(Debug, Default)]
+pub struct RiskFlagsCache {
+    flags: Mutex<HashMap<String, u32>>,
+}
+
+impl RiskFlagsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flags for a mint (0 when clean or unchecked)
+    pub fn flags(&self, mint: &str) -> u32 {
+        self.flags.lock().unwrap().get(mint).copied().unwrap_or(0)
+    }
+
+    pub fn set(&self, mint: &str, flags: u32) {
+        let mut cache = self.flags.lock().unwrap();
+        if flags == 0 {
+            cache.remove(mint);
+        } else {
+            cache.insert(mint.to_string(), flags);
+        }
+    }
+
+    /// Risky mints currently known
+    pub fn len(&self) -> usize {
+        self.flags.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Mint account fields the checks need
+#[derive(Debug, Clone, PartialEq)]
+struct MintInfo {
+    decimals: u8,
+    mint_authority: bool,
+    freeze_authority: bool,
+}
+
+impl MintInfo {
+    fn flags(&self) -> u32 {
+        let mut flags = 0;
+        if self.mint_authority {
+            flags |= RISK_MINT_AUTHORITY;
+        }
+        if self.freeze_authority {
+            flags |= RISK_FREEZE_AUTHORITY;
+        }
+        flags
+    }
+}
+
+/// Mint fields from `getAccountInfo` (jsonParsed; SPL Token and Token-2022)
+fn parse_mint_info(response: &serde_json::Value) -> Option<MintInfo> {
+    let info = response.pointer("/result/value/data/parsed/info")?;
+    let authority_set = |key: &str| info.get(key).and_then(|a| a.as_str()).is_some();
+    Some(MintInfo {
+        decimals: info.get("decimals")?.as_u64()? as u8,
+        mint_authority: authority_set("mintAuthority"),
+        freeze_authority: authority_set("freezeAuthority"),
+    })
+}
+
+/// LP mints from a `getProgramAccounts` response sliced to the LP mint field
+fn parse_lp_mints(response: &serde_json::Value) -> Vec<String> {
+    response
+        .get("result")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|account| {
+            let encoded = account.pointer("/account/data/0")?.as_str()?;
+            let bytes = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+            Pubkey::try_from(bytes.get(..32)?).ok().map(|lp_mint| lp_mint.to_string())
+        })
+        .collect()
+}
+
+/// Whether enough of an LP mint's supply is burned or locked
+///
+/// `holdings` are (amount, held by the incinerator or a lock program).
+fn lp_is_safe(supply: u128, holdings: impl IntoIterator<Item = (u128, bool)>) -> bool {
+    if supply == 0 {
+        return true;
+    }
+    let locked: u128 = holdings
+        .into_iter()
+        .filter(|(_, locked)| *locked)
+        .map(|(amount, _)| amount)
+        .sum();
+    locked as f64 / supply as f64 >= LP_SAFE_SHARE
+}
+
+/// Enrichment stage writing `risk_flags` for newly traded mints
+pub struct RiskChecker {
+    db_path: String,
+    rpc_url: String,
+    client: reqwest::Client,
+    interval: Duration,
+    max_mints: usize,
+    recheck_secs: i64,
+    lock_programs: HashSet<String>,
+    /// Flags of risky mints, read by the engines
+    cache: Arc<RiskFlagsCache>,
+    /// Flags stored by earlier runs were loaded into `cache`
+    loaded: bool,
+}
+
+impl RiskChecker {
+    /// Create the stage from the environment (see module docs)
+    ///
+    /// Returns None unless enabled and an RPC endpoint is configured.
+    pub fn from_env(db_path: String) -> Option<Self> {
+        let enabled = std::env::var("RISK_CHECK_ENABLED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let Ok(rpc_url) = std::env::var("SOLANA_RPC_URL") else {
+            log::warn!("⚠️  RISK_CHECK_ENABLED is set but SOLANA_RPC_URL is not; risk checks disabled");
+            return None;
+        };
+
+        let interval_secs = std::env::var("RISK_CHECK_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|n: &u64| *n > 0)
+            .unwrap_or(60);
+        let max_mints = std::env::var("RISK_MAX_MINTS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(20);
+        let recheck_secs = std::env::var("RISK_RECHECK_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3600);
+
+        let mut lock_programs: HashSet<String> =
+            LP_LOCK_PROGRAMS.iter().map(|p| p.to_string()).collect();
+        if let Ok(extra) = std::env::var("RISK_LP_LOCK_PROGRAMS") {
+            lock_programs.extend(
+                extra
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .map(str::to_string),
+            );
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .ok()?;
+
+        Some(Self {
+            db_path,
+            rpc_url,
+            client,
+            interval: Duration::from_secs(interval_secs),
+            max_mints,
+            recheck_secs,
+            lock_programs,
+            cache: Arc::new(RiskFlagsCache::new()),
+            loaded: false,
+        })
+    }
+
+    /// The cache this stage publishes flags to (see `PipelineEngine::set_risk_flags`)
+    pub fn flags_cache(&self) -> Arc<RiskFlagsCache> {
+        self.cache.clone()
+    }
+
+    async fn rpc(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, SolflowError> {
+        rpc_call(&self.client, &self.rpc_url, method, params).await
+    }
+
+    /// Risk flags and decimals of a mint
+    async fn check(&self, mint: &str) -> Result<(u32, u8), SolflowError> {
+        let response = self
+            .rpc("getAccountInfo", serde_json::json!([mint, {"encoding": "jsonParsed"}]))
+            .await?;
+        let Some(info) = parse_mint_info(&response) else {
+            return Err(SolflowError::enrichment(
+                codes::ENRICHMENT_RESPONSE,
+                format!("{} is not a parsable mint account", mint),
+            ));
+        };
+
+        let mut flags = info.flags();
+        let lp_mints = self.find_lp_mints(mint).await?;
+        if !lp_mints.is_empty() {
+            let mut any_safe = false;
+            for lp_mint in &lp_mints {
+                if self.lp_mint_is_safe(lp_mint).await? {
+                    any_safe = true;
+                    break;
+                }
+            }
+            if !any_safe {
+                flags |= RISK_LP_UNLOCKED;
+            }
+        }
+
+        Ok((flags, info.decimals))
+    }
+
+    /// LP mints of every known pool trading `mint`
+    async fn find_lp_mints(&self, mint: &str) -> Result<Vec<String>, SolflowError> {
+        let mut lp_mints = Vec::new();
+        for layout in POOL_LAYOUTS {
+            let mut filters = vec![serde_json::json!({
+                "memcmp": {"offset": layout.mint_offset, "bytes": mint}
+            })];
+            if let Some(size) = layout.data_size {
+                filters.push(serde_json::json!({"dataSize": size}));
+            }
+            let response = self
+                .rpc(
+                    "getProgramAccounts",
+                    serde_json::json!([
+                        layout.program,
+                        {
+                            "encoding": "base64",
+                            "dataSlice": {"offset": layout.lp_mint_offset, "length": 32},
+                            "filters": filters,
+                        }
+                    ]),
+                )
+                .await?;
+            lp_mints.extend(parse_lp_mints(&response));
+        }
+        Ok(lp_mints)
+    }
+
+    /// Whether an LP mint is burned, or held by the incinerator or lock programs
+    async fn lp_mint_is_safe(&self, lp_mint: &str) -> Result<bool, SolflowError> {
+        let Some(supply) = parse_supply(&self.rpc("getTokenSupply", serde_json::json!([lp_mint])).await?)
+        else {
+            return Ok(false);
+        };
+        if supply == 0 {
+            return Ok(true);
+        }
+
+        let largest = parse_largest_accounts(
+            &self.rpc("getTokenLargestAccounts", serde_json::json!([lp_mint])).await?,
+        );
+        if largest.is_empty() {
+            return Ok(false);
+        }
+        let addresses: Vec<&str> = largest.iter().map(|(address, _)| address.as_str()).collect();
+        let owners = parse_token_owners(
+            &self
+                .rpc("getMultipleAccounts", serde_json::json!([addresses, {"encoding": "jsonParsed"}]))
+                .await?,
+        );
+
+        let mut distinct_owners: Vec<&str> = owners.iter().flatten().map(String::as_str).collect();
+        distinct_owners.sort_unstable();
+        distinct_owners.dedup();
+        let owner_programs: HashMap<&str, Option<String>> = distinct_owners
+            .iter()
+            .copied()
+            .zip(parse_account_programs(
+                &self
+                    .rpc(
+                        "getMultipleAccounts",
+                        serde_json::json!([
+                            distinct_owners,
+                            {"encoding": "base64", "dataSlice": {"offset": 0, "length": 0}}
+                        ]),
+                    )
+                    .await?,
+            ))
+            .collect();
+
+        let holdings = largest.iter().zip(&owners).map(|((_, amount), owner)| {
+            let locked = owner.as_deref().is_some_and(|owner| {
+                owner == INCINERATOR
+                    || owner_programs
+                        .get(owner)
+                        .and_then(|program| program.as_deref())
+                        .is_some_and(|program| self.lock_programs.contains(program))
+            });
+            (*amount, locked)
+        });
+        Ok(lp_is_safe(supply, holdings))
+    }
+
+    /// Unchecked active mints first, then flagged mints due for a re-check
+    fn due_mints(&self, conn: &Connection, now: i64) -> rusqlite::Result<Vec<String>> {
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT ta.mint
+            FROM token_aggregates ta
+            LEFT JOIN token_metadata tm ON ta.mint = tm.mint
+            WHERE ta.last_trade_timestamp >= ?1
+              AND (tm.blocked IS NULL OR tm.blocked = 0)
+              AND (tm.risk_checked_at IS NULL
+                   OR (tm.risk_flags != 0 AND tm.risk_checked_at <= ?2))
+            ORDER BY tm.risk_checked_at IS NOT NULL, ta.last_trade_timestamp DESC
+            LIMIT ?3
+            "#,
+        )?;
+        let mints = stmt
+            .query_map(
+                rusqlite::params![now - ACTIVE_WINDOW_SECS, now - self.recheck_secs, self.max_mints as i64],
+                |row| row.get(0),
+            )?
+            .collect();
+        mints
+    }
+
+    /// Seed the cache with flags stored by earlier runs
+    fn load_stored_flags(&self, conn: &Connection) -> rusqlite::Result<usize> {
+        let mut stmt = conn.prepare("SELECT mint, risk_flags FROM token_metadata WHERE risk_flags != 0")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for (mint, flags) in &rows {
+            self.cache.set(mint, *flags);
+        }
+        Ok(rows.len())
+    }
+}
+
+#[async_trait]
+impl Enricher for RiskChecker {
+    fn name(&self) -> &'static str {
+        "risk_check"
+    }
+
+    fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    async fn enrich(&mut self, now: i64) -> Result<(), SolflowError> {
+        let mints = {
+            let conn = Connection::open(&self.db_path)?;
+            if !self.loaded {
+                let loaded = self.load_stored_flags(&conn)?;
+                log::info!("🚩 Loaded risk flags for {} mints", loaded);
+                self.loaded = true;
+            }
+            self.due_mints(&conn, now)?
+        };
+
+        let mut results = Vec::with_capacity(mints.len());
+        for mint in &mints {
+            match self.check(mint).await {
+                Ok((flags, decimals)) => results.push((mint, flags, decimals)),
+                Err(e) => log::debug!("⚠️  Risk check failed for {}: {}", mint, e),
+            }
+        }
+
+        let conn = Connection::open(&self.db_path)?;
+        let cache = &self.cache;
+        for (mint, flags, decimals) in &results {
+            // A new row keeps updated_at 0 so the metadata scheduler still treats it as never fetched
+            conn.execute(
+                r#"
+                INSERT INTO token_metadata (mint, decimals, created_at, updated_at, risk_flags, risk_checked_at)
+                VALUES (?1, ?2, ?3, 0, ?4, ?3)
+                ON CONFLICT(mint) DO UPDATE SET
+                    risk_flags = excluded.risk_flags,
+                    risk_checked_at = excluded.risk_checked_at
+                "#,
+                rusqlite::params![mint, decimals, now, flags],
+            )?;
+            if *flags != 0 && cache.flags(mint) != *flags {
+                log::info!("🚩 {} risk: {}", mint, flag_names(*flags).join(", "));
+            }
+            cache.set(mint, *flags);
+        }
+        log::debug!("🚩 Risk checks: {}/{} mints checked", results.len(), mints.len());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_flags_and_severity_downgrade() {
+        let response = serde_json::json!({"result": {"value": {"data": {"parsed": {"info": {
+            "decimals": 6,
+            "mintAuthority": "Dev1111111111111111111111111111111111111111",
+            "freezeAuthority": null,
+            "supply": "1000000000000000",
+        }}}}}});
+        let info = parse_mint_info(&response).unwrap();
+        assert_eq!(info.decimals, 6);
+        assert_eq!(info.flags(), RISK_MINT_AUTHORITY);
+
+        // LP: burned outright, locked/incinerated, or left with the deployer
+        assert!(lp_is_safe(0, []));
+        assert!(lp_is_safe(1000, [(950, true), (50, false)]));
+        assert!(!lp_is_safe(1000, [(500, true), (500, false)]));
+
+        let lp_mint = Pubkey::new_from_array([7; 32]);
+        let encoded = base64::engine::general_purpose::STANDARD.encode(lp_mint.to_bytes());
+        let pools = serde_json::json!({"result": [
+            {"pubkey": "pool", "account": {"data": [encoded, "base64"]}},
+        ]});
+        assert_eq!(parse_lp_mints(&pools), vec![lp_mint.to_string()]);
+
+        let all = RISK_MINT_AUTHORITY | RISK_FREEZE_AUTHORITY | RISK_LP_UNLOCKED;
+        assert_eq!(downgrade_severity(4, 0), 4);
+        assert_eq!(downgrade_severity(4, RISK_FREEZE_AUTHORITY), 3);
+        assert_eq!(downgrade_severity(3, all), 1);
+        assert_eq!(flag_names(RISK_MINT_AUTHORITY | RISK_LP_UNLOCKED), vec!["mint authority", "unlocked LP"]);
+
+        let cache = RiskFlagsCache::new();
+        cache.set("risky", all);
+        cache.set("clean", 0);
+        assert_eq!(cache.flags("risky"), all);
+        assert_eq!(cache.len(), 1);
+        cache.set("risky", 0);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,409 @@
+//! Multi-database write routing
+//!
+//! Routes pipeline writes to different SQLite files by table (and, for
+//! signals, by severity) so read-heavy dashboard queries against history and
+//! rollups don't contend with the latency-sensitive aggregate flush.
+//!
+//! Routes are declared in `DB_ROUTES`:
+//!
+//! ```text
+//! DB_ROUTES="hot=/var/lib/solflow/hot.db:token_aggregates,token_signals>=3;cold=/var/lib/solflow/cold.db:token_signals,dca_activity_buckets,system_metrics,session_rollups"
+//! ```
+//!
+//! - Routes are `;`-separated `name=path:table,table,...` entries
+//! - `token_signals>=N` matches signals with severity ≥ N only
+//! - Rules are checked in declaration order; the first match wins
+//! - Anything unmatched goes to the primary database (`SOLFLOW_DB_PATH`)
+//! - `dca_activity_rollups` is written with, and routed like, `dca_activity_buckets`
+//!
+//! The blocklist always lives in the primary database, so signal writes are
+//! checked against it regardless of where the signal is routed.
+
+use super::audit_trades::AuditTrade;
+use super::db::{run_schema_migrations, AggregateDbWriter, SqliteAggregateWriter};
+use super::schema_check::verify_schema;
+use super::sessions::SessionRollup;
+use super::signals::TokenSignal;
+use super::types::AggregatedTokenState;
+use crate::error::{codes, SolflowError};
+use async_trait::async_trait;
+use rusqlite::Connection;
+use std::sync::Arc;
+
+/// Tables that can be routed to a separate database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoutedTable {
+    TokenAggregates,
+    TokenSignals,
+    DcaActivityBuckets,
+    SystemMetrics,
+    SessionRollups,
+    AuditTrades,
+}
+
+impl RoutedTable {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "token_aggregates" => Some(Self::TokenAggregates),
+            "token_signals" => Some(Self::TokenSignals),
+            "dca_activity_buckets" => Some(Self::DcaActivityBuckets),
+            "system_metrics" => Some(Self::SystemMetrics),
+            "session_rollups" => Some(Self::SessionRollups),
+            "audit_trades" => Some(Self::AuditTrades),
+            _ => None,
+        }
+    }
+}
+
+/// A single table rule within a route
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteRule {
+    pub table: RoutedTable,
+    /// Minimum signal severity (token_signals only)
+    pub min_severity: Option<i32>,
+}
+
+impl RouteRule {
+    fn matches(&self, table: RoutedTable, severity: Option<i32>) -> bool {
+        if self.table != table {
+            return false;
+        }
+        match (self.min_severity, severity) {
+            (Some(min), Some(severity)) => severity >= min,
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+}
+
+/// A named database target and the tables routed to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbRoute {
+    pub name: String,
+    pub db_path: String,
+    pub rules: Vec<RouteRule>,
+}
+
+/// Parse a `DB_ROUTES` specification
+pub fn parse_routes(spec: &str) -> Result<Vec<DbRoute>, String> {
+    let mut routes = Vec::new();
+
+    for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, rest) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("route '{}' must be name=path:tables", entry))?;
+        let (db_path, tables) = rest
+            .rsplit_once(':')
+            .ok_or_else(|| format!("route '{}' is missing ':tables'", name))?;
+
+        let mut rules = Vec::new();
+        for table_spec in tables.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let (table_name, min_severity) = match table_spec.split_once(">=") {
+                Some((table_name, min)) => {
+                    let min = min
+                        .trim()
+                        .parse::<i32>()
+                        .map_err(|_| format!("invalid severity in '{}'", table_spec))?;
+                    (table_name.trim(), Some(min))
+                }
+                None => (table_spec, None),
+            };
+
+            let table = RoutedTable::from_name(table_name)
+                .ok_or_else(|| format!("unknown table '{}' in route '{}'", table_name, name))?;
+            if min_severity.is_some() && table != RoutedTable::TokenSignals {
+                return Err(format!("severity filter only applies to token_signals ('{}')", table_spec));
+            }
+
+            rules.push(RouteRule { table, min_severity });
+        }
+
+        if rules.is_empty() {
+            return Err(format!("route '{}' has no tables", name));
+        }
+
+        routes.push(DbRoute {
+            name: name.trim().to_string(),
+            db_path: db_path.trim().to_string(),
+            rules,
+        });
+    }
+
+    Ok(routes)
+}
+
+/// AggregateDbWriter that fans writes out to multiple SQLite databases
+pub struct RoutedAggregateWriter {
+    /// Primary database (unmatched tables + mint_blocklist)
+    primary: Arc<SqliteAggregateWriter>,
+    routes: Vec<(DbRoute, Arc<SqliteAggregateWriter>)>,
+}
+
+impl RoutedAggregateWriter {
+    /// Open every routed database, applying schema migrations to each
+    ///
+    /// Arguments:
+    /// - `primary_path`: Primary database (must already have schema)
+    /// - `routes`: Parsed route declarations
+    /// - `schema_dir`: Directory with `/sql/*.sql` migrations for routed databases
+    pub fn new(
+        primary_path: &str,
+        routes: Vec<DbRoute>,
+        schema_dir: &str,
+    ) -> Result<Self, SolflowError> {
+        let primary = Arc::new(SqliteAggregateWriter::new(primary_path)?);
+
+        let mut opened = Vec::with_capacity(routes.len());
+        for route in routes {
+            // Routes pointing at the primary file share its writer
+            let writer = if route.db_path == primary_path {
+                primary.clone()
+            } else {
+                let mut conn = Connection::open(&route.db_path)?;
+                run_schema_migrations(&mut conn, schema_dir)?;
+                verify_schema(&conn)?;
+                drop(conn);
+                Arc::new(SqliteAggregateWriter::new(&route.db_path)?)
+            };
+
+            log::info!(
+                "🔀 DB route '{}' → {} ({} rules)",
+                route.name,
+                route.db_path,
+                route.rules.len()
+            );
+            opened.push((route, writer));
+        }
+
+        Ok(Self { primary, routes: opened })
+    }
+
+    /// Writer for a table (and signal severity), falling back to the primary
+    fn writer_for(&self, table: RoutedTable, severity: Option<i32>) -> &Arc<SqliteAggregateWriter> {
+        self.routes
+            .iter()
+            .find(|(route, _)| route.rules.iter().any(|rule| rule.matches(table, severity)))
+            .map(|(_, writer)| writer)
+            .unwrap_or(&self.primary)
+    }
+
+    /// Clean up old DCA buckets in whichever database holds them
+    pub async fn cleanup_old_dca_buckets(&self) -> Result<usize, SolflowError> {
+        self.writer_for(RoutedTable::DcaActivityBuckets, None)
+            .cleanup_old_dca_buckets()
+            .await
+    }
+}
+
+#[async_trait]
+impl AggregateDbWriter for RoutedAggregateWriter {
+    async fn write_aggregates(
+        &self,
+        aggregates: Vec<AggregatedTokenState>,
+    ) -> Result<(), SolflowError> {
+        let aggregate_writer = self.writer_for(RoutedTable::TokenAggregates, None);
+        let bucket_writer = self.writer_for(RoutedTable::DcaActivityBuckets, None);
+
+        if Arc::ptr_eq(aggregate_writer, bucket_writer) {
+            return aggregate_writer.write_aggregates_queued(aggregates, true).await;
+        }
+
+        aggregate_writer.write_aggregates_queued(aggregates.clone(), false).await?;
+        bucket_writer.write_dca_activity_queued(aggregates).await
+    }
+
+    async fn write_signal(
+        &self,
+        signal: TokenSignal,
+    ) -> Result<(), SolflowError> {
+        // Blocklist is authoritative in the primary database
+        if self.primary.is_mint_blocked(&signal.mint, signal.created_at).await? {
+            return Err(SolflowError::db(
+                codes::DB_MINT_BLOCKED,
+                format!("Mint {} is blocked, signal not written", signal.mint),
+            ));
+        }
+
+        self.writer_for(RoutedTable::TokenSignals, Some(signal.severity))
+            .write_signal(signal)
+            .await
+    }
+
+    async fn write_signals(
+        &self,
+        signals: Vec<TokenSignal>,
+    ) -> Result<Vec<TokenSignal>, SolflowError> {
+        let Some(since) = signals.iter().map(|signal| signal.created_at).min() else {
+            return Ok(Vec::new());
+        };
+        // Blocklist is authoritative in the primary database
+        let blocklist = self.primary.signal_blocklist(since).await?;
+
+        let mut batches: Vec<(&Arc<SqliteAggregateWriter>, Vec<TokenSignal>)> = Vec::new();
+        for signal in signals.into_iter().filter(|signal| !blocklist.blocks(signal)) {
+            let writer = self.writer_for(RoutedTable::TokenSignals, Some(signal.severity));
+            match batches.iter_mut().find(|(w, _)| Arc::ptr_eq(w, writer)) {
+                Some((_, batch)) => batch.push(signal),
+                None => batches.push((writer, vec![signal])),
+            }
+        }
+
+        let mut written = Vec::new();
+        for (writer, batch) in batches {
+            written.extend(writer.write_signals(batch).await?);
+        }
+        Ok(written)
+    }
+
+    async fn write_system_metric(
+        &self,
+        key: &str,
+        value_json: &str,
+    ) -> Result<(), SolflowError> {
+        self.writer_for(RoutedTable::SystemMetrics, None)
+            .write_system_metric(key, value_json)
+            .await
+    }
+
+    async fn write_session_rollups(
+        &self,
+        rollups: Vec<SessionRollup>,
+    ) -> Result<(), SolflowError> {
+        self.writer_for(RoutedTable::SessionRollups, None)
+            .write_session_rollups(rollups)
+            .await
+    }
+
+    async fn write_audit_trades(
+        &self,
+        trades: Vec<AuditTrade>,
+        prune_before: i64,
+    ) -> Result<(), SolflowError> {
+        self.writer_for(RoutedTable::AuditTrades, None)
+            .write_audit_trades(trades, prune_before)
+            .await
+    }
+
+    /// Compact every database that can hold signals (each up to `limit`)
+    async fn compact_signal_details(
+        &self,
+        before: i64,
+        limit: usize,
+    ) -> Result<usize, SolflowError> {
+        let mut writers: Vec<&Arc<SqliteAggregateWriter>> = vec![&self.primary];
+        for (route, writer) in &self.routes {
+            let holds_signals = route.rules.iter().any(|rule| rule.table == RoutedTable::TokenSignals);
+            if holds_signals && !writers.iter().any(|w| Arc::ptr_eq(w, writer)) {
+                writers.push(writer);
+            }
+        }
+        let mut compacted = 0;
+        for writer in writers {
+            compacted += writer.compact_signal_details(before, limit).await?;
+        }
+        Ok(compacted)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::signals::SignalType;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_routes() {
+        let routes = parse_routes(
+            "hot=/tmp/hot.db:token_aggregates,token_signals>=3; cold=/tmp/cold.db:token_signals,system_metrics",
+        )
+        .unwrap();
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].name, "hot");
+        assert_eq!(routes[0].db_path, "/tmp/hot.db");
+        assert_eq!(
+            routes[0].rules[1],
+            RouteRule { table: RoutedTable::TokenSignals, min_severity: Some(3) }
+        );
+        assert_eq!(routes[1].rules.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_routes_rejects_bad_specs() {
+        assert!(parse_routes("hot=/tmp/hot.db").is_err());
+        assert!(parse_routes("hot=/tmp/hot.db:trades").is_err());
+        assert!(parse_routes("hot=/tmp/hot.db:token_aggregates>=2").is_err());
+        assert!(parse_routes("hot=/tmp/hot.db:").is_err());
+    }
+
+    #[test]
+    fn test_rule_matching_by_severity() {
+        let rule = RouteRule { table: RoutedTable::TokenSignals, min_severity: Some(3) };
+        assert!(rule.matches(RoutedTable::TokenSignals, Some(4)));
+        assert!(!rule.matches(RoutedTable::TokenSignals, Some(2)));
+        assert!(!rule.matches(RoutedTable::SystemMetrics, None));
+    }
+
+    fn count_rows(db_path: &str, table: &str) -> i64 {
+        let conn = Connection::open(db_path).unwrap();
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_signals_routed_by_severity() {
+        let dir = TempDir::new().unwrap();
+        let primary = dir.path().join("primary.db").to_str().unwrap().to_string();
+        let hot = dir.path().join("hot.db").to_str().unwrap().to_string();
+        let cold = dir.path().join("cold.db").to_str().unwrap().to_string();
+
+        let mut conn = Connection::open(&primary).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        drop(conn);
+
+        let routes = parse_routes(&format!(
+            "hot={}:token_signals>=4;cold={}:token_signals,system_metrics",
+            hot, cold
+        ))
+        .unwrap();
+        let writer = RoutedAggregateWriter::new(&primary, routes, "sql").unwrap();
+
+        let high = TokenSignal::new("mint_a".to_string(), SignalType::Surge, 60, 1000).with_severity(5);
+        let low = TokenSignal::new("mint_b".to_string(), SignalType::Surge, 60, 1000).with_severity(2);
+        writer.write_signal(high).await.unwrap();
+        writer.write_signal(low).await.unwrap();
+        writer.write_system_metric("k", r#"{"v":1}"#).await.unwrap();
+
+        assert_eq!(count_rows(&hot, "token_signals"), 1);
+        assert_eq!(count_rows(&cold, "token_signals"), 1);
+        assert_eq!(count_rows(&cold, "system_metrics"), 1);
+        assert_eq!(count_rows(&primary, "token_signals"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_routed_signal_respects_primary_blocklist() {
+        let dir = TempDir::new().unwrap();
+        let primary = dir.path().join("primary.db").to_str().unwrap().to_string();
+        let hot = dir.path().join("hot.db").to_str().unwrap().to_string();
+
+        let mut conn = Connection::open(&primary).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        conn.execute(
+            "INSERT INTO mint_blocklist (mint, reason, blocked_by, created_at, expires_at) VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params!["blocked_mint", "test", "test", 0, Option::<i64>::None],
+        )
+        .unwrap();
+        drop(conn);
+
+        let routes = parse_routes(&format!("hot={}:token_signals", hot)).unwrap();
+        let writer = RoutedAggregateWriter::new(&primary, routes, "sql").unwrap();
+
+        let signal = TokenSignal::new("blocked_mint".to_string(), SignalType::Breakout, 60, 1000);
+        assert!(writer.write_signal(signal).await.is_err());
+        assert_eq!(count_rows(&hot, "token_signals"), 0);
+    }
+}
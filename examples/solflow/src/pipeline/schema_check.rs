@@ -0,0 +1,277 @@
+//! Startup schema drift detection
+//!
+//! `CREATE TABLE IF NOT EXISTS` never touches an existing table, so a
+//! database created by an older or hand-edited DDL can be missing columns
+//! the writers use. rusqlite only reports that as "no such column" on the
+//! first flush that hits it. The runtime calls `verify_schema` once after
+//! migrations (for every routed database too), which fails with every
+//! missing table and column instead.
+//!
+//! `EXPECTED_SCHEMA` lists the columns the pipeline reads or writes; keep it
+//! in sync with the queries in `db.rs`, `lease.rs`, `fast_path.rs`,
+//! `metadata_scheduler.rs` and `integrity.rs` when a table in `/sql/` changes.
+
+use crate::error::{codes, SolflowError};
+use rusqlite::Connection;
+use std::collections::HashSet;
+
+/// (table, columns used by the pipeline)
+pub const EXPECTED_SCHEMA: &[(&str, &[&str])] = &[
+    (
+        "token_aggregates",
+        &[
+            "mint",
+            "source_program",
+            "last_trade_timestamp",
+            "net_flow_60s_sol",
+            "net_flow_300s_sol",
+            "net_flow_900s_sol",
+            "net_flow_3600s_sol",
+            "net_flow_7200s_sol",
+            "net_flow_14400s_sol",
+            "buy_count_60s",
+            "sell_count_60s",
+            "buy_count_300s",
+            "sell_count_300s",
+            "buy_count_900s",
+            "sell_count_900s",
+            "unique_wallets_300s",
+            "unique_clusters_300s",
+            "new_wallets_300s",
+            "fees_paid_300s_sol",
+            "venue_fees_300s_sol",
+            "fee_adjusted_net_flow_300s_sol",
+            "liquidity_sol",
+            "pool_price_sol",
+            "priority_fees_300s_sol",
+            "jito_tips_300s_sol",
+            "program_breakdown_300s_json",
+            "early_buyers_count",
+            "early_holder_retention",
+            "top10_holder_pct",
+            "fresh_wallet_ratio_60s",
+            "fresh_wallet_ratio_300s",
+            "fresh_wallet_ratio_900s",
+            "unique_wallets_3600s",
+            "unique_wallets_7200s",
+            "unique_wallets_14400s",
+            "net_flow_sparkline_json",
+            "bot_trades_300s",
+            "bot_wallets_300s",
+            "avg_trade_size_300s_sol",
+            "volume_300s_sol",
+            "dca_buys_60s",
+            "dca_buys_300s",
+            "dca_buys_900s",
+            "dca_buys_3600s",
+            "dca_buys_14400s",
+            "net_flow_60s_final_sol",
+            "net_flow_300s_final_sol",
+            "net_flow_900s_final_sol",
+            "buy_count_300s_final",
+            "sell_count_300s_final",
+            "unfinalized_trades_300s",
+            "window_metrics_json",
+            "price_usd",
+            "price_sol",
+            "market_cap_usd",
+            "last_trade_slot",
+            "last_trade_signature",
+            "last_data_gap_at",
+            "updated_at",
+            "created_at",
+        ],
+    ),
+    (
+        "token_signals",
+        &[
+            "mint",
+            "signal_type",
+            "window_seconds",
+            "severity",
+            "score",
+            "details_json",
+            "created_at",
+            "source",
+            "sample_signatures",
+        ],
+    ),
+    ("mint_blocklist", &["mint", "reason", "blocked_by", "created_at", "expires_at"]),
+    ("system_metrics", &["key", "value_json", "updated_at"]),
+    (
+        "dca_activity_buckets",
+        &["mint", "bucket_timestamp", "buy_count", "last_slot", "last_signature"],
+    ),
+    (
+        "dca_activity_rollups",
+        &["mint", "bucket_timestamp", "buy_count", "last_slot", "last_signature"],
+    ),
+    (
+        "session_rollups",
+        &[
+            "mint",
+            "session_date",
+            "session",
+            "buy_sol",
+            "sell_sol",
+            "net_flow_sol",
+            "buy_count",
+            "sell_count",
+            "unique_wallets",
+            "updated_at",
+        ],
+    ),
+    (
+        "audit_trades",
+        &[
+            "mint",
+            "signature",
+            "slot",
+            "timestamp",
+            "direction",
+            "sol_amount",
+            "token_amount",
+            "user_account",
+            "source_program",
+            "fee_lamports",
+            "venue_fee_sol",
+            "net_sol",
+            "recorded_at",
+        ],
+    ),
+    (
+        "token_metadata",
+        &[
+            "mint",
+            "name",
+            "symbol",
+            "image_url",
+            "price_usd",
+            "market_cap",
+            "pair_created_at",
+            "mint_created_at",
+            "decimals",
+            "blocked",
+            "follow_price",
+            "risk_flags",
+            "risk_checked_at",
+            "updated_at",
+            "created_at",
+        ],
+    ),
+    ("instance_leases", &["name", "holder", "acquired_at", "expires_at"]),
+    (
+        "quarantined_rows",
+        &["source_table", "row_key", "reason", "row_json", "quarantined_at"],
+    ),
+];
+
+/// Difference between `EXPECTED_SCHEMA` and a database
+#[derive(Debug, Default, PartialEq)]
+pub struct SchemaDrift {
+    pub missing_tables: Vec<String>,
+    /// (table, column)
+    pub missing_columns: Vec<(String, String)>,
+}
+
+impl SchemaDrift {
+    pub fn is_empty(&self) -> bool {
+        self.missing_tables.is_empty() && self.missing_columns.is_empty()
+    }
+}
+
+impl std::fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "database schema does not match the pipeline (see /sql/):")?;
+        for table in &self.missing_tables {
+            writeln!(f, "  - missing table {}", table)?;
+        }
+        for (table, column) in &self.missing_columns {
+            writeln!(f, "  - missing column {}.{}", table, column)?;
+        }
+        write!(f, "Fix the table DDL or add the column to ADDED_COLUMNS in pipeline/db.rs")
+    }
+}
+
+impl std::error::Error for SchemaDrift {}
+
+/// Compare the database against `EXPECTED_SCHEMA`
+pub fn schema_drift(conn: &Connection) -> Result<SchemaDrift, rusqlite::Error> {
+    let mut drift = SchemaDrift::default();
+
+    for (table, columns) in EXPECTED_SCHEMA {
+        let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+        let existing = stmt
+            .query_map([table], |row| row.get::<_, String>(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        if existing.is_empty() {
+            drift.missing_tables.push(table.to_string());
+            continue;
+        }
+
+        for column in *columns {
+            if !existing.contains(*column) {
+                drift.missing_columns.push((table.to_string(), column.to_string()));
+            }
+        }
+    }
+
+    Ok(drift)
+}
+
+/// Fail with the full diff if the database has drifted
+pub fn verify_schema(conn: &Connection) -> Result<(), SolflowError> {
+    let drift = schema_drift(conn)?;
+    if drift.is_empty() {
+        log::info!("✅ Schema check passed ({} tables)", EXPECTED_SCHEMA.len());
+        Ok(())
+    } else {
+        Err(SolflowError::db(codes::DB_SCHEMA, drift))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::db::run_schema_migrations;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_migrated_database_has_no_drift() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut conn = Connection::open(temp_file.path()).unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+
+        assert!(schema_drift(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_drift_lists_missing_tables_and_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE token_signals (
+                mint TEXT, signal_type TEXT, window_seconds INTEGER,
+                severity INTEGER, score REAL, details_json TEXT, created_at INTEGER
+            );
+            CREATE TABLE system_metrics (key TEXT, value TEXT, updated_at INTEGER);",
+        )
+        .unwrap();
+
+        let drift = schema_drift(&conn).unwrap();
+        assert!(drift.missing_tables.contains(&"token_aggregates".to_string()));
+        assert!(!drift.missing_tables.contains(&"token_signals".to_string()));
+        assert_eq!(
+            drift.missing_columns,
+            vec![
+                ("token_signals".to_string(), "source".to_string()),
+                ("token_signals".to_string(), "sample_signatures".to_string()),
+                ("system_metrics".to_string(), "value_json".to_string()),
+            ]
+        );
+
+        let message = verify_schema(&conn).unwrap_err().to_string();
+        assert!(message.contains("missing column token_signals.source"));
+        assert!(message.contains("missing table token_aggregates"));
+    }
+}
@@ -0,0 +1,254 @@
+//! Trading-session rollups (Asia / EU / US)
+//!
+//! Flow that piles up during one region's trading hours is a pattern the
+//! uniform rolling windows can't show. Each trade is assigned to the session
+//! that opened most recently, using local exchange opening times so DST
+//! shifts are followed:
+//!
+//! - ASIA: 09:00 Asia/Tokyo
+//! - EU: 08:00 Europe/London
+//! - US: 09:30 America/New_York
+//!
+//! A session runs until the next one opens, so the three partition the day.
+//! Totals are kept per mint and market-wide (`MARKET_WIDE_MINT`) and upserted
+//! into `session_rollups`, keyed by the session's local opening date.
+//! Unique wallets use the same HyperLogLog sketch as the minute buckets.
+
+use super::hll::HyperLogLog;
+use super::types::{TradeDirection, TradeEvent};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone};
+use chrono_tz::Tz;
+use std::collections::{HashMap, HashSet};
+
+/// `session_rollups.mint` value for market-wide totals
+pub const MARKET_WIDE_MINT: &str = "*";
+
+/// Regional trading session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TradingSession {
+    Asia,
+    Eu,
+    Us,
+}
+
+impl TradingSession {
+    pub const ALL: [TradingSession; 3] = [Self::Asia, Self::Eu, Self::Us];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asia => "ASIA",
+            Self::Eu => "EU",
+            Self::Us => "US",
+        }
+    }
+
+    fn time_zone(&self) -> Tz {
+        match self {
+            Self::Asia => chrono_tz::Asia::Tokyo,
+            Self::Eu => chrono_tz::Europe::London,
+            Self::Us => chrono_tz::America::New_York,
+        }
+    }
+
+    fn opens_at(&self) -> NaiveTime {
+        match self {
+            Self::Asia => NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            Self::Eu => NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            Self::Us => NaiveTime::from_hms_opt(9, 30, 0).unwrap(),
+        }
+    }
+
+    /// Most recent opening at or before `timestamp`: (local date, unix time)
+    fn last_open(&self, timestamp: i64) -> Option<(NaiveDate, i64)> {
+        let tz = self.time_zone();
+        let local = DateTime::from_timestamp(timestamp, 0)?.with_timezone(&tz);
+        let mut date = local.date_naive();
+        if local.time() < self.opens_at() {
+            date -= Duration::days(1);
+        }
+        let open = tz
+            .from_local_datetime(&date.and_time(self.opens_at()))
+            .earliest()?;
+        Some((date, open.timestamp()))
+    }
+}
+
+/// Session a timestamp falls in, with the session's local opening date
+pub fn session_at(timestamp: i64) -> Option<(TradingSession, NaiveDate)> {
+    TradingSession::ALL
+        .iter()
+        .filter_map(|session| session.last_open(timestamp).map(|(date, open)| (*session, date, open)))
+        .max_by_key(|(_, _, open)| *open)
+        .map(|(session, date, _)| (session, date))
+}
+
+/// One `session_rollups` row
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionRollup {
+    pub mint: String,
+    /// Local date the session opened (YYYY-MM-DD)
+    pub session_date: String,
+    pub session: String,
+    pub buy_sol: f64,
+    pub sell_sol: f64,
+    pub buy_count: i32,
+    pub sell_count: i32,
+    /// Estimated distinct wallets (see `hll`)
+    pub unique_wallets: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Default)]
+struct SessionTotals {
+    buy_sol: f64,
+    sell_sol: f64,
+    buy_count: i32,
+    sell_count: i32,
+    wallets: HyperLogLog,
+}
+
+type SessionKey = (String, NaiveDate, TradingSession);
+
+/// Accumulates per-mint and market-wide session totals between flushes
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    totals: HashMap<SessionKey, SessionTotals>,
+    /// Keys changed since the last drain
+    dirty: HashSet<SessionKey>,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, trade: &TradeEvent) {
+        let Some((session, date)) = session_at(trade.timestamp) else {
+            return;
+        };
+        for mint in [trade.mint.as_str(), MARKET_WIDE_MINT] {
+            let key = (mint.to_string(), date, session);
+            let totals = self.totals.entry(key.clone()).or_default();
+            match trade.direction {
+                TradeDirection::Buy => {
+                    totals.buy_sol += trade.sol_amount;
+                    totals.buy_count += 1;
+                }
+                TradeDirection::Sell => {
+                    totals.sell_sol += trade.sol_amount;
+                    totals.sell_count += 1;
+                }
+                TradeDirection::Unknown => {}
+            }
+            totals.wallets.insert(&trade.user_account);
+            self.dirty.insert(key);
+        }
+    }
+
+    /// Rows changed since the last drain
+    ///
+    /// Sessions that have ended by `now` are dropped from memory afterwards;
+    /// their final totals are part of this (or an earlier) drain.
+    pub fn drain(&mut self, now: i64) -> Vec<SessionRollup> {
+        let rollups = self
+            .dirty
+            .drain()
+            .filter_map(|key| {
+                let totals = self.totals.get(&key)?;
+                Some(SessionRollup {
+                    mint: key.0.clone(),
+                    session_date: key.1.format("%Y-%m-%d").to_string(),
+                    session: key.2.as_str().to_string(),
+                    buy_sol: totals.buy_sol,
+                    sell_sol: totals.sell_sol,
+                    buy_count: totals.buy_count,
+                    sell_count: totals.sell_count,
+                    unique_wallets: totals.wallets.estimate() as i64,
+                    updated_at: now,
+                })
+            })
+            .collect();
+
+        if let Some((session, date)) = session_at(now) {
+            self.totals.retain(|(_, d, s), _| (*d, *s) == (date, session));
+        }
+        rollups
+    }
+
+    /// Number of (mint, session) entries held
+    pub fn len(&self) -> usize {
+        self.totals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.totals.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(rfc3339: &str) -> i64 {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().timestamp()
+    }
+
+    fn trade(timestamp: i64, mint: &str, direction: TradeDirection, sol: f64, wallet: &str) -> TradeEvent {
+        TradeEvent {
+            direction,
+            sol_amount: sol,
+            user_account: wallet.to_string(),
+            ..TradeEvent::test_buy(mint, timestamp)
+        }
+    }
+
+    #[test]
+    fn test_session_boundaries_follow_dst() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+        // Winter: London open 08:00 UTC, New York open 14:30 UTC
+        assert_eq!(session_at(ts("2025-01-15T00:00:00Z")), Some((TradingSession::Asia, date("2025-01-15"))));
+        assert_eq!(session_at(ts("2025-01-15T07:59:59Z")), Some((TradingSession::Asia, date("2025-01-15"))));
+        assert_eq!(session_at(ts("2025-01-15T08:00:00Z")), Some((TradingSession::Eu, date("2025-01-15"))));
+        assert_eq!(session_at(ts("2025-01-15T14:00:00Z")), Some((TradingSession::Eu, date("2025-01-15"))));
+        assert_eq!(session_at(ts("2025-01-15T14:30:00Z")), Some((TradingSession::Us, date("2025-01-15"))));
+        // US session runs past UTC midnight until Tokyo opens
+        assert_eq!(session_at(ts("2025-01-15T23:30:00Z")), Some((TradingSession::Us, date("2025-01-15"))));
+
+        // Summer: both open an hour earlier in UTC
+        assert_eq!(session_at(ts("2025-07-15T07:00:00Z")), Some((TradingSession::Eu, date("2025-07-15"))));
+        assert_eq!(session_at(ts("2025-07-15T13:30:00Z")), Some((TradingSession::Us, date("2025-07-15"))));
+    }
+
+    #[test]
+    fn test_tracker_rollups_and_eviction() {
+        let mut tracker = SessionTracker::new();
+        let asia = ts("2025-01-15T02:00:00Z");
+        tracker.record(&trade(asia, "mint_a", TradeDirection::Buy, 2.0, "w1"));
+        tracker.record(&trade(asia + 60, "mint_a", TradeDirection::Sell, 0.5, "w2"));
+        tracker.record(&trade(asia + 120, "mint_b", TradeDirection::Buy, 1.0, "w1"));
+
+        let mut rollups = tracker.drain(asia + 180);
+        rollups.sort_by(|a, b| a.mint.cmp(&b.mint));
+        assert_eq!(rollups.len(), 3);
+        let market = &rollups[0];
+        assert_eq!(market.mint, MARKET_WIDE_MINT);
+        assert_eq!(market.session, "ASIA");
+        assert_eq!(market.session_date, "2025-01-15");
+        assert_eq!((market.buy_count, market.sell_count), (2, 1));
+        assert_eq!(market.unique_wallets, 2);
+        assert!((rollups[1].buy_sol - 2.0).abs() < 1e-9);
+        assert!((rollups[1].sell_sol - 0.5).abs() < 1e-9);
+
+        // Nothing new: nothing to write
+        assert!(tracker.drain(asia + 240).is_empty());
+
+        // Once the EU session opens, the Asia totals are released
+        let eu = ts("2025-01-15T09:00:00Z");
+        tracker.record(&trade(eu, "mint_a", TradeDirection::Buy, 1.0, "w3"));
+        let rollups = tracker.drain(eu);
+        assert_eq!(rollups.len(), 2);
+        assert!(rollups.iter().all(|r| r.session == "EU"));
+        assert_eq!(tracker.len(), 2);
+    }
+}
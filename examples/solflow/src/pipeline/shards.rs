@@ -0,0 +1,294 @@
+//! Mint-sharded PipelineEngine
+//!
+//! A single engine behind one mutex serializes trade processing against the
+//! flush. `ShardedEngine` splits the tokens across N engines by a hash of the
+//! mint (`PIPELINE_SHARDS`, default: 1). Ingestion runs one task per shard,
+//! each fed by its own channel, and the flush computes every shard in
+//! parallel before merging aggregates and signals into one write batch.
+//!
+//! Per-token state never spans shards, so aggregates and signals are the same
+//! as with one engine. Cross-token state is handled here:
+//!
+//! - session rollups: the market-wide row sums every token, so with more than
+//!   one shard a single `SessionTracker` is kept here instead of per engine
+//! - latency: the global percentiles are recomputed from all shards' samples
+//! - wallet tracking follows wallets across tokens and needs one shard (the
+//!   runtime falls back to a single shard when it is enabled)
+
+use super::engine::PipelineEngine;
+use super::latency::{summarize, LatencySummary};
+use super::sessions::{SessionRollup, SessionTracker};
+use super::snapshot::{EngineSnapshot, MintSnapshot};
+use super::state::RollingMetrics;
+use super::thresholds::SignalThresholdsConfig;
+use super::types::{Confirmation, TokenMigration, TradeEvent};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+
+/// PipelineEngine split across shards by mint
+pub struct ShardedEngine {
+    shards: Vec<Arc<Mutex<PipelineEngine>>>,
+
+    /// Market-wide session totals; None with one shard (the engine keeps them)
+    sessions: Option<Mutex<SessionTracker>>,
+}
+
+impl ShardedEngine {
+    /// Build `count` shards (at least one), configuring each with `make_engine`
+    pub fn new(count: usize, mut make_engine: impl FnMut(usize) -> PipelineEngine) -> Self {
+        let count = count.max(1);
+        let shards = (0..count)
+            .map(|index| {
+                let mut engine = make_engine(index);
+                if count > 1 {
+                    engine.set_session_tracking(false);
+                }
+                Arc::new(Mutex::new(engine))
+            })
+            .collect();
+        Self {
+            shards,
+            sessions: (count > 1).then(|| Mutex::new(SessionTracker::new())),
+        }
+    }
+
+    /// Wrap an existing engine as the only shard
+    pub fn single(engine: Arc<Mutex<PipelineEngine>>) -> Self {
+        Self {
+            shards: vec![engine],
+            sessions: None,
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Shard that owns `mint`
+    pub fn shard_index(&self, mint: &str) -> usize {
+        if self.shards.len() == 1 {
+            return 0;
+        }
+        // DefaultHasher::new() uses fixed keys, so the mapping is stable across runs
+        let mut hasher = DefaultHasher::new();
+        mint.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    pub fn shard(&self, mint: &str) -> &Arc<Mutex<PipelineEngine>> {
+        &self.shards[self.shard_index(mint)]
+    }
+
+    pub fn shards(&self) -> &[Arc<Mutex<PipelineEngine>>] {
+        &self.shards
+    }
+
+    /// Record a trade's session totals (no-op with one shard)
+    ///
+    /// Called once per trade before it is handed to its shard.
+    pub fn record_session(&self, trade: &TradeEvent) {
+        if let Some(sessions) = &self.sessions {
+            sessions.lock().unwrap().record(trade);
+        }
+    }
+
+    /// Process a trade on its shard, outside the per-shard ingestion tasks
+    pub fn process_trade(&self, trade: TradeEvent) {
+        self.record_session(&trade);
+        self.shard(&trade.mint).lock().unwrap().process_trade(trade);
+    }
+
+    /// Queue a TOKEN_MIGRATED signal on the mint's shard
+    pub fn process_migration(&self, migration: TokenMigration) {
+        self.shard(&migration.mint).lock().unwrap().process_migration(migration);
+    }
+
+    /// Record pooled SOL on the mint's shard
+    pub fn process_liquidity(&self, mint: &str, liquidity_sol: f64) {
+        self.shard(mint).lock().unwrap().process_liquidity(mint, liquidity_sol);
+    }
+
+    /// Record an on-chain pool price on the mint's shard
+    pub fn process_pool_price(&self, mint: &str, price_sol: f64) {
+        self.shard(mint).lock().unwrap().process_pool_price(mint, price_sol);
+    }
+
+    /// Current engine time (shards share the clock)
+    pub fn now(&self) -> i64 {
+        self.shards[0].lock().unwrap().now()
+    }
+
+    pub fn latest_price_sol(&self, mint: &str) -> Option<f64> {
+        self.shard(mint).lock().unwrap().latest_price_sol(mint)
+    }
+
+    pub fn is_tracking(&self, mint: &str) -> bool {
+        self.shard(mint).lock().unwrap().is_tracking(mint)
+    }
+
+    pub fn rolling_metrics(&self, mint: &str) -> Option<RollingMetrics> {
+        self.shard(mint).lock().unwrap().rolling_metrics(mint)
+    }
+
+    /// Mints with rolling state, across every shard
+    pub fn active_mints(&self) -> Vec<String> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.lock().unwrap().get_active_mints())
+            .collect()
+    }
+
+    pub fn prune_inactive_mints(&self, now: i64, threshold_secs: i64) {
+        for shard in &self.shards {
+            shard.lock().unwrap().prune_inactive_mints(now, threshold_secs);
+        }
+    }
+
+    /// Session rollup rows changed since the last call
+    pub fn drain_session_rollups(&self, now: i64) -> Vec<SessionRollup> {
+        match &self.sessions {
+            Some(sessions) => sessions.lock().unwrap().drain(now),
+            None => self.shards[0].lock().unwrap().drain_session_rollups(now),
+        }
+    }
+
+    /// Upgrade confirmations on every shard; returns trades upgraded
+    pub fn confirm_through_slot(&self, slot: u64, confirmation: Confirmation) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().confirm_through_slot(slot, confirmation))
+            .sum()
+    }
+
+    /// Roll back slots on every shard; returns trades removed
+    pub fn rollback_slots(&self, slots: RangeInclusive<u64>, now: i64) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().rollback_slots(slots.clone(), now))
+            .sum()
+    }
+
+    /// Apply thresholds to every shard; returns false if they were unchanged
+    pub fn set_signal_thresholds(&self, thresholds: SignalThresholdsConfig) -> bool {
+        if self.shards[0].lock().unwrap().signal_thresholds() == &thresholds {
+            return false;
+        }
+        for shard in &self.shards {
+            shard.lock().unwrap().set_signal_thresholds(thresholds.clone());
+        }
+        true
+    }
+
+    /// Snapshot every shard's mints (see `snapshot`)
+    pub fn snapshot(&self, now: i64) -> EngineSnapshot {
+        let mints = self
+            .shards
+            .iter()
+            .flat_map(|shard| shard.lock().unwrap().snapshot_mints())
+            .collect();
+        EngineSnapshot::new(now, mints)
+    }
+
+    /// Restore snapshotted mints onto their shards (the shard count may have changed)
+    pub fn restore(&self, snapshot: EngineSnapshot, now: i64) -> usize {
+        let mut by_shard: Vec<Vec<MintSnapshot>> = vec![Vec::new(); self.shards.len()];
+        for mint in snapshot.mints {
+            by_shard[self.shard_index(&mint.state.mint)].push(mint);
+        }
+        self.shards
+            .iter()
+            .zip(by_shard)
+            .map(|(shard, mints)| shard.lock().unwrap().restore_mints(mints, now))
+            .sum()
+    }
+
+    pub fn clear_touched_mints(&self) {
+        for shard in &self.shards {
+            shard.lock().unwrap().clear_touched_mints();
+        }
+    }
+
+    /// Record flush latency on the shards owning `mints`
+    pub fn record_flush_latency(&self, mints: &[String], flushed_at_ms: i64) {
+        if self.shards.len() == 1 {
+            self.shards[0].lock().unwrap().record_flush_latency(mints, flushed_at_ms);
+            return;
+        }
+        let mut by_shard: HashMap<usize, Vec<String>> = HashMap::new();
+        for mint in mints {
+            by_shard.entry(self.shard_index(mint)).or_default().push(mint.clone());
+        }
+        for (index, mints) in by_shard {
+            self.shards[index].lock().unwrap().record_flush_latency(&mints, flushed_at_ms);
+        }
+    }
+
+    /// Latency percentiles across all shards
+    pub fn global_latency_summary(&self) -> Option<LatencySummary> {
+        if self.shards.len() == 1 {
+            return self.shards[0].lock().unwrap().global_latency_summary();
+        }
+        let samples = self
+            .shards
+            .iter()
+            .flat_map(|shard| shard.lock().unwrap().latency_samples())
+            .collect();
+        summarize(samples)
+    }
+
+    /// Per-token latency percentiles (tokens never span shards)
+    pub fn per_mint_latency_summaries(&self) -> HashMap<String, LatencySummary> {
+        self.shards
+            .iter()
+            .flat_map(|shard| shard.lock().unwrap().per_mint_latency_summaries())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::sessions::MARKET_WIDE_MINT;
+
+    fn make_trade(mint: &str, timestamp: i64, user: &str) -> TradeEvent {
+        TradeEvent {
+            user_account: user.to_string(),
+            ..TradeEvent::test_buy(mint, timestamp)
+        }
+    }
+
+    #[test]
+    fn test_mints_stay_on_one_shard_and_sessions_merge() {
+        let now = 1_700_000_000;
+        let engine =
+            ShardedEngine::new(4, |_| PipelineEngine::new_with_timestamp_fn(Box::new(move || now)));
+        assert_eq!(engine.shard_count(), 4);
+
+        let mints: Vec<String> = (0..32).map(|i| format!("mint_{}", i)).collect();
+        for (i, mint) in mints.iter().enumerate() {
+            engine.process_trade(make_trade(mint, now, &format!("wallet_{}", i)));
+        }
+
+        // Each mint lives on exactly the shard it hashes to
+        for mint in &mints {
+            let owner = engine.shard_index(mint);
+            for (index, shard) in engine.shards().iter().enumerate() {
+                assert_eq!(shard.lock().unwrap().is_tracking(mint), index == owner);
+            }
+        }
+        let used: std::collections::HashSet<usize> =
+            mints.iter().map(|m| engine.shard_index(m)).collect();
+        assert!(used.len() > 1);
+
+        // The market-wide session row counts trades from every shard
+        let rollups = engine.drain_session_rollups(now);
+        let market = rollups
+            .iter()
+            .find(|r| r.mint == MARKET_WIDE_MINT)
+            .expect("market-wide rollup");
+        assert_eq!(market.buy_count, 32);
+    }
+}
@@ -0,0 +1,141 @@
+//! Coordinated shutdown across subsystems
+//!
+//! Ctrl+C used to stop the streamers with `ShutdownStrategy::Immediate` and
+//! exit after a fixed 2s sleep, dropping in-flight aggregates and buffered
+//! writer output. `Shutdown` broadcasts one stop request to every subsystem
+//! holding a `ShutdownSignal`, and the runtime then waits for them in order:
+//!
+//! 1. streamers stop ingesting and flush their writers (JSONL/SQLite/...)
+//! 2. ingestion drains the trade channel and does a final flush to the database
+//! 3. the TUI restores the terminal
+//!
+//! Everything shares one grace period (`SHUTDOWN_GRACE_SECS`, default: 10);
+//! subsystems still running when it ends are abandoned with a warning.
+
+use std::env;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+
+const DEFAULT_GRACE_SECS: u64 = 10;
+
+/// Sends the shutdown request
+pub struct Shutdown {
+    tx: broadcast::Sender<()>,
+    grace_period: Duration,
+}
+
+impl Shutdown {
+    pub fn new(grace_period: Duration) -> Self {
+        let (tx, _) = broadcast::channel(1);
+        Self { tx, grace_period }
+    }
+
+    /// Grace period from `SHUTDOWN_GRACE_SECS`
+    pub fn from_env() -> Self {
+        let grace_secs = env::var("SHUTDOWN_GRACE_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_GRACE_SECS);
+        Self::new(Duration::from_secs(grace_secs))
+    }
+
+    /// A signal for one subsystem (take it before triggering)
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal {
+            rx: self.tx.subscribe(),
+            triggered: false,
+        }
+    }
+
+    /// Ask every subsystem to stop; returns the deadline for waiting on them
+    pub fn trigger(&self) -> Instant {
+        let _ = self.tx.send(());
+        Instant::now() + self.grace_period
+    }
+
+    pub fn grace_period(&self) -> Duration {
+        self.grace_period
+    }
+}
+
+/// Receives the shutdown request
+///
+/// Dropping the `Shutdown` counts as a request, so a subsystem never waits
+/// on a sender that is gone.
+pub struct ShutdownSignal {
+    rx: broadcast::Receiver<()>,
+    triggered: bool,
+}
+
+impl ShutdownSignal {
+    /// Resolves once shutdown is requested
+    pub async fn recv(&mut self) {
+        if !self.triggered {
+            let _ = self.rx.recv().await;
+            self.triggered = true;
+        }
+    }
+
+    /// Whether shutdown has been requested (non-blocking)
+    pub fn is_triggered(&mut self) -> bool {
+        if !self.triggered {
+            self.triggered = !matches!(self.rx.try_recv(), Err(broadcast::error::TryRecvError::Empty));
+        }
+        self.triggered
+    }
+}
+
+impl Clone for ShutdownSignal {
+    fn clone(&self) -> Self {
+        Self {
+            rx: self.rx.resubscribe(),
+            triggered: self.triggered,
+        }
+    }
+}
+
+/// Wait for a subsystem's task until `deadline`; false if it was abandoned
+pub async fn join_until(name: &str, task: JoinHandle<()>, deadline: Instant) -> bool {
+    match tokio::time::timeout_at(deadline, task).await {
+        Ok(Ok(())) => {
+            log::info!("   ├─ {} stopped", name);
+            true
+        }
+        Ok(Err(e)) => {
+            log::error!("❌ {} task failed during shutdown: {}", name, e);
+            true
+        }
+        Err(_) => {
+            log::warn!("⚠️  {} still running after the shutdown grace period, abandoning it", name);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_signal_reaches_every_subsystem() {
+        let shutdown = Shutdown::new(Duration::from_millis(50));
+        let mut signal = shutdown.signal();
+        let mut clone = signal.clone();
+        assert!(!signal.is_triggered());
+
+        let waiter = tokio::spawn(async move { clone.recv().await });
+        let stuck = tokio::spawn(std::future::pending::<()>());
+
+        let deadline = shutdown.trigger();
+        assert!(signal.is_triggered());
+        signal.recv().await; // Already triggered: returns immediately
+        assert!(join_until("waiter", waiter, deadline).await);
+        assert!(!join_until("stuck", stuck, deadline).await);
+
+        // A dropped sender releases anyone still waiting
+        let mut orphan = Shutdown::new(Duration::ZERO).signal();
+        orphan.recv().await;
+        assert!(orphan.is_triggered());
+    }
+}
@@ -1,6 +1,23 @@
 //! Signal type definitions for the aggregate-only architecture
 //!
 //! Phase 1: Type definitions only (no detection logic)
+//!
+//! `details_json` is produced from the typed `SignalDetails` structs below and
+//! carries a `schema_version` field. Consumers should parse it with
+//! `SignalDetails::from_json`, which rejects unknown versions and malformed
+//! payloads instead of guessing.
+
+use crate::error::{codes, SolflowError};
+use serde::{Deserialize, Serialize};
+
+/// Current version of the `details_json` schema
+///
+/// Bump when a field is removed, renamed or changes meaning. Adding an
+/// optional field does not require a bump.
+pub const SIGNAL_DETAILS_SCHEMA_VERSION: u32 = 1;
+
+/// Source tag of signals produced by the on-chain detectors
+pub const SIGNAL_SOURCE_ONCHAIN: &str = "onchain";
 
 /// Signal types matching token_signals.signal_type column
 ///
@@ -12,6 +29,13 @@
 /// - SURGE: Sustained high volume over time window
 /// - BOT_DROPOFF: Sudden decrease in bot trading activity
 /// - DCA_CONVICTION: Jupiter DCA BUYs overlap with spot BUYs (accumulation signal)
+/// - POSITION_EXIT: An exit rule tripped for a token held in `positions`
+/// - EXTERNAL: Submitted through the signal webhook (source tagged, see `webhook`)
+/// - SMART_WALLET_ENTRY: A historically profitable wallet bought the token (see `wallets`)
+/// - REACTIVATION: Buy burst on a token with no trades for hours (dormant wake-up)
+/// - TOKEN_MIGRATED: A launchpad token left its bonding curve for an AMM pool
+/// - LIQUIDITY_DRAIN: SOL in the token's pools fell sharply from its 300s peak
+/// - Custom: Emitted by a user-registered `SignalDetector` (see `state`)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SignalType {
     Breakout,
@@ -19,6 +43,14 @@ pub enum SignalType {
     Surge,
     BotDropoff,
     DcaConviction,
+    PositionExit,
+    External,
+    SmartWalletEntry,
+    Reactivation,
+    TokenMigrated,
+    LiquidityDrain,
+    /// Database string of a custom detector's signal, e.g. "WHALE_ENTRY"
+    Custom(&'static str),
 }
 
 impl SignalType {
@@ -32,6 +64,13 @@ impl SignalType {
             SignalType::Surge => "SURGE",
             SignalType::BotDropoff => "BOT_DROPOFF",
             SignalType::DcaConviction => "DCA_CONVICTION",
+            SignalType::PositionExit => "POSITION_EXIT",
+            SignalType::External => "EXTERNAL",
+            SignalType::SmartWalletEntry => "SMART_WALLET_ENTRY",
+            SignalType::Reactivation => "REACTIVATION",
+            SignalType::TokenMigrated => "TOKEN_MIGRATED",
+            SignalType::LiquidityDrain => "LIQUIDITY_DRAIN",
+            SignalType::Custom(name) => name,
         }
     }
 }
@@ -72,6 +111,14 @@ pub struct TokenSignal {
     /// Unix timestamp when signal was created
     pub created_at: i64,
 
+    /// Where the signal came from: `onchain` for detector output, otherwise
+    /// the tag given by the external submitter (e.g. `telegram`)
+    pub source: String,
+
+    /// Recent signatures behind the signal, newest first (severity-5 signals
+    /// only; the notifier re-checks them over RPC, see `reconcile`)
+    pub sample_signatures: Vec<String>,
+
     // Note: sent_to_discord and seen_in_terminal are set by downstream
     // consumers and not included in this struct (they default to 0 in SQL)
 }
@@ -92,6 +139,8 @@ impl TokenSignal {
             score: None,
             details_json: None,
             created_at,
+            source: SIGNAL_SOURCE_ONCHAIN.to_string(),
+            sample_signatures: Vec::new(),
         }
     }
 
@@ -107,13 +156,283 @@ impl TokenSignal {
         self
     }
 
+    /// Set the signal source tag
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Attach the signatures of trades that contributed to the signal
+    pub fn with_sample_signatures(mut self, signatures: Vec<String>) -> Self {
+        self.sample_signatures = signatures;
+        self
+    }
+
     /// Set signal details as JSON string
     pub fn with_details(mut self, details_json: String) -> Self {
         self.details_json = Some(details_json);
         self
     }
+
+    /// Set signal details from a typed, versioned details struct
+    pub fn with_typed_details(self, details: SignalDetails) -> Self {
+        self.with_details(details.to_json())
+    }
+}
+
+/// BREAKOUT details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakoutDetails {
+    pub net_flow_60s: f64,
+    pub unique_wallets: i32,
+    /// Funding clusters among those wallets (see `wallet_graph`)
+    #[serde(default)]
+    pub unique_clusters: i32,
+    pub buy_ratio: f64,
+}
+
+/// FOCUSED details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusedDetails {
+    pub net_flow_300s: f64,
+    pub unique_wallets: i32,
+    /// Funding clusters among those wallets (see `wallet_graph`)
+    #[serde(default)]
+    pub unique_clusters: i32,
+    pub bot_ratio: f64,
+}
+
+/// SURGE details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurgeDetails {
+    pub net_flow_60s: f64,
+    pub volume_ratio: f64,
+    pub buy_count: i32,
+}
+
+/// BOT_DROPOFF details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BotDropoffDetails {
+    pub bot_decline_pct: f64,
+    pub prev_bot_count: i32,
+    pub new_wallets: i32,
+}
+
+/// DCA_CONVICTION details
+///
+/// Order-size fields are present only when DCA order accounts were decoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DcaConvictionDetails {
+    pub overlap_ratio: f64,
+    pub dca_buys: usize,
+    pub spot_buys: usize,
+    pub matched_dca: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dca_orders: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committed_sol_remaining: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining_cycles: Option<u64>,
+}
+
+/// POSITION_EXIT details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionExitDetails {
+    pub position_id: i64,
+    /// STOP_LOSS | SELL_PRESSURE | TAKE_PROFIT
+    pub reason: String,
+    pub entry_price_sol: f64,
+    pub price_sol: f64,
+    pub pnl_pct: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub net_flow_300s: Option<f64>,
+}
+
+/// EXTERNAL details (webhook submissions)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalDetails {
+    /// Short label from the submitter (e.g. "CALL", "KOL_MENTION")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// SMART_WALLET_ENTRY details (the wallet's record before this entry)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmartWalletEntryDetails {
+    pub wallet: String,
+    pub sol_amount: f64,
+    pub win_rate: f64,
+    pub closed_trades: u32,
+    pub realized_pnl_sol: f64,
+    pub win_streak: u32,
+}
+
+/// REACTIVATION details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReactivationDetails {
+    /// Hours without trades before the burst
+    pub idle_hours: f64,
+    pub buy_count_300s: i32,
+    pub net_flow_300s: f64,
+    pub unique_wallets: i32,
+}
+
+/// TOKEN_MIGRATED details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenMigratedDetails {
+    /// Launch platform the token graduated from, e.g. "pumpfun"
+    pub launch_platform: String,
+    /// Venue the liquidity moved to, e.g. "pumpswap"
+    pub destination: String,
+    pub pool: String,
+    /// SOL seeded into the new pool
+    pub sol_amount: f64,
+    pub signature: String,
+}
+
+/// LIQUIDITY_DRAIN details
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityDrainDetails {
+    /// Highest pooled SOL in the 300s window
+    pub peak_liquidity_sol: f64,
+    pub liquidity_sol: f64,
+    /// Drop from the peak, in percent
+    pub drop_pct: f64,
+}
+
+/// Typed per-signal details stored in `token_signals.details_json`
+///
+/// Serialized form: `{"schema_version":1,"signal_type":"BREAKOUT",...fields}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "signal_type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SignalDetails {
+    Breakout(BreakoutDetails),
+    Focused(FocusedDetails),
+    Surge(SurgeDetails),
+    BotDropoff(BotDropoffDetails),
+    DcaConviction(DcaConvictionDetails),
+    PositionExit(PositionExitDetails),
+    External(ExternalDetails),
+    SmartWalletEntry(SmartWalletEntryDetails),
+    Reactivation(ReactivationDetails),
+    TokenMigrated(TokenMigratedDetails),
+    LiquidityDrain(LiquidityDrainDetails),
+}
+
+/// Versioned wrapper written to the database
+#[derive(Serialize, Deserialize)]
+struct DetailsEnvelope {
+    schema_version: u32,
+    #[serde(flatten)]
+    details: SignalDetails,
+}
+
+impl SignalDetails {
+    /// Signal type these details belong to
+    pub fn signal_type(&self) -> SignalType {
+        match self {
+            SignalDetails::Breakout(_) => SignalType::Breakout,
+            SignalDetails::Focused(_) => SignalType::Focused,
+            SignalDetails::Surge(_) => SignalType::Surge,
+            SignalDetails::BotDropoff(_) => SignalType::BotDropoff,
+            SignalDetails::DcaConviction(_) => SignalType::DcaConviction,
+            SignalDetails::PositionExit(_) => SignalType::PositionExit,
+            SignalDetails::External(_) => SignalType::External,
+            SignalDetails::SmartWalletEntry(_) => SignalType::SmartWalletEntry,
+            SignalDetails::Reactivation(_) => SignalType::Reactivation,
+            SignalDetails::TokenMigrated(_) => SignalType::TokenMigrated,
+            SignalDetails::LiquidityDrain(_) => SignalType::LiquidityDrain,
+        }
+    }
+
+    /// Serialize with the current schema version
+    pub fn to_json(&self) -> String {
+        let envelope = DetailsEnvelope {
+            schema_version: SIGNAL_DETAILS_SCHEMA_VERSION,
+            details: self.clone(),
+        };
+        // Plain structs of numbers cannot fail to serialize
+        serde_json::to_string(&envelope).expect("signal details serialize")
+    }
+
+    /// Parse and validate a `details_json` payload
+    ///
+    /// Fails on unknown schema versions, unknown signal types, or missing /
+    /// mistyped fields.
+    pub fn from_json(json: &str) -> Result<Self, SolflowError> {
+        let envelope: DetailsEnvelope = serde_json::from_str(json)
+            .map_err(|e| SolflowError::db(codes::DB_INVALID_DATA, e))?;
+        if envelope.schema_version != SIGNAL_DETAILS_SCHEMA_VERSION {
+            return Err(SolflowError::db(
+                codes::DB_INVALID_DATA,
+                format!(
+                    "unsupported details schema_version {} (expected {})",
+                    envelope.schema_version, SIGNAL_DETAILS_SCHEMA_VERSION
+                ),
+            ));
+        }
+        Ok(envelope.details)
+    }
 }
 
-// TODO: Phase 3-C - Add helper methods for JSON serialization
-// - fn to_json(&self) -> String - Serialize entire signal to JSON for logging
-// - fn from_json(json: &str) -> Result<Self> - Deserialize from JSON
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_details_round_trip() {
+        let details = SignalDetails::Breakout(BreakoutDetails {
+            net_flow_60s: 12.5,
+            unique_wallets: 8,
+            unique_clusters: 6,
+            buy_ratio: 0.9,
+        });
+
+        let json = details.to_json();
+        assert!(json.contains(r#""schema_version":1"#));
+        assert!(json.contains(r#""signal_type":"BREAKOUT""#));
+        assert_eq!(SignalDetails::from_json(&json).unwrap(), details);
+    }
+
+    #[test]
+    fn test_signal_type_tag_matches_db_string() {
+        let details = SignalDetails::BotDropoff(BotDropoffDetails {
+            bot_decline_pct: 80.0,
+            prev_bot_count: 10,
+            new_wallets: 4,
+        });
+        let value: serde_json::Value = serde_json::from_str(&details.to_json()).unwrap();
+        assert_eq!(value["signal_type"], details.signal_type().as_str());
+    }
+
+    #[test]
+    fn test_optional_dca_fields_omitted() {
+        let details = SignalDetails::DcaConviction(DcaConvictionDetails {
+            overlap_ratio: 0.5,
+            dca_buys: 4,
+            spot_buys: 10,
+            matched_dca: 2,
+            dca_orders: None,
+            committed_sol_remaining: None,
+            remaining_cycles: None,
+        });
+        assert!(!details.to_json().contains("committed_sol_remaining"));
+    }
+
+    #[test]
+    fn test_rejects_unknown_version_and_malformed() {
+        assert!(SignalDetails::from_json(
+            r#"{"schema_version":99,"signal_type":"SURGE","net_flow_60s":1.0,"volume_ratio":3.0,"buy_count":10}"#
+        )
+        .is_err());
+        assert!(SignalDetails::from_json(
+            r#"{"schema_version":1,"signal_type":"SURGE","net_flow_60s":1.0}"#
+        )
+        .is_err());
+        assert!(SignalDetails::from_json(r#"{"net_flow_60s":1.0}"#).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+//! Engine state snapshots across restarts
+//!
+//! Rolling windows live in memory, so a restart loses up to 4 hours of
+//! context (15 minutes of trades, the minute buckets behind the 1h-4h
+//! windows) and re-emits every signal that was already active. With
+//! `ENGINE_SNAPSHOT_PATH` set, the runtime periodically writes each mint's
+//! `TokenRollingState`, last bot count and active signal types to one JSON
+//! file, and writes a final one on shutdown.
+//!
+//! On startup the snapshot is loaded unless it is older than
+//! `ENGINE_SNAPSHOT_MAX_AGE_SECS`. Restored states are evicted to the current
+//! time, so trades that fell out of their windows while the runtime was down
+//! are dropped and mints idle for longer than the longest window are skipped.
+//!
+//! The event journal's checkpoint trails the snapshot, so trades replayed
+//! from it after a restore can already be in the restored windows; the
+//! runtime skips replayed trades listed in `EngineSnapshot::trade_keys`.
+//!
+//! The file is replaced atomically (write to `<path>.tmp`, then rename).
+
+use super::state::TokenRollingState;
+use super::window_set::MAX_WINDOW_SECS;
+use crate::error::{codes, SolflowError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Bumped when the snapshot layout changes; other versions are ignored
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// One mint's engine state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MintSnapshot {
+    pub state: TokenRollingState,
+    /// Last bot_trades_300s, for BOT_DROPOFF
+    pub last_bot_count: Option<i32>,
+    /// Signal types currently active (not re-emitted after restore)
+    pub active_signals: Vec<String>,
+}
+
+impl MintSnapshot {
+    /// Whether the mint traded within the longest window before `now`
+    pub fn is_live(&self, now: i64) -> bool {
+        self.state.last_seen_ts >= now - MAX_WINDOW_SECS
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub version: u32,
+    pub taken_at: i64,
+    pub mints: Vec<MintSnapshot>,
+}
+
+impl EngineSnapshot {
+    pub fn new(taken_at: i64, mints: Vec<MintSnapshot>) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            taken_at,
+            mints,
+        }
+    }
+
+    /// (signature, trade index) of every signed trade in the snapshot's windows
+    pub fn trade_keys(&self) -> HashSet<(String, u16)> {
+        self.mints
+            .iter()
+            .flat_map(|mint| mint.state.trades_900s())
+            .filter(|trade| !trade.signature.is_empty())
+            .map(|trade| (trade.signature.clone(), trade.trade_index))
+            .collect()
+    }
+}
+
+/// Write `snapshot` to `path`, replacing any previous snapshot atomically
+pub fn write_snapshot(path: &Path, snapshot: &EngineSnapshot) -> Result<(), SolflowError> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_vec(snapshot)
+        .map_err(|e| SolflowError::db(codes::DB_INVALID_DATA, e))?;
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Load the snapshot at `path`
+///
+/// None when there is no snapshot, it was taken more than `max_age_secs`
+/// before `now`, or it has another `SNAPSHOT_VERSION`.
+pub fn read_snapshot(
+    path: &Path,
+    now: i64,
+    max_age_secs: i64,
+) -> Result<Option<EngineSnapshot>, SolflowError> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let snapshot: EngineSnapshot = serde_json::from_slice(&bytes).map_err(|e| {
+        SolflowError::db(
+            codes::DB_INVALID_DATA,
+            format!("Invalid engine snapshot {}: {}", path.display(), e),
+        )
+    })?;
+
+    if snapshot.version != SNAPSHOT_VERSION {
+        log::warn!(
+            "⚠️  Ignoring engine snapshot {} (version {}, expected {})",
+            path.display(),
+            snapshot.version,
+            SNAPSHOT_VERSION
+        );
+        return Ok(None);
+    }
+    if now - snapshot.taken_at > max_age_secs {
+        log::info!(
+            "⏭️  Ignoring engine snapshot {} ({}s old, max {}s)",
+            path.display(),
+            now - snapshot.taken_at,
+            max_age_secs
+        );
+        return Ok(None);
+    }
+    Ok(Some(snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::engine::PipelineEngine;
+    use crate::pipeline::journal::{EventJournal, JournalConfig};
+    use crate::pipeline::types::TradeEvent;
+    use tempfile::TempDir;
+
+    fn trade(mint: &str, timestamp: i64, wallet: &str) -> TradeEvent {
+        TradeEvent {
+            user_account: wallet.to_string(),
+            signature: format!("sig_{}_{}", wallet, timestamp),
+            slot: timestamp as u64,
+            ..TradeEvent::test_buy(mint, timestamp)
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_evicts_stale_trades() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("engine.json");
+        assert!(read_snapshot(&path, 0, 3600).unwrap().is_none());
+
+        let mut engine = PipelineEngine::new_with_timestamp_fn(Box::new(|| 100_000));
+        engine.process_trade(trade("active", 99_150, "w1")); // leaves the 900s buffer by 100_100
+        engine.process_trade(trade("active", 99_950, "w2"));
+        engine.process_trade(trade("idle", 100_000 - MAX_WINDOW_SECS + 50, "w3"));
+        write_snapshot(&path, &EngineSnapshot::new(100_000, engine.snapshot_mints())).unwrap();
+
+        // Too old to restore
+        assert!(read_snapshot(&path, 110_000, 3600).unwrap().is_none());
+
+        let snapshot = read_snapshot(&path, 100_100, 3600).unwrap().unwrap();
+        assert_eq!(snapshot.mints.len(), 2);
+
+        let mut restored = PipelineEngine::new_with_timestamp_fn(Box::new(|| 100_100));
+        assert_eq!(restored.restore_mints(snapshot.mints, 100_100), 1);
+        assert!(restored.is_tracking("active"));
+        assert!(!restored.is_tracking("idle"));
+
+        let (metrics, _, _) = restored.compute_metrics("active", 100_100).unwrap();
+        assert_eq!(metrics.buy_count_900s, 1);
+        assert_eq!(metrics.unique_wallets_300s, 1);
+    }
+
+    #[test]
+    fn test_journal_replay_after_restore_skips_snapshotted_trades() {
+        let dir = TempDir::new().unwrap();
+        let config = JournalConfig {
+            dir: dir.path().join("journal"),
+            segment_bytes: 64 * 1024,
+        };
+
+        // Trades journaled but never checkpointed; the snapshot lands between them
+        let mut engine = PipelineEngine::new_with_timestamp_fn(Box::new(|| 100_000));
+        let mut journal = EventJournal::open(config.clone()).unwrap();
+        for (timestamp, wallet) in [(99_900, "w1"), (99_950, "w2")] {
+            journal.append(&trade("mint", timestamp, wallet)).unwrap();
+            engine.process_trade(trade("mint", timestamp, wallet));
+        }
+        let snapshot = EngineSnapshot::new(100_000, engine.snapshot_mints());
+        journal.append(&trade("mint", 99_990, "w3")).unwrap();
+        drop(journal);
+
+        // Restart: restore, then replay the journal as the runtime does
+        let keys = snapshot.trade_keys();
+        assert_eq!(keys.len(), 2);
+        let mut restored = PipelineEngine::new_with_timestamp_fn(Box::new(|| 100_010));
+        restored.restore_mints(snapshot.mints, 100_010);
+        let journal = EventJournal::open(config).unwrap();
+        let mut skipped = 0;
+        let replayed = journal
+            .replay(|trade| {
+                if keys.contains(&(trade.signature.clone(), trade.trade_index)) {
+                    skipped += 1;
+                } else {
+                    restored.process_trade(trade);
+                }
+            })
+            .unwrap();
+        assert_eq!((replayed, skipped), (3, 2));
+
+        let (metrics, _, _) = restored.compute_metrics("mint", 100_010).unwrap();
+        assert_eq!(metrics.buy_count_900s, 3);
+    }
+}
@@ -3,21 +3,35 @@
 //! Phase 2: Rolling window logic and lifecycle methods implemented
 //! Phase 3-A: Bot detection implemented
 //! Phase 3-B: Signal detection implemented
-
-use super::types::{TradeDirection, TradeEvent};
-use super::signals::{SignalType, TokenSignal};
-use std::collections::{HashMap, HashSet, VecDeque};
+//!
+//! Signals come from `SignalDetector`s in a `SignalDetectorRegistry`; the
+//! built-in detectors are registered by default and custom ones can be added
+//! on the engine. Built-in thresholds come from a `SignalThresholdsConfig`
+//! (see `thresholds`).
+
+use super::minute_buckets::MinuteBuckets;
+use super::thresholds::SignalThresholdsConfig;
+use super::types::{Confirmation, DcaOrderInfo, TradeDirection, TradeEvent};
+use super::window_set::{WindowMetrics, WindowSet, TRADE_BUFFER_SECS};
+use super::signals::{
+    BotDropoffDetails, BreakoutDetails, DcaConvictionDetails, FocusedDetails,
+    LiquidityDrainDetails, ReactivationDetails, SignalDetails, SignalType, SurgeDetails, TokenSignal,
+};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, OnceLock};
 
 /// Per-token rolling state container
 ///
-/// Maintains rolling buffers for six time windows:
+/// Maintains one time-ordered trade buffer for the short windows:
 /// - 60s (1 minute)
 /// - 300s (5 minutes)
 /// - 900s (15 minutes)
-/// - 3600s (1 hour)
-/// - 7200s (2 hours)
-/// - 14400s (4 hours)
-#[derive(Debug, Clone)]
+///
+/// Each window is a suffix of the 900s buffer (`trades_60s()` etc.), so a
+/// trade is stored once. Minute buckets cover the long windows (3600s,
+/// 7200s, 14400s).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TokenRollingState {
     /// Token mint address
     pub mint: String,
@@ -25,34 +39,50 @@ pub struct TokenRollingState {
     /// Phase 5: Last timestamp when this mint received a trade (for pruning)
     pub last_seen_ts: i64,
 
-    /// Rolling buffer: trades in last 60 seconds
-    pub trades_60s: Vec<TradeEvent>,
-
-    /// Rolling buffer: trades in last 300 seconds (5 minutes)
-    pub trades_300s: Vec<TradeEvent>,
-
-    /// Rolling buffer: trades in last 900 seconds (15 minutes)
-    pub trades_900s: Vec<TradeEvent>,
+    /// Timestamp of the first trade since this state was (re)created
+    /// Used for per-token signal warm-up after a cold start
+    pub first_seen_ts: i64,
 
-    /// Rolling buffer: trades in last 3600 seconds (1 hour)
-    pub trades_3600s: Vec<TradeEvent>,
+    /// Trades in the last 900 seconds, ordered by timestamp (arrival order
+    /// for equal timestamps). Kept contiguous so windows can be sliced.
+    trades: VecDeque<TradeEvent>,
 
-    /// Rolling buffer: trades in last 7200 seconds (2 hours)
-    pub trades_7200s: Vec<TradeEvent>,
+    /// Latest eviction time; window `secs` holds trades at or after `window_now - secs`
+    window_now: i64,
 
-    /// Rolling buffer: trades in last 14400 seconds (4 hours)
-    pub trades_14400s: Vec<TradeEvent>,
+    /// Per-minute aggregates for the 3600s/7200s/14400s windows
+    pub minute_buckets: MinuteBuckets,
 
     /// Unique wallet addresses in 300s window
     pub unique_wallets_300s: HashSet<String>,
 
+    /// Wallets in the 300s window whose first trade on this mint is also in the window
+    pub new_wallets_300s: HashSet<String>,
+
+    /// First and most recent trade timestamp per wallet on this mint
+    /// Key: wallet; Value: (first_trade_ts, last_trade_ts)
+    ///
+    /// Pruned by last trade with the 14400s window, so a wallet idle for
+    /// longer than 4 hours counts as new again when it returns. Capped at
+    /// `MAX_WALLET_FIRST_SEEN` by dropping the least recently active wallets.
+    pub wallet_first_seen: HashMap<String, (i64, i64)>,
+
     /// Bot wallet addresses in 300s window
     pub bot_wallets_300s: HashSet<String>,
 
-    /// Trades grouped by source program (for DCA correlation)
+    /// Per-wallet bot classifications reused between flushes
+    pub bot_cache: HashMap<String, BotClassification>,
+
+    /// Wallets whose 300s window changed since their last classification
+    pub bot_cache_dirty: HashSet<String>,
+
+    /// Per-program activity summaries (for DCA correlation)
     /// Key: source_program (e.g., "PumpSwap", "BonkSwap", "Moonshot", "JupiterDCA")
-    /// Value: Vector of trades from that program
-    pub trades_by_program: HashMap<String, Vec<TradeEvent>>,
+    /// Value: Timestamp-only summary of trades from that program
+    ///
+    /// Stores timestamps instead of cloned TradeEvents: correlation only needs
+    /// BUY timing, so full trade copies were pure overhead.
+    pub program_activity: HashMap<String, ProgramActivity>,
 
     /// DCA rolling windows: timestamps of JupiterDCA BUY trades
     /// Phase 6: DCA Rolling Windows (feature/dca-rolling-windows)
@@ -64,6 +94,135 @@ pub struct TokenRollingState {
     pub dca_timestamps_900s: VecDeque<i64>,
     pub dca_timestamps_3600s: VecDeque<i64>,
     pub dca_timestamps_14400s: VecDeque<i64>,
+
+    /// Latest decoded context per Jupiter DCA order filling into this token
+    /// Key: DCA order account; Value: (order context, timestamp of most recent fill)
+    ///
+    /// Pruned with the 14400s window so finished orders stop counting toward
+    /// committed SOL once their fills age out.
+    pub dca_orders: HashMap<String, (DcaOrderInfo, i64)>,
+
+    /// Signature and slot of the most recent JupiterDCA BUY (for bucket tracing)
+    pub last_dca_trade: Option<(String, u64)>,
+
+    /// First `EARLY_BUYER_COUNT` distinct buyers seen for this mint, in order
+    /// Value: tokens bought and sold since their first buy (never pruned)
+    pub early_buyers: Vec<(String, EarlyHolder)>,
+
+    /// Lowest slot of a buffered trade not yet finalized (None when all are)
+    pub unfinalized_min_slot: Option<u64>,
+
+    /// Most recent resumption of trading after at least `MIN_WAKE_IDLE_SECS`
+    /// without trades (REACTIVATION)
+    #[serde(default)]
+    pub wake_up: Option<WakeUp>,
+
+    /// SOL in the mint's known pool vaults as (timestamp, sol), oldest first
+    ///
+    /// Samples older than 300s are pruned, except the latest (LIQUIDITY_DRAIN).
+    #[serde(default)]
+    pub liquidity: VecDeque<(i64, f64)>,
+
+    /// Latest price read from the mint's pool state account (see
+    /// `streamer_core::pool_state`), SOL per token
+    #[serde(default)]
+    pub pool_price_sol: Option<f64>,
+}
+
+/// Shortest trading gap recorded as a wake-up
+pub const MIN_WAKE_IDLE_SECS: i64 = 3600;
+
+/// Trading resumed on a mint that had gone quiet
+///
+/// The engine remembers the last trade of pruned mints, so a wake-up is
+/// detected even when the mint's state was dropped while it was dormant.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WakeUp {
+    /// Timestamp of the first trade after the gap
+    pub resumed_at: i64,
+    /// Seconds between the previous trade and `resumed_at`
+    pub idle_secs: i64,
+}
+
+/// Minutes of per-minute net flow published for sparklines
+const SPARKLINE_MINUTES: usize = 60;
+
+/// Number of first buyers tracked per mint for early-holder retention
+pub const EARLY_BUYER_COUNT: usize = 20;
+
+/// Fraction of bought tokens an early buyer must still hold to count as holding
+const EARLY_HOLDER_MIN_RETAINED: f64 = 0.1;
+
+/// Maximum wallets kept in a mint's first-seen history
+const MAX_WALLET_FIRST_SEEN: usize = 20_000;
+
+/// Size the first-seen history is trimmed to once over the cap, so a busy mint
+/// isn't re-trimmed on every flush
+const WALLET_FIRST_SEEN_TRIM_TO: usize = MAX_WALLET_FIRST_SEEN * 3 / 4;
+
+/// Net token position of an early buyer
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct EarlyHolder {
+    pub tokens_bought: f64,
+    pub tokens_sold: f64,
+}
+
+impl EarlyHolder {
+    /// Still holding at least `EARLY_HOLDER_MIN_RETAINED` of what they bought
+    pub fn is_holding(&self) -> bool {
+        self.tokens_bought > 0.0
+            && self.tokens_bought - self.tokens_sold >= self.tokens_bought * EARLY_HOLDER_MIN_RETAINED
+    }
+}
+
+/// Timestamp-only summary of one program's trades for a token
+///
+/// Both queues are ordered oldest-first and pruned to the longest window (14400s).
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ProgramActivity {
+    /// Timestamps of all trades from this program
+    pub trade_timestamps: VecDeque<i64>,
+
+    /// Timestamps of BUY trades from this program
+    pub buy_timestamps: VecDeque<i64>,
+}
+
+impl ProgramActivity {
+    /// Record a trade from this program
+    fn record(&mut self, timestamp: i64, direction: TradeDirection) {
+        self.trade_timestamps.push_back(timestamp);
+        if direction == TradeDirection::Buy {
+            self.buy_timestamps.push_back(timestamp);
+        }
+    }
+
+    /// Drop timestamps older than the cutoff
+    fn evict_before(&mut self, cutoff: i64) {
+        while self.trade_timestamps.front().is_some_and(|&ts| ts < cutoff) {
+            self.trade_timestamps.pop_front();
+        }
+        while self.buy_timestamps.front().is_some_and(|&ts| ts < cutoff) {
+            self.buy_timestamps.pop_front();
+        }
+    }
+
+    /// Number of trades from this program still in the window
+    pub fn trade_count(&self) -> usize {
+        self.trade_timestamps.len()
+    }
+
+    /// True when no trades from this program remain
+    pub fn is_empty(&self) -> bool {
+        self.trade_timestamps.is_empty()
+    }
+}
+
+/// Net flow and trade counts for one venue (source program)
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct ProgramFlow {
+    pub net_flow_sol: f64,
+    pub buy_count: i32,
+    pub sell_count: i32,
 }
 
 /// Internal metrics snapshot computed from rolling windows
@@ -94,6 +253,39 @@ pub struct RollingMetrics {
 
     // Advanced metrics (300s window)
     pub unique_wallets_300s: i32,
+    // Funding-linked wallets counted once (see `wallet_graph`); equals
+    // unique_wallets_300s until the engine resolves clusters
+    pub unique_clusters_300s: i32,
+    pub new_wallets_300s: i32,
+
+    // Unique wallets (1h/2h/4h windows), estimated by merging the minute
+    // buckets' HyperLogLog sketches instead of holding hours of wallet sets.
+    // Exact up to 64 distinct wallets; above that the standard error is
+    // ~3.3% (`hll::STANDARD_ERROR`), i.e. within ±6.5% for ~95% of values.
+    // `unique_wallets_300s` stays an exact count.
+    pub unique_wallets_3600s: i32,
+    pub unique_wallets_7200s: i32,
+    pub unique_wallets_14400s: i32,
+    pub fees_paid_300s_sol: f64,
+    // Paid on top of the base fee to land (300s window)
+    pub priority_fees_300s_sol: f64,
+    pub jito_tips_300s_sol: f64,
+
+    // Per-venue breakdown (300s window), keyed by source program
+    pub program_breakdown_300s: BTreeMap<String, ProgramFlow>,
+
+    // Early holders: first buyers seen and the share still holding
+    pub early_buyers_count: i32,
+    pub early_holder_retention: Option<f64>,
+
+    // Share of buyers (with resolved wallet age) whose wallets are fresh;
+    // filled in by the engine when wallet-age enrichment is enabled
+    pub fresh_wallet_ratio_60s: Option<f64>,
+    pub fresh_wallet_ratio_300s: Option<f64>,
+    pub fresh_wallet_ratio_900s: Option<f64>,
+
+    // Net flow per minute for the last hour (minute_start, net_flow_sol), oldest first
+    pub net_flow_by_minute_60m: Vec<(i64, f64)>,
     
     // Bot detection metrics (Phase 3-A)
     pub bot_wallets_count_300s: i32,
@@ -106,6 +298,26 @@ pub struct RollingMetrics {
     pub dca_buys_900s: i32,
     pub dca_buys_3600s: i32,
     pub dca_buys_14400s: i32,
+
+    // Final view (finalized trades only); the flows and counts above are the
+    // fast view and also include Processed/Confirmed trades
+    pub net_flow_60s_final_sol: f64,
+    pub net_flow_300s_final_sol: f64,
+    pub net_flow_900s_final_sol: f64,
+    pub buy_count_300s_final: i32,
+    pub sell_count_300s_final: i32,
+    pub unfinalized_trades_300s: i32,
+
+    // Net flow and counts for every configured window (see `window_set`),
+    // shortest first; the core 60s/300s/900s fields above are copied from here
+    pub windows: Vec<WindowMetrics>,
+}
+
+impl RollingMetrics {
+    /// Metrics for a configured window
+    pub fn window(&self, secs: i64) -> Option<&WindowMetrics> {
+        self.windows.iter().find(|window| window.secs == secs)
+    }
 }
 
 /// Bot detection heuristics applied to a trade window
@@ -117,15 +329,23 @@ pub struct RollingMetrics {
 /// 2. Rapid consecutive trades: Multiple trades within 1 second
 /// 3. Alternating buy/sell patterns: Repeated flip-flopping
 /// 4. Near-identical trade sizes: Repeated same SOL amounts
+/// 5. Paying to land every trade: a Jito tip or a priority fee of at least
+///    `BOT_PRIORITY_FEE_LAMPORTS` on each of 3+ trades
 ///
-/// Returns: (Set of bot wallet addresses, total count of trades from bots)
+/// Only wallets for which `needs_eval` returns true are grouped and checked,
+/// so cached wallets skip the per-wallet heuristics entirely.
+///
+/// Returns: wallet → (is_bot, trade count in window)
 ///
 /// TODO: Phase 3+ refinements
 /// - Add MEV transaction pattern detection
 /// - Integrate known bot wallet blocklist
 /// - Tune thresholds based on production data
 /// - Add probabilistic scoring (0.0-1.0) instead of binary classification
-fn detect_bot_wallets(trades: &[TradeEvent]) -> (HashSet<String>, i32) {
+fn classify_wallets(
+    trades: &[TradeEvent],
+    needs_eval: impl Fn(&str) -> bool,
+) -> HashMap<String, (bool, usize)> {
     // Wallet-level statistics for bot detection
     #[derive(Debug, Default)]
     struct WalletStats {
@@ -133,12 +353,16 @@ fn detect_bot_wallets(trades: &[TradeEvent]) -> (HashSet<String>, i32) {
         timestamps: Vec<i64>,
         directions: Vec<TradeDirection>,
         sol_amounts: Vec<f64>,
+        paid_to_land: usize,
     }
 
     // Group trades by wallet
     let mut wallet_stats: HashMap<String, WalletStats> = HashMap::new();
     
     for trade in trades {
+        if !needs_eval(&trade.user_account) {
+            continue;
+        }
         let stats = wallet_stats
             .entry(trade.user_account.clone())
             .or_default();
@@ -147,12 +371,14 @@ fn detect_bot_wallets(trades: &[TradeEvent]) -> (HashSet<String>, i32) {
         stats.timestamps.push(trade.timestamp);
         stats.directions.push(trade.direction);
         stats.sol_amounts.push(trade.sol_amount);
+        if trade.jito_tip_lamports > 0 || trade.priority_fee_lamports >= BOT_PRIORITY_FEE_LAMPORTS {
+            stats.paid_to_land += 1;
+        }
     }
 
-    let mut bot_wallets = HashSet::new();
-    let mut bot_trades_count = 0;
+    let mut classifications = HashMap::with_capacity(wallet_stats.len());
 
-    for (wallet, stats) in wallet_stats.iter() {
+    for (wallet, stats) in wallet_stats {
         let mut is_bot = false;
 
         // Heuristic 1: High-frequency trading (> 10 trades in 300s)
@@ -218,41 +444,137 @@ fn detect_bot_wallets(trades: &[TradeEvent]) -> (HashSet<String>, i32) {
             }
         }
 
-        if is_bot {
-            bot_wallets.insert(wallet.clone());
-            bot_trades_count += stats.trade_count as i32;
+        // Heuristic 5: Every trade bundled with a Jito tip or a high priority fee
+        if !is_bot && stats.trade_count >= 3 && stats.paid_to_land == stats.trade_count {
+            is_bot = true;
         }
+
+        classifications.insert(wallet, (is_bot, stats.trade_count));
     }
 
-    (bot_wallets, bot_trades_count)
+    classifications
 }
 
-/// Signal detection configuration constants
+/// Priority fee (lamports) above which a trade counts as paying to land
 ///
-/// Phase 3-B: Signal Detection Implementation
+/// 0.001 SOL is well above what wallets pay by default, even when congested.
+const BOT_PRIORITY_FEE_LAMPORTS: u64 = 1_000_000;
+
+/// How long a cached bot classification is trusted without re-evaluation
 ///
-/// These thresholds control signal triggering sensitivity.
-/// TODO: Tune based on production data and false positive rates.
-mod signal_thresholds {
-    // BREAKOUT thresholds
-    pub const BREAKOUT_NET_FLOW_60S_MIN: f64 = 5.0; // Min 5 SOL net inflow in 60s
-    pub const BREAKOUT_WALLET_GROWTH_MIN: i32 = 5; // Min 5 new unique wallets
-    pub const BREAKOUT_BUY_RATIO_MIN: f64 = 0.75; // 75% buys vs total trades
-    
-    // FOCUSED thresholds
-    pub const FOCUSED_WALLET_CONCENTRATION_MAX: f64 = 0.3; // Max 30% of volume from single wallet
-    pub const FOCUSED_MIN_VOLUME: f64 = 3.0; // Min 3 SOL volume
-    pub const FOCUSED_BOT_RATIO_MAX: f64 = 0.2; // Max 20% bot trades
-    
-    // SURGE thresholds
-    pub const SURGE_VOLUME_RATIO_MIN: f64 = 3.0; // 60s volume ≥ 3x average 300s volume
-    pub const SURGE_BUY_COUNT_60S_MIN: i32 = 10; // Min 10 buys in 60s
-    pub const SURGE_NET_FLOW_60S_MIN: f64 = 8.0; // Min 8 SOL net inflow
-    
-    // BOT_DROPOFF thresholds
-    pub const BOT_DROPOFF_DECLINE_RATIO_MIN: f64 = 0.5; // 50%+ bot trade decline
-    pub const BOT_DROPOFF_MIN_PREVIOUS_BOTS: i32 = 5; // Need at least 5 bot trades before
-    pub const BOT_DROPOFF_NEW_WALLET_MIN: i32 = 3; // Min 3 new wallets entering
+/// Classifications are also invalidated whenever a wallet's 300s window
+/// changes (new trade or eviction), so the TTL only bounds staleness.
+const BOT_CACHE_TTL_SECS: i64 = 30;
+
+/// Cached bot classification for one wallet in the 300s window
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BotClassification {
+    pub is_bot: bool,
+    /// Wallet's trade count in the 300s window at evaluation time
+    pub trade_count: usize,
+    /// Timestamp of the evaluation
+    pub evaluated_at: i64,
+}
+
+/// Which wallet count BREAKOUT uses for its wallet-growth check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WalletGrowthMetric {
+    /// Any wallet active in the 300s window, funding-linked wallets
+    /// counted once (`unique_clusters_300s`)
+    #[default]
+    AnyActivity,
+    /// Only wallets whose first trade on the mint is in the window (`new_wallets_300s`)
+    NewWallets,
+}
+
+impl WalletGrowthMetric {
+    /// Parse from `BREAKOUT_WALLET_METRIC` (`any` | `new`)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "any" | "unique" => Some(Self::AnyActivity),
+            "new" => Some(Self::NewWallets),
+            _ => None,
+        }
+    }
+
+    /// Wallet count from metrics according to this mode
+    pub fn count(&self, metrics: &RollingMetrics) -> i32 {
+        match self {
+            Self::AnyActivity => metrics.unique_clusters_300s,
+            Self::NewWallets => metrics.new_wallets_300s,
+        }
+    }
+}
+
+/// How trades with outsized SOL amounts are treated in window metrics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutlierMode {
+    /// Use every trade as-is
+    #[default]
+    Off,
+    /// Clamp outlier SOL amounts to the threshold
+    Cap,
+    /// Drop outlier trades from flows and counts
+    Exclude,
+}
+
+impl OutlierMode {
+    /// Parse from `OUTLIER_MODE` (`off` | `cap` | `exclude`)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "off" | "none" => Some(Self::Off),
+            "cap" => Some(Self::Cap),
+            "exclude" => Some(Self::Exclude),
+            _ => None,
+        }
+    }
+}
+
+/// Minimum trades in the baseline window before outliers are considered
+const OUTLIER_MIN_SAMPLES: usize = 20;
+
+/// Outlier-trade handling for `compute_rolling_metrics_with`
+///
+/// A trade is an outlier when its SOL amount is more than `max_stddev`
+/// standard deviations above the token's mean trade size over the 3600s
+/// window. A single mispriced or arb transaction otherwise dominates net
+/// flow and fires false SURGEs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutlierPolicy {
+    pub mode: OutlierMode,
+    pub max_stddev: f64,
+}
+
+impl Default for OutlierPolicy {
+    fn default() -> Self {
+        Self {
+            mode: OutlierMode::Off,
+            max_stddev: 4.0,
+        }
+    }
+}
+
+impl OutlierPolicy {
+    /// SOL amount above which a trade is an outlier
+    ///
+    /// `baseline` is (trade count, mean, variance) of trade sizes. Returns
+    /// None when disabled or when the baseline has too few trades.
+    fn threshold(&self, baseline: (usize, f64, f64)) -> Option<f64> {
+        let (count, mean, variance) = baseline;
+        if self.mode == OutlierMode::Off || count < OUTLIER_MIN_SAMPLES {
+            return None;
+        }
+
+        Some(mean + self.max_stddev * variance.sqrt())
+    }
+}
+
+/// Round a detail value to a fixed number of decimal places
+///
+/// Keeps details_json compact; scores themselves are not rounded.
+fn round_to(value: f64, places: i32) -> f64 {
+    let factor = 10f64.powi(places);
+    (value * factor).round() / factor
 }
 
 /// Compute DCA-to-spot correlation for a token
@@ -261,29 +583,28 @@ mod signal_thresholds {
 /// within a 60-second time window.
 ///
 /// Arguments:
-/// - `spot_trades`: BUY trades from spot programs (PumpSwap, BonkSwap, Moonshot)
-/// - `dca_trades`: BUY trades from Jupiter DCA
+/// - `spot_buy_timestamps`: BUY timestamps from spot programs (PumpSwap, BonkSwap, Moonshot)
+/// - `dca_buy_timestamps`: BUY timestamps from Jupiter DCA
 /// - `window_secs`: Time window for correlation (default: 60 seconds)
 ///
 /// Returns: (overlap_ratio, matched_dca_count)
 /// - overlap_ratio: Percentage of DCA trades with matching spot trades (0.0-1.0)
 /// - matched_dca_count: Number of DCA trades that had overlapping spot activity
 fn compute_dca_correlation(
-    spot_trades: &[TradeEvent],
-    dca_trades: &[TradeEvent],
+    spot_buy_timestamps: &[i64],
+    dca_buy_timestamps: &[i64],
     window_secs: i64,
 ) -> (f64, usize) {
-    if dca_trades.is_empty() {
+    if dca_buy_timestamps.is_empty() {
         return (0.0, 0);
     }
 
     let mut matched_dca_count = 0;
 
     // For each DCA trade, check if there's a spot trade within ±window_secs
-    for dca_trade in dca_trades {
-        let dca_ts = dca_trade.timestamp;
-        let has_matching_spot = spot_trades.iter().any(|spot_trade| {
-            let time_diff = (spot_trade.timestamp - dca_ts).abs();
+    for &dca_ts in dca_buy_timestamps {
+        let has_matching_spot = spot_buy_timestamps.iter().any(|&spot_ts| {
+            let time_diff = (spot_ts - dca_ts).abs();
             time_diff <= window_secs
         });
 
@@ -292,238 +613,507 @@ fn compute_dca_correlation(
         }
     }
 
-    let overlap_ratio = matched_dca_count as f64 / dca_trades.len() as f64;
+    let overlap_ratio = matched_dca_count as f64 / dca_buy_timestamps.len() as f64;
     (overlap_ratio, matched_dca_count)
 }
 
-/// Detect trading signals from rolling metrics
-///
-/// Phase 3-B: Signal Detection Implementation
-///
-/// Analyzes rolling window metrics and trade patterns to detect:
-/// - BREAKOUT: Sharp volume increase with wallet growth
-/// - FOCUSED: Concentrated buying from few non-bot wallets
-/// - SURGE: Explosive buy volume spike
-/// - BOT_DROPOFF: Sudden bot activity decline opening market
-/// - DCA_CONVICTION: Jupiter DCA BUYs overlap with spot BUYs
+/// Inputs a signal detector sees for one token at flush time
+pub struct DetectionContext<'a> {
+    pub mint: &'a str,
+    /// Window metrics (outlier policy and wallet ages already applied)
+    pub metrics: &'a RollingMetrics,
+    /// Full rolling state (trade buffers, minute buckets, DCA activity)
+    pub state: &'a TokenRollingState,
+    pub now: i64,
+    /// Bot trade count from the previous flush (BOT_DROPOFF)
+    pub previous_bot_count: Option<i32>,
+    /// Wallet count BREAKOUT uses for its wallet-growth check
+    pub wallet_metric: WalletGrowthMetric,
+    /// Thresholds for the built-in detectors
+    pub thresholds: &'a SignalThresholdsConfig,
+}
+
+/// One signal type's detection rule
 ///
-/// Returns: Vec of detected signals with scores and details
+/// Implement this to add a signal without patching `state.rs`, and register
+/// it on the engine (`PipelineEngine::register_detector`). Custom detectors
+/// use `SignalType::Custom("NAME")`, choose their own window (any of the
+/// state's buffers or minute buckets) and set `window_seconds`, severity,
+/// score and details on the signal they return.
 ///
-/// TODO: Phase 3+ refinements
-/// - Add historical baseline comparison (requires state tracking)
-/// - Implement multi-timeframe confirmation (60s + 300s alignment)
-/// - Add price momentum indicators (requires price data)
-/// - Machine learning scoring model
-fn detect_signals(
-    mint: &str,
-    metrics: &RollingMetrics,
-    current_timestamp: i64,
-    previous_bot_count: Option<i32>, // For BOT_DROPOFF detection
-    trades_by_program: &HashMap<String, Vec<TradeEvent>>, // For DCA_CONVICTION detection
-) -> Vec<TokenSignal> {
-    use signal_thresholds::*;
-    
-    let mut signals = Vec::new();
+/// Signals are deduplicated per (mint, signal type): a detector returning
+/// `Some` on every flush produces one row per trend, not one per flush.
+pub trait SignalDetector: Send + Sync {
+    /// Signal type this detector emits
+    fn signal_type(&self) -> SignalType;
+
+    /// Return a signal if the rule matches
+    fn detect(&self, ctx: &DetectionContext) -> Option<TokenSignal>;
+}
+
+/// Ordered set of signal detectors run on every flush
+#[derive(Clone, Default)]
+pub struct SignalDetectorRegistry {
+    detectors: Vec<Arc<dyn SignalDetector>>,
+}
+
+impl SignalDetectorRegistry {
+    /// Empty registry (no signals)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Built-in detectors: BREAKOUT, FOCUSED, SURGE, BOT_DROPOFF, DCA_CONVICTION,
+    /// REACTIVATION, LIQUIDITY_DRAIN
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(BreakoutDetector);
+        registry.register(FocusedDetector);
+        registry.register(SurgeDetector);
+        registry.register(BotDropoffDetector);
+        registry.register(DcaConvictionDetector);
+        registry.register(ReactivationDetector);
+        registry.register(LiquidityDrainDetector);
+        registry
+    }
+
+    /// Add a detector, replacing any registered for the same signal type
+    pub fn register(&mut self, detector: impl SignalDetector + 'static) {
+        let signal_type = detector.signal_type();
+        self.detectors.retain(|d| d.signal_type() != signal_type);
+        self.detectors.push(Arc::new(detector));
+    }
+
+    /// Remove the detector for a signal type (e.g. to disable a built-in)
+    pub fn unregister(&mut self, signal_type: SignalType) {
+        self.detectors.retain(|d| d.signal_type() != signal_type);
+    }
 
-    // Calculate derived metrics for detection
+    /// Signal types of the registered detectors, in run order
+    pub fn signal_types(&self) -> Vec<SignalType> {
+        self.detectors.iter().map(|d| d.signal_type()).collect()
+    }
+
+    /// Run every detector
+    pub fn detect(&self, ctx: &DetectionContext) -> Vec<TokenSignal> {
+        self.detectors.iter().filter_map(|d| d.detect(ctx)).collect()
+    }
+}
+
+/// Registry used by the `detect_signals*` convenience methods
+fn default_detectors() -> &'static SignalDetectorRegistry {
+    static DEFAULT: OnceLock<SignalDetectorRegistry> = OnceLock::new();
+    DEFAULT.get_or_init(SignalDetectorRegistry::with_defaults)
+}
+
+/// Thresholds used by the `detect_signals*` convenience methods
+fn default_thresholds() -> &'static SignalThresholdsConfig {
+    static DEFAULT: OnceLock<SignalThresholdsConfig> = OnceLock::new();
+    DEFAULT.get_or_init(SignalThresholdsConfig::default)
+}
+
+/// Windows used by `compute_rolling_metrics_with`
+fn default_windows() -> &'static WindowSet {
+    static DEFAULT: OnceLock<WindowSet> = OnceLock::new();
+    DEFAULT.get_or_init(WindowSet::default)
+}
+
+/// Ratio of buys to all trades in the 60s window
+fn buy_ratio_60s(metrics: &RollingMetrics) -> f64 {
     let total_trades_60s = metrics.buy_count_60s + metrics.sell_count_60s;
-    let total_trades_300s = metrics.buy_count_300s + metrics.sell_count_300s;
-    
-    let buy_ratio_60s = if total_trades_60s > 0 {
+    if total_trades_60s > 0 {
         metrics.buy_count_60s as f64 / total_trades_60s as f64
     } else {
         0.0
-    };
-    
-    let bot_ratio_300s = if total_trades_300s > 0 {
+    }
+}
+
+/// Ratio of bot trades to all trades in the 300s window
+fn bot_ratio_300s(metrics: &RollingMetrics) -> f64 {
+    let total_trades_300s = metrics.buy_count_300s + metrics.sell_count_300s;
+    if total_trades_300s > 0 {
         metrics.bot_trades_count_300s as f64 / total_trades_300s as f64
     } else {
         0.0
-    };
-    
-    // Average 300s volume per 60s window (for surge detection)
-    let avg_volume_per_60s = metrics.net_flow_300s_sol.abs() / 5.0;
-    
-    // BREAKOUT Detection
-    // Sharp positive net flow with wallet growth and high buy ratio
-    if metrics.net_flow_60s_sol > BREAKOUT_NET_FLOW_60S_MIN
-        && metrics.unique_wallets_300s >= BREAKOUT_WALLET_GROWTH_MIN
-        && buy_ratio_60s > BREAKOUT_BUY_RATIO_MIN
-    {
+    }
+}
+
+/// BREAKOUT: sharp positive net flow with wallet growth and high buy ratio
+pub struct BreakoutDetector;
+
+impl SignalDetector for BreakoutDetector {
+    fn signal_type(&self) -> SignalType {
+        SignalType::Breakout
+    }
+
+    fn detect(&self, ctx: &DetectionContext) -> Option<TokenSignal> {
+        let thresholds = &ctx.thresholds.breakout;
+        let metrics = ctx.metrics;
+        let buy_ratio_60s = buy_ratio_60s(metrics);
+
+        let breakout_wallets = ctx.wallet_metric.count(metrics);
+        if metrics.net_flow_60s_sol <= thresholds.net_flow_60s_min
+            || breakout_wallets < thresholds.wallet_growth_min
+            || buy_ratio_60s <= thresholds.buy_ratio_min
+        {
+            return None;
+        }
+
         // Compute breakout score (0.0-1.0)
         let flow_score = (metrics.net_flow_60s_sol / 20.0).min(1.0);
-        let wallet_score = (metrics.unique_wallets_300s as f64 / 20.0).min(1.0);
+        let wallet_score = (breakout_wallets as f64 / 20.0).min(1.0);
         let ratio_score = buy_ratio_60s;
         let breakout_score = (flow_score + wallet_score + ratio_score) / 3.0;
-        
-        let details = format!(
-            r#"{{"net_flow_60s":{:.2},"unique_wallets":{},"buy_ratio":{:.2}}}"#,
-            metrics.net_flow_60s_sol, metrics.unique_wallets_300s, buy_ratio_60s
-        );
-        
+
+        let details = SignalDetails::Breakout(BreakoutDetails {
+            net_flow_60s: round_to(metrics.net_flow_60s_sol, 2),
+            unique_wallets: metrics.unique_wallets_300s,
+            unique_clusters: metrics.unique_clusters_300s,
+            buy_ratio: round_to(buy_ratio_60s, 2),
+        });
+
         let severity = if breakout_score > 0.8 { 5 }
                        else if breakout_score > 0.6 { 4 }
                        else if breakout_score > 0.4 { 3 }
                        else { 2 };
-        
-        signals.push(
-            TokenSignal::new(mint.to_string(), SignalType::Breakout, 60, current_timestamp)
+
+        Some(
+            TokenSignal::new(ctx.mint.to_string(), SignalType::Breakout, 60, ctx.now)
                 .with_severity(severity)
                 .with_score(breakout_score)
-                .with_details(details),
-        );
+                .with_typed_details(details),
+        )
     }
-    
-    // FOCUSED Detection
-    // Concentrated buying from few wallets, low bot activity
-    if metrics.net_flow_300s_sol > FOCUSED_MIN_VOLUME
-        && bot_ratio_300s < FOCUSED_BOT_RATIO_MAX
-        && metrics.unique_wallets_300s > 0
-        && metrics.unique_wallets_300s <= 10
-    {
+}
+
+/// FOCUSED: concentrated buying from few wallets, low bot activity
+///
+/// Wallets are counted by funding cluster, so one entity buying through
+/// many funded wallets still reads as concentrated.
+pub struct FocusedDetector;
+
+impl SignalDetector for FocusedDetector {
+    fn signal_type(&self) -> SignalType {
+        SignalType::Focused
+    }
+
+    fn detect(&self, ctx: &DetectionContext) -> Option<TokenSignal> {
+        let thresholds = &ctx.thresholds.focused;
+        let metrics = ctx.metrics;
+        let bot_ratio_300s = bot_ratio_300s(metrics);
+
+        if metrics.net_flow_300s_sol <= thresholds.min_volume
+            || bot_ratio_300s >= thresholds.bot_ratio_max
+            || metrics.unique_clusters_300s <= 0
+            || metrics.unique_clusters_300s > thresholds.max_wallets
+        {
+            return None;
+        }
+
         // Concentration metric: inverse of wallet count (fewer wallets = higher concentration)
-        let concentration = 1.0 / metrics.unique_wallets_300s as f64;
-        
+        let concentration = 1.0 / metrics.unique_clusters_300s as f64;
+
         // Focused score based on volume and concentration
         let volume_score = (metrics.net_flow_300s_sol / 10.0).min(1.0);
         let concentration_score = concentration.min(1.0);
         let bot_absence_score = 1.0 - bot_ratio_300s;
         let focused_score = (volume_score + concentration_score + bot_absence_score) / 3.0;
-        
-        let details = format!(
-            r#"{{"net_flow_300s":{:.2},"unique_wallets":{},"bot_ratio":{:.2}}}"#,
-            metrics.net_flow_300s_sol, metrics.unique_wallets_300s, bot_ratio_300s
-        );
-        
-        let severity = if metrics.unique_wallets_300s <= 3 { 4 } else { 3 };
-        
-        signals.push(
-            TokenSignal::new(mint.to_string(), SignalType::Focused, 300, current_timestamp)
+
+        let details = SignalDetails::Focused(FocusedDetails {
+            net_flow_300s: round_to(metrics.net_flow_300s_sol, 2),
+            unique_wallets: metrics.unique_wallets_300s,
+            unique_clusters: metrics.unique_clusters_300s,
+            bot_ratio: round_to(bot_ratio_300s, 2),
+        });
+
+        let severity = if metrics.unique_clusters_300s <= 3 { 4 } else { 3 };
+
+        Some(
+            TokenSignal::new(ctx.mint.to_string(), SignalType::Focused, 300, ctx.now)
                 .with_severity(severity)
                 .with_score(focused_score)
-                .with_details(details),
-        );
+                .with_typed_details(details),
+        )
     }
-    
-    // SURGE Detection
-    // Explosive buy volume spike (60s volume >> average 300s volume)
-    if metrics.net_flow_60s_sol > SURGE_NET_FLOW_60S_MIN
-        && metrics.buy_count_60s >= SURGE_BUY_COUNT_60S_MIN
-        && avg_volume_per_60s > 0.0
-    {
+}
+
+/// SURGE: explosive buy volume spike (60s volume >> average 300s volume)
+pub struct SurgeDetector;
+
+impl SignalDetector for SurgeDetector {
+    fn signal_type(&self) -> SignalType {
+        SignalType::Surge
+    }
+
+    fn detect(&self, ctx: &DetectionContext) -> Option<TokenSignal> {
+        let thresholds = &ctx.thresholds.surge;
+        let metrics = ctx.metrics;
+
+        // Average 300s volume per 60s window
+        let avg_volume_per_60s = metrics.net_flow_300s_sol.abs() / 5.0;
+
+        if metrics.net_flow_60s_sol <= thresholds.net_flow_60s_min
+            || metrics.buy_count_60s < thresholds.buy_count_60s_min
+            || avg_volume_per_60s <= 0.0
+        {
+            return None;
+        }
+
         let volume_ratio = metrics.net_flow_60s_sol / avg_volume_per_60s;
-        
-        if volume_ratio >= SURGE_VOLUME_RATIO_MIN {
-            // Surge score based on volume acceleration
-            let ratio_score = (volume_ratio / 10.0).min(1.0);
-            let velocity_score = (metrics.buy_count_60s as f64 / 30.0).min(1.0);
-            let surge_score = (ratio_score + velocity_score) / 2.0;
-            
-            let details = format!(
-                r#"{{"net_flow_60s":{:.2},"volume_ratio":{:.2},"buy_count":{}}}"#,
-                metrics.net_flow_60s_sol, volume_ratio, metrics.buy_count_60s
-            );
-            
-            let severity = if volume_ratio >= 5.0 { 5 }
-                           else if volume_ratio >= 4.0 { 4 }
-                           else { 3 };
-            
-            signals.push(
-                TokenSignal::new(mint.to_string(), SignalType::Surge, 60, current_timestamp)
-                    .with_severity(severity)
-                    .with_score(surge_score)
-                    .with_details(details),
-            );
+        if volume_ratio < thresholds.volume_ratio_min {
+            return None;
         }
+
+        // Surge score based on volume acceleration
+        let ratio_score = (volume_ratio / 10.0).min(1.0);
+        let velocity_score = (metrics.buy_count_60s as f64 / 30.0).min(1.0);
+        let surge_score = (ratio_score + velocity_score) / 2.0;
+
+        let details = SignalDetails::Surge(SurgeDetails {
+            net_flow_60s: round_to(metrics.net_flow_60s_sol, 2),
+            volume_ratio: round_to(volume_ratio, 2),
+            buy_count: metrics.buy_count_60s,
+        });
+
+        let severity = if volume_ratio >= 5.0 { 5 }
+                       else if volume_ratio >= 4.0 { 4 }
+                       else { 3 };
+
+        Some(
+            TokenSignal::new(ctx.mint.to_string(), SignalType::Surge, 60, ctx.now)
+                .with_severity(severity)
+                .with_score(surge_score)
+                .with_typed_details(details),
+        )
     }
-    
-    // BOT_DROPOFF Detection
-    // Sudden decline in bot activity with new wallet influx
-    if let Some(prev_bot_count) = previous_bot_count {
-        if prev_bot_count >= BOT_DROPOFF_MIN_PREVIOUS_BOTS
-            && metrics.unique_wallets_300s >= BOT_DROPOFF_NEW_WALLET_MIN
+}
+
+/// BOT_DROPOFF: sudden decline in bot activity with new wallet influx
+pub struct BotDropoffDetector;
+
+impl SignalDetector for BotDropoffDetector {
+    fn signal_type(&self) -> SignalType {
+        SignalType::BotDropoff
+    }
+
+    fn detect(&self, ctx: &DetectionContext) -> Option<TokenSignal> {
+        let thresholds = &ctx.thresholds.bot_dropoff;
+        let metrics = ctx.metrics;
+        let prev_bot_count = ctx.previous_bot_count?;
+
+        if prev_bot_count < thresholds.min_previous_bots
+            || metrics.unique_wallets_300s < thresholds.new_wallet_min
         {
-            let bot_decline = if prev_bot_count > 0 {
-                (prev_bot_count - metrics.bot_trades_count_300s) as f64 / prev_bot_count as f64
-            } else {
-                0.0
-            };
-            
-            if bot_decline >= BOT_DROPOFF_DECLINE_RATIO_MIN {
-                // Bot dropoff score based on decline magnitude and new wallets
-                let decline_score = bot_decline.min(1.0);
-                let wallet_score = (metrics.unique_wallets_300s as f64 / 10.0).min(1.0);
-                let dropoff_score = (decline_score + wallet_score) / 2.0;
-                
-                let details = format!(
-                    r#"{{"bot_decline_pct":{:.0},"prev_bot_count":{},"new_wallets":{}}}"#,
-                    bot_decline * 100.0, prev_bot_count, metrics.unique_wallets_300s
-                );
-                
-                let severity = if bot_decline >= 0.8 { 4 } else { 3 };
-                
-                signals.push(
-                    TokenSignal::new(mint.to_string(), SignalType::BotDropoff, 300, current_timestamp)
-                        .with_severity(severity)
-                        .with_score(dropoff_score)
-                        .with_details(details),
-                );
-            }
+            return None;
+        }
+
+        let bot_decline = if prev_bot_count > 0 {
+            (prev_bot_count - metrics.bot_trades_count_300s) as f64 / prev_bot_count as f64
+        } else {
+            0.0
+        };
+        if bot_decline < thresholds.decline_ratio_min {
+            return None;
         }
+
+        // Bot dropoff score based on decline magnitude and new wallets
+        let decline_score = bot_decline.min(1.0);
+        let wallet_score = (metrics.unique_wallets_300s as f64 / 10.0).min(1.0);
+        let dropoff_score = (decline_score + wallet_score) / 2.0;
+
+        let details = SignalDetails::BotDropoff(BotDropoffDetails {
+            bot_decline_pct: round_to(bot_decline * 100.0, 0),
+            prev_bot_count,
+            new_wallets: metrics.unique_wallets_300s,
+        });
+
+        let severity = if bot_decline >= 0.8 { 4 } else { 3 };
+
+        Some(
+            TokenSignal::new(ctx.mint.to_string(), SignalType::BotDropoff, 300, ctx.now)
+                .with_severity(severity)
+                .with_score(dropoff_score)
+                .with_typed_details(details),
+        )
     }
-    
-    // DCA_CONVICTION Detection
-    // Jupiter DCA BUYs overlap with spot BUYs (coordinated accumulation)
-    // Collect spot BUY trades (PumpSwap, BonkSwap, Moonshot)
-    let spot_programs = ["PumpSwap", "BonkSwap", "Moonshot"];
-    let mut spot_buys = Vec::new();
-    for program in &spot_programs {
-        if let Some(trades) = trades_by_program.get(*program) {
-            for trade in trades {
-                if trade.direction == TradeDirection::Buy {
-                    spot_buys.push(trade.clone());
-                }
+}
+
+/// DCA_CONVICTION: Jupiter DCA BUYs overlap with spot BUYs (coordinated accumulation)
+pub struct DcaConvictionDetector;
+
+impl SignalDetector for DcaConvictionDetector {
+    fn signal_type(&self) -> SignalType {
+        SignalType::DcaConviction
+    }
+
+    fn detect(&self, ctx: &DetectionContext) -> Option<TokenSignal> {
+        let thresholds = &ctx.thresholds.dca_conviction;
+        let program_activity = &ctx.state.program_activity;
+        let dca_orders = &ctx.state.dca_orders;
+
+        // Collect spot BUY trades (launchpad venues, Raydium, Orca and Meteora pools)
+        let spot_programs = [
+            "PumpSwap",
+            "BonkSwap",
+            "Moonshot",
+            "RaydiumAMM",
+            "RaydiumCLMM",
+            "OrcaWhirlpool",
+            "MeteoraDLMM",
+            "MeteoraDAMM",
+            "MeteoraDAMMv2",
+        ];
+        let mut spot_buys: Vec<i64> = Vec::new();
+        for program in &spot_programs {
+            if let Some(activity) = program_activity.get(*program) {
+                spot_buys.extend(activity.buy_timestamps.iter().copied());
             }
         }
+
+        // Collect DCA BUY timestamps
+        let dca_buys: Vec<i64> = program_activity
+            .get("JupiterDCA")
+            .map(|activity| activity.buy_timestamps.iter().copied().collect())
+            .unwrap_or_default();
+
+        // Compute correlation only if we have both spot and DCA activity
+        if spot_buys.is_empty() || dca_buys.is_empty() {
+            return None;
+        }
+        let (overlap_ratio, matched_count) = compute_dca_correlation(&spot_buys, &dca_buys, 60);
+
+        // Threshold: 25%+ overlap (default) = DCA_CONVICTION signal
+        if overlap_ratio < thresholds.overlap_min {
+            return None;
+        }
+
+        // Size context from decoded DCA order accounts (if any were resolved)
+        let committed_sol_remaining: f64 = dca_orders
+            .values()
+            .map(|(order, _)| order.committed_sol_remaining)
+            .sum();
+        let remaining_cycles: u64 = dca_orders
+            .values()
+            .map(|(order, _)| order.remaining_cycles)
+            .sum();
+
+        let has_orders = !dca_orders.is_empty();
+        let details = SignalDetails::DcaConviction(DcaConvictionDetails {
+            overlap_ratio: round_to(overlap_ratio, 2),
+            dca_buys: dca_buys.len(),
+            spot_buys: spot_buys.len(),
+            matched_dca: matched_count,
+            dca_orders: has_orders.then_some(dca_orders.len()),
+            committed_sol_remaining: has_orders.then_some(round_to(committed_sol_remaining, 4)),
+            remaining_cycles: has_orders.then_some(remaining_cycles),
+        });
+
+        // Scale score by committed SOL so dust DCAs don't score like large programs.
+        // Without decoded orders (no RPC configured) the raw overlap is kept.
+        let score = if dca_orders.is_empty() {
+            overlap_ratio
+        } else {
+            overlap_ratio * (committed_sol_remaining / thresholds.full_weight_sol).min(1.0)
+        };
+
+        // Severity based on overlap strength
+        let severity = if overlap_ratio >= 0.5 { 5 }
+                       else if overlap_ratio >= 0.4 { 4 }
+                       else if overlap_ratio >= 0.3 { 3 }
+                       else { 2 };
+
+        Some(
+            TokenSignal::new(ctx.mint.to_string(), SignalType::DcaConviction, 60, ctx.now)
+                .with_severity(severity)
+                .with_score(score)
+                .with_typed_details(details),
+        )
     }
-    
-    // Collect DCA BUY trades
-    let mut dca_buys = Vec::new();
-    if let Some(dca_trades) = trades_by_program.get("JupiterDCA") {
-        for trade in dca_trades {
-            if trade.direction == TradeDirection::Buy {
-                dca_buys.push(trade.clone());
-            }
+}
+
+/// REACTIVATION: buy burst within 300s of trading resuming on a dormant token
+pub struct ReactivationDetector;
+
+impl SignalDetector for ReactivationDetector {
+    fn signal_type(&self) -> SignalType {
+        SignalType::Reactivation
+    }
+
+    fn detect(&self, ctx: &DetectionContext) -> Option<TokenSignal> {
+        let thresholds = &ctx.thresholds.reactivation;
+        let metrics = ctx.metrics;
+        let wake_up = ctx.state.wake_up?;
+
+        // Only the burst right after the gap counts; later flow is ordinary trading
+        if wake_up.idle_secs < thresholds.dormant_secs_min
+            || ctx.now - wake_up.resumed_at > 300
+            || metrics.buy_count_300s < thresholds.buy_count_300s_min
+            || metrics.net_flow_300s_sol < thresholds.net_flow_300s_min
+        {
+            return None;
         }
+
+        let idle_hours = wake_up.idle_secs as f64 / 3600.0;
+        let buy_score = (metrics.buy_count_300s as f64 / 20.0).min(1.0);
+        let flow_score = (metrics.net_flow_300s_sol / 10.0).min(1.0);
+        let idle_score = (idle_hours / 72.0).min(1.0);
+        let score = (buy_score + flow_score + idle_score) / 3.0;
+
+        let details = SignalDetails::Reactivation(ReactivationDetails {
+            idle_hours: round_to(idle_hours, 1),
+            buy_count_300s: metrics.buy_count_300s,
+            net_flow_300s: round_to(metrics.net_flow_300s_sol, 2),
+            unique_wallets: metrics.unique_wallets_300s,
+        });
+
+        let severity = if score >= 0.7 { 4 } else if score >= 0.4 { 3 } else { 2 };
+
+        Some(
+            TokenSignal::new(ctx.mint.to_string(), SignalType::Reactivation, 300, ctx.now)
+                .with_severity(severity)
+                .with_score(score)
+                .with_typed_details(details),
+        )
     }
-    
-    // Compute correlation if we have both spot and DCA activity
-    if !spot_buys.is_empty() && !dca_buys.is_empty() {
-        let (overlap_ratio, matched_count) = compute_dca_correlation(&spot_buys, &dca_buys, 60);
-        
-        // Threshold: 25%+ overlap = DCA_CONVICTION signal
-        if overlap_ratio >= 0.25 {
-            let details = format!(
-                r#"{{"overlap_ratio":{:.2},"dca_buys":{},"spot_buys":{},"matched_dca":{}}}"#,
-                overlap_ratio, dca_buys.len(), spot_buys.len(), matched_count
-            );
-            
-            // Severity based on overlap strength
-            let severity = if overlap_ratio >= 0.5 { 5 }
-                           else if overlap_ratio >= 0.4 { 4 }
-                           else if overlap_ratio >= 0.3 { 3 }
-                           else { 2 };
-            
-            signals.push(
-                TokenSignal::new(mint.to_string(), SignalType::DcaConviction, 60, current_timestamp)
-                    .with_severity(severity)
-                    .with_score(overlap_ratio)
-                    .with_details(details),
-            );
+}
+
+/// LIQUIDITY_DRAIN: pooled SOL fell sharply from its 300s peak (LP pulled or dumped into)
+pub struct LiquidityDrainDetector;
+
+impl SignalDetector for LiquidityDrainDetector {
+    fn signal_type(&self) -> SignalType {
+        SignalType::LiquidityDrain
+    }
+
+    fn detect(&self, ctx: &DetectionContext) -> Option<TokenSignal> {
+        let thresholds = &ctx.thresholds.liquidity_drain;
+        let liquidity = ctx.state.liquidity_sol()?;
+        let peak = ctx.state.peak_liquidity_300s(ctx.now)?;
+        if peak <= 0.0 || peak < thresholds.peak_liquidity_sol_min {
+            return None;
+        }
+
+        let drop_ratio = 1.0 - liquidity / peak;
+        if drop_ratio < thresholds.drop_ratio_min {
+            return None;
         }
+
+        let details = SignalDetails::LiquidityDrain(LiquidityDrainDetails {
+            peak_liquidity_sol: round_to(peak, 2),
+            liquidity_sol: round_to(liquidity, 2),
+            drop_pct: round_to(drop_ratio * 100.0, 1),
+        });
+
+        // Kept below RECONCILED_SEVERITY: there are no trades to reconcile
+        let severity = if drop_ratio >= 0.8 { 4 } else if drop_ratio >= 0.5 { 3 } else { 2 };
+
+        Some(
+            TokenSignal::new(ctx.mint.to_string(), SignalType::LiquidityDrain, 300, ctx.now)
+                .with_severity(severity)
+                .with_score(drop_ratio.min(1.0))
+                .with_typed_details(details),
+        )
     }
-    
-    signals
 }
 
 impl TokenRollingState {
@@ -535,55 +1125,130 @@ impl TokenRollingState {
         Self {
             mint,
             last_seen_ts: 0, // Phase 5: Will be updated on first trade
-            trades_60s: Vec::with_capacity(100),
-            trades_300s: Vec::with_capacity(500),
-            trades_900s: Vec::with_capacity(1500),
-            trades_3600s: Vec::with_capacity(6000),
-            trades_7200s: Vec::with_capacity(12000),
-            trades_14400s: Vec::with_capacity(24000),
+            first_seen_ts: 0, // Set on first trade
+            trades: VecDeque::with_capacity(1500),
+            window_now: i64::MIN,
+            minute_buckets: MinuteBuckets::new(),
             unique_wallets_300s: HashSet::new(),
+            new_wallets_300s: HashSet::new(),
+            wallet_first_seen: HashMap::new(),
             bot_wallets_300s: HashSet::new(),
-            trades_by_program: HashMap::new(),
+            bot_cache: HashMap::new(),
+            bot_cache_dirty: HashSet::new(),
+            program_activity: HashMap::new(),
             // Phase 6: DCA Rolling Windows
             dca_timestamps_60s: VecDeque::with_capacity(10),
             dca_timestamps_300s: VecDeque::with_capacity(50),
             dca_timestamps_900s: VecDeque::with_capacity(150),
             dca_timestamps_3600s: VecDeque::with_capacity(600),
             dca_timestamps_14400s: VecDeque::with_capacity(2400),
+            dca_orders: HashMap::new(),
+            last_dca_trade: None,
+            early_buyers: Vec::with_capacity(EARLY_BUYER_COUNT),
+            unfinalized_min_slot: None,
+            wake_up: None,
+            liquidity: VecDeque::new(),
+            pool_price_sol: None,
+        }
+    }
+
+    /// Record pooled SOL reported by the liquidity tracker
+    pub fn record_liquidity(&mut self, timestamp: i64, liquidity_sol: f64) {
+        self.liquidity.push_back((timestamp, liquidity_sol));
+        while self.liquidity.len() > 1 && self.liquidity[0].0 < timestamp - 300 {
+            self.liquidity.pop_front();
         }
     }
 
+    /// Latest pooled SOL (None until the tracker reported the mint)
+    pub fn liquidity_sol(&self) -> Option<f64> {
+        self.liquidity.back().map(|(_, sol)| *sol)
+    }
+
+    /// Highest pooled SOL in the 300s before `now`, counting the latest sample
+    pub fn peak_liquidity_300s(&self, now: i64) -> Option<f64> {
+        self.liquidity
+            .iter()
+            .filter(|(ts, _)| *ts >= now - 300)
+            .map(|(_, sol)| *sol)
+            .chain(self.liquidity_sol())
+            .reduce(f64::max)
+    }
+
+    /// Trades in the last 60 seconds, oldest first
+    pub fn trades_60s(&self) -> &[TradeEvent] {
+        self.window(60)
+    }
+
+    /// Trades in the last 300 seconds, oldest first
+    pub fn trades_300s(&self) -> &[TradeEvent] {
+        self.window(300)
+    }
+
+    /// Trades in the last 900 seconds, oldest first
+    pub fn trades_900s(&self) -> &[TradeEvent] {
+        self.window(900)
+    }
+
+    /// Suffix of the buffer with trades at or after `window_now - secs`
+    fn window(&self, secs: i64) -> &[TradeEvent] {
+        let (trades, wrapped) = self.trades.as_slices();
+        debug_assert!(wrapped.is_empty(), "trade buffer must stay contiguous");
+        let cutoff = self.window_now.saturating_sub(secs);
+        &trades[trades.partition_point(|trade| trade.timestamp < cutoff)..]
+    }
+
     /// Add a trade to rolling windows
     ///
     /// Phase 2: Implemented
-    /// - Pushes trade to all three window buffers
+    /// - Inserts trade into the time-ordered buffer and its minute bucket
     /// - Updates unique_wallets_300s with trade wallet
-    /// - Updates bot_wallets_300s with placeholder logic
-    /// - Adds trade to program-specific bucket for DCA correlation
+    /// - Records the wallet's first trade on this mint (new_wallets_300s)
+    /// - Marks the wallet's cached bot classification dirty
+    /// - Tracks the first buyers' net positions (early-holder retention)
+    /// - Records trade timestamp in program-specific summary for DCA correlation
     /// Phase 5: Updates last_seen_ts for pruning
     /// Phase 6: Appends DCA timestamps for JupiterDCA BUY trades
+    /// - Records a wake-up when trading resumes after a long gap
     pub fn add_trade(&mut self, trade: TradeEvent) {
+        let idle_secs = trade.timestamp - self.last_seen_ts;
+        if self.last_seen_ts > 0 && idle_secs >= MIN_WAKE_IDLE_SECS {
+            self.wake_up = Some(WakeUp {
+                resumed_at: trade.timestamp,
+                idle_secs,
+            });
+        }
+
         // Phase 5: Update last seen timestamp for pruning
         self.last_seen_ts = trade.timestamp;
+        if self.first_seen_ts == 0 {
+            self.first_seen_ts = trade.timestamp;
+        }
 
         // Track wallet in 300s window
         self.unique_wallets_300s
             .insert(trade.user_account.clone());
 
-        // TODO: Phase 3 - Implement actual bot detection logic
-        // For now, use placeholder: no bot detection
-        // Bot detection will be based on:
-        // - High frequency trading patterns
-        // - MEV transaction characteristics
-        // - Known bot wallet addresses
-        // Placeholder: never mark as bot in Phase 2
-        let _is_bot = false;
-
-        // Add to program-specific bucket for DCA correlation
-        self.trades_by_program
+        // A wallet is new if this is its first trade on the mint
+        match self.wallet_first_seen.get_mut(&trade.user_account) {
+            Some((_, last_ts)) => *last_ts = (*last_ts).max(trade.timestamp),
+            None => {
+                self.wallet_first_seen
+                    .insert(trade.user_account.clone(), (trade.timestamp, trade.timestamp));
+                self.new_wallets_300s.insert(trade.user_account.clone());
+            }
+        }
+
+        // Wallet's window changed: re-evaluate its bot classification
+        self.bot_cache_dirty.insert(trade.user_account.clone());
+
+        self.record_early_buyer(&trade);
+
+        // Record timestamp in program-specific summary for DCA correlation
+        self.program_activity
             .entry(trade.source_program.clone())
-            .or_insert_with(Vec::new)
-            .push(trade.clone());
+            .or_default()
+            .record(trade.timestamp, trade.direction);
 
         // Phase 6: Track DCA BUY timestamps for rolling windows
         // Only track JupiterDCA BUY trades (not sells, not other programs)
@@ -594,15 +1259,31 @@ impl TokenRollingState {
             self.dca_timestamps_900s.push_back(timestamp);
             self.dca_timestamps_3600s.push_back(timestamp);
             self.dca_timestamps_14400s.push_back(timestamp);
+            self.last_dca_trade = Some((trade.signature.clone(), trade.slot));
+        }
+
+        // Keep the latest decoded order context for each DCA order
+        if let Some(order) = &trade.dca_order {
+            self.dca_orders
+                .insert(order.order_account.clone(), (order.clone(), trade.timestamp));
         }
 
-        // Add to all window buffers (most recent trades)
-        self.trades_60s.push(trade.clone());
-        self.trades_300s.push(trade.clone());
-        self.trades_900s.push(trade.clone());
-        self.trades_3600s.push(trade.clone());
-        self.trades_7200s.push(trade.clone());
-        self.trades_14400s.push(trade);
+        if trade.confirmation < Confirmation::Finalized {
+            self.unfinalized_min_slot = Some(
+                self.unfinalized_min_slot
+                    .map_or(trade.slot, |slot| slot.min(trade.slot)),
+            );
+        }
+
+        // Long windows only need per-minute sums
+        self.minute_buckets.record(&trade);
+
+        // Insert in time order; late trades land just before newer ones
+        let index = self
+            .trades
+            .partition_point(|buffered| buffered.timestamp <= trade.timestamp);
+        self.trades.insert(index, trade);
+        self.trades.make_contiguous();
     }
 
     /// Evict trades older than window cutoffs
@@ -610,15 +1291,15 @@ impl TokenRollingState {
     /// Phase 2: Implemented
     /// - Removes trades outside each window's time range
     /// - Recomputes unique_wallets_300s from remaining trades
-    /// - Recomputes bot_wallets_300s from remaining trades
-    /// - Evicts old trades from program-specific buckets
+    /// - Recomputes new_wallets_300s from first-seen timestamps
+    /// - Marks wallets with evicted 300s trades for bot re-evaluation
+    /// - Evicts old timestamps from program-specific summaries
     /// Phase 6: Prunes DCA timestamps outside each window
     pub fn evict_old_trades(&mut self, now: i64) {
         let cutoff_60s = now - 60;
         let cutoff_300s = now - 300;
         let cutoff_900s = now - 900;
         let cutoff_3600s = now - 3600;
-        let cutoff_7200s = now - 7200;
         let cutoff_14400s = now - 14400;
 
         // Phase 6: Prune DCA timestamps from front of queues (oldest first)
@@ -658,45 +1339,332 @@ impl TokenRollingState {
             }
         }
 
-        // Evict from 60s window
-        self.trades_60s
-            .retain(|trade| trade.timestamp >= cutoff_60s);
+        // Wallets whose trades leave the 300s window need bot re-evaluation
+        let previous_cutoff_300s = self.window_now.saturating_sub(300);
+        if cutoff_300s > previous_cutoff_300s {
+            for trade in self.trades.iter() {
+                if trade.timestamp >= cutoff_300s {
+                    break;
+                }
+                if trade.timestamp >= previous_cutoff_300s {
+                    self.bot_cache_dirty.insert(trade.user_account.clone());
+                }
+            }
+        }
+
+        // Windows only move forward; trades older than 900s leave the buffer
+        self.window_now = self.window_now.max(now);
+        while self
+            .trades
+            .front()
+            .is_some_and(|trade| trade.timestamp < cutoff_900s)
+        {
+            self.trades.pop_front();
+        }
+        self.trades.make_contiguous();
+
+        // Drop minute buckets older than the 14400s window (4 hours)
+        self.minute_buckets.evict(now);
+
+        // Evict from program-specific summaries (use 14400s window as longest)
+        for activity in self.program_activity.values_mut() {
+            activity.evict_before(cutoff_14400s);
+        }
+        self.program_activity.retain(|_, activity| !activity.is_empty());
+        self.dca_orders
+            .retain(|_, (_, last_fill_ts)| *last_fill_ts >= cutoff_14400s);
+        if self.dca_timestamps_14400s.is_empty() {
+            self.last_dca_trade = None;
+        }
+        self.wallet_first_seen
+            .retain(|_, (_, last_ts)| *last_ts >= cutoff_14400s);
+        if self.wallet_first_seen.len() > MAX_WALLET_FIRST_SEEN {
+            // Least recently active wallets go first; they count as new if they return
+            let excess = self.wallet_first_seen.len() - WALLET_FIRST_SEEN_TRIM_TO;
+            let mut by_last_seen: Vec<(i64, &String)> = self
+                .wallet_first_seen
+                .iter()
+                .map(|(wallet, (_, last_ts))| (*last_ts, wallet))
+                .collect();
+            by_last_seen.select_nth_unstable(excess - 1);
+            let stale: Vec<String> = by_last_seen[..excess]
+                .iter()
+                .map(|(_, wallet)| (*wallet).clone())
+                .collect();
+            for wallet in stale {
+                self.wallet_first_seen.remove(&wallet);
+            }
+        }
+
+        // Recompute unique wallets from remaining 300s trades
+        self.unique_wallets_300s = self
+            .trades_300s()
+            .iter()
+            .map(|trade| trade.user_account.clone())
+            .collect();
+
+        // New wallets: active in the 300s window with their first trade inside it
+        let first_seen = &self.wallet_first_seen;
+        self.new_wallets_300s = self
+            .unique_wallets_300s
+            .iter()
+            .filter(|wallet| {
+                first_seen
+                    .get(*wallet)
+                    .is_some_and(|(first_ts, _)| *first_ts >= cutoff_300s)
+            })
+            .cloned()
+            .collect();
+
+        // Forget classifications for wallets that left the 300s window
+        let unique_wallets = &self.unique_wallets_300s;
+        self.bot_cache.retain(|wallet, _| unique_wallets.contains(wallet));
+        self.bot_cache_dirty.retain(|wallet| unique_wallets.contains(wallet));
+        self.bot_wallets_300s.retain(|wallet| unique_wallets.contains(wallet));
+    }
+
+    /// Upgrade buffered trades in slots up to `slot` to `confirmation`
+    ///
+    /// A slot reaching a commitment implies its ancestors have too; trades
+    /// from abandoned forks are expected to have been rolled back already.
+    /// Returns the number of trades upgraded.
+    pub fn confirm_through_slot(&mut self, slot: u64, confirmation: Confirmation) -> usize {
+        if self.unfinalized_min_slot.is_none_or(|min_slot| min_slot > slot) {
+            return 0;
+        }
 
-        // Evict from 300s window
-        self.trades_300s
-            .retain(|trade| trade.timestamp >= cutoff_300s);
+        let mut upgraded = 0;
+        for trade in self.trades.iter_mut() {
+            if trade.slot <= slot && trade.confirmation < confirmation {
+                trade.confirmation = confirmation;
+                upgraded += 1;
+            }
+        }
 
-        // Evict from 900s window
-        self.trades_900s
-            .retain(|trade| trade.timestamp >= cutoff_900s);
+        self.unfinalized_min_slot = self
+            .trades
+            .iter()
+            .filter(|trade| trade.confirmation < Confirmation::Finalized)
+            .map(|trade| trade.slot)
+            .min();
+        upgraded
+    }
 
-        // Evict from 3600s window (1 hour)
-        self.trades_3600s
-            .retain(|trade| trade.timestamp >= cutoff_3600s);
+    /// Remove trades from slots after `slot` (fork switch back to `slot`)
+    ///
+    /// See `evict_trades_in_slots`. Returns the number of trades removed.
+    pub fn evict_trades_after_slot(&mut self, slot: u64, now: i64) -> usize {
+        self.evict_trades_in_slots(slot.saturating_add(1)..=u64::MAX, now)
+    }
 
-        // Evict from 7200s window (2 hours)
-        self.trades_7200s
-            .retain(|trade| trade.timestamp >= cutoff_7200s);
+    /// Remove trades from rolled-back slots
+    ///
+    /// Trades still in the 900s buffer are taken out of the short windows,
+    /// minute buckets, program/DCA timestamp summaries and early-buyer
+    /// positions, then the wallet sets are rebuilt. Minute wallet sketches
+    /// can't forget wallets, so long-window unique-wallet estimates may stay
+    /// slightly high. Returns the number of trades removed.
+    pub fn evict_trades_in_slots(&mut self, slots: RangeInclusive<u64>, now: i64) -> usize {
+        let rolled_back: Vec<TradeEvent> = self
+            .trades
+            .iter()
+            .filter(|trade| slots.contains(&trade.slot))
+            .cloned()
+            .collect();
+        if rolled_back.is_empty() {
+            return 0;
+        }
+
+        self.trades.retain(|trade| !slots.contains(&trade.slot));
+        self.trades.make_contiguous();
+
+        fn remove_one(queue: &mut VecDeque<i64>, timestamp: i64) {
+            if let Some(i) = queue.iter().rposition(|&ts| ts == timestamp) {
+                queue.remove(i);
+            }
+        }
+
+        for trade in &rolled_back {
+            self.minute_buckets.remove(trade);
+            self.bot_cache_dirty.insert(trade.user_account.clone());
+
+            if let Some(activity) = self.program_activity.get_mut(&trade.source_program) {
+                remove_one(&mut activity.trade_timestamps, trade.timestamp);
+                if trade.direction == TradeDirection::Buy {
+                    remove_one(&mut activity.buy_timestamps, trade.timestamp);
+                }
+            }
+
+            if trade.source_program == "JupiterDCA" && trade.direction == TradeDirection::Buy {
+                for queue in [
+                    &mut self.dca_timestamps_60s,
+                    &mut self.dca_timestamps_300s,
+                    &mut self.dca_timestamps_900s,
+                    &mut self.dca_timestamps_3600s,
+                    &mut self.dca_timestamps_14400s,
+                ] {
+                    remove_one(queue, trade.timestamp);
+                }
+            }
+
+            if let Some((_, holder)) = self
+                .early_buyers
+                .iter_mut()
+                .find(|(wallet, _)| *wallet == trade.user_account)
+            {
+                match trade.direction {
+                    TradeDirection::Buy => holder.tokens_bought -= trade.token_amount,
+                    TradeDirection::Sell => holder.tokens_sold -= trade.token_amount,
+                    TradeDirection::Unknown => {}
+                }
+            }
+        }
+        self.program_activity.retain(|_, activity| !activity.is_empty());
+        // A rolled-back first buy frees the early-buyer slot
+        self.early_buyers
+            .retain(|(_, holder)| holder.tokens_bought > 0.0);
+
+        if self
+            .last_dca_trade
+            .as_ref()
+            .is_some_and(|(_, slot)| slots.contains(slot))
+        {
+            self.last_dca_trade = self
+                .trades
+                .iter()
+                .rev()
+                .find(|t| t.source_program == "JupiterDCA" && t.direction == TradeDirection::Buy)
+                .map(|t| (t.signature.clone(), t.slot));
+        }
+
+        // Wallets first seen in a rolled-back trade with nothing left count as unseen
+        let earliest = rolled_back.iter().map(|t| t.timestamp).min().unwrap_or(now);
+        let remaining: HashSet<&str> = self
+            .trades
+            .iter()
+            .map(|t| t.user_account.as_str())
+            .collect();
+        for trade in &rolled_back {
+            if !remaining.contains(trade.user_account.as_str())
+                && self
+                    .wallet_first_seen
+                    .get(&trade.user_account)
+                    .is_some_and(|(first_ts, _)| *first_ts >= earliest)
+            {
+                self.wallet_first_seen.remove(&trade.user_account);
+            }
+        }
+
+        // Rebuild unique/new wallet sets and bot caches from what is left
+        self.evict_old_trades(now);
+
+        rolled_back.len()
+    }
+
+    /// Re-evaluate bot classifications for wallets whose window changed
+    ///
+    /// Only dirty wallets, wallets never classified, and classifications older
+    /// than `BOT_CACHE_TTL_SECS` are re-checked; everything else is reused.
+    /// Called before each flush so `compute_rolling_metrics` reads the cache.
+    pub fn refresh_bot_cache(&mut self, now: i64) {
+        let ttl_cutoff = now - BOT_CACHE_TTL_SECS;
+        let cache = &self.bot_cache;
+        let dirty = &self.bot_cache_dirty;
+
+        let reevaluated = classify_wallets(self.trades_300s(), |wallet| {
+            dirty.contains(wallet)
+                || cache
+                    .get(wallet)
+                    .is_none_or(|cached| cached.evaluated_at < ttl_cutoff)
+        });
+
+        for (wallet, (is_bot, trade_count)) in reevaluated {
+            self.bot_cache.insert(
+                wallet,
+                BotClassification {
+                    is_bot,
+                    trade_count,
+                    evaluated_at: now,
+                },
+            );
+        }
+        self.bot_cache_dirty.clear();
+
+        self.bot_wallets_300s = self
+            .bot_cache
+            .iter()
+            .filter(|(_, cached)| cached.is_bot)
+            .map(|(wallet, _)| wallet.clone())
+            .collect();
+    }
 
-        // Evict from 14400s window (4 hours)
-        self.trades_14400s
-            .retain(|trade| trade.timestamp >= cutoff_14400s);
+    /// Update early-buyer positions for a trade
+    ///
+    /// The first `EARLY_BUYER_COUNT` distinct buyers are recorded; after that
+    /// only their positions change. Sells by untracked wallets are ignored.
+    fn record_early_buyer(&mut self, trade: &TradeEvent) {
+        let position = self
+            .early_buyers
+            .iter_mut()
+            .find(|(wallet, _)| *wallet == trade.user_account)
+            .map(|(_, holder)| holder);
+
+        match (trade.direction, position) {
+            (TradeDirection::Buy, Some(holder)) => holder.tokens_bought += trade.token_amount,
+            (TradeDirection::Sell, Some(holder)) => holder.tokens_sold += trade.token_amount,
+            (TradeDirection::Buy, None) if self.early_buyers.len() < EARLY_BUYER_COUNT => {
+                self.early_buyers.push((
+                    trade.user_account.clone(),
+                    EarlyHolder {
+                        tokens_bought: trade.token_amount,
+                        tokens_sold: 0.0,
+                    },
+                ));
+            }
+            _ => {}
+        }
+    }
 
-        // Evict from program-specific buckets (use 14400s window as longest)
-        for trades in self.trades_by_program.values_mut() {
-            trades.retain(|trade| trade.timestamp >= cutoff_14400s);
+    /// Share of early buyers still holding (None before the first buy)
+    pub fn early_holder_retention(&self) -> Option<f64> {
+        if self.early_buyers.is_empty() {
+            return None;
         }
+        let holding = self
+            .early_buyers
+            .iter()
+            .filter(|(_, holder)| holder.is_holding())
+            .count();
+        Some(holding as f64 / self.early_buyers.len() as f64)
+    }
 
-        // Recompute unique wallets from remaining 300s trades
-        self.unique_wallets_300s.clear();
-        for trade in &self.trades_300s {
-            self.unique_wallets_300s.insert(trade.user_account.clone());
+    /// Bot wallet and bot trade counts for the 300s window
+    ///
+    /// Uses cached classifications where still valid and classifies the rest
+    /// on the fly (without storing them).
+    fn bot_counts_300s(&self) -> (i32, i32) {
+        let cache = &self.bot_cache;
+        let dirty = &self.bot_cache_dirty;
+        let uncached = classify_wallets(self.trades_300s(), |wallet| {
+            dirty.contains(wallet) || !cache.contains_key(wallet)
+        });
+
+        let cached = self
+            .bot_cache
+            .iter()
+            .filter(|(wallet, _)| !dirty.contains(*wallet))
+            .map(|(_, cached)| (cached.is_bot, cached.trade_count));
+
+        let mut bot_wallets = 0;
+        let mut bot_trades = 0;
+        for (is_bot, trade_count) in uncached.into_values().chain(cached) {
+            if is_bot {
+                bot_wallets += 1;
+                bot_trades += trade_count as i32;
+            }
         }
 
-        // Recompute bot wallets from remaining 300s trades
-        // TODO: Phase 3 - Implement actual bot detection logic
-        // For now, placeholder: no bot detection
-        self.bot_wallets_300s.clear();
+        (bot_wallets, bot_trades)
     }
 
     /// Detect trading signals from current rolling state
@@ -713,9 +1681,68 @@ impl TokenRollingState {
         &self,
         current_timestamp: i64,
         previous_bot_count: Option<i32>,
+    ) -> Vec<TokenSignal> {
+        self.detect_signals_with_wallet_metric(
+            current_timestamp,
+            previous_bot_count,
+            WalletGrowthMetric::default(),
+        )
+    }
+
+    /// Detect trading signals, choosing the wallet count BREAKOUT uses
+    ///
+    /// Same as `detect_signals`, but `wallet_metric` selects whether BREAKOUT's
+    /// wallet-growth check counts any active wallet or only new wallets.
+    pub fn detect_signals_with_wallet_metric(
+        &self,
+        current_timestamp: i64,
+        previous_bot_count: Option<i32>,
+        wallet_metric: WalletGrowthMetric,
     ) -> Vec<TokenSignal> {
         let metrics = self.compute_rolling_metrics();
-        detect_signals(&self.mint, &metrics, current_timestamp, previous_bot_count, &self.trades_by_program)
+        self.detect_signals_from_metrics(&metrics, current_timestamp, previous_bot_count, wallet_metric)
+    }
+
+    /// Detect trading signals from already-computed metrics
+    ///
+    /// Lets callers compute metrics once (e.g. with an outlier policy) and
+    /// reuse them for both signals and aggregates.
+    pub fn detect_signals_from_metrics(
+        &self,
+        metrics: &RollingMetrics,
+        current_timestamp: i64,
+        previous_bot_count: Option<i32>,
+        wallet_metric: WalletGrowthMetric,
+    ) -> Vec<TokenSignal> {
+        self.detect_signals_with(
+            default_detectors(),
+            default_thresholds(),
+            metrics,
+            current_timestamp,
+            previous_bot_count,
+            wallet_metric,
+        )
+    }
+
+    /// Run a detector registry with the given thresholds against already-computed metrics
+    pub fn detect_signals_with(
+        &self,
+        detectors: &SignalDetectorRegistry,
+        thresholds: &SignalThresholdsConfig,
+        metrics: &RollingMetrics,
+        current_timestamp: i64,
+        previous_bot_count: Option<i32>,
+        wallet_metric: WalletGrowthMetric,
+    ) -> Vec<TokenSignal> {
+        detectors.detect(&DetectionContext {
+            mint: &self.mint,
+            metrics,
+            state: self,
+            now: current_timestamp,
+            previous_bot_count,
+            wallet_metric,
+            thresholds,
+        })
     }
 
     /// Compute rolling metrics from current window state
@@ -724,22 +1751,63 @@ impl TokenRollingState {
     /// Phase 3-A: Bot detection integrated
     /// Returns internal metrics snapshot (not AggregatedTokenState)
     pub fn compute_rolling_metrics(&self) -> RollingMetrics {
+        self.compute_rolling_metrics_with(OutlierPolicy::default())
+    }
+
+    /// Compute rolling metrics, applying an outlier-trade policy to flows and counts
+    ///
+    /// The outlier threshold comes from the 3600s window and is applied to
+    /// every window's net flow and buy/sell counts.
+    pub fn compute_rolling_metrics_with(&self, outliers: OutlierPolicy) -> RollingMetrics {
+        self.compute_rolling_metrics_for(outliers, default_windows())
+    }
+
+    /// Compute rolling metrics for a configured window set
+    ///
+    /// Windows up to 900s come from the trade buffer, longer ones from the
+    /// minute buckets. `windows` always includes the core 60s/300s/900s.
+    pub fn compute_rolling_metrics_for(
+        &self,
+        outliers: OutlierPolicy,
+        windows: &WindowSet,
+    ) -> RollingMetrics {
+        let outlier_threshold = outliers.threshold(self.minute_buckets.size_stats(3600));
+
+        // SOL amount after the outlier policy (None = excluded)
+        let adjust_amount = |sol_amount: f64| -> Option<f64> {
+            match outlier_threshold.filter(|t| sol_amount > *t) {
+                Some(threshold) => match outliers.mode {
+                    OutlierMode::Cap => Some(threshold),
+                    OutlierMode::Exclude => None,
+                    OutlierMode::Off => Some(sol_amount),
+                },
+                None => Some(sol_amount),
+            }
+        };
+        let policy_amount = |trade: &TradeEvent| adjust_amount(trade.sol_amount);
+
         // Helper function to compute net flow and counts for a window
-        fn compute_window_metrics(
-            trades: &[TradeEvent],
-        ) -> (f64, i32, i32) {
+        // (`final_only` skips trades whose slot isn't finalized yet)
+        let compute_window_metrics = |trades: &[TradeEvent], final_only: bool| -> (f64, i32, i32) {
             let mut net_flow = 0.0;
             let mut buy_count = 0;
             let mut sell_count = 0;
 
             for trade in trades {
+                if final_only && trade.confirmation < Confirmation::Finalized {
+                    continue;
+                }
+                let Some(sol_amount) = policy_amount(trade) else {
+                    continue;
+                };
+
                 match trade.direction {
                     TradeDirection::Buy => {
-                        net_flow += trade.sol_amount;
+                        net_flow += sol_amount;
                         buy_count += 1;
                     }
                     TradeDirection::Sell => {
-                        net_flow -= trade.sol_amount;
+                        net_flow -= sol_amount;
                         sell_count += 1;
                     }
                     TradeDirection::Unknown => {
@@ -750,24 +1818,90 @@ impl TokenRollingState {
             }
 
             (net_flow, buy_count, sell_count)
+        };
+
+        // Compute metrics for each configured window
+        let window_metrics: Vec<WindowMetrics> = windows
+            .secs()
+            .map(|secs| {
+                let (net_flow_sol, buy_count, sell_count) = if secs <= TRADE_BUFFER_SECS {
+                    compute_window_metrics(self.window(secs), false)
+                } else {
+                    let totals = self.minute_buckets.totals(secs, adjust_amount);
+                    (totals.net_flow_sol, totals.buy_count, totals.sell_count)
+                };
+                WindowMetrics { secs, net_flow_sol, buy_count, sell_count }
+            })
+            .collect();
+        let core = |secs: i64| {
+            window_metrics
+                .iter()
+                .find(|window| window.secs == secs)
+                .map(|window| (window.net_flow_sol, window.buy_count, window.sell_count))
+                .expect("window set includes the core windows")
+        };
+        let (net_flow_60s, buy_count_60s, sell_count_60s) = core(60);
+        let (net_flow_300s, buy_count_300s, sell_count_300s) = core(300);
+        let (net_flow_900s, buy_count_900s, sell_count_900s) = core(900);
+
+        // Final view: the same windows counting finalized trades only
+        let (net_flow_60s_final, _, _) = compute_window_metrics(self.trades_60s(), true);
+        let (net_flow_300s_final, buy_count_300s_final, sell_count_300s_final) =
+            compute_window_metrics(self.trades_300s(), true);
+        let (net_flow_900s_final, _, _) = compute_window_metrics(self.trades_900s(), true);
+        let unfinalized_trades_300s = self
+            .trades_300s()
+            .iter()
+            .filter(|trade| trade.confirmation < Confirmation::Finalized)
+            .count() as i32;
+        let net_flow_3600s = self.minute_buckets.totals(3600, adjust_amount).net_flow_sol;
+        let net_flow_7200s = self.minute_buckets.totals(7200, adjust_amount).net_flow_sol;
+        let net_flow_14400s = self.minute_buckets.totals(14400, adjust_amount).net_flow_sol;
+
+        // Where the 300s flow happened, per source program
+        let mut program_breakdown_300s: BTreeMap<String, ProgramFlow> = BTreeMap::new();
+        for trade in self.trades_300s() {
+            let Some(sol_amount) = policy_amount(trade) else {
+                continue;
+            };
+            let flow = program_breakdown_300s
+                .entry(trade.source_program.clone())
+                .or_default();
+            match trade.direction {
+                TradeDirection::Buy => {
+                    flow.net_flow_sol += sol_amount;
+                    flow.buy_count += 1;
+                }
+                TradeDirection::Sell => {
+                    flow.net_flow_sol -= sol_amount;
+                    flow.sell_count += 1;
+                }
+                TradeDirection::Unknown => {}
+            }
         }
 
-        // Compute metrics for each window
-        let (net_flow_60s, buy_count_60s, sell_count_60s) =
-            compute_window_metrics(&self.trades_60s);
-        let (net_flow_300s, buy_count_300s, sell_count_300s) =
-            compute_window_metrics(&self.trades_300s);
-        let (net_flow_900s, buy_count_900s, sell_count_900s) =
-            compute_window_metrics(&self.trades_900s);
-        let (net_flow_3600s, _, _) =
-            compute_window_metrics(&self.trades_3600s);
-        let (net_flow_7200s, _, _) =
-            compute_window_metrics(&self.trades_7200s);
-        let (net_flow_14400s, _, _) =
-            compute_window_metrics(&self.trades_14400s);
-
-        // Phase 3-A: Detect bot wallets in 300s window
-        let (bot_wallets, bot_trades_count) = detect_bot_wallets(&self.trades_300s);
+        // Transaction fees paid by trades in the 300s window
+        let fees_paid_300s_sol = self
+            .trades_300s()
+            .iter()
+            .map(|trade| trade.fee_lamports as f64)
+            .sum::<f64>()
+            / 1_000_000_000.0;
+        let priority_fees_300s_sol = self
+            .trades_300s()
+            .iter()
+            .map(|trade| trade.priority_fee_lamports as f64)
+            .sum::<f64>()
+            / 1_000_000_000.0;
+        let jito_tips_300s_sol = self
+            .trades_300s()
+            .iter()
+            .map(|trade| trade.jito_tip_lamports as f64)
+            .sum::<f64>()
+            / 1_000_000_000.0;
+
+        // Phase 3-A: Detect bot wallets in 300s window (cached per wallet)
+        let (bot_wallets_count, bot_trades_count) = self.bot_counts_300s();
 
         // Phase 6: DCA buy counts from timestamp queues
         let dca_buys_60s = self.dca_timestamps_60s.len() as i32;
@@ -790,7 +1924,22 @@ impl TokenRollingState {
             buy_count_900s,
             sell_count_900s,
             unique_wallets_300s: self.unique_wallets_300s.len() as i32,
-            bot_wallets_count_300s: bot_wallets.len() as i32,
+            unique_clusters_300s: self.unique_wallets_300s.len() as i32,
+            unique_wallets_3600s: self.minute_buckets.summary(3600).unique_wallets as i32,
+            unique_wallets_7200s: self.minute_buckets.summary(7200).unique_wallets as i32,
+            unique_wallets_14400s: self.minute_buckets.summary(14400).unique_wallets as i32,
+            new_wallets_300s: self.new_wallets_300s.len() as i32,
+            fees_paid_300s_sol,
+            priority_fees_300s_sol,
+            jito_tips_300s_sol,
+            program_breakdown_300s,
+            early_buyers_count: self.early_buyers.len() as i32,
+            early_holder_retention: self.early_holder_retention(),
+            fresh_wallet_ratio_60s: None,
+            fresh_wallet_ratio_300s: None,
+            fresh_wallet_ratio_900s: None,
+            net_flow_by_minute_60m: self.minute_buckets.flow_series(SPARKLINE_MINUTES),
+            bot_wallets_count_300s: bot_wallets_count,
             bot_trades_count_300s: bot_trades_count,
             // Phase 6: DCA Rolling Windows
             dca_buys_60s,
@@ -798,6 +1947,13 @@ impl TokenRollingState {
             dca_buys_900s,
             dca_buys_3600s,
             dca_buys_14400s,
+            net_flow_60s_final_sol: net_flow_60s_final,
+            net_flow_300s_final_sol: net_flow_300s_final,
+            net_flow_900s_final_sol: net_flow_900s_final,
+            buy_count_300s_final,
+            sell_count_300s_final,
+            unfinalized_trades_300s,
+            windows: window_metrics,
         }
     }
 }
@@ -827,6 +1983,7 @@ mod tests {
             token_decimals: 6,
             user_account: user_account.to_string(),
             source_program: "test_program".to_string(),
+            ..Default::default()
         }
     }
 
@@ -974,6 +2131,48 @@ mod tests {
         assert_eq!(metrics.bot_trades_count_300s, 5);
     }
 
+    #[test]
+    fn test_bot_detection_paid_to_land() {
+        // Scenario: Two wallets, 3 spaced-out buys of different sizes each;
+        // one tips Jito or pays a high priority fee every time
+        let mut state = TokenRollingState::new("test_mint".to_string());
+
+        let base_time = 1000;
+        for i in 0..3 {
+            let mut bundled = make_trade(
+                base_time + i * 60,
+                "test_mint",
+                TradeDirection::Buy,
+                0.5 + i as f64,
+                "bundled_bot",
+            );
+            if i == 1 {
+                bundled.priority_fee_lamports = 2_000_000;
+            } else {
+                bundled.jito_tip_lamports = 100_000;
+            }
+            state.add_trade(bundled);
+
+            let mut manual = make_trade(
+                base_time + i * 60 + 5,
+                "test_mint",
+                TradeDirection::Buy,
+                0.7 + i as f64,
+                "manual_wallet",
+            );
+            manual.priority_fee_lamports = 10_000;
+            state.add_trade(manual);
+        }
+
+        let metrics = state.compute_rolling_metrics();
+
+        // Expect: Only the wallet paying to land every trade is a bot
+        assert_eq!(metrics.bot_wallets_count_300s, 1);
+        assert_eq!(metrics.bot_trades_count_300s, 3);
+        assert!((metrics.jito_tips_300s_sol - 0.0002).abs() < 1e-12);
+        assert!((metrics.priority_fees_300s_sol - 0.00203).abs() < 1e-12);
+    }
+
     #[test]
     fn test_bot_detection_mixed_activity() {
         // Scenario: Mix of normal wallets and bot wallets
@@ -1401,6 +2600,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("spot_wallet_{}", i),
                 source_program: "PumpSwap".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1416,6 +2616,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("dca_wallet_{}", i),
                 source_program: "JupiterDCA".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1439,6 +2640,130 @@ mod tests {
         assert!(details.contains("spot_buys"));
     }
 
+    #[test]
+    fn test_dca_conviction_scaled_by_committed_sol() {
+        // Scenario: identical overlap, but one DCA order is dust and one is large
+        fn state_with_order(committed_sol_remaining: f64) -> TokenRollingState {
+            let mut state = TokenRollingState::new("dca_size_mint".to_string());
+            let base_time = 10000;
+
+            for i in 0..10 {
+                state.add_trade(TradeEvent {
+                    user_account: format!("spot_wallet_{}", i),
+                    ..TradeEvent::test_buy("dca_size_mint", base_time + i * 10)
+                });
+            }
+
+            for i in 0..5 {
+                state.add_trade(TradeEvent {
+                    timestamp: base_time + i * 20 + 5,
+                    mint: "dca_size_mint".to_string(),
+                    direction: TradeDirection::Buy,
+                    sol_amount: 0.1,
+                    token_amount: 100.0,
+                    token_decimals: 6,
+                    user_account: "dca_wallet".to_string(),
+                    source_program: "JupiterDCA".to_string(),
+                    dca_order: Some(DcaOrderInfo {
+                        order_account: "dca_order_1".to_string(),
+                        committed_sol_remaining,
+                        sol_per_cycle: 0.1,
+                        remaining_cycles: (committed_sol_remaining / 0.1) as u64,
+                    }),
+                    ..Default::default()
+                });
+            }
+
+            state
+        }
+
+        let dust = state_with_order(0.5);
+        let large = state_with_order(100.0);
+
+        // Repeated fills of the same order collapse to one entry
+        assert_eq!(dust.dca_orders.len(), 1);
+
+        let find_dca = |state: &TokenRollingState| {
+            state
+                .detect_signals(10120, None)
+                .into_iter()
+                .find(|s| s.signal_type == SignalType::DcaConviction)
+                .expect("DCA_CONVICTION should be detected")
+        };
+        let dust_signal = find_dca(&dust);
+        let large_signal = find_dca(&large);
+
+        assert!(dust_signal.score.unwrap() < large_signal.score.unwrap());
+        assert!((large_signal.score.unwrap() - 1.0).abs() < 1e-9); // Full overlap, full weight
+        let details = SignalDetails::from_json(dust_signal.details_json.as_ref().unwrap()).unwrap();
+        match details {
+            SignalDetails::DcaConviction(d) => assert_eq!(d.committed_sol_remaining, Some(0.5)),
+            other => panic!("unexpected details: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unique_wallets_long_windows() {
+        let mut state = TokenRollingState::new("test_mint".to_string());
+        let base_time = 1_000_000;
+
+        // 30 wallets an hour and a half ago, 5 of which return now
+        for i in 0..30 {
+            state.add_trade(make_trade(
+                base_time,
+                "test_mint",
+                TradeDirection::Buy,
+                1.0,
+                &format!("wallet_{}", i),
+            ));
+        }
+        let now = base_time + 5400;
+        for i in 0..5 {
+            state.add_trade(make_trade(
+                now,
+                "test_mint",
+                TradeDirection::Sell,
+                1.0,
+                &format!("wallet_{}", i),
+            ));
+        }
+        state.evict_old_trades(now);
+
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.unique_wallets_300s, 5);
+        assert_eq!(metrics.unique_wallets_3600s, 5);
+        assert_eq!(metrics.unique_wallets_7200s, 30);
+        assert_eq!(metrics.unique_wallets_14400s, 30);
+    }
+
+    #[test]
+    fn test_dca_orders_evicted_with_long_window() {
+        let mut state = TokenRollingState::new("dca_evict_mint".to_string());
+        state.add_trade(TradeEvent {
+            timestamp: 1000,
+            mint: "dca_evict_mint".to_string(),
+            direction: TradeDirection::Buy,
+            sol_amount: 0.1,
+            token_amount: 100.0,
+            token_decimals: 6,
+            user_account: "dca_wallet".to_string(),
+            source_program: "JupiterDCA".to_string(),
+            dca_order: Some(DcaOrderInfo {
+                order_account: "dca_order_1".to_string(),
+                committed_sol_remaining: 10.0,
+                sol_per_cycle: 0.1,
+                remaining_cycles: 100,
+            }),
+            ..Default::default()
+        });
+
+        state.evict_old_trades(1000 + 14400);
+        assert_eq!(state.dca_orders.len(), 1);
+
+        state.evict_old_trades(1000 + 14401);
+        assert!(state.dca_orders.is_empty());
+    }
+
     #[test]
     fn test_dca_conviction_no_overlap() {
         // Scenario: DCA BUYs but no overlapping spot BUYs → no signal
@@ -1457,6 +2782,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("spot_wallet_{}", i),
                 source_program: "PumpSwap".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1472,6 +2798,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("dca_wallet_{}", i),
                 source_program: "JupiterDCA".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1500,6 +2827,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("spot_wallet_{}", i),
                 source_program: "BonkSwap".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1521,6 +2849,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("dca_wallet_{}", i),
                 source_program: "JupiterDCA".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1551,6 +2880,7 @@ mod tests {
                     token_decimals: 6,
                     user_account: format!("{}_wallet_{}", program, i),
                     source_program: program.to_string(),
+                    ..Default::default()
                 };
                 state.add_trade(trade);
             }
@@ -1567,6 +2897,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("dca_wallet_{}", i),
                 source_program: "JupiterDCA".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1577,6 +2908,33 @@ mod tests {
         assert!(signals.iter().any(|s| s.signal_type == SignalType::DcaConviction));
     }
 
+    #[test]
+    fn test_dca_conviction_counts_raydium_spot_buys() {
+        let mut state = TokenRollingState::new("raydium_mint".to_string());
+        let base_time = 10000;
+        let trade = |timestamp: i64, program: &str, wallet: String| TradeEvent {
+            timestamp,
+            mint: "raydium_mint".to_string(),
+            direction: TradeDirection::Buy,
+            sol_amount: 1.0,
+            token_amount: 1000.0,
+            token_decimals: 6,
+            user_account: wallet,
+            source_program: program.to_string(),
+            ..Default::default()
+        };
+
+        // Spot buys only on Raydium pools, DCA fills alongside them
+        for i in 0..4 {
+            let program = if i % 2 == 0 { "RaydiumAMM" } else { "RaydiumCLMM" };
+            state.add_trade(trade(base_time + i * 20, program, format!("spot_{}", i)));
+            state.add_trade(trade(base_time + i * 20 + 5, "JupiterDCA", format!("dca_{}", i)));
+        }
+
+        let signals = state.detect_signals(base_time + 120, None);
+        assert!(signals.iter().any(|s| s.signal_type == SignalType::DcaConviction));
+    }
+
     #[test]
     fn test_dca_conviction_only_buy_direction() {
         // Scenario: SELL trades should NOT be considered for DCA_CONVICTION
@@ -1595,6 +2953,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("spot_wallet_{}", i),
                 source_program: "PumpSwap".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1610,6 +2969,7 @@ mod tests {
                 token_decimals: 6,
                 user_account: format!("dca_wallet_{}", i),
                 source_program: "JupiterDCA".to_string(),
+                ..Default::default()
             };
             state.add_trade(trade);
         }
@@ -1647,6 +3007,7 @@ mod tests {
                     token_decimals: 6,
                     user_account: format!("spot_{}", i),
                     source_program: "PumpSwap".to_string(),
+                    ..Default::default()
                 };
                 state.add_trade(trade);
             }
@@ -1668,6 +3029,7 @@ mod tests {
                     token_decimals: 6,
                     user_account: format!("dca_{}", i),
                     source_program: "JupiterDCA".to_string(),
+                    ..Default::default()
                 };
                 state.add_trade(trade);
             }
@@ -1689,4 +3051,431 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_program_activity_stores_timestamps_and_evicts() {
+        // Scenario: Program summaries hold timestamps only and drop with the 4h window
+        let mut state = TokenRollingState::new("activity_mint".to_string());
+        let base_time = 100_000;
+
+        state.add_trade(make_trade(base_time, "activity_mint", TradeDirection::Buy, 1.0, "w1"));
+        state.add_trade(make_trade(base_time + 10, "activity_mint", TradeDirection::Sell, 1.0, "w2"));
+
+        let activity = state.program_activity.get("test_program").unwrap();
+        assert_eq!(activity.trade_count(), 2);
+        assert_eq!(activity.buy_timestamps.len(), 1);
+
+        // Evict past the 14400s window: empty summaries are removed entirely
+        state.evict_old_trades(base_time + 14400 + 11);
+        assert!(state.program_activity.is_empty());
+    }
+
+    #[test]
+    fn test_new_wallets_counts_first_trades_only() {
+        // Scenario: Returning wallets stay unique but are not new
+        let mut state = TokenRollingState::new("new_wallet_mint".to_string());
+        let base_time = 100_000;
+
+        // w1 and w2 trade early, then return 10 minutes later
+        state.add_trade(make_trade(base_time, "new_wallet_mint", TradeDirection::Buy, 1.0, "w1"));
+        state.add_trade(make_trade(base_time, "new_wallet_mint", TradeDirection::Buy, 1.0, "w2"));
+
+        let later = base_time + 600;
+        state.add_trade(make_trade(later, "new_wallet_mint", TradeDirection::Buy, 1.0, "w1"));
+        state.add_trade(make_trade(later, "new_wallet_mint", TradeDirection::Buy, 1.0, "w2"));
+        state.add_trade(make_trade(later, "new_wallet_mint", TradeDirection::Buy, 1.0, "w3"));
+        state.evict_old_trades(later);
+
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.unique_wallets_300s, 3);
+        assert_eq!(metrics.new_wallets_300s, 1);
+
+        // First-seen history is dropped with the 14400s window
+        state.evict_old_trades(later + 14400 + 1);
+        assert!(state.wallet_first_seen.is_empty());
+    }
+
+    #[test]
+    fn test_wallet_first_seen_capped() {
+        // Scenario: A mint with more distinct wallets than the cap inside 4 hours
+        let mut state = TokenRollingState::new("crowded_mint".to_string());
+        let base_time = 100_000;
+        for i in 0..(MAX_WALLET_FIRST_SEEN + 1) {
+            let ts = base_time + (i / 10) as i64;
+            state.wallet_first_seen.insert(format!("w{}", i), (ts, ts));
+        }
+
+        state.evict_old_trades(base_time + 3600);
+
+        // Trimmed to the target, keeping the most recently active wallets
+        assert_eq!(state.wallet_first_seen.len(), WALLET_FIRST_SEEN_TRIM_TO);
+        assert!(state.wallet_first_seen.contains_key(&format!("w{}", MAX_WALLET_FIRST_SEEN)));
+        assert!(!state.wallet_first_seen.contains_key("w0"));
+    }
+
+    #[test]
+    fn test_breakout_with_new_wallet_metric() {
+        // Scenario: BREAKOUT flow driven by wallets that were already holders
+        let mut state = TokenRollingState::new("returning_mint".to_string());
+        let base_time = 100_000;
+
+        for i in 0..8 {
+            state.add_trade(make_trade(base_time, "returning_mint", TradeDirection::Buy, 0.1, &format!("wallet_{}", i)));
+        }
+
+        let later = base_time + 1000;
+        for i in 0..20 {
+            state.add_trade(make_trade(
+                later + i as i64,
+                "returning_mint",
+                TradeDirection::Buy,
+                1.0,
+                &format!("wallet_{}", i % 8),
+            ));
+        }
+        state.evict_old_trades(later + 30);
+
+        let any = state.detect_signals_with_wallet_metric(later + 30, None, WalletGrowthMetric::AnyActivity);
+        assert!(any.iter().any(|s| s.signal_type == SignalType::Breakout));
+
+        let new_only = state.detect_signals_with_wallet_metric(later + 30, None, WalletGrowthMetric::NewWallets);
+        assert!(!new_only.iter().any(|s| s.signal_type == SignalType::Breakout));
+    }
+
+    #[test]
+    fn test_wallet_growth_metric_parse() {
+        assert_eq!(WalletGrowthMetric::parse("new"), Some(WalletGrowthMetric::NewWallets));
+        assert_eq!(WalletGrowthMetric::parse(" ANY "), Some(WalletGrowthMetric::AnyActivity));
+        assert_eq!(WalletGrowthMetric::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_signal_thresholds_config_applied() {
+        // Scenario: 20 SOL of buys in 30s from 20 wallets
+        let mut state = TokenRollingState::new("tuned_mint".to_string());
+        let base_time = 100_000;
+        for i in 0..20 {
+            state.add_trade(make_trade(base_time + i as i64, "tuned_mint", TradeDirection::Buy, 1.0, &format!("wallet_{}", i)));
+        }
+        let metrics = state.compute_rolling_metrics();
+        let detect = |thresholds: &SignalThresholdsConfig| {
+            state.detect_signals_with(
+                &SignalDetectorRegistry::with_defaults(),
+                thresholds,
+                &metrics,
+                base_time + 30,
+                None,
+                WalletGrowthMetric::AnyActivity,
+            )
+        };
+
+        let defaults = detect(&SignalThresholdsConfig::default());
+        assert!(defaults.iter().any(|s| s.signal_type == SignalType::Breakout));
+
+        let mut raised = SignalThresholdsConfig::default();
+        raised.breakout.net_flow_60s_min = 25.0;
+        let tuned = detect(&raised);
+        assert!(!tuned.iter().any(|s| s.signal_type == SignalType::Breakout));
+    }
+
+    #[test]
+    fn test_bot_cache_reuses_clean_classifications() {
+        // Scenario: Cached classifications survive until the wallet trades again
+        let mut state = TokenRollingState::new("cache_mint".to_string());
+        let base_time = 1000;
+
+        for i in 0..12 {
+            state.add_trade(make_trade(base_time + i * 10, "cache_mint", TradeDirection::Buy, 1.0 + i as f64, "bot"));
+        }
+        state.add_trade(make_trade(base_time + 5, "cache_mint", TradeDirection::Buy, 2.0, "human"));
+        state.evict_old_trades(base_time + 120);
+        state.refresh_bot_cache(base_time + 120);
+
+        assert!(state.bot_cache_dirty.is_empty());
+        assert_eq!(state.bot_wallets_300s.len(), 1);
+        let evaluated_at = state.bot_cache["human"].evaluated_at;
+
+        // New trade from the bot only re-evaluates the bot
+        state.add_trade(make_trade(base_time + 125, "cache_mint", TradeDirection::Buy, 3.0, "bot"));
+        state.evict_old_trades(base_time + 125);
+        assert!(state.bot_cache_dirty.contains("bot"));
+        assert!(!state.bot_cache_dirty.contains("human"));
+
+        state.refresh_bot_cache(base_time + 125);
+        assert_eq!(state.bot_cache["human"].evaluated_at, evaluated_at);
+        assert_eq!(state.bot_cache["bot"].trade_count, 13);
+
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.bot_wallets_count_300s, 1);
+        assert_eq!(metrics.bot_trades_count_300s, 13);
+    }
+
+    #[test]
+    fn test_bot_cache_invalidated_by_eviction_and_ttl() {
+        // Scenario: Eviction and TTL expiry both force re-evaluation
+        let mut state = TokenRollingState::new("ttl_mint".to_string());
+        let base_time = 1000;
+
+        for i in 0..11 {
+            state.add_trade(make_trade(base_time + i * 2, "ttl_mint", TradeDirection::Buy, 1.0 + i as f64 * 0.1, "wallet"));
+        }
+        state.add_trade(make_trade(base_time + 200, "ttl_mint", TradeDirection::Buy, 5.0, "wallet"));
+        state.refresh_bot_cache(base_time + 200);
+        assert!(state.bot_cache["wallet"].is_bot);
+
+        // Early trades age out: the wallet drops below the high-frequency threshold
+        state.evict_old_trades(base_time + 305);
+        assert!(state.bot_cache_dirty.contains("wallet"));
+        state.refresh_bot_cache(base_time + 305);
+        assert!(!state.bot_cache["wallet"].is_bot);
+        assert!(state.bot_wallets_300s.is_empty());
+
+        // TTL expiry re-evaluates even without changes
+        state.refresh_bot_cache(base_time + 305 + BOT_CACHE_TTL_SECS + 1);
+        assert_eq!(state.bot_cache["wallet"].evaluated_at, base_time + 305 + BOT_CACHE_TTL_SECS + 1);
+
+        // Wallet leaving the window is dropped from the cache
+        state.evict_old_trades(base_time + 600);
+        assert!(state.bot_cache.is_empty());
+    }
+
+    /// 30 modest buys spread over the hour, 10 recent small buys and one 100 SOL arb buy
+    fn make_outlier_state(base_time: i64) -> TokenRollingState {
+        let mut state = TokenRollingState::new("outlier_mint".to_string());
+        for i in 0..30 {
+            state.add_trade(make_trade(base_time - 3000 + i * 90, "outlier_mint", TradeDirection::Buy, 0.5, &format!("old_{}", i)));
+        }
+        for i in 0..10 {
+            state.add_trade(make_trade(base_time + i * 5, "outlier_mint", TradeDirection::Buy, 0.3, &format!("recent_{}", i)));
+        }
+        state.add_trade(make_trade(base_time + 55, "outlier_mint", TradeDirection::Buy, 100.0, "arb"));
+        state.evict_old_trades(base_time + 60);
+        state
+    }
+
+    #[test]
+    fn test_outlier_policy_cap_and_exclude() {
+        let base_time = 10000;
+        let state = make_outlier_state(base_time);
+
+        let off = state.compute_rolling_metrics();
+        assert!((off.net_flow_60s_sol - 103.0).abs() < 1e-9);
+        assert_eq!(off.buy_count_60s, 11);
+
+        let capped = state.compute_rolling_metrics_with(OutlierPolicy {
+            mode: OutlierMode::Cap,
+            max_stddev: 4.0,
+        });
+        assert!(capped.net_flow_60s_sol < off.net_flow_60s_sol);
+        assert!(capped.net_flow_60s_sol > 3.0);
+        assert_eq!(capped.buy_count_60s, 11);
+
+        let excluded = state.compute_rolling_metrics_with(OutlierPolicy {
+            mode: OutlierMode::Exclude,
+            max_stddev: 4.0,
+        });
+        assert!((excluded.net_flow_60s_sol - 3.0).abs() < 1e-9);
+        assert_eq!(excluded.buy_count_60s, 10);
+    }
+
+    #[test]
+    fn test_outlier_exclusion_suppresses_false_surge() {
+        let base_time = 10000;
+        let state = make_outlier_state(base_time);
+        let now = base_time + 60;
+
+        let signals = state.detect_signals(now, None);
+        assert!(signals.iter().any(|s| s.signal_type == SignalType::Surge));
+
+        let metrics = state.compute_rolling_metrics_with(OutlierPolicy {
+            mode: OutlierMode::Exclude,
+            max_stddev: 4.0,
+        });
+        let signals = state.detect_signals_from_metrics(&metrics, now, None, WalletGrowthMetric::AnyActivity);
+        assert!(!signals.iter().any(|s| s.signal_type == SignalType::Surge));
+    }
+
+    #[test]
+    fn test_outlier_policy_needs_baseline_samples() {
+        let mut state = TokenRollingState::new("thin_mint".to_string());
+        state.add_trade(make_trade(1000, "thin_mint", TradeDirection::Buy, 0.1, "a"));
+        state.add_trade(make_trade(1001, "thin_mint", TradeDirection::Buy, 50.0, "b"));
+
+        let policy = OutlierPolicy { mode: OutlierMode::Exclude, max_stddev: 1.0 };
+        let metrics = state.compute_rolling_metrics_with(policy);
+        assert!((metrics.net_flow_60s_sol - 50.1).abs() < 1e-9);
+        assert_eq!(OutlierMode::parse("CAP"), Some(OutlierMode::Cap));
+        assert_eq!(OutlierMode::parse("bogus"), None);
+    }
+
+    #[test]
+    fn test_program_breakdown_300s() {
+        use crate::pipeline::types::AggregatedTokenState;
+
+        let mut state = TokenRollingState::new("venue_mint".to_string());
+        let mut pump = make_trade(1000, "venue_mint", TradeDirection::Buy, 3.0, "a");
+        pump.source_program = "PumpSwap".to_string();
+        let mut pump_sell = make_trade(1001, "venue_mint", TradeDirection::Sell, 1.0, "b");
+        pump_sell.source_program = "PumpSwap".to_string();
+        let mut bonk = make_trade(1002, "venue_mint", TradeDirection::Buy, 2.0, "c");
+        bonk.source_program = "BonkSwap".to_string();
+        state.add_trade(pump);
+        state.add_trade(pump_sell);
+        state.add_trade(bonk);
+
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.program_breakdown_300s.len(), 2);
+        assert_eq!(
+            metrics.program_breakdown_300s["PumpSwap"],
+            ProgramFlow { net_flow_sol: 2.0, buy_count: 1, sell_count: 1 }
+        );
+        assert_eq!(metrics.program_breakdown_300s["BonkSwap"].net_flow_sol, 2.0);
+
+        let aggregate = AggregatedTokenState::from_metrics("venue_mint", &metrics, None, 1002, 1010);
+        let json: serde_json::Value =
+            serde_json::from_str(aggregate.program_breakdown_300s_json.as_deref().unwrap()).unwrap();
+        assert_eq!(json["PumpSwap"]["buy_count"], 1);
+        assert_eq!(json["BonkSwap"]["net_flow_sol"], 2.0);
+    }
+
+    #[test]
+    fn test_early_holder_retention() {
+        let mut state = TokenRollingState::new("early_mint".to_string());
+        assert_eq!(state.early_holder_retention(), None);
+
+        // Sells before any tracked buy are ignored
+        state.add_trade(make_trade(999, "early_mint", TradeDirection::Sell, 1.0, "seller"));
+        for i in 0..(EARLY_BUYER_COUNT + 5) {
+            state.add_trade(make_trade(1000 + i as i64, "early_mint", TradeDirection::Buy, 1.0, &format!("buyer_{}", i)));
+        }
+        assert_eq!(state.early_buyers.len(), EARLY_BUYER_COUNT);
+        assert_eq!(state.early_holder_retention(), Some(1.0));
+
+        // Half the early buyers sell everything; a late buyer selling doesn't count
+        for i in 0..EARLY_BUYER_COUNT / 2 {
+            state.add_trade(make_trade(2000 + i as i64, "early_mint", TradeDirection::Sell, 1.0, &format!("buyer_{}", i)));
+        }
+        state.add_trade(make_trade(2100, "early_mint", TradeDirection::Sell, 1.0, &format!("buyer_{}", EARLY_BUYER_COUNT + 1)));
+
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.early_buyers_count, EARLY_BUYER_COUNT as i32);
+        assert_eq!(metrics.early_holder_retention, Some(0.5));
+    }
+
+    #[test]
+    fn test_confirm_through_slot_final_view() {
+        let mut state = TokenRollingState::new("final_mint".to_string());
+        for (ts, slot, direction, sol) in [
+            (1000, 100, TradeDirection::Buy, 4.0),
+            (1010, 101, TradeDirection::Sell, 1.0),
+            (1020, 102, TradeDirection::Buy, 2.0),
+        ] {
+            let mut trade = make_trade(ts, "final_mint", direction, sol, "wallet");
+            trade.slot = slot;
+            trade.confirmation = Confirmation::Processed;
+            state.add_trade(trade);
+        }
+
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.net_flow_300s_sol, 5.0);
+        assert_eq!(metrics.net_flow_300s_final_sol, 0.0);
+        assert_eq!(metrics.unfinalized_trades_300s, 3);
+
+        // Confirmed doesn't reach the final view
+        assert_eq!(state.confirm_through_slot(102, Confirmation::Confirmed), 3);
+        assert_eq!(state.compute_rolling_metrics().unfinalized_trades_300s, 3);
+
+        assert_eq!(state.confirm_through_slot(101, Confirmation::Finalized), 2);
+        assert_eq!(state.unfinalized_min_slot, Some(102));
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.net_flow_300s_final_sol, 3.0);
+        assert_eq!(metrics.buy_count_300s_final, 1);
+        assert_eq!(metrics.sell_count_300s_final, 1);
+        assert_eq!(metrics.unfinalized_trades_300s, 1);
+
+        // Nothing at or below an already-finalized slot is revisited
+        assert_eq!(state.confirm_through_slot(101, Confirmation::Finalized), 0);
+        assert_eq!(state.trades_60s()[2].confirmation, Confirmation::Confirmed);
+    }
+
+    #[test]
+    fn test_evict_trades_after_slot() {
+        let mut state = TokenRollingState::new("fork_mint".to_string());
+        let mut trade = |ts: i64, slot: u64, direction, sol: f64, wallet: &str| {
+            let mut trade = make_trade(ts, "fork_mint", direction, sol, wallet);
+            trade.slot = slot;
+            state.add_trade(trade);
+        };
+        trade(1000, 100, TradeDirection::Buy, 2.0, "kept");
+        trade(1010, 101, TradeDirection::Buy, 5.0, "forked_buyer");
+        trade(1020, 102, TradeDirection::Sell, 1.0, "kept");
+        trade(1030, 102, TradeDirection::Buy, 3.0, "forked_buyer");
+
+        assert_eq!(state.evict_trades_after_slot(101, 1030), 0);
+        assert_eq!(state.evict_trades_in_slots(101..=101, 1030), 1);
+        assert_eq!(state.evict_trades_after_slot(101, 1030), 2);
+
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.net_flow_300s_sol, 2.0);
+        assert_eq!(metrics.net_flow_3600s_sol, 2.0);
+        assert_eq!(metrics.unique_wallets_300s, 1);
+        assert_eq!(state.trades_900s().len(), 1);
+        assert!(!state.wallet_first_seen.contains_key("forked_buyer"));
+        assert_eq!(state.early_buyers.len(), 1);
+    }
+
+    #[test]
+    fn test_windows_are_suffixes_of_one_buffer() {
+        let mut state = TokenRollingState::new("ring_mint".to_string());
+        // Late arrivals are placed by timestamp, not arrival order
+        for ts in [1000, 1500, 1900, 1700, 1950] {
+            state.add_trade(make_trade(ts, "ring_mint", TradeDirection::Buy, 1.0, &format!("w{}", ts)));
+        }
+        state.evict_old_trades(2000);
+
+        let timestamps = |trades: &[TradeEvent]| trades.iter().map(|t| t.timestamp).collect::<Vec<_>>();
+        assert_eq!(timestamps(state.trades_900s()), vec![1500, 1700, 1900, 1950]);
+        assert_eq!(timestamps(state.trades_300s()), vec![1700, 1900, 1950]);
+        assert_eq!(timestamps(state.trades_60s()), vec![1950]);
+
+        let metrics = state.compute_rolling_metrics();
+        assert_eq!(metrics.buy_count_60s, 1);
+        assert_eq!(metrics.buy_count_300s, 3);
+        assert_eq!(metrics.buy_count_900s, 4);
+
+        // Windows only move forward, and the buffer drops what left the 900s window
+        state.evict_old_trades(1900);
+        assert_eq!(timestamps(state.trades_60s()), vec![1950]);
+        state.evict_old_trades(2600);
+        assert_eq!(timestamps(state.trades_900s()), vec![1700, 1900, 1950]);
+        assert!(state.trades_60s().is_empty());
+    }
+
+    #[test]
+    fn test_configured_windows_from_buffer_and_minute_buckets() {
+        let mut state = TokenRollingState::new("windows_mint".to_string());
+        state.add_trade(make_trade(8_000, "windows_mint", TradeDirection::Buy, 3.0, "w1"));
+        state.add_trade(make_trade(9_980, "windows_mint", TradeDirection::Sell, 0.5, "w2"));
+        state.add_trade(make_trade(10_010, "windows_mint", TradeDirection::Buy, 1.0, "w3"));
+        state.evict_old_trades(10_020);
+
+        let windows = WindowSet::parse("30s,1h").unwrap();
+        let metrics = state.compute_rolling_metrics_for(OutlierPolicy::default(), &windows);
+        let secs: Vec<i64> = metrics.windows.iter().map(|w| w.secs).collect();
+        assert_eq!(secs, vec![30, 60, 300, 900, 3600]);
+
+        let short = metrics.window(30).unwrap();
+        assert_eq!((short.net_flow_sol, short.buy_count, short.sell_count), (1.0, 1, 0));
+        // Core fields come from the same window computation
+        assert_eq!(metrics.net_flow_60s_sol, 0.5);
+        assert_eq!((metrics.buy_count_60s, metrics.sell_count_60s), (1, 1));
+
+        // The 1h window reaches past the trade buffer into the minute buckets
+        let hour = metrics.window(3600).unwrap();
+        assert_eq!((hour.net_flow_sol, hour.buy_count, hour.sell_count), (3.5, 2, 1));
+        assert_eq!(metrics.net_flow_3600s_sol, hour.net_flow_sol);
+
+        // Default windows add nothing beyond the fixed columns
+        assert_eq!(state.compute_rolling_metrics().windows.len(), 3);
+    }
 }
@@ -0,0 +1,165 @@
+//! Self-description of a running instance
+//!
+//! Operators and scripts shouldn't have to reconstruct what an instance is
+//! doing from its environment and startup logs. The runtime builds a
+//! `RuntimeStatus` at startup (configuration, tracked programs, rolling
+//! windows) and registers each output sink as it is enabled; the query API
+//! serves it at `GET /status` together with the database size and the last
+//! aggregate flush. `pipeline_runtime --status` fetches and prints it from the
+//! instance at `API_ADDR` (sending `API_TOKEN`).
+//!
+//! ```json
+//! {"version": "0.1.0", "instance_id": "host:123", "mode": "unified",
+//!  "started_at": 1700000000, "uptime_secs": 3600,
+//!  "config": {"flush_interval_ms": 5000, "windows_secs": [60, 300, 900], ...},
+//!  "programs": [{"name": "PumpSwap", "program_id": "pAMM..."}],
+//!  "sinks": ["sqlite", "live_ws", "notifier:alpha (discord)"],
+//!  "db": {"path": "/var/lib/solflow/solflow.db", "size_bytes": 52428800},
+//!  "last_flush_at": 1700003599, "last_flush_age_secs": 1}
+//! ```
+
+use super::config::PipelineConfig;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+/// A program the instance ingests trades for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrackedProgram {
+    pub name: String,
+    pub program_id: String,
+}
+
+/// Startup configuration plus the sinks enabled since
+#[derive(Debug)]
+pub struct RuntimeStatus {
+    started_at: i64,
+    mode: String,
+    instance_id: String,
+    config: Value,
+    programs: Vec<TrackedProgram>,
+    sinks: Mutex<Vec<String>>,
+}
+
+impl RuntimeStatus {
+    /// `mode` is how trades arrive: unified, legacy or replay
+    pub fn new(
+        config: &PipelineConfig,
+        mode: &str,
+        programs: Vec<TrackedProgram>,
+        started_at: i64,
+    ) -> Self {
+        let config_json = json!({
+            "channel_buffer": config.channel_buffer,
+            "flush_interval_ms": config.flush_interval_ms,
+            "price_interval_ms": config.price_interval_ms,
+            "metadata_interval_ms": config.metadata_interval_ms,
+            "engine_shards": config.engine_shards,
+            "windows_secs": config.windows.secs().collect::<Vec<_>>(),
+            "signal_warmup_secs": config.signal_warmup_secs,
+            "token_warmup_secs": config.token_warmup_secs,
+            "breakout_wallet_metric": format!("{:?}", config.breakout_wallet_metric),
+            "outlier_policy": format!("{:?}", config.outlier_policy),
+            "db_routes": config.db_routes,
+            "lease_enabled": config.lease_enabled,
+            "snapshot_path": config.snapshot_path,
+        });
+        Self {
+            started_at,
+            mode: mode.to_string(),
+            instance_id: config.instance_id.clone(),
+            config: config_json,
+            programs,
+            sinks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Record an enabled output sink (duplicates are ignored)
+    pub fn add_sink(&self, sink: impl Into<String>) {
+        let sink = sink.into();
+        let mut sinks = self.sinks.lock().unwrap();
+        if !sinks.contains(&sink) {
+            sinks.push(sink);
+        }
+    }
+
+    /// Status document with the database facts the caller looked up
+    pub fn to_json(
+        &self,
+        db_path: &str,
+        db_size_bytes: Option<u64>,
+        last_flush_at: Option<i64>,
+        now: i64,
+    ) -> Value {
+        json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "instance_id": self.instance_id,
+            "mode": self.mode,
+            "started_at": self.started_at,
+            "uptime_secs": now - self.started_at,
+            "config": self.config,
+            "programs": self.programs,
+            "sinks": *self.sinks.lock().unwrap(),
+            "db": {"path": db_path, "size_bytes": db_size_bytes},
+            "last_flush_at": last_flush_at,
+            "last_flush_age_secs": last_flush_at.map(|ts| now - ts),
+        })
+    }
+}
+
+/// Fetch `/status` from the query API at `addr` (`pipeline_runtime --status`)
+pub async fn fetch_status(addr: &str, token: Option<&str>) -> Result<Value, String> {
+    let url = if addr.starts_with("http://") || addr.starts_with("https://") {
+        format!("{}/status", addr.trim_end_matches('/'))
+    } else {
+        format!("http://{}/status", addr)
+    };
+    let mut request = reqwest::Client::new()
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(10));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("{}: {}", url, e))?;
+    response.json().await.map_err(|e| format!("{}: {}", url, e))
+}
+
+/// Database file size including its WAL (None if the file is missing)
+pub fn db_size_bytes(db_path: &str) -> Option<u64> {
+    let main = std::fs::metadata(db_path).ok()?.len();
+    let wal = std::fs::metadata(format!("{}-wal", db_path)).map_or(0, |m| m.len());
+    Some(main + wal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_reports_config_and_sinks() {
+        let config = PipelineConfig::from_env();
+        let programs = vec![TrackedProgram {
+            name: "PumpSwap".to_string(),
+            program_id: "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA".to_string(),
+        }];
+        let status = RuntimeStatus::new(&config, "unified", programs, 1000);
+        status.add_sink("sqlite");
+        status.add_sink("live_ws");
+        status.add_sink("sqlite");
+
+        let body = status.to_json("x.db", Some(4096), Some(1090), 1100);
+        assert_eq!(body["mode"], "unified");
+        assert_eq!(body["uptime_secs"], 100);
+        assert_eq!(body["sinks"], json!(["sqlite", "live_ws"]));
+        assert_eq!(body["programs"][0]["name"], "PumpSwap");
+        assert_eq!(body["config"]["flush_interval_ms"], config.flush_interval_ms);
+        assert_eq!(body["db"]["size_bytes"], 4096);
+        assert_eq!(body["last_flush_age_secs"], 10);
+
+        assert!(db_size_bytes("/nonexistent/solflow.db").is_none());
+    }
+}
@@ -0,0 +1,340 @@
+//! Telegram bot: signal alerts plus blocklist commands
+//!
+//! With `TELEGRAM_BOT_TOKEN` and `TELEGRAM_CHAT_IDS` set, the notifier pushes
+//! signals at or above `TELEGRAM_MIN_SEVERITY` to each chat (when
+//! `NOTIFY_ROUTES` is unset; otherwise route to a `telegram` sink there), and
+//! this module long-polls the bot's updates so operators can mute noisy
+//! tokens from their phone:
+//!
+//! ```text
+//! /block <mint> [reason]   add to mint_blocklist (permanent; blocked_by = telegram:<user>)
+//! /unblock <mint>          remove from mint_blocklist
+//! /blocked                 the 10 most recent blocks
+//! ```
+//!
+//! Only messages from the listed chats are acted on; anything else is
+//! ignored without a reply. The blocklist lives in the primary database
+//! (see `routing`), so blocks take effect on the next check everywhere.
+//!
+//! Environment variables:
+//! - `TELEGRAM_BOT_TOKEN`: Bot token, or a `file:`/`keyring:` reference (unset = disabled)
+//! - `TELEGRAM_CHAT_IDS`: Comma-separated chat IDs that receive alerts and may send commands
+//! - `TELEGRAM_MIN_SEVERITY`: Lowest severity pushed to the chats (default: 4)
+
+use crate::error::{codes, SolflowError};
+use crate::streamer_core::secrets::secret_from_env;
+use rusqlite::{params, Connection};
+use serde::Deserialize;
+use serde_json::json;
+use solana_pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Seconds Telegram holds a getUpdates request open waiting for messages
+const LONG_POLL_SECS: u64 = 25;
+
+/// Blocks listed by `/blocked`
+const MAX_LISTED_BLOCKS: i64 = 10;
+
+/// Bot token and the chats it serves
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelegramBotConfig {
+    pub bot_token: String,
+    pub chat_ids: Vec<String>,
+    pub min_severity: i32,
+}
+
+impl TelegramBotConfig {
+    /// Load from the environment (see module docs); None when no token is set
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let Some(bot_token) = secret_from_env("TELEGRAM_BOT_TOKEN")? else {
+            return Ok(None);
+        };
+        let chat_ids: Vec<String> = std::env::var("TELEGRAM_CHAT_IDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+        if chat_ids.is_empty() {
+            return Err("TELEGRAM_BOT_TOKEN is set but TELEGRAM_CHAT_IDS is empty".to_string());
+        }
+        let min_severity = std::env::var("TELEGRAM_MIN_SEVERITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(4);
+        Ok(Some(Self {
+            bot_token,
+            chat_ids,
+            min_severity,
+        }))
+    }
+}
+
+/// A command sent to the bot
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotCommand {
+    Block { mint: String, reason: Option<String> },
+    Unblock { mint: String },
+    ListBlocked,
+    Help,
+}
+
+/// Parse a message; None when it isn't a command (plain chat is ignored)
+pub fn parse_command(text: &str) -> Option<Result<BotCommand, String>> {
+    let mut words = text.split_whitespace();
+    // Commands in groups arrive as /block@SolflowBot
+    let command = words.next()?.strip_prefix('/')?;
+    let command = command.split('@').next().unwrap_or(command);
+    let mint = |mint: Option<&str>| match mint {
+        Some(mint) if Pubkey::from_str(mint).is_ok() => Ok(mint.to_string()),
+        Some(mint) => Err(format!("'{}' is not a mint address", mint)),
+        None => Err(format!("usage: /{} <mint>", command)),
+    };
+    Some(match command {
+        "block" => mint(words.next()).map(|mint| {
+            let reason = words.collect::<Vec<_>>().join(" ");
+            BotCommand::Block {
+                mint,
+                reason: (!reason.is_empty()).then_some(reason),
+            }
+        }),
+        "unblock" => mint(words.next()).map(|mint| BotCommand::Unblock { mint }),
+        "blocked" => Ok(BotCommand::ListBlocked),
+        _ => Ok(BotCommand::Help),
+    })
+}
+
+const HELP: &str = "/block <mint> [reason] - mute a token\n/unblock <mint> - unmute it\n/blocked - recent blocks";
+
+/// Apply a command to `mint_blocklist`, returning the reply text
+pub fn execute_command(
+    conn: &Connection,
+    command: &BotCommand,
+    user: &str,
+    now: i64,
+) -> Result<String, rusqlite::Error> {
+    Ok(match command {
+        BotCommand::Block { mint, reason } => {
+            conn.execute(
+                "INSERT INTO mint_blocklist (mint, reason, blocked_by, created_at, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, NULL)
+                 ON CONFLICT(mint) DO UPDATE SET
+                    reason = excluded.reason,
+                    blocked_by = excluded.blocked_by,
+                    created_at = excluded.created_at,
+                    expires_at = NULL",
+                params![mint, reason, format!("telegram:{}", user), now],
+            )?;
+            format!("🚫 Blocked {}", mint)
+        }
+        BotCommand::Unblock { mint } => {
+            match conn.execute("DELETE FROM mint_blocklist WHERE mint = ?1", [mint])? {
+                0 => format!("{} was not blocked", mint),
+                _ => format!("✅ Unblocked {}", mint),
+            }
+        }
+        BotCommand::ListBlocked => {
+            let mut stmt = conn.prepare(
+                "SELECT mint, reason, blocked_by FROM mint_blocklist
+                 WHERE expires_at IS NULL OR expires_at > ?1
+                 ORDER BY created_at DESC
+                 LIMIT ?2",
+            )?;
+            let lines = stmt
+                .query_map(params![now, MAX_LISTED_BLOCKS], |row| {
+                    let mint: String = row.get(0)?;
+                    let reason: Option<String> = row.get(1)?;
+                    let blocked_by: Option<String> = row.get(2)?;
+                    Ok(format!(
+                        "{} - {} ({})",
+                        mint,
+                        reason.as_deref().unwrap_or("no reason"),
+                        blocked_by.as_deref().unwrap_or("unknown")
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            if lines.is_empty() {
+                "Blocklist is empty".to_string()
+            } else {
+                lines.join("\n")
+            }
+        }
+        BotCommand::Help => HELP.to_string(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdatesResponse {
+    ok: bool,
+    #[serde(default)]
+    result: Vec<Update>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    message: Option<IncomingMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IncomingMessage {
+    chat: Chat,
+    from: Option<User>,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    id: i64,
+    username: Option<String>,
+}
+
+/// Long-polls the bot's updates and answers commands
+pub struct TelegramBot {
+    config: TelegramBotConfig,
+    conn: Mutex<Connection>,
+    client: reqwest::Client,
+    /// Next update_id to fetch (Telegram drops everything below it)
+    offset: i64,
+}
+
+impl TelegramBot {
+    pub fn new(db_path: &str, config: TelegramBotConfig) -> Result<Self, SolflowError> {
+        let conn = Connection::open(db_path)?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(LONG_POLL_SECS + 10))
+            .build()
+            .map_err(|e| SolflowError::config(codes::CONFIG_INVALID, e))?;
+        Ok(Self {
+            config,
+            conn: Mutex::new(conn),
+            client,
+            offset: 0,
+        })
+    }
+
+    pub fn config(&self) -> &TelegramBotConfig {
+        &self.config
+    }
+
+    fn api_url(&self, method: &str) -> String {
+        format!("https://api.telegram.org/bot{}/{}", self.config.bot_token, method)
+    }
+
+    /// Wait for new messages and answer the commands among them;
+    /// returns how many commands were handled
+    pub async fn poll_once(&mut self) -> Result<usize, String> {
+        // The bot token is part of the URL: keep it out of error messages
+        let response: UpdatesResponse = self
+            .client
+            .post(self.api_url("getUpdates"))
+            .json(&json!({
+                "offset": self.offset,
+                "timeout": LONG_POLL_SECS,
+                "allowed_updates": ["message"],
+            }))
+            .send()
+            .await
+            .map_err(|e| e.without_url().to_string())?
+            .json()
+            .await
+            .map_err(|e| e.without_url().to_string())?;
+        if !response.ok {
+            return Err(response.description.unwrap_or_else(|| "getUpdates failed".to_string()));
+        }
+
+        let mut handled = 0;
+        for update in response.result {
+            self.offset = self.offset.max(update.update_id + 1);
+            let Some(message) = update.message else { continue };
+            let chat_id = message.chat.id.to_string();
+            if !self.config.chat_ids.contains(&chat_id) {
+                continue;
+            }
+            let Some(command) = message.text.as_deref().and_then(parse_command) else {
+                continue;
+            };
+            let user = message
+                .from
+                .map(|user| user.username.unwrap_or_else(|| user.id.to_string()))
+                .unwrap_or_else(|| chat_id.clone());
+            let reply = match command {
+                Ok(command) => {
+                    let conn = self.conn.lock().unwrap();
+                    let now = chrono::Utc::now().timestamp();
+                    match execute_command(&conn, &command, &user, now) {
+                        Ok(reply) => {
+                            log::info!("📱 Telegram command from {}: {:?}", user, command);
+                            reply
+                        }
+                        Err(e) => format!("❌ {}", e),
+                    }
+                }
+                Err(usage) => usage,
+            };
+            self.reply(&chat_id, &reply).await?;
+            handled += 1;
+        }
+        Ok(handled)
+    }
+
+    async fn reply(&self, chat_id: &str, text: &str) -> Result<(), String> {
+        self.client
+            .post(self.api_url("sendMessage"))
+            .json(&json!({"chat_id": chat_id, "text": text}))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.without_url().to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+
+    #[test]
+    fn test_commands_update_blocklist() {
+        assert_eq!(
+            parse_command(&format!("/block@SolflowBot {} rug pull", MINT)),
+            Some(Ok(BotCommand::Block {
+                mint: MINT.to_string(),
+                reason: Some("rug pull".to_string()),
+            }))
+        );
+        assert!(parse_command("/block not-a-mint").unwrap().is_err());
+        assert!(parse_command("/unblock").unwrap().is_err());
+        assert_eq!(parse_command("/start"), Some(Ok(BotCommand::Help)));
+        assert!(parse_command("gm").is_none());
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../../sql/01_mint_blocklist.sql")).unwrap();
+        let block = parse_command(&format!("/block {}", MINT)).unwrap().unwrap();
+        execute_command(&conn, &block, "alice", 1000).unwrap();
+        // Re-blocking replaces the previous entry
+        execute_command(&conn, &block, "bob", 2000).unwrap();
+        let blocked_by: String = conn
+            .query_row("SELECT blocked_by FROM mint_blocklist WHERE mint = ?1", [MINT], |row| row.get(0))
+            .unwrap();
+        assert_eq!(blocked_by, "telegram:bob");
+        assert!(execute_command(&conn, &BotCommand::ListBlocked, "bob", 2000)
+            .unwrap()
+            .contains("telegram:bob"));
+
+        let unblock = BotCommand::Unblock { mint: MINT.to_string() };
+        assert!(execute_command(&conn, &unblock, "bob", 2000).unwrap().starts_with("✅"));
+        assert!(execute_command(&conn, &unblock, "bob", 2000).unwrap().contains("was not blocked"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Handlebars-style payload templates for notifier sinks
+//!
+//! A sink can carry per-signal-type templates so its messages can go straight
+//! into a trading bot, Slack or Notion without a transformer in between:
+//!
+//! ```json
+//! {"type": "webhook", "url": "https://hooks.slack.com/services/...",
+//!  "templates": {
+//!    "BREAKOUT": "{\"text\": \"🚀 {{symbol}} breakout (sev {{severity}}) {{dexscreener_url}}\"}",
+//!    "*": "{\"text\": \"{{text}}\"}"}}
+//! ```
+//!
+//! - `{{field}}` inserts the value; on webhook sinks strings are JSON-escaped
+//!   (without quotes) so they can sit inside a quoted JSON string, and missing
+//!   values render empty
+//! - `{{json field}}` inserts the value as a JSON literal (quoted string,
+//!   number or `null`), for nullable fields in JSON bodies
+//!
+//! Fields are those of `NotifySignal` plus `text` (the default one-line
+//! message) and `dexscreener_url`. Unknown fields and unclosed tags are
+//! rejected when the config is loaded. Templates apply to single signals;
+//! digests keep the built-in format.
+
+use super::notifier::NotifySignal;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Fields a template may reference
+pub const TEMPLATE_FIELDS: &[&str] = &[
+    "id",
+    "mint",
+    "signal_type",
+    "severity",
+    "score",
+    "window_seconds",
+    "source",
+    "created_at",
+    "symbol",
+    "pattern_tag",
+    "market_cap_usd",
+    "text",
+    "dexscreener_url",
+];
+
+/// Template key matching every signal type without its own template
+pub const FALLBACK_TEMPLATE_KEY: &str = "*";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Field { name: String, as_json: bool },
+}
+
+/// A parsed payload template
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct PayloadTemplate {
+    parts: Vec<Part>,
+}
+
+impl TryFrom<String> for PayloadTemplate {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl PayloadTemplate {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| format!("unclosed '{{{{' in template: {}", source))?;
+            let tag = after[..end].trim();
+            let (name, as_json) = match tag.strip_prefix("json ") {
+                Some(name) => (name.trim(), true),
+                None => (tag, false),
+            };
+            if !TEMPLATE_FIELDS.contains(&name) {
+                return Err(format!(
+                    "unknown template field '{}' (expected one of {})",
+                    name,
+                    TEMPLATE_FIELDS.join(", ")
+                ));
+            }
+            parts.push(Part::Field {
+                name: name.to_string(),
+                as_json,
+            });
+            rest = &after[end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+        Ok(Self { parts })
+    }
+
+    /// Render for a signal; `escape_json` JSON-escapes plain `{{field}}` strings
+    pub fn render(&self, signal: &NotifySignal, escape_json: bool) -> String {
+        let mut fields = serde_json::to_value(signal).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut fields {
+            map.insert("text".to_string(), signal.text().into());
+            map.insert(
+                "dexscreener_url".to_string(),
+                format!("https://dexscreener.com/solana/{}", signal.mint).into(),
+            );
+        }
+
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => out.push_str(text),
+                Part::Field { name, as_json } => {
+                    let value = fields.get(name).unwrap_or(&Value::Null);
+                    match (value, *as_json) {
+                        (value, true) => out.push_str(&value.to_string()),
+                        (Value::Null, false) => {}
+                        (Value::String(s), false) if escape_json => {
+                            // Encoded string minus its surrounding quotes
+                            let encoded = Value::String(s.clone()).to_string();
+                            out.push_str(&encoded[1..encoded.len() - 1]);
+                        }
+                        (Value::String(s), false) => out.push_str(s),
+                        (other, false) => out.push_str(&other.to_string()),
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal() -> NotifySignal {
+        NotifySignal {
+            id: 7,
+            mint: "mint1".to_string(),
+            signal_type: "BREAKOUT".to_string(),
+            severity: 4,
+            score: None,
+            window_seconds: 60,
+            source: "onchain".to_string(),
+            created_at: 1000,
+            symbol: Some("T\"Q".to_string()),
+            pattern_tag: None,
+            market_cap_usd: Some(250000.0),
+            sample_signatures: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_json_body() {
+        let template = PayloadTemplate::parse(
+            r#"{"text": "{{symbol}} sev {{severity}}", "score": {{json score}}, "tag": {{json pattern_tag}}, "url": "{{dexscreener_url}}"}"#,
+        )
+        .unwrap();
+        let body = template.render(&signal(), true);
+        let value: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["text"], "T\"Q sev 4");
+        assert!(value["score"].is_null());
+        assert!(value["tag"].is_null());
+        assert_eq!(value["url"], "https://dexscreener.com/solana/mint1");
+
+        let text = PayloadTemplate::parse("{{ signal_type }} {{symbol}}{{pattern_tag}}!").unwrap();
+        assert_eq!(text.render(&signal(), false), "BREAKOUT T\"Q!");
+    }
+
+    #[test]
+    fn test_rejects_bad_templates() {
+        assert!(PayloadTemplate::parse("{{wallet}}").unwrap_err().contains("wallet"));
+        assert!(PayloadTemplate::parse("{{mint").is_err());
+        assert!(PayloadTemplate::parse("no tags at all").is_ok());
+    }
+}
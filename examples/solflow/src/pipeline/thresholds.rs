@@ -0,0 +1,406 @@
+//! Runtime-configurable signal thresholds
+//!
+//! The built-in detectors (BREAKOUT, FOCUSED, SURGE, BOT_DROPOFF,
+//! DCA_CONVICTION, REACTIVATION, LIQUIDITY_DRAIN) read their thresholds from a `SignalThresholdsConfig`
+//! instead of compiled-in constants. Defaults are the values the detectors
+//! shipped with; a deployment can override any subset from a TOML or JSON
+//! file and individual values from env, and the runtime re-reads the file
+//! when it changes, without a restart.
+//!
+//! Example (`thresholds.toml`):
+//! ```toml
+//! [breakout]
+//! net_flow_60s_min = 8.0
+//!
+//! [surge]
+//! buy_count_60s_min = 15
+//! ```
+//!
+//! Environment variables:
+//! - `SIGNAL_THRESHOLDS_PATH`: TOML (`.toml`) or JSON file (default: built-in thresholds)
+//! - `SIGNAL_THRESHOLDS_RELOAD_SECS`: How often the file is checked for changes (default: 10, 0 = never)
+//! - `SIGNAL_THRESHOLD_<SIGNAL>_<FIELD>`: Override one value on top of the file,
+//!   e.g. `SIGNAL_THRESHOLD_BREAKOUT_NET_FLOW_60S_MIN=8`
+
+use super::shards::ShardedEngine;
+use super::state::MIN_WAKE_IDLE_SECS;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// BREAKOUT: sharp positive net flow with wallet growth and high buy ratio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BreakoutThresholds {
+    /// Min SOL net inflow in 60s
+    pub net_flow_60s_min: f64,
+    /// Min wallets (any active or new, per BREAKOUT_WALLET_METRIC)
+    pub wallet_growth_min: i32,
+    /// Min share of buys in 60s trades
+    pub buy_ratio_min: f64,
+}
+
+impl Default for BreakoutThresholds {
+    fn default() -> Self {
+        Self {
+            net_flow_60s_min: 5.0,
+            wallet_growth_min: 5,
+            buy_ratio_min: 0.75,
+        }
+    }
+}
+
+/// FOCUSED: concentrated buying from few wallets, low bot activity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FocusedThresholds {
+    /// Min SOL net inflow in 300s
+    pub min_volume: f64,
+    /// Max share of bot trades in 300s
+    pub bot_ratio_max: f64,
+    /// Max wallets in 300s for the buying to count as concentrated
+    pub max_wallets: i32,
+}
+
+impl Default for FocusedThresholds {
+    fn default() -> Self {
+        Self {
+            min_volume: 3.0,
+            bot_ratio_max: 0.2,
+            max_wallets: 10,
+        }
+    }
+}
+
+/// SURGE: explosive buy volume spike (60s volume >> average 300s volume)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SurgeThresholds {
+    /// Min 60s net flow relative to the average 60s slice of 300s
+    pub volume_ratio_min: f64,
+    /// Min buys in 60s
+    pub buy_count_60s_min: i32,
+    /// Min SOL net inflow in 60s
+    pub net_flow_60s_min: f64,
+}
+
+impl Default for SurgeThresholds {
+    fn default() -> Self {
+        Self {
+            volume_ratio_min: 3.0,
+            buy_count_60s_min: 10,
+            net_flow_60s_min: 8.0,
+        }
+    }
+}
+
+/// BOT_DROPOFF: sudden decline in bot activity with new wallet influx
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotDropoffThresholds {
+    /// Min decline in bot trades since the previous flush (0.5 = 50%)
+    pub decline_ratio_min: f64,
+    /// Bot trades needed on the previous flush
+    pub min_previous_bots: i32,
+    /// Min wallets active in 300s
+    pub new_wallet_min: i32,
+}
+
+impl Default for BotDropoffThresholds {
+    fn default() -> Self {
+        Self {
+            decline_ratio_min: 0.5,
+            min_previous_bots: 5,
+            new_wallet_min: 3,
+        }
+    }
+}
+
+/// DCA_CONVICTION: Jupiter DCA buys overlapping spot buys
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DcaConvictionThresholds {
+    /// Min share of DCA buys with a spot buy within 60s
+    pub overlap_min: f64,
+    /// Committed SOL at which the score is no longer scaled down
+    pub full_weight_sol: f64,
+}
+
+impl Default for DcaConvictionThresholds {
+    fn default() -> Self {
+        Self {
+            overlap_min: 0.25,
+            full_weight_sol: 50.0,
+        }
+    }
+}
+
+/// REACTIVATION: buy burst on a token that had gone quiet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReactivationThresholds {
+    /// Min seconds without trades before the burst (at least `MIN_WAKE_IDLE_SECS`)
+    pub dormant_secs_min: i64,
+    /// Min buys in the 300s since trading resumed
+    pub buy_count_300s_min: i32,
+    /// Min SOL net inflow in 300s
+    pub net_flow_300s_min: f64,
+}
+
+impl Default for ReactivationThresholds {
+    fn default() -> Self {
+        Self {
+            dormant_secs_min: 21600,
+            buy_count_300s_min: 5,
+            net_flow_300s_min: 1.0,
+        }
+    }
+}
+
+/// LIQUIDITY_DRAIN: pooled SOL falling from its 300s peak
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LiquidityDrainThresholds {
+    /// Min peak pooled SOL in 300s (ignores dust pools)
+    pub peak_liquidity_sol_min: f64,
+    /// Min drop from the peak, as a share of it
+    pub drop_ratio_min: f64,
+}
+
+impl Default for LiquidityDrainThresholds {
+    fn default() -> Self {
+        Self {
+            peak_liquidity_sol_min: 10.0,
+            drop_ratio_min: 0.3,
+        }
+    }
+}
+
+/// Thresholds for every built-in signal; missing sections/fields keep defaults
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SignalThresholdsConfig {
+    pub breakout: BreakoutThresholds,
+    pub focused: FocusedThresholds,
+    pub surge: SurgeThresholds,
+    pub bot_dropoff: BotDropoffThresholds,
+    pub dca_conviction: DcaConvictionThresholds,
+    pub reactivation: ReactivationThresholds,
+    pub liquidity_drain: LiquidityDrainThresholds,
+}
+
+impl SignalThresholdsConfig {
+    /// Parse a TOML or JSON document
+    pub fn parse(content: &str, toml_format: bool) -> Result<Self, String> {
+        if toml_format {
+            toml::from_str(content).map_err(|e| format!("invalid thresholds TOML: {}", e))
+        } else {
+            serde_json::from_str(content).map_err(|e| format!("invalid thresholds JSON: {}", e))
+        }
+    }
+
+    /// Read a file; `.toml` files are TOML, anything else JSON
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let toml_format = path.extension().and_then(|ext| ext.to_str()) == Some("toml");
+        Self::parse(&content, toml_format)
+    }
+
+    /// Apply `SIGNAL_THRESHOLD_<SIGNAL>_<FIELD>` overrides
+    pub fn with_env_overrides(self) -> Result<Self, String> {
+        self.with_overrides(|key| std::env::var(key).ok())
+    }
+
+    /// Set one value by `<signal>.<field>` name, e.g. `surge.buy_count_60s_min`
+    pub fn with_value(self, key: &str, value: &str) -> Result<Self, String> {
+        let (section, field) = key
+            .split_once('.')
+            .ok_or_else(|| format!("expected <signal>.<field>, got '{}'", key))?;
+        let current = serde_json::to_value(&self).map_err(|e| e.to_string())?;
+        if current.get(section).and_then(|fields| fields.get(field)).is_none() {
+            return Err(format!("unknown threshold '{}'", key));
+        }
+        let override_key = format!(
+            "SIGNAL_THRESHOLD_{}_{}",
+            section.to_uppercase(),
+            field.to_uppercase()
+        );
+        self.with_overrides(|lookup_key| (lookup_key == override_key).then(|| value.to_string()))
+    }
+
+    fn with_overrides(self, lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let mut value = serde_json::to_value(&self).map_err(|e| e.to_string())?;
+        let sections = value.as_object_mut().expect("thresholds serialize to an object");
+
+        for (section, fields) in sections.iter_mut() {
+            let fields = fields.as_object_mut().expect("threshold sections serialize to objects");
+            for (field, current) in fields.iter_mut() {
+                let key = format!(
+                    "SIGNAL_THRESHOLD_{}_{}",
+                    section.to_uppercase(),
+                    field.to_uppercase()
+                );
+                let Some(raw) = lookup(&key) else { continue };
+                let raw = raw.trim();
+                *current = if current.is_i64() {
+                    raw.parse::<i64>().map(Into::into).map_err(|e| format!("{}: {}", key, e))?
+                } else {
+                    raw.parse::<f64>()
+                        .map(Into::into)
+                        .map_err(|e| format!("{}: {}", key, e))?
+                };
+            }
+        }
+
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    /// Reject values that would disable or always trigger a signal by mistake
+    pub fn validate(&self) -> Result<(), String> {
+        let ratios = [
+            ("breakout.buy_ratio_min", self.breakout.buy_ratio_min),
+            ("focused.bot_ratio_max", self.focused.bot_ratio_max),
+            ("bot_dropoff.decline_ratio_min", self.bot_dropoff.decline_ratio_min),
+            ("dca_conviction.overlap_min", self.dca_conviction.overlap_min),
+            ("liquidity_drain.drop_ratio_min", self.liquidity_drain.drop_ratio_min),
+        ];
+        for (name, ratio) in ratios {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(format!("{} must be between 0 and 1 (got {})", name, ratio));
+            }
+        }
+
+        let non_negative = [
+            ("breakout.net_flow_60s_min", self.breakout.net_flow_60s_min),
+            ("focused.min_volume", self.focused.min_volume),
+            ("surge.volume_ratio_min", self.surge.volume_ratio_min),
+            ("surge.net_flow_60s_min", self.surge.net_flow_60s_min),
+            ("reactivation.net_flow_300s_min", self.reactivation.net_flow_300s_min),
+            ("liquidity_drain.peak_liquidity_sol_min", self.liquidity_drain.peak_liquidity_sol_min),
+        ];
+        for (name, value) in non_negative {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!("{} must be a non-negative number (got {})", name, value));
+            }
+        }
+
+        if self.dca_conviction.full_weight_sol <= 0.0 {
+            return Err("dca_conviction.full_weight_sol must be positive".to_string());
+        }
+        if self.focused.max_wallets < 1 {
+            return Err("focused.max_wallets must be at least 1".to_string());
+        }
+        // Shorter gaps aren't recorded as a wake-up, so the signal could never fire
+        if self.reactivation.dormant_secs_min < MIN_WAKE_IDLE_SECS {
+            return Err(format!(
+                "reactivation.dormant_secs_min must be at least {}",
+                MIN_WAKE_IDLE_SECS
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Load from SIGNAL_THRESHOLDS_PATH (if set) plus env overrides
+    pub fn load_from_env() -> Result<Self, String> {
+        let base = match thresholds_path() {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        let config = base.with_env_overrides()?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// SIGNAL_THRESHOLDS_PATH, if set
+pub fn thresholds_path() -> Option<PathBuf> {
+    std::env::var("SIGNAL_THRESHOLDS_PATH")
+        .ok()
+        .filter(|path| !path.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Re-apply the thresholds file to the engine whenever it changes
+///
+/// Invalid edits are logged and the previous thresholds stay active.
+pub fn spawn_thresholds_reload(engine: Arc<ShardedEngine>, path: PathBuf, reload_secs: u64) {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    tokio::spawn(async move {
+        let mut last_modified: Option<SystemTime> = modified(&path);
+        let mut interval = tokio::time::interval(Duration::from_secs(reload_secs.max(1)));
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            let current = modified(&path);
+            if current.is_none() || current == last_modified {
+                continue;
+            }
+            last_modified = current;
+
+            match SignalThresholdsConfig::load_from_env() {
+                Ok(thresholds) => {
+                    if engine.set_signal_thresholds(thresholds) {
+                        log::info!("🎚️  Reloaded signal thresholds from {}", path.display());
+                    }
+                }
+                Err(e) => log::warn!(
+                    "⚠️  Ignoring thresholds change in {}: {} (keeping previous thresholds)",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_partial_toml_and_json_keep_defaults() {
+        let toml = "[breakout]\nnet_flow_60s_min = 8.0\n\n[surge]\nbuy_count_60s_min = 15\n";
+        let config = SignalThresholdsConfig::parse(toml, true).unwrap();
+        assert_eq!(config.breakout.net_flow_60s_min, 8.0);
+        assert_eq!(config.breakout.wallet_growth_min, 5);
+        assert_eq!(config.surge.buy_count_60s_min, 15);
+        assert_eq!(config.focused, FocusedThresholds::default());
+
+        let json = r#"{"bot_dropoff": {"min_previous_bots": 10}}"#;
+        let config = SignalThresholdsConfig::parse(json, false).unwrap();
+        assert_eq!(config.bot_dropoff.min_previous_bots, 10);
+        assert_eq!(config.bot_dropoff.decline_ratio_min, 0.5);
+
+        // Typos are errors rather than silently ignored
+        assert!(SignalThresholdsConfig::parse("[breakout]\nnet_flow_min = 1.0\n", true).is_err());
+    }
+
+    #[test]
+    fn test_env_overrides_and_validation() {
+        let env = HashMap::from([
+            ("SIGNAL_THRESHOLD_BREAKOUT_NET_FLOW_60S_MIN", "12.5"),
+            ("SIGNAL_THRESHOLD_FOCUSED_MAX_WALLETS", "6"),
+        ]);
+        let config = SignalThresholdsConfig::default()
+            .with_overrides(|key| env.get(key).map(|v| v.to_string()))
+            .unwrap();
+        assert_eq!(config.breakout.net_flow_60s_min, 12.5);
+        assert_eq!(config.focused.max_wallets, 6);
+        assert!(config.validate().is_ok());
+
+        let bad = HashMap::from([("SIGNAL_THRESHOLD_SURGE_BUY_COUNT_60S_MIN", "ten")]);
+        assert!(SignalThresholdsConfig::default()
+            .with_overrides(|key| bad.get(key).map(|v| v.to_string()))
+            .is_err());
+
+        let mut config = SignalThresholdsConfig::default();
+        config.breakout.buy_ratio_min = 75.0;
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,299 @@
+//! Interactive signal-threshold tuning over a replay window
+//!
+//! A full backtest replays a capture through the whole runtime for every
+//! threshold change. `pipeline_runtime --tune --replay <capture>` (with the
+//! usual `--from`/`--to`) loads the window into memory once and opens a
+//! prompt instead; each change re-runs only the engine over the in-memory
+//! trades, flushing every `AGGREGATE_FLUSH_INTERVAL_MS` of trade time (full
+//! flush every 60s) as live ingestion does, and prints which signals would
+//! fire and how that differs from the thresholds the session started with.
+//! Nothing is written to the database.
+//!
+//! Commands:
+//! - `show` - current thresholds as TOML (ready for `SIGNAL_THRESHOLDS_PATH`)
+//! - `set <signal>.<field> <value>` - change one threshold and re-run,
+//!   e.g. `set surge.buy_count_60s_min 15`
+//! - `run` - re-run and list the signals that fire
+//! - `reset` - back to the starting thresholds
+//! - `save <path>` - write the current thresholds as TOML
+//! - `quit`
+
+use super::engine::PipelineEngine;
+use super::replay::ReplayClock;
+use super::thresholds::SignalThresholdsConfig;
+use super::types::TradeEvent;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Write};
+
+/// Live ingestion recomputes every active mint this often
+const FULL_FLUSH_SECS: i64 = 60;
+
+/// Signals listed per section of a report
+const MAX_LISTED: usize = 20;
+
+/// One signal the engine emitted during an evaluation
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FiredSignal {
+    pub created_at: i64,
+    pub mint: String,
+    pub signal_type: String,
+}
+
+/// Replay `trades` through a fresh engine with `thresholds`
+///
+/// `configure` applies the runtime's other engine settings (windows,
+/// warm-up, outlier policy, ...) so results match what live would emit.
+pub fn evaluate(
+    trades: &[TradeEvent],
+    thresholds: &SignalThresholdsConfig,
+    flush_interval_secs: i64,
+    configure: &dyn Fn(&mut PipelineEngine),
+) -> Vec<FiredSignal> {
+    let Some(first) = trades.first() else {
+        return Vec::new();
+    };
+    let flush_interval_secs = flush_interval_secs.max(1);
+    let clock = ReplayClock::new();
+    clock.advance_to(first.timestamp);
+
+    let mut engine = PipelineEngine::new_with_timestamp_fn(clock.now_fn());
+    configure(&mut engine);
+    engine.set_signal_thresholds(thresholds.clone());
+
+    let mut fired = Vec::new();
+    let mut next_flush = first.timestamp + flush_interval_secs;
+    let mut next_full_flush = first.timestamp + FULL_FLUSH_SECS;
+    let mut flush_until = |engine: &mut PipelineEngine, until: i64, fired: &mut Vec<FiredSignal>| {
+        while next_flush <= until {
+            clock.advance_to(next_flush);
+            let full = next_flush >= next_full_flush;
+            if full {
+                next_full_flush = next_flush + FULL_FLUSH_SECS;
+            }
+            flush(engine, next_flush, full, fired);
+            next_flush += flush_interval_secs;
+        }
+    };
+
+    for trade in trades {
+        flush_until(&mut engine, trade.timestamp, &mut fired);
+        clock.advance_to(trade.timestamp);
+        engine.process_trade(trade.clone());
+    }
+    let last = trades[trades.len() - 1].timestamp;
+    flush_until(&mut engine, last + flush_interval_secs, &mut fired);
+
+    fired
+}
+
+/// One flush cycle: touched mints (or every mint on a full flush)
+fn flush(engine: &mut PipelineEngine, now: i64, full: bool, fired: &mut Vec<FiredSignal>) {
+    let mints = if full {
+        engine.get_active_mints()
+    } else {
+        engine.get_touched_mints()
+    };
+    for mint in mints {
+        if let Ok((metrics, signals, _)) = engine.compute_metrics(&mint, now) {
+            engine.update_bot_history(&mint, metrics.bot_trades_count_300s);
+            fired.extend(signals.into_iter().map(|signal| FiredSignal {
+                created_at: signal.created_at,
+                mint: signal.mint,
+                signal_type: signal.signal_type.as_str().to_string(),
+            }));
+        }
+    }
+    engine.clear_touched_mints();
+}
+
+/// Signals fired with the current thresholds, compared to the baseline
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Evaluation {
+    pub signals: Vec<FiredSignal>,
+    /// Fired now but not with the baseline thresholds
+    pub gained: Vec<FiredSignal>,
+    /// Fired with the baseline thresholds but not now
+    pub lost: Vec<FiredSignal>,
+}
+
+impl Evaluation {
+    pub fn compare(signals: Vec<FiredSignal>, baseline: &[FiredSignal]) -> Self {
+        let current: BTreeSet<&FiredSignal> = signals.iter().collect();
+        let before: BTreeSet<&FiredSignal> = baseline.iter().collect();
+        let gained = current.difference(&before).map(|s| (*s).clone()).collect();
+        let lost = before.difference(&current).map(|s| (*s).clone()).collect();
+        Self { signals, gained, lost }
+    }
+
+    /// Per-type counts with the change from baseline, then gained/lost signals
+    pub fn report(&self) -> String {
+        let mut counts: BTreeMap<&str, (i64, i64)> = BTreeMap::new();
+        for signal in &self.signals {
+            counts.entry(&signal.signal_type).or_default().0 += 1;
+        }
+        for signal in &self.gained {
+            counts.entry(&signal.signal_type).or_default().1 += 1;
+        }
+        for signal in &self.lost {
+            counts.entry(&signal.signal_type).or_default().1 -= 1;
+        }
+
+        let mut out = format!(
+            "{} signals ({} gained, {} lost vs. starting thresholds)\n",
+            self.signals.len(),
+            self.gained.len(),
+            self.lost.len()
+        );
+        for (signal_type, (count, delta)) in &counts {
+            out.push_str(&format!("  {:<16} {:>6} ({:+})\n", signal_type, count, delta));
+        }
+        for (label, signals) in [("+", &self.gained), ("-", &self.lost)] {
+            for signal in signals.iter().take(MAX_LISTED) {
+                out.push_str(&format!(
+                    "  {} {} {} {}\n",
+                    label, signal.created_at, signal.signal_type, signal.mint
+                ));
+            }
+            if signals.len() > MAX_LISTED {
+                out.push_str(&format!("  {} ... {} more\n", label, signals.len() - MAX_LISTED));
+            }
+        }
+        out
+    }
+}
+
+/// Trades, thresholds and the baseline they are compared against
+pub struct TuningSession {
+    trades: Vec<TradeEvent>,
+    flush_interval_secs: i64,
+    configure: Box<dyn Fn(&mut PipelineEngine)>,
+    initial: SignalThresholdsConfig,
+    baseline: Vec<FiredSignal>,
+    thresholds: SignalThresholdsConfig,
+}
+
+impl TuningSession {
+    /// Evaluates `thresholds` once as the baseline
+    pub fn new(
+        trades: Vec<TradeEvent>,
+        thresholds: SignalThresholdsConfig,
+        flush_interval_secs: i64,
+        configure: Box<dyn Fn(&mut PipelineEngine)>,
+    ) -> Self {
+        let baseline = evaluate(&trades, &thresholds, flush_interval_secs, &*configure);
+        Self {
+            trades,
+            flush_interval_secs,
+            configure,
+            initial: thresholds.clone(),
+            baseline,
+            thresholds,
+        }
+    }
+
+    pub fn thresholds(&self) -> &SignalThresholdsConfig {
+        &self.thresholds
+    }
+
+    /// Change one threshold (rejected if it fails validation)
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let updated = self.thresholds.clone().with_value(key, value)?;
+        updated.validate()?;
+        self.thresholds = updated;
+        Ok(())
+    }
+
+    pub fn evaluate(&self) -> Evaluation {
+        let signals = evaluate(&self.trades, &self.thresholds, self.flush_interval_secs, &*self.configure);
+        Evaluation::compare(signals, &self.baseline)
+    }
+
+    /// Run one command; None means quit
+    pub fn handle(&mut self, line: &str) -> Option<String> {
+        let mut words = line.split_whitespace();
+        let output = match (words.next(), words.next(), words.next()) {
+            (None, _, _) => String::new(),
+            (Some("quit" | "exit" | "q"), _, _) => return None,
+            (Some("show"), None, _) => toml::to_string(&self.thresholds)
+                .unwrap_or_else(|e| format!("error: {}\n", e)),
+            (Some("run"), None, _) => self.evaluate().report(),
+            (Some("set"), Some(key), Some(value)) => match self.set(key, value) {
+                Ok(()) => self.evaluate().report(),
+                Err(e) => format!("error: {}\n", e),
+            },
+            (Some("reset"), None, _) => {
+                self.thresholds = self.initial.clone();
+                self.evaluate().report()
+            }
+            (Some("save"), Some(path), None) => match toml::to_string(&self.thresholds) {
+                Ok(content) => match std::fs::write(path, content) {
+                    Ok(()) => format!("saved to {}\n", path),
+                    Err(e) => format!("error: {}: {}\n", path, e),
+                },
+                Err(e) => format!("error: {}\n", e),
+            },
+            _ => "commands: show | set <signal>.<field> <value> | run | reset | save <path> | quit\n"
+                .to_string(),
+        };
+        Some(output)
+    }
+}
+
+/// Read commands from `input` until `quit` or end of input
+pub fn run_repl(
+    session: &mut TuningSession,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> std::io::Result<()> {
+    write!(output, "{}", session.evaluate().report())?;
+    write!(output, "tune> ")?;
+    output.flush()?;
+    for line in input.lines() {
+        let Some(response) = session.handle(&line?) else {
+            break;
+        };
+        write!(output, "{}tune> ", response)?;
+        output.flush()?;
+    }
+    writeln!(output)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fired(created_at: i64, mint: &str) -> FiredSignal {
+        FiredSignal {
+            created_at,
+            mint: mint.to_string(),
+            signal_type: "SURGE".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_session_commands_and_diff() {
+        let mut session = TuningSession::new(
+            Vec::new(),
+            SignalThresholdsConfig::default(),
+            1,
+            Box::new(|_: &mut PipelineEngine| {}),
+        );
+        assert!(session.handle("set surge.buy_count_60s_min 15").unwrap().starts_with("0 signals"));
+        assert_eq!(session.thresholds().surge.buy_count_60s_min, 15);
+        assert!(session.handle("set surge.nope 1").unwrap().contains("unknown threshold"));
+        assert!(session.handle("set breakout.buy_ratio_min 2").unwrap().starts_with("error"));
+        assert!(session.handle("show").unwrap().contains("buy_count_60s_min = 15"));
+        session.handle("reset");
+        assert_eq!(session.thresholds(), &SignalThresholdsConfig::default());
+        assert!(session.handle("quit").is_none());
+
+        let evaluation = Evaluation::compare(
+            vec![fired(100, "a"), fired(200, "b")],
+            &[fired(100, "a"), fired(150, "c")],
+        );
+        assert_eq!(evaluation.gained, vec![fired(200, "b")]);
+        assert_eq!(evaluation.lost, vec![fired(150, "c")]);
+        assert!(evaluation.report().contains("SURGE                 2 (+0)"));
+    }
+}
@@ -6,13 +6,24 @@
 //! - Field names use exact SQL column names (snake_case)
 
 /// Trade direction enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum TradeDirection {
     Buy,
     Sell,
     Unknown,
 }
 
+/// Commitment a trade's slot has reached, lowest first
+///
+/// Trades are tagged with the streamer's ingest commitment and upgraded in
+/// place as slot-status updates arrive (see `streamer_core::slot_status`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum Confirmation {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
 /// Token metadata matching the token_metadata table schema
 ///
 /// Schema reference: `/sql/00_token_metadata.sql`
@@ -32,7 +43,7 @@ pub struct TokenMetadata {
 ///
 /// This represents a single trade extracted from on-chain data.
 /// These events are held in-memory in rolling windows and never persisted as raw trades.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TradeEvent {
     pub timestamp: i64,
     pub mint: String,
@@ -42,13 +53,126 @@ pub struct TradeEvent {
     pub token_decimals: u8,
     pub user_account: String,
     pub source_program: String,
+
+    /// Transaction signature (for tracing aggregates back to transactions)
+    pub signature: String,
+
+    /// Position of this trade among the trades extracted from its transaction
+    ///
+    /// With `signature`, identifies the trade across re-deliveries (see `dedup`).
+    /// Journals written before the rename store it as `instruction_index`.
+    #[serde(default, alias = "instruction_index")]
+    pub trade_index: u16,
+
+    /// Slot the transaction landed in
+    pub slot: u64,
+
+    /// Transaction fee paid, in lamports
+    pub fee_lamports: u64,
+
+    /// Priority fee paid (compute unit price × limit), in lamports
+    #[serde(default)]
+    pub priority_fee_lamports: u64,
+
+    /// Lamports tipped to Jito's tip accounts
+    #[serde(default)]
+    pub jito_tip_lamports: u64,
+
+    /// Commitment reached so far (upgraded as slot updates arrive)
+    pub confirmation: Confirmation,
+
+    /// Jupiter DCA order backing this fill (JupiterDCA trades only, when resolvable)
+    pub dca_order: Option<DcaOrderInfo>,
+}
+
+/// Blank trade for fixtures: set the fields that matter and fill the rest
+/// with `..Default::default()`
+impl Default for TradeEvent {
+    fn default() -> Self {
+        Self {
+            timestamp: 0,
+            mint: String::new(),
+            direction: TradeDirection::Unknown,
+            sol_amount: 0.0,
+            token_amount: 0.0,
+            token_decimals: 0,
+            user_account: String::new(),
+            source_program: String::new(),
+            signature: String::new(),
+            trade_index: 0,
+            slot: 0,
+            fee_lamports: 0,
+            priority_fee_lamports: 0,
+            jito_tip_lamports: 0,
+            confirmation: Confirmation::Confirmed,
+            dca_order: None,
+        }
+    }
+}
+
+#[cfg(test)]
+impl TradeEvent {
+    /// Test fixture: a 1 SOL buy of 1000 tokens of `mint` by "wallet" on
+    /// PumpSwap; override the fields under test with struct update syntax
+    pub(crate) fn test_buy(mint: &str, timestamp: i64) -> Self {
+        Self {
+            timestamp,
+            mint: mint.to_string(),
+            direction: TradeDirection::Buy,
+            sol_amount: 1.0,
+            token_amount: 1000.0,
+            token_decimals: 6,
+            user_account: "wallet".to_string(),
+            source_program: "PumpSwap".to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Decoded Jupiter DCA order context attached to a DCA fill
+///
+/// Fills only show one cycle's amount; the order account shows how much SOL
+/// is still committed to future cycles.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DcaOrderInfo {
+    /// DCA order account address
+    pub order_account: String,
+
+    /// SOL deposited but not yet used or withdrawn
+    pub committed_sol_remaining: f64,
+
+    /// SOL spent per cycle
+    pub sol_per_cycle: f64,
+
+    /// Cycles left before the order is exhausted
+    pub remaining_cycles: u64,
+}
+
+/// A launchpad token graduating from its bonding curve to an AMM pool
+///
+/// Decoded by the streamer (see `streamer_core::pumpfun`) and turned into a
+/// TOKEN_MIGRATED signal by the engine.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TokenMigration {
+    pub mint: String,
+    /// Launch platform the token graduated from, e.g. "pumpfun"
+    pub launch_platform: String,
+    /// Venue the liquidity moved to, e.g. "pumpswap"
+    pub destination: String,
+    /// New pool account
+    pub pool: String,
+    /// SOL seeded into the new pool
+    pub sol_amount: f64,
+    pub token_decimals: u8,
+    pub signature: String,
+    pub timestamp: i64,
 }
 
 /// Aggregated token state matching the token_aggregates table schema
 ///
 /// Schema reference: `/sql/02_token_aggregates.sql`
 /// All field names are EXACT matches to SQL column names.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct AggregatedTokenState {
     // Primary key
     pub mint: String,
@@ -84,6 +208,39 @@ pub struct AggregatedTokenState {
 
     // Advanced metrics (300s window)
     pub unique_wallets_300s: Option<i32>,
+    /// Funding clusters among those wallets (see `wallet_graph`)
+    pub unique_clusters_300s: Option<i32>,
+    pub new_wallets_300s: Option<i32>,
+    pub fees_paid_300s_sol: Option<f64>,
+    /// Venue swap fees from the `fees` models (set via with_window_fees)
+    pub venue_fees_300s_sol: Option<f64>,
+    /// 300s net flow minus venue and transaction fees
+    pub fee_adjusted_net_flow_300s_sol: Option<f64>,
+    /// SOL in the mint's known pool vaults (liquidity tracker; None until seen)
+    pub liquidity_sol: Option<f64>,
+    /// SOL per token from the mint's pool state account (pool state tracker; None until seen)
+    pub pool_price_sol: Option<f64>,
+    /// Priority fees paid by trades in the 300s window
+    pub priority_fees_300s_sol: Option<f64>,
+    /// Jito tips paid by trades in the 300s window
+    pub jito_tips_300s_sol: Option<f64>,
+    /// Per-venue net flow and counts as JSON: `{"PumpSwap": {"net_flow_sol", "buy_count", "sell_count"}}`
+    pub program_breakdown_300s_json: Option<String>,
+
+    // Early holders (first buyers seen for the mint)
+    pub early_buyers_count: Option<i32>,
+    pub early_holder_retention: Option<f64>,
+
+    // Buyer wallet age cohorts (share of buyers with fresh wallets)
+    pub fresh_wallet_ratio_60s: Option<f64>,
+    pub fresh_wallet_ratio_300s: Option<f64>,
+    pub fresh_wallet_ratio_900s: Option<f64>,
+    /// Approximate (HyperLogLog): exact up to 64 distinct wallets, otherwise ~3.3% standard error
+    pub unique_wallets_3600s: Option<i32>,
+    pub unique_wallets_7200s: Option<i32>,
+    pub unique_wallets_14400s: Option<i32>,
+    /// Net flow per minute for the last hour as JSON: `[[minute_start, net_flow_sol], ...]`
+    pub net_flow_sparkline_json: Option<String>,
     pub bot_trades_300s: Option<i32>,
     pub bot_wallets_300s: Option<i32>,
 
@@ -99,6 +256,30 @@ pub struct AggregatedTokenState {
     pub dca_buys_3600s: Option<i32>,
     pub dca_buys_14400s: Option<i32>,
 
+    // Final view: only trades whose slot is finalized. The columns above
+    // are the fast view and also count Processed/Confirmed trades.
+    pub net_flow_60s_final_sol: Option<f64>,
+    pub net_flow_300s_final_sol: Option<f64>,
+    pub net_flow_900s_final_sol: Option<f64>,
+    pub buy_count_300s_final: Option<i32>,
+    pub sell_count_300s_final: Option<i32>,
+    pub unfinalized_trades_300s: Option<i32>,
+
+    /// Configured windows without a fixed column, as JSON keyed by generated
+    /// column name (see `window_set`); None with the default windows
+    pub window_metrics_json: Option<String>,
+
+    // Trace references (most recent trade / DCA buy)
+    pub last_trade_slot: Option<i64>,
+    pub last_trade_signature: Option<String>,
+    pub last_dca_slot: Option<i64>,
+    pub last_dca_signature: Option<String>,
+
+    /// End time of the latest unbackfilled gap in the live stream that the
+    /// windows reach back over (see `streamer_core::slot_gaps`); windows
+    /// longer than `updated_at - last_data_gap_at` are degraded
+    pub last_data_gap_at: Option<i64>,
+
     // Timestamps
     pub updated_at: i64,
     pub created_at: i64,
@@ -174,6 +355,29 @@ impl AggregatedTokenState {
 
             // Advanced metrics (300s window)
             unique_wallets_300s: Some(metrics.unique_wallets_300s),
+            unique_clusters_300s: Some(metrics.unique_clusters_300s),
+            new_wallets_300s: Some(metrics.new_wallets_300s),
+            fees_paid_300s_sol: Some(metrics.fees_paid_300s_sol),
+            venue_fees_300s_sol: None,
+            fee_adjusted_net_flow_300s_sol: None,
+            liquidity_sol: None,
+            pool_price_sol: None,
+            priority_fees_300s_sol: Some(metrics.priority_fees_300s_sol),
+            jito_tips_300s_sol: Some(metrics.jito_tips_300s_sol),
+            program_breakdown_300s_json: Self::compute_program_breakdown_json(metrics),
+
+            // Early holders
+            early_buyers_count: Some(metrics.early_buyers_count),
+            early_holder_retention: metrics.early_holder_retention,
+
+            // Buyer wallet age cohorts
+            fresh_wallet_ratio_60s: metrics.fresh_wallet_ratio_60s,
+            fresh_wallet_ratio_300s: metrics.fresh_wallet_ratio_300s,
+            fresh_wallet_ratio_900s: metrics.fresh_wallet_ratio_900s,
+            unique_wallets_3600s: Some(metrics.unique_wallets_3600s),
+            unique_wallets_7200s: Some(metrics.unique_wallets_7200s),
+            unique_wallets_14400s: Some(metrics.unique_wallets_14400s),
+            net_flow_sparkline_json: Self::compute_net_flow_sparkline_json(metrics),
             bot_trades_300s: Some(metrics.bot_trades_count_300s),
             bot_wallets_300s: Some(metrics.bot_wallets_count_300s),
 
@@ -189,12 +393,73 @@ impl AggregatedTokenState {
             dca_buys_3600s: Some(metrics.dca_buys_3600s),
             dca_buys_14400s: Some(metrics.dca_buys_14400s),
 
+            // Final (finalized-only) view
+            net_flow_60s_final_sol: Some(metrics.net_flow_60s_final_sol),
+            net_flow_300s_final_sol: Some(metrics.net_flow_300s_final_sol),
+            net_flow_900s_final_sol: Some(metrics.net_flow_900s_final_sol),
+            buy_count_300s_final: Some(metrics.buy_count_300s_final),
+            sell_count_300s_final: Some(metrics.sell_count_300s_final),
+            unfinalized_trades_300s: Some(metrics.unfinalized_trades_300s),
+            window_metrics_json: super::window_set::extra_columns_json(&metrics.windows),
+
+            // Trace references (set via with_trade_refs)
+            last_trade_slot: None,
+            last_trade_signature: None,
+            last_dca_slot: None,
+            last_dca_signature: None,
+            last_data_gap_at: None,
+
             // Timestamps
             updated_at: now,
             created_at,
         }
     }
 
+    /// Attach trace references to the most recent trade and DCA buy
+    ///
+    /// Lets a suspicious aggregate be traced back to specific transactions.
+    /// Empty signatures (unknown source) are left as None.
+    pub fn with_trade_refs(
+        mut self,
+        last_trade: Option<&TradeEvent>,
+        last_dca: Option<&(String, u64)>,
+    ) -> Self {
+        if let Some(trade) = last_trade.filter(|t| !t.signature.is_empty()) {
+            self.last_trade_slot = Some(trade.slot as i64);
+            self.last_trade_signature = Some(trade.signature.clone());
+        }
+        if let Some((signature, slot)) = last_dca.filter(|(sig, _)| !sig.is_empty()) {
+            self.last_dca_slot = Some(*slot as i64);
+            self.last_dca_signature = Some(signature.clone());
+        }
+        self
+    }
+
+    /// Attach venue fees and fee-adjusted net flow for the 300s window
+    pub fn with_window_fees(mut self, fees: super::fees::WindowFees) -> Self {
+        self.venue_fees_300s_sol = Some(fees.venue_fees_sol);
+        self.fee_adjusted_net_flow_300s_sol = Some(fees.fee_adjusted_net_flow_sol);
+        self
+    }
+
+    /// Mark windows computed over a gap in the live stream
+    pub fn with_data_gap(mut self, last_data_gap_at: Option<i64>) -> Self {
+        self.last_data_gap_at = last_data_gap_at;
+        self
+    }
+
+    /// Attach the mint's pooled SOL (None until the liquidity tracker saw it)
+    pub fn with_liquidity(mut self, liquidity_sol: Option<f64>) -> Self {
+        self.liquidity_sol = liquidity_sol;
+        self
+    }
+
+    /// Attach the mint's on-chain pool price (None until the pool state tracker saw it)
+    pub fn with_pool_price(mut self, pool_price_sol: Option<f64>) -> Self {
+        self.pool_price_sol = pool_price_sol;
+        self
+    }
+
     /// Compute average trade size from 300s window metrics
     ///
     /// Returns None if no trades in window (division by zero protection)
@@ -209,6 +474,22 @@ impl AggregatedTokenState {
         }
     }
 
+    /// Serialize the per-venue 300s breakdown (None when no trades)
+    fn compute_program_breakdown_json(metrics: &super::state::RollingMetrics) -> Option<String> {
+        if metrics.program_breakdown_300s.is_empty() {
+            return None;
+        }
+        serde_json::to_string(&metrics.program_breakdown_300s).ok()
+    }
+
+    /// Serialize the per-minute net flow sparkline (None when no buckets)
+    fn compute_net_flow_sparkline_json(metrics: &super::state::RollingMetrics) -> Option<String> {
+        if metrics.net_flow_by_minute_60m.is_empty() {
+            return None;
+        }
+        serde_json::to_string(&metrics.net_flow_by_minute_60m).ok()
+    }
+
     /// Compute total volume in 300s window
     ///
     /// Volume is the absolute value of net flow (ignores direction)
@@ -250,6 +531,21 @@ mod tests {
             buy_count_900s: 50,
             sell_count_900s: 25,
             unique_wallets_300s: 12,
+            unique_clusters_300s: 12,
+            unique_wallets_3600s: 12,
+            unique_wallets_7200s: 12,
+            unique_wallets_14400s: 12,
+            new_wallets_300s: 6,
+            fees_paid_300s_sol: 0.0,
+            priority_fees_300s_sol: 0.0,
+            jito_tips_300s_sol: 0.0,
+            program_breakdown_300s: Default::default(),
+            early_buyers_count: 0,
+            early_holder_retention: None,
+            fresh_wallet_ratio_60s: None,
+            fresh_wallet_ratio_300s: None,
+            fresh_wallet_ratio_900s: None,
+            net_flow_by_minute_60m: Vec::new(),
             bot_wallets_count_300s: 2,
             bot_trades_count_300s: 6,
             // Phase 6: DCA Rolling Windows
@@ -258,6 +554,13 @@ mod tests {
             dca_buys_900s: 8,
             dca_buys_3600s: 15,
             dca_buys_14400s: 30,
+            net_flow_60s_final_sol: 0.0,
+            net_flow_300s_final_sol: 0.0,
+            net_flow_900s_final_sol: 0.0,
+            buy_count_300s_final: 0,
+            sell_count_300s_final: 0,
+            unfinalized_trades_300s: 0,
+            windows: Vec::new(),
         }
     }
 
@@ -426,6 +729,21 @@ mod tests {
             buy_count_900s: 0,
             sell_count_900s: 0,
             unique_wallets_300s: 0,
+            unique_clusters_300s: 0,
+            unique_wallets_3600s: 0,
+            unique_wallets_7200s: 0,
+            unique_wallets_14400s: 0,
+            new_wallets_300s: 0,
+            fees_paid_300s_sol: 0.0,
+            priority_fees_300s_sol: 0.0,
+            jito_tips_300s_sol: 0.0,
+            program_breakdown_300s: Default::default(),
+            early_buyers_count: 0,
+            early_holder_retention: None,
+            fresh_wallet_ratio_60s: None,
+            fresh_wallet_ratio_300s: None,
+            fresh_wallet_ratio_900s: None,
+            net_flow_by_minute_60m: Vec::new(),
             bot_wallets_count_300s: 0,
             bot_trades_count_300s: 0,
             dca_buys_60s: 0,
@@ -433,6 +751,13 @@ mod tests {
             dca_buys_900s: 0,
             dca_buys_3600s: 0,
             dca_buys_14400s: 0,
+            net_flow_60s_final_sol: 0.0,
+            net_flow_300s_final_sol: 0.0,
+            net_flow_900s_final_sol: 0.0,
+            buy_count_300s_final: 0,
+            sell_count_300s_final: 0,
+            unfinalized_trades_300s: 0,
+            windows: Vec::new(),
         };
 
         let mint = "zero_trades_mint";
@@ -462,6 +787,21 @@ mod tests {
             buy_count_900s: 25,
             sell_count_900s: 50,
             unique_wallets_300s: 8,
+            unique_clusters_300s: 8,
+            unique_wallets_3600s: 8,
+            unique_wallets_7200s: 8,
+            unique_wallets_14400s: 8,
+            new_wallets_300s: 4,
+            fees_paid_300s_sol: 0.0,
+            priority_fees_300s_sol: 0.0,
+            jito_tips_300s_sol: 0.0,
+            program_breakdown_300s: Default::default(),
+            early_buyers_count: 0,
+            early_holder_retention: None,
+            fresh_wallet_ratio_60s: None,
+            fresh_wallet_ratio_300s: None,
+            fresh_wallet_ratio_900s: None,
+            net_flow_by_minute_60m: Vec::new(),
             bot_wallets_count_300s: 1,
             bot_trades_count_300s: 3,
             dca_buys_60s: 0,
@@ -469,6 +809,13 @@ mod tests {
             dca_buys_900s: 2,
             dca_buys_3600s: 5,
             dca_buys_14400s: 10,
+            net_flow_60s_final_sol: 0.0,
+            net_flow_300s_final_sol: 0.0,
+            net_flow_900s_final_sol: 0.0,
+            buy_count_300s_final: 0,
+            sell_count_300s_final: 0,
+            unfinalized_trades_300s: 0,
+            windows: Vec::new(),
         };
 
         let mint = "negative_flow_mint";
@@ -511,4 +858,30 @@ mod tests {
         let state4 = AggregatedTokenState::from_metrics(mint, &metrics, None, 2000, 3000);
         assert_eq!(state4.source_program, "unknown");
     }
+
+    #[test]
+    fn test_with_trade_refs() {
+        let metrics = make_test_metrics();
+        let trade = TradeEvent {
+            signature: "sig_last".to_string(),
+            slot: 250_000_000,
+            ..TradeEvent::test_buy("trace_mint", 2000)
+        };
+        let dca = ("sig_dca".to_string(), 249_999_990);
+
+        let state = AggregatedTokenState::from_metrics("trace_mint", &metrics, None, 2000, 3000)
+            .with_trade_refs(Some(&trade), Some(&dca));
+        assert_eq!(state.last_trade_slot, Some(250_000_000));
+        assert_eq!(state.last_trade_signature.as_deref(), Some("sig_last"));
+        assert_eq!(state.last_dca_slot, Some(249_999_990));
+        assert_eq!(state.last_dca_signature.as_deref(), Some("sig_dca"));
+
+        // Unknown signatures are not recorded
+        let mut unsigned = trade.clone();
+        unsigned.signature.clear();
+        let state = AggregatedTokenState::from_metrics("trace_mint", &metrics, None, 2000, 3000)
+            .with_trade_refs(Some(&unsigned), None);
+        assert_eq!(state.last_trade_signature, None);
+        assert_eq!(state.last_dca_slot, None);
+    }
 }
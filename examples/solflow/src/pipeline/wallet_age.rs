@@ -0,0 +1,285 @@
+//! Buyer wallet age enrichment
+//!
+//! Launches bought mostly by day-old wallets behave very differently from
+//! ones bought by established wallets. A wallet's age is when it was first
+//! seen on chain: the oldest `blockTime` returned by `getSignaturesForAddress`
+//! (one page of 1000). Wallets with more history than that are "at least"
+//! that old, which is all the fresh/established split needs.
+//!
+//! Lookups are asynchronous so ingestion never waits on RPC: the engine
+//! queues unknown buyers in a shared `WalletAgeCache`, a background
+//! `WalletAgeResolver` drains the queue at a fixed rate, and metrics use
+//! whatever ages are known at flush time.
+//!
+//! Environment variables:
+//! - `WALLET_AGE_ENABLED`: Resolve buyer wallet ages (default: false)
+//! - `SOLANA_RPC_URL`: RPC endpoint used for lookups (required when enabled)
+//! - `WALLET_AGE_LOOKUPS_PER_SEC`: RPC lookup rate (default: 5)
+//! - `FRESH_WALLET_MAX_AGE_SECS`: Wallets younger than this are fresh (default: 86400)
+
+use super::types::{TradeDirection, TradeEvent};
+use crate::error::{codes, SolflowError};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Resolved wallets kept in memory
+const WALLET_AGE_CACHE_CAPACITY: usize = 100_000;
+
+/// Wallets waiting for lookup (further requests are dropped)
+const MAX_PENDING_LOOKUPS: usize = 10_000;
+
+/// Signatures fetched per lookup (the `getSignaturesForAddress` maximum)
+const SIGNATURES_PAGE_LIMIT: usize = 1000;
+
+#[derive(Debug, Default)]
+struct WalletAgeInner {
+    /// Wallet → first seen on chain (unix seconds)
+    first_seen: HashMap<String, i64>,
+    pending: VecDeque<String>,
+    queued: HashSet<String>,
+}
+
+/// Shared wallet first-seen cache with a lookup queue
+#[derive(Debug, Clone, Default)]
+pub struct WalletAgeCache {
+    inner: Arc<Mutex<WalletAgeInner>>,
+}
+
+impl WalletAgeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// First-seen timestamp if already resolved
+    pub fn first_seen(&self, wallet: &str) -> Option<i64> {
+        self.inner.lock().unwrap().first_seen.get(wallet).copied()
+    }
+
+    /// Queue a wallet for lookup unless resolved or already queued
+    pub fn request(&self, wallet: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.first_seen.contains_key(wallet)
+            || inner.queued.contains(wallet)
+            || inner.pending.len() >= MAX_PENDING_LOOKUPS
+        {
+            return;
+        }
+        inner.queued.insert(wallet.to_string());
+        inner.pending.push_back(wallet.to_string());
+    }
+
+    /// Record a resolved wallet
+    pub fn insert(&self, wallet: &str, first_seen: i64) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.first_seen.len() >= WALLET_AGE_CACHE_CAPACITY {
+            // Cheap bound: ages are re-resolvable, so dropping any entry is safe
+            if let Some(evicted) = inner.first_seen.keys().next().cloned() {
+                inner.first_seen.remove(&evicted);
+            }
+        }
+        inner.first_seen.insert(wallet.to_string(), first_seen);
+    }
+
+    /// Take the next wallet awaiting lookup
+    fn next_pending(&self) -> Option<String> {
+        let mut inner = self.inner.lock().unwrap();
+        let wallet = inner.pending.pop_front()?;
+        inner.queued.remove(&wallet);
+        Some(wallet)
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.inner.lock().unwrap().pending.len()
+    }
+
+    /// Share of a window's buyers (with known age) whose wallets are fresh
+    ///
+    /// Returns None when no buyer in the window has a resolved age yet.
+    pub fn fresh_buyer_ratio(
+        &self,
+        trades: &[TradeEvent],
+        now: i64,
+        max_age_secs: i64,
+    ) -> Option<f64> {
+        let inner = self.inner.lock().unwrap();
+        let buyers: HashSet<&str> = trades
+            .iter()
+            .filter(|t| t.direction == TradeDirection::Buy)
+            .map(|t| t.user_account.as_str())
+            .collect();
+
+        let mut known = 0;
+        let mut fresh = 0;
+        for first_seen in buyers.iter().filter_map(|w| inner.first_seen.get(*w)) {
+            known += 1;
+            if now - first_seen < max_age_secs {
+                fresh += 1;
+            }
+        }
+
+        (known > 0).then(|| fresh as f64 / known as f64)
+    }
+}
+
+/// Background resolver draining a `WalletAgeCache` queue via RPC
+pub struct WalletAgeResolver {
+    rpc_url: String,
+    client: reqwest::Client,
+    lookups_per_sec: u64,
+    pub fresh_max_age_secs: i64,
+}
+
+impl WalletAgeResolver {
+    /// Create a resolver from the environment (see module docs)
+    ///
+    /// Returns None unless enabled and an RPC endpoint is configured.
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("WALLET_AGE_ENABLED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let Ok(rpc_url) = std::env::var("SOLANA_RPC_URL") else {
+            log::warn!("⚠️  WALLET_AGE_ENABLED is set but SOLANA_RPC_URL is not; wallet ages disabled");
+            return None;
+        };
+
+        let lookups_per_sec = std::env::var("WALLET_AGE_LOOKUPS_PER_SEC")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|n: &u64| *n > 0)
+            .unwrap_or(5);
+
+        let fresh_max_age_secs = std::env::var("FRESH_WALLET_MAX_AGE_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(86_400);
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .ok()?;
+
+        Some(Self {
+            rpc_url,
+            client,
+            lookups_per_sec,
+            fresh_max_age_secs,
+        })
+    }
+
+    /// Resolve queued wallets forever, at most `lookups_per_sec` per second
+    pub fn spawn(self, cache: WalletAgeCache) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_millis(1000 / self.lookups_per_sec.max(1)));
+            loop {
+                interval.tick().await;
+                let Some(wallet) = cache.next_pending() else {
+                    continue;
+                };
+
+                match self.fetch_first_seen(&wallet).await {
+                    Ok(Some(first_seen)) => cache.insert(&wallet, first_seen),
+                    Ok(None) => {} // No history: leave unknown
+                    Err(e) => log::debug!("⚠️  Wallet age lookup failed for {}: {}", wallet, e),
+                }
+            }
+        })
+    }
+
+    /// Oldest block time in the wallet's most recent signatures page
+    async fn fetch_first_seen(
+        &self,
+        wallet: &str,
+    ) -> Result<Option<i64>, SolflowError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getSignaturesForAddress",
+            "params": [wallet, {"limit": SIGNATURES_PAGE_LIMIT}],
+        });
+
+        let response: serde_json::Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(SolflowError::enrichment(
+                codes::ENRICHMENT_HTTP,
+                format!("RPC error: {}", error),
+            ));
+        }
+
+        Ok(oldest_block_time(&response))
+    }
+}
+
+/// Oldest `blockTime` in a `getSignaturesForAddress` response
+fn oldest_block_time(response: &serde_json::Value) -> Option<i64> {
+    response
+        .get("result")?
+        .as_array()?
+        .iter()
+        .filter_map(|sig| sig.get("blockTime")?.as_i64())
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buy(wallet: &str) -> TradeEvent {
+        TradeEvent {
+            user_account: wallet.to_string(),
+            ..TradeEvent::test_buy("mint", 1000)
+        }
+    }
+
+    #[test]
+    fn test_request_queue_dedupes_and_skips_resolved() {
+        let cache = WalletAgeCache::new();
+        cache.request("a");
+        cache.request("a");
+        cache.insert("b", 0);
+        cache.request("b");
+        assert_eq!(cache.pending_len(), 1);
+
+        assert_eq!(cache.next_pending().as_deref(), Some("a"));
+        assert_eq!(cache.next_pending(), None);
+    }
+
+    #[test]
+    fn test_fresh_buyer_ratio_ignores_unknown_wallets() {
+        let cache = WalletAgeCache::new();
+        let now = 1_000_000;
+        cache.insert("fresh", now - 3600);
+        cache.insert("old", now - 90 * 86_400);
+
+        let trades = vec![buy("fresh"), buy("fresh"), buy("old"), buy("unknown")];
+        assert_eq!(cache.fresh_buyer_ratio(&trades, now, 86_400), Some(0.5));
+        assert_eq!(cache.fresh_buyer_ratio(&[buy("unknown")], now, 86_400), None);
+    }
+
+    #[test]
+    fn test_oldest_block_time() {
+        let response = serde_json::json!({
+            "result": [
+                {"signature": "s1", "blockTime": 1_700_000_300},
+                {"signature": "s2", "blockTime": null},
+                {"signature": "s3", "blockTime": 1_700_000_100},
+            ]
+        });
+        assert_eq!(oldest_block_time(&response), Some(1_700_000_100));
+        assert_eq!(oldest_block_time(&serde_json::json!({"result": []})), None);
+    }
+}
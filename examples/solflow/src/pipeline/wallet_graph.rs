@@ -0,0 +1,200 @@
+//! Funding-linked wallet clusters
+//!
+//! Many "unique wallets" are one entity: a funder tops up a batch of fresh
+//! wallets and has each of them buy. `WalletGraph` records those funding
+//! edges (SOL sent by a signer to a fresh or co-signing wallet, see
+//! `streamer_core::funding`) and joins both ends with union-find, so every
+//! wallet reachable through funding lands in one cluster.
+//!
+//! `unique_clusters_300s` counts the distinct clusters among a window's
+//! wallets (a wallet without funding edges is its own cluster); BREAKOUT and
+//! FOCUSED count clusters instead of wallets so sybils don't pass as crowds.
+//!
+//! Only funding inside streamed (tracked-program) transactions is seen, so
+//! clusters are a lower bound on linkage. The streamers record into the
+//! process-wide graph (`shared()`); an engine reads it once handed it with
+//! `PipelineEngine::set_wallet_graph`. The graph is cleared once it holds
+//! `WALLET_GRAPH_MAX_WALLETS` wallets.
+//!
+//! Environment variables:
+//! - `WALLET_GRAPH_MAX_WALLETS`: Wallets kept before the graph is reset (default: 500000, 0 = disabled)
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const DEFAULT_MAX_WALLETS: usize = 500_000;
+
+#[derive(Debug, Default)]
+struct UnionFind {
+    index: HashMap<String, usize>,
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    fn id(&mut self, wallet: &str) -> usize {
+        if let Some(&id) = self.index.get(wallet) {
+            return id;
+        }
+        let id = self.parent.len();
+        self.index.insert(wallet.to_string(), id);
+        self.parent.push(id);
+        self.rank.push(0);
+        id
+    }
+
+    fn find(&mut self, mut id: usize) -> usize {
+        while self.parent[id] != id {
+            // Path halving
+            self.parent[id] = self.parent[self.parent[id]];
+            id = self.parent[id];
+        }
+        id
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        match self.rank[a].cmp(&self.rank[b]) {
+            std::cmp::Ordering::Less => self.parent[a] = b,
+            std::cmp::Ordering::Greater => self.parent[b] = a,
+            std::cmp::Ordering::Equal => {
+                self.parent[b] = a;
+                self.rank[a] += 1;
+            }
+        }
+    }
+}
+
+/// Wallets joined by funding edges
+#[derive(Debug)]
+pub struct WalletGraph {
+    max_wallets: usize,
+    inner: Mutex<UnionFind>,
+}
+
+impl WalletGraph {
+    /// Graph holding up to `max_wallets` wallets (0 disables it)
+    pub fn new(max_wallets: usize) -> Self {
+        Self {
+            max_wallets,
+            inner: Mutex::new(UnionFind::default()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let max_wallets = std::env::var("WALLET_GRAPH_MAX_WALLETS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_WALLETS);
+        Self::new(max_wallets)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_wallets > 0
+    }
+
+    /// Record that `funder` sent SOL to `funded`
+    pub fn link(&self, funder: &str, funded: &str) {
+        if !self.is_enabled() || funder == funded {
+            return;
+        }
+        let mut graph = self.inner.lock().unwrap();
+        let new_wallets = [funder, funded]
+            .iter()
+            .filter(|wallet| !graph.index.contains_key(**wallet))
+            .count();
+        if graph.parent.len() + new_wallets > self.max_wallets {
+            log::info!(
+                "🕸️  Wallet graph reached {} wallets, starting over",
+                graph.parent.len()
+            );
+            *graph = UnionFind::default();
+        }
+        let (a, b) = (graph.id(funder), graph.id(funded));
+        graph.union(a, b);
+    }
+
+    /// Whether two wallets are linked through funding
+    pub fn same_cluster(&self, a: &str, b: &str) -> bool {
+        if a == b {
+            return true;
+        }
+        let mut graph = self.inner.lock().unwrap();
+        match (graph.index.get(a).copied(), graph.index.get(b).copied()) {
+            (Some(a), Some(b)) => graph.find(a) == graph.find(b),
+            _ => false,
+        }
+    }
+
+    /// Distinct clusters among `wallets` (unlinked wallets count once each)
+    pub fn cluster_count<'a>(&self, wallets: impl IntoIterator<Item = &'a String>) -> i32 {
+        let mut graph = self.inner.lock().unwrap();
+        let mut roots = HashSet::new();
+        let mut unlinked = 0;
+        for wallet in wallets {
+            match graph.index.get(wallet.as_str()).copied() {
+                Some(id) => {
+                    let root = graph.find(id);
+                    roots.insert(root);
+                }
+                None => unlinked += 1,
+            }
+        }
+        (roots.len() + unlinked) as i32
+    }
+
+    /// Wallets currently in the graph
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+static SHARED: OnceLock<Arc<WalletGraph>> = OnceLock::new();
+
+/// The process-wide graph, created from the environment on first use
+pub fn shared() -> Arc<WalletGraph> {
+    SHARED.get_or_init(|| Arc::new(WalletGraph::from_env())).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_funded_wallets_collapse_into_one_cluster() {
+        let graph = WalletGraph::new(100);
+        // One funder tops up three buyers; one of them funds a fourth
+        graph.link("funder", "sybil_1");
+        graph.link("funder", "sybil_2");
+        graph.link("sybil_2", "sybil_3");
+        graph.link("other_funder", "independent");
+
+        let wallets: Vec<String> = ["sybil_1", "sybil_2", "sybil_3", "independent", "organic"]
+            .iter()
+            .map(|w| w.to_string())
+            .collect();
+        // sybils → 1, independent → 1, organic (no edges) → 1
+        assert_eq!(graph.cluster_count(&wallets), 3);
+        assert!(graph.same_cluster("sybil_1", "sybil_3"));
+        assert!(!graph.same_cluster("sybil_1", "independent"));
+
+        // Reset at capacity
+        let small = WalletGraph::new(3);
+        small.link("a", "b");
+        small.link("c", "d");
+        assert_eq!(small.len(), 2);
+        assert!(!small.same_cluster("a", "b"));
+
+        // Disabled graph links nothing
+        let disabled = WalletGraph::new(0);
+        disabled.link("a", "b");
+        assert!(disabled.is_empty());
+    }
+}
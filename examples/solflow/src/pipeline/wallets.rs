@@ -0,0 +1,390 @@
+//! Per-wallet rolling state and wallet-level signals
+//!
+//! `TokenRollingState` is keyed by mint, so it can say how much flowed into a
+//! token but not who has a track record. `WalletTracker` follows each wallet
+//! across mints: its recent trades (tokens traded and net SOL flow over
+//! 5m/1h/4h), its open round trips, and the realized PnL, win/loss counts and
+//! streaks of the round trips it has closed.
+//!
+//! A round trip opens on a wallet's first buy of a mint and closes once it
+//! has sold (nearly) everything it bought. When a wallet whose closed round
+//! trips clear the "smart" bar opens a new one, the tracker queues a
+//! SMART_WALLET_ENTRY signal for that mint; the engine emits it on the mint's
+//! next `compute_metrics`, after warm-up, like the other signals.
+//!
+//! Environment variables:
+//! - `WALLET_TRACKING_ENABLED`: Track wallets and emit SMART_WALLET_ENTRY (default: false)
+//! - `SMART_WALLET_MIN_CLOSED`: Closed round trips before a wallet can qualify (default: 5)
+//! - `SMART_WALLET_MIN_WIN_RATE`: Fraction of closed round trips in profit (default: 0.6)
+//! - `SMART_WALLET_MIN_PNL_SOL`: Realized PnL across closed round trips (default: 2.0)
+//! - `SMART_WALLET_MIN_ENTRY_SOL`: Smallest buy that counts as an entry (default: 0.5)
+//! - `WALLET_IDLE_PRUNE_SECS`: Forget idle wallets that don't qualify (default: 86400)
+
+use super::signals::{SignalDetails, SignalType, SmartWalletEntryDetails, TokenSignal};
+use super::types::{TradeDirection, TradeEvent};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::env;
+
+/// Windows reported by `WalletRollingState::window_stats`
+pub const WALLET_WINDOWS: [i64; 3] = [300, 3600, 14400];
+
+/// A round trip closes once this fraction of the bought tokens is sold
+const CLOSE_FRACTION: f64 = 0.95;
+
+/// Smart-wallet thresholds and memory bounds
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletTrackingConfig {
+    pub min_closed_trades: u32,
+    pub min_win_rate: f64,
+    pub min_realized_pnl_sol: f64,
+    pub min_entry_sol: f64,
+    pub idle_prune_secs: i64,
+}
+
+impl Default for WalletTrackingConfig {
+    fn default() -> Self {
+        Self {
+            min_closed_trades: 5,
+            min_win_rate: 0.6,
+            min_realized_pnl_sol: 2.0,
+            min_entry_sol: 0.5,
+            idle_prune_secs: 86_400,
+        }
+    }
+}
+
+impl WalletTrackingConfig {
+    /// Load from env; None when WALLET_TRACKING_ENABLED is not true
+    pub fn from_env() -> Option<Self> {
+        let enabled = env::var("WALLET_TRACKING_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        fn parse<T: std::str::FromStr>(name: &str, default: T) -> T {
+            env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        }
+
+        let defaults = Self::default();
+        Some(Self {
+            min_closed_trades: parse("SMART_WALLET_MIN_CLOSED", defaults.min_closed_trades),
+            min_win_rate: parse("SMART_WALLET_MIN_WIN_RATE", defaults.min_win_rate),
+            min_realized_pnl_sol: parse("SMART_WALLET_MIN_PNL_SOL", defaults.min_realized_pnl_sol),
+            min_entry_sol: parse("SMART_WALLET_MIN_ENTRY_SOL", defaults.min_entry_sol),
+            idle_prune_secs: parse("WALLET_IDLE_PRUNE_SECS", defaults.idle_prune_secs),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WalletTrade {
+    timestamp: i64,
+    mint: String,
+    direction: TradeDirection,
+    sol_amount: f64,
+}
+
+/// Round trip in progress for one mint
+#[derive(Debug, Clone, Default)]
+struct OpenPosition {
+    sol_spent: f64,
+    sol_received: f64,
+    tokens_bought: f64,
+    tokens_sold: f64,
+}
+
+/// A wallet's activity within one window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WalletWindowStats {
+    pub tokens_traded: usize,
+    /// SOL received from sells minus SOL spent on buys
+    pub net_sol_flow: f64,
+    pub buy_count: i32,
+    pub sell_count: i32,
+}
+
+/// Rolling activity and round-trip history for one wallet
+#[derive(Debug, Clone, Default)]
+pub struct WalletRollingState {
+    /// Trades within the longest window, oldest first
+    recent: VecDeque<WalletTrade>,
+    positions: HashMap<String, OpenPosition>,
+    pub wins: u32,
+    pub losses: u32,
+    /// Consecutive profitable round trips (0 after a loss)
+    pub win_streak: u32,
+    /// Consecutive losing round trips (0 after a win)
+    pub loss_streak: u32,
+    pub realized_pnl_sol: f64,
+    pub last_seen_ts: i64,
+}
+
+impl WalletRollingState {
+    fn add_trade(&mut self, trade: &TradeEvent, now: i64) {
+        self.last_seen_ts = self.last_seen_ts.max(trade.timestamp);
+        self.recent.push_back(WalletTrade {
+            timestamp: trade.timestamp,
+            mint: trade.mint.clone(),
+            direction: trade.direction,
+            sol_amount: trade.sol_amount,
+        });
+        let cutoff = now - WALLET_WINDOWS[WALLET_WINDOWS.len() - 1];
+        while self.recent.front().is_some_and(|t| t.timestamp < cutoff) {
+            self.recent.pop_front();
+        }
+
+        match trade.direction {
+            TradeDirection::Buy => {
+                let position = self.positions.entry(trade.mint.clone()).or_default();
+                position.sol_spent += trade.sol_amount;
+                position.tokens_bought += trade.token_amount;
+            }
+            TradeDirection::Sell => {
+                // Sells of tokens bought before tracking started carry no PnL
+                let Some(position) = self.positions.get_mut(&trade.mint) else {
+                    return;
+                };
+                position.sol_received += trade.sol_amount;
+                position.tokens_sold += trade.token_amount;
+                if position.tokens_sold >= position.tokens_bought * CLOSE_FRACTION {
+                    let pnl = position.sol_received - position.sol_spent;
+                    self.positions.remove(&trade.mint);
+                    self.close_round_trip(pnl);
+                }
+            }
+            TradeDirection::Unknown => {}
+        }
+    }
+
+    fn close_round_trip(&mut self, pnl: f64) {
+        self.realized_pnl_sol += pnl;
+        if pnl > 0.0 {
+            self.wins += 1;
+            self.win_streak += 1;
+            self.loss_streak = 0;
+        } else {
+            self.losses += 1;
+            self.loss_streak += 1;
+            self.win_streak = 0;
+        }
+    }
+
+    pub fn closed_trades(&self) -> u32 {
+        self.wins + self.losses
+    }
+
+    /// Fraction of closed round trips in profit (None before the first close)
+    pub fn win_rate(&self) -> Option<f64> {
+        let closed = self.closed_trades();
+        (closed > 0).then(|| self.wins as f64 / closed as f64)
+    }
+
+    pub fn has_open_position(&self, mint: &str) -> bool {
+        self.positions.contains_key(mint)
+    }
+
+    /// Whether the wallet's closed round trips clear the smart-wallet bar
+    pub fn is_smart(&self, config: &WalletTrackingConfig) -> bool {
+        self.closed_trades() >= config.min_closed_trades
+            && self.win_rate().unwrap_or(0.0) >= config.min_win_rate
+            && self.realized_pnl_sol >= config.min_realized_pnl_sol
+    }
+
+    /// Activity within the last `window` seconds
+    pub fn window_stats(&self, now: i64, window: i64) -> WalletWindowStats {
+        let mut stats = WalletWindowStats::default();
+        let mut mints = HashSet::new();
+        for trade in self.recent.iter().filter(|t| t.timestamp >= now - window) {
+            mints.insert(trade.mint.as_str());
+            match trade.direction {
+                TradeDirection::Buy => {
+                    stats.buy_count += 1;
+                    stats.net_sol_flow -= trade.sol_amount;
+                }
+                TradeDirection::Sell => {
+                    stats.sell_count += 1;
+                    stats.net_sol_flow += trade.sol_amount;
+                }
+                TradeDirection::Unknown => {}
+            }
+        }
+        stats.tokens_traded = mints.len();
+        stats
+    }
+}
+
+/// All tracked wallets plus wallet-level signals waiting for their mint's flush
+pub struct WalletTracker {
+    config: WalletTrackingConfig,
+    wallets: HashMap<String, WalletRollingState>,
+    /// mint → SMART_WALLET_ENTRY signals not yet handed to the engine
+    pending: HashMap<String, Vec<TokenSignal>>,
+}
+
+impl WalletTracker {
+    pub fn new(config: WalletTrackingConfig) -> Self {
+        Self {
+            config,
+            wallets: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &WalletTrackingConfig {
+        &self.config
+    }
+
+    /// Update the trading wallet; queues SMART_WALLET_ENTRY for qualifying entries
+    pub fn record(&mut self, trade: &TradeEvent, now: i64) {
+        if trade.user_account.is_empty() || trade.direction == TradeDirection::Unknown {
+            return;
+        }
+
+        let wallet = self.wallets.entry(trade.user_account.clone()).or_default();
+        let is_entry = trade.direction == TradeDirection::Buy
+            && !wallet.has_open_position(&trade.mint)
+            && trade.sol_amount >= self.config.min_entry_sol;
+        // Judge the wallet on its record before this trade
+        if is_entry && wallet.is_smart(&self.config) {
+            let signal = smart_wallet_entry(trade, wallet, now);
+            self.pending.entry(trade.mint.clone()).or_default().push(signal);
+        }
+
+        wallet.add_trade(trade, now);
+    }
+
+    /// Queued wallet-level signals for `mint`
+    pub fn drain_signals(&mut self, mint: &str) -> Vec<TokenSignal> {
+        self.pending.remove(mint).unwrap_or_default()
+    }
+
+    pub fn wallet(&self, address: &str) -> Option<&WalletRollingState> {
+        self.wallets.get(address)
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+
+    /// Forget idle wallets that don't qualify as smart, and their open round trips
+    ///
+    /// Smart wallets are kept: their history is what the signal is built on.
+    pub fn prune(&mut self, now: i64) {
+        let cutoff = now - self.config.idle_prune_secs;
+        let before = self.wallets.len();
+        let config = &self.config;
+        self.wallets
+            .retain(|_, wallet| wallet.last_seen_ts >= cutoff || wallet.is_smart(config));
+
+        let pruned = before - self.wallets.len();
+        if pruned > 0 {
+            log::debug!("🗑️  Pruned {} idle wallets ({} tracked)", pruned, self.wallets.len());
+        }
+    }
+
+    /// Drop queued signals for a mint whose state was pruned
+    pub fn forget_mint(&mut self, mint: &str) {
+        self.pending.remove(mint);
+    }
+}
+
+fn smart_wallet_entry(trade: &TradeEvent, wallet: &WalletRollingState, now: i64) -> TokenSignal {
+    let win_rate = wallet.win_rate().unwrap_or(0.0);
+    let severity = if win_rate >= 0.8 && wallet.win_streak >= 3 { 4 } else { 3 };
+    TokenSignal::new(trade.mint.clone(), SignalType::SmartWalletEntry, 0, now)
+        .with_severity(severity)
+        .with_score(win_rate)
+        .with_typed_details(SignalDetails::SmartWalletEntry(SmartWalletEntryDetails {
+            wallet: trade.user_account.clone(),
+            sol_amount: trade.sol_amount,
+            win_rate,
+            closed_trades: wallet.closed_trades(),
+            realized_pnl_sol: wallet.realized_pnl_sol,
+            win_streak: wallet.win_streak,
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(wallet: &str, mint: &str, direction: TradeDirection, sol: f64, tokens: f64, ts: i64) -> TradeEvent {
+        TradeEvent {
+            direction,
+            sol_amount: sol,
+            token_amount: tokens,
+            user_account: wallet.to_string(),
+            ..TradeEvent::test_buy(mint, ts)
+        }
+    }
+
+    fn round_trip(tracker: &mut WalletTracker, wallet: &str, mint: &str, spent: f64, received: f64, ts: i64) {
+        tracker.record(&trade(wallet, mint, TradeDirection::Buy, spent, 1000.0, ts), ts);
+        tracker.record(&trade(wallet, mint, TradeDirection::Sell, received, 1000.0, ts + 60), ts + 60);
+    }
+
+    #[test]
+    fn test_round_trips_update_record_and_windows() {
+        let mut tracker = WalletTracker::new(WalletTrackingConfig::default());
+        round_trip(&mut tracker, "w", "A", 1.0, 2.0, 1000);
+        round_trip(&mut tracker, "w", "B", 1.0, 0.5, 2000);
+        // Partial sell keeps C open
+        tracker.record(&trade("w", "C", TradeDirection::Buy, 1.0, 1000.0, 3000), 3000);
+        tracker.record(&trade("w", "C", TradeDirection::Sell, 0.6, 500.0, 3100), 3100);
+
+        let wallet = tracker.wallet("w").unwrap();
+        assert_eq!((wallet.wins, wallet.losses), (1, 1));
+        assert_eq!((wallet.win_streak, wallet.loss_streak), (0, 1));
+        assert!((wallet.realized_pnl_sol - 0.5).abs() < 1e-9);
+        assert!(wallet.has_open_position("C"));
+
+        let stats = wallet.window_stats(3100, 300);
+        assert_eq!(stats.tokens_traded, 1);
+        assert!((stats.net_sol_flow - -0.4).abs() < 1e-9);
+        assert_eq!(wallet.window_stats(3100, 3600).tokens_traded, 3);
+    }
+
+    #[test]
+    fn test_smart_wallet_entry_queued_once_per_position() {
+        let config = WalletTrackingConfig {
+            min_closed_trades: 3,
+            min_win_rate: 0.6,
+            min_realized_pnl_sol: 1.0,
+            ..WalletTrackingConfig::default()
+        };
+        let mut tracker = WalletTracker::new(config);
+        for (i, mint) in ["A", "B", "C"].into_iter().enumerate() {
+            round_trip(&mut tracker, "smart", mint, 1.0, 2.0, 1000 + i as i64 * 100);
+            round_trip(&mut tracker, "dumb", mint, 1.0, 0.5, 1000 + i as i64 * 100);
+        }
+
+        tracker.record(&trade("smart", "NEW", TradeDirection::Buy, 2.0, 1000.0, 5000), 5000);
+        // Adding to an open position is not a new entry
+        tracker.record(&trade("smart", "NEW", TradeDirection::Buy, 2.0, 1000.0, 5010), 5010);
+        tracker.record(&trade("dumb", "NEW", TradeDirection::Buy, 2.0, 1000.0, 5020), 5020);
+
+        let signals = tracker.drain_signals("NEW");
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type, SignalType::SmartWalletEntry);
+        match SignalDetails::from_json(signals[0].details_json.as_deref().unwrap()).unwrap() {
+            SignalDetails::SmartWalletEntry(details) => {
+                assert_eq!(details.wallet, "smart");
+                assert_eq!(details.closed_trades, 3);
+                assert_eq!(details.win_streak, 3);
+            }
+            other => panic!("unexpected details {:?}", other),
+        }
+        assert!(tracker.drain_signals("NEW").is_empty());
+
+        // Idle non-qualifying wallets are pruned, smart ones kept
+        tracker.prune(5020 + 86_400 * 2);
+        assert!(tracker.wallet("smart").is_some());
+        assert!(tracker.wallet("dumb").is_none());
+    }
+}
@@ -0,0 +1,277 @@
+//! Webhook input for external signals
+//!
+//! Lets off-chain sources (a Telegram scraper, a KOL tracker, manual calls)
+//! push events into `token_signals` next to the on-chain detectors. Each
+//! submission becomes an EXTERNAL signal tagged with the submitter's
+//! `source`, so dashboards and downstream consumers can correlate human intel
+//! with on-chain activity on the same mint. Writes go through the normal
+//! signal path, so blocklisted mints are rejected.
+//!
+//! ```text
+//! POST /signals
+//! Authorization: Bearer <SIGNAL_WEBHOOK_TOKEN>
+//! {"mint": "...", "source": "telegram", "severity": 3,
+//!  "label": "CALL", "message": "called in alpha chat", "url": "https://t.me/..."}
+//! ```
+//!
+//! Environment variables:
+//! - `SIGNAL_WEBHOOK_ADDR`: Listen address, e.g. `127.0.0.1:8787` (unset = disabled)
+//! - `SIGNAL_WEBHOOK_TOKEN`: Required bearer token (unset = no auth, localhost use only);
+//!   accepts `file:`/`keyring:` references or `SIGNAL_WEBHOOK_TOKEN_FILE`
+
+use super::db::AggregateDbWriter;
+use super::signals::{
+    ExternalDetails, SignalDetails, SignalType, TokenSignal, SIGNAL_SOURCE_ONCHAIN,
+};
+use crate::streamer_core::secrets::secret_from_env;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Longest accepted free-text field (message/url), in bytes
+const MAX_TEXT_LEN: usize = 1024;
+
+/// Longest accepted source tag / label
+const MAX_TAG_LEN: usize = 32;
+
+/// Body of `POST /signals`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExternalSignalRequest {
+    pub mint: String,
+    /// Submitter tag, e.g. "telegram" (lowercase letters, digits, `_`, `-`)
+    pub source: String,
+    #[serde(default)]
+    pub severity: Option<i32>,
+    #[serde(default)]
+    pub score: Option<f64>,
+    /// Window the event refers to; 0 for a point-in-time event
+    #[serde(default)]
+    pub window_seconds: Option<i32>,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+fn is_valid_mint(mint: &str) -> bool {
+    (32..=44).contains(&mint.len())
+        && mint
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'))
+}
+
+fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.len() <= MAX_TAG_LEN
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+impl ExternalSignalRequest {
+    /// Validate and convert to an EXTERNAL signal
+    pub fn into_signal(self, now: i64) -> Result<TokenSignal, String> {
+        let mint = self.mint.trim().to_string();
+        if !is_valid_mint(&mint) {
+            return Err(format!("invalid mint '{}'", mint));
+        }
+        if !is_valid_tag(&self.source) || self.source == SIGNAL_SOURCE_ONCHAIN {
+            return Err(format!(
+                "invalid source '{}' (1-{} chars of a-z, 0-9, _ or -; '{}' is reserved)",
+                self.source, MAX_TAG_LEN, SIGNAL_SOURCE_ONCHAIN
+            ));
+        }
+        if self.label.as_ref().is_some_and(|l| l.is_empty() || l.len() > MAX_TAG_LEN) {
+            return Err(format!("label must be 1-{} chars", MAX_TAG_LEN));
+        }
+        let too_long = |text: &Option<String>| text.as_ref().is_some_and(|t| t.len() > MAX_TEXT_LEN);
+        if too_long(&self.message) || too_long(&self.url) {
+            return Err(format!("message/url longer than {} bytes", MAX_TEXT_LEN));
+        }
+        let window_seconds = self.window_seconds.unwrap_or(0);
+        if window_seconds < 0 {
+            return Err("window_seconds must not be negative".to_string());
+        }
+
+        let mut signal = TokenSignal::new(mint, SignalType::External, window_seconds, now)
+            .with_severity(self.severity.unwrap_or(1))
+            .with_source(self.source)
+            .with_typed_details(SignalDetails::External(ExternalDetails {
+                label: self.label,
+                message: self.message,
+                url: self.url,
+            }));
+        if let Some(score) = self.score.filter(|s| s.is_finite()) {
+            signal = signal.with_score(score);
+        }
+        Ok(signal)
+    }
+}
+
+#[derive(Clone)]
+struct WebhookState {
+    db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
+    token: Option<String>,
+}
+
+fn is_authorized(headers: &HeaderMap, token: Option<&str>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|given| given == token)
+}
+
+async fn submit_signal(
+    State(state): State<WebhookState>,
+    headers: HeaderMap,
+    Json(request): Json<ExternalSignalRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !is_authorized(&headers, state.token.as_deref()) {
+        return (StatusCode::UNAUTHORIZED, Json(json!({"error": "unauthorized"})));
+    }
+
+    let signal = match request.into_signal(chrono::Utc::now().timestamp()) {
+        Ok(signal) => signal,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))),
+    };
+    let (mint, source) = (signal.mint.clone(), signal.source.clone());
+
+    let result = state
+        .db_writer
+        .write_signal(signal)
+        .await
+        .map_err(|e| e.to_string());
+    match result {
+        Ok(()) => {
+            info!("📨 EXTERNAL signal from {} for {}", source, mint);
+            (StatusCode::CREATED, Json(json!({"status": "ok"})))
+        }
+        Err(e) => {
+            warn!("⚠️  EXTERNAL signal from {} rejected: {}", source, e);
+            (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({"error": e})))
+        }
+    }
+}
+
+/// Webhook server settings
+#[derive(Debug, Clone)]
+pub struct SignalWebhook {
+    pub addr: String,
+    token: Option<String>,
+}
+
+impl SignalWebhook {
+    /// Create from the environment (see module docs); None unless an address is set
+    pub fn from_env() -> Option<Self> {
+        let addr = std::env::var("SIGNAL_WEBHOOK_ADDR")
+            .ok()
+            .filter(|s| !s.trim().is_empty())?;
+        // Fail closed: an unreadable token reference disables the webhook
+        let token = match secret_from_env("SIGNAL_WEBHOOK_TOKEN") {
+            Ok(token) => token,
+            Err(e) => {
+                warn!("⚠️  Signal webhook disabled: {}", e);
+                return None;
+            }
+        };
+        Some(Self { addr, token })
+    }
+
+    pub fn has_token(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Bind and serve until the process exits
+    pub async fn serve(
+        self,
+        db_writer: Arc<dyn AggregateDbWriter + Send + Sync>,
+    ) -> Result<(), std::io::Error> {
+        if self.token.is_none() {
+            warn!("⚠️  SIGNAL_WEBHOOK_TOKEN not set: webhook accepts unauthenticated signals");
+        }
+
+        let app = Router::new()
+            .route("/signals", post(submit_signal))
+            .with_state(WebhookState {
+                db_writer,
+                token: self.token,
+            });
+
+        let listener = tokio::net::TcpListener::bind(&self.addr).await?;
+        axum::serve(listener, app).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINT: &str = "7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr";
+
+    fn request(source: &str) -> ExternalSignalRequest {
+        ExternalSignalRequest {
+            mint: MINT.to_string(),
+            source: source.to_string(),
+            severity: Some(9),
+            score: None,
+            window_seconds: None,
+            label: Some("CALL".to_string()),
+            message: Some("called in alpha chat".to_string()),
+            url: None,
+        }
+    }
+
+    #[test]
+    fn test_into_signal() {
+        let signal = request("telegram").into_signal(1000).unwrap();
+        assert_eq!(signal.signal_type, SignalType::External);
+        assert_eq!(signal.source, "telegram");
+        assert_eq!(signal.severity, 5);
+        assert_eq!(signal.window_seconds, 0);
+        assert_eq!(signal.created_at, 1000);
+
+        let details = SignalDetails::from_json(signal.details_json.as_deref().unwrap()).unwrap();
+        match details {
+            SignalDetails::External(external) => assert_eq!(external.label.as_deref(), Some("CALL")),
+            other => panic!("unexpected details: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_requests() {
+        assert!(request("onchain").into_signal(0).is_err());
+        assert!(request("Telegram Bot").into_signal(0).is_err());
+
+        let mut bad_mint = request("telegram");
+        bad_mint.mint = "not-a-mint".to_string();
+        assert!(bad_mint.into_signal(0).is_err());
+
+        let mut long_message = request("telegram");
+        long_message.message = Some("x".repeat(MAX_TEXT_LEN + 1));
+        assert!(long_message.into_signal(0).is_err());
+    }
+
+    #[test]
+    fn test_bearer_auth() {
+        let mut headers = HeaderMap::new();
+        assert!(is_authorized(&headers, None));
+        assert!(!is_authorized(&headers, Some("secret")));
+
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        assert!(is_authorized(&headers, Some("secret")));
+        assert!(!is_authorized(&headers, Some("other")));
+    }
+}
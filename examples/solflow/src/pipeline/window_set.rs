@@ -0,0 +1,224 @@
+//! Configurable rolling-window sizes
+//!
+//! The 60s/300s/900s windows drive the built-in signals and are always
+//! present; `PIPELINE_WINDOWS` adds more, e.g. `30s,60,300,900,1h`. Every
+//! configured window gets the same metrics (net flow, buy and sell counts)
+//! under generated column names: `net_flow_30s_sol`, `buy_count_30s`,
+//! `sell_count_30s`. Names that are real `token_aggregates` columns are
+//! written there; the rest go to `window_metrics_json`, so adding a window
+//! needs no schema change.
+//!
+//! Windows up to 900s are sliced from the per-token trade buffer. Longer
+//! windows (whole minutes, up to 14400s) are summed from the minute buckets.
+
+use serde_json::{Map, Value};
+use std::time::Duration;
+
+/// Windows the built-in signals and fixed columns depend on
+pub const CORE_WINDOW_SECS: [i64; 3] = [60, 300, 900];
+
+/// Longest window served from the trade buffer
+pub const TRADE_BUFFER_SECS: i64 = 900;
+
+/// Longest window the minute buckets cover
+pub const MAX_WINDOW_SECS: i64 = 14_400;
+
+/// Generated window columns that exist in `/sql/02_token_aggregates.sql`
+const FIXED_COLUMNS: &[&str] = &[
+    "net_flow_60s_sol",
+    "net_flow_300s_sol",
+    "net_flow_900s_sol",
+    "net_flow_3600s_sol",
+    "net_flow_7200s_sol",
+    "net_flow_14400s_sol",
+    "buy_count_60s",
+    "sell_count_60s",
+    "buy_count_300s",
+    "sell_count_300s",
+    "buy_count_900s",
+    "sell_count_900s",
+];
+
+/// Sorted, de-duplicated window sizes (always including the core windows)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowSet {
+    windows: Vec<Duration>,
+}
+
+impl Default for WindowSet {
+    fn default() -> Self {
+        Self {
+            windows: CORE_WINDOW_SECS
+                .iter()
+                .map(|secs| Duration::from_secs(*secs as u64))
+                .collect(),
+        }
+    }
+}
+
+impl WindowSet {
+    /// Core windows plus `windows`; fails on a size no source can serve
+    pub fn new(windows: impl IntoIterator<Item = Duration>) -> Result<Self, String> {
+        let mut set = Self::default();
+        for window in windows {
+            let secs = window.as_secs() as i64;
+            if secs == 0 || window.subsec_nanos() != 0 {
+                return Err(format!("window {:?} must be a whole number of seconds", window));
+            }
+            if secs > MAX_WINDOW_SECS {
+                return Err(format!("window {}s is longer than {}s", secs, MAX_WINDOW_SECS));
+            }
+            if secs > TRADE_BUFFER_SECS && secs % 60 != 0 {
+                return Err(format!(
+                    "window {}s must be whole minutes above {}s",
+                    secs, TRADE_BUFFER_SECS
+                ));
+            }
+            set.windows.push(window);
+        }
+        set.windows.sort();
+        set.windows.dedup();
+        Ok(set)
+    }
+
+    /// Parse a comma-separated list such as `30s,60,5m,1h` (bare numbers are seconds)
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let windows = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(parse_window)
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(windows)
+    }
+
+    pub fn windows(&self) -> &[Duration] {
+        &self.windows
+    }
+
+    /// Window sizes in seconds, shortest first
+    pub fn secs(&self) -> impl Iterator<Item = i64> + '_ {
+        self.windows.iter().map(|window| window.as_secs() as i64)
+    }
+
+    /// Generated column names for every window
+    pub fn columns(&self) -> Vec<WindowColumns> {
+        self.secs().map(WindowColumns::for_secs).collect()
+    }
+}
+
+fn parse_window(token: &str) -> Result<Duration, String> {
+    let (digits, unit) = match token.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => token.split_at(index),
+        None => (token, "s"),
+    };
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid window '{}'", token))?;
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return Err(format!("invalid window unit in '{}' (use s, m or h)", token)),
+    };
+    Ok(Duration::from_secs(value * multiplier))
+}
+
+/// Column names generated for one window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowColumns {
+    pub secs: i64,
+    pub net_flow: String,
+    pub buy_count: String,
+    pub sell_count: String,
+}
+
+impl WindowColumns {
+    pub fn for_secs(secs: i64) -> Self {
+        Self {
+            secs,
+            net_flow: format!("net_flow_{}s_sol", secs),
+            buy_count: format!("buy_count_{}s", secs),
+            sell_count: format!("sell_count_{}s", secs),
+        }
+    }
+}
+
+/// Whether a generated column is a real `token_aggregates` column
+pub fn is_fixed_column(name: &str) -> bool {
+    FIXED_COLUMNS.contains(&name)
+}
+
+/// Net flow and trade counts for one configured window
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WindowMetrics {
+    pub secs: i64,
+    pub net_flow_sol: f64,
+    pub buy_count: i32,
+    pub sell_count: i32,
+}
+
+/// `window_metrics_json` for the generated columns without a fixed column
+///
+/// None when every configured window maps onto fixed columns.
+pub fn extra_columns_json(windows: &[WindowMetrics]) -> Option<String> {
+    let mut object = Map::new();
+    for window in windows {
+        let columns = WindowColumns::for_secs(window.secs);
+        let values = [
+            (columns.net_flow, serde_json::json!(window.net_flow_sol)),
+            (columns.buy_count, Value::from(window.buy_count)),
+            (columns.sell_count, Value::from(window.sell_count)),
+        ];
+        for (name, value) in values {
+            if !is_fixed_column(&name) {
+                object.insert(name, value);
+            }
+        }
+    }
+    (!object.is_empty()).then(|| Value::Object(object).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_adds_core_windows_and_maps_columns() {
+        let set = WindowSet::parse("1h, 30s,5m,60").unwrap();
+        assert_eq!(set.secs().collect::<Vec<_>>(), vec![30, 60, 300, 900, 3600]);
+        assert_eq!(WindowSet::parse("").unwrap(), WindowSet::default());
+
+        assert!(WindowSet::parse("90m").unwrap().secs().any(|s| s == 5400));
+        assert!(WindowSet::parse("1000").is_err()); // not whole minutes
+        assert!(WindowSet::parse("5h").is_err());
+        assert!(WindowSet::parse("10x").is_err());
+
+        let columns = WindowColumns::for_secs(30);
+        assert_eq!(columns.net_flow, "net_flow_30s_sol");
+        assert_eq!(columns.sell_count, "sell_count_30s");
+
+        // 3600s has a fixed net flow column but no fixed counts
+        let json = extra_columns_json(&[
+            WindowMetrics { secs: 30, net_flow_sol: 1.5, buy_count: 2, sell_count: 1 },
+            WindowMetrics { secs: 60, net_flow_sol: 2.0, buy_count: 3, sell_count: 1 },
+            WindowMetrics { secs: 3600, net_flow_sol: 9.0, buy_count: 7, sell_count: 4 },
+        ])
+        .unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "net_flow_30s_sol": 1.5,
+                "buy_count_30s": 2,
+                "sell_count_30s": 1,
+                "buy_count_3600s": 7,
+                "sell_count_3600s": 4,
+            })
+        );
+        assert_eq!(
+            extra_columns_json(&[WindowMetrics { secs: 300, ..Default::default() }]),
+            None
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Shared account-key cache
+//!
+//! Hot accounts (pools, fee recipients, active wallets, the tracked programs)
+//! appear in thousands of transactions per minute, and every trade event
+//! base58-encodes its user and program keys. `AccountKeyCache` keeps the
+//! encoded address and the account's classification in a bounded LRU, so
+//! repeat accounts cost one hash lookup instead of an encode plus set probes.
+//!
+//! One process-wide cache (`shared()`) is used by every processor, so the
+//! per-datasource processor clones all warm the same entries.
+//!
+//! Environment variables:
+//! - `ACCOUNT_CACHE_CAPACITY`: Entries kept (default: 50000)
+//! - `KNOWN_POOL_ACCOUNTS`: Comma-separated pool/vault accounts (default: none)
+//! - `KNOWN_FEE_ACCOUNTS`: Comma-separated protocol fee recipients (default: none)
+
+use crate::instruction_scanner::InstructionScanner;
+use lru::LruCache;
+use solana_pubkey::Pubkey;
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const DEFAULT_CAPACITY: usize = 50_000;
+
+/// Infrastructure programs that show up as accounts in most swaps
+const SYSTEM_PROGRAMS: &[&str] = &[
+    "11111111111111111111111111111111",
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    "ComputeBudget111111111111111111111111111111",
+];
+
+/// What an account is, as far as flow attribution cares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountClass {
+    Wallet,
+    Program,
+    Pool,
+    FeeAccount,
+}
+
+/// Cached view of one account
+#[derive(Debug, Clone)]
+pub struct CachedAccount {
+    pub address: Arc<str>,
+    pub class: AccountClass,
+}
+
+/// Bounded LRU of pubkey → (base58 address, classification)
+pub struct AccountKeyCache {
+    entries: Mutex<LruCache<Pubkey, CachedAccount>>,
+    programs: HashSet<Pubkey>,
+    pools: HashSet<Pubkey>,
+    fee_accounts: HashSet<Pubkey>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+fn parse_keys<'a>(keys: impl IntoIterator<Item = &'a str>) -> HashSet<Pubkey> {
+    keys.into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| match Pubkey::from_str(s) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                log::warn!("⚠️  Ignoring invalid account '{}': {}", s, e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn keys_from_env(name: &str) -> HashSet<Pubkey> {
+    std::env::var(name)
+        .map(|value| parse_keys(value.split(',')))
+        .unwrap_or_default()
+}
+
+impl AccountKeyCache {
+    /// Empty cache that knows the tracked and infrastructure programs
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        let tracked = InstructionScanner::new().tracked_program_ids();
+        let mut programs = parse_keys(SYSTEM_PROGRAMS.iter().copied());
+        programs.extend(parse_keys(tracked.iter().map(String::as_str)));
+
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            programs,
+            pools: HashSet::new(),
+            fee_accounts: HashSet::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Create from the environment (see module docs)
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("ACCOUNT_CACHE_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        Self::new(capacity)
+            .with_pools(keys_from_env("KNOWN_POOL_ACCOUNTS"))
+            .with_fee_accounts(keys_from_env("KNOWN_FEE_ACCOUNTS"))
+    }
+
+    pub fn with_pools(mut self, pools: impl IntoIterator<Item = Pubkey>) -> Self {
+        self.pools.extend(pools);
+        self
+    }
+
+    pub fn with_fee_accounts(mut self, fee_accounts: impl IntoIterator<Item = Pubkey>) -> Self {
+        self.fee_accounts.extend(fee_accounts);
+        self
+    }
+
+    fn classify_uncached(&self, key: &Pubkey) -> AccountClass {
+        if self.programs.contains(key) {
+            AccountClass::Program
+        } else if self.pools.contains(key) {
+            AccountClass::Pool
+        } else if self.fee_accounts.contains(key) {
+            AccountClass::FeeAccount
+        } else {
+            AccountClass::Wallet
+        }
+    }
+
+    /// Address and classification, computed once per cached key
+    pub fn lookup(&self, key: &Pubkey) -> CachedAccount {
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(cached) = entries.get(key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return cached.clone();
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let cached = CachedAccount {
+            address: Arc::from(key.to_string()),
+            class: self.classify_uncached(key),
+        };
+        entries.put(*key, cached.clone());
+        cached
+    }
+
+    /// Base58 address of `key`
+    pub fn address(&self, key: &Pubkey) -> Arc<str> {
+        self.lookup(key).address
+    }
+
+    pub fn classify(&self, key: &Pubkey) -> AccountClass {
+        self.lookup(key).class
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// (hits, misses) since startup
+    pub fn stats(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+}
+
+static SHARED: OnceLock<Arc<AccountKeyCache>> = OnceLock::new();
+
+/// The process-wide cache, created from the environment on first use
+pub fn shared() -> Arc<AccountKeyCache> {
+    SHARED
+        .get_or_init(|| {
+            let cache = AccountKeyCache::from_env();
+            log::info!(
+                "🗂️  Account cache: {} known programs, {} pools, {} fee accounts",
+                cache.programs.len(),
+                cache.pools.len(),
+                cache.fee_accounts.len()
+            );
+            Arc::new(cache)
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(index: u8) -> Pubkey {
+        let mut bytes = [0u8; 32];
+        bytes[0] = index;
+        Pubkey::from(bytes)
+    }
+
+    #[test]
+    fn test_lookup_caches_address_and_class() {
+        let cache = AccountKeyCache::new(8)
+            .with_pools([key(1)])
+            .with_fee_accounts([key(2)]);
+        let pumpswap = Pubkey::from_str("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA").unwrap();
+
+        assert_eq!(cache.classify(&key(1)), AccountClass::Pool);
+        assert_eq!(cache.classify(&key(2)), AccountClass::FeeAccount);
+        assert_eq!(cache.classify(&key(3)), AccountClass::Wallet);
+        assert_eq!(cache.classify(&pumpswap), AccountClass::Program);
+
+        assert_eq!(&*cache.address(&key(3)), key(3).to_string());
+        assert_eq!(cache.stats(), (1, 4));
+    }
+
+    #[test]
+    fn test_cache_is_bounded_lru() {
+        let cache = AccountKeyCache::new(2);
+        cache.lookup(&key(1));
+        cache.lookup(&key(2));
+        // Touch 1 so 2 is least recently used
+        cache.lookup(&key(1));
+        cache.lookup(&key(3));
+
+        assert_eq!(cache.len(), 2);
+        let (hits, misses) = cache.stats();
+        cache.lookup(&key(1));
+        assert_eq!(cache.stats(), (hits + 1, misses));
+        cache.lookup(&key(2));
+        assert_eq!(cache.stats(), (hits + 1, misses + 1));
+    }
+}
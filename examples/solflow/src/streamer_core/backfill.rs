@@ -0,0 +1,415 @@
+//! Historical backfill datasource
+//!
+//! The live datasources start at "now", so rolling windows and detectors need
+//! hours of streaming before they mean anything. Backfill walks
+//! `getSignaturesForAddress` for every tracked program back to the start of a
+//! time/slot range, fetches each transaction with `getTransaction` and feeds
+//! them through the same Pipeline and processor as live data, oldest first.
+//! The unified streamer runs it to completion before connecting live.
+//!
+//! For an open-ended range (no end bound), signatures that landed while the
+//! backfill was running are picked up in follow-up passes, so the live stream
+//! starts close to where history ends.
+//!
+//! Environment variables:
+//! - `BACKFILL_HOURS`: Backfill the last N hours (enables backfill)
+//! - `BACKFILL_START_TIME` / `BACKFILL_END_TIME`: Unix time range (alternative to BACKFILL_HOURS)
+//! - `BACKFILL_START_SLOT` / `BACKFILL_END_SLOT`: Slot range, combined with the time range
+//! - `BACKFILL_RPC_URL`: RPC endpoint (default: SOLANA_RPC_URL)
+//! - `BACKFILL_CONCURRENCY`: Parallel getTransaction requests (default: 8)
+
+use crate::streamer_core::config::ConfigError;
+use crate::streamer_core::grpc_client::TRACKED_PROGRAMS;
+use crate::streamer_core::secrets::redact_url;
+use async_trait::async_trait;
+use carbon_core::{
+    datasource::{Datasource, DatasourceId, TransactionUpdate, Update, UpdateType},
+    error::{CarbonResult, Error as CarbonError},
+    metrics::MetricsCollection,
+    transformers::transaction_metadata_from_original_meta,
+};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::RpcTransactionConfig,
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio_util::sync::CancellationToken;
+
+/// getSignaturesForAddress page size (RPC maximum)
+const SIGNATURE_PAGE_LIMIT: usize = 1000;
+
+/// Follow-up passes for signatures that landed during the backfill
+const MAX_CATCH_UP_PASSES: usize = 3;
+
+const MAX_RETRIES: u32 = 3;
+
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    pub rpc_url: String,
+    pub programs: Vec<Pubkey>,
+    pub start_time: Option<i64>,
+    pub end_time: Option<i64>,
+    pub start_slot: Option<u64>,
+    pub end_slot: Option<u64>,
+    pub concurrency: usize,
+}
+
+fn parse_env<T: FromStr>(name: &str) -> Result<Option<T>, ConfigError> {
+    match std::env::var(name) {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| ConfigError::InvalidValue(format!("{} must be a number, got '{}'", name, value))),
+        _ => Ok(None),
+    }
+}
+
+fn rpc_url_from_env() -> Result<String, ConfigError> {
+    std::env::var("BACKFILL_RPC_URL")
+        .or_else(|_| std::env::var("SOLANA_RPC_URL"))
+        .map_err(|_| ConfigError::MissingVariable("BACKFILL_RPC_URL or SOLANA_RPC_URL".to_string()))
+}
+
+fn tracked_programs() -> Vec<Pubkey> {
+    TRACKED_PROGRAMS
+        .iter()
+        .map(|(_, program_id)| Pubkey::from_str(program_id).expect("tracked program IDs are valid"))
+        .collect()
+}
+
+impl BackfillConfig {
+    /// Load from the environment (see module docs); None when no range is set
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        let hours: Option<i64> = parse_env("BACKFILL_HOURS")?;
+        let start_time = match hours {
+            Some(hours) => Some(chrono::Utc::now().timestamp() - hours * 3600),
+            None => parse_env("BACKFILL_START_TIME")?,
+        };
+        let end_time = parse_env("BACKFILL_END_TIME")?;
+        let start_slot = parse_env("BACKFILL_START_SLOT")?;
+        let end_slot = parse_env("BACKFILL_END_SLOT")?;
+
+        // Without a lower bound the walk would go back to genesis
+        if start_time.is_none() && start_slot.is_none() {
+            if end_time.is_some() || end_slot.is_some() {
+                return Err(ConfigError::InvalidValue(
+                    "backfill needs BACKFILL_HOURS, BACKFILL_START_TIME or BACKFILL_START_SLOT".to_string(),
+                ));
+            }
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            rpc_url: rpc_url_from_env()?,
+            programs: tracked_programs(),
+            start_time,
+            end_time,
+            start_slot,
+            end_slot,
+            concurrency: parse_env("BACKFILL_CONCURRENCY")?.unwrap_or(8).max(1),
+        }))
+    }
+
+    /// Backfill of slots the live stream missed (see `slot_gaps`)
+    pub fn for_slots(start_slot: u64, end_slot: u64) -> Result<Self, ConfigError> {
+        Ok(Self {
+            rpc_url: rpc_url_from_env()?,
+            programs: tracked_programs(),
+            start_time: None,
+            end_time: None,
+            start_slot: Some(start_slot),
+            end_slot: Some(end_slot),
+            concurrency: parse_env("BACKFILL_CONCURRENCY")?.unwrap_or(8).max(1),
+        })
+    }
+
+    fn is_open_ended(&self) -> bool {
+        self.end_time.is_none() && self.end_slot.is_none()
+    }
+
+    /// Signature is older than the range: stop walking this program
+    fn is_before_range(&self, slot: u64, block_time: Option<i64>) -> bool {
+        self.start_slot.is_some_and(|start| slot < start)
+            || matches!((self.start_time, block_time), (Some(start), Some(time)) if time < start)
+    }
+
+    /// Signature is newer than the range: skip it and keep walking
+    fn is_after_range(&self, slot: u64, block_time: Option<i64>) -> bool {
+        self.end_slot.is_some_and(|end| slot > end)
+            || matches!((self.end_time, block_time), (Some(end), Some(time)) if time > end)
+    }
+}
+
+pub struct BackfillDatasource {
+    config: BackfillConfig,
+}
+
+impl BackfillDatasource {
+    pub fn new(config: BackfillConfig) -> Self {
+        Self { config }
+    }
+
+    /// In-range signatures of all programs not seen before, oldest first
+    ///
+    /// `until` holds the newest signature per program from the previous pass.
+    async fn collect_signatures(
+        &self,
+        client: &RpcClient,
+        until: &mut HashMap<Pubkey, Signature>,
+        seen: &mut HashSet<Signature>,
+    ) -> CarbonResult<Vec<(u64, Signature)>> {
+        let mut collected = Vec::new();
+
+        for program in &self.config.programs {
+            let mut before = None;
+            let mut newest = None;
+
+            'pages: loop {
+                let page = with_retry("getSignaturesForAddress", || {
+                    client.get_signatures_for_address_with_config(
+                        program,
+                        GetConfirmedSignaturesForAddress2Config {
+                            before,
+                            until: until.get(program).copied(),
+                            limit: Some(SIGNATURE_PAGE_LIMIT),
+                            commitment: Some(CommitmentConfig::confirmed()),
+                        },
+                    )
+                })
+                .await?;
+
+                for info in &page {
+                    let Ok(signature) = Signature::from_str(&info.signature) else {
+                        continue;
+                    };
+                    newest.get_or_insert(signature);
+                    before = Some(signature);
+
+                    if self.config.is_before_range(info.slot, info.block_time) {
+                        break 'pages;
+                    }
+                    if info.err.is_some() || self.config.is_after_range(info.slot, info.block_time) {
+                        continue;
+                    }
+                    if seen.insert(signature) {
+                        collected.push((info.slot, signature));
+                    }
+                }
+
+                if page.len() < SIGNATURE_PAGE_LIMIT {
+                    break;
+                }
+            }
+
+            if let Some(newest) = newest {
+                until.insert(*program, newest);
+            }
+        }
+
+        collected.sort_by_key(|(slot, _)| *slot);
+        Ok(collected)
+    }
+
+    /// Fetch and emit transactions in order; returns how many were sent
+    async fn emit_transactions(
+        &self,
+        client: &Arc<RpcClient>,
+        signatures: &[(u64, Signature)],
+        id: &DatasourceId,
+        sender: &Sender<(Update, DatasourceId)>,
+        cancellation_token: &CancellationToken,
+    ) -> CarbonResult<usize> {
+        let mut sent = 0;
+
+        for chunk in signatures.chunks(self.config.concurrency) {
+            if cancellation_token.is_cancelled() {
+                break;
+            }
+
+            // Fetch the chunk in parallel, emit in slot order
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|(_, signature)| {
+                    let client = Arc::clone(client);
+                    let signature = *signature;
+                    tokio::spawn(async move {
+                        let transaction = with_retry("getTransaction", || {
+                            client.get_transaction_with_config(
+                                &signature,
+                                RpcTransactionConfig {
+                                    encoding: Some(UiTransactionEncoding::Base64),
+                                    commitment: Some(CommitmentConfig::confirmed()),
+                                    max_supported_transaction_version: Some(0),
+                                },
+                            )
+                        })
+                        .await;
+                        (signature, transaction)
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                let (signature, transaction) = handle
+                    .await
+                    .map_err(|e| CarbonError::FailedToConsumeDatasource(e.to_string()))?;
+                let transaction = match transaction {
+                    Ok(transaction) => transaction,
+                    Err(e) => {
+                        log::warn!("⚠️  Backfill skipped {}: {}", signature, e);
+                        continue;
+                    }
+                };
+                let Some(update) = to_update(signature, transaction) else {
+                    continue;
+                };
+                // Blocking send: history must not be dropped under backpressure
+                if sender.send((update, id.clone())).await.is_err() {
+                    return Ok(sent);
+                }
+                sent += 1;
+            }
+        }
+
+        Ok(sent)
+    }
+}
+
+async fn with_retry<T, E, F, Fut>(what: &str, mut request: F) -> CarbonResult<T>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut backoff = Duration::from_millis(500);
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < MAX_RETRIES => {
+                log::warn!("⚠️  {} failed (attempt {}/{}): {}", what, attempt + 1, MAX_RETRIES, e);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
+            }
+            Err(e) => return Err(CarbonError::FailedToConsumeDatasource(format!("{}: {}", what, e))),
+        }
+    }
+}
+
+fn to_update(signature: Signature, fetched: EncodedConfirmedTransactionWithStatusMeta) -> Option<Update> {
+    let transaction = fetched.transaction;
+    let meta = transaction.meta?;
+    if meta.status.is_err() {
+        return None;
+    }
+    let decoded = transaction.transaction.decode()?;
+    let meta = transaction_metadata_from_original_meta(meta).ok()?;
+
+    Some(Update::Transaction(Box::new(TransactionUpdate {
+        signature,
+        transaction: decoded,
+        meta,
+        is_vote: false,
+        slot: fetched.slot,
+        block_time: fetched.block_time,
+        block_hash: None,
+    })))
+}
+
+#[async_trait]
+impl Datasource for BackfillDatasource {
+    async fn consume(
+        &self,
+        id: DatasourceId,
+        sender: Sender<(Update, DatasourceId)>,
+        cancellation_token: CancellationToken,
+        _metrics: Arc<MetricsCollection>,
+    ) -> CarbonResult<()> {
+        let client = Arc::new(RpcClient::new_with_commitment(
+            self.config.rpc_url.clone(),
+            CommitmentConfig::confirmed(),
+        ));
+
+        log::info!("⏪ Backfill starting ({})", redact_url(&self.config.rpc_url));
+        log::info!(
+            "   ├─ Time: {:?} → {:?}",
+            self.config.start_time,
+            self.config.end_time
+        );
+        log::info!(
+            "   ├─ Slots: {:?} → {:?}",
+            self.config.start_slot,
+            self.config.end_slot
+        );
+        log::info!("   └─ Programs: {}", self.config.programs.len());
+
+        let passes = if self.config.is_open_ended() { 1 + MAX_CATCH_UP_PASSES } else { 1 };
+        let mut until = HashMap::new();
+        let mut seen = HashSet::new();
+        let mut total = 0;
+
+        for pass in 0..passes {
+            if cancellation_token.is_cancelled() {
+                break;
+            }
+            let signatures = self.collect_signatures(&client, &mut until, &mut seen).await?;
+            if signatures.is_empty() {
+                break;
+            }
+            log::info!("⏪ Backfill pass {}: {} transactions", pass + 1, signatures.len());
+            total += self
+                .emit_transactions(&client, &signatures, &id, &sender, &cancellation_token)
+                .await?;
+        }
+
+        log::info!("✅ Backfill complete: {} transactions replayed", total);
+        Ok(())
+    }
+
+    fn update_types(&self) -> Vec<UpdateType> {
+        vec![UpdateType::Transaction]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(start_time: Option<i64>, end_time: Option<i64>, start_slot: Option<u64>) -> BackfillConfig {
+        BackfillConfig {
+            rpc_url: String::new(),
+            programs: Vec::new(),
+            start_time,
+            end_time,
+            start_slot,
+            end_slot: None,
+            concurrency: 1,
+        }
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let by_time = config(Some(1_000), Some(2_000), None);
+        assert!(by_time.is_before_range(50, Some(999)));
+        assert!(!by_time.is_before_range(50, Some(1_000)));
+        assert!(by_time.is_after_range(50, Some(2_001)));
+        assert!(!by_time.is_after_range(50, None));
+        assert!(!by_time.is_open_ended());
+
+        let by_slot = config(None, None, Some(100));
+        assert!(by_slot.is_before_range(99, Some(5_000)));
+        assert!(!by_slot.is_before_range(100, None));
+        assert!(by_slot.is_open_ended());
+    }
+}
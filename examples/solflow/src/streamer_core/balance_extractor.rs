@@ -1,5 +1,104 @@
+//! Balance deltas and quote currencies
+//!
+//! Trades are read from pre/post balances. The quote side is usually native
+//! SOL, but wallets also pay from WSOL, USDC and USDT token accounts; those
+//! changes are picked up per owner (`extract_quote_changes`) and valued in
+//! SOL, stablecoins at the cached SOL/USD price (`set_sol_usd_price`, kept
+//! fresh by `dexscreener::run_sol_usd_refresh`).
+//!
+//! Token deltas of fee-on-transfer (Token-2022) mints carry the fee withheld,
+//! so callers can take the gross or net side (see `token_extensions`).
+//!
+//! Environment variables:
+//! - `SOL_USD_REFRESH_SECS`: How often the SOL/USD price is fetched (default: 60, 0 = never)
+//! - `SOL_USD_MAX_AGE_SECS`: Oldest price used to value stablecoin trades; older
+//!   means those trades are dropped (default: 600)
+
+use crate::streamer_core::token_extensions;
 use solana_pubkey::Pubkey;
 use solana_transaction_status::TransactionStatusMeta;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+pub const USDC_MINT: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+pub const USDT_MINT: &str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
+
+const DEFAULT_SOL_USD_REFRESH_SECS: u64 = 60;
+const DEFAULT_SOL_USD_MAX_AGE_SECS: i64 = 600;
+
+/// Latest SOL/USD price and when it was fetched
+static SOL_USD_PRICE: Mutex<Option<(f64, i64)>> = Mutex::new(None);
+
+/// Record the SOL/USD price fetched at `fetched_at`
+pub fn set_sol_usd_price(price: f64, fetched_at: i64) {
+    if price.is_finite() && price > 0.0 {
+        *SOL_USD_PRICE.lock().unwrap() = Some((price, fetched_at));
+    }
+}
+
+/// Cached SOL/USD price, unless older than `SOL_USD_MAX_AGE_SECS`
+pub fn sol_usd_price(now: i64) -> Option<f64> {
+    static MAX_AGE: OnceLock<i64> = OnceLock::new();
+    let max_age = *MAX_AGE.get_or_init(|| {
+        std::env::var("SOL_USD_MAX_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_SOL_USD_MAX_AGE_SECS)
+    });
+    SOL_USD_PRICE
+        .lock()
+        .unwrap()
+        .filter(|(_, fetched_at)| now - fetched_at <= max_age)
+        .map(|(price, _)| price)
+}
+
+/// SOL/USD refresh interval (`SOL_USD_REFRESH_SECS`), None when disabled
+pub fn sol_usd_refresh_interval() -> Option<Duration> {
+    let secs = std::env::var("SOL_USD_REFRESH_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_SOL_USD_REFRESH_SECS);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Currency the other side of a token trade is paid in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteCurrency {
+    /// Native SOL or WSOL
+    Sol,
+    Usdc,
+    Usdt,
+}
+
+impl QuoteCurrency {
+    /// Quote currency of a token mint (WSOL counts as SOL)
+    pub fn from_mint(mint: &str) -> Option<Self> {
+        match mint {
+            WSOL_MINT => Some(QuoteCurrency::Sol),
+            USDC_MINT => Some(QuoteCurrency::Usdc),
+            USDT_MINT => Some(QuoteCurrency::Usdt),
+            _ => None,
+        }
+    }
+
+    /// SOL-equivalent of `amount`
+    ///
+    /// None for stablecoins while no fresh SOL/USD price is cached.
+    pub fn to_sol(self, amount: f64, now: i64) -> Option<f64> {
+        match self {
+            QuoteCurrency::Sol => Some(amount),
+            QuoteCurrency::Usdc | QuoteCurrency::Usdt => sol_usd_price(now).map(|price| amount / price),
+        }
+    }
+}
+
+/// Net change of one quote currency across a wallet's token accounts
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteChange {
+    pub currency: QuoteCurrency,
+    pub ui_change: f64,
+}
 
 #[derive(Debug, Clone)]
 pub struct BalanceDelta {
@@ -9,6 +108,8 @@ pub struct BalanceDelta {
     pub ui_change: f64,
     pub decimals: u8,
     pub is_sol: bool,
+    /// Token-2022 transfer fee withheld on this change, UI units (0 for fee-free mints)
+    pub transfer_fee: f64,
 }
 
 impl BalanceDelta {
@@ -23,6 +124,34 @@ impl BalanceDelta {
     pub fn abs_ui_change(&self) -> f64 {
         self.ui_change.abs()
     }
+
+    /// Amount sent, before the transfer fee (an inflow received this minus the fee)
+    pub fn gross_abs_ui_change(&self) -> f64 {
+        if self.is_inflow() {
+            self.abs_ui_change() + self.transfer_fee
+        } else {
+            self.abs_ui_change()
+        }
+    }
+
+    /// Amount delivered, after the transfer fee (an outflow sent this plus the fee)
+    pub fn net_abs_ui_change(&self) -> f64 {
+        if self.is_outflow() {
+            (self.abs_ui_change() - self.transfer_fee).max(0.0)
+        } else {
+            self.abs_ui_change()
+        }
+    }
+}
+
+/// Transfer fee (UI units) withheld on a token balance change of `raw_change`
+fn transfer_fee_ui(mint: &str, raw_change: i128, decimals: u8) -> f64 {
+    let Some(fee) = token_extensions::shared().transfer_fee(mint) else {
+        return 0.0;
+    };
+    let amount = raw_change.unsigned_abs().min(u64::MAX as u128) as u64;
+    let raw_fee = if raw_change > 0 { fee.fee_for_net(amount) } else { fee.fee(amount) };
+    raw_fee as f64 / 10f64.powi(decimals as i32)
 }
 
 pub fn build_full_account_keys(
@@ -69,12 +198,15 @@ pub fn extract_sol_changes(
             ui_change,
             decimals: 9,
             is_sol: true,
+            transfer_fee: 0.0,
         });
     }
 
     deltas
 }
 
+/// Token balance changes, with each Token-2022 transfer fee filled in from
+/// the `token_extensions` cache
 pub fn extract_token_changes(
     meta: &TransactionStatusMeta,
     _account_keys: &[Pubkey],
@@ -124,6 +256,7 @@ pub fn extract_token_changes(
             ui_change,
             decimals,
             is_sol: false,
+            transfer_fee: transfer_fee_ui(&pre.mint, raw_change, decimals),
         });
     }
 
@@ -147,6 +280,7 @@ pub fn extract_token_changes(
                     ui_change: post_ui,
                     decimals,
                     is_sol: false,
+                    transfer_fee: transfer_fee_ui(&post.mint, post_raw as i128, decimals),
                 });
             }
         }
@@ -154,3 +288,80 @@ pub fn extract_token_changes(
 
     deltas
 }
+
+/// Net WSOL/USDC/USDT change of the token accounts `owner` holds
+///
+/// Accounts opened and closed within the transaction (wrap/unwrap around a
+/// native SOL swap) appear in neither balance list, so they net to nothing.
+pub fn extract_quote_changes(meta: &TransactionStatusMeta, owner: &Pubkey) -> Vec<QuoteChange> {
+    let owner = owner.to_string();
+    let mut changes: Vec<QuoteChange> = Vec::new();
+    let pre = meta.pre_token_balances.iter().flatten().map(|b| (b, -1.0));
+    let post = meta.post_token_balances.iter().flatten().map(|b| (b, 1.0));
+
+    for (balance, sign) in pre.chain(post).filter(|(b, _)| b.owner == owner) {
+        let Some(currency) = QuoteCurrency::from_mint(&balance.mint) else {
+            continue;
+        };
+        let amount = sign * balance.ui_token_amount.ui_amount.unwrap_or(0.0);
+        match changes.iter_mut().find(|c| c.currency == currency) {
+            Some(change) => change.ui_change += amount,
+            None => changes.push(QuoteChange { currency, ui_change: amount }),
+        }
+    }
+
+    changes.retain(|c| c.ui_change != 0.0);
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_account_decoder_client_types::token::UiTokenAmount;
+    use solana_transaction_status::TransactionTokenBalance;
+
+    fn balance(account_index: u8, mint: &str, owner: &str, ui_amount: f64, decimals: u8) -> TransactionTokenBalance {
+        TransactionTokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: Some(ui_amount),
+                decimals,
+                amount: ((ui_amount * 10f64.powi(decimals as i32)) as u64).to_string(),
+                ui_amount_string: ui_amount.to_string(),
+            },
+            owner: owner.to_string(),
+            program_id: "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_usdc_quote_change_valued_in_sol() {
+        let user = Pubkey::new_from_array([1; 32]);
+        let pool = Pubkey::new_from_array([2; 32]).to_string();
+        let meta = TransactionStatusMeta {
+            pre_token_balances: Some(vec![
+                balance(1, USDC_MINT, &user.to_string(), 500.0, 6),
+                balance(2, USDC_MINT, &pool, 10_000.0, 6),
+            ]),
+            post_token_balances: Some(vec![
+                balance(1, USDC_MINT, &user.to_string(), 200.0, 6),
+                balance(2, USDC_MINT, &pool, 10_300.0, 6),
+                balance(3, "TokenMint", &user.to_string(), 1_000.0, 6),
+            ]),
+            ..Default::default()
+        };
+
+        // Only the user's accounts count: 300 USDC out
+        let changes = extract_quote_changes(&meta, &user);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].currency, QuoteCurrency::Usdc);
+        assert!((changes[0].ui_change + 300.0).abs() < 1e-9);
+
+        // Stablecoins need a fresh SOL/USD price; SOL never does
+        assert_eq!(QuoteCurrency::Sol.to_sol(2.0, 1_000), Some(2.0));
+        set_sol_usd_price(150.0, 1_000);
+        assert_eq!(QuoteCurrency::Usdc.to_sol(300.0, 1_060), Some(2.0));
+        assert_eq!(QuoteCurrency::Usdt.to_sol(300.0, 1_000 + 601), None);
+    }
+}
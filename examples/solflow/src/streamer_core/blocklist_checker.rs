@@ -23,6 +23,7 @@
 //! - Updates to mint_blocklist are reflected immediately
 //! - No restart required for blocklist changes
 
+use crate::error::{codes, SolflowError};
 use rusqlite::{Connection, OptionalExtension};
 use std::sync::{Arc, Mutex};
 
@@ -42,7 +43,7 @@ impl BlocklistChecker {
     /// - `db_path`: Path to SQLite database containing mint_blocklist table
     ///
     /// Returns: BlocklistChecker instance or error if database cannot be opened
-    pub fn new(db_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn new(db_path: &str) -> Result<Self, SolflowError> {
         let conn = Connection::open(db_path)?;
         
         // Verify mint_blocklist table exists
@@ -53,7 +54,10 @@ impl BlocklistChecker {
         ).optional()?.unwrap_or(false);
         
         if !table_exists {
-            return Err("mint_blocklist table not found in database".into());
+            return Err(SolflowError::db(
+                codes::DB_SCHEMA,
+                "mint_blocklist table not found in database",
+            ));
         }
         
         Ok(Self {
@@ -73,11 +77,12 @@ impl BlocklistChecker {
     /// - `Ok(true)` - Mint is blocked (discard trade)
     /// - `Ok(false)` - Mint is not blocked (process trade)
     /// - `Err(...)` - Database error
-    pub fn is_blocked(&self, mint: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    pub fn is_blocked(&self, mint: &str) -> Result<bool, SolflowError> {
         let conn = self.conn.lock().unwrap();
         let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() as i64;
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default();
 
         let mut stmt = conn.prepare_cached(
             "SELECT mint FROM mint_blocklist 
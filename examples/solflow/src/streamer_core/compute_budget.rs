@@ -0,0 +1,153 @@
+//! Priority fees and Jito tips
+//!
+//! What a transaction paid on top of its base fee to land quickly: the
+//! priority fee set with ComputeBudget instructions (compute unit price ×
+//! compute unit limit) and any tip paid to one of Jito's tip accounts. Bots
+//! pay one or the other on nearly every trade, wallets trading by hand rarely
+//! do, which makes both useful bot-detection features (see `pipeline::state`).
+//!
+//! Tips are read from the tip accounts' balance changes rather than decoded
+//! transfers, so tips paid through a CPI count as well.
+
+use carbon_core::transaction::TransactionMetadata;
+use solana_pubkey::Pubkey;
+use solana_transaction_status::TransactionStatusMeta;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+/// ComputeBudget instruction tags
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Limit applied per instruction when the transaction sets none
+const DEFAULT_UNITS_PER_INSTRUCTION: u64 = 200_000;
+const MAX_COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Jito's tip payment accounts
+const JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+fn compute_budget_program() -> &'static Pubkey {
+    static PROGRAM: OnceLock<Pubkey> = OnceLock::new();
+    PROGRAM.get_or_init(|| Pubkey::from_str(COMPUTE_BUDGET_PROGRAM_ID).unwrap())
+}
+
+fn jito_tip_accounts() -> &'static [Pubkey] {
+    static ACCOUNTS: OnceLock<Vec<Pubkey>> = OnceLock::new();
+    ACCOUNTS.get_or_init(|| {
+        JITO_TIP_ACCOUNTS
+            .iter()
+            .map(|address| Pubkey::from_str(address).unwrap())
+            .collect()
+    })
+}
+
+/// Fees a transaction paid to land, beyond the base fee
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LandingFees {
+    pub priority_fee_lamports: u64,
+    pub jito_tip_lamports: u64,
+}
+
+/// Priority fee and Jito tip paid by a transaction
+pub fn landing_fees(metadata: &TransactionMetadata, account_keys: &[Pubkey]) -> LandingFees {
+    let instructions = metadata
+        .message
+        .instructions()
+        .iter()
+        .filter_map(|ix| Some((account_keys.get(ix.program_id_index as usize)?, ix.data.as_slice())));
+    LandingFees {
+        priority_fee_lamports: priority_fee(instructions),
+        jito_tip_lamports: jito_tip(&metadata.meta, account_keys),
+    }
+}
+
+/// Priority fee from top-level (program, data) instructions
+///
+/// Without `SetComputeUnitLimit` the runtime's default limit applies: 200k
+/// units per non-ComputeBudget instruction, capped at 1.4M.
+fn priority_fee<'a>(instructions: impl Iterator<Item = (&'a Pubkey, &'a [u8])>) -> u64 {
+    let mut unit_limit = None;
+    let mut unit_price: u64 = 0;
+    let mut other_instructions: u64 = 0;
+
+    for (program, data) in instructions {
+        if program != compute_budget_program() {
+            other_instructions += 1;
+            continue;
+        }
+        match data.split_first() {
+            Some((&SET_COMPUTE_UNIT_LIMIT, rest)) => {
+                unit_limit = rest.get(..4).map(|b| u32::from_le_bytes(b.try_into().unwrap()) as u64);
+            }
+            Some((&SET_COMPUTE_UNIT_PRICE, rest)) => {
+                unit_price = rest.get(..8).map_or(0, |b| u64::from_le_bytes(b.try_into().unwrap()));
+            }
+            _ => {}
+        }
+    }
+
+    let unit_limit = unit_limit
+        .unwrap_or(other_instructions * DEFAULT_UNITS_PER_INSTRUCTION)
+        .min(MAX_COMPUTE_UNIT_LIMIT);
+    (unit_price as u128 * unit_limit as u128).div_ceil(MICRO_LAMPORTS_PER_LAMPORT) as u64
+}
+
+/// Lamports the Jito tip accounts received
+fn jito_tip(meta: &TransactionStatusMeta, account_keys: &[Pubkey]) -> u64 {
+    let tip_accounts = jito_tip_accounts();
+    account_keys
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| tip_accounts.contains(key))
+        .filter_map(|(index, _)| {
+            let pre = *meta.pre_balances.get(index)?;
+            let post = *meta.post_balances.get(index)?;
+            Some(post.saturating_sub(pre))
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_fee_and_jito_tip() {
+        let budget = *compute_budget_program();
+        let swap_program = Pubkey::new_from_array([7; 32]);
+        let limit = [&[SET_COMPUTE_UNIT_LIMIT][..], &150_000u32.to_le_bytes()].concat();
+        let price = [&[SET_COMPUTE_UNIT_PRICE][..], &2_000_000u64.to_le_bytes()].concat();
+
+        // 150k units at 2 lamports each
+        let instructions = [(&budget, &limit[..]), (&budget, &price[..]), (&swap_program, &[9u8][..])];
+        assert_eq!(priority_fee(instructions.into_iter()), 300_000);
+
+        // No limit set: 200k units for each of the two other instructions
+        let instructions = [(&budget, &price[..]), (&swap_program, &[][..]), (&swap_program, &[][..])];
+        assert_eq!(priority_fee(instructions.into_iter()), 800_000);
+
+        // No price, no priority fee
+        assert_eq!(priority_fee([(&swap_program, &[][..])].into_iter()), 0);
+
+        // Tip account credited 1M lamports; the fee payer's debit doesn't count
+        let account_keys = vec![Pubkey::new_from_array([1; 32]), jito_tip_accounts()[3]];
+        let meta = TransactionStatusMeta {
+            pre_balances: vec![10_000_000, 5_000],
+            post_balances: vec![8_995_000, 1_005_000],
+            ..Default::default()
+        };
+        assert_eq!(jito_tip(&meta, &account_keys), 1_000_000);
+    }
+}
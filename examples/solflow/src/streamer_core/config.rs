@@ -1,3 +1,4 @@
+use super::secrets::{redact_url, secret_from_env, REDACTED};
 use std::env;
 use yellowstone_grpc_proto::geyser::CommitmentLevel;
 use tokio::sync::mpsc;
@@ -6,6 +7,48 @@ use tokio::sync::mpsc;
 pub enum BackendType {
     Jsonl,
     Sqlite,
+    /// Central Postgres database (`output_path` is the connection string)
+    Postgres,
+    /// Kafka topic (`output_path` is the broker list; needs the `kafka` feature)
+    Kafka,
+}
+
+/// Source of transaction updates for the unified streamer
+#[derive(Debug, Clone, PartialEq)]
+pub enum DatasourceKind {
+    /// Yellowstone gRPC (full fidelity)
+    Grpc,
+    /// RPC websocket `blockSubscribe` (reduced fidelity fallback)
+    RpcBlockSubscribe,
+}
+
+impl DatasourceKind {
+    /// True when the datasource delivers lower-fidelity data than Yellowstone
+    pub fn is_reduced_fidelity(&self) -> bool {
+        matches!(self, DatasourceKind::RpcBlockSubscribe)
+    }
+
+    /// Known limitations of this datasource (empty for full fidelity)
+    pub fn fidelity_notes(&self) -> &'static [&'static str] {
+        match self {
+            DatasourceKind::Grpc => &[],
+            DatasourceKind::RpcBlockSubscribe => &[
+                "whole-block delivery: trades arrive after the block is confirmed, not per transaction",
+                "processed commitment unavailable (confirmed at best)",
+                "one subscription per program: multi-program transactions are deduplicated by signature",
+                "requires an RPC provider with blockSubscribe enabled",
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for DatasourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DatasourceKind::Grpc => write!(f, "grpc"),
+            DatasourceKind::RpcBlockSubscribe => write!(f, "rpc_block_subscribe"),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -19,10 +62,32 @@ pub struct StreamerConfig {
     pub pipeline_tx: Option<mpsc::Sender<crate::pipeline::types::TradeEvent>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct RuntimeConfig {
+    /// Transaction source (DATASOURCE=grpc|rpc)
+    pub datasource: DatasourceKind,
+    /// Yellowstone endpoint (empty when using the RPC fallback)
+    ///
+    /// The first of `geyser_urls`; streamers with failover pick one per
+    /// connection (see `endpoints`).
     pub geyser_url: String,
+    /// Every Yellowstone endpoint, primary first (GEYSER_URLS, else GEYSER_URL)
+    pub geyser_urls: Vec<String>,
+    /// Second provider consumed alongside the first (GEYSER_SECONDARY_URL, see `redundancy`)
+    pub secondary_geyser_url: Option<String>,
+    /// Token for the second provider (GEYSER_SECONDARY_X_TOKEN)
+    pub secondary_x_token: Option<String>,
+    /// RPC websocket endpoint for the `blockSubscribe` fallback
+    pub rpc_ws_url: Option<String>,
     pub x_token: Option<String>,
+    /// Extra accounts, any of which must appear (GRPC_ACCOUNT_INCLUDE)
+    pub account_include: Vec<String>,
+    /// Accounts that must not appear (GRPC_ACCOUNT_EXCLUDE)
+    pub account_exclude: Vec<String>,
+    /// Wallets subscribed to in addition to the programs (FOCUS_WALLETS)
+    pub focus_wallets: Vec<String>,
+    /// Subscribe to the focus wallets only, not all venue traffic (FOCUS_WALLETS_ONLY)
+    pub focus_wallets_only: bool,
     pub commitment_level: CommitmentLevel,
     pub rust_log: String,
     pub output_max_size_mb: u64,
@@ -30,6 +95,33 @@ pub struct RuntimeConfig {
     pub enable_jsonl: bool,
 }
 
+// Manual Debug so endpoints and X_TOKEN never reach logs in full
+impl std::fmt::Debug for RuntimeConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RuntimeConfig")
+            .field("datasource", &self.datasource)
+            .field("geyser_url", &redact_url(&self.geyser_url))
+            .field(
+                "geyser_urls",
+                &self.geyser_urls.iter().map(|url| redact_url(url)).collect::<Vec<_>>(),
+            )
+            .field("secondary_geyser_url", &self.secondary_geyser_url.as_deref().map(redact_url))
+            .field("secondary_x_token", &self.secondary_x_token.as_ref().map(|_| REDACTED))
+            .field("rpc_ws_url", &self.rpc_ws_url.as_deref().map(redact_url))
+            .field("x_token", &self.x_token.as_ref().map(|_| REDACTED))
+            .field("account_include", &self.account_include)
+            .field("account_exclude", &self.account_exclude)
+            .field("focus_wallets", &self.focus_wallets)
+            .field("focus_wallets_only", &self.focus_wallets_only)
+            .field("commitment_level", &self.commitment_level)
+            .field("rust_log", &self.rust_log)
+            .field("output_max_size_mb", &self.output_max_size_mb)
+            .field("output_max_rotations", &self.output_max_rotations)
+            .field("enable_jsonl", &self.enable_jsonl)
+            .finish()
+    }
+}
+
 #[derive(Debug)]
 pub enum ConfigError {
     MissingVariable(String),
@@ -49,17 +141,88 @@ impl std::error::Error for ConfigError {}
 
 impl RuntimeConfig {
     pub fn from_env() -> Result<Self, ConfigError> {
-        let geyser_url = env::var("GEYSER_URL")
-            .map_err(|_| ConfigError::MissingVariable("GEYSER_URL".to_string()))?;
+        let rpc_ws_url = env::var("RPC_WS_URL").ok();
 
-        if !geyser_url.starts_with("http://") && !geyser_url.starts_with("https://") {
+        // Explicit DATASOURCE wins; otherwise fall back to RPC only when no
+        // Yellowstone endpoint is configured but an RPC websocket is
+        let datasource = match env::var("DATASOURCE").ok().as_deref() {
+            Some("grpc") => DatasourceKind::Grpc,
+            Some("rpc") => DatasourceKind::RpcBlockSubscribe,
+            Some(other) => {
+                return Err(ConfigError::InvalidValue(format!(
+                    "DATASOURCE must be 'grpc' or 'rpc', got '{}'",
+                    other
+                )));
+            }
+            None if env::var("GEYSER_URL").is_err()
+                && env::var("GEYSER_URLS").is_err()
+                && rpc_ws_url.is_some() =>
+            {
+                DatasourceKind::RpcBlockSubscribe
+            }
+            None => DatasourceKind::Grpc,
+        };
+
+        let geyser_urls = match datasource {
+            DatasourceKind::Grpc => {
+                let geyser_urls = match env::var("GEYSER_URLS") {
+                    Ok(list) => parse_geyser_urls(&list)?,
+                    Err(_) => parse_geyser_urls(
+                        &env::var("GEYSER_URL")
+                            .map_err(|_| ConfigError::MissingVariable("GEYSER_URL".to_string()))?,
+                    )?,
+                };
+                if geyser_urls.is_empty() {
+                    return Err(ConfigError::MissingVariable("GEYSER_URL".to_string()));
+                }
+                geyser_urls
+            }
+            DatasourceKind::RpcBlockSubscribe => {
+                let ws_url = rpc_ws_url
+                    .as_deref()
+                    .ok_or_else(|| ConfigError::MissingVariable("RPC_WS_URL".to_string()))?;
+
+                if !ws_url.starts_with("ws://") && !ws_url.starts_with("wss://") {
+                    return Err(ConfigError::InvalidValue(
+                        "RPC_WS_URL must start with ws:// or wss://".to_string(),
+                    ));
+                }
+                Vec::new()
+            }
+        };
+        let geyser_url = geyser_urls.first().cloned().unwrap_or_default();
+
+        let x_token = secret_from_env("X_TOKEN").map_err(ConfigError::InvalidValue)?;
+
+        let secondary_geyser_url = match (&datasource, env::var("GEYSER_SECONDARY_URL")) {
+            (DatasourceKind::Grpc, Ok(url)) => parse_geyser_urls(&url)?.into_iter().next(),
+            _ => None,
+        };
+        let secondary_x_token =
+            secret_from_env("GEYSER_SECONDARY_X_TOKEN").map_err(ConfigError::InvalidValue)?;
+
+        let account_include = parse_account_list(
+            "GRPC_ACCOUNT_INCLUDE",
+            &env::var("GRPC_ACCOUNT_INCLUDE").unwrap_or_default(),
+        )?;
+        let account_exclude = parse_account_list(
+            "GRPC_ACCOUNT_EXCLUDE",
+            &env::var("GRPC_ACCOUNT_EXCLUDE").unwrap_or_default(),
+        )?;
+
+        let focus_wallets = parse_account_list(
+            "FOCUS_WALLETS",
+            &env::var("FOCUS_WALLETS").unwrap_or_default(),
+        )?;
+        let focus_wallets_only = env::var("FOCUS_WALLETS_ONLY")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+        if focus_wallets_only && focus_wallets.is_empty() {
             return Err(ConfigError::InvalidValue(
-                "GEYSER_URL must start with http:// or https://".to_string(),
+                "FOCUS_WALLETS_ONLY=true requires FOCUS_WALLETS".to_string(),
             ));
         }
 
-        let x_token = env::var("X_TOKEN").ok();
-
         let commitment_str = env::var("COMMITMENT_LEVEL").unwrap_or_else(|_| "Confirmed".to_string());
         let commitment_level = match commitment_str.to_lowercase().as_str() {
             "finalized" => CommitmentLevel::Finalized,
@@ -93,8 +256,17 @@ impl RuntimeConfig {
             .unwrap_or(false);
 
         Ok(Self {
+            datasource,
             geyser_url,
+            geyser_urls,
+            secondary_geyser_url,
+            secondary_x_token,
+            rpc_ws_url,
             x_token,
+            account_include,
+            account_exclude,
+            focus_wallets,
+            focus_wallets_only,
             commitment_level,
             rust_log,
             output_max_size_mb,
@@ -102,6 +274,57 @@ impl RuntimeConfig {
             enable_jsonl,
         })
     }
+
+    /// This config pointed at the secondary provider, when one is configured
+    pub fn secondary(&self) -> Option<RuntimeConfig> {
+        let url = self.secondary_geyser_url.clone()?;
+        let mut secondary = self.clone();
+        secondary.geyser_url = url.clone();
+        secondary.geyser_urls = vec![url];
+        secondary.secondary_geyser_url = None;
+        secondary.x_token = self.secondary_x_token.clone();
+        Some(secondary)
+    }
+}
+
+/// Parse a comma-separated list of Yellowstone endpoints
+///
+/// Entries are trimmed and empty entries skipped; each must be http(s).
+fn parse_geyser_urls(value: &str) -> Result<Vec<String>, ConfigError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            if !entry.starts_with("http://") && !entry.starts_with("https://") {
+                return Err(ConfigError::InvalidValue(
+                    "GEYSER_URL must start with http:// or https://".to_string(),
+                ));
+            }
+            Ok(entry.to_string())
+        })
+        .collect()
+}
+
+/// Parse a comma-separated list of account addresses
+///
+/// Entries are trimmed and empty entries skipped; each must look like a
+/// base58 pubkey (32-44 characters).
+fn parse_account_list(var: &str, value: &str) -> Result<Vec<String>, ConfigError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            if entry.len() < 32 || entry.len() > 44 {
+                return Err(ConfigError::InvalidValue(format!(
+                    "{} entry '{}' is not a base58 pubkey",
+                    var, entry
+                )));
+            }
+            Ok(entry.to_string())
+        })
+        .collect()
 }
 
 impl StreamerConfig {
@@ -113,6 +336,8 @@ impl StreamerConfig {
                 match args.get(idx + 1).map(|s| s.as_str()) {
                     Some("sqlite") => return BackendType::Sqlite,
                     Some("jsonl") => return BackendType::Jsonl,
+                    Some("postgres") => return BackendType::Postgres,
+                    Some("kafka") => return BackendType::Kafka,
                     _ => {}
                 }
             }
@@ -137,3 +362,58 @@ impl StreamerConfig {
         Ok(())
     }
 }
+
+#[cfg(test)]
+impl RuntimeConfig {
+    /// gRPC at https://a.example, confirmed, no account filters
+    pub(crate) fn for_tests() -> Self {
+        Self {
+            datasource: DatasourceKind::Grpc,
+            geyser_url: "https://a.example".to_string(),
+            geyser_urls: vec!["https://a.example".to_string()],
+            secondary_geyser_url: None,
+            secondary_x_token: None,
+            rpc_ws_url: None,
+            x_token: None,
+            account_include: Vec::new(),
+            account_exclude: Vec::new(),
+            focus_wallets: Vec::new(),
+            focus_wallets_only: false,
+            commitment_level: CommitmentLevel::Confirmed,
+            rust_log: "info".to_string(),
+            output_max_size_mb: 100,
+            output_max_rotations: 10,
+            enable_jsonl: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_account_list() {
+        let accounts = parse_account_list(
+            "GRPC_ACCOUNT_INCLUDE",
+            " pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA, ,MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG",
+        )
+        .unwrap();
+        assert_eq!(
+            accounts,
+            vec![
+                "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA".to_string(),
+                "MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG".to_string(),
+            ]
+        );
+
+        assert!(parse_account_list("GRPC_ACCOUNT_EXCLUDE", "").unwrap().is_empty());
+        assert!(parse_account_list("GRPC_ACCOUNT_EXCLUDE", "not_a_pubkey").is_err());
+
+        assert_eq!(
+            parse_geyser_urls("https://a.example:443, http://b.example:10000,").unwrap(),
+            vec!["https://a.example:443".to_string(), "http://b.example:10000".to_string()]
+        );
+        assert!(parse_geyser_urls("https://a.example, b.example").is_err());
+    }
+}
@@ -0,0 +1,333 @@
+//! Jupiter DCA order account resolution
+//!
+//! A DCA fill only shows one cycle's swap. The DCA order account referenced by
+//! the fill holds the full order (deposit, amount used, per-cycle size), which
+//! tells a 0.1-SOL-per-cycle dust order apart from a 50-SOL program.
+//!
+//! The order account is located in the fill instruction, fetched via
+//! `getAccountInfo` on a Solana RPC endpoint and decoded with the Jupiter DCA
+//! decoder. Lookups run in background tasks so the trade path never waits on
+//! RPC: a fill whose order isn't cached yet goes out without order context,
+//! and later fills of the same order pick up the cached result. Results are
+//! cached per order for a short TTL so repeated fills don't hit RPC every cycle.
+//!
+//! Environment variables:
+//! - `SOLANA_RPC_URL`: RPC endpoint used to fetch order accounts (resolution disabled if unset)
+//! - `DCA_ORDER_CACHE_TTL_SECS`: How long a decoded order is reused (default: 30)
+
+use crate::error::{codes, SolflowError};
+use crate::pipeline::types::DcaOrderInfo;
+use base64::Engine;
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_core::transaction::TransactionMetadata;
+use carbon_jupiter_dca_decoder::accounts::dca::Dca;
+use solana_pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Jupiter DCA program ID
+const JUPITER_DCA_PROGRAM_ID: &str = "DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M";
+
+/// Wrapped SOL mint (DCA orders funded with SOL use WSOL as input mint)
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Discriminators of DCA fill instructions (initiate/fulfill, flash and DLMM)
+///
+/// All four place the DCA order account at index 1.
+const FILL_DISCRIMINATORS: [[u8; 8]; 4] = [
+    [0x8f, 0xcd, 0x03, 0xbf, 0xa2, 0xd7, 0xf5, 0x31], // initiate_flash_fill
+    [0x73, 0x40, 0xe2, 0x4e, 0x21, 0xd3, 0x69, 0xa2], // fulfill_flash_fill
+    [0x9b, 0xc1, 0x50, 0x79, 0x5b, 0x93, 0xfe, 0xbb], // initiate_dlmm_fill
+    [0x01, 0xe6, 0x76, 0xfb, 0x2d, 0xb1, 0x65, 0xbb], // fulfill_dlmm_fill
+];
+
+/// Index of the DCA order account in fill instructions
+const DCA_ACCOUNT_INDEX: usize = 1;
+
+/// Find the DCA order account referenced by a fill instruction
+///
+/// Scans outer instructions first, then inner (CPI) instructions.
+pub fn find_order_account(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+) -> Option<Pubkey> {
+    let program_id = Pubkey::from_str(JUPITER_DCA_PROGRAM_ID).ok()?;
+
+    let order_account = |program_id_index: u8, accounts: &[u8], data: &[u8]| -> Option<Pubkey> {
+        if account_keys.get(program_id_index as usize) != Some(&program_id) {
+            return None;
+        }
+        let discriminator = data.get(..8)?;
+        if !FILL_DISCRIMINATORS.iter().any(|d| d == discriminator) {
+            return None;
+        }
+        let index = *accounts.get(DCA_ACCOUNT_INDEX)? as usize;
+        account_keys.get(index).copied()
+    };
+
+    for ix in metadata.message.instructions() {
+        if let Some(order) = order_account(ix.program_id_index, &ix.accounts, &ix.data) {
+            return Some(order);
+        }
+    }
+
+    if let Some(inner_groups) = &metadata.meta.inner_instructions {
+        for inner_group in inner_groups {
+            for inner in &inner_group.instructions {
+                let ix = &inner.instruction;
+                if let Some(order) = order_account(ix.program_id_index, &ix.accounts, &ix.data) {
+                    return Some(order);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Build order context from a decoded DCA account
+///
+/// Returns None for orders not funded with SOL, since "committed SOL" is
+/// meaningless for e.g. USDC-funded orders.
+pub fn order_info(order_account: &Pubkey, dca: &Dca) -> Option<DcaOrderInfo> {
+    if dca.input_mint.to_string() != WSOL_MINT {
+        return None;
+    }
+
+    let remaining_lamports = dca
+        .in_deposited
+        .saturating_sub(dca.in_withdrawn)
+        .saturating_sub(dca.in_used);
+
+    let remaining_cycles = if dca.in_amount_per_cycle > 0 {
+        remaining_lamports.div_ceil(dca.in_amount_per_cycle)
+    } else {
+        0
+    };
+
+    Some(DcaOrderInfo {
+        order_account: order_account.to_string(),
+        committed_sol_remaining: remaining_lamports as f64 / LAMPORTS_PER_SOL,
+        sol_per_cycle: dca.in_amount_per_cycle as f64 / LAMPORTS_PER_SOL,
+        remaining_cycles,
+    })
+}
+
+/// Resolves DCA fills to decoded order context via RPC, with a TTL cache
+#[derive(Clone)]
+pub struct DcaOrderResolver {
+    rpc_url: String,
+    client: reqwest::Client,
+    ttl: Duration,
+    /// Cached lookups, including negative results (closed or non-SOL orders)
+    cache: Arc<Mutex<HashMap<Pubkey, (Instant, Option<DcaOrderInfo>)>>>,
+    /// Orders with a lookup currently running
+    in_flight: Arc<Mutex<HashSet<Pubkey>>>,
+}
+
+impl DcaOrderResolver {
+    /// Create a resolver from `SOLANA_RPC_URL` / `DCA_ORDER_CACHE_TTL_SECS`
+    ///
+    /// Returns None when no RPC endpoint is configured.
+    pub fn from_env() -> Option<Self> {
+        let rpc_url = std::env::var("SOLANA_RPC_URL").ok()?;
+
+        let ttl_secs = std::env::var("DCA_ORDER_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(30);
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .ok()?;
+
+        Some(Self {
+            rpc_url,
+            client,
+            ttl: Duration::from_secs(ttl_secs),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
+        })
+    }
+
+    /// Resolve the DCA order behind a fill transaction without waiting on RPC
+    ///
+    /// Returns the cached order when there is a fresh entry. On a miss the
+    /// lookup is spawned in the background and None is returned; the next
+    /// fill of the same order gets the result. Also None if the transaction
+    /// isn't a DCA fill or the order isn't SOL-funded.
+    pub fn resolve(
+        &self,
+        metadata: &TransactionMetadata,
+        account_keys: &[Pubkey],
+    ) -> Option<DcaOrderInfo> {
+        let order_account = find_order_account(metadata, account_keys)?;
+
+        if let Some(cached) = self.cached(&order_account) {
+            return cached;
+        }
+
+        if self.in_flight.lock().unwrap().insert(order_account) {
+            let resolver = self.clone();
+            tokio::spawn(async move {
+                resolver.refresh(&order_account).await;
+                resolver.in_flight.lock().unwrap().remove(&order_account);
+            });
+        }
+
+        None
+    }
+
+    /// Fresh cache entry for an order (the inner Option is the lookup result)
+    fn cached(&self, order_account: &Pubkey) -> Option<Option<DcaOrderInfo>> {
+        let cache = self.cache.lock().unwrap();
+        let (fetched_at, cached) = cache.get(order_account)?;
+        (fetched_at.elapsed() < self.ttl).then(|| cached.clone())
+    }
+
+    /// Fetch an order and store the result in the cache
+    async fn refresh(&self, order_account: &Pubkey) {
+        match self.fetch(order_account).await {
+            Ok(info) => {
+                let mut cache = self.cache.lock().unwrap();
+                cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < self.ttl);
+                cache.insert(*order_account, (Instant::now(), info));
+            }
+            Err(e) => {
+                log::warn!("⚠️  DCA order lookup failed for {}: {}", order_account, e);
+            }
+        }
+    }
+
+    /// Fetch and decode a DCA order account via `getAccountInfo`
+    async fn fetch(
+        &self,
+        order_account: &Pubkey,
+    ) -> Result<Option<DcaOrderInfo>, SolflowError> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [order_account.to_string(), {"encoding": "base64"}],
+        });
+
+        let response: serde_json::Value = self
+            .client
+            .post(&self.rpc_url)
+            .json(&request)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(SolflowError::enrichment(
+                codes::ENRICHMENT_HTTP,
+                format!("RPC error: {}", error),
+            ));
+        }
+
+        // Closed orders come back as a null value
+        let Some(encoded) = response
+            .pointer("/result/value/data/0")
+            .and_then(|d| d.as_str())
+        else {
+            return Ok(None);
+        };
+
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| SolflowError::enrichment(codes::ENRICHMENT_RESPONSE, e))?;
+        let dca = Dca::deserialize(&data).ok_or_else(|| {
+            SolflowError::enrichment(codes::ENRICHMENT_RESPONSE, "account is not a DCA order")
+        })?;
+
+        Ok(order_info(order_account, &dca))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_dca(input_mint: &str, deposited: u64, used: u64, per_cycle: u64) -> Dca {
+        Dca {
+            user: Pubkey::new_from_array([1; 32]),
+            input_mint: Pubkey::from_str(input_mint).unwrap(),
+            output_mint: Pubkey::new_from_array([2; 32]),
+            idx: 0,
+            next_cycle_at: 0,
+            in_deposited: deposited,
+            in_withdrawn: 0,
+            out_withdrawn: 0,
+            in_used: used,
+            out_received: 0,
+            in_amount_per_cycle: per_cycle,
+            cycle_frequency: 60,
+            next_cycle_amount_left: 0,
+            in_account: Pubkey::new_from_array([3; 32]),
+            out_account: Pubkey::new_from_array([4; 32]),
+            min_out_amount: 0,
+            max_out_amount: 0,
+            keeper_in_balance_before_borrow: 0,
+            dca_out_balance_before_swap: 0,
+            created_at: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_order_info_committed_sol() {
+        let order = Pubkey::new_from_array([5; 32]);
+        // 50 SOL deposited, 10 SOL used, 2 SOL per cycle
+        let dca = make_dca(WSOL_MINT, 50_000_000_000, 10_000_000_000, 2_000_000_000);
+
+        let info = order_info(&order, &dca).unwrap();
+        assert_eq!(info.order_account, order.to_string());
+        assert!((info.committed_sol_remaining - 40.0).abs() < 1e-9);
+        assert!((info.sol_per_cycle - 2.0).abs() < 1e-9);
+        assert_eq!(info.remaining_cycles, 20);
+    }
+
+    #[test]
+    fn test_order_info_partial_cycle_rounds_up() {
+        let dca = make_dca(WSOL_MINT, 250_000_000, 0, 100_000_000);
+        let info = order_info(&Pubkey::new_from_array([5; 32]), &dca).unwrap();
+        assert_eq!(info.remaining_cycles, 3);
+    }
+
+    #[test]
+    fn test_cached_respects_ttl() {
+        let resolver = DcaOrderResolver {
+            rpc_url: String::new(),
+            client: reqwest::Client::new(),
+            ttl: Duration::from_secs(30),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
+        };
+        let fresh = Pubkey::new_from_array([5; 32]);
+        let stale = Pubkey::new_from_array([6; 32]);
+        let info = order_info(&fresh, &make_dca(WSOL_MINT, 1_000_000_000, 0, 100_000_000));
+        {
+            let mut cache = resolver.cache.lock().unwrap();
+            cache.insert(fresh, (Instant::now(), info.clone()));
+            cache.insert(stale, (Instant::now() - Duration::from_secs(31), info.clone()));
+        }
+
+        assert_eq!(resolver.cached(&fresh), Some(info));
+        assert_eq!(resolver.cached(&stale), None);
+        assert_eq!(resolver.cached(&Pubkey::new_from_array([7; 32])), None);
+    }
+
+    #[test]
+    fn test_order_info_ignores_non_sol_orders() {
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let dca = make_dca(usdc, 1_000_000, 0, 100_000);
+        assert!(order_info(&Pubkey::new_from_array([5; 32]), &dca).is_none());
+    }
+}
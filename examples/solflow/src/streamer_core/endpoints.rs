@@ -0,0 +1,302 @@
+//! Yellowstone endpoint pool with health scoring and failover
+//!
+//! With several endpoints in `GEYSER_URLS` (primary first), every connection
+//! attempt goes to the healthiest one. A background task probes each
+//! endpoint with `getSlot` every `GEYSER_HEALTH_INTERVAL_SECS`; an endpoint's
+//! score is its slot lag behind the best endpoint plus a penalty per stream
+//! or connection error in the last five minutes, and an endpoint whose probe
+//! failed is only used when nothing else is reachable. Ties go to the
+//! earlier endpoint, so traffic returns to the primary once it recovers.
+//!
+//! The unified streamer also watches the endpoint it is connected to: when it
+//! falls more than `GEYSER_MAX_SLOT_LAG` slots behind (or stops answering
+//! probes) and a better endpoint exists, the subscription is cancelled and
+//! re-opened there. Processor state is shared across connections, and trades
+//! delivered by both subscriptions are dropped by the pipeline's dedup.
+//!
+//! Environment variables:
+//! - `GEYSER_URLS`: Comma-separated endpoints, primary first (default: GEYSER_URL)
+//! - `GEYSER_HEALTH_INTERVAL_SECS`: Time between probes (default: 10)
+//! - `GEYSER_MAX_SLOT_LAG`: Slot lag that triggers failover (default: 100)
+
+use crate::streamer_core::config::RuntimeConfig;
+use crate::streamer_core::metrics;
+use crate::streamer_core::secrets::redact_url;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::tonic::transport::ClientTlsConfig;
+
+/// Errors older than this no longer count against an endpoint
+const ERROR_WINDOW: Duration = Duration::from_secs(300);
+
+/// Score added per recent error, in slots of lag
+const ERROR_PENALTY_SLOTS: u64 = 50;
+
+/// Score of an endpoint whose last probe failed
+const UNREACHABLE_SCORE: u64 = u64::MAX / 2;
+
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    /// Slot reported by the last successful probe
+    last_slot: Option<u64>,
+    /// Whether the last probe failed
+    unreachable: bool,
+    /// Recent stream/connection errors
+    errors: VecDeque<Instant>,
+}
+
+/// Health-scored set of Yellowstone endpoints (see module docs)
+pub struct EndpointPool {
+    urls: Vec<String>,
+    health: Mutex<Vec<EndpointHealth>>,
+    probe_interval: Duration,
+    max_slot_lag: u64,
+}
+
+impl EndpointPool {
+    pub fn new(urls: Vec<String>, probe_interval: Duration, max_slot_lag: u64) -> Self {
+        let health = urls.iter().map(|_| EndpointHealth::default()).collect();
+        Self {
+            urls,
+            health: Mutex::new(health),
+            probe_interval,
+            max_slot_lag,
+        }
+    }
+
+    /// The config's endpoints with GEYSER_HEALTH_INTERVAL_SECS / GEYSER_MAX_SLOT_LAG
+    pub fn from_config(config: &RuntimeConfig) -> Arc<Self> {
+        let probe_secs = std::env::var("GEYSER_HEALTH_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|n: &u64| *n > 0)
+            .unwrap_or(10);
+        let max_slot_lag = std::env::var("GEYSER_MAX_SLOT_LAG")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(100);
+        Arc::new(Self::new(
+            config.geyser_urls.clone(),
+            Duration::from_secs(probe_secs),
+            max_slot_lag,
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    pub fn url(&self, index: usize) -> &str {
+        &self.urls[index]
+    }
+
+    pub fn probe_interval(&self) -> Duration {
+        self.probe_interval
+    }
+
+    /// `config` pointed at endpoint `index`
+    pub fn config_for(&self, config: &RuntimeConfig, index: usize) -> RuntimeConfig {
+        let mut endpoint_config = config.clone();
+        endpoint_config.geyser_url = self.urls[index].clone();
+        endpoint_config
+    }
+
+    /// Record a probe result (the endpoint's current slot)
+    pub fn record_probe(&self, index: usize, slot: Option<u64>) {
+        let mut health = self.health.lock().unwrap();
+        health[index].unreachable = slot.is_none();
+        if slot.is_some() {
+            health[index].last_slot = slot;
+        }
+    }
+
+    /// Record a connection or stream error (ignored for unknown endpoints)
+    pub fn record_error(&self, index: usize, now: Instant) {
+        if let Some(endpoint) = self.health.lock().unwrap().get_mut(index) {
+            endpoint.errors.push_back(now);
+        }
+    }
+
+    /// Slots endpoint `index` is behind the most advanced endpoint
+    pub fn slot_lag(&self, index: usize) -> Option<u64> {
+        let health = self.health.lock().unwrap();
+        let best = health.iter().filter_map(|h| h.last_slot).max()?;
+        Some(best.saturating_sub(health[index].last_slot?))
+    }
+
+    /// Lower is healthier
+    pub fn score(&self, index: usize, now: Instant) -> u64 {
+        let lag = self.slot_lag(index).unwrap_or(0);
+        let mut health = self.health.lock().unwrap();
+        let endpoint = &mut health[index];
+        while endpoint
+            .errors
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= ERROR_WINDOW)
+        {
+            endpoint.errors.pop_front();
+        }
+        let penalty = endpoint.errors.len() as u64 * ERROR_PENALTY_SLOTS;
+        if endpoint.unreachable {
+            UNREACHABLE_SCORE + penalty
+        } else {
+            lag + penalty
+        }
+    }
+
+    /// Endpoint to connect to next (lowest score, earliest on ties)
+    pub fn select(&self, now: Instant) -> usize {
+        (0..self.urls.len())
+            .min_by_key(|&index| (self.score(index, now), index))
+            .unwrap_or(0)
+    }
+
+    /// A better endpoint to move to, if `current` is lagging or unreachable
+    pub fn failover_target(&self, current: usize, now: Instant) -> Option<usize> {
+        let unreachable = self.health.lock().unwrap()[current].unreachable;
+        let unhealthy =
+            unreachable || self.slot_lag(current).is_some_and(|lag| lag > self.max_slot_lag);
+        if !unhealthy {
+            return None;
+        }
+        let best = self.select(now);
+        (best != current && self.score(best, now) < self.score(current, now)).then_some(best)
+    }
+
+    /// Probe every endpoint until `stop` is cancelled (no-op for one endpoint)
+    pub fn spawn_health_checks(self: &Arc<Self>, config: &RuntimeConfig, stop: CancellationToken) {
+        if self.len() < 2 {
+            return;
+        }
+        let pool = self.clone();
+        let x_token = config.x_token.clone();
+        let commitment = config.commitment_level;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(pool.probe_interval);
+            loop {
+                tokio::select! {
+                    _ = stop.cancelled() => return,
+                    _ = interval.tick() => {}
+                }
+                for index in 0..pool.len() {
+                    let slot = match probe_slot(pool.url(index), x_token.clone(), commitment).await {
+                        Ok(slot) => Some(slot),
+                        Err(e) => {
+                            log::warn!("⚠️  Geyser endpoint {} probe failed: {}", redact_url(pool.url(index)), e);
+                            None
+                        }
+                    };
+                    pool.record_probe(index, slot);
+                }
+            }
+        });
+    }
+}
+
+/// Current slot of a Yellowstone endpoint
+async fn probe_slot(
+    url: &str,
+    x_token: Option<String>,
+    commitment: yellowstone_grpc_proto::geyser::CommitmentLevel,
+) -> Result<u64, String> {
+    let mut client = GeyserGrpcClient::build_from_shared(url.to_string())
+        .map_err(|e| e.to_string())?
+        .x_token(x_token)
+        .map_err(|e| e.to_string())?
+        .tls_config(ClientTlsConfig::new().with_enabled_roots())
+        .map_err(|e| e.to_string())?
+        .timeout(Duration::from_secs(5))
+        .connect()
+        .await
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .get_slot(Some(commitment))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(response.slot)
+}
+
+/// Cancel `connection` when the pool finds a better endpoint than `current`
+///
+/// Returns a flag set when the cancellation was a failover (not a shutdown).
+pub fn watch_for_failover(
+    pool: Arc<EndpointPool>,
+    current: usize,
+    connection: CancellationToken,
+) -> Arc<std::sync::atomic::AtomicBool> {
+    let failed_over = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    if pool.len() < 2 {
+        return failed_over;
+    }
+    let flag = failed_over.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(pool.probe_interval());
+        loop {
+            tokio::select! {
+                _ = connection.cancelled() => return,
+                _ = interval.tick() => {}
+            }
+            if let Some(lag) = pool.slot_lag(current) {
+                metrics::update_gauge(metrics::GEYSER_SLOT_LAG, lag as f64).await;
+            }
+            if let Some(target) = pool.failover_target(current, Instant::now()) {
+                log::warn!(
+                    "🔀 Geyser endpoint {} unhealthy (lag: {:?} slots); failing over to {}",
+                    redact_url(pool.url(current)),
+                    pool.slot_lag(current),
+                    redact_url(pool.url(target))
+                );
+                metrics::increment_counter(metrics::GEYSER_FAILOVERS, 1).await;
+                flag.store(true, std::sync::atomic::Ordering::SeqCst);
+                connection.cancel();
+                return;
+            }
+        }
+    });
+    failed_over
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores_prefer_caught_up_error_free_endpoints() {
+        let pool = EndpointPool::new(
+            vec!["https://a".to_string(), "https://b".to_string(), "https://c".to_string()],
+            Duration::from_secs(10),
+            100,
+        );
+        let now = Instant::now();
+
+        // Unprobed endpoints tie; the primary wins
+        assert_eq!(pool.select(now), 0);
+        assert_eq!(pool.failover_target(0, now), None);
+
+        // Primary falls 500 slots behind
+        pool.record_probe(0, Some(1_000));
+        pool.record_probe(1, Some(1_500));
+        pool.record_probe(2, None);
+        assert_eq!(pool.slot_lag(0), Some(500));
+        assert_eq!(pool.select(now), 1);
+        assert_eq!(pool.failover_target(0, now), Some(1));
+        assert_eq!(pool.failover_target(1, now), None);
+
+        // Errors count against an endpoint until they age out
+        pool.record_probe(0, Some(1_500));
+        pool.record_error(0, now);
+        assert_eq!(pool.score(0, now), ERROR_PENALTY_SLOTS);
+        assert_eq!(pool.select(now), 1);
+        assert_eq!(pool.select(now + ERROR_WINDOW), 0);
+
+        // An unreachable endpoint is never preferred
+        assert!(pool.score(2, now) > pool.score(0, now));
+    }
+}
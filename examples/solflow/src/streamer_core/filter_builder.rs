@@ -0,0 +1,228 @@
+//! Yellowstone transaction filter builder
+//!
+//! `account_required` is AND logic, so matching any of several programs needs
+//! one filter per program: Yellowstone ORs the filters of a subscription.
+//! The builder emits that minimal set (one filter per distinct program, in
+//! insertion order) and validates every address as a base58 pubkey before a
+//! subscription is attempted.
+//!
+//! Focus wallets get one extra filter (`focus_wallets_filter`) whose
+//! `account_include` lists the wallets, so any transaction touching one of
+//! them matches regardless of program.
+
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use yellowstone_grpc_proto::geyser::SubscribeRequestFilterTransactions;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterError {
+    /// (filter name, value) that is not a base58 pubkey
+    InvalidPubkey(String, String),
+    /// Two different programs registered under the same filter name
+    DuplicateName(String),
+}
+
+impl std::fmt::Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterError::InvalidPubkey(name, value) => {
+                write!(f, "filter '{}': '{}' is not a base58 pubkey", name, value)
+            }
+            FilterError::DuplicateName(name) => {
+                write!(f, "filter name '{}' is used for two different programs", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// Builds `transactions` filters with OR semantics across programs
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFilterBuilder {
+    /// (filter name, program ID)
+    programs: Vec<(String, String)>,
+    account_include: Vec<String>,
+    account_exclude: Vec<String>,
+    focus_wallets: Vec<String>,
+    include_failed: bool,
+}
+
+impl TransactionFilterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match transactions involving `program_id`; the filter is named `<name>_filter`
+    pub fn program(mut self, name: impl Into<String>, program_id: impl Into<String>) -> Self {
+        self.programs.push((name.into(), program_id.into()));
+        self
+    }
+
+    /// Add programs named `program_<index>` (e.g. from a PROGRAM_FILTERS list)
+    pub fn programs<I, S>(mut self, program_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for program_id in program_ids {
+            let name = format!("program_{}", self.programs.len());
+            self = self.program(name, program_id);
+        }
+        self
+    }
+
+    /// Accounts added to every filter; a transaction must touch at least one
+    pub fn account_include(mut self, accounts: Vec<String>) -> Self {
+        self.account_include = accounts;
+        self
+    }
+
+    /// Accounts added to every filter; a transaction must touch none
+    pub fn account_exclude(mut self, accounts: Vec<String>) -> Self {
+        self.account_exclude = accounts;
+        self
+    }
+
+    /// Match transactions touching any of `wallets`, in addition to the programs
+    pub fn focus_wallets(mut self, wallets: Vec<String>) -> Self {
+        self.focus_wallets = wallets;
+        self
+    }
+
+    /// Also deliver failed transactions (default: successful only)
+    pub fn include_failed(mut self, include_failed: bool) -> Self {
+        self.include_failed = include_failed;
+        self
+    }
+
+    /// Validate and build the filter map
+    ///
+    /// A program listed more than once gets a single filter (the first name
+    /// wins). No programs and no focus wallets yields an empty map, i.e. no
+    /// transaction filter.
+    pub fn build(self) -> Result<HashMap<String, SubscribeRequestFilterTransactions>, FilterError> {
+        for (kind, accounts) in [
+            ("account_include", &self.account_include),
+            ("account_exclude", &self.account_exclude),
+            ("focus_wallets", &self.focus_wallets),
+        ] {
+            for account in accounts {
+                validate(kind, account)?;
+            }
+        }
+
+        let mut filters = HashMap::new();
+        let mut seen_programs = Vec::new();
+        for (name, program_id) in &self.programs {
+            validate(name, program_id)?;
+            if seen_programs.contains(program_id) {
+                continue;
+            }
+            seen_programs.push(program_id.clone());
+
+            let filter = SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(self.include_failed),
+                account_include: self.account_include.clone(),
+                account_exclude: self.account_exclude.clone(),
+                account_required: vec![program_id.clone()], // ONE program per filter
+                signature: None,
+            };
+            if filters.insert(format!("{}_filter", name), filter).is_some() {
+                return Err(FilterError::DuplicateName(name.clone()));
+            }
+        }
+
+        if !self.focus_wallets.is_empty() {
+            let filter = SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(self.include_failed),
+                account_include: self.focus_wallets.clone(), // ANY wallet
+                account_exclude: self.account_exclude.clone(),
+                account_required: Vec::new(),
+                signature: None,
+            };
+            if filters.insert("focus_wallets_filter".to_string(), filter).is_some() {
+                return Err(FilterError::DuplicateName("focus_wallets".to_string()));
+            }
+        }
+
+        Ok(filters)
+    }
+}
+
+fn validate(name: &str, value: &str) -> Result<(), FilterError> {
+    Pubkey::from_str(value)
+        .map(|_| ())
+        .map_err(|_| FilterError::InvalidPubkey(name.to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUMPSWAP: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
+    const MOONSHOT: &str = "MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG";
+
+    #[test]
+    fn test_one_filter_per_distinct_program() {
+        let filters = TransactionFilterBuilder::new()
+            .program("pumpswap", PUMPSWAP)
+            .program("moonshot", MOONSHOT)
+            .program("pumpswap_again", PUMPSWAP)
+            .account_exclude(vec![MOONSHOT.to_string()])
+            .build()
+            .unwrap();
+
+        assert_eq!(filters.len(), 2);
+        let pumpswap = &filters["pumpswap_filter"];
+        assert_eq!(pumpswap.account_required, vec![PUMPSWAP.to_string()]);
+        assert_eq!(pumpswap.account_exclude, vec![MOONSHOT.to_string()]);
+        assert_eq!(pumpswap.failed, Some(false));
+        assert_eq!(pumpswap.vote, Some(false));
+
+        let unnamed = TransactionFilterBuilder::new()
+            .programs([PUMPSWAP, MOONSHOT])
+            .build()
+            .unwrap();
+        assert!(unnamed.contains_key("program_0_filter"));
+        assert!(unnamed.contains_key("program_1_filter"));
+        assert!(TransactionFilterBuilder::new().build().unwrap().is_empty());
+
+        let focused = TransactionFilterBuilder::new()
+            .program("pumpswap", PUMPSWAP)
+            .account_include(vec![MOONSHOT.to_string()])
+            .focus_wallets(vec![PUMPSWAP.to_string(), MOONSHOT.to_string()])
+            .build()
+            .unwrap();
+        assert_eq!(focused.len(), 2);
+        let wallets = &focused["focus_wallets_filter"];
+        assert_eq!(wallets.account_include, vec![PUMPSWAP.to_string(), MOONSHOT.to_string()]);
+        assert!(wallets.account_required.is_empty());
+    }
+
+    #[test]
+    fn test_validation() {
+        let err = TransactionFilterBuilder::new()
+            .program("bad", "not-a-pubkey")
+            .build()
+            .unwrap_err();
+        assert_eq!(err, FilterError::InvalidPubkey("bad".to_string(), "not-a-pubkey".to_string()));
+
+        let err = TransactionFilterBuilder::new()
+            .program("pumpswap", PUMPSWAP)
+            .account_include(vec!["short".to_string()])
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, FilterError::InvalidPubkey(kind, _) if kind == "account_include"));
+
+        let err = TransactionFilterBuilder::new()
+            .program("dex", PUMPSWAP)
+            .program("dex", MOONSHOT)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, FilterError::DuplicateName("dex".to_string()));
+    }
+}
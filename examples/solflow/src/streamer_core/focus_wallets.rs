@@ -0,0 +1,82 @@
+//! Wallet-level focus subscriptions
+//!
+//! Following a handful of smart-money wallets shouldn't require ingesting all
+//! venue traffic. `FOCUS_WALLETS` adds a subscription filter matching any
+//! transaction that touches one of the listed wallets (Yellowstone
+//! `account_include`, or one `blockSubscribe` per wallet on the RPC fallback),
+//! ORed with the program filters. With `FOCUS_WALLETS_ONLY=true` the program
+//! filters are dropped and only the wallets' transactions are streamed.
+//!
+//! Transactions still go through the instruction scanner, so only trades on
+//! tracked venues become events. Trades involving a focus wallet carry it in
+//! `TradeEvent::focus_wallet` (the trader when it is a focus wallet, otherwise
+//! the first focus wallet among the transaction's accounts), which the JSONL
+//! and Kafka backends emit.
+
+use solana_pubkey::Pubkey;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Focus wallet set used to tag trade events
+#[derive(Debug, Clone, Default)]
+pub struct FocusWallets {
+    wallets: HashSet<Pubkey>,
+}
+
+impl FocusWallets {
+    /// None when no wallets are given; invalid addresses are skipped (the
+    /// config layer has already validated them)
+    pub fn new(wallets: &[String]) -> Option<Self> {
+        let wallets: HashSet<Pubkey> = wallets
+            .iter()
+            .filter_map(|wallet| Pubkey::from_str(wallet).ok())
+            .collect();
+        (!wallets.is_empty()).then_some(Self { wallets })
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+
+    pub fn contains(&self, account: &Pubkey) -> bool {
+        self.wallets.contains(account)
+    }
+
+    /// Focus wallet a trade is attributed to: the trader if it is one,
+    /// otherwise the first one among the transaction's accounts
+    pub fn tag(&self, user_account: Option<&Pubkey>, account_keys: &[Pubkey]) -> Option<Pubkey> {
+        user_account
+            .filter(|user| self.contains(user))
+            .or_else(|| account_keys.iter().find(|key| self.contains(key)))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_prefers_trader() {
+        let trader = Pubkey::new_from_array([1; 32]);
+        let other_focus = Pubkey::new_from_array([2; 32]);
+        let stranger = Pubkey::new_from_array([3; 32]);
+        let focus = FocusWallets::new(&[
+            trader.to_string(),
+            other_focus.to_string(),
+            "not-a-pubkey".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(focus.len(), 2);
+        assert!(FocusWallets::new(&[]).is_none());
+
+        let keys = [stranger, other_focus, trader];
+        assert_eq!(focus.tag(Some(&trader), &keys), Some(trader));
+        assert_eq!(focus.tag(Some(&stranger), &keys), Some(other_focus));
+        assert_eq!(focus.tag(None, &[stranger]), None);
+    }
+}
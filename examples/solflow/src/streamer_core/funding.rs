@@ -0,0 +1,60 @@
+//! Funding edges for the wallet graph
+//!
+//! A funding edge is native SOL sent by one of the transaction's signers to
+//! a wallet that is either another signer or a fresh account (no lamports
+//! before the transaction) - the shape of a bundler topping up the wallets
+//! that buy in the same transaction. Transfers to pools, vaults, fee and tip
+//! accounts (not signers, already funded) and from program-derived accounts
+//! (never signers) are not edges, nor are amounts below rent-sized top-ups.
+//!
+//! Edges are recorded in the process-wide `pipeline::wallet_graph`.
+
+use crate::pipeline::wallet_graph;
+use crate::streamer_core::transfer_direction::extract_transfers;
+use carbon_core::transaction::TransactionMetadata;
+use solana_pubkey::Pubkey;
+use std::collections::HashSet;
+
+/// Smallest transfer counted as funding (0.01 SOL)
+const MIN_FUNDING_LAMPORTS: u64 = 10_000_000;
+
+/// (funder, funded) pairs in a transaction
+pub fn funding_edges(metadata: &TransactionMetadata, account_keys: &[Pubkey]) -> Vec<(Pubkey, Pubkey)> {
+    let signer_count = metadata.message.header().num_required_signatures as usize;
+    let signers = &account_keys[..signer_count.min(account_keys.len())];
+    let token_accounts: HashSet<usize> = metadata
+        .meta
+        .pre_token_balances
+        .iter()
+        .chain(metadata.meta.post_token_balances.iter())
+        .flatten()
+        .map(|balance| balance.account_index as usize)
+        .collect();
+    let is_wallet = |key: &Pubkey| {
+        let Some(index) = account_keys.iter().position(|k| k == key) else {
+            return false;
+        };
+        !token_accounts.contains(&index)
+            && (index < signers.len() || metadata.meta.pre_balances.get(index) == Some(&0))
+    };
+
+    extract_transfers(metadata, account_keys)
+        .into_iter()
+        .filter(|transfer| transfer.mint.is_none() && transfer.amount >= MIN_FUNDING_LAMPORTS)
+        .filter_map(|transfer| Some((transfer.from_owner?, transfer.to_owner?)))
+        .filter(|(from, to)| from != to && signers.contains(from) && is_wallet(to))
+        .collect()
+}
+
+/// Record a transaction's funding edges in the wallet graph; returns how many
+pub fn record_funding(metadata: &TransactionMetadata, account_keys: &[Pubkey]) -> usize {
+    let graph = wallet_graph::shared();
+    if !graph.is_enabled() {
+        return 0;
+    }
+    let edges = funding_edges(metadata, account_keys);
+    for (funder, funded) in &edges {
+        graph.link(&funder.to_string(), &funded.to_string());
+    }
+    edges.len()
+}
@@ -1,15 +1,19 @@
+use crate::error::SolflowError;
 use crate::streamer_core::config::RuntimeConfig;
+use crate::streamer_core::endpoints::EndpointPool;
 use crate::streamer_core::error_handler::{ExponentialBackoff, MaxRetriesExceeded};
+use crate::streamer_core::filter_builder::{FilterError, TransactionFilterBuilder};
 use carbon_yellowstone_grpc_datasource::YellowstoneGrpcGeyserClient;
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use yellowstone_grpc_proto::geyser::{CommitmentLevel, SubscribeRequestFilterTransactions};
+use yellowstone_grpc_proto::geyser::CommitmentLevel;
 
 #[derive(Debug)]
 pub enum ClientError {
     Connection(String),
+    InvalidFilter(FilterError),
     MaxRetries,
 }
 
@@ -19,10 +23,17 @@ impl From<MaxRetriesExceeded> for ClientError {
     }
 }
 
+impl From<FilterError> for ClientError {
+    fn from(err: FilterError) -> Self {
+        ClientError::InvalidFilter(err)
+    }
+}
+
 impl std::fmt::Display for ClientError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ClientError::Connection(msg) => write!(f, "Connection error: {}", msg),
+            ClientError::InvalidFilter(e) => write!(f, "Invalid transaction filter: {}", e),
             ClientError::MaxRetries => write!(f, "Maximum retry attempts exceeded"),
         }
     }
@@ -30,10 +41,30 @@ impl std::fmt::Display for ClientError {
 
 impl std::error::Error for ClientError {}
 
+/// All tracked programs as (filter name, program ID)
+///
+/// Shared by the gRPC client and the RPC `blockSubscribe` fallback.
+pub const TRACKED_PROGRAMS: [(&str, &str); 11] = [
+    ("pumpfun", "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"),
+    ("pumpswap", "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA"),
+    ("bonkswap", "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj"),
+    ("moonshot", "MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG"),
+    ("jupiter_dca", "DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M"),
+    ("raydium_amm", "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"),
+    ("raydium_clmm", "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK"),
+    ("orca_whirlpool", "whirLbMiicVdio4qvUfM5KaG6Ct8VwpYzGff3uctyCc"),
+    ("meteora_dlmm", "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"),
+    ("meteora_damm", "Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB"),
+    ("meteora_damm_v2", "cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG"),
+];
+
 /// Create gRPC client with multi-program filtering (Option B - APPROVED)
 ///
 /// This function creates a client that subscribes to transactions involving
-/// any of the 5 tracked programs: PumpFun, PumpSwap, BonkSwap, Moonshot, Jupiter DCA.
+/// any of the 11 tracked programs: PumpFun, PumpSwap, BonkSwap, Moonshot, Jupiter DCA,
+/// Raydium AMM v4, Raydium CLMM, Orca Whirlpool and Meteora DLMM / DAMM v1 / DAMM v2.
+/// Raydium, Orca and Meteora transactions that aren't swaps are dropped by the
+/// `InstructionScanner`.
 ///
 /// The gRPC filter matches ANY transaction where these programs appear in the
 /// account keys, which covers both outer and inner (CPI) instructions because
@@ -41,40 +72,48 @@ impl std::error::Error for ClientError {}
 ///
 /// CRITICAL: Uses OR semantics by creating one filter per program.
 /// Multiple filters in the map are treated as OR logic by Yellowstone gRPC.
+///
+/// `config.account_include` / `config.account_exclude` are added to every
+/// program filter, scoping the subscription server-side: a transaction must
+/// also touch at least one included account (when any are set) and none of
+/// the excluded ones.
+///
+/// `config.focus_wallets` adds a wallet filter ORed with the program filters;
+/// with `config.focus_wallets_only` the program filters are left out (see
+/// `focus_wallets`).
 pub async fn create_multi_program_client(
     config: &RuntimeConfig,
 ) -> Result<YellowstoneGrpcGeyserClient, ClientError> {
-    // Define all tracked programs with their identifiers
-    let programs = vec![
-        ("pumpfun", "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"),
-        ("pumpswap", "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA"),
-        ("bonkswap", "LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj"),
-        ("moonshot", "MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG"),
-        ("jupiter_dca", "DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M"),
-    ];
-
-    // Create separate filter for each program (OR logic)
-    // account_required with multiple entries uses AND logic (all must be present)
-    // Multiple filters in the map use OR logic (any filter can match)
-    // This follows the pattern from grpc_verify.rs:486-502
-    let mut transaction_filters = HashMap::new();
-
-    for (name, program_id) in programs.iter() {
-        let filter = SubscribeRequestFilterTransactions {
-            vote: Some(false),
-            failed: Some(false),
-            account_include: vec![],
-            account_exclude: vec![],
-            account_required: vec![program_id.to_string()], // ONE program per filter
-            signature: None,
-        };
-        transaction_filters.insert(format!("{}_filter", name), filter);
-    }
+    let programs: &[(&str, &str)] = if config.focus_wallets_only { &[] } else { &TRACKED_PROGRAMS };
+
+    // One filter per program (OR logic), see filter_builder
+    let transaction_filters = programs
+        .iter()
+        .fold(TransactionFilterBuilder::new(), |builder, (name, program_id)| {
+            builder.program(*name, *program_id)
+        })
+        .account_include(config.account_include.clone())
+        .account_exclude(config.account_exclude.clone())
+        .focus_wallets(config.focus_wallets.clone())
+        .build()?;
 
     log::info!("🔗 Creating multi-program gRPC client");
-    log::info!("   Registered {} transaction filters for multi-program matching", programs.len());
-    log::info!("   Filter logic: OR (transactions matching ANY of the 5 programs)");
-    log::info!("   Filtering: PumpFun, PumpSwap, BonkSwap, Moonshot, Jupiter DCA");
+    log::info!("   Registered {} transaction filters for multi-program matching", transaction_filters.len());
+    if config.focus_wallets_only {
+        log::info!("   Filtering: {} focus wallets only (FOCUS_WALLETS_ONLY)", config.focus_wallets.len());
+    } else {
+        log::info!("   Filter logic: OR (transactions matching ANY of the 11 programs)");
+        log::info!("   Filtering: PumpFun, PumpSwap, BonkSwap, Moonshot, Jupiter DCA, Raydium AMM/CLMM, Orca Whirlpool, Meteora DLMM/DAMM");
+        if !config.focus_wallets.is_empty() {
+            log::info!("   Focus wallets: {} (any transaction touching them)", config.focus_wallets.len());
+        }
+    }
+    if !config.account_include.is_empty() {
+        log::info!("   Account include: {} accounts (any must match)", config.account_include.len());
+    }
+    if !config.account_exclude.is_empty() {
+        log::info!("   Account exclude: {} accounts", config.account_exclude.len());
+    }
 
     Ok(YellowstoneGrpcGeyserClient::new(
         config.geyser_url.clone(),
@@ -100,17 +139,9 @@ pub async fn create_single_account_client(
     account_address: &str,
     commitment_level: CommitmentLevel,
 ) -> Result<YellowstoneGrpcGeyserClient, ClientError> {
-    let transaction_filter = SubscribeRequestFilterTransactions {
-        vote: Some(false),
-        failed: Some(false),
-        account_include: vec![],
-        account_exclude: vec![],
-        account_required: vec![account_address.to_string()],
-        signature: None,
-    };
-
-    let mut transaction_filters = HashMap::new();
-    transaction_filters.insert("account_filter".to_string(), transaction_filter);
+    let transaction_filters = TransactionFilterBuilder::new()
+        .program("account", account_address)
+        .build()?;
 
     Ok(YellowstoneGrpcGeyserClient::new(
         geyser_url.to_string(),
@@ -132,17 +163,9 @@ pub async fn create_client(
     config: &RuntimeConfig,
     program_filter: &str,
 ) -> Result<YellowstoneGrpcGeyserClient, ClientError> {
-    let transaction_filter = SubscribeRequestFilterTransactions {
-        vote: Some(false),
-        failed: Some(false),
-        account_include: vec![],
-        account_exclude: vec![],
-        account_required: vec![program_filter.to_string()],
-        signature: None,
-    };
-
-    let mut transaction_filters = HashMap::new();
-    transaction_filters.insert("program_filter".to_string(), transaction_filter);
+    let transaction_filters = TransactionFilterBuilder::new()
+        .program("program", program_filter)
+        .build()?;
 
     Ok(YellowstoneGrpcGeyserClient::new(
         config.geyser_url.clone(),
@@ -156,6 +179,10 @@ pub async fn create_client(
     ))
 }
 
+/// Run `process_fn` on a client, reconnecting after errors
+///
+/// Each attempt connects to the healthiest of `config.geyser_urls` (see
+/// `endpoints`), so errors on one endpoint fail over to the next.
 pub async fn run_with_reconnect<F, Fut>(
     config: &RuntimeConfig,
     program_filter: &str,
@@ -163,26 +190,35 @@ pub async fn run_with_reconnect<F, Fut>(
 ) -> Result<(), ClientError>
 where
     F: Fn(YellowstoneGrpcGeyserClient) -> Fut,
-    Fut: Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+    Fut: Future<Output = Result<(), SolflowError>>,
 {
     let mut backoff = ExponentialBackoff::new(5, 60, 10);
+    let pool = EndpointPool::from_config(config);
+    let probes = tokio_util::sync::CancellationToken::new();
+    let _stop_probes = probes.clone().drop_guard();
+    pool.spawn_health_checks(config, probes);
 
     loop {
-        match create_client(config, program_filter).await {
+        let endpoint = pool.select(std::time::Instant::now());
+        match create_client(&pool.config_for(config, endpoint), program_filter).await {
             Ok(client) => {
                 log::info!("✅ Connected to gRPC server");
                 backoff.reset();
                 
                 if let Err(e) = process_fn(client).await {
-                    log::error!("❌ Pipeline error: {:?}", e);
+                    log::error!("❌ Pipeline error: {}", e);
+                    pool.record_error(endpoint, std::time::Instant::now());
                     backoff.sleep().await?;
                 } else {
                     log::info!("✅ Pipeline completed gracefully");
                     return Ok(());
                 }
             }
+            // A bad filter won't fix itself on retry
+            Err(e @ ClientError::InvalidFilter(_)) => return Err(e),
             Err(e) => {
                 log::error!("❌ Connection failed: {:?}", e);
+                pool.record_error(endpoint, std::time::Instant::now());
                 backoff.sleep().await?;
             }
         }
@@ -0,0 +1,254 @@
+//! Jupiter v6 route decoding
+//!
+//! Balance deltas only show a transaction's net effect, so a multi-hop
+//! Jupiter route (SOL → USDC → TOKEN, or a SOL → TOKEN split across two
+//! pools) collapses into one trade with misattributed amounts. Jupiter v6
+//! logs a `SwapEvent` (or, on newer routes, one `SwapsEvent` listing every
+//! hop) as a self-CPI for each leg it executes; this module decodes those
+//! events and turns each leg into its own trade.
+//!
+//! Legs are valued in SOL: a leg against WSOL uses its SOL side directly,
+//! and a leg between two tokens is valued through the leg that produced its
+//! input (the SOL → USDC hop prices the USDC spent on USDC → TOKEN).
+//! Intermediate hops (tokens a route both receives and spends) carry value
+//! but are not reported as trades, so a SOL → TOKEN → SOL round trip yields
+//! none. Routes with a leg that can't be valued fall back to balance deltas.
+
+use crate::streamer_core::trade_detector::{TradeDirection, TradeInfo};
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_core::transaction::TransactionMetadata;
+use carbon_jupiter_swap_decoder::instructions::swap_event::SwapEvent;
+use carbon_jupiter_swap_decoder::instructions::swaps_event::SwapsEvent;
+use carbon_jupiter_swap_decoder::PROGRAM_ID as JUPITER_V6_PROGRAM_ID;
+use solana_pubkey::Pubkey;
+use solana_transaction_status::TransactionStatusMeta;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+/// Wrapped SOL mint
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// One hop of a Jupiter route, as logged by the program
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapLeg {
+    /// AMM program the hop executed on (absent from `SwapsEvent` hops)
+    pub amm: Option<Pubkey>,
+    pub input_mint: Pubkey,
+    pub input_amount: u64,
+    pub output_mint: Pubkey,
+    pub output_amount: u64,
+}
+
+/// Decode the swap events Jupiter v6 logged in this transaction, in order
+pub fn decode_route_legs(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+) -> Vec<SwapLeg> {
+    let mut legs = Vec::new();
+    let Some(inner_groups) = &metadata.meta.inner_instructions else {
+        return legs;
+    };
+
+    for inner_group in inner_groups {
+        for inner in &inner_group.instructions {
+            let ix = &inner.instruction;
+            if account_keys.get(ix.program_id_index as usize) != Some(&JUPITER_V6_PROGRAM_ID) {
+                continue;
+            }
+            if let Some(event) = SwapEvent::deserialize(&ix.data) {
+                legs.push(SwapLeg {
+                    amm: Some(event.amm),
+                    input_mint: event.input_mint,
+                    input_amount: event.input_amount,
+                    output_mint: event.output_mint,
+                    output_amount: event.output_amount,
+                });
+            } else if let Some(event) = SwapsEvent::deserialize(&ix.data) {
+                legs.extend(event.swap_events.into_iter().map(|event| SwapLeg {
+                    amm: None,
+                    input_mint: event.input_mint,
+                    input_amount: event.input_amount,
+                    output_mint: event.output_mint,
+                    output_amount: event.output_amount,
+                }));
+            }
+        }
+    }
+
+    legs
+}
+
+/// Decimals of every mint with a token balance in the transaction
+pub fn token_decimals(meta: &TransactionStatusMeta) -> HashMap<Pubkey, u8> {
+    meta.pre_token_balances
+        .iter()
+        .chain(meta.post_token_balances.iter())
+        .flatten()
+        .filter_map(|balance| {
+            let mint = Pubkey::from_str(&balance.mint).ok()?;
+            Some((mint, balance.ui_token_amount.decimals))
+        })
+        .collect()
+}
+
+/// One trade per route leg; None when a leg can't be valued in SOL
+///
+/// `user_account` is the route's signer (Jupiter swaps on behalf of the fee
+/// payer). Each trade's `program_id` is the leg's AMM, if known.
+pub fn route_trades(
+    legs: &[SwapLeg],
+    decimals: &HashMap<Pubkey, u8>,
+    user_account: Option<Pubkey>,
+) -> Option<Vec<TradeInfo>> {
+    let wsol = Pubkey::from_str(WSOL_MINT).expect("valid WSOL mint");
+    let lamports_per_unit = sol_rates(legs, &wsol);
+
+    // Tokens the route both receives and spends are hops, not trades
+    let received: HashSet<Pubkey> = legs.iter().map(|leg| leg.output_mint).collect();
+    let spent: HashSet<Pubkey> = legs.iter().map(|leg| leg.input_mint).collect();
+    let is_hop = |mint: &Pubkey| *mint == wsol || (received.contains(mint) && spent.contains(mint));
+
+    let trade = |mint: &Pubkey, raw_amount: u64, lamports: f64, direction, amm| {
+        let token_decimals = *decimals.get(mint)?;
+        Some(TradeInfo {
+            mint: mint.to_string(),
+            sol_amount: lamports / LAMPORTS_PER_SOL,
+            token_amount: raw_amount as f64 / 10f64.powi(token_decimals as i32),
+            token_decimals,
+            direction,
+            user_account,
+            program_id: amm,
+        })
+    };
+
+    let mut trades = Vec::new();
+    for leg in legs {
+        let lamports = lamports_per_unit
+            .get(&leg.input_mint)
+            .map(|rate| rate * leg.input_amount as f64)
+            .or_else(|| {
+                lamports_per_unit
+                    .get(&leg.output_mint)
+                    .map(|rate| rate * leg.output_amount as f64)
+            })?;
+
+        if !is_hop(&leg.input_mint) {
+            let sell = trade(&leg.input_mint, leg.input_amount, lamports, TradeDirection::Sell, leg.amm);
+            trades.push(sell?);
+        }
+        if !is_hop(&leg.output_mint) {
+            let buy = trade(&leg.output_mint, leg.output_amount, lamports, TradeDirection::Buy, leg.amm);
+            trades.push(buy?);
+        }
+    }
+
+    Some(trades)
+}
+
+/// Lamports per raw unit of each mint, propagated along the route from WSOL
+fn sol_rates(legs: &[SwapLeg], wsol: &Pubkey) -> HashMap<Pubkey, f64> {
+    let mut rates = HashMap::from([(*wsol, 1.0)]);
+    // Each pass prices at least one more mint or nothing changes
+    for _ in 0..legs.len() {
+        let mut changed = false;
+        for leg in legs {
+            if leg.input_amount == 0 || leg.output_amount == 0 {
+                continue;
+            }
+            let (input, output) = (leg.input_amount as f64, leg.output_amount as f64);
+            let (mint, rate) = match (rates.get(&leg.input_mint), rates.get(&leg.output_mint)) {
+                (Some(rate), None) => (leg.output_mint, rate * input / output),
+                (None, Some(rate)) => (leg.input_mint, rate * output / input),
+                _ => continue,
+            };
+            rates.insert(mint, rate);
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    rates
+}
+
+/// Decode and value a transaction's Jupiter route
+///
+/// None when the transaction has no Jupiter route or it can't be valued
+/// (use balance deltas instead).
+pub fn extract_route_trades(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+) -> Option<Vec<TradeInfo>> {
+    let legs = decode_route_legs(metadata, account_keys);
+    if legs.is_empty() {
+        return None;
+    }
+    let decimals = token_decimals(&metadata.meta);
+    route_trades(&legs, &decimals, account_keys.first().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mint(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    fn leg(input_mint: Pubkey, input_amount: u64, output_mint: Pubkey, output_amount: u64) -> SwapLeg {
+        SwapLeg {
+            amm: Some(mint(99)),
+            input_mint,
+            input_amount,
+            output_mint,
+            output_amount,
+        }
+    }
+
+    #[test]
+    fn test_route_legs_become_per_leg_trades() {
+        let wsol = Pubkey::from_str(WSOL_MINT).unwrap();
+        let (usdc, token, other) = (mint(1), mint(2), mint(3));
+        let decimals = HashMap::from([(usdc, 6), (token, 6), (other, 9), (wsol, 9)]);
+
+        // SOL → USDC → TOKEN: the USDC hop is valued but not reported
+        let legs = [
+            leg(wsol, 2_000_000_000, usdc, 300_000_000),
+            leg(usdc, 300_000_000, token, 5_000_000_000),
+        ];
+        let trades = route_trades(&legs, &decimals, None).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].mint, token.to_string());
+        assert!(matches!(trades[0].direction, TradeDirection::Buy));
+        assert!((trades[0].sol_amount - 2.0).abs() < 1e-9);
+        assert!((trades[0].token_amount - 5000.0).abs() < 1e-9);
+        assert_eq!(trades[0].program_id, Some(mint(99)));
+
+        // A SOL → TOKEN split across two pools is two buys; TOKEN → SOL is a sell
+        let legs = [
+            leg(wsol, 1_000_000_000, token, 1_000_000),
+            leg(wsol, 500_000_000, token, 400_000),
+            leg(other, 7_000_000_000, wsol, 250_000_000),
+        ];
+        let trades = route_trades(&legs, &decimals, None).unwrap();
+        let summary: Vec<_> = trades
+            .iter()
+            .map(|t| (t.mint.clone(), <&str>::from(t.direction), (t.sol_amount * 100.0).round()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (token.to_string(), "BUY", 100.0),
+                (token.to_string(), "BUY", 50.0),
+                (other.to_string(), "SELL", 25.0),
+            ]
+        );
+
+        // A round trip is all hops; nothing to value against falls back to deltas
+        let round_trip = [leg(wsol, 1_000, token, 5), leg(token, 5, wsol, 1_100)];
+        assert_eq!(route_trades(&round_trip, &decimals, None).unwrap().len(), 0);
+        assert!(route_trades(&[leg(usdc, 1, token, 1)], &decimals, None).is_none());
+    }
+}
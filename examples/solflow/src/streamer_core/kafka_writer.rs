@@ -0,0 +1,306 @@
+//! Kafka writer backend (`--backend kafka`, built with `--features kafka`)
+//!
+//! Publishes every TradeEvent as JSON to one topic, keyed by mint so a
+//! token's trades stay ordered on one partition, for consumers outside this
+//! process.
+//!
+//! Sends are non-blocking. Records librdkafka can't take (local queue full)
+//! or that fail delivery are kept in an in-memory overflow buffer and resent
+//! after an exponential backoff (100ms doubling to 30s). While the buffer is
+//! non-empty new records queue behind it, preserving per-mint order. When the
+//! buffer reaches `KAFKA_OVERFLOW_CAPACITY` the oldest records are dropped and
+//! counted.
+//!
+//! Environment variables:
+//! - `KAFKA_BROKERS`: Bootstrap servers, e.g. `kafka-1:9092,kafka-2:9092`
+//! - `KAFKA_TOPIC`: Topic (default: solflow.trades)
+//! - `KAFKA_OVERFLOW_CAPACITY`: Buffered records before dropping (default: 100000)
+//! - `KAFKA_CONFIG_<PROPERTY>`: Extra librdkafka property, `_` for `.`, e.g.
+//!   `KAFKA_CONFIG_SECURITY_PROTOCOL=SASL_SSL`, `KAFKA_CONFIG_SASL_PASSWORD=file:/run/secrets/kafka`
+//!   (accepts `file:` / `keyring:` references, see `secrets`)
+
+use crate::streamer_core::{
+    output_writer::TradeEvent,
+    secrets::secret_from_env,
+    writer_backend::{WriterBackend, WriterError},
+};
+use async_trait::async_trait;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::message::Message;
+use rdkafka::producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer};
+use rdkafka::ClientContext;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// First retry delay after a failure
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Longest retry delay
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Prefix of env vars passed through as librdkafka properties
+const CONFIG_PREFIX: &str = "KAFKA_CONFIG_";
+
+/// A record waiting to be (re)sent
+#[derive(Debug, Clone, PartialEq)]
+struct PendingRecord {
+    key: String,
+    payload: Vec<u8>,
+}
+
+/// Bounded FIFO of unsent records with exponential retry backoff
+#[derive(Debug)]
+struct OverflowBuffer {
+    records: VecDeque<PendingRecord>,
+    capacity: usize,
+    dropped: u64,
+    backoff: Duration,
+    retry_at: Option<Instant>,
+}
+
+impl OverflowBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::new(),
+            capacity: capacity.max(1),
+            dropped: 0,
+            backoff: INITIAL_BACKOFF,
+            retry_at: None,
+        }
+    }
+
+    /// Append, dropping the oldest record when full
+    fn push_back(&mut self, record: PendingRecord) {
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+            self.dropped += 1;
+            if self.dropped.is_power_of_two() {
+                log::warn!("⚠️  Kafka overflow buffer full: {} trades dropped so far", self.dropped);
+            }
+        }
+        self.records.push_back(record);
+    }
+
+    /// Whether buffered records may be resent now
+    fn ready(&self, now: Instant) -> bool {
+        !self.records.is_empty() && self.retry_at.is_none_or(|at| at <= now)
+    }
+
+    /// A send failed: wait out the backoff, then double it
+    fn on_failure(&mut self, now: Instant) {
+        self.retry_at = Some(now + self.backoff);
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+    }
+
+    fn on_success(&mut self) {
+        self.retry_at = None;
+        self.backoff = INITIAL_BACKOFF;
+    }
+}
+
+/// Requeues records whose delivery failed (called on the producer's poll thread)
+struct DeliveryTracker {
+    buffer: Arc<Mutex<OverflowBuffer>>,
+}
+
+impl ClientContext for DeliveryTracker {}
+
+impl ProducerContext for DeliveryTracker {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _: Self::DeliveryOpaque) {
+        let Err((error, message)) = result else {
+            return;
+        };
+        log::debug!("Kafka delivery failed, requeueing: {}", error);
+        let record = PendingRecord {
+            key: message
+                .key()
+                .map(|key| String::from_utf8_lossy(key).into_owned())
+                .unwrap_or_default(),
+            payload: message.payload().map(<[u8]>::to_vec).unwrap_or_default(),
+        };
+        let mut buffer = self.buffer.lock().unwrap();
+        buffer.push_back(record);
+        buffer.on_failure(Instant::now());
+    }
+}
+
+pub struct KafkaWriter {
+    producer: ThreadedProducer<DeliveryTracker>,
+    topic: String,
+    buffer: Arc<Mutex<OverflowBuffer>>,
+}
+
+impl KafkaWriter {
+    /// Create a producer for `brokers` configured from `KAFKA_*` env vars
+    pub fn new(brokers: &str) -> Result<Self, WriterError> {
+        let topic = std::env::var("KAFKA_TOPIC")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| "solflow.trades".to_string());
+        let capacity = std::env::var("KAFKA_OVERFLOW_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(100_000);
+
+        let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", brokers)
+            .set("enable.idempotence", "true")
+            .set("compression.type", "lz4")
+            .set("linger.ms", "20");
+        for (name, _) in std::env::vars() {
+            let Some(property) = name.strip_prefix(CONFIG_PREFIX) else {
+                continue;
+            };
+            let value = secret_from_env(&name)
+                .map_err(WriterError::Publish)?
+                .unwrap_or_default();
+            config.set(property.to_ascii_lowercase().replace('_', "."), value);
+        }
+
+        let buffer = Arc::new(Mutex::new(OverflowBuffer::new(capacity)));
+        let producer: ThreadedProducer<DeliveryTracker> = config
+            .create_with_context(DeliveryTracker {
+                buffer: buffer.clone(),
+            })
+            .map_err(|e| WriterError::Publish(e.to_string()))?;
+
+        log::info!("✅ Kafka producer ready: {} → topic {}", brokers, topic);
+        Ok(Self {
+            producer,
+            topic,
+            buffer,
+        })
+    }
+
+    /// Hand a record to librdkafka; Err gives it back when it should be retried
+    fn send(&self, record: PendingRecord) -> Result<(), PendingRecord> {
+        let result = self.producer.send(
+            BaseRecord::to(&self.topic)
+                .key(&record.key)
+                .payload(&record.payload),
+        );
+        match result {
+            Ok(()) => Ok(()),
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::MessageSizeTooLarge), _)) => {
+                log::warn!("⚠️  Dropping oversized Kafka record for {}", record.key);
+                Ok(())
+            }
+            Err((e, _)) => {
+                log::debug!("Kafka send failed, buffering: {}", e);
+                Err(record)
+            }
+        }
+    }
+
+    /// Resend buffered records in order until one fails or none are left
+    fn drain_overflow(&self) {
+        loop {
+            let record = {
+                let mut buffer = self.buffer.lock().unwrap();
+                if !buffer.ready(Instant::now()) {
+                    return;
+                }
+                buffer.records.pop_front()
+            };
+            let Some(record) = record else {
+                return;
+            };
+            let mut buffer = self.buffer.lock().unwrap();
+            match self.send(record) {
+                Ok(()) => buffer.on_success(),
+                Err(record) => {
+                    buffer.records.push_front(record);
+                    buffer.on_failure(Instant::now());
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl WriterBackend for KafkaWriter {
+    async fn write(&mut self, event: &TradeEvent) -> Result<(), WriterError> {
+        let record = PendingRecord {
+            key: event.mint.clone(),
+            payload: serde_json::to_vec(event)?,
+        };
+
+        self.drain_overflow();
+
+        let mut buffer = self.buffer.lock().unwrap();
+        // Queue behind buffered records so a mint's trades stay in order
+        if !buffer.records.is_empty() {
+            buffer.push_back(record);
+            return Ok(());
+        }
+        if let Err(record) = self.send(record) {
+            buffer.push_back(record);
+            buffer.on_failure(Instant::now());
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), WriterError> {
+        self.drain_overflow();
+        self.producer
+            .flush(Duration::from_secs(10))
+            .map_err(|e| WriterError::Publish(e.to_string()))?;
+        let pending = self.buffer.lock().unwrap().records.len();
+        if pending > 0 {
+            log::warn!("⚠️  {} trades still buffered for Kafka after flush", pending);
+        }
+        Ok(())
+    }
+
+    fn backend_type(&self) -> &'static str {
+        "Kafka"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(key: &str) -> PendingRecord {
+        PendingRecord {
+            key: key.to_string(),
+            payload: key.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_overflow_buffer_drops_oldest_and_backs_off() {
+        let mut buffer = OverflowBuffer::new(2);
+        let now = Instant::now();
+        assert!(!buffer.ready(now));
+
+        for key in ["a", "b", "c"] {
+            buffer.push_back(record(key));
+        }
+        assert_eq!(buffer.dropped, 1);
+        assert_eq!(buffer.records, VecDeque::from(vec![record("b"), record("c")]));
+        assert!(buffer.ready(now));
+
+        buffer.on_failure(now);
+        assert!(!buffer.ready(now));
+        assert!(buffer.ready(now + INITIAL_BACKOFF));
+        buffer.on_failure(now);
+        assert!(!buffer.ready(now + INITIAL_BACKOFF));
+        assert!(buffer.ready(now + INITIAL_BACKOFF * 2));
+
+        for _ in 0..20 {
+            buffer.on_failure(now);
+        }
+        assert_eq!(buffer.backoff, MAX_BACKOFF);
+
+        buffer.on_success();
+        assert_eq!(buffer.backoff, INITIAL_BACKOFF);
+        assert!(buffer.ready(now));
+    }
+}
@@ -1,13 +1,54 @@
+use crate::error::{codes, ErrorCategory, SolflowError};
 use crate::instruction_scanner::InstructionScanner;
+use crate::meta_analysis::{build_transaction_capture, AnomalyCapture, CaptureMetadata};
+use crate::pipeline::dexscreener;
+use crate::pipeline::fast_path::FastPathRoute;
+use crate::pipeline::shutdown::ShutdownSignal;
+use crate::pipeline::types::{Confirmation, TokenMigration};
 use crate::streamer_core::{
-    balance_extractor::{build_full_account_keys, extract_sol_changes, extract_token_changes},
+    account_cache::{self, AccountClass, AccountKeyCache},
+    backfill::{BackfillConfig, BackfillDatasource},
+    balance_extractor::{
+        build_full_account_keys, extract_quote_changes, extract_sol_changes, extract_token_changes,
+        sol_usd_refresh_interval,
+    },
     blocklist_checker::BlocklistChecker,
-    config::{BackendType, RuntimeConfig, StreamerConfig},
-    grpc_client::{run_with_reconnect, create_multi_program_client},
+    compute_budget,
+    config::{BackendType, DatasourceKind, RuntimeConfig, StreamerConfig},
+    dca_order::DcaOrderResolver,
+    endpoints::{watch_for_failover, EndpointPool},
+    focus_wallets::FocusWallets,
+    funding,
+    grpc_client::{run_with_reconnect, create_multi_program_client, ClientError},
+    jupiter_route,
+    liquidity::{self, PoolVault},
+    meteora,
+    metrics::{
+        with_pipeline_metrics, BLOCKLIST_HITS, CHANNEL_SEND_FAILURES, DATA_GAPS, DATA_GAP_SLOTS,
+        DIRECTIONS_INFERRED, DISCRIMINATOR_DIRECTIONS, FUNDING_EDGES, JUPITER_ROUTE_TRADES, METEORA_SWAP_TRADES, POOL_STATE_ACCOUNTS_FOUND, POOL_VAULTS_FOUND,
+        PUMPFUN_CURVE_TRADES, SPLIT_SWAP_TRADES, TOKEN_MIGRATIONS, TRADES_EXTRACTED,
+    },
     output_writer::{JsonlWriter, TradeEvent},
+    pool_state::{self, PoolAccount},
+    postgres_writer::PostgresWriter,
+    program_registry::{self, ProgramRegistry, SharedScanner},
+    pumpfun,
+    redundancy::RedundantDatasource,
+    rpc_client::{create_multi_program_rpc_datasources, SignatureDedup},
+    secrets::redact_url,
+    slot_gaps::{self, SlotGap, SlotGapConfig, SlotGapTracker},
+    slot_status::ingest_confirmation,
     sqlite_writer::SqliteWriter,
-    trade_detector::extract_trade_info,
-    writer_backend::WriterBackend,
+    subscription,
+    swap_splitter,
+    token_extensions,
+    trade_detector::{
+        apply_instruction_action, drop_intermediate_mints, extract_all_trades, extract_quoted_trades,
+        extract_trade_info,
+    },
+    transfer_direction::resolve_unknown_directions,
+    whirlpool,
+    writer_backend::{WriterBackend, WriterError},
 };
 use async_trait::async_trait;
 use carbon_core::{
@@ -17,11 +58,12 @@ use carbon_core::{
     processor::Processor,
     transaction::TransactionProcessorInputType,
 };
-use carbon_log_metrics::LogMetrics;
+use carbon_yellowstone_grpc_datasource::YellowstoneGrpcGeyserClient;
 use chrono::Utc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
 
 #[path = "../empty_decoder.rs"]
 mod empty_decoder;
@@ -32,6 +74,11 @@ use empty_decoder::EmptyDecoderCollection;
 /// Phase 4.2: Dual-channel streaming helper
 fn convert_to_pipeline_event(
     event: &TradeEvent,
+    trade_index: u16,
+    slot: u64,
+    fee_lamports: u64,
+    confirmation: Confirmation,
+    dca_order: Option<crate::pipeline::types::DcaOrderInfo>,
 ) -> crate::pipeline::types::TradeEvent {
     use crate::pipeline::types::TradeDirection;
     
@@ -48,6 +95,14 @@ fn convert_to_pipeline_event(
         token_decimals: event.token_decimals,
         user_account: event.user_account.clone().unwrap_or_default(),
         source_program: event.program_name.clone(),
+        signature: event.signature.clone(),
+        trade_index,
+        slot,
+        fee_lamports,
+        priority_fee_lamports: event.priority_fee_lamports,
+        jito_tip_lamports: event.jito_tip_lamports,
+        confirmation,
+        dca_order,
     }
 }
 
@@ -63,6 +118,10 @@ struct TradeProcessor {
     enable_jsonl: bool,
     /// Blocklist checker for GRPC-level filtering
     blocklist_checker: Option<BlocklistChecker>,
+    /// Process-wide pubkey → address/classification cache
+    account_cache: Arc<AccountKeyCache>,
+    /// Commitment pipeline trades are tagged with (the subscription's)
+    confirmation: Confirmation,
 }
 
 impl TradeProcessor {
@@ -75,6 +134,8 @@ impl TradeProcessor {
             send_count: Arc::new(AtomicU64::new(0)),
             enable_jsonl,
             blocklist_checker,
+            account_cache: account_cache::shared(),
+            confirmation: Confirmation::Confirmed,
         }
     }
 }
@@ -86,13 +147,51 @@ impl Processor for TradeProcessor {
     async fn process(
         &mut self,
         (metadata, _instructions, _): Self::InputType,
-        _metrics: Arc<MetricsCollection>,
+        metrics: Arc<MetricsCollection>,
     ) -> CarbonResult<()> {
         let account_keys = build_full_account_keys(&metadata, &metadata.meta);
         let sol_deltas = extract_sol_changes(&metadata.meta, &account_keys);
+        // Fee schedules of unseen Token-2022 mints, for later transactions
+        token_extensions::shared().prefetch(&metadata.meta);
         let token_deltas = extract_token_changes(&metadata.meta, &account_keys);
+        let landing_fees = compute_budget::landing_fees(&metadata, &account_keys);
+        let funding_edges = funding::record_funding(&metadata, &account_keys);
+        if funding_edges > 0 {
+            metrics.increment_counter(FUNDING_EDGES, funding_edges as u64).await?;
+        }
+
+        // Bundled swaps become one trade each; otherwise one trade per transaction
+        let mut trades = match swap_splitter::split_swaps(&metadata, &account_keys) {
+            Some(trades) => {
+                metrics.increment_counter(SPLIT_SWAP_TRADES, trades.len() as u64).await?;
+                trades
+            }
+            None => {
+                let quote_changes = extract_quote_changes(&metadata.meta, &metadata.fee_payer);
+                extract_quoted_trades(
+                    &quote_changes,
+                    &token_deltas,
+                    metadata.fee_payer,
+                    Utc::now().timestamp(),
+                )
+                .into_iter()
+                .next()
+                .or_else(|| extract_trade_info(&sol_deltas, &token_deltas, &account_keys))
+                .into_iter()
+                .collect()
+            }
+        };
+        let inferred = resolve_unknown_directions(&mut trades, &metadata, &account_keys);
+        if inferred > 0 {
+            metrics.increment_counter(DIRECTIONS_INFERRED, inferred as u64).await?;
+        }
+
+        for (trade_index, trade_info) in trades.into_iter().enumerate() {
+            let Ok(trade_index) = u16::try_from(trade_index) else {
+                log::warn!("⚠️  Too many trades in {}, dropping the rest", metadata.signature);
+                break;
+            };
 
-        if let Some(trade_info) = extract_trade_info(&sol_deltas, &token_deltas, &account_keys) {
             // CRITICAL: Check blocklist BEFORE any processing
             // This is the earliest point in the pipeline - if blocked, discard immediately
             if let Some(ref checker) = self.blocklist_checker {
@@ -101,7 +200,8 @@ impl Processor for TradeProcessor {
                         // Token is blocked - discard trade event immediately
                         // No aggregation, no metrics, no DB writes, no WebSocket push
                         log::debug!("🚫 Blocked token detected, discarding: {}", trade_info.mint);
-                        return Ok(());
+                        metrics.increment_counter(BLOCKLIST_HITS, 1).await?;
+                        continue;
                     }
                     Ok(false) => {
                         // Token is allowed - continue processing
@@ -114,6 +214,7 @@ impl Processor for TradeProcessor {
                 }
             }
 
+            metrics.increment_counter(TRADES_EXTRACTED, 1).await?;
             let discriminator = extract_discriminator_hex(&metadata);
 
             let event = TradeEvent {
@@ -126,14 +227,26 @@ impl Processor for TradeProcessor {
                 sol_amount: trade_info.sol_amount,
                 token_amount: trade_info.token_amount,
                 token_decimals: trade_info.token_decimals,
-                user_account: trade_info.user_account.map(|pk| pk.to_string()),
+                user_account: trade_info
+                    .user_account
+                    .map(|pk| self.account_cache.address(&pk).to_string()),
                 discriminator,
+                focus_wallet: None,
+                priority_fee_lamports: landing_fees.priority_fee_lamports,
+                jito_tip_lamports: landing_fees.jito_tip_lamports,
             };
 
             // Phase 4.2 Primary Path: Send to pipeline channel (non-blocking)
             // This ALWAYS happens regardless of JSONL setting
             if let Some(tx) = &self.pipeline_tx {
-                let pipeline_event = convert_to_pipeline_event(&event);
+                let pipeline_event = convert_to_pipeline_event(
+                    &event,
+                    trade_index,
+                    metadata.slot,
+                    metadata.meta.fee,
+                    self.confirmation,
+                    None,
+                );
                 
                 // try_send is non-blocking - never impacts streamer performance
                 if tx.try_send(pipeline_event).is_ok() {
@@ -143,6 +256,7 @@ impl Processor for TradeProcessor {
                         log::info!("📊 Pipeline ingestion active: {} trades sent", count);
                     }
                 } else {
+                    metrics.increment_counter(CHANNEL_SEND_FAILURES, 1).await?;
                     // Channel full or closed - log only once per 1000 failures
                     static FAILURE_COUNT: AtomicU64 = AtomicU64::new(0);
                     let failures = FAILURE_COUNT.fetch_add(1, Ordering::Relaxed);
@@ -174,6 +288,13 @@ impl Processor for TradeProcessor {
     }
 }
 
+/// Flush buffered writer output (JSONL lines, SQLite/Postgres batches) before exit
+async fn flush_writer(processor: &UnifiedTradeProcessor) {
+    if let Err(e) = processor.writer.lock().await.flush().await {
+        log::error!("❌ Failed to flush writer on shutdown: {:?}", e);
+    }
+}
+
 fn extract_discriminator_hex(metadata: &carbon_core::transaction::TransactionMetadata) -> String {
     let message = &metadata.message;
     
@@ -186,11 +307,42 @@ fn extract_discriminator_hex(metadata: &carbon_core::transaction::TransactionMet
     "0000000000000000".to_string()
 }
 
-pub async fn run(streamer_config: StreamerConfig) -> Result<(), Box<dyn std::error::Error>> {
+#[cfg(feature = "kafka")]
+fn kafka_writer(brokers: &str) -> Result<Box<dyn WriterBackend>, WriterError> {
+    Ok(Box::new(super::kafka_writer::KafkaWriter::new(brokers)?))
+}
+
+#[cfg(not(feature = "kafka"))]
+fn kafka_writer(_brokers: &str) -> Result<Box<dyn WriterBackend>, WriterError> {
+    Err(WriterError::Publish(
+        "Kafka backend requires building with --features kafka".to_string(),
+    ))
+}
+
+/// Start the SOL/USD price refresh once per process (stablecoin-quoted trades)
+fn start_sol_usd_refresh() {
+    static STARTED: std::sync::Once = std::sync::Once::new();
+    if let Some(interval) = sol_usd_refresh_interval() {
+        STARTED.call_once(|| {
+            tokio::spawn(dexscreener::run_sol_usd_refresh(interval));
+        });
+    }
+}
+
+pub async fn run(streamer_config: StreamerConfig) -> Result<(), SolflowError> {
     streamer_config.validate()?;
+    start_sol_usd_refresh();
     
     let runtime_config = RuntimeConfig::from_env()?;
 
+    // Per-program streamers are gRPC-only; the RPC fallback is unified-mode only
+    if runtime_config.datasource != DatasourceKind::Grpc {
+        return Err(SolflowError::config(
+            codes::CONFIG_INVALID,
+            "RPC fallback datasource requires the unified streamer (USE_UNIFIED_STREAMER=true)",
+        ));
+    }
+
     // Skip logger init if running inside pipeline_runtime (already initialized)
     if std::env::var("ENABLE_PIPELINE").unwrap_or_default() != "true" {
         env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(&runtime_config.rust_log))
@@ -201,8 +353,8 @@ pub async fn run(streamer_config: StreamerConfig) -> Result<(), Box<dyn std::err
 
     log::info!("🚀 Starting {} streamer", streamer_config.program_name);
     log::info!("   Program ID: {}", streamer_config.program_id);
-    log::info!("   Output: {}", streamer_config.output_path);
-    log::info!("   Geyser URL: {}", runtime_config.geyser_url);
+    log::info!("   Output: {}", redact_url(&streamer_config.output_path));
+    log::info!("   Geyser URL: {}", redact_url(&runtime_config.geyser_url));
     log::info!("   Commitment: {:?}", runtime_config.commitment_level);
 
     // Initialize blocklist checker (GRPC-level filtering)
@@ -245,32 +397,33 @@ pub async fn run(streamer_config: StreamerConfig) -> Result<(), Box<dyn std::err
         BackendType::Sqlite => {
             Box::new(SqliteWriter::new(&streamer_config.output_path)?)
         }
+        BackendType::Postgres => {
+            Box::new(PostgresWriter::connect(&streamer_config.output_path).await?)
+        }
+        BackendType::Kafka => kafka_writer(&streamer_config.output_path)?,
     };
     
     log::info!("📊 Backend: {}", writer.backend_type());
 
-    let processor = TradeProcessor::new(
+    let mut processor = TradeProcessor::new(
         streamer_config.clone(), 
         writer, 
         runtime_config.enable_jsonl,
         blocklist_checker.clone()
     );
+    processor.confirmation = ingest_confirmation(runtime_config.commitment_level);
 
     run_with_reconnect(&runtime_config, &streamer_config.program_id, move |client| {
         let proc = processor.clone();
         async move {
-            let result: Result<(), Box<dyn std::error::Error + Send + Sync>> = async {
-                Pipeline::builder()
-                    .datasource(client)
-                    .metrics(Arc::new(LogMetrics::new()))
+            let result: Result<(), SolflowError> = async {
+                with_pipeline_metrics(Pipeline::builder().datasource(client))
                     .metrics_flush_interval(3)
                     .transaction::<EmptyDecoderCollection, ()>(proc, None)
                 .shutdown_strategy(ShutdownStrategy::Immediate)
-                .build()
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?
+                .build()?
                 .run()
-                .await
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+                .await?;
             Ok(())
             }.await;
             result
@@ -288,12 +441,39 @@ pub async fn run(streamer_config: StreamerConfig) -> Result<(), Box<dyn std::err
 /// processing balance deltas.
 #[derive(Clone)]
 struct UnifiedTradeProcessor {
-    scanner: InstructionScanner,
+    /// Swapped when the registry file changes (see `program_registry`)
+    scanner: SharedScanner,
     writer: Arc<Mutex<Box<dyn WriterBackend>>>,
     pipeline_tx: Option<mpsc::Sender<crate::pipeline::types::TradeEvent>>,
     send_count: Arc<AtomicU64>,
     enable_jsonl: bool,
     blocklist_checker: Option<BlocklistChecker>,
+    /// Resolves Jupiter DCA fills to their order accounts (None if SOLANA_RPC_URL unset)
+    dca_resolver: Option<DcaOrderResolver>,
+    /// Cross-subscription signature dedup (RPC fallback datasource only)
+    signature_dedup: Option<Arc<std::sync::Mutex<SignatureDedup>>>,
+    /// Full transaction capture for mints armed by severe signals
+    anomaly_capture: Option<AnomalyCapture>,
+    /// Wait for pipeline channel capacity instead of dropping (backfill)
+    blocking_send: bool,
+    /// Watched mints bypass the batch channel (see `pipeline::fast_path`)
+    fast_path: Option<FastPathRoute>,
+    /// Wallets whose trades are tagged (FOCUS_WALLETS, see `focus_wallets`)
+    focus_wallets: Option<Arc<FocusWallets>>,
+    /// Process-wide pubkey → address/classification cache
+    account_cache: Arc<AccountKeyCache>,
+    /// Commitment pipeline trades are tagged with (the subscription's)
+    confirmation: Confirmation,
+    /// Curve-to-AMM migrations for the engine (see `pumpfun`)
+    migration_tx: Option<mpsc::Sender<TokenMigration>>,
+    /// Pool vaults for the liquidity tracker (see `liquidity`)
+    vault_tx: Option<mpsc::Sender<PoolVault>>,
+    /// Pool state accounts for on-chain prices (see `pool_state`)
+    pool_tx: Option<mpsc::Sender<PoolAccount>>,
+    /// Live slot sequence, shared across reconnects (see `slot_gaps`)
+    slot_gaps: Option<Arc<std::sync::Mutex<SlotGapTracker>>>,
+    /// Backfill detected gaps over RPC (SLOT_GAP_BACKFILL)
+    gap_backfill: bool,
 }
 
 impl UnifiedTradeProcessor {
@@ -303,16 +483,94 @@ impl UnifiedTradeProcessor {
         enable_jsonl: bool,
         blocklist_checker: Option<BlocklistChecker>,
         pipeline_tx: Option<mpsc::Sender<crate::pipeline::types::TradeEvent>>,
+        dca_resolver: Option<DcaOrderResolver>,
+        signature_dedup: Option<SignatureDedup>,
+        anomaly_capture: Option<AnomalyCapture>,
     ) -> Self {
         Self {
-            scanner,
+            scanner: SharedScanner::new(scanner),
             writer: Arc::new(Mutex::new(writer)),
             pipeline_tx,
             send_count: Arc::new(AtomicU64::new(0)),
             enable_jsonl,
             blocklist_checker,
+            dca_resolver,
+            signature_dedup: signature_dedup.map(|d| Arc::new(std::sync::Mutex::new(d))),
+            anomaly_capture,
+            blocking_send: false,
+            fast_path: None,
+            focus_wallets: None,
+            account_cache: account_cache::shared(),
+            confirmation: Confirmation::Confirmed,
+            migration_tx: None,
+            vault_tx: None,
+            pool_tx: None,
+            slot_gaps: None,
+            gap_backfill: false,
         }
     }
+
+    /// DATA_GAP: record and report a gap in the live stream, backfilling it if enabled
+    async fn report_slot_gap(&mut self, gap: SlotGap, metrics: &MetricsCollection) -> CarbonResult<()> {
+        log::warn!(
+            "⚠️  DATA_GAP: slots {}..={} missed ({} slots, {}s)",
+            gap.start_slot,
+            gap.end_slot,
+            gap.slots(),
+            gap.end_time - gap.start_time
+        );
+        metrics.increment_counter(DATA_GAPS, 1).await?;
+        metrics.increment_counter(DATA_GAP_SLOTS, gap.slots()).await?;
+        slot_gaps::shared().record(gap);
+
+        if self.gap_backfill {
+            self.spawn_gap_backfill(gap);
+        }
+        Ok(())
+    }
+
+    /// Re-fetch a gap's transactions over RPC, then close it
+    fn spawn_gap_backfill(&self, gap: SlotGap) {
+        let config = match BackfillConfig::for_slots(gap.start_slot, gap.end_slot) {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("⚠️  DATA_GAP backfill skipped: {}", e);
+                return;
+            }
+        };
+        let mut processor = self.clone();
+        processor.blocking_send = true;
+        processor.fast_path = None;
+        processor.vault_tx = None; // Historical balances are stale
+        processor.slot_gaps = None; // Backfill runs oldest first per program
+        processor.signature_dedup = None;
+
+        tokio::spawn(async move {
+            let result: Result<(), SolflowError> = async {
+                with_pipeline_metrics(Pipeline::builder().datasource(BackfillDatasource::new(config)))
+                    .metrics_flush_interval(3)
+                    .transaction::<EmptyDecoderCollection, ()>(processor, None)
+                    .shutdown_strategy(ShutdownStrategy::Immediate)
+                    .build()?
+                    .run()
+                    .await?;
+                Ok(())
+            }
+            .await;
+            match result {
+                Ok(()) => {
+                    slot_gaps::shared().resolve(&gap);
+                    log::info!("✅ DATA_GAP slots {}..={} backfilled", gap.start_slot, gap.end_slot);
+                }
+                Err(e) => log::error!(
+                    "❌ DATA_GAP backfill of slots {}..={} failed: {}",
+                    gap.start_slot,
+                    gap.end_slot,
+                    e
+                ),
+            }
+        });
+    }
 }
 
 #[async_trait]
@@ -322,10 +580,27 @@ impl Processor for UnifiedTradeProcessor {
     async fn process(
         &mut self,
         (metadata, _instructions, _): Self::InputType,
-        _metrics: Arc<MetricsCollection>,
+        metrics: Arc<MetricsCollection>,
     ) -> CarbonResult<()> {
+        // RPC fallback delivers a transaction once per matching program subscription
+        if let Some(dedup) = &self.signature_dedup {
+            if !dedup.lock().unwrap().insert(metadata.signature) {
+                return Ok(());
+            }
+        }
+
+        // Every delivered transaction advances the slot sequence, matched or not
+        if let Some(tracker) = &self.slot_gaps {
+            let block_time = metadata.block_time.unwrap_or_else(|| Utc::now().timestamp());
+            let gap = tracker.lock().unwrap().observe(metadata.slot, block_time);
+            if let Some(gap) = gap {
+                self.report_slot_gap(gap, &metrics).await?;
+            }
+        }
+
         // STEP 1: Scan for tracked programs (NEW - FILTERING LAYER)
-        let program_match = match self.scanner.scan(&metadata) {
+        let scanner = self.scanner.current();
+        let program_match = match scanner.scan(&metadata) {
             Some(m) => m,
             None => {
                 // No tracked program found - discard transaction immediately
@@ -345,27 +620,119 @@ impl Processor for UnifiedTradeProcessor {
         // STEP 2: Extract balance deltas (UNCHANGED)
         let account_keys = build_full_account_keys(&metadata, &metadata.meta);
         let sol_deltas = extract_sol_changes(&metadata.meta, &account_keys);
+        // Fee schedules of unseen Token-2022 mints, for later transactions
+        token_extensions::shared().prefetch(&metadata.meta);
         let token_deltas = extract_token_changes(&metadata.meta, &account_keys);
+        let landing_fees = compute_budget::landing_fees(&metadata, &account_keys);
+        let funding_edges = funding::record_funding(&metadata, &account_keys);
+        if funding_edges > 0 {
+            metrics.increment_counter(FUNDING_EDGES, funding_edges as u64).await?;
+        }
 
-        // STEP 3: Extract ALL trades (MULTI-MINT SUPPORT)
-        let all_trades = crate::streamer_core::trade_detector::extract_all_trades(
-            &sol_deltas,
-            &token_deltas,
-            &account_keys,
-        );
+        // Curve-to-AMM migrations go to the engine as TOKEN_MIGRATED signals
+        if let Some(tx) = &self.migration_tx {
+            for migration in pumpfun::migrations(&metadata, &account_keys) {
+                if tx.try_send(migration).is_ok() {
+                    metrics.increment_counter(TOKEN_MIGRATIONS, 1).await?;
+                }
+            }
+        }
+
+        // STEP 3: Extract ALL trades (one per decoded swap event or bundled swap, else one per mint)
+        let decoded = jupiter_route::extract_route_trades(&metadata, &account_keys)
+            .map(|trades| (JUPITER_ROUTE_TRADES, trades))
+            .or_else(|| {
+                meteora::extract_swap_trades(&metadata, &account_keys)
+                    .map(|trades| (METEORA_SWAP_TRADES, trades))
+            })
+            .or_else(|| {
+                pumpfun::extract_bonding_curve_trades(&metadata, &account_keys)
+                    .map(|trades| (PUMPFUN_CURVE_TRADES, trades))
+            })
+            .or_else(|| {
+                swap_splitter::split_swaps(&metadata, &account_keys)
+                    .map(|trades| (SPLIT_SWAP_TRADES, trades))
+            });
+        let mut all_trades = match decoded {
+            Some((counter, trades)) => {
+                metrics.increment_counter(counter, trades.len() as u64).await?;
+                trades
+            }
+            None => {
+                // Wallets paying from WSOL/USDC/USDT accounts show no native SOL flow
+                let quote_changes = extract_quote_changes(&metadata.meta, &metadata.fee_payer);
+                let mut trades = extract_quoted_trades(
+                    &quote_changes,
+                    &token_deltas,
+                    metadata.fee_payer,
+                    Utc::now().timestamp(),
+                );
+                if trades.is_empty() {
+                    trades = extract_all_trades(&sol_deltas, &token_deltas, &account_keys);
+                }
+                let hops = whirlpool::two_hop_intermediate_mints(&metadata, &account_keys);
+                drop_intermediate_mints(&mut trades, &hops);
+                // A buy/sell instruction names the direction better than SOL flow
+                let corrected = apply_instruction_action(&mut trades, program_match.action);
+                if corrected > 0 {
+                    metrics.increment_counter(DISCRIMINATOR_DIRECTIONS, corrected as u64).await?;
+                }
+                trades
+            }
+        };
+
+        // Resolve Unknown directions from SPL/SOL transfers (otherwise excluded from net flow)
+        let inferred = resolve_unknown_directions(&mut all_trades, &metadata, &account_keys);
+        if inferred > 0 {
+            metrics.increment_counter(DIRECTIONS_INFERRED, inferred as u64).await?;
+        }
 
         // Early exit if no trades found
         if all_trades.is_empty() {
             return Ok(());
         }
 
+        // Pools the trades went through feed the liquidity tracker
+        if let Some(tx) = &self.vault_tx {
+            let mints: Vec<&str> = all_trades.iter().map(|t| t.mint.as_str()).collect();
+            for vault in liquidity::pool_vaults(&metadata, &account_keys, &mints) {
+                if tx.try_send(vault).is_ok() {
+                    metrics.increment_counter(POOL_VAULTS_FOUND, 1).await?;
+                }
+            }
+        }
+
+        // ...and their state accounts feed the pool price tracker
+        if let Some(tx) = &self.pool_tx {
+            let mints: Vec<&str> = all_trades.iter().map(|t| t.mint.as_str()).collect();
+            for pool in pool_state::pool_accounts(&metadata, &account_keys, &mints) {
+                if tx.try_send(pool).is_ok() {
+                    metrics.increment_counter(POOL_STATE_ACCOUNTS_FOUND, 1).await?;
+                }
+            }
+        }
+
+        // Cached DCA order behind Jupiter DCA fills; misses resolve in the background (pipeline only)
+        let dca_order = match (&self.dca_resolver, &self.pipeline_tx) {
+            (Some(resolver), Some(_)) if program_match.program_name == "JupiterDCA" => {
+                resolver.resolve(&metadata, &account_keys)
+            }
+            _ => None,
+        };
+
         // STEP 4-6: Process each trade (one event per mint)
-        for trade_info in all_trades {
+        for (trade_index, trade_info) in all_trades.into_iter().enumerate() {
+            let Ok(trade_index) = u16::try_from(trade_index) else {
+                log::warn!("⚠️  Too many trades in {}, dropping the rest", metadata.signature);
+                break;
+            };
+
             // STEP 4: Blocklist check (UNCHANGED)
             if let Some(ref checker) = self.blocklist_checker {
                 match checker.is_blocked(&trade_info.mint) {
                     Ok(true) => {
                         log::debug!("🚫 Blocked token: {}", trade_info.mint);
+                        metrics.increment_counter(BLOCKLIST_HITS, 1).await?;
                         continue; // Skip this mint, process others
                     }
                     Ok(false) => {}
@@ -375,31 +742,96 @@ impl Processor for UnifiedTradeProcessor {
                 }
             }
 
+            metrics.increment_counter(TRADES_EXTRACTED, 1).await?;
+
+            // Capture-on-anomaly: full transaction record while the mint is armed
+            if let Some(capture) = &self.anomaly_capture {
+                let now = metadata.block_time.unwrap_or_else(|| Utc::now().timestamp());
+                if capture.is_armed(&trade_info.mint, now) {
+                    let record = build_transaction_capture(
+                        &metadata,
+                        CaptureMetadata {
+                            program_id: self.account_cache.address(&program_match.program_id).to_string(),
+                            program_name: program_match.program_name.to_string(),
+                            capture_tool_version: env!("CARGO_PKG_VERSION").to_string(),
+                            captured_at: Utc::now().timestamp(),
+                        },
+                    );
+                    if let Err(e) = capture.write(&trade_info.mint, &record).await {
+                        log::warn!("⚠️  Anomaly capture write failed for {}: {}", trade_info.mint, e);
+                    }
+                }
+            }
+
             let discriminator = extract_discriminator_hex(&metadata);
 
+            let focus_wallet = self.focus_wallets.as_ref().and_then(|focus| {
+                focus
+                    .tag(trade_info.user_account.as_ref(), &account_keys)
+                    .map(|wallet| self.account_cache.address(&wallet).to_string())
+            });
+
+            let user_account = trade_info.user_account.map(|pk| self.account_cache.lookup(&pk));
+            if let Some(cached) = user_account.as_ref().filter(|c| c.class != AccountClass::Wallet) {
+                log::debug!(
+                    "Trade user for {} is a known {:?} account {} (signature: {})",
+                    trade_info.mint,
+                    cached.class,
+                    cached.address,
+                    metadata.signature
+                );
+            }
+
+            // Route legs on a tracked AMM are attributed to it rather than the first match
+            let (program_id, program_name) = trade_info
+                .program_id
+                .and_then(|id| scanner.program_name(&id).map(|name| (id, name)))
+                .unwrap_or((program_match.program_id, program_match.program_name));
+
             // STEP 5: Create trade event (UPDATED WITH MATCHED PROGRAM)
             let event = TradeEvent {
                 timestamp: metadata.block_time.unwrap_or_else(|| Utc::now().timestamp()),
                 signature: metadata.signature.to_string(),
-                program_id: program_match.program_id.to_string(),
-                program_name: program_match.program_name.to_string(), // From scanner
+                program_id: self.account_cache.address(&program_id).to_string(),
+                program_name: program_name.to_string(), // From scanner
                 action: <&str>::from(trade_info.direction).to_string(),
                 mint: trade_info.mint.clone(),
                 sol_amount: trade_info.sol_amount,
                 token_amount: trade_info.token_amount,
                 token_decimals: trade_info.token_decimals,
-                user_account: trade_info.user_account.map(|pk| pk.to_string()),
+                user_account: user_account.map(|cached| cached.address.to_string()),
                 discriminator,
+                focus_wallet,
+                priority_fee_lamports: landing_fees.priority_fee_lamports,
+                jito_tip_lamports: landing_fees.jito_tip_lamports,
             };
 
             // STEP 6: Write to pipeline + JSONL (UNCHANGED)
             if let Some(tx) = &self.pipeline_tx {
-                let pipeline_event = convert_to_pipeline_event(&event);
-                if tx.try_send(pipeline_event).is_ok() {
+                let pipeline_event = convert_to_pipeline_event(
+                    &event,
+                    trade_index,
+                    metadata.slot,
+                    metadata.meta.fee,
+                    self.confirmation,
+                    dca_order.clone(),
+                );
+                let routed = match &self.fast_path {
+                    Some(route) => route.try_route(pipeline_event),
+                    None => Err(pipeline_event),
+                };
+                let sent = match routed {
+                    Ok(()) => true,
+                    Err(pipeline_event) if self.blocking_send => tx.send(pipeline_event).await.is_ok(),
+                    Err(pipeline_event) => tx.try_send(pipeline_event).is_ok(),
+                };
+                if sent {
                     let count = self.send_count.fetch_add(1, Ordering::Relaxed);
                     if count > 0 && count % 10_000 == 0 {
                         log::info!("📊 Pipeline ingestion: {} trades sent", count);
                     }
+                } else {
+                    metrics.increment_counter(CHANNEL_SEND_FAILURES, 1).await?;
                 }
             }
 
@@ -432,8 +864,33 @@ impl Processor for UnifiedTradeProcessor {
 pub async fn run_unified(
     streamer_config: StreamerConfig,
     scanner: InstructionScanner,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), SolflowError> {
+    run_unified_with_capture(streamer_config, scanner, None, None, None, None, None, None).await
+}
+
+/// Run the unified streamer, capturing full transactions for armed mints
+///
+/// `anomaly_capture` is shared with the `PipelineEngine`, which arms mints
+/// when severe signals fire (see `meta_analysis::anomaly_capture`).
+/// `fast_path` sends live trades for watched mints to the fast channel
+/// instead of `pipeline_tx` (see `pipeline::fast_path`).
+/// `shutdown` stops the datasource, lets pending updates finish and flushes
+/// the writer before returning (see `pipeline::shutdown`).
+/// `migration_tx` receives pump.fun curve-to-PumpSwap migrations (see `pumpfun`).
+/// `vault_tx` receives the pool vaults trades went through (see `liquidity`).
+/// `pool_tx` receives the pool state accounts trades went through (see `pool_state`).
+pub async fn run_unified_with_capture(
+    streamer_config: StreamerConfig,
+    scanner: InstructionScanner,
+    anomaly_capture: Option<AnomalyCapture>,
+    fast_path: Option<FastPathRoute>,
+    shutdown: Option<ShutdownSignal>,
+    migration_tx: Option<mpsc::Sender<TokenMigration>>,
+    vault_tx: Option<mpsc::Sender<PoolVault>>,
+    pool_tx: Option<mpsc::Sender<PoolAccount>>,
+) -> Result<(), SolflowError> {
     streamer_config.validate()?;
+    start_sol_usd_refresh();
 
     let runtime_config = RuntimeConfig::from_env()?;
 
@@ -477,57 +934,193 @@ pub async fn run_unified(
         BackendType::Sqlite => {
             Box::new(SqliteWriter::new(&streamer_config.output_path)?)
         }
+        BackendType::Postgres => {
+            Box::new(PostgresWriter::connect(&streamer_config.output_path).await?)
+        }
+        BackendType::Kafka => kafka_writer(&streamer_config.output_path)?,
     };
 
     log::info!("📊 Backend: {}", writer.backend_type());
 
     let pipeline_tx = streamer_config.pipeline_tx.clone();
 
-    let processor = UnifiedTradeProcessor::new(
+    // Initialize DCA order resolver
+    let dca_resolver = DcaOrderResolver::from_env();
+    if dca_resolver.is_some() {
+        log::info!("✅ DCA order resolver enabled (SOLANA_RPC_URL)");
+    } else {
+        log::info!("ℹ️  DCA order resolver disabled (SOLANA_RPC_URL not set)");
+    }
+
+    let mut processor = UnifiedTradeProcessor::new(
         scanner,
         writer,
         runtime_config.enable_jsonl,
         blocklist_checker,
         pipeline_tx,
+        dca_resolver,
+        runtime_config
+            .datasource
+            .is_reduced_fidelity()
+            .then(SignatureDedup::new),
+        anomaly_capture,
     );
+    processor.fast_path = fast_path;
+    processor.migration_tx = migration_tx;
+    processor.vault_tx = vault_tx;
+    processor.pool_tx = pool_tx;
+    processor.focus_wallets = FocusWallets::new(&runtime_config.focus_wallets).map(Arc::new);
+    processor.confirmation = ingest_confirmation(runtime_config.commitment_level);
+
+    // Warm the engine up with history before going live (see backfill)
+    if let Some(backfill_config) = BackfillConfig::from_env()? {
+        let mut backfill_processor = processor.clone();
+        backfill_processor.blocking_send = true;
+        backfill_processor.fast_path = None; // History has no latency budget
+        backfill_processor.vault_tx = None; // Historical balances are stale
+        let cancellation_token = CancellationToken::new();
 
-    // Create multi-program gRPC client and run with reconnect logic
+        with_pipeline_metrics(Pipeline::builder().datasource(BackfillDatasource::new(backfill_config)))
+            .metrics_flush_interval(3)
+            .transaction::<EmptyDecoderCollection, ()>(backfill_processor, None)
+            .shutdown_strategy(ShutdownStrategy::Immediate)
+            .datasource_cancellation_token(cancellation_token.clone())
+            .build()?
+            .run()
+            .await?;
+
+        // Ctrl+C during backfill stops the streamer rather than going live
+        if cancellation_token.is_cancelled() {
+            return Ok(());
+        }
+    }
+
+    // Live slot sequence is checked for gaps (see slot_gaps)
+    if let Some(gap_config) = SlotGapConfig::from_env().filter(|_| slot_gaps::subscription_is_dense(&runtime_config)) {
+        processor.slot_gaps = Some(Arc::new(std::sync::Mutex::new(SlotGapTracker::new(gap_config.threshold))));
+        processor.gap_backfill = gap_config.backfill;
+    }
+
+    log::info!("📡 Datasource: {}", runtime_config.datasource);
+
+    // Coordinated shutdown cancels the datasource of every connection attempt
+    let stop = CancellationToken::new();
+    let graceful = shutdown.is_some();
+    if let Some(mut signal) = shutdown {
+        let stop = stop.clone();
+        tokio::spawn(async move {
+            signal.recv().await;
+            stop.cancel();
+        });
+    }
+
+    // Create multi-program datasource(s) and run with reconnect logic
     let mut backoff = crate::streamer_core::error_handler::ExponentialBackoff::new(5, 60, 10);
 
+    // Each connection goes to the healthiest Yellowstone endpoint (see endpoints)
+    let endpoints = EndpointPool::from_config(&runtime_config);
+    endpoints.spawn_health_checks(&runtime_config, stop.clone());
+
+    // Filters can change at runtime without reconnecting (see subscription)
+    let subscription = match runtime_config.datasource {
+        DatasourceKind::Grpc => {
+            let registry = ProgramRegistry::from_env()
+                .map_err(|e| SolflowError::config(codes::CONFIG_INVALID, e))?;
+            let programs = registry.subscription_programs();
+            Some(subscription::install(&runtime_config, programs).map_err(ClientError::from)?)
+        }
+        DatasourceKind::RpcBlockSubscribe => None,
+    };
+    program_registry::spawn_registry_reload(processor.scanner.clone(), subscription.clone(), stop.clone());
+    let with_updates = |client: YellowstoneGrpcGeyserClient| match &subscription {
+        Some(control) => client.with_filter_updates(control.subscribe()),
+        None => client,
+    };
+
     loop {
-        match create_multi_program_client(&runtime_config).await {
-            Ok(client) => {
-                log::info!("✅ Connected to gRPC server (multi-program filter)");
+        if stop.is_cancelled() {
+            log::info!("🛑 Unified streamer stopped (shutdown)");
+            flush_writer(&processor).await;
+            return Ok(());
+        }
+
+        let endpoint = endpoints.select(std::time::Instant::now());
+        let builder = match runtime_config.datasource {
+            DatasourceKind::Grpc => {
+                let primary = create_multi_program_client(&endpoints.config_for(&runtime_config, endpoint))
+                    .await
+                    .map(with_updates);
+                // A second provider is merged in rather than failed over to (see redundancy)
+                match (primary, runtime_config.secondary()) {
+                    (Ok(primary), Some(secondary)) => create_multi_program_client(&secondary)
+                        .await
+                        .map(|secondary| {
+                            Pipeline::builder().datasource(RedundantDatasource::new(primary, with_updates(secondary)))
+                        }),
+                    (primary, _) => primary.map(|client| Pipeline::builder().datasource(client)),
+                }
+            }
+            DatasourceKind::RpcBlockSubscribe => create_multi_program_rpc_datasources(&runtime_config)
+                .map(|sources| {
+                    sources
+                        .into_iter()
+                        .fold(Pipeline::builder(), |builder, source| builder.datasource(source))
+                }),
+        };
+
+        match builder {
+            Ok(builder) => {
+                log::info!("✅ Connected to {} datasource (multi-program filter)", runtime_config.datasource);
                 backoff.reset();
 
+                // Cancelled on shutdown, or when a healthier endpoint takes over
+                let connection = stop.child_token();
+                let failed_over = watch_for_failover(endpoints.clone(), endpoint, connection.clone());
+
                 let proc = processor.clone();
-                let result: Result<(), Box<dyn std::error::Error + Send + Sync>> = async {
-                    Pipeline::builder()
-                        .datasource(client)
-                        .metrics(Arc::new(LogMetrics::new()))
+                let result: Result<(), SolflowError> = async {
+                    with_pipeline_metrics(builder)
                         .metrics_flush_interval(3)
                         .transaction::<EmptyDecoderCollection, ()>(proc, None)
-                        .shutdown_strategy(ShutdownStrategy::Immediate)
-                        .build()
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?
+                        .shutdown_strategy(if graceful {
+                            ShutdownStrategy::ProcessPending
+                        } else {
+                            ShutdownStrategy::Immediate
+                        })
+                        .datasource_cancellation_token(connection.clone())
+                        .build()?
                         .run()
-                        .await
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+                        .await?;
                     Ok(())
                 }
                 .await;
+                connection.cancel(); // Stops the failover watch
 
-                if let Err(e) = result {
-                    log::error!("❌ Pipeline error: {:?}", e);
-                    backoff.sleep().await.map_err(|_| "Max retries exceeded")?;
-                } else {
-                    log::info!("✅ Pipeline completed gracefully");
-                    return Ok(());
+                match result {
+                    _ if failed_over.load(Ordering::SeqCst) && !stop.is_cancelled() => {
+                        log::info!("🔀 Resubscribing on a healthier endpoint");
+                    }
+                    Err(e) if !stop.is_cancelled() => {
+                        log::error!("❌ Pipeline error: {}", e);
+                        endpoints.record_error(endpoint, std::time::Instant::now());
+                        backoff.sleep().await?;
+                    }
+                    _ => {
+                        log::info!("✅ Pipeline completed gracefully");
+                        flush_writer(&processor).await;
+                        return Ok(());
+                    }
                 }
             }
             Err(e) => {
-                log::error!("❌ Connection failed: {:?}", e);
-                backoff.sleep().await.map_err(|_| "Max retries exceeded")?;
+                let e = SolflowError::from(e);
+                // A bad filter or endpoint config won't fix itself on retry
+                if e.category() == ErrorCategory::Config {
+                    return Err(e);
+                }
+                log::error!("❌ Connection failed: {}", e);
+                endpoints.record_error(endpoint, std::time::Instant::now());
+                backoff.sleep().await?;
             }
         }
     }
@@ -0,0 +1,346 @@
+//! Pool liquidity tracking from vault account updates
+//!
+//! AMM pools keep their SOL side in a WSOL token account (the vault) owned
+//! by the pool's authority. The streamer learns vaults from the trades it
+//! already sees: a WSOL account in a swap whose owner also holds the traded
+//! mint, other than the fee payer, is a pool vault for that mint. With
+//! `LIQUIDITY_TRACKING_ENABLED=true` the runtime opens a separate account
+//! subscription on the known vaults, re-subscribing as new ones are found,
+//! and reports the SOL pooled per mint to the engine (`liquidity_sol`,
+//! LIQUIDITY_DRAIN).
+//!
+//! Bonding curves hold lamports rather than WSOL, so pre-migration pump.fun
+//! tokens have no tracked liquidity.
+//!
+//! Environment variables:
+//! - `LIQUIDITY_TRACKING_ENABLED`: Track pool vault balances (default: false)
+//! - `LIQUIDITY_MAX_VAULTS`: Most vaults subscribed to; later ones are
+//!   ignored (default: 2000)
+
+use crate::streamer_core::config::{DatasourceKind, RuntimeConfig};
+use carbon_core::transaction::TransactionMetadata;
+use futures::{sink::SinkExt, StreamExt};
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestFilterAccounts,
+    SubscribeRequestPing, SubscribeUpdateAccount,
+};
+use yellowstone_grpc_proto::tonic::transport::ClientTlsConfig;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// SPL token account layout: mint (32), owner (32), amount (u64)
+const TOKEN_ACCOUNT_AMOUNT: std::ops::Range<usize> = 64..72;
+
+const DEFAULT_MAX_VAULTS: usize = 2000;
+
+/// WSOL vault of a pool trading `mint`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolVault {
+    pub mint: String,
+    pub vault: Pubkey,
+    /// WSOL balance after the transaction the vault was found in (lamports)
+    pub amount: u64,
+}
+
+/// SOL pooled across a mint's known vaults
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiquidityUpdate {
+    pub mint: String,
+    pub liquidity_sol: f64,
+    pub slot: u64,
+}
+
+/// Whether the liquidity tracker runs (gRPC datasource and `LIQUIDITY_TRACKING_ENABLED`)
+pub fn tracking_enabled(config: &RuntimeConfig) -> bool {
+    config.datasource == DatasourceKind::Grpc
+        && std::env::var("LIQUIDITY_TRACKING_ENABLED")
+            .map(|v| v.to_lowercase() == "true" || v == "1")
+            .unwrap_or(false)
+}
+
+fn max_vaults() -> usize {
+    std::env::var("LIQUIDITY_MAX_VAULTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&max| max > 0)
+        .unwrap_or(DEFAULT_MAX_VAULTS)
+}
+
+/// WSOL vaults of the pools the traded mints were swapped against
+pub fn pool_vaults(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+    traded_mints: &[&str],
+) -> Vec<PoolVault> {
+    let Some(balances) = metadata.meta.post_token_balances.as_ref() else {
+        return Vec::new();
+    };
+    let fee_payer = metadata.fee_payer.to_string();
+
+    let mut vaults: Vec<PoolVault> = Vec::new();
+    for &mint in traded_mints.iter().filter(|&&mint| mint != WSOL_MINT) {
+        let holds_mint = |owner: &str| balances.iter().any(|b| b.mint == mint && b.owner == owner);
+        for balance in balances
+            .iter()
+            .filter(|b| b.mint == WSOL_MINT && b.owner != fee_payer && holds_mint(&b.owner))
+        {
+            let (Some(vault), Ok(amount)) = (
+                account_keys.get(balance.account_index as usize),
+                balance.ui_token_amount.amount.parse::<u64>(),
+            ) else {
+                continue;
+            };
+            if !vaults.iter().any(|v| v.vault == *vault) {
+                vaults.push(PoolVault {
+                    mint: mint.to_string(),
+                    vault: *vault,
+                    amount,
+                });
+            }
+        }
+    }
+    vaults
+}
+
+/// Known vaults and their latest balances
+#[derive(Debug)]
+pub struct LiquidityTracker {
+    /// Vault → (mint, lamports)
+    vaults: HashMap<Pubkey, (String, u64)>,
+    max_vaults: usize,
+}
+
+impl LiquidityTracker {
+    pub fn new(max_vaults: usize) -> Self {
+        Self {
+            vaults: HashMap::new(),
+            max_vaults,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.vaults.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vaults.is_empty()
+    }
+
+    /// Start tracking a vault; None if it is already known or the tracker is full
+    pub fn add(&mut self, vault: PoolVault, slot: u64) -> Option<LiquidityUpdate> {
+        if self.vaults.contains_key(&vault.vault) || self.vaults.len() >= self.max_vaults {
+            return None;
+        }
+        self.vaults.insert(vault.vault, (vault.mint.clone(), vault.amount));
+        Some(self.liquidity(&vault.mint, slot))
+    }
+
+    /// Apply a vault's new token account data; None unless its balance changed
+    pub fn update(&mut self, vault: &Pubkey, data: &[u8], slot: u64) -> Option<LiquidityUpdate> {
+        let amount = u64::from_le_bytes(data.get(TOKEN_ACCOUNT_AMOUNT)?.try_into().ok()?);
+        let (mint, current) = self.vaults.get_mut(vault)?;
+        if *current == amount {
+            return None;
+        }
+        *current = amount;
+        let mint = mint.clone();
+        Some(self.liquidity(&mint, slot))
+    }
+
+    fn liquidity(&self, mint: &str, slot: u64) -> LiquidityUpdate {
+        let lamports: u64 = self
+            .vaults
+            .values()
+            .filter(|(vault_mint, _)| vault_mint == mint)
+            .map(|(_, amount)| amount)
+            .sum();
+        LiquidityUpdate {
+            mint: mint.to_string(),
+            liquidity_sol: lamports as f64 / LAMPORTS_PER_SOL,
+            slot,
+        }
+    }
+
+    /// Account subscription on every known vault
+    fn subscribe_request(&self, config: &RuntimeConfig) -> SubscribeRequest {
+        SubscribeRequest {
+            accounts: HashMap::from([(
+                "pool_vaults".to_string(),
+                SubscribeRequestFilterAccounts {
+                    account: self.vaults.keys().map(|vault| vault.to_string()).collect(),
+                    ..Default::default()
+                },
+            )]),
+            commitment: Some(config.commitment_level as i32),
+            ..Default::default()
+        }
+    }
+}
+
+/// Track vaults from `vaults` and send per-mint liquidity to `tx`
+///
+/// Nothing is subscribed until the first vault arrives (an empty account
+/// filter would match every account). Reconnects after stream errors;
+/// returns when either channel is closed.
+pub async fn run_liquidity_stream(
+    config: RuntimeConfig,
+    mut vaults: mpsc::Receiver<PoolVault>,
+    tx: mpsc::Sender<LiquidityUpdate>,
+) {
+    if !tracking_enabled(&config) {
+        return;
+    }
+    let mut tracker = LiquidityTracker::new(max_vaults());
+
+    loop {
+        while tracker.is_empty() {
+            let Some(vault) = vaults.recv().await else {
+                return;
+            };
+            if let Some(update) = tracker.add(vault, 0) {
+                if tx.send(update).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        match stream_once(&config, &mut tracker, &mut vaults, &tx).await {
+            Ok(()) => return,
+            Err(e) => log::warn!("⚠️  Liquidity stream: {} - reconnecting", e),
+        }
+        if tx.is_closed() {
+            return;
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Ok(()) when a channel closed, Err on stream failure
+async fn stream_once(
+    config: &RuntimeConfig,
+    tracker: &mut LiquidityTracker,
+    vaults: &mut mpsc::Receiver<PoolVault>,
+    tx: &mpsc::Sender<LiquidityUpdate>,
+) -> Result<(), String> {
+    let mut client = GeyserGrpcClient::build_from_shared(config.geyser_url.clone())
+        .map_err(|e| e.to_string())?
+        .x_token(config.x_token.clone())
+        .map_err(|e| e.to_string())?
+        .tls_config(ClientTlsConfig::new().with_enabled_roots())
+        .map_err(|e| e.to_string())?
+        .connect()
+        .await
+        .map_err(|e| e.to_string())?;
+    let (mut subscribe_tx, mut stream) = client
+        .subscribe_with_request(Some(tracker.subscribe_request(config)))
+        .await
+        .map_err(|e| e.to_string())?;
+    log::info!("💧 Liquidity stream connected ({} pool vaults)", tracker.len());
+
+    let mut last_slot = 0;
+    loop {
+        tokio::select! {
+            vault = vaults.recv() => {
+                let Some(vault) = vault else {
+                    return Ok(());
+                };
+                let Some(update) = tracker.add(vault, last_slot) else {
+                    continue;
+                };
+                // Replaces the subscription's filters with the grown vault set
+                subscribe_tx
+                    .send(tracker.subscribe_request(config))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if tx.send(update).await.is_err() {
+                    return Ok(());
+                }
+            }
+            message = stream.next() => {
+                let Some(message) = message else {
+                    return Err("stream ended".to_string());
+                };
+                match message.map_err(|e| e.to_string())?.update_oneof {
+                    Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                        account: Some(account),
+                        slot,
+                        ..
+                    })) => {
+                        last_slot = slot;
+                        let Ok(vault) = Pubkey::try_from(account.pubkey.as_slice()) else {
+                            continue;
+                        };
+                        if let Some(update) = tracker.update(&vault, &account.data, slot) {
+                            if tx.send(update).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                    Some(UpdateOneof::Ping(_)) => {
+                        subscribe_tx
+                            .send(SubscribeRequest {
+                                ping: Some(SubscribeRequestPing { id: 1 }),
+                                ..Default::default()
+                            })
+                            .await
+                            .map_err(|e| e.to_string())?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_account_data(amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 165];
+        data[TOKEN_ACCOUNT_AMOUNT].copy_from_slice(&amount.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_tracker_sums_vaults_per_mint() {
+        let mut tracker = LiquidityTracker::new(2);
+        let vault_a = Pubkey::new_from_array([1; 32]);
+        let vault_b = Pubkey::new_from_array([2; 32]);
+        let vault = |vault, amount| PoolVault {
+            mint: "mint".to_string(),
+            vault,
+            amount,
+        };
+
+        let update = tracker.add(vault(vault_a, 30_000_000_000), 10).unwrap();
+        assert!((update.liquidity_sol - 30.0).abs() < 1e-9);
+        assert!(tracker.add(vault(vault_a, 1), 10).is_none());
+
+        // A second pool for the same mint adds to its liquidity
+        let update = tracker.add(vault(vault_b, 10_000_000_000), 11).unwrap();
+        assert!((update.liquidity_sol - 40.0).abs() < 1e-9);
+
+        // Full: further vaults are ignored
+        assert!(tracker.add(vault(Pubkey::new_from_array([3; 32]), 1), 12).is_none());
+
+        // LP pulled from the first pool
+        let update = tracker.update(&vault_a, &token_account_data(2_000_000_000), 13).unwrap();
+        assert!((update.liquidity_sol - 12.0).abs() < 1e-9);
+        assert_eq!(update.slot, 13);
+
+        // Unchanged balances, unknown accounts and short data report nothing
+        assert!(tracker.update(&vault_a, &token_account_data(2_000_000_000), 14).is_none());
+        assert!(tracker.update(&Pubkey::new_from_array([9; 32]), &token_account_data(5), 14).is_none());
+        assert!(tracker.update(&vault_b, &[0u8; 10], 14).is_none());
+    }
+}
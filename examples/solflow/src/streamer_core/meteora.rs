@@ -0,0 +1,200 @@
+//! Meteora DLMM and DAMM v2 swap decoding
+//!
+//! Both programs CPI a swap event into themselves for every swap they
+//! execute, whether called directly or by an aggregator. The event carries
+//! the leg's amounts and direction; the swap instruction that emitted it
+//! names the pool's two mints. This module pairs each event with its
+//! instruction and turns the legs into trades attributed to the Meteora
+//! program, so Meteora volume shows up under its own `source_program`
+//! instead of whichever tracked program the scanner matched first.
+//!
+//! Legs are valued in SOL the same way Jupiter route legs are (see
+//! `jupiter_route::route_trades`). DAMM v1 pools only log their events, so
+//! their swaps still go through balance deltas.
+
+use crate::instruction_scanner::{METEORA_DAMM_V2_SWAPS, METEORA_DLMM_SWAPS};
+use crate::streamer_core::jupiter_route::{route_trades, token_decimals, SwapLeg};
+use crate::streamer_core::trade_detector::TradeInfo;
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_core::transaction::TransactionMetadata;
+use carbon_meteora_damm_v2_decoder::instructions::evt_swap2_event::EvtSwap2Event;
+use carbon_meteora_damm_v2_decoder::instructions::evt_swap_event::EvtSwapEvent;
+use carbon_meteora_damm_v2_decoder::PROGRAM_ID as DAMM_V2_PROGRAM_ID;
+use carbon_meteora_dlmm_decoder::instructions::swap_event::SwapEvent as DlmmSwapEvent;
+use carbon_meteora_dlmm_decoder::PROGRAM_ID as DLMM_PROGRAM_ID;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+
+/// DLMM swap accounts naming the pair's X and Y mints (same in every swap variant)
+const DLMM_TOKEN_X_MINT: usize = 6;
+const DLMM_TOKEN_Y_MINT: usize = 7;
+
+/// DAMM v2 swap accounts naming the pool's A and B mints
+const DAMM_V2_TOKEN_A_MINT: usize = 6;
+const DAMM_V2_TOKEN_B_MINT: usize = 7;
+
+/// DAMM v2 `trade_direction` for A → B swaps
+const DAMM_V2_A_TO_B: u8 = 0;
+
+/// The two mints of the pool a swap instruction trades against
+///
+/// None unless `data` is a DLMM or DAMM v2 swap.
+fn pool_mints(
+    program_id: &Pubkey,
+    accounts: &[u8],
+    data: &[u8],
+    account_keys: &[Pubkey],
+) -> Option<(Pubkey, Pubkey)> {
+    let (swaps, first, second) = if *program_id == DLMM_PROGRAM_ID {
+        (METEORA_DLMM_SWAPS, DLMM_TOKEN_X_MINT, DLMM_TOKEN_Y_MINT)
+    } else if *program_id == DAMM_V2_PROGRAM_ID {
+        (METEORA_DAMM_V2_SWAPS, DAMM_V2_TOKEN_A_MINT, DAMM_V2_TOKEN_B_MINT)
+    } else {
+        return None;
+    };
+    if !swaps.iter().any(|d| data.starts_with(d)) {
+        return None;
+    }
+    let key = |position: usize| account_keys.get(*accounts.get(position)? as usize).copied();
+    Some((key(first)?, key(second)?))
+}
+
+/// Decode a swap event against the mints of the swap that emitted it
+///
+/// `mints` is the pool's (X, Y) pair for DLMM, (A, B) for DAMM v2.
+pub fn swap_leg(program_id: &Pubkey, mints: (Pubkey, Pubkey), data: &[u8]) -> Option<SwapLeg> {
+    let (forward, input_amount, output_amount) = if *program_id == DLMM_PROGRAM_ID {
+        let event = DlmmSwapEvent::deserialize(data)?;
+        (event.swap_for_y, event.amount_in, event.amount_out)
+    } else if *program_id == DAMM_V2_PROGRAM_ID {
+        if let Some(event) = EvtSwapEvent::deserialize(data) {
+            let forward = event.trade_direction == DAMM_V2_A_TO_B;
+            (forward, event.actual_amount_in, event.swap_result.output_amount)
+        } else {
+            // Amounts as they left and reached the user (Token-2022 transfer fees included)
+            let event = EvtSwap2Event::deserialize(data)?;
+            let forward = event.trade_direction == DAMM_V2_A_TO_B;
+            (
+                forward,
+                event.included_transfer_fee_amount_in,
+                event.excluded_transfer_fee_amount_out,
+            )
+        }
+    } else {
+        return None;
+    };
+
+    let (input_mint, output_mint) = if forward { mints } else { (mints.1, mints.0) };
+    Some(SwapLeg {
+        amm: Some(*program_id),
+        input_mint,
+        input_amount,
+        output_mint,
+        output_amount,
+    })
+}
+
+/// Decode every DLMM and DAMM v2 swap in this transaction, in execution order
+pub fn decode_swap_legs(metadata: &TransactionMetadata, account_keys: &[Pubkey]) -> Vec<SwapLeg> {
+    let mut legs = Vec::new();
+    // Mints of the latest swap instruction per program, awaiting its event
+    let mut pending: HashMap<Pubkey, (Pubkey, Pubkey)> = HashMap::new();
+    let mut visit = |program_id_index: u8, accounts: &[u8], data: &[u8]| {
+        let Some(program_id) = account_keys.get(program_id_index as usize) else {
+            return;
+        };
+        if let Some(mints) = pool_mints(program_id, accounts, data, account_keys) {
+            pending.insert(*program_id, mints);
+        } else if let Some(mints) = pending.get(program_id) {
+            if let Some(leg) = swap_leg(program_id, *mints, data) {
+                pending.remove(program_id);
+                legs.push(leg);
+            }
+        }
+    };
+
+    let inner_groups = metadata.meta.inner_instructions.as_deref().unwrap_or_default();
+    for (index, ix) in metadata.message.instructions().iter().enumerate() {
+        visit(ix.program_id_index, &ix.accounts, &ix.data);
+        for inner_group in inner_groups.iter().filter(|group| group.index as usize == index) {
+            for inner in &inner_group.instructions {
+                let ix = &inner.instruction;
+                visit(ix.program_id_index, &ix.accounts, &ix.data);
+            }
+        }
+    }
+
+    legs
+}
+
+/// Decode and value a transaction's Meteora swaps
+///
+/// None when the transaction has no DLMM or DAMM v2 swap event or a leg
+/// can't be valued (use balance deltas instead).
+pub fn extract_swap_trades(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+) -> Option<Vec<TradeInfo>> {
+    let legs = decode_swap_legs(metadata, account_keys);
+    if legs.is_empty() {
+        return None;
+    }
+    let decimals = token_decimals(&metadata.meta);
+    route_trades(&legs, &decimals, account_keys.first().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streamer_core::trade_detector::TradeDirection;
+    use std::str::FromStr;
+
+    /// `SwapEvent` CPI data: event tag + discriminator, then the Borsh fields
+    fn dlmm_swap_event(amount_in: u64, amount_out: u64, swap_for_y: bool) -> Vec<u8> {
+        let mut data = vec![0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+        data.extend([0x51, 0x6c, 0xe3, 0xbe, 0xcd, 0xd0, 0x0a, 0xc4]);
+        data.extend([0u8; 64]); // lb_pair, from
+        data.extend(0i32.to_le_bytes()); // start_bin_id
+        data.extend(0i32.to_le_bytes()); // end_bin_id
+        data.extend(amount_in.to_le_bytes());
+        data.extend(amount_out.to_le_bytes());
+        data.push(swap_for_y as u8);
+        data.extend(0u64.to_le_bytes()); // fee
+        data.extend(0u64.to_le_bytes()); // protocol_fee
+        data.extend(0u128.to_le_bytes()); // fee_bps
+        data.extend(0u64.to_le_bytes()); // host_fee
+        data
+    }
+
+    #[test]
+    fn test_dlmm_swap_becomes_meteora_trade() {
+        let wsol = Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap();
+        let token = Pubkey::new_from_array([7; 32]);
+        let mut account_keys: Vec<Pubkey> = (0..16u8).map(|i| Pubkey::new_from_array([100 + i; 32])).collect();
+        account_keys[6] = token; // token_x_mint
+        account_keys[7] = wsol; // token_y_mint
+        account_keys[15] = DLMM_PROGRAM_ID;
+        let accounts: Vec<u8> = (0..16).collect();
+
+        // swap2 names the pair; liquidity instructions don't
+        let swap2 = [0x41, 0x4b, 0x3f, 0x4c, 0xeb, 0x5b, 0x5b, 0x88, 0, 0];
+        let mints = pool_mints(&DLMM_PROGRAM_ID, &accounts, &swap2, &account_keys).unwrap();
+        assert_eq!(mints, (token, wsol));
+        let add_liquidity = [0xb5, 0x9d, 0x59, 0x43, 0x8f, 0xb6, 0x34, 0x48];
+        assert!(pool_mints(&DLMM_PROGRAM_ID, &accounts, &add_liquidity, &account_keys).is_none());
+
+        // Y → X: 2 SOL in, 5000 TOKEN out is a buy attributed to DLMM
+        let leg = swap_leg(&DLMM_PROGRAM_ID, mints, &dlmm_swap_event(2_000_000_000, 5_000_000_000, false)).unwrap();
+        assert_eq!((leg.input_mint, leg.output_mint), (wsol, token));
+        let decimals = HashMap::from([(token, 6), (wsol, 9)]);
+        let trades = route_trades(&[leg], &decimals, None).unwrap();
+        assert_eq!(trades.len(), 1);
+        assert!(matches!(trades[0].direction, TradeDirection::Buy));
+        assert!((trades[0].sol_amount - 2.0).abs() < 1e-9);
+        assert!((trades[0].token_amount - 5000.0).abs() < 1e-9);
+        assert_eq!(trades[0].program_id, Some(DLMM_PROGRAM_ID));
+
+        // Another program's data never decodes as a Meteora event
+        assert!(swap_leg(&account_keys[0], mints, &dlmm_swap_event(1, 1, true)).is_none());
+    }
+}
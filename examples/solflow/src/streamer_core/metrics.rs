@@ -0,0 +1,162 @@
+//! Pipeline metrics: `LogMetrics` plus an optional Prometheus exporter
+//!
+//! Every carbon pipeline built by the streamers gets the collectors returned
+//! by `pipeline_metrics()`. With `PROMETHEUS_METRICS_ADDR` set, one
+//! process-wide `PrometheusMetrics` serves `/metrics` on that address, so
+//! carbon's own counters (`transaction_updates_processed`, `updates_queued`,
+//! ...) and the solflow metrics below can be scraped and alerted on.
+//!
+//! Code outside a carbon pipeline (ingestion, flush loop) records through
+//! the free functions here, which are no-ops when the exporter is disabled.
+//!
+//! Environment variables:
+//! - `PROMETHEUS_METRICS_ADDR`: Exporter listen address, e.g. `0.0.0.0:9100` (default: disabled)
+//! - `LOG_METRICS_ENABLED`: Keep the periodic `LogMetrics` stderr report (default: true)
+
+use carbon_core::metrics::Metrics;
+use carbon_core::pipeline::PipelineBuilder;
+use carbon_log_metrics::LogMetrics;
+use carbon_prometheus_metrics::PrometheusMetrics;
+use std::net::SocketAddr;
+use std::sync::{Arc, OnceLock};
+
+/// Trades extracted from matched transactions
+pub const TRADES_EXTRACTED: &str = "solflow_trades_extracted";
+/// Unknown trade directions resolved from transfer topology
+pub const DIRECTIONS_INFERRED: &str = "solflow_directions_inferred";
+/// Balance-delta directions replaced by the discriminator table (see `program_registry`)
+pub const DISCRIMINATOR_DIRECTIONS: &str = "solflow_discriminator_directions";
+/// Trades decoded from Jupiter route swap events (see `jupiter_route`)
+pub const JUPITER_ROUTE_TRADES: &str = "solflow_jupiter_route_trades";
+/// Trades decoded from Meteora DLMM / DAMM v2 swap events (see `meteora`)
+pub const METEORA_SWAP_TRADES: &str = "solflow_meteora_swap_trades";
+/// Trades decoded from pump.fun bonding-curve trade events (see `pumpfun`)
+pub const PUMPFUN_CURVE_TRADES: &str = "solflow_pumpfun_curve_trades";
+/// Trades split out of transactions bundling several swaps (see `swap_splitter`)
+pub const SPLIT_SWAP_TRADES: &str = "solflow_split_swap_trades";
+/// Curve-to-AMM migrations sent to the pipeline (see `pumpfun`)
+pub const TOKEN_MIGRATIONS: &str = "solflow_token_migrations";
+/// Pool vaults sent to the liquidity tracker (see `liquidity`)
+pub const POOL_VAULTS_FOUND: &str = "solflow_pool_vaults_found";
+/// Pool state accounts sent to the pool price tracker (see `pool_state`)
+pub const POOL_STATE_ACCOUNTS_FOUND: &str = "solflow_pool_state_accounts_found";
+/// Funding edges recorded in the wallet graph (see `funding`)
+pub const FUNDING_EDGES: &str = "solflow_funding_edges";
+/// Trades discarded because their mint is in `mint_blocklist`
+pub const BLOCKLIST_HITS: &str = "solflow_blocklist_hits";
+/// Trades dropped because the pipeline channel was full
+pub const CHANNEL_SEND_FAILURES: &str = "solflow_channel_send_failures";
+/// Trades waiting in the pipeline channel (sampled every flush)
+pub const CHANNEL_DEPTH: &str = "solflow_channel_depth";
+/// Wall time of one aggregate/signal flush cycle
+pub const FLUSH_LATENCY_MS: &str = "solflow_flush_latency_milliseconds";
+/// Mints computed per flush cycle
+pub const FLUSH_MINTS: &str = "solflow_flush_mints";
+/// Signals written per flush cycle
+pub const SIGNALS_WRITTEN: &str = "solflow_signals_written";
+/// SQLite write jobs waiting for the writer thread (sampled per write)
+pub const SQLITE_WRITE_QUEUE_DEPTH: &str = "solflow_sqlite_write_queue_depth";
+/// SQLite writes that had to wait because the write queue was full
+pub const SQLITE_WRITE_QUEUE_FULL: &str = "solflow_sqlite_write_queue_full";
+/// Expired rows deleted by the retention manager
+pub const RETENTION_ROWS_DELETED: &str = "solflow_retention_rows_deleted";
+/// Free pages returned to the filesystem by incremental vacuum
+pub const RETENTION_PAGES_RECLAIMED: &str = "solflow_retention_pages_reclaimed";
+/// Re-delivered trades dropped by (signature, trade index)
+pub const TRADES_DEDUPLICATED: &str = "solflow_trades_deduplicated";
+/// Slots the connected Yellowstone endpoint is behind the best one
+pub const GEYSER_SLOT_LAG: &str = "solflow_geyser_slot_lag";
+/// Subscriptions moved to a healthier Yellowstone endpoint
+pub const GEYSER_FAILOVERS: &str = "solflow_geyser_failovers";
+/// Gaps in the live slot sequence (see `slot_gaps`)
+pub const DATA_GAPS: &str = "solflow_data_gaps";
+/// Slots missed across all detected gaps
+pub const DATA_GAP_SLOTS: &str = "solflow_data_gap_slots";
+/// Slots the primary Geyser source is behind the secondary (see `redundancy`)
+pub const PRIMARY_SOURCE_SLOT_LAG: &str = "solflow_geyser_primary_slot_lag";
+/// Slots the secondary Geyser source is behind the primary
+pub const SECONDARY_SOURCE_SLOT_LAG: &str = "solflow_geyser_secondary_slot_lag";
+/// Slots the primary source skipped that the secondary delivered
+pub const PRIMARY_SOURCE_MISSED_SLOTS: &str = "solflow_geyser_primary_missed_slots";
+/// Slots the secondary source skipped that the primary delivered
+pub const SECONDARY_SOURCE_MISSED_SLOTS: &str = "solflow_geyser_secondary_missed_slots";
+/// Transactions delivered by both Geyser sources, second copy dropped
+pub const REDUNDANT_DUPLICATES: &str = "solflow_geyser_redundant_duplicates";
+
+/// Histogram buckets (milliseconds) sized around the default 5s flush interval
+const LATENCY_BUCKETS_MS: &[f64] = &[
+    5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+];
+
+static PROMETHEUS: OnceLock<Option<Arc<PrometheusMetrics>>> = OnceLock::new();
+
+/// The process-wide exporter (None when PROMETHEUS_METRICS_ADDR is unset or invalid)
+pub fn prometheus() -> Option<Arc<PrometheusMetrics>> {
+    PROMETHEUS
+        .get_or_init(|| {
+            let addr = std::env::var("PROMETHEUS_METRICS_ADDR").ok()?;
+            match addr.parse::<SocketAddr>() {
+                Ok(addr) => Some(Arc::new(
+                    PrometheusMetrics::new_with_addr(addr)
+                        .with_histogram_buckets(LATENCY_BUCKETS_MS.to_vec()),
+                )),
+                Err(e) => {
+                    log::error!("❌ Invalid PROMETHEUS_METRICS_ADDR '{}': {} - exporter disabled", addr, e);
+                    None
+                }
+            }
+        })
+        .clone()
+}
+
+/// Start the exporter before any pipeline runs (replay, runtime-only metrics)
+///
+/// Pipelines initialize it again on start; the listener is only bound once.
+pub async fn init_exporter() {
+    if let Some(prometheus) = prometheus() {
+        if let Err(e) = prometheus.initialize().await {
+            log::error!("❌ Prometheus exporter failed to start: {}", e);
+        }
+    }
+}
+
+/// Collectors for a carbon pipeline (see module docs)
+pub fn pipeline_metrics() -> Vec<Arc<dyn Metrics>> {
+    let log_metrics = std::env::var("LOG_METRICS_ENABLED")
+        .map(|v| v != "false" && v != "0")
+        .unwrap_or(true);
+
+    let mut metrics: Vec<Arc<dyn Metrics>> = Vec::new();
+    if log_metrics {
+        metrics.push(Arc::new(LogMetrics::new()));
+    }
+    if let Some(prometheus) = prometheus() {
+        metrics.push(prometheus);
+    }
+    metrics
+}
+
+/// Add `pipeline_metrics()` to a pipeline builder
+pub fn with_pipeline_metrics(builder: PipelineBuilder) -> PipelineBuilder {
+    pipeline_metrics()
+        .into_iter()
+        .fold(builder, |builder, metrics| builder.metrics(metrics))
+}
+
+pub async fn increment_counter(name: &str, value: u64) {
+    if let Some(prometheus) = prometheus() {
+        let _ = prometheus.increment_counter(name, value).await;
+    }
+}
+
+pub async fn update_gauge(name: &str, value: f64) {
+    if let Some(prometheus) = prometheus() {
+        let _ = prometheus.update_gauge(name, value).await;
+    }
+}
+
+pub async fn record_histogram(name: &str, value: f64) {
+    if let Some(prometheus) = prometheus() {
+        let _ = prometheus.record_histogram(name, value).await;
+    }
+}
@@ -0,0 +1,353 @@
+//! Token mint creation watcher
+//!
+//! The persistence scorer discounts young tokens, but `pair_created_at` only
+//! arrives once DexScreener lists a pair - often hours after launch. With
+//! `MINT_WATCHER_ENABLED=true` the runtime opens a separate transaction
+//! subscription on the SPL Token and Token-2022 programs and records every
+//! `InitializeMint`/`InitializeMint2` in `token_metadata`: the mint's
+//! decimals, `mint_created_at`, and the launch platform when a tracked
+//! launchpad created it.
+//!
+//! Every token program transaction is a firehose. `MINT_WATCHER_PROGRAMS`
+//! narrows the subscription to transactions that also invoke one of the
+//! listed programs (typically the launchpads).
+//!
+//! Environment variables:
+//! - `MINT_WATCHER_ENABLED`: Record mint creations (default: false)
+//! - `MINT_WATCHER_PROGRAMS`: Comma-separated program ids a watched
+//!   transaction must also invoke (default: unset = every mint creation)
+
+use crate::error::SolflowError;
+use crate::instruction_scanner::InstructionScanner;
+use crate::pipeline::engine::launch_platform;
+use crate::streamer_core::config::{DatasourceKind, RuntimeConfig};
+use futures::{sink::SinkExt, StreamExt};
+use rusqlite::Connection;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::env;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestFilterTransactions,
+    SubscribeRequestPing, SubscribeUpdateTransactionInfo,
+};
+use yellowstone_grpc_proto::tonic::transport::ClientTlsConfig;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+const TOKEN_PROGRAM_ID: Pubkey = Pubkey::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Token instruction tags (same in both programs); the mint is account 0
+const INITIALIZE_MINT: u8 = 0;
+const INITIALIZE_MINT2: u8 = 20;
+
+/// A mint initialized on-chain
+#[derive(Debug, Clone, PartialEq)]
+pub struct MintCreation {
+    pub mint: String,
+    pub decimals: u8,
+    /// Launchpad that created the mint (None for any other program)
+    pub launch_platform: Option<String>,
+    /// Top-level program whose instruction initialized the mint
+    pub creator_program: String,
+    pub signature: String,
+    pub slot: u64,
+    pub created_at: i64,
+}
+
+/// Whether the watcher runs (`MINT_WATCHER_ENABLED`, gRPC datasource only)
+pub fn watcher_enabled(config: &RuntimeConfig) -> bool {
+    config.datasource == DatasourceKind::Grpc
+        && env::var("MINT_WATCHER_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false)
+}
+
+/// Programs a watched transaction must also invoke (`MINT_WATCHER_PROGRAMS`)
+fn required_programs() -> Vec<String> {
+    env::var("MINT_WATCHER_PROGRAMS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|program| program.trim().to_string())
+        .filter(|program| !program.is_empty())
+        .collect()
+}
+
+/// One filter per required program (Yellowstone requires all of `account_required`)
+fn subscribe_request(required: &[String]) -> SubscribeRequest {
+    let filter = |account_required: Vec<String>| SubscribeRequestFilterTransactions {
+        vote: Some(false),
+        failed: Some(false),
+        account_include: vec![TOKEN_PROGRAM_ID.to_string(), TOKEN_2022_PROGRAM_ID.to_string()],
+        account_required,
+        ..Default::default()
+    };
+    let transactions = if required.is_empty() {
+        HashMap::from([("mints".to_string(), filter(Vec::new()))])
+    } else {
+        required
+            .iter()
+            .map(|program| (format!("mints_{}", program), filter(vec![program.clone()])))
+            .collect()
+    };
+    SubscribeRequest {
+        transactions,
+        ..Default::default()
+    }
+}
+
+/// Mint and decimals if this instruction initializes a mint
+fn initialized_mint(
+    program_id: &Pubkey,
+    accounts: &[u8],
+    data: &[u8],
+    account_keys: &[Pubkey],
+) -> Option<(Pubkey, u8)> {
+    if *program_id != TOKEN_PROGRAM_ID && *program_id != TOKEN_2022_PROGRAM_ID {
+        return None;
+    }
+    match data {
+        [INITIALIZE_MINT | INITIALIZE_MINT2, decimals, ..] => {
+            Some((*account_keys.get(*accounts.first()? as usize)?, *decimals))
+        }
+        _ => None,
+    }
+}
+
+/// Every mint this transaction initialized, attributed to its top-level program
+fn mint_creations(
+    info: &SubscribeUpdateTransactionInfo,
+    slot: u64,
+    now: i64,
+    scanner: &InstructionScanner,
+) -> Vec<MintCreation> {
+    let (Some(message), Some(meta)) = (
+        info.transaction.as_ref().and_then(|tx| tx.message.as_ref()),
+        info.meta.as_ref(),
+    ) else {
+        return Vec::new();
+    };
+    let account_keys: Vec<Pubkey> = message
+        .account_keys
+        .iter()
+        .chain(&meta.loaded_writable_addresses)
+        .chain(&meta.loaded_readonly_addresses)
+        .filter_map(|key| Pubkey::try_from(key.as_slice()).ok())
+        .collect();
+    let signature = solana_signature::Signature::try_from(info.signature.as_slice())
+        .map(|signature| signature.to_string())
+        .unwrap_or_default();
+
+    let mut creations = Vec::new();
+    for (index, ix) in message.instructions.iter().enumerate() {
+        let Some(creator) = account_keys.get(ix.program_id_index as usize) else {
+            continue;
+        };
+        let inner = meta
+            .inner_instructions
+            .iter()
+            .filter(|group| group.index as usize == index)
+            .flat_map(|group| &group.instructions)
+            .map(|inner| (inner.program_id_index, &inner.accounts, &inner.data));
+        for (program_id_index, accounts, data) in
+            std::iter::once((ix.program_id_index, &ix.accounts, &ix.data)).chain(inner)
+        {
+            let Some(program_id) = account_keys.get(program_id_index as usize) else {
+                continue;
+            };
+            if let Some((mint, decimals)) = initialized_mint(program_id, accounts, data, &account_keys) {
+                creations.push(MintCreation {
+                    mint: mint.to_string(),
+                    decimals,
+                    launch_platform: scanner
+                        .program_name(creator)
+                        .and_then(launch_platform)
+                        .map(str::to_string),
+                    creator_program: creator.to_string(),
+                    signature: signature.clone(),
+                    slot,
+                    created_at: now,
+                });
+            }
+        }
+    }
+    creations
+}
+
+/// Record a mint creation in `token_metadata`
+///
+/// New mints get a row; existing rows only gain `mint_created_at` and
+/// `launch_platform` where those are still NULL.
+pub fn record_mint_creation(conn: &Connection, creation: &MintCreation) -> Result<(), SolflowError> {
+    conn.execute(
+        r#"
+        INSERT INTO token_metadata
+            (mint, decimals, launch_platform, mint_created_at, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?4, ?4)
+        ON CONFLICT(mint) DO UPDATE SET
+            mint_created_at = COALESCE(token_metadata.mint_created_at, excluded.mint_created_at),
+            launch_platform = COALESCE(token_metadata.launch_platform, excluded.launch_platform)
+        "#,
+        rusqlite::params![
+            creation.mint,
+            creation.decimals,
+            creation.launch_platform,
+            creation.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Subscribe to token program transactions and send mint creations to `tx`
+///
+/// Reconnects after stream errors; returns when `tx` is closed.
+pub async fn run_mint_watcher(config: RuntimeConfig, tx: mpsc::Sender<MintCreation>) {
+    let required = required_programs();
+    if required.is_empty() {
+        log::warn!("⚠️  Mint watcher subscribes to every token program transaction (set MINT_WATCHER_PROGRAMS to narrow it)");
+    }
+    let request = subscribe_request(&required);
+    let scanner = InstructionScanner::new();
+
+    loop {
+        if let Err(e) = stream_once(&config, &request, &scanner, &tx).await {
+            log::warn!("⚠️  Mint watcher stream: {} - reconnecting", e);
+        }
+        if tx.is_closed() {
+            return;
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn stream_once(
+    config: &RuntimeConfig,
+    request: &SubscribeRequest,
+    scanner: &InstructionScanner,
+    tx: &mpsc::Sender<MintCreation>,
+) -> Result<(), String> {
+    let mut client = GeyserGrpcClient::build_from_shared(config.geyser_url.clone())
+        .map_err(|e| e.to_string())?
+        .x_token(config.x_token.clone())
+        .map_err(|e| e.to_string())?
+        .tls_config(ClientTlsConfig::new().with_enabled_roots())
+        .map_err(|e| e.to_string())?
+        .connect()
+        .await
+        .map_err(|e| e.to_string())?;
+    let request = SubscribeRequest {
+        commitment: Some(config.commitment_level as i32),
+        ..request.clone()
+    };
+    let (mut subscribe_tx, mut stream) = client
+        .subscribe_with_request(Some(request))
+        .await
+        .map_err(|e| e.to_string())?;
+    log::info!("🪙 Mint watcher stream connected ({:?})", config.commitment_level);
+
+    while let Some(message) = stream.next().await {
+        match message.map_err(|e| e.to_string())?.update_oneof {
+            Some(UpdateOneof::Transaction(update)) => {
+                let Some(info) = update.transaction else {
+                    continue;
+                };
+                let now = chrono::Utc::now().timestamp();
+                for creation in mint_creations(&info, update.slot, now, scanner) {
+                    if tx.send(creation).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            Some(UpdateOneof::Ping(_)) => {
+                subscribe_tx
+                    .send(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: 1 }),
+                        ..Default::default()
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            _ => {}
+        }
+    }
+    Err("stream ended".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yellowstone_grpc_proto::prelude::{
+        CompiledInstruction, InnerInstruction, InnerInstructions, Message, Transaction,
+        TransactionStatusMeta,
+    };
+
+    #[test]
+    fn test_launchpad_mint_creation_detected() {
+        let pumpfun = Pubkey::from_str_const("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+        let mint = Pubkey::new_from_array([7; 32]);
+        let payer = Pubkey::new_from_array([1; 32]);
+        let keys = [payer, mint, pumpfun, TOKEN_PROGRAM_ID];
+
+        // pump.fun `create` CPIs InitializeMint2 (6 decimals) and a transfer
+        let info = SubscribeUpdateTransactionInfo {
+            signature: vec![3; 64],
+            transaction: Some(Transaction {
+                message: Some(Message {
+                    account_keys: keys.iter().map(|key| key.to_bytes().to_vec()).collect(),
+                    instructions: vec![CompiledInstruction {
+                        program_id_index: 2,
+                        accounts: vec![0, 1],
+                        data: vec![0x18, 0x1e, 0xc8, 0x28, 0x05, 0x1c, 0x07, 0x77],
+                    }],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            meta: Some(TransactionStatusMeta {
+                inner_instructions: vec![InnerInstructions {
+                    index: 0,
+                    instructions: vec![
+                        InnerInstruction {
+                            program_id_index: 3,
+                            accounts: vec![1],
+                            data: [vec![INITIALIZE_MINT2, 6], vec![0; 33]].concat(),
+                            stack_height: Some(2),
+                        },
+                        InnerInstruction {
+                            program_id_index: 3,
+                            accounts: vec![1, 0],
+                            data: vec![3, 1, 0, 0, 0, 0, 0, 0, 0],
+                            stack_height: Some(2),
+                        },
+                    ],
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let creations = mint_creations(&info, 42, 1_700_000_000, &InstructionScanner::new());
+        assert_eq!(creations.len(), 1);
+        assert_eq!(creations[0].mint, mint.to_string());
+        assert_eq!(creations[0].decimals, 6);
+        assert_eq!(creations[0].launch_platform.as_deref(), Some("pumpfun"));
+        assert_eq!(creations[0].creator_program, pumpfun.to_string());
+
+        // Recorded once; a later DexScreener row keeps the on-chain time
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../../sql/00_token_metadata.sql")).unwrap();
+        record_mint_creation(&conn, &creations[0]).unwrap();
+        record_mint_creation(&conn, &MintCreation { created_at: 1_800_000_000, ..creations[0].clone() }).unwrap();
+        let (created_at, platform): (i64, String) = conn
+            .query_row(
+                "SELECT mint_created_at, launch_platform FROM token_metadata WHERE mint = ?1",
+                [mint.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((created_at, platform.as_str()), (1_700_000_000, "pumpfun"));
+    }
+}
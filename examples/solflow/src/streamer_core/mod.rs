@@ -1,16 +1,45 @@
+pub mod account_cache;
+pub mod backfill;
 pub mod balance_extractor;
 pub mod blocklist_checker;
+pub mod compute_budget;
 pub mod config;
+pub mod dca_order;
+pub mod endpoints;
 pub mod error_handler;
+pub mod filter_builder;
+pub mod focus_wallets;
+pub mod funding;
 pub mod grpc_client;
+pub mod jupiter_route;
+pub mod liquidity;
+pub mod meteora;
+pub mod metrics;
+pub mod mint_watcher;
 pub mod output_writer;
+pub mod pool_state;
+pub mod program_registry;
+pub mod pumpfun;
+pub mod redundancy;
+pub mod rpc_client;
+pub mod secrets;
+pub mod slot_gaps;
+pub mod slot_status;
+pub mod subscription;
+pub mod swap_splitter;
+pub mod token_extensions;
 pub mod trade_detector;
+pub mod transfer_direction;
+pub mod whirlpool;
 pub mod writer_backend;
 pub mod sqlite_writer;
+pub mod postgres_writer;
+#[cfg(feature = "kafka")]
+pub mod kafka_writer;
 
 mod lib;
 
 pub use blocklist_checker::BlocklistChecker;
 pub use config::{RuntimeConfig, StreamerConfig};
-pub use lib::{run, run_unified};
+pub use lib::{run, run_unified, run_unified_with_capture};
 pub use output_writer::TradeEvent;
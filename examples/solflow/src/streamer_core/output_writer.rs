@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 use async_trait::async_trait;
 use crate::streamer_core::writer_backend::{WriterBackend, WriterError};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TradeEvent {
     pub timestamp: i64,
     pub signature: String,
@@ -18,6 +18,15 @@ pub struct TradeEvent {
     pub token_decimals: u8,
     pub user_account: Option<String>,
     pub discriminator: String,
+    /// Focus wallet involved in the trade (see `focus_wallets`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_wallet: Option<String>,
+    /// Priority fee the transaction paid, in lamports (see `compute_budget`)
+    #[serde(default)]
+    pub priority_fee_lamports: u64,
+    /// Lamports tipped to Jito's tip accounts
+    #[serde(default)]
+    pub jito_tip_lamports: u64,
 }
 
 pub struct JsonlWriter {
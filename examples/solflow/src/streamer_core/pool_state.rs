@@ -0,0 +1,497 @@
+//! On-chain pool prices from pool state account updates
+//!
+//! Every other price in the pipeline is either trade-derived (the engine's
+//! `latest_price_sol`) or fetched from DexScreener. With
+//! `POOL_STATE_ENABLED=true` the runtime also opens an account subscription
+//! on the pool state accounts of the tracked venues and prices each mint
+//! from its pool's own reserves, so the price follows the pool between
+//! trades and doesn't depend on an external API:
+//!
+//! - pump.fun bonding curves: virtual SOL reserves over virtual token
+//!   reserves; a completed curve stops being priced (its liquidity moved to
+//!   PumpSwap)
+//! - Orca Whirlpools paired with WSOL: the pool's `sqrt_price`
+//!
+//! Pool accounts are learned from the trades the streamer already sees
+//! (the curve of a pump.fun buy/sell, the pool of a Whirlpool swap) and the
+//! subscription grows as new ones are found, like the liquidity tracker's
+//! (see `liquidity`). The engine writes the latest price as
+//! `pool_price_sol`. Other venues are not priced here.
+//!
+//! Environment variables:
+//! - `POOL_STATE_ENABLED`: Track pool state accounts (default: false)
+//! - `POOL_STATE_MAX_ACCOUNTS`: Most pool accounts subscribed to; later ones
+//!   are ignored (default: 2000)
+
+use crate::instruction_scanner::{PUMPSWAP_BUY, PUMPSWAP_SELL};
+use crate::streamer_core::config::{DatasourceKind, RuntimeConfig};
+use crate::streamer_core::jupiter_route::token_decimals;
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_core::transaction::TransactionMetadata;
+use carbon_orca_whirlpool_decoder::accounts::whirlpool::Whirlpool;
+use carbon_orca_whirlpool_decoder::instructions::swap::Swap;
+use carbon_orca_whirlpool_decoder::instructions::swap_v2::SwapV2;
+use carbon_orca_whirlpool_decoder::PROGRAM_ID as WHIRLPOOL_PROGRAM_ID;
+use carbon_pumpfun_decoder::PROGRAM_ID as PUMPFUN_PROGRAM_ID;
+use futures::{sink::SinkExt, StreamExt};
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, SubscribeRequest, SubscribeRequestFilterAccounts,
+    SubscribeRequestPing, SubscribeUpdateAccount,
+};
+use yellowstone_grpc_proto::tonic::transport::ClientTlsConfig;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+const SOL_DECIMALS: i32 = 9;
+
+/// Decimals of every pump.fun token (used when the transaction has no balance for the mint)
+const PUMPFUN_TOKEN_DECIMALS: u8 = 6;
+
+const DEFAULT_MAX_ACCOUNTS: usize = 2000;
+
+/// pump.fun `buy` / `sell` accounts naming the mint and its bonding curve
+/// (Anchor gives them the same discriminators as PumpSwap's)
+const CURVE_MINT: usize = 2;
+const CURVE_ACCOUNT: usize = 3;
+
+/// Whirlpool `swap` accounts: the pool and its two vaults
+const SWAP_WHIRLPOOL: usize = 2;
+const SWAP_VAULT_A: usize = 4;
+const SWAP_VAULT_B: usize = 6;
+
+/// Whirlpool `swap_v2` accounts: the pool and its two mints
+const SWAP_V2_WHIRLPOOL: usize = 4;
+const SWAP_V2_MINT_A: usize = 5;
+const SWAP_V2_MINT_B: usize = 6;
+
+/// `BondingCurve` account discriminator
+const BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [0x17, 0xb7, 0xf8, 0x37, 0x60, 0xd8, 0xac, 0x60];
+
+/// `BondingCurve` fields read here: virtual token reserves, virtual SOL
+/// reserves and the `complete` flag. Curves created before pump.fun added
+/// `creator` are shorter than the decoder's `BondingCurve`, so the fixed
+/// prefix is read directly.
+const CURVE_VIRTUAL_TOKEN_RESERVES: std::ops::Range<usize> = 8..16;
+const CURVE_VIRTUAL_SOL_RESERVES: std::ops::Range<usize> = 16..24;
+const CURVE_COMPLETE: usize = 48;
+
+/// Layout of a tracked pool state account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolKind {
+    /// pump.fun bonding curve
+    BondingCurve,
+    /// Orca Whirlpool paired with WSOL
+    Whirlpool,
+}
+
+/// Pool state account of a pool trading `mint` against SOL
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolAccount {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub kind: PoolKind,
+    pub token_decimals: u8,
+}
+
+/// Price of a mint in its pool after an account update
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolPriceUpdate {
+    pub mint: String,
+    /// SOL per whole token
+    pub price_sol: f64,
+    pub slot: u64,
+}
+
+/// Whether pool state tracking runs (gRPC datasource and `POOL_STATE_ENABLED`)
+pub fn tracking_enabled(config: &RuntimeConfig) -> bool {
+    config.datasource == DatasourceKind::Grpc
+        && std::env::var("POOL_STATE_ENABLED")
+            .map(|v| v.to_lowercase() == "true" || v == "1")
+            .unwrap_or(false)
+}
+
+fn max_accounts() -> usize {
+    std::env::var("POOL_STATE_MAX_ACCOUNTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&max| max > 0)
+        .unwrap_or(DEFAULT_MAX_ACCOUNTS)
+}
+
+/// Pool state accounts of the pools the traded mints were swapped in
+pub fn pool_accounts(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+    traded_mints: &[&str],
+) -> Vec<PoolAccount> {
+    let decimals = token_decimals(&metadata.meta);
+    let token_account_mints: HashMap<usize, String> = [
+        &metadata.meta.pre_token_balances,
+        &metadata.meta.post_token_balances,
+    ]
+    .into_iter()
+    .flatten()
+    .flatten()
+    .map(|balance| (balance.account_index as usize, balance.mint.clone()))
+    .collect();
+
+    let key = |accounts: &[u8], position: usize| {
+        accounts
+            .get(position)
+            .and_then(|&index| account_keys.get(index as usize))
+            .copied()
+    };
+
+    let wsol = Pubkey::from_str(WSOL_MINT).expect("valid WSOL mint");
+    let mut pools: Vec<PoolAccount> = Vec::new();
+    let mut check = |program_id_index: u8, accounts: &[u8], data: &[u8]| {
+        let program_id = account_keys.get(program_id_index as usize);
+        let pool = if program_id == Some(&PUMPFUN_PROGRAM_ID)
+            && (data.starts_with(PUMPSWAP_BUY) || data.starts_with(PUMPSWAP_SELL))
+        {
+            let (Some(mint), Some(account)) = (key(accounts, CURVE_MINT), key(accounts, CURVE_ACCOUNT)) else {
+                return;
+            };
+            PoolAccount {
+                mint,
+                account,
+                kind: PoolKind::BondingCurve,
+                token_decimals: decimals.get(&mint).copied().unwrap_or(PUMPFUN_TOKEN_DECIMALS),
+            }
+        } else if program_id == Some(&WHIRLPOOL_PROGRAM_ID) {
+            let (whirlpool, mint_a, mint_b) = if Swap::deserialize(data).is_some() {
+                let vault_mint = |position| {
+                    accounts
+                        .get(position)
+                        .and_then(|&index| token_account_mints.get(&(index as usize)))
+                        .and_then(|mint| Pubkey::from_str(mint).ok())
+                };
+                (key(accounts, SWAP_WHIRLPOOL), vault_mint(SWAP_VAULT_A), vault_mint(SWAP_VAULT_B))
+            } else if SwapV2::deserialize(data).is_some() {
+                (
+                    key(accounts, SWAP_V2_WHIRLPOOL),
+                    key(accounts, SWAP_V2_MINT_A),
+                    key(accounts, SWAP_V2_MINT_B),
+                )
+            } else {
+                return;
+            };
+            let (Some(account), Some(mint_a), Some(mint_b)) = (whirlpool, mint_a, mint_b) else {
+                return;
+            };
+            let mint = match (mint_a == wsol, mint_b == wsol) {
+                (false, true) => mint_a,
+                (true, false) => mint_b,
+                _ => return,
+            };
+            let Some(&token_decimals) = decimals.get(&mint) else {
+                return;
+            };
+            PoolAccount {
+                mint,
+                account,
+                kind: PoolKind::Whirlpool,
+                token_decimals,
+            }
+        } else {
+            return;
+        };
+        let traded = traded_mints.iter().any(|&m| m == pool.mint.to_string());
+        if traded && !pools.iter().any(|p| p.account == pool.account) {
+            pools.push(pool);
+        }
+    };
+    for ix in metadata.message.instructions() {
+        check(ix.program_id_index, &ix.accounts, &ix.data);
+    }
+    if let Some(inner_groups) = &metadata.meta.inner_instructions {
+        for inner_group in inner_groups {
+            for inner in &inner_group.instructions {
+                let ix = &inner.instruction;
+                check(ix.program_id_index, &ix.accounts, &ix.data);
+            }
+        }
+    }
+    pools
+}
+
+/// SOL per token on a bonding curve; None for other accounts and completed curves
+pub fn bonding_curve_price(data: &[u8], token_decimals: u8) -> Option<f64> {
+    if data.get(..8)? != BONDING_CURVE_DISCRIMINATOR || *data.get(CURVE_COMPLETE)? != 0 {
+        return None;
+    }
+    let virtual_tokens = u64::from_le_bytes(data.get(CURVE_VIRTUAL_TOKEN_RESERVES)?.try_into().ok()?);
+    let virtual_sol = u64::from_le_bytes(data.get(CURVE_VIRTUAL_SOL_RESERVES)?.try_into().ok()?);
+    if virtual_tokens == 0 {
+        return None;
+    }
+    let sol = virtual_sol as f64 / 10f64.powi(SOL_DECIMALS);
+    let tokens = virtual_tokens as f64 / 10f64.powi(token_decimals as i32);
+    Some(sol / tokens)
+}
+
+/// SOL per `mint` in a WSOL Whirlpool; None for other accounts or pairs
+pub fn whirlpool_price(data: &[u8], mint: &Pubkey, token_decimals: u8) -> Option<f64> {
+    let pool = Whirlpool::deserialize(data)?;
+    // sqrt_price is Q64.64: its square is B per A in base units
+    let sqrt_price = pool.sqrt_price as f64 / 2f64.powi(64);
+    let b_per_a = sqrt_price * sqrt_price;
+    if b_per_a <= 0.0 {
+        return None;
+    }
+    let scale = 10f64.powi(token_decimals as i32 - SOL_DECIMALS);
+    if pool.token_mint_a == *mint {
+        Some(b_per_a * scale)
+    } else if pool.token_mint_b == *mint {
+        Some(scale / b_per_a)
+    } else {
+        None
+    }
+}
+
+/// Known pool accounts and their latest prices
+#[derive(Debug)]
+pub struct PoolStateTracker {
+    /// Account → (pool, last price reported)
+    pools: HashMap<Pubkey, (PoolAccount, Option<f64>)>,
+    max_accounts: usize,
+}
+
+impl PoolStateTracker {
+    pub fn new(max_accounts: usize) -> Self {
+        Self {
+            pools: HashMap::new(),
+            max_accounts,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    /// Start tracking a pool; false if it is already known or the tracker is full
+    pub fn add(&mut self, pool: PoolAccount) -> bool {
+        if self.pools.contains_key(&pool.account) || self.pools.len() >= self.max_accounts {
+            return false;
+        }
+        self.pools.insert(pool.account, (pool, None));
+        true
+    }
+
+    /// Apply a pool's new account data; None unless its price changed
+    ///
+    /// A completed bonding curve is dropped: its mint trades elsewhere now.
+    pub fn update(&mut self, account: &Pubkey, data: &[u8], slot: u64) -> Option<PoolPriceUpdate> {
+        let (pool, _) = self.pools.get(account)?;
+        let (kind, mint, token_decimals) = (pool.kind, pool.mint, pool.token_decimals);
+        let price = match kind {
+            PoolKind::BondingCurve => {
+                let price = bonding_curve_price(data, token_decimals);
+                if price.is_none() && data.get(CURVE_COMPLETE).is_some_and(|&complete| complete != 0) {
+                    self.pools.remove(account);
+                    return None;
+                }
+                price?
+            }
+            PoolKind::Whirlpool => whirlpool_price(data, &mint, token_decimals)?,
+        };
+        let (_, last_price) = self.pools.get_mut(account)?;
+        if *last_price == Some(price) {
+            return None;
+        }
+        *last_price = Some(price);
+        Some(PoolPriceUpdate {
+            mint: mint.to_string(),
+            price_sol: price,
+            slot,
+        })
+    }
+
+    /// Account subscription on every known pool
+    fn subscribe_request(&self, config: &RuntimeConfig) -> SubscribeRequest {
+        SubscribeRequest {
+            accounts: HashMap::from([(
+                "pool_state".to_string(),
+                SubscribeRequestFilterAccounts {
+                    account: self.pools.keys().map(|account| account.to_string()).collect(),
+                    ..Default::default()
+                },
+            )]),
+            commitment: Some(config.commitment_level as i32),
+            ..Default::default()
+        }
+    }
+}
+
+/// Track pools from `pools` and send per-mint prices to `tx`
+///
+/// Nothing is subscribed until the first pool arrives (an empty account
+/// filter would match every account). Reconnects after stream errors;
+/// returns when either channel is closed.
+pub async fn run_pool_state_stream(
+    config: RuntimeConfig,
+    mut pools: mpsc::Receiver<PoolAccount>,
+    tx: mpsc::Sender<PoolPriceUpdate>,
+) {
+    if !tracking_enabled(&config) {
+        return;
+    }
+    let mut tracker = PoolStateTracker::new(max_accounts());
+
+    loop {
+        while tracker.is_empty() {
+            let Some(pool) = pools.recv().await else {
+                return;
+            };
+            tracker.add(pool);
+        }
+
+        match stream_once(&config, &mut tracker, &mut pools, &tx).await {
+            Ok(()) => return,
+            Err(e) => log::warn!("⚠️  Pool state stream: {} - reconnecting", e),
+        }
+        if tx.is_closed() {
+            return;
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Ok(()) when a channel closed, Err on stream failure
+async fn stream_once(
+    config: &RuntimeConfig,
+    tracker: &mut PoolStateTracker,
+    pools: &mut mpsc::Receiver<PoolAccount>,
+    tx: &mpsc::Sender<PoolPriceUpdate>,
+) -> Result<(), String> {
+    let mut client = GeyserGrpcClient::build_from_shared(config.geyser_url.clone())
+        .map_err(|e| e.to_string())?
+        .x_token(config.x_token.clone())
+        .map_err(|e| e.to_string())?
+        .tls_config(ClientTlsConfig::new().with_enabled_roots())
+        .map_err(|e| e.to_string())?
+        .connect()
+        .await
+        .map_err(|e| e.to_string())?;
+    let (mut subscribe_tx, mut stream) = client
+        .subscribe_with_request(Some(tracker.subscribe_request(config)))
+        .await
+        .map_err(|e| e.to_string())?;
+    log::info!("📈 Pool state stream connected ({} pool accounts)", tracker.len());
+
+    loop {
+        tokio::select! {
+            pool = pools.recv() => {
+                let Some(pool) = pool else {
+                    return Ok(());
+                };
+                if !tracker.add(pool) {
+                    continue;
+                }
+                // Replaces the subscription's filters with the grown pool set
+                subscribe_tx
+                    .send(tracker.subscribe_request(config))
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            message = stream.next() => {
+                let Some(message) = message else {
+                    return Err("stream ended".to_string());
+                };
+                match message.map_err(|e| e.to_string())?.update_oneof {
+                    Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                        account: Some(account),
+                        slot,
+                        ..
+                    })) => {
+                        let Ok(pubkey) = Pubkey::try_from(account.pubkey.as_slice()) else {
+                            continue;
+                        };
+                        if let Some(update) = tracker.update(&pubkey, &account.data, slot) {
+                            if tx.send(update).await.is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                    Some(UpdateOneof::Ping(_)) => {
+                        subscribe_tx
+                            .send(SubscribeRequest {
+                                ping: Some(SubscribeRequestPing { id: 1 }),
+                                ..Default::default()
+                            })
+                            .await
+                            .map_err(|e| e.to_string())?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve_data(virtual_tokens: u64, virtual_sol: u64, complete: bool) -> Vec<u8> {
+        let mut data = vec![0u8; 81];
+        data[..8].copy_from_slice(&BONDING_CURVE_DISCRIMINATOR);
+        data[CURVE_VIRTUAL_TOKEN_RESERVES].copy_from_slice(&virtual_tokens.to_le_bytes());
+        data[CURVE_VIRTUAL_SOL_RESERVES].copy_from_slice(&virtual_sol.to_le_bytes());
+        data[CURVE_COMPLETE] = complete as u8;
+        data
+    }
+
+    #[test]
+    fn test_tracker_prices_bonding_curves() {
+        let mut tracker = PoolStateTracker::new(2);
+        let curve = Pubkey::new_from_array([1; 32]);
+        let mint = Pubkey::new_from_array([2; 32]);
+        let pool = PoolAccount {
+            mint,
+            account: curve,
+            kind: PoolKind::BondingCurve,
+            token_decimals: 6,
+        };
+        assert!(tracker.add(pool.clone()));
+        assert!(!tracker.add(pool));
+
+        // A fresh curve: 30 SOL against 1.073B tokens
+        let update = tracker
+            .update(&curve, &curve_data(1_073_000_000_000_000, 30_000_000_000, false), 10)
+            .unwrap();
+        assert_eq!(update.mint, mint.to_string());
+        assert!((update.price_sol - 30.0 / 1_073_000_000.0).abs() < 1e-15);
+        assert_eq!(update.slot, 10);
+
+        // Unchanged reserves, unknown accounts and short data report nothing
+        assert!(tracker
+            .update(&curve, &curve_data(1_073_000_000_000_000, 30_000_000_000, false), 11)
+            .is_none());
+        assert!(tracker
+            .update(&Pubkey::new_from_array([9; 32]), &curve_data(1, 1, false), 11)
+            .is_none());
+        assert!(tracker.update(&curve, &[0u8; 20], 11).is_none());
+
+        // Curves created before `creator` was added are 49 bytes
+        let buy = curve_data(800_000_000_000_000, 40_000_000_000, false);
+        let update = tracker.update(&curve, &buy[..49], 12).unwrap();
+        assert!((update.price_sol - 40.0 / 800_000_000.0).abs() < 1e-15);
+
+        // A completed curve is dropped
+        assert!(tracker.update(&curve, &curve_data(1, 1, true), 13).is_none());
+        assert!(tracker.is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Postgres writer backend
+//!
+//! Lets streamer instances on several hosts write into one central database.
+//! Behaves like `SqliteWriter`: trades are buffered and flushed as a single
+//! multi-row INSERT per batch, and duplicate signatures are ignored.
+//!
+//! The schema is applied on connect from the numbered migrations in
+//! `sql/postgres/`, recorded in `schema_migrations`. Instances that start at
+//! the same time serialize on an advisory lock, so each migration runs once.
+//!
+//! Environment variables:
+//! - `SOLFLOW_POSTGRES_URL`: Connection string, e.g. `postgres://user:pw@host/solflow`
+//!   (accepts `file:` / `keyring:` references, see `secrets`)
+//! - `POSTGRES_MAX_CONNECTIONS`: Pool size (default: 5)
+
+use crate::streamer_core::{
+    output_writer::TradeEvent,
+    secrets::{redact_url, secret_from_env},
+    writer_backend::{WriterBackend, WriterError},
+};
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::{Postgres, QueryBuilder};
+use std::time::{Duration, Instant};
+
+/// (version, DDL) in apply order; never edit a shipped migration, add a new one
+const MIGRATIONS: &[(i32, &str)] = &[(1, include_str!("../../sql/postgres/001_trades.sql"))];
+
+/// Advisory lock key held while migrating ("solflow" in ASCII)
+const MIGRATION_LOCK_ID: i64 = 0x736f_6c66_6c6f_77;
+
+/// Read `SOLFLOW_POSTGRES_URL`
+pub fn database_url_from_env() -> Result<String, WriterError> {
+    secret_from_env("SOLFLOW_POSTGRES_URL")
+        .map_err(WriterError::Database)?
+        .ok_or_else(|| WriterError::Database("SOLFLOW_POSTGRES_URL is not set".to_string()))
+}
+
+pub struct PostgresWriter {
+    pool: PgPool,
+    batch: Vec<TradeEvent>,
+    batch_size: usize,
+    last_flush: Instant,
+    flush_interval_secs: u64,
+}
+
+impl PostgresWriter {
+    /// Connect, creating a pool of `POSTGRES_MAX_CONNECTIONS`, and run pending migrations
+    pub async fn connect(database_url: &str) -> Result<Self, WriterError> {
+        let max_connections = std::env::var("POSTGRES_MAX_CONNECTIONS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(5);
+
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .acquire_timeout(Duration::from_secs(10))
+            .connect(database_url)
+            .await?;
+
+        let applied = migrate(&pool).await?;
+
+        log::info!(
+            "✅ Postgres connected: {} (pool: {}, {} migration(s) applied)",
+            redact_url(database_url),
+            max_connections,
+            applied
+        );
+
+        Ok(Self {
+            pool,
+            batch: Vec::with_capacity(100),
+            batch_size: 100,
+            last_flush: Instant::now(),
+            flush_interval_secs: 2,
+        })
+    }
+
+    async fn flush_batch(&mut self) -> Result<(), WriterError> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO trades
+             (program, program_name, mint, signature, action, sol_amount,
+              token_amount, token_decimals, user_account, discriminator, timestamp) ",
+        );
+        query.push_values(&self.batch, |mut row, event| {
+            row.push_bind(&event.program_id)
+                .push_bind(&event.program_name)
+                .push_bind(&event.mint)
+                .push_bind(&event.signature)
+                .push_bind(&event.action)
+                .push_bind(event.sol_amount)
+                .push_bind(event.token_amount)
+                .push_bind(event.token_decimals as i32)
+                .push_bind(&event.user_account)
+                .push_bind(&event.discriminator)
+                .push_bind(event.timestamp);
+        });
+        query.push(" ON CONFLICT (signature) DO NOTHING");
+        query.build().execute(&self.pool).await?;
+
+        log::debug!("✅ Flushed {} trades to Postgres", self.batch.len());
+        self.batch.clear();
+        self.last_flush = Instant::now();
+
+        Ok(())
+    }
+}
+
+/// Apply migrations not yet recorded in `schema_migrations`; returns how many ran
+async fn migrate(pool: &PgPool) -> Result<usize, WriterError> {
+    let mut tx = pool.begin().await?;
+
+    // Released on commit/rollback
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(MIGRATION_LOCK_ID)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at BIGINT NOT NULL
+        )",
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let applied: Vec<i32> = sqlx::query_scalar("SELECT version FROM schema_migrations")
+        .fetch_all(&mut *tx)
+        .await?;
+
+    let mut count = 0;
+    for (version, ddl) in MIGRATIONS {
+        if applied.contains(version) {
+            continue;
+        }
+        sqlx::raw_sql(ddl).execute(&mut *tx).await?;
+        sqlx::query("INSERT INTO schema_migrations (version, applied_at) VALUES ($1, $2)")
+            .bind(version)
+            .bind(chrono::Utc::now().timestamp())
+            .execute(&mut *tx)
+            .await?;
+        log::info!("📐 Applied Postgres migration {}", version);
+        count += 1;
+    }
+
+    tx.commit().await?;
+    Ok(count)
+}
+
+#[async_trait]
+impl WriterBackend for PostgresWriter {
+    async fn write(&mut self, event: &TradeEvent) -> Result<(), WriterError> {
+        self.batch.push(event.clone());
+
+        // Auto-flush if batch full or time elapsed
+        if self.batch.len() >= self.batch_size
+           || self.last_flush.elapsed().as_secs() >= self.flush_interval_secs {
+            self.flush_batch().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), WriterError> {
+        self.flush_batch().await
+    }
+
+    fn backend_type(&self) -> &'static str {
+        "Postgres"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_event(signature: &str) -> TradeEvent {
+        TradeEvent {
+            timestamp: 1700000000,
+            signature: signature.to_string(),
+            program_id: "test_program".to_string(),
+            program_name: "TestDEX".to_string(),
+            action: "BUY".to_string(),
+            mint: "test_mint".to_string(),
+            sol_amount: 1.5,
+            token_amount: 1000.0,
+            token_decimals: 6,
+            user_account: Some("user1".to_string()),
+            discriminator: "0123456789abcdef".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_migrations_ordered() {
+        assert!(MIGRATIONS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(MIGRATIONS.iter().all(|(version, _)| *version > 0));
+    }
+
+    /// Needs a scratch database: TEST_POSTGRES_URL=postgres://... cargo test
+    #[tokio::test]
+    async fn test_postgres_batch_write() {
+        let Ok(url) = std::env::var("TEST_POSTGRES_URL") else {
+            eprintln!("TEST_POSTGRES_URL not set, skipping");
+            return;
+        };
+        let mut writer = PostgresWriter::connect(&url).await.unwrap();
+        // A second instance finds nothing left to migrate
+        let second = PostgresWriter::connect(&url).await.unwrap();
+        assert_eq!(migrate(&second.pool).await.unwrap(), 0);
+
+        let run = chrono::Utc::now().timestamp_nanos_opt().unwrap();
+        for i in 0..150 {
+            let event = create_test_event(&format!("pg_test_{}_{}", run, i));
+            writer.write(&event).await.unwrap();
+        }
+        writer.write(&create_test_event(&format!("pg_test_{}_0", run))).await.unwrap(); // Duplicate
+        writer.flush().await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM trades WHERE signature LIKE $1")
+            .bind(format!("pg_test_{}_%", run))
+            .fetch_one(&writer.pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 150);
+    }
+}
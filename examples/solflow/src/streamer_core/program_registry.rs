@@ -0,0 +1,406 @@
+//! Tracked-program registry, optionally loaded from a TOML file
+//!
+//! The `InstructionScanner` is built from a `ProgramRegistry`: the built-in
+//! one mirrors the programs the streamer shipped with, and
+//! `PROGRAMS_CONFIG_PATH` replaces it with a file. The unified streamer
+//! re-reads the file when it changes and, without a restart, swaps in a new
+//! scanner and replaces the program filters of the gRPC subscription (see
+//! `subscription`). Invalid edits are logged and the previous registry stays
+//! active.
+//!
+//! Discriminators are hex instruction prefixes that say what an instruction
+//! does:
+//!
+//! - `buy_discriminators` / `sell_discriminators`: the direction of the
+//!   trade; the trade detector prefers it over balance deltas (see
+//!   `trade_detector::apply_instruction_action`)
+//! - `swap_discriminators`: trades of either direction (from balance deltas)
+//! - `add_liquidity_discriminators` / `remove_liquidity_discriminators`:
+//!   never matched, so deposits and withdrawals aren't recorded as trades
+//!
+//! A program with `swap_discriminators` only matches its swap, buy and sell
+//! instructions; any other program matches every instruction that isn't a
+//! liquidity one.
+//!
+//! Example (`programs.toml`):
+//! ```toml
+//! [[program]]
+//! program_id = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA"
+//! name = "PumpSwap"
+//! category = "amm"
+//! buy_discriminators = ["66063d1201daebea"]
+//! sell_discriminators = ["33e685a4017f83ad"]
+//! add_liquidity_discriminators = ["f223c68952e1f2b6"]
+//! remove_liquidity_discriminators = ["b712469c946da122"]
+//!
+//! [[program]]
+//! program_id = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"
+//! name = "RaydiumAMM"
+//! category = "amm"
+//! swap_discriminators = ["09", "0b", "10", "11"]
+//! ```
+//!
+//! Environment variables:
+//! - `PROGRAMS_CONFIG_PATH`: Registry TOML file (default: built-in programs)
+//! - `PROGRAMS_RELOAD_SECS`: How often the file is checked for changes (default: 10, 0 = never)
+
+use crate::instruction_scanner::{
+    InstructionScanner, METEORA_DAMM_SWAPS, METEORA_DAMM_V2_SWAPS, METEORA_DLMM_SWAPS,
+    ORCA_WHIRLPOOL_SWAPS, PUMPSWAP_BUY, PUMPSWAP_DEPOSIT, PUMPSWAP_SELL, PUMPSWAP_WITHDRAW,
+    RAYDIUM_AMM_V4_SWAPS, RAYDIUM_CLMM_SWAPS,
+};
+use crate::streamer_core::subscription::SubscriptionControl;
+use crate::streamer_core::trade_detector::InstructionAction;
+use serde::{Deserialize, Deserializer};
+use solana_pubkey::Pubkey;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio_util::sync::CancellationToken;
+
+/// One tracked program
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramEntry {
+    pub program_id: String,
+    /// Recorded as the trade's `program_name`
+    pub name: String,
+    /// launchpad, amm, clmm, dlmm, dca, ... (informational)
+    #[serde(default = "default_category")]
+    pub category: String,
+    #[serde(default, deserialize_with = "hex_list")]
+    pub buy_discriminators: Vec<Vec<u8>>,
+    #[serde(default, deserialize_with = "hex_list")]
+    pub sell_discriminators: Vec<Vec<u8>>,
+    /// Swaps whose direction comes from balance deltas
+    #[serde(default, deserialize_with = "hex_list")]
+    pub swap_discriminators: Vec<Vec<u8>>,
+    #[serde(default, deserialize_with = "hex_list")]
+    pub add_liquidity_discriminators: Vec<Vec<u8>>,
+    #[serde(default, deserialize_with = "hex_list")]
+    pub remove_liquidity_discriminators: Vec<Vec<u8>>,
+}
+
+fn default_category() -> String {
+    "other".to_string()
+}
+
+fn hex_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| hex::decode(s).map_err(|e| serde::de::Error::custom(format!("'{}': {}", s, e))))
+        .collect()
+}
+
+impl ProgramEntry {
+    fn builtin(program_id: &str, name: &str, category: &str, swaps: &[&[u8]]) -> Self {
+        Self {
+            program_id: program_id.to_string(),
+            name: name.to_string(),
+            category: category.to_string(),
+            buy_discriminators: Vec::new(),
+            sell_discriminators: Vec::new(),
+            swap_discriminators: swaps.iter().map(|d| d.to_vec()).collect(),
+            add_liquidity_discriminators: Vec::new(),
+            remove_liquidity_discriminators: Vec::new(),
+        }
+    }
+
+    /// Discriminator table: instruction prefix → action
+    pub fn actions(&self) -> Vec<(Vec<u8>, InstructionAction)> {
+        let tagged = |discriminators: &Vec<Vec<u8>>, action| {
+            discriminators.iter().map(move |d| (d.clone(), action)).collect::<Vec<_>>()
+        };
+        [
+            tagged(&self.buy_discriminators, InstructionAction::Buy),
+            tagged(&self.sell_discriminators, InstructionAction::Sell),
+            tagged(&self.swap_discriminators, InstructionAction::Swap),
+            tagged(&self.add_liquidity_discriminators, InstructionAction::AddLiquidity),
+            tagged(&self.remove_liquidity_discriminators, InstructionAction::RemoveLiquidity),
+        ]
+        .concat()
+    }
+
+    /// Whether only swap, buy and sell instructions match
+    pub fn swap_only(&self) -> bool {
+        !self.swap_discriminators.is_empty()
+    }
+}
+
+/// The programs the unified streamer tracks
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramRegistry {
+    #[serde(rename = "program")]
+    pub programs: Vec<ProgramEntry>,
+}
+
+impl ProgramRegistry {
+    /// The programs the streamer ships with
+    pub fn builtin() -> Self {
+        Self {
+            programs: vec![
+                ProgramEntry::builtin("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P", "PumpFun", "launchpad", &[]),
+                ProgramEntry {
+                    buy_discriminators: vec![PUMPSWAP_BUY.to_vec()],
+                    sell_discriminators: vec![PUMPSWAP_SELL.to_vec()],
+                    add_liquidity_discriminators: vec![PUMPSWAP_DEPOSIT.to_vec()],
+                    remove_liquidity_discriminators: vec![PUMPSWAP_WITHDRAW.to_vec()],
+                    ..ProgramEntry::builtin("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA", "PumpSwap", "amm", &[])
+                },
+                ProgramEntry::builtin("LanMV9sAd7wArD4vJFi2qDdfnVhFxYSUg6eADduJ3uj", "BonkSwap", "launchpad", &[]),
+                ProgramEntry::builtin("MoonCVVNZFSYkqNXP6bxHLPL6QQJiMagDL3qcqUQTrG", "Moonshot", "launchpad", &[]),
+                ProgramEntry::builtin("DCA265Vj8a9CEuX1eb1LWRnDT7uK6q1xMipnNyatn23M", "JupiterDCA", "dca", &[]),
+                ProgramEntry::builtin(
+                    "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+                    "RaydiumAMM",
+                    "amm",
+                    RAYDIUM_AMM_V4_SWAPS,
+                ),
+                ProgramEntry::builtin(
+                    "CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK",
+                    "RaydiumCLMM",
+                    "clmm",
+                    RAYDIUM_CLMM_SWAPS,
+                ),
+                ProgramEntry::builtin(
+                    "whirLbMiicVdio4qvUfM5KaG6Ct8VwpYzGff3uctyCc",
+                    "OrcaWhirlpool",
+                    "clmm",
+                    ORCA_WHIRLPOOL_SWAPS,
+                ),
+                ProgramEntry::builtin(
+                    "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo",
+                    "MeteoraDLMM",
+                    "dlmm",
+                    METEORA_DLMM_SWAPS,
+                ),
+                ProgramEntry::builtin(
+                    "Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB",
+                    "MeteoraDAMM",
+                    "amm",
+                    METEORA_DAMM_SWAPS,
+                ),
+                ProgramEntry::builtin(
+                    "cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG",
+                    "MeteoraDAMMv2",
+                    "amm",
+                    METEORA_DAMM_V2_SWAPS,
+                ),
+            ],
+        }
+    }
+
+    /// Parse and validate a TOML document
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let registry: Self =
+            toml::from_str(content).map_err(|e| format!("invalid programs TOML: {}", e))?;
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Self::parse(&content)
+    }
+
+    /// The PROGRAMS_CONFIG_PATH file if set, the built-in registry otherwise
+    pub fn from_env() -> Result<Self, String> {
+        match registry_path() {
+            Some(path) => Self::from_file(&path),
+            None => Ok(Self::builtin()),
+        }
+    }
+
+    /// Reject registries the scanner or the gRPC filters can't use
+    pub fn validate(&self) -> Result<(), String> {
+        if self.programs.is_empty() {
+            return Err("no programs defined".to_string());
+        }
+        let mut ids = HashSet::new();
+        let mut names = HashSet::new();
+        for entry in &self.programs {
+            Pubkey::from_str(&entry.program_id)
+                .map_err(|_| format!("{}: '{}' is not a base58 pubkey", entry.name, entry.program_id))?;
+            if entry.name.trim().is_empty() {
+                return Err(format!("{}: name is empty", entry.program_id));
+            }
+            // Filter names are the lowercased program names
+            if !names.insert(entry.name.to_lowercase()) {
+                return Err(format!("program name '{}' is used twice", entry.name));
+            }
+            if !ids.insert(entry.program_id.as_str()) {
+                return Err(format!("program {} is listed twice", entry.program_id));
+            }
+            if entry.actions().iter().any(|(discriminator, _)| discriminator.is_empty()) {
+                return Err(format!("{}: empty discriminator", entry.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// (filter name, program ID) pairs for the gRPC subscription
+    pub fn subscription_programs(&self) -> Vec<(String, String)> {
+        self.programs
+            .iter()
+            .map(|entry| (entry.name.to_lowercase(), entry.program_id.clone()))
+            .collect()
+    }
+}
+
+/// PROGRAMS_CONFIG_PATH, if set
+pub fn registry_path() -> Option<PathBuf> {
+    std::env::var("PROGRAMS_CONFIG_PATH")
+        .ok()
+        .filter(|path| !path.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Scanner for the registry the environment selects
+pub fn scanner_from_env() -> Result<InstructionScanner, String> {
+    let registry = ProgramRegistry::from_env()?;
+    if let Some(path) = registry_path() {
+        log::info!("📋 Loaded {} tracked programs from {}", registry.programs.len(), path.display());
+    }
+    InstructionScanner::from_registry(&registry)
+}
+
+/// The scanner in use, replaced when the registry file changes
+#[derive(Clone)]
+pub struct SharedScanner(Arc<RwLock<Arc<InstructionScanner>>>);
+
+impl SharedScanner {
+    pub fn new(scanner: InstructionScanner) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(scanner))))
+    }
+
+    pub fn current(&self) -> Arc<InstructionScanner> {
+        self.0.read().unwrap().clone()
+    }
+
+    pub fn replace(&self, scanner: InstructionScanner) {
+        *self.0.write().unwrap() = Arc::new(scanner);
+    }
+}
+
+/// Re-apply the registry file to `scanner` and `subscription` whenever it changes
+///
+/// Does nothing without PROGRAMS_CONFIG_PATH or with PROGRAMS_RELOAD_SECS=0.
+pub fn spawn_registry_reload(
+    scanner: SharedScanner,
+    subscription: Option<Arc<SubscriptionControl>>,
+    stop: CancellationToken,
+) {
+    let Some(path) = registry_path() else {
+        return;
+    };
+    let reload_secs = std::env::var("PROGRAMS_RELOAD_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(10u64);
+    if reload_secs == 0 {
+        return;
+    }
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    tokio::spawn(async move {
+        let mut last_modified: Option<SystemTime> = modified(&path);
+        let mut interval = tokio::time::interval(Duration::from_secs(reload_secs));
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = stop.cancelled() => return,
+                _ = interval.tick() => {}
+            }
+            let current = modified(&path);
+            if current.is_none() || current == last_modified {
+                continue;
+            }
+            last_modified = current;
+
+            let reloaded = ProgramRegistry::from_file(&path).and_then(|registry| {
+                let next = InstructionScanner::from_registry(&registry)?;
+                Ok((registry, next))
+            });
+            let (registry, next) = match reloaded {
+                Ok(reloaded) => reloaded,
+                Err(e) => {
+                    log::warn!(
+                        "⚠️  Ignoring programs change in {}: {} (keeping previous programs)",
+                        path.display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            if let Some(control) = &subscription {
+                if let Err(e) = control.set_programs(registry.subscription_programs()) {
+                    log::warn!("⚠️  Ignoring programs change in {}: {}", path.display(), e);
+                    continue;
+                }
+            }
+            scanner.replace(next);
+            log::info!("📋 Reloaded {} tracked programs from {}", registry.programs.len(), path.display());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_validates_and_builds_scanner() {
+        let registry = ProgramRegistry::parse(
+            r#"
+            [[program]]
+            program_id = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P"
+            name = "PumpFun"
+            category = "launchpad"
+            buy_discriminators = ["66063d1201daebea"]
+            sell_discriminators = ["33e685a4017f83ad"]
+
+            [[program]]
+            program_id = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4"
+            name = "Jupiter"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(registry.programs[1].category, "other");
+        assert_eq!(
+            registry.programs[0].actions()[1],
+            (vec![0x33, 0xe6, 0x85, 0xa4, 0x01, 0x7f, 0x83, 0xad], InstructionAction::Sell)
+        );
+        assert!(!registry.programs[0].swap_only());
+        assert_eq!(
+            registry.subscription_programs()[0],
+            ("pumpfun".to_string(), "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P".to_string())
+        );
+
+        let scanner = InstructionScanner::from_registry(&registry).unwrap();
+        assert_eq!(scanner.program_count(), 2);
+        let jupiter = Pubkey::from_str("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4").unwrap();
+        assert_eq!(scanner.program_name(&jupiter), Some("Jupiter"));
+        assert_eq!(scanner.program_category(&jupiter), Some("other"));
+
+        // Bad pubkeys, bad hex, duplicates and unknown keys are rejected
+        let bad = [
+            "[[program]]\nprogram_id = \"nope\"\nname = \"X\"",
+            "[[program]]\nprogram_id = \"6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P\"\nname = \"X\"\nbuy_discriminators = [\"zz\"]",
+            "[[program]]\nprogram_id = \"6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P\"\nname = \"X\"\n[[program]]\nprogram_id = \"JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4\"\nname = \"x\"",
+            "[[program]]\nprogram_id = \"6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P\"\nname = \"X\"\nprice = 1",
+            "program = []",
+        ];
+        for content in bad {
+            assert!(ProgramRegistry::parse(content).is_err(), "accepted: {}", content);
+        }
+
+        // The built-in registry is what the scanner ships with
+        assert!(ProgramRegistry::builtin().validate().is_ok());
+        assert_eq!(ProgramRegistry::builtin().programs.len(), 11);
+    }
+}
@@ -0,0 +1,141 @@
+//! pump.fun bonding-curve event decoding
+//!
+//! The bonding curve CPIs a `TradeEvent` into itself for every buy and sell,
+//! naming the mint, the user and the exact SOL and token amounts. Decoding
+//! those is more reliable than balance deltas, which also see the fee
+//! recipient, the creator vault and any bundled instructions.
+//!
+//! When a curve completes, pump.fun's `migrate` moves its liquidity into a
+//! PumpSwap pool and logs a `CompletePumpAmmMigrationEvent`; those become
+//! `TokenMigration`s for the pipeline (TOKEN_MIGRATED signals). Tokens that
+//! graduated to Raydium before pump.fun switched to PumpSwap were migrated by
+//! an off-program wallet and aren't detected.
+
+use crate::pipeline::types::TokenMigration;
+use crate::streamer_core::jupiter_route::token_decimals;
+use crate::streamer_core::trade_detector::{TradeDirection, TradeInfo};
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_core::transaction::TransactionMetadata;
+use carbon_pumpfun_decoder::instructions::complete_pump_amm_migration_event::CompletePumpAmmMigrationEvent;
+use carbon_pumpfun_decoder::instructions::trade_event::TradeEvent as CurveTradeEvent;
+use carbon_pumpfun_decoder::PROGRAM_ID as PUMPFUN_PROGRAM_ID;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+
+/// Decimals of every pump.fun token (used when the transaction has no balance for the mint)
+const PUMPFUN_TOKEN_DECIMALS: u8 = 6;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Data of every instruction pump.fun CPI'd into itself (its event log)
+fn event_data<'a>(
+    metadata: &'a TransactionMetadata,
+    account_keys: &'a [Pubkey],
+) -> impl Iterator<Item = &'a [u8]> {
+    metadata
+        .meta
+        .inner_instructions
+        .iter()
+        .flatten()
+        .flat_map(|inner_group| &inner_group.instructions)
+        .map(|inner| &inner.instruction)
+        .filter(|ix| account_keys.get(ix.program_id_index as usize) == Some(&PUMPFUN_PROGRAM_ID))
+        .map(|ix| ix.data.as_slice())
+}
+
+/// One trade for a bonding-curve `TradeEvent`
+pub fn curve_trade(event: &CurveTradeEvent, decimals: &HashMap<Pubkey, u8>) -> TradeInfo {
+    let token_decimals = decimals.get(&event.mint).copied().unwrap_or(PUMPFUN_TOKEN_DECIMALS);
+    TradeInfo {
+        mint: event.mint.to_string(),
+        sol_amount: event.sol_amount as f64 / LAMPORTS_PER_SOL,
+        token_amount: event.token_amount as f64 / 10f64.powi(token_decimals as i32),
+        token_decimals,
+        direction: if event.is_buy {
+            TradeDirection::Buy
+        } else {
+            TradeDirection::Sell
+        },
+        user_account: Some(event.user),
+        program_id: Some(PUMPFUN_PROGRAM_ID),
+    }
+}
+
+/// One trade per bonding-curve buy or sell in this transaction
+///
+/// None when pump.fun logged no trade (use balance deltas instead).
+pub fn extract_bonding_curve_trades(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+) -> Option<Vec<TradeInfo>> {
+    let decimals = token_decimals(&metadata.meta);
+    let trades: Vec<TradeInfo> = event_data(metadata, account_keys)
+        .filter_map(CurveTradeEvent::deserialize)
+        .map(|event| curve_trade(&event, &decimals))
+        .collect();
+    (!trades.is_empty()).then_some(trades)
+}
+
+/// Curve-to-PumpSwap migrations completed in this transaction
+pub fn migrations(metadata: &TransactionMetadata, account_keys: &[Pubkey]) -> Vec<TokenMigration> {
+    let decimals = token_decimals(&metadata.meta);
+    event_data(metadata, account_keys)
+        .filter_map(CompletePumpAmmMigrationEvent::deserialize)
+        .map(|event| TokenMigration {
+            mint: event.mint.to_string(),
+            launch_platform: "pumpfun".to_string(),
+            destination: "pumpswap".to_string(),
+            pool: event.pool.to_string(),
+            sol_amount: event.sol_amount as f64 / LAMPORTS_PER_SOL,
+            token_decimals: decimals.get(&event.mint).copied().unwrap_or(PUMPFUN_TOKEN_DECIMALS),
+            signature: metadata.signature.to_string(),
+            timestamp: event.timestamp,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `TradeEvent` CPI data: event tag + discriminator, then the Borsh fields
+    fn trade_event_data(mint: Pubkey, sol_amount: u64, token_amount: u64, is_buy: bool) -> Vec<u8> {
+        let mut data = vec![0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+        data.extend([0xbd, 0xdb, 0x7f, 0xd3, 0x4e, 0xe6, 0x61, 0xee]);
+        data.extend(mint.to_bytes());
+        data.extend(sol_amount.to_le_bytes());
+        data.extend(token_amount.to_le_bytes());
+        data.push(is_buy as u8);
+        data.extend([9u8; 32]); // user
+        data.extend(1_700_000_000i64.to_le_bytes()); // timestamp
+        data.extend([0u8; 32]); // virtual/real reserves
+        data.extend([0u8; 32]); // fee_recipient
+        data.extend([0u8; 16]); // fee_basis_points, fee
+        data.extend([0u8; 32]); // creator
+        data.extend([0u8; 16]); // creator_fee_basis_points, creator_fee
+        data.push(0); // track_volume
+        data.extend([0u8; 24]); // total_unclaimed_tokens, total_claimed_tokens, current_sol_volume
+        data.extend(0i64.to_le_bytes()); // last_update_timestamp
+        data
+    }
+
+    #[test]
+    fn test_curve_trade_event_becomes_trade() {
+        let mint = Pubkey::new_from_array([7; 32]);
+        let data = trade_event_data(mint, 1_500_000_000, 2_000_000_000_000, false);
+        let event = CurveTradeEvent::deserialize(&data).unwrap();
+
+        let trade = curve_trade(&event, &HashMap::new());
+        assert_eq!(trade.mint, mint.to_string());
+        assert!(matches!(trade.direction, TradeDirection::Sell));
+        assert!((trade.sol_amount - 1.5).abs() < 1e-9);
+        assert!((trade.token_amount - 2_000_000.0).abs() < 1e-6);
+        assert_eq!(trade.user_account, Some(Pubkey::new_from_array([9; 32])));
+        assert_eq!(trade.program_id, Some(PUMPFUN_PROGRAM_ID));
+
+        // Migration events don't decode as trades
+        let mut migration = data;
+        migration[8..16].copy_from_slice(&[0xbd, 0xe9, 0x5d, 0xb9, 0x5c, 0x94, 0xea, 0x94]);
+        assert!(CurveTradeEvent::deserialize(&migration).is_none());
+    }
+}
@@ -0,0 +1,258 @@
+//! Dual-provider redundancy: two Geyser subscriptions merged into one stream
+//!
+//! With `GEYSER_SECONDARY_URL` set, the unified streamer subscribes to its
+//! Yellowstone endpoint and to a second provider at the same time, with the
+//! same filters. `RedundantDatasource` forwards the first copy of every
+//! transaction to the pipeline and drops the second, so when one provider
+//! stalls or drops data the other covers it without a reconnect.
+//!
+//! The two streams are also reconciled per slot. Once the merged stream is
+//! `RECONCILE_DELAY_SLOTS` past a slot, a source that delivered later slots
+//! but none of the transactions the other delivered in that slot has missed
+//! it. Missed slots and each source's lag behind the other are reported as
+//! metrics every `REPORT_INTERVAL`, so a provider that silently drops slots
+//! shows up in the dashboards. Slots above a source's highest slot count as
+//! lag, not as missed.
+//!
+//! Environment variables:
+//! - `GEYSER_SECONDARY_URL`: Second Yellowstone endpoint (default: disabled)
+//! - `GEYSER_SECONDARY_X_TOKEN`: Its token, same forms as X_TOKEN (default: none)
+
+use crate::streamer_core::metrics::{
+    PRIMARY_SOURCE_MISSED_SLOTS, PRIMARY_SOURCE_SLOT_LAG, REDUNDANT_DUPLICATES,
+    SECONDARY_SOURCE_MISSED_SLOTS, SECONDARY_SOURCE_SLOT_LAG,
+};
+use crate::streamer_core::rpc_client::SignatureDedup;
+use async_trait::async_trait;
+use carbon_core::{
+    datasource::{Datasource, DatasourceId, Update, UpdateType},
+    error::{CarbonResult, Error as CarbonError},
+    metrics::MetricsCollection,
+};
+use solana_signature::Signature;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Sender};
+use tokio_util::sync::CancellationToken;
+
+/// Slots the merged stream must be past a slot before it is reconciled
+const RECONCILE_DELAY_SLOTS: u64 = 32;
+
+/// Time between reconciliation reports
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Source names for logs and datasource ids, primary first
+const SOURCE_NAMES: [&str; 2] = ["primary", "secondary"];
+const SOURCE_SLOT_LAG: [&str; 2] = [PRIMARY_SOURCE_SLOT_LAG, SECONDARY_SOURCE_SLOT_LAG];
+const SOURCE_MISSED_SLOTS: [&str; 2] = [PRIMARY_SOURCE_MISSED_SLOTS, SECONDARY_SOURCE_MISSED_SLOTS];
+
+/// Per-slot delivery bookkeeping for the two sources
+#[derive(Debug, Default)]
+pub struct SlotReconciler {
+    dedup: SignatureDedup,
+    /// Highest slot each source delivered a transaction in
+    highest: [Option<u64>; 2],
+    /// Unreconciled slots and which sources delivered in them
+    pending: BTreeMap<u64, [bool; 2]>,
+    /// Slots up to here are reconciled; late deliveries are not tracked
+    reconciled_through: u64,
+    /// Slots each source missed since start
+    missed: [u64; 2],
+}
+
+impl SlotReconciler {
+    /// Record a transaction from `source`; true for the first copy
+    pub fn observe(&mut self, source: usize, signature: Signature, slot: u64) -> bool {
+        self.highest[source] = self.highest[source].max(Some(slot));
+        if slot > self.reconciled_through {
+            self.pending.entry(slot).or_default()[source] = true;
+        }
+        self.dedup.insert(signature)
+    }
+
+    /// Reconcile slots the stream has moved past; returns slots missed per source
+    pub fn reconcile(&mut self) -> [u64; 2] {
+        let mut missed = [0; 2];
+        let Some(head) = self.highest.iter().flatten().max().copied() else {
+            return missed;
+        };
+        while let Some((&slot, &delivered)) = self.pending.first_key_value() {
+            if slot + RECONCILE_DELAY_SLOTS > head {
+                break;
+            }
+            self.pending.pop_first();
+            self.reconciled_through = slot;
+            for source in 0..2 {
+                let past_slot = self.highest[source].is_some_and(|highest| highest > slot);
+                if !delivered[source] && past_slot {
+                    missed[source] += 1;
+                }
+            }
+        }
+        for source in 0..2 {
+            self.missed[source] += missed[source];
+        }
+        missed
+    }
+
+    /// Slots `source` is behind the other source
+    pub fn slot_lag(&self, source: usize) -> Option<u64> {
+        let head = self.highest.iter().flatten().max()?;
+        Some(head - self.highest[source]?)
+    }
+
+    /// Slots `source` missed since start
+    pub fn missed(&self, source: usize) -> u64 {
+        self.missed[source]
+    }
+}
+
+/// Two datasources consumed at once and merged (see module docs)
+pub struct RedundantDatasource {
+    sources: [Arc<dyn Datasource + Send + Sync>; 2],
+}
+
+impl RedundantDatasource {
+    pub fn new(primary: impl Datasource + 'static, secondary: impl Datasource + 'static) -> Self {
+        Self {
+            sources: [Arc::new(primary), Arc::new(secondary)],
+        }
+    }
+}
+
+#[async_trait]
+impl Datasource for RedundantDatasource {
+    async fn consume(
+        &self,
+        id: DatasourceId,
+        sender: Sender<(Update, DatasourceId)>,
+        cancellation_token: CancellationToken,
+        metrics: Arc<MetricsCollection>,
+    ) -> CarbonResult<()> {
+        let source_ids = SOURCE_NAMES.map(DatasourceId::new_named);
+        let (merged_tx, merged_rx) = mpsc::channel(sender.max_capacity());
+        tokio::spawn(merge(
+            merged_rx,
+            source_ids.clone(),
+            id,
+            sender,
+            cancellation_token.clone(),
+            metrics.clone(),
+        ));
+
+        let (primary, secondary) = futures::future::join(
+            self.sources[0].consume(
+                source_ids[0].clone(),
+                merged_tx.clone(),
+                cancellation_token.clone(),
+                metrics.clone(),
+            ),
+            self.sources[1].consume(source_ids[1].clone(), merged_tx, cancellation_token, metrics),
+        )
+        .await;
+
+        match (primary, secondary) {
+            (Err(e), Err(_)) => Err(CarbonError::FailedToConsumeDatasource(format!(
+                "both Geyser sources failed: {}",
+                e
+            ))),
+            (Err(e), Ok(())) | (Ok(()), Err(e)) => {
+                log::warn!("⚠️  One Geyser source failed, continuing on the other: {}", e);
+                Ok(())
+            }
+            (Ok(()), Ok(())) => Ok(()),
+        }
+    }
+
+    fn update_types(&self) -> Vec<UpdateType> {
+        self.sources[0].update_types()
+    }
+}
+
+/// Forward first copies from both sources to the pipeline, reporting as it goes
+async fn merge(
+    mut merged_rx: mpsc::Receiver<(Update, DatasourceId)>,
+    source_ids: [DatasourceId; 2],
+    id: DatasourceId,
+    sender: Sender<(Update, DatasourceId)>,
+    cancellation_token: CancellationToken,
+    metrics: Arc<MetricsCollection>,
+) {
+    let mut reconciler = SlotReconciler::default();
+    let mut report = tokio::time::interval(REPORT_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = cancellation_token.cancelled() => return,
+            _ = report.tick() => report_sources(&mut reconciler, &metrics).await,
+            received = merged_rx.recv() => {
+                let Some((update, source_id)) = received else { return };
+                if let Update::Transaction(transaction) = &update {
+                    let source = source_ids.iter().position(|s| *s == source_id).unwrap_or(0);
+                    if !reconciler.observe(source, transaction.signature, transaction.slot) {
+                        let _ = metrics.increment_counter(REDUNDANT_DUPLICATES, 1).await;
+                        continue;
+                    }
+                }
+                if sender.send((update, id.clone())).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn report_sources(reconciler: &mut SlotReconciler, metrics: &MetricsCollection) {
+    let missed = reconciler.reconcile();
+    for source in 0..2 {
+        if let Some(lag) = reconciler.slot_lag(source) {
+            let _ = metrics.update_gauge(SOURCE_SLOT_LAG[source], lag as f64).await;
+        }
+        if missed[source] > 0 {
+            let _ = metrics.increment_counter(SOURCE_MISSED_SLOTS[source], missed[source]).await;
+            log::warn!(
+                "⚠️  {} Geyser source missed {} slots the other delivered ({} since start)",
+                SOURCE_NAMES[source],
+                missed[source],
+                reconciler.missed(source)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(n: u8) -> Signature {
+        Signature::from([n; 64])
+    }
+
+    #[test]
+    fn test_reconciler_dedups_and_counts_missed_slots() {
+        let mut reconciler = SlotReconciler::default();
+
+        // Both sources deliver slot 100; the second copy is dropped
+        assert!(reconciler.observe(0, signature(1), 100));
+        assert!(!reconciler.observe(1, signature(1), 100));
+
+        // The secondary skips slot 101 but keeps going
+        assert!(reconciler.observe(0, signature(2), 101));
+        assert!(reconciler.observe(1, signature(3), 102));
+        assert!(!reconciler.observe(0, signature(3), 102));
+        assert_eq!(reconciler.slot_lag(0), Some(0));
+
+        // Nothing is reconciled until the stream is far enough past
+        assert_eq!(reconciler.reconcile(), [0, 0]);
+
+        // The primary runs ahead; the secondary lags instead of missing
+        assert!(reconciler.observe(0, signature(4), 102 + RECONCILE_DELAY_SLOTS));
+        assert_eq!(reconciler.reconcile(), [0, 1]);
+        assert_eq!(reconciler.missed(1), 1);
+        assert_eq!(reconciler.slot_lag(1), Some(RECONCILE_DELAY_SLOTS));
+
+        // A late copy of a reconciled slot is still deduplicated
+        assert!(!reconciler.observe(1, signature(2), 101));
+        assert_eq!(reconciler.reconcile(), [0, 0]);
+    }
+}
@@ -0,0 +1,140 @@
+//! RPC websocket fallback datasource
+//!
+//! For deployments without a Yellowstone endpoint, the unified streamer can
+//! consume `blockSubscribe` over a plain RPC websocket instead. Fidelity is
+//! reduced (see `DatasourceKind::fidelity_notes`): blocks arrive whole after
+//! confirmation, and `blockSubscribe` only accepts a single program filter, so
+//! one subscription is opened per tracked program and transactions touching
+//! several tracked programs are deduplicated by signature.
+//!
+//! Focus wallets (`FOCUS_WALLETS`) get one extra subscription each; with
+//! `FOCUS_WALLETS_ONLY=true` the program subscriptions are skipped.
+
+use crate::streamer_core::config::RuntimeConfig;
+use crate::streamer_core::grpc_client::{ClientError, TRACKED_PROGRAMS};
+use carbon_rpc_block_subscribe_datasource::{Filters, RpcBlockSubscribe};
+use solana_client::rpc_config::{RpcBlockSubscribeConfig, RpcBlockSubscribeFilter};
+use solana_commitment_config::CommitmentConfig;
+use solana_signature::Signature;
+use solana_transaction_status::{TransactionDetails, UiTransactionEncoding};
+use std::collections::{HashSet, VecDeque};
+use yellowstone_grpc_proto::geyser::CommitmentLevel;
+
+/// Signatures remembered for cross-subscription deduplication
+const DEDUP_CAPACITY: usize = 10_000;
+
+/// Create one `blockSubscribe` datasource per tracked program and focus wallet
+pub fn create_multi_program_rpc_datasources(
+    config: &RuntimeConfig,
+) -> Result<Vec<RpcBlockSubscribe>, ClientError> {
+    let rpc_ws_url = config
+        .rpc_ws_url
+        .clone()
+        .ok_or_else(|| ClientError::Connection("RPC_WS_URL not set".to_string()))?;
+
+    // blockSubscribe does not support processed commitment
+    let commitment = match config.commitment_level {
+        CommitmentLevel::Finalized => CommitmentConfig::finalized(),
+        _ => CommitmentConfig::confirmed(),
+    };
+
+    log::warn!("⚠️  Using RPC blockSubscribe fallback datasource (REDUCED FIDELITY)");
+    for note in config.datasource.fidelity_notes() {
+        log::warn!("   ├─ {}", note);
+    }
+    log::warn!("   └─ Commitment: {:?}", commitment.commitment);
+    if !config.account_include.is_empty() || !config.account_exclude.is_empty() {
+        log::warn!("⚠️  GRPC_ACCOUNT_INCLUDE/GRPC_ACCOUNT_EXCLUDE are ignored by blockSubscribe");
+    }
+
+    let programs = TRACKED_PROGRAMS
+        .iter()
+        .filter(|_| !config.focus_wallets_only)
+        .map(|(_, program_id)| program_id.to_string());
+    let datasources: Vec<RpcBlockSubscribe> = programs
+        .chain(config.focus_wallets.iter().cloned())
+        .map(|account| {
+            let filters = Filters::new(
+                RpcBlockSubscribeFilter::MentionsAccountOrProgram(account),
+                Some(RpcBlockSubscribeConfig {
+                    commitment: Some(commitment),
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    transaction_details: Some(TransactionDetails::Full),
+                    show_rewards: Some(false),
+                    max_supported_transaction_version: Some(0),
+                }),
+            );
+            RpcBlockSubscribe::new(rpc_ws_url.clone(), filters)
+        })
+        .collect();
+
+    log::info!(
+        "🔗 Created {} RPC block subscriptions ({})",
+        datasources.len(),
+        rpc_ws_url
+    );
+
+    Ok(datasources)
+}
+
+/// Bounded set of recently seen signatures
+///
+/// The same transaction is delivered once per matching program subscription,
+/// so the processor drops repeats before extracting trades.
+#[derive(Debug, Default)]
+pub struct SignatureDedup {
+    seen: HashSet<Signature>,
+    order: VecDeque<Signature>,
+}
+
+impl SignatureDedup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true the first time a signature is seen
+    pub fn insert(&mut self, signature: Signature) -> bool {
+        if !self.seen.insert(signature) {
+            return false;
+        }
+
+        self.order.push_back(signature);
+        if self.order.len() > DEDUP_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_rejects_repeats() {
+        let mut dedup = SignatureDedup::new();
+        let sig = Signature::from([1u8; 64]);
+
+        assert!(dedup.insert(sig));
+        assert!(!dedup.insert(sig));
+    }
+
+    #[test]
+    fn test_dedup_bounded() {
+        let mut dedup = SignatureDedup::new();
+        let first = Signature::from([0u8; 64]);
+        dedup.insert(first);
+
+        for i in 1..=DEDUP_CAPACITY as u32 {
+            let mut bytes = [0u8; 64];
+            bytes[..4].copy_from_slice(&i.to_le_bytes());
+            dedup.insert(Signature::from(bytes));
+        }
+
+        // Oldest entry evicted, so it is accepted again
+        assert_eq!(dedup.order.len(), DEDUP_CAPACITY);
+        assert!(dedup.insert(first));
+    }
+}
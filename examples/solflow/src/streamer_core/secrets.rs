@@ -0,0 +1,146 @@
+//! Secret resolution and log redaction
+//!
+//! Tokens kept literally in `.env` end up in shell histories, `ps` output
+//! and logs. Secret env vars (`X_TOKEN`, webhook tokens and URLs) may hold a
+//! reference instead of the value:
+//!
+//! - `file:/path/to/secret` - contents of the file (surrounding whitespace trimmed)
+//! - `keyring:<service>/<account>` - OS keyring entry, read with `secret-tool`
+//!   (Linux, libsecret) or `security` (macOS keychain)
+//! - `NAME_FILE=/path` - same as `NAME=file:/path` (NAME itself must be unset)
+//!
+//! Any other value is used literally. Resolved secrets must only be logged
+//! through `redact_url` / `redact_env_value`.
+
+use std::process::Command;
+
+/// Env var name fragments whose values are treated as secrets
+const SECRET_NAME_PARTS: &[&str] = &[
+    "TOKEN", "SECRET", "PASSWORD", "PASSWD", "KEY", "AUTH", "CREDENTIAL", "WEBHOOK", "ROUTES",
+];
+
+pub const REDACTED: &str = "<redacted>";
+
+/// Resolve a secret value or reference (see module docs)
+pub fn resolve_secret(value: &str) -> Result<String, String> {
+    if let Some(path) = value.strip_prefix("file:") {
+        return read_secret_file(path);
+    }
+    if let Some(entry) = value.strip_prefix("keyring:") {
+        let (service, account) = entry
+            .split_once('/')
+            .ok_or_else(|| format!("keyring reference must be keyring:<service>/<account>, got '{}'", entry))?;
+        return read_keyring(service, account);
+    }
+    Ok(value.to_string())
+}
+
+/// Read a secret env var, following `file:` / `keyring:` references and `NAME_FILE`
+///
+/// Returns None when neither `NAME` nor `NAME_FILE` is set (or both are empty).
+pub fn secret_from_env(name: &str) -> Result<Option<String>, String> {
+    let value = std::env::var(name).ok().filter(|v| !v.is_empty());
+    let file = std::env::var(format!("{}_FILE", name)).ok().filter(|v| !v.is_empty());
+    let secret = match (value, file) {
+        (Some(_), Some(_)) => return Err(format!("set either {} or {}_FILE, not both", name, name)),
+        (Some(value), None) => resolve_secret(&value),
+        (None, Some(path)) => read_secret_file(&path),
+        (None, None) => return Ok(None),
+    };
+    secret
+        .map(Some)
+        .map_err(|e| format!("{}: {}", name, e))
+}
+
+fn read_secret_file(path: &str) -> Result<String, String> {
+    let contents = std::fs::read_to_string(path.trim())
+        .map_err(|e| format!("failed to read secret file {}: {}", path, e))?;
+    let secret = contents.trim();
+    if secret.is_empty() {
+        return Err(format!("secret file {} is empty", path));
+    }
+    Ok(secret.to_string())
+}
+
+fn read_keyring(service: &str, account: &str) -> Result<String, String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["find-generic-password", "-s", service, "-a", account, "-w"])
+            .output()
+    } else {
+        Command::new("secret-tool")
+            .args(["lookup", "service", service, "account", account])
+            .output()
+    }
+    .map_err(|e| format!("failed to run keyring tool: {}", e))?;
+
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || secret.is_empty() {
+        return Err(format!("no keyring entry for {}/{}", service, account));
+    }
+    Ok(secret)
+}
+
+/// Whether an env var name looks like it holds a secret
+pub fn is_secret_name(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    SECRET_NAME_PARTS.iter().any(|part| upper.contains(part))
+}
+
+/// Strip credentials and query string (API keys, x-token) from a URL for logging
+pub fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let rest = match rest.split_once('?') {
+        Some((path, _)) => format!("{}?{}", path, REDACTED),
+        None => rest.to_string(),
+    };
+    match rest.split_once('@') {
+        Some((_, host)) if !host.is_empty() => format!("{}://{}@{}", scheme, REDACTED, host),
+        _ => format!("{}://{}", scheme, rest),
+    }
+}
+
+/// Redact an env var value for display: secrets entirely, URLs partially
+pub fn redact_env_value(name: &str, value: &str) -> String {
+    if is_secret_name(name) {
+        REDACTED.to_string()
+    } else {
+        redact_url(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_file_reference() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("x_token");
+        std::fs::write(&path, "  abc123\n").unwrap();
+
+        let reference = format!("file:{}", path.display());
+        assert_eq!(resolve_secret(&reference).unwrap(), "abc123");
+        assert_eq!(resolve_secret("literal").unwrap(), "literal");
+        assert!(resolve_secret("file:/nonexistent/secret").is_err());
+        assert!(resolve_secret("keyring:no-account").is_err());
+    }
+
+    #[test]
+    fn test_redaction() {
+        assert_eq!(redact_env_value("X_TOKEN", "abc"), REDACTED);
+        assert_eq!(redact_env_value("SIGNAL_WEBHOOK_TOKEN", "abc"), REDACTED);
+        assert_eq!(redact_env_value("NOTIFY_ROUTES", "{}"), REDACTED);
+        assert_eq!(
+            redact_url("https://user:pw@grpc.example.com/?x-token=abc"),
+            "https://<redacted>@grpc.example.com/?<redacted>"
+        );
+        assert_eq!(
+            redact_url("https://mainnet.helius-rpc.com/?api-key=abc"),
+            "https://mainnet.helius-rpc.com/?<redacted>"
+        );
+        assert_eq!(redact_env_value("SOLFLOW_DB_PATH", "/data/solflow.db"), "/data/solflow.db");
+    }
+}
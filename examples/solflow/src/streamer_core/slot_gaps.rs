@@ -0,0 +1,220 @@
+//! Slot gap detection for the live datasource
+//!
+//! Tracked programs trade in nearly every slot, so when the slot of the next
+//! delivered transaction jumps more than `SLOT_GAP_THRESHOLD` slots past the
+//! highest one seen, the stream likely lost data: a reconnect, a provider
+//! stall, or a dropped subscription. Quiet program sets trade less often, so
+//! detection is opt-in. Each gap is:
+//!
+//! - reported as a `DATA_GAP` (log line plus the `solflow_data_gaps` and
+//!   `solflow_data_gap_slots` counters)
+//! - kept in the process-wide registry (`shared()`) until it ages out of the
+//!   longest rolling window; an engine handed the registry
+//!   (`PipelineEngine::set_data_gaps`) stamps aggregates whose windows reach
+//!   back over an open gap with `last_data_gap_at`
+//! - with `SLOT_GAP_BACKFILL=true`, backfilled over RPC (see `backfill`);
+//!   once the backfill completes the gap is closed and aggregates are no
+//!   longer marked
+//!
+//! The tracker lives across reconnects, so the slots missed while
+//! reconnecting are caught too. Subscriptions scoped to a few accounts
+//! (`FOCUS_WALLETS_ONLY`, `GRPC_ACCOUNT_INCLUDE`) have natural gaps and skip
+//! detection.
+//!
+//! Environment variables:
+//! - `SLOT_GAP_THRESHOLD`: Skipped slots that count as a gap, e.g. 150 (default: 0 = off)
+//! - `SLOT_GAP_BACKFILL`: Backfill gaps over RPC, needs BACKFILL_RPC_URL or SOLANA_RPC_URL (default: false)
+
+use crate::streamer_core::config::RuntimeConfig;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Gaps are remembered for the longest fixed rolling window
+pub const GAP_MEMORY_SECS: i64 = 14_400;
+
+/// Slots missed between two delivered transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotGap {
+    /// First missing slot
+    pub start_slot: u64,
+    /// Last missing slot
+    pub end_slot: u64,
+    /// Block time of the last transaction before the gap
+    pub start_time: i64,
+    /// Block time of the first transaction after it
+    pub end_time: i64,
+}
+
+impl SlotGap {
+    pub fn slots(&self) -> u64 {
+        self.end_slot - self.start_slot + 1
+    }
+}
+
+/// Gap detection settings (SLOT_GAP_* env vars)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlotGapConfig {
+    pub threshold: u64,
+    pub backfill: bool,
+}
+
+impl SlotGapConfig {
+    /// Load from env; None when SLOT_GAP_THRESHOLD is unset or 0
+    pub fn from_env() -> Option<Self> {
+        let threshold = std::env::var("SLOT_GAP_THRESHOLD")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0u64);
+        if threshold == 0 {
+            return None;
+        }
+        let backfill = std::env::var("SLOT_GAP_BACKFILL")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+        Some(Self { threshold, backfill })
+    }
+}
+
+/// Whether the subscription sees traffic in nearly every slot
+///
+/// Account-scoped subscriptions (focus wallets only, or an account include
+/// list) skip slots routinely, so jumps there are not data loss.
+pub fn subscription_is_dense(config: &RuntimeConfig) -> bool {
+    !config.focus_wallets_only && config.account_include.is_empty()
+}
+
+/// Watches the delivered slot sequence for jumps
+#[derive(Debug)]
+pub struct SlotGapTracker {
+    threshold: u64,
+    /// Highest slot delivered and its block time
+    last: Option<(u64, i64)>,
+}
+
+impl SlotGapTracker {
+    pub fn new(threshold: u64) -> Self {
+        Self { threshold, last: None }
+    }
+
+    /// Record a delivered transaction; returns the gap before it, if any
+    ///
+    /// Out-of-order deliveries below the highest slot are ignored.
+    pub fn observe(&mut self, slot: u64, block_time: i64) -> Option<SlotGap> {
+        let gap = match self.last {
+            Some((last_slot, _)) if slot <= last_slot => return None,
+            Some((last_slot, last_time)) if slot - last_slot > self.threshold => Some(SlotGap {
+                start_slot: last_slot + 1,
+                end_slot: slot - 1,
+                start_time: last_time,
+                end_time: block_time,
+            }),
+            _ => None,
+        };
+        self.last = Some((slot, block_time));
+        gap
+    }
+}
+
+/// Open gaps, shared by the streamer and the engine
+#[derive(Debug, Default)]
+pub struct DataGaps {
+    open: Mutex<Vec<SlotGap>>,
+}
+
+impl DataGaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a detected gap, forgetting gaps older than any window
+    pub fn record(&self, gap: SlotGap) {
+        let mut open = self.open.lock().unwrap();
+        open.retain(|g| g.end_time >= gap.end_time - GAP_MEMORY_SECS);
+        open.push(gap);
+    }
+
+    /// Close a gap whose data was backfilled
+    pub fn resolve(&self, gap: &SlotGap) {
+        self.open.lock().unwrap().retain(|g| g != gap);
+    }
+
+    /// End time of the latest open gap that a window starting at `since` covers
+    pub fn latest_since(&self, since: i64) -> Option<i64> {
+        self.open
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|g| g.end_time >= since)
+            .map(|g| g.end_time)
+            .max()
+    }
+
+    pub fn len(&self) -> usize {
+        self.open.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+static SHARED: OnceLock<Arc<DataGaps>> = OnceLock::new();
+
+/// The process-wide registry (empty unless gap detection runs)
+pub fn shared() -> Arc<DataGaps> {
+    SHARED.get_or_init(|| Arc::new(DataGaps::new())).clone()
+}
+
+/// Whether a window of `window_secs` ending at `now` covers a gap ending at `last_data_gap_at`
+pub fn window_degraded(last_data_gap_at: Option<i64>, now: i64, window_secs: i64) -> bool {
+    last_data_gap_at.is_some_and(|gap_at| gap_at >= now - window_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_reports_jumps_and_registry_marks_windows() {
+        let mut tracker = SlotGapTracker::new(150);
+        assert_eq!(tracker.observe(1_000, 100), None);
+        assert_eq!(tracker.observe(1_100, 140), None);
+        // Late delivery from an earlier slot
+        assert_eq!(tracker.observe(1_050, 120), None);
+
+        let gap = tracker.observe(1_400, 260).unwrap();
+        assert_eq!(
+            gap,
+            SlotGap { start_slot: 1_101, end_slot: 1_399, start_time: 140, end_time: 260 }
+        );
+        assert_eq!(gap.slots(), 299);
+        assert_eq!(tracker.observe(1_401, 261), None);
+
+        let gaps = DataGaps::new();
+        gaps.record(gap);
+        assert_eq!(gaps.latest_since(0), Some(260));
+        // Windows starting after the gap are complete
+        assert_eq!(gaps.latest_since(261), None);
+        assert!(window_degraded(Some(260), 500, 300));
+        assert!(!window_degraded(Some(260), 500, 60));
+
+        // Old gaps age out; backfilled gaps are closed
+        let later = SlotGap { start_slot: 90_000, end_slot: 90_500, start_time: 20_000, end_time: 20_200 };
+        gaps.record(later);
+        assert_eq!(gaps.len(), 1);
+        gaps.resolve(&later);
+        assert!(gaps.is_empty());
+    }
+
+    #[test]
+    fn test_account_scoped_subscriptions_skip_detection() {
+        let mut config = RuntimeConfig::for_tests();
+        assert!(subscription_is_dense(&config));
+
+        config.account_include = vec!["pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA".to_string()];
+        assert!(!subscription_is_dense(&config));
+
+        config.account_include.clear();
+        config.focus_wallets_only = true;
+        assert!(!subscription_is_dense(&config));
+    }
+}
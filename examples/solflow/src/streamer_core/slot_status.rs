@@ -0,0 +1,274 @@
+//! Commitment tracking and fork detection from Yellowstone slot-status updates
+//!
+//! Trades are ingested at the streamer's commitment (`COMMITMENT_LEVEL`) and
+//! tagged with it. A separate slot subscription then reports:
+//!
+//! - confirmation upgrades: a slot reaching Confirmed or Finalized upgrades
+//!   buffered trades in that slot and its ancestors, so the pipeline can keep
+//!   a finalized-only ("final") view next to the low-latency ("fast") one
+//! - rollbacks: trades ingested at Processed or Confirmed can belong to a
+//!   fork the cluster later abandons
+//!   - a slot marked dead rolls back that slot
+//!   - a slot reaching the ingest status whose parent is below the last slot
+//!     seen at that status means the chain switched forks; everything after
+//!     the parent up to that last slot is rolled back
+//!
+//! Finalized ingestion needs neither, so no subscription is made.
+//!
+//! Environment variables:
+//! - `REORG_ROLLBACK_ENABLED`: Roll back abandoned slots (default: true)
+
+use crate::pipeline::types::Confirmation;
+use crate::streamer_core::config::{DatasourceKind, RuntimeConfig};
+use futures::{sink::SinkExt, StreamExt};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::geyser::{
+    subscribe_update::UpdateOneof, CommitmentLevel, SlotStatus, SubscribeRequest,
+    SubscribeRequestFilterSlots, SubscribeRequestPing, SubscribeUpdateSlot,
+};
+use yellowstone_grpc_proto::tonic::transport::ClientTlsConfig;
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// What the slot-status stream reports to the pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlotEvent {
+    /// Trades in these slots belong to an abandoned fork
+    RolledBack(RangeInclusive<u64>),
+    /// `slot` and its ancestors reached `confirmation`
+    Reached { slot: u64, confirmation: Confirmation },
+}
+
+/// Confirmation state of trades ingested at `commitment`
+pub fn ingest_confirmation(commitment: CommitmentLevel) -> Confirmation {
+    match commitment {
+        CommitmentLevel::Processed => Confirmation::Processed,
+        CommitmentLevel::Confirmed => Confirmation::Confirmed,
+        CommitmentLevel::Finalized => Confirmation::Finalized,
+    }
+}
+
+/// Upgrade implied by a slot status, if it is above the ingest commitment
+fn upgrade_for(status: SlotStatus, ingested: Confirmation) -> Option<Confirmation> {
+    let reached = match status {
+        SlotStatus::SlotConfirmed => Confirmation::Confirmed,
+        SlotStatus::SlotFinalized => Confirmation::Finalized,
+        _ => return None,
+    };
+    (reached > ingested).then_some(reached)
+}
+
+/// Tracks the chain tip at one commitment status and reports rolled-back slots
+#[derive(Debug, Clone)]
+pub struct ForkTracker {
+    tracked: SlotStatus,
+    last_slot: Option<u64>,
+}
+
+impl ForkTracker {
+    /// None for Finalized commitment, which never rolls back
+    pub fn new(commitment: CommitmentLevel) -> Option<Self> {
+        let tracked = match commitment {
+            CommitmentLevel::Processed => SlotStatus::SlotProcessed,
+            CommitmentLevel::Confirmed => SlotStatus::SlotConfirmed,
+            CommitmentLevel::Finalized => return None,
+        };
+        Some(Self {
+            tracked,
+            last_slot: None,
+        })
+    }
+
+    /// Feed one slot update; returns the slots to roll back, if any
+    pub fn observe(
+        &mut self,
+        slot: u64,
+        parent: Option<u64>,
+        status: SlotStatus,
+    ) -> Option<RangeInclusive<u64>> {
+        if status == SlotStatus::SlotDead {
+            return Some(slot..=slot);
+        }
+        if status != self.tracked {
+            return None;
+        }
+
+        let rolled_back = match (parent, self.last_slot) {
+            (Some(parent), Some(last)) if parent < last => Some(parent + 1..=last),
+            _ => None,
+        };
+        self.last_slot = Some(match (rolled_back.is_some(), self.last_slot) {
+            (false, Some(last)) => last.max(slot),
+            _ => slot,
+        });
+        rolled_back
+    }
+}
+
+/// Whether the slot-status stream is needed for this streamer configuration
+///
+/// Only the gRPC datasource carries slot updates, and Finalized ingestion
+/// has nothing to upgrade or roll back.
+pub fn stream_enabled(config: &RuntimeConfig) -> bool {
+    config.datasource == DatasourceKind::Grpc
+        && config.commitment_level != CommitmentLevel::Finalized
+}
+
+/// Whether abandoned slots are rolled back (`REORG_ROLLBACK_ENABLED`)
+pub fn rollback_enabled() -> bool {
+    std::env::var("REORG_ROLLBACK_ENABLED")
+        .map(|v| v.to_lowercase() != "false" && v != "0")
+        .unwrap_or(true)
+}
+
+/// Subscribe to slot updates and send upgrades and rollbacks to `tx`
+///
+/// Reconnects after stream errors; returns when `tx` is closed.
+pub async fn run_slot_status_stream(
+    config: RuntimeConfig,
+    rollback: bool,
+    tx: mpsc::Sender<SlotEvent>,
+) {
+    if !stream_enabled(&config) {
+        return;
+    }
+    let mut tracker = ForkTracker::new(config.commitment_level).filter(|_| rollback);
+    let request = SubscribeRequest {
+        slots: HashMap::from([(
+            "slots".to_string(),
+            SubscribeRequestFilterSlots {
+                filter_by_commitment: Some(false),
+                interslot_updates: Some(false),
+            },
+        )]),
+        ..Default::default()
+    };
+
+    loop {
+        if let Err(e) = stream_once(&config, &request, &mut tracker, &tx).await {
+            log::warn!("⚠️  Slot status stream: {} - reconnecting", e);
+        }
+        if tx.is_closed() {
+            return;
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn stream_once(
+    config: &RuntimeConfig,
+    request: &SubscribeRequest,
+    tracker: &mut Option<ForkTracker>,
+    tx: &mpsc::Sender<SlotEvent>,
+) -> Result<(), String> {
+    let ingested = ingest_confirmation(config.commitment_level);
+    let mut client = GeyserGrpcClient::build_from_shared(config.geyser_url.clone())
+        .map_err(|e| e.to_string())?
+        .x_token(config.x_token.clone())
+        .map_err(|e| e.to_string())?
+        .tls_config(ClientTlsConfig::new().with_enabled_roots())
+        .map_err(|e| e.to_string())?
+        .connect()
+        .await
+        .map_err(|e| e.to_string())?;
+    let (mut subscribe_tx, mut stream) = client
+        .subscribe_with_request(Some(request.clone()))
+        .await
+        .map_err(|e| e.to_string())?;
+    log::info!("🍴 Slot status stream connected ({:?})", config.commitment_level);
+
+    while let Some(message) = stream.next().await {
+        match message.map_err(|e| e.to_string())?.update_oneof {
+            Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                slot, parent, status, ..
+            })) => {
+                let Ok(status) = SlotStatus::try_from(status) else {
+                    continue;
+                };
+                let rolled_back = tracker
+                    .as_mut()
+                    .and_then(|tracker| tracker.observe(slot, parent, status));
+                if let Some(range) = rolled_back {
+                    log::warn!(
+                        "🍴 Slots {}..={} abandoned ({:?} at slot {})",
+                        range.start(),
+                        range.end(),
+                        status,
+                        slot
+                    );
+                    if tx.send(SlotEvent::RolledBack(range)).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                if let Some(confirmation) = upgrade_for(status, ingested) {
+                    if tx.send(SlotEvent::Reached { slot, confirmation }).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            Some(UpdateOneof::Ping(_)) => {
+                subscribe_tx
+                    .send(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: 1 }),
+                        ..Default::default()
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            _ => {}
+        }
+    }
+    Err("stream ended".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fork_tracker_rollbacks() {
+        assert!(ForkTracker::new(CommitmentLevel::Finalized).is_none());
+        let mut tracker = ForkTracker::new(CommitmentLevel::Confirmed).unwrap();
+
+        // Linear chain and other statuses roll nothing back
+        assert_eq!(tracker.observe(100, Some(99), SlotStatus::SlotConfirmed), None);
+        assert_eq!(tracker.observe(101, Some(100), SlotStatus::SlotConfirmed), None);
+        assert_eq!(tracker.observe(104, Some(103), SlotStatus::SlotProcessed), None);
+        assert_eq!(tracker.observe(103, Some(101), SlotStatus::SlotConfirmed), None);
+
+        // Dead slots roll back only themselves
+        assert_eq!(tracker.observe(102, Some(101), SlotStatus::SlotDead), Some(102..=102));
+
+        // Chain switches to a fork off 101: slots after it up to 103 are gone
+        assert_eq!(
+            tracker.observe(105, Some(101), SlotStatus::SlotConfirmed),
+            Some(102..=103)
+        );
+        assert_eq!(tracker.observe(106, Some(105), SlotStatus::SlotConfirmed), None);
+    }
+
+    #[test]
+    fn test_upgrades_above_ingest_commitment() {
+        let processed = ingest_confirmation(CommitmentLevel::Processed);
+        assert_eq!(
+            upgrade_for(SlotStatus::SlotConfirmed, processed),
+            Some(Confirmation::Confirmed)
+        );
+        assert_eq!(
+            upgrade_for(SlotStatus::SlotFinalized, processed),
+            Some(Confirmation::Finalized)
+        );
+        assert_eq!(upgrade_for(SlotStatus::SlotProcessed, processed), None);
+
+        let confirmed = ingest_confirmation(CommitmentLevel::Confirmed);
+        assert_eq!(upgrade_for(SlotStatus::SlotConfirmed, confirmed), None);
+        assert_eq!(
+            upgrade_for(SlotStatus::SlotFinalized, confirmed),
+            Some(Confirmation::Finalized)
+        );
+    }
+}
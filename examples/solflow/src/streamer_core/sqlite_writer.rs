@@ -158,6 +158,7 @@ mod tests {
             token_decimals: 6,
             user_account: Some("user1".to_string()),
             discriminator: "0123456789abcdef".to_string(),
+            ..Default::default()
         }
     }
     
@@ -0,0 +1,231 @@
+//! Runtime changes to the unified streamer's gRPC subscription
+//!
+//! Changing what the streamer subscribes to used to need a restart.
+//! `SubscriptionControl` holds the current filter set; every change is
+//! validated, rebuilt into Yellowstone transaction filters and sent over the
+//! open stream (see `with_filter_updates` on the Yellowstone datasource), so
+//! a new launch can be followed the moment it is announced. Reconnects and
+//! the secondary provider (see `redundancy`) subscribe with the current set.
+//!
+//! - programs: start as the tracked programs (none with FOCUS_WALLETS_ONLY)
+//!   and are replaced when the registry file is reloaded (see
+//!   `program_registry`), which also drops programs added here.
+//!   Transactions are still matched by the `InstructionScanner`, so an added
+//!   program yields trades only when it calls a tracked one (e.g. a new router)
+//! - accounts: start as FOCUS_WALLETS; any transaction touching one of them
+//!   matches, whatever the program (a new mint, pool or wallet)
+//!
+//! `GRPC_ACCOUNT_INCLUDE` / `GRPC_ACCOUNT_EXCLUDE` still apply to every
+//! program filter. Changes last until the process exits. The query API
+//! serves the control under `/subscription` (see `api::rest`).
+
+use crate::streamer_core::config::RuntimeConfig;
+use crate::streamer_core::filter_builder::{FilterError, TransactionFilterBuilder};
+use carbon_yellowstone_grpc_datasource::FilterUpdate;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::watch;
+
+/// What the subscription currently matches
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SubscriptionFilters {
+    /// (filter name, program ID)
+    pub programs: Vec<(String, String)>,
+    /// Accounts matched regardless of program
+    pub accounts: Vec<String>,
+}
+
+/// Current filters of the unified streamer, changeable at runtime
+pub struct SubscriptionControl {
+    focus_wallets_only: bool,
+    account_include: Vec<String>,
+    account_exclude: Vec<String>,
+    filters: Mutex<SubscriptionFilters>,
+    updates: watch::Sender<FilterUpdate>,
+}
+
+impl SubscriptionControl {
+    /// `config`'s filters for `programs`, (filter name, program ID) pairs
+    pub fn new(config: &RuntimeConfig, programs: Vec<(String, String)>) -> Result<Self, FilterError> {
+        let programs = if config.focus_wallets_only { Vec::new() } else { programs };
+        let filters = SubscriptionFilters {
+            programs,
+            accounts: config.focus_wallets.clone(),
+        };
+        let update = build(&filters, &config.account_include, &config.account_exclude)?;
+        let (updates, _) = watch::channel(update);
+        Ok(Self {
+            focus_wallets_only: config.focus_wallets_only,
+            account_include: config.account_include.clone(),
+            account_exclude: config.account_exclude.clone(),
+            filters: Mutex::new(filters),
+            updates,
+        })
+    }
+
+    pub fn filters(&self) -> SubscriptionFilters {
+        self.filters.lock().unwrap().clone()
+    }
+
+    /// Receiver for a Yellowstone client (`with_filter_updates`)
+    pub fn subscribe(&self) -> watch::Receiver<FilterUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Also subscribe to `program_id`; false when already subscribed
+    pub fn add_program(&self, program_id: &str) -> Result<bool, FilterError> {
+        self.apply(|filters| {
+            if filters.programs.iter().any(|(_, id)| id == program_id) {
+                return false;
+            }
+            filters
+                .programs
+                .push((format!("added_{}", program_id), program_id.to_string()));
+            true
+        })
+    }
+
+    /// Stop subscribing to `program_id`; false when not subscribed
+    pub fn remove_program(&self, program_id: &str) -> Result<bool, FilterError> {
+        self.apply(|filters| {
+            let before = filters.programs.len();
+            filters.programs.retain(|(_, id)| id != program_id);
+            filters.programs.len() != before
+        })
+    }
+
+    /// Replace all program filters (ignored with FOCUS_WALLETS_ONLY)
+    pub fn set_programs(&self, programs: Vec<(String, String)>) -> Result<bool, FilterError> {
+        if self.focus_wallets_only {
+            return Ok(false);
+        }
+        self.apply(|filters| {
+            let changed = filters.programs != programs;
+            filters.programs = programs;
+            changed
+        })
+    }
+
+    /// Also match transactions touching `account`; false when already matched
+    pub fn add_account(&self, account: &str) -> Result<bool, FilterError> {
+        self.apply(|filters| {
+            if filters.accounts.iter().any(|a| a == account) {
+                return false;
+            }
+            filters.accounts.push(account.to_string());
+            true
+        })
+    }
+
+    /// Stop matching `account`; false when not matched
+    pub fn remove_account(&self, account: &str) -> Result<bool, FilterError> {
+        self.apply(|filters| {
+            let before = filters.accounts.len();
+            filters.accounts.retain(|a| a != account);
+            filters.accounts.len() != before
+        })
+    }
+
+    /// Apply `change` and push the rebuilt filters if it changed anything
+    fn apply(&self, change: impl FnOnce(&mut SubscriptionFilters) -> bool) -> Result<bool, FilterError> {
+        let mut filters = self.filters.lock().unwrap();
+        let mut next = filters.clone();
+        if !change(&mut next) {
+            return Ok(false);
+        }
+        let update = build(&next, &self.account_include, &self.account_exclude)?;
+        log::info!(
+            "🔧 Subscription updated: {} programs, {} accounts",
+            next.programs.len(),
+            next.accounts.len()
+        );
+        *filters = next;
+        self.updates.send_replace(update);
+        Ok(true)
+    }
+}
+
+fn build(
+    filters: &SubscriptionFilters,
+    account_include: &[String],
+    account_exclude: &[String],
+) -> Result<FilterUpdate, FilterError> {
+    let transaction_filters = filters
+        .programs
+        .iter()
+        .fold(TransactionFilterBuilder::new(), |builder, (name, program_id)| {
+            builder.program(name.clone(), program_id.clone())
+        })
+        .account_include(account_include.to_vec())
+        .account_exclude(account_exclude.to_vec())
+        .focus_wallets(filters.accounts.clone())
+        .build()?;
+    Ok(FilterUpdate {
+        account_filters: HashMap::new(),
+        transaction_filters,
+    })
+}
+
+static INSTALLED: OnceLock<Arc<SubscriptionControl>> = OnceLock::new();
+
+/// Create the process's control for `config` (the first call wins)
+pub fn install(
+    config: &RuntimeConfig,
+    programs: Vec<(String, String)>,
+) -> Result<Arc<SubscriptionControl>, FilterError> {
+    if let Some(control) = INSTALLED.get() {
+        return Ok(control.clone());
+    }
+    let control = Arc::new(SubscriptionControl::new(config, programs)?);
+    Ok(INSTALLED.get_or_init(|| control).clone())
+}
+
+/// The control, once a gRPC streamer has started in this process
+pub fn installed() -> Option<Arc<SubscriptionControl>> {
+    INSTALLED.get().cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streamer_core::grpc_client::TRACKED_PROGRAMS;
+
+    const ROUTER: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+    const MINT: &str = "So11111111111111111111111111111111111111112";
+
+    #[test]
+    fn test_changes_are_pushed_to_subscribers() {
+        let programs = TRACKED_PROGRAMS
+            .iter()
+            .map(|(name, program_id)| (name.to_string(), program_id.to_string()))
+            .collect();
+        let control = SubscriptionControl::new(&RuntimeConfig::for_tests(), programs).unwrap();
+        let mut updates = control.subscribe();
+        assert_eq!(updates.borrow().transaction_filters.len(), TRACKED_PROGRAMS.len());
+
+        assert!(control.add_program(ROUTER).unwrap());
+        assert!(!control.add_program(ROUTER).unwrap());
+        assert!(updates.has_changed().unwrap());
+        let update = updates.borrow_and_update().clone();
+        assert_eq!(
+            update.transaction_filters[&format!("added_{}_filter", ROUTER)].account_required,
+            vec![ROUTER.to_string()]
+        );
+
+        assert!(control.add_account(MINT).unwrap());
+        assert!(control.remove_program(TRACKED_PROGRAMS[0].1).unwrap());
+        let update = updates.borrow_and_update().clone();
+        assert_eq!(update.transaction_filters.len(), TRACKED_PROGRAMS.len() + 1);
+        assert_eq!(update.transaction_filters["focus_wallets_filter"].account_include, vec![MINT.to_string()]);
+
+        // Invalid input leaves the subscription untouched
+        assert!(control.add_account("not-a-pubkey").is_err());
+        assert!(!updates.has_changed().unwrap());
+        assert_eq!(control.filters().accounts, vec![MINT.to_string()]);
+
+        // A registry reload replaces all program filters, added ones included
+        assert!(control.set_programs(vec![("router".to_string(), ROUTER.to_string())]).unwrap());
+        assert_eq!(updates.borrow_and_update().transaction_filters.len(), 2);
+    }
+}
@@ -0,0 +1,186 @@
+//! Per-instruction swap attribution for bundled transactions
+//!
+//! Bots bundle several independent swaps into one transaction (buy one
+//! token, sell another, ...). Balance deltas net them together: there is one
+//! SOL delta for the whole transaction, so `extract_all_trades` gives every
+//! mint the same SOL amount and direction, and the per-program
+//! `TradeProcessor` keeps only the first mint.
+//!
+//! Here each top-level instruction is attributed on its own, together with
+//! the instructions it invoked: the fee payer's net SOL/WSOL and token
+//! transfers within the group make one trade with that group's mint,
+//! amounts and direction.
+//!
+//! A transaction is only split when it has at least two such swaps and every
+//! group that moved a token for the user resolved to one; anything else
+//! (single swaps, token-for-token legs, SOL the transfers don't show) keeps
+//! the balance-delta path.
+
+use crate::streamer_core::jupiter_route::token_decimals;
+use crate::streamer_core::trade_detector::{TradeDirection, TradeInfo};
+use crate::streamer_core::transfer_direction::{extract_transfer_groups, Transfer};
+use carbon_core::transaction::TransactionMetadata;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+/// Outcome of one instruction group for the user
+#[derive(Debug, PartialEq)]
+enum GroupSwap {
+    /// No token moved to or from the user (wraps, fees, account setup)
+    NoTokens,
+    /// Tokens moved but the group isn't a single SOL-for-token swap
+    Unresolved,
+    Trade {
+        mint: String,
+        lamports: u64,
+        raw_tokens: u64,
+        buy: bool,
+    },
+}
+
+/// The user's swap within one instruction group
+fn group_swap(transfers: &[Transfer], user: &Pubkey) -> GroupSwap {
+    let mut sol_net: i128 = 0;
+    let mut token_nets: HashMap<&str, i128> = HashMap::new();
+
+    for transfer in transfers {
+        let net = match transfer.mint.as_deref() {
+            _ if transfer.is_sol() => &mut sol_net,
+            Some(mint) => token_nets.entry(mint).or_insert(0),
+            None => continue,
+        };
+        if transfer.to_owner.as_ref() == Some(user) {
+            *net += transfer.amount as i128;
+        }
+        if transfer.from_owner.as_ref() == Some(user) {
+            *net -= transfer.amount as i128;
+        }
+    }
+
+    token_nets.retain(|_, net| *net != 0);
+    if token_nets.is_empty() {
+        return GroupSwap::NoTokens;
+    }
+    let mut token_nets = token_nets.into_iter();
+    let (Some((mint, token_net)), None) = (token_nets.next(), token_nets.next()) else {
+        return GroupSwap::Unresolved;
+    };
+    // Tokens in for SOL out is a buy, tokens out for SOL in a sell
+    if sol_net == 0 || sol_net.signum() == token_net.signum() {
+        return GroupSwap::Unresolved;
+    }
+
+    GroupSwap::Trade {
+        mint: mint.to_string(),
+        lamports: sol_net.unsigned_abs() as u64,
+        raw_tokens: token_net.unsigned_abs() as u64,
+        buy: token_net > 0,
+    }
+}
+
+/// One trade per swap instruction of the fee payer, if the transaction bundles several
+pub fn split_swaps(metadata: &TransactionMetadata, account_keys: &[Pubkey]) -> Option<Vec<TradeInfo>> {
+    let user = metadata.fee_payer;
+    let mut swaps = Vec::new();
+    for transfers in extract_transfer_groups(metadata, account_keys) {
+        match group_swap(&transfers, &user) {
+            GroupSwap::NoTokens => {}
+            GroupSwap::Unresolved => return None,
+            trade => swaps.push(trade),
+        }
+    }
+    if swaps.len() < 2 {
+        return None;
+    }
+
+    let decimals = token_decimals(&metadata.meta);
+    swaps
+        .into_iter()
+        .map(|swap| {
+            let GroupSwap::Trade {
+                mint,
+                lamports,
+                raw_tokens,
+                buy,
+            } = swap
+            else {
+                return None;
+            };
+            let token_decimals = *decimals.get(&Pubkey::from_str(&mint).ok()?)?;
+            Some(TradeInfo {
+                sol_amount: lamports as f64 / LAMPORTS_PER_SOL,
+                token_amount: raw_tokens as f64 / 10f64.powi(token_decimals as i32),
+                token_decimals,
+                direction: if buy { TradeDirection::Buy } else { TradeDirection::Sell },
+                user_account: Some(user),
+                program_id: None,
+                mint,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WSOL: &str = "So11111111111111111111111111111111111111112";
+
+    fn key(index: u8) -> Pubkey {
+        Pubkey::new_from_array([index; 32])
+    }
+
+    fn transfer(mint: Option<&str>, from: u8, to: u8, amount: u64) -> Transfer {
+        Transfer {
+            mint: mint.map(str::to_string),
+            from_owner: Some(key(from)),
+            to_owner: Some(key(to)),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_bundled_swaps_attributed_per_group() {
+        let (user, pool_a, pool_b) = (1, 2, 3);
+
+        // Buy A: SOL out, A in
+        let buy = vec![
+            transfer(None, user, pool_a, 2_000_000_000),
+            transfer(Some("MintA"), pool_a, user, 5_000_000),
+        ];
+        assert_eq!(
+            group_swap(&buy, &key(user)),
+            GroupSwap::Trade {
+                mint: "MintA".to_string(),
+                lamports: 2_000_000_000,
+                raw_tokens: 5_000_000,
+                buy: true
+            }
+        );
+
+        // Sell B for WSOL
+        let sell = vec![
+            transfer(Some("MintB"), user, pool_b, 7_000),
+            transfer(Some(WSOL), pool_b, user, 500_000_000),
+        ];
+        assert!(matches!(group_swap(&sell, &key(user)), GroupSwap::Trade { buy: false, .. }));
+
+        // Setup instructions move no tokens; token-for-token legs can't be valued
+        assert_eq!(group_swap(&[transfer(None, user, 9, 2_039_280)], &key(user)), GroupSwap::NoTokens);
+        let token_for_token = vec![
+            transfer(Some("MintA"), user, pool_a, 1_000),
+            transfer(Some("MintB"), pool_b, user, 2_000),
+        ];
+        assert_eq!(group_swap(&token_for_token, &key(user)), GroupSwap::Unresolved);
+
+        // SOL moving the same way as the token is not a swap
+        let both_in = vec![
+            transfer(None, pool_a, user, 1_000),
+            transfer(Some("MintA"), pool_a, user, 1_000),
+        ];
+        assert_eq!(group_swap(&both_in, &key(user)), GroupSwap::Unresolved);
+    }
+}
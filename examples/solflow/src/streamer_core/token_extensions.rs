@@ -0,0 +1,254 @@
+//! Token-2022 transfer fees
+//!
+//! Token-2022 mints with the `TransferFeeConfig` extension withhold a fee on
+//! every transfer: the sender's balance drops by the gross amount, the
+//! receiver's rises by the net. Balance deltas then disagree about the size
+//! of a trade - a buyer looks like they received less than the pool sent,
+//! and later selling everything looks like a partial sell of the gross.
+//!
+//! The fee schedule lives in the mint account, which transactions don't
+//! carry. Mints are fetched via `getAccountInfo` the first time a Token-2022
+//! balance for them is seen and their fee is cached for the process; until
+//! the fetch completes (or without `SOLANA_RPC_URL`) the mint is treated as
+//! fee-free. `extract_token_changes` reads the cache to report each delta's
+//! fee (see `BalanceDelta::gross_abs_ui_change` / `net_abs_ui_change`).
+//!
+//! Environment variables:
+//! - `SOLANA_RPC_URL`: RPC endpoint used to fetch mint accounts (fees ignored if unset)
+
+use crate::error::{codes, SolflowError};
+use base64::Engine;
+use solana_transaction_status::TransactionStatusMeta;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+/// Extensions start after the base account size plus the account-type byte
+const EXTENSIONS_OFFSET: usize = 166;
+/// TLV type of `TransferFeeConfig`
+const TRANSFER_FEE_CONFIG: u16 = 1;
+/// Offset of `older_transfer_fee` in `TransferFeeConfig` (two authorities, withheld amount)
+const OLDER_FEE_OFFSET: usize = 72;
+const TRANSFER_FEE_LEN: usize = 18;
+
+const SLOTS_PER_EPOCH: u64 = 432_000;
+const MAX_FEE_BASIS_POINTS: u64 = 10_000;
+
+/// One transfer fee schedule entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFee {
+    /// First epoch the fee applies to
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let data = data.get(..TRANSFER_FEE_LEN)?;
+        Some(Self {
+            epoch: u64::from_le_bytes(data[0..8].try_into().ok()?),
+            maximum_fee: u64::from_le_bytes(data[8..16].try_into().ok()?),
+            basis_points: u16::from_le_bytes(data[16..18].try_into().ok()?),
+        })
+    }
+
+    /// Fee withheld when `amount` (raw) is sent
+    pub fn fee(&self, amount: u64) -> u64 {
+        let bps = self.basis_points as u128;
+        if bps == 0 || amount == 0 {
+            return 0;
+        }
+        let fee = (amount as u128 * bps).div_ceil(MAX_FEE_BASIS_POINTS as u128);
+        fee.min(self.maximum_fee as u128) as u64
+    }
+
+    /// Fee withheld from a transfer that delivered `net` (raw)
+    pub fn fee_for_net(&self, net: u64) -> u64 {
+        let bps = self.basis_points as u128;
+        if bps == 0 || net == 0 {
+            return 0;
+        }
+        if bps >= MAX_FEE_BASIS_POINTS as u128 {
+            return self.maximum_fee;
+        }
+        let gross = (net as u128 * MAX_FEE_BASIS_POINTS as u128)
+            .div_ceil(MAX_FEE_BASIS_POINTS as u128 - bps);
+        ((gross - net as u128) as u64).min(self.maximum_fee)
+    }
+}
+
+/// Transfer fee in effect for a mint account at `epoch`, None if it has no fee extension
+pub fn parse_transfer_fee(mint_data: &[u8], epoch: u64) -> Option<TransferFee> {
+    let mut offset = EXTENSIONS_OFFSET;
+    while let Some(header) = mint_data.get(offset..offset + 4) {
+        let extension_type = u16::from_le_bytes([header[0], header[1]]);
+        let length = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let value = mint_data.get(offset + 4..offset + 4 + length)?;
+        if extension_type == TRANSFER_FEE_CONFIG {
+            let older = TransferFee::parse(value.get(OLDER_FEE_OFFSET..)?)?;
+            let newer = TransferFee::parse(value.get(OLDER_FEE_OFFSET + TRANSFER_FEE_LEN..)?)?;
+            let fee = if epoch >= newer.epoch { newer } else { older };
+            return (fee.basis_points > 0).then_some(fee);
+        }
+        offset += 4 + length;
+    }
+    None
+}
+
+/// Transfer fees of Token-2022 mints, fetched once per mint
+pub struct MintFeeCache {
+    rpc: Option<(String, reqwest::Client)>,
+    /// Fetched mints; None means no transfer fee
+    fees: Mutex<HashMap<String, Option<TransferFee>>>,
+    pending: Mutex<HashSet<String>>,
+}
+
+impl MintFeeCache {
+    pub fn from_env() -> Self {
+        let rpc = std::env::var("SOLANA_RPC_URL").ok().and_then(|url| {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .build()
+                .ok()?;
+            Some((url, client))
+        });
+        Self {
+            rpc,
+            fees: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Cached transfer fee of a mint (None while unknown or fee-free)
+    pub fn transfer_fee(&self, mint: &str) -> Option<TransferFee> {
+        self.fees.lock().unwrap().get(mint).copied().flatten()
+    }
+
+    /// Record a mint's fee (or lack of one)
+    pub fn insert(&self, mint: &str, fee: Option<TransferFee>) {
+        self.fees.lock().unwrap().insert(mint.to_string(), fee);
+    }
+
+    /// Fetch, in the background, the Token-2022 mints in this transaction not yet known
+    pub fn prefetch(self: &Arc<Self>, meta: &TransactionStatusMeta) {
+        if self.rpc.is_none() {
+            return;
+        }
+        let balances = meta.post_token_balances.iter().flatten();
+        for balance in balances.filter(|b| b.program_id == TOKEN_2022_PROGRAM_ID) {
+            if self.fees.lock().unwrap().contains_key(&balance.mint)
+                || !self.pending.lock().unwrap().insert(balance.mint.clone())
+            {
+                continue;
+            }
+            let cache = self.clone();
+            let mint = balance.mint.clone();
+            tokio::spawn(async move {
+                match cache.fetch(&mint).await {
+                    Ok(fee) => {
+                        if let Some(fee) = fee {
+                            log::debug!("🪙 {} charges a {} bps transfer fee", mint, fee.basis_points);
+                        }
+                        cache.insert(&mint, fee);
+                    }
+                    Err(e) => log::warn!("⚠️  Mint lookup failed for {}: {}", mint, e),
+                }
+                cache.pending.lock().unwrap().remove(&mint);
+            });
+        }
+    }
+
+    async fn fetch(&self, mint: &str) -> Result<Option<TransferFee>, SolflowError> {
+        let Some((rpc_url, client)) = &self.rpc else {
+            return Ok(None);
+        };
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [mint, {"encoding": "base64"}],
+        });
+
+        let response: serde_json::Value =
+            client.post(rpc_url).json(&request).send().await?.json().await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(SolflowError::enrichment(
+                codes::ENRICHMENT_HTTP,
+                format!("RPC error: {}", error),
+            ));
+        }
+
+        let slot = response.pointer("/result/context/slot").and_then(|s| s.as_u64()).unwrap_or(0);
+        let Some(encoded) = response.pointer("/result/value/data/0").and_then(|d| d.as_str()) else {
+            return Ok(None);
+        };
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| SolflowError::enrichment(codes::ENRICHMENT_RESPONSE, e))?;
+
+        Ok(parse_transfer_fee(&data, slot / SLOTS_PER_EPOCH))
+    }
+}
+
+static SHARED: OnceLock<Arc<MintFeeCache>> = OnceLock::new();
+
+/// The process-wide cache, created from the environment on first use
+pub fn shared() -> Arc<MintFeeCache> {
+    SHARED.get_or_init(|| Arc::new(MintFeeCache::from_env())).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Token-2022 mint with a TransferFeeConfig extension
+    fn mint_data(older: TransferFee, newer: TransferFee) -> Vec<u8> {
+        let mut data = vec![0u8; EXTENSIONS_OFFSET];
+        data[165] = 1; // AccountType::Mint
+        // An unrelated extension first (MintCloseAuthority)
+        data.extend(3u16.to_le_bytes());
+        data.extend(32u16.to_le_bytes());
+        data.extend([0u8; 32]);
+        data.extend(TRANSFER_FEE_CONFIG.to_le_bytes());
+        data.extend(108u16.to_le_bytes());
+        data.extend([0u8; 72]); // authorities, withheld amount
+        for fee in [older, newer] {
+            data.extend(fee.epoch.to_le_bytes());
+            data.extend(fee.maximum_fee.to_le_bytes());
+            data.extend(fee.basis_points.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_transfer_fee_parsed_and_applied() {
+        let older = TransferFee {
+            epoch: 0,
+            maximum_fee: u64::MAX,
+            basis_points: 100,
+        };
+        let newer = TransferFee {
+            epoch: 700,
+            maximum_fee: 5_000,
+            basis_points: 300,
+        };
+        let data = mint_data(older, newer);
+
+        // Newer schedule only from its epoch on
+        assert_eq!(parse_transfer_fee(&data, 699), Some(older));
+        assert_eq!(parse_transfer_fee(&data, 700), Some(newer));
+        // Plain mints (no extensions) have no fee
+        assert_eq!(parse_transfer_fee(&data[..82], 700), None);
+
+        // 1% of 10_000 sent; the receiver got 9_900 of it
+        assert_eq!(older.fee(10_000), 100);
+        assert_eq!(older.fee_for_net(9_900), 100);
+        // Capped at maximum_fee
+        assert_eq!(newer.fee(1_000_000), 5_000);
+        assert_eq!(newer.fee_for_net(995_000), 5_000);
+    }
+}
@@ -1,5 +1,6 @@
-use crate::streamer_core::balance_extractor::BalanceDelta;
+use crate::streamer_core::balance_extractor::{BalanceDelta, QuoteChange, QuoteCurrency};
 use solana_pubkey::Pubkey;
+use std::collections::HashSet;
 
 #[derive(Debug, Clone)]
 pub struct TradeInfo {
@@ -9,9 +10,11 @@ pub struct TradeInfo {
     pub token_decimals: u8,
     pub direction: TradeDirection,
     pub user_account: Option<Pubkey>,
+    /// Program that executed the swap, when decoded per leg (see `jupiter_route`)
+    pub program_id: Option<Pubkey>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TradeDirection {
     Buy,
     Sell,
@@ -28,6 +31,53 @@ impl From<TradeDirection> for &'static str {
     }
 }
 
+/// What a tracked program's instruction does, from the discriminator table
+/// (see `program_registry`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstructionAction {
+    Buy,
+    Sell,
+    /// Either direction, decided by balance deltas
+    Swap,
+    AddLiquidity,
+    RemoveLiquidity,
+}
+
+impl InstructionAction {
+    /// Direction the instruction names, if any
+    pub fn direction(self) -> Option<TradeDirection> {
+        match self {
+            InstructionAction::Buy => Some(TradeDirection::Buy),
+            InstructionAction::Sell => Some(TradeDirection::Sell),
+            _ => None,
+        }
+    }
+
+    /// Deposits and withdrawals move tokens and SOL but aren't trades
+    pub fn is_liquidity(self) -> bool {
+        matches!(self, InstructionAction::AddLiquidity | InstructionAction::RemoveLiquidity)
+    }
+}
+
+/// Take the direction of balance-delta trades from the matched instruction
+///
+/// The discriminator table is the primary signal; SOL flow misreads
+/// wallet-to-wallet transfers and some route programs. Only a lone trade is
+/// overridden, since the instruction doesn't say which of several mints it
+/// bought. Returns how many trades changed direction.
+pub fn apply_instruction_action(trades: &mut [TradeInfo], action: Option<InstructionAction>) -> usize {
+    let Some(direction) = action.and_then(InstructionAction::direction) else {
+        return 0;
+    };
+    match trades {
+        [trade] if trade.direction != direction => {
+            trade.direction = direction;
+            1
+        }
+        _ => 0,
+    }
+}
+
 fn find_primary_token_mint(token_deltas: &[BalanceDelta]) -> Option<String> {
     token_deltas
         .iter()
@@ -36,6 +86,19 @@ fn find_primary_token_mint(token_deltas: &[BalanceDelta]) -> Option<String> {
         .map(|d| d.mint.clone())
 }
 
+/// Token amount on the wallet's side of a transfer-fee mint: received on a
+/// buy (net), sent on a sell (gross), whichever account's delta was picked
+///
+/// The largest delta is the pool's on a buy but the wallet's on a sell, so
+/// raw deltas would record more bought than the wallet can later sell.
+fn wallet_side_amount(delta: &BalanceDelta, direction: TradeDirection) -> f64 {
+    match direction {
+        TradeDirection::Buy => delta.net_abs_ui_change(),
+        TradeDirection::Sell => delta.gross_abs_ui_change(),
+        TradeDirection::Unknown => delta.abs_ui_change(),
+    }
+}
+
 fn find_user_account(sol_deltas: &[BalanceDelta]) -> Option<usize> {
     sol_deltas
         .iter()
@@ -167,7 +230,7 @@ pub fn extract_all_trades(
             None => continue,
         };
 
-        let token_amount = largest_delta.abs_ui_change();
+        let token_amount = wallet_side_amount(largest_delta, direction);
         let token_decimals = largest_delta.decimals;
 
         trades.push(TradeInfo {
@@ -177,6 +240,7 @@ pub fn extract_all_trades(
             token_decimals,
             direction,
             user_account,
+            program_id: None,
         });
     }
 
@@ -206,6 +270,66 @@ pub fn extract_trade_info(
         .next()
 }
 
+/// Extract trades paid from WSOL, USDC or USDT token accounts
+///
+/// `extract_all_trades` reads the quote side from native SOL balances, so a
+/// wallet paying from a token account shows only its fee there and the trade
+/// is dropped. Here the quote side is the user's largest quote-currency
+/// change (see `extract_quote_changes`), valued in SOL: a quote outflow is a
+/// BUY of every other mint the transaction moved, an inflow a SELL.
+///
+/// Empty when the user's quote balances didn't move, or only in stablecoins
+/// while no SOL/USD price is cached.
+pub fn extract_quoted_trades(
+    quote_changes: &[QuoteChange],
+    token_deltas: &[BalanceDelta],
+    user_account: Pubkey,
+    now: i64,
+) -> Vec<TradeInfo> {
+    const MIN_QUOTE_SOL: f64 = 0.0001;
+
+    let Some((quote_sol, outflow)) = quote_changes
+        .iter()
+        .filter_map(|c| Some((c.currency.to_sol(c.ui_change.abs(), now)?, c.ui_change < 0.0)))
+        .filter(|(sol, _)| *sol >= MIN_QUOTE_SOL)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+    else {
+        return Vec::new();
+    };
+    let direction = if outflow { TradeDirection::Buy } else { TradeDirection::Sell };
+
+    let mut largest: Vec<&BalanceDelta> = Vec::new();
+    for delta in token_deltas.iter().filter(|d| QuoteCurrency::from_mint(&d.mint).is_none()) {
+        match largest.iter_mut().find(|d| d.mint == delta.mint) {
+            Some(current) if current.raw_change.abs() < delta.raw_change.abs() => *current = delta,
+            Some(_) => {}
+            None => largest.push(delta),
+        }
+    }
+
+    largest
+        .into_iter()
+        .map(|delta| TradeInfo {
+            mint: delta.mint.clone(),
+            sol_amount: quote_sol,
+            token_amount: wallet_side_amount(delta, direction),
+            token_decimals: delta.decimals,
+            direction,
+            user_account: Some(user_account),
+            program_id: None,
+        })
+        .collect()
+}
+
+/// Drop trades on mints a multi-hop swap only passed through
+///
+/// Balance deltas include pool vaults, so the intermediate token of an Orca
+/// two-hop swap (see `whirlpool`) looks traded even though the user never
+/// held it.
+pub fn drop_intermediate_mints(trades: &mut Vec<TradeInfo>, intermediate_mints: &HashSet<String>) {
+    trades.retain(|trade| !intermediate_mints.contains(&trade.mint));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,6 +352,7 @@ mod tests {
                 ui_change: -1.0,
                 decimals: 9,
                 is_sol: true,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -239,6 +364,7 @@ mod tests {
                 ui_change: 1000.0,
                 decimals: 6,
                 is_sol: false,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -267,6 +393,7 @@ mod tests {
                 ui_change: -2.0,
                 decimals: 9,
                 is_sol: true,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -278,6 +405,7 @@ mod tests {
                 ui_change: 500.0,
                 decimals: 6,
                 is_sol: false,
+                transfer_fee: 0.0,
             },
             BalanceDelta {
                 account_index: 2,
@@ -286,6 +414,7 @@ mod tests {
                 ui_change: 2000.0,
                 decimals: 6,
                 is_sol: false,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -320,6 +449,7 @@ mod tests {
                 ui_change: -1.0,
                 decimals: 9,
                 is_sol: true,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -331,6 +461,7 @@ mod tests {
                 ui_change: 1.0,
                 decimals: 9,
                 is_sol: false, // Token account wrapping SOL
+                transfer_fee: 0.0,
             },
             BalanceDelta {
                 account_index: 2,
@@ -339,6 +470,7 @@ mod tests {
                 ui_change: 100.0,
                 decimals: 6,
                 is_sol: false,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -362,6 +494,7 @@ mod tests {
                 ui_change: 1000.0,
                 decimals: 6,
                 is_sol: false,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -383,6 +516,7 @@ mod tests {
                 ui_change: 1.5,
                 decimals: 9,
                 is_sol: true,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -394,6 +528,7 @@ mod tests {
                 ui_change: -500.0,
                 decimals: 6,
                 is_sol: false,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -407,6 +542,69 @@ mod tests {
         assert_eq!(trades[0].token_amount, 500.0); // Absolute value
     }
 
+    #[test]
+    fn test_extract_all_trades_transfer_fee_round_trip() {
+        // Test: 1% fee-on-transfer token; the pool sends 1000, the wallet receives 990
+        let buy_sol = vec![BalanceDelta {
+            account_index: 0,
+            mint: "So11111111111111111111111111111111111111112".to_string(),
+            raw_change: -1_000_000_000,
+            ui_change: -1.0,
+            decimals: 9,
+            is_sol: true,
+            transfer_fee: 0.0,
+        }];
+        let buy_tokens = vec![
+            BalanceDelta {
+                account_index: 1,
+                mint: "FeeToken".to_string(),
+                raw_change: 990_000000,
+                ui_change: 990.0,
+                decimals: 6,
+                is_sol: false,
+                transfer_fee: 10.0,
+            },
+            BalanceDelta {
+                account_index: 2,
+                mint: "FeeToken".to_string(),
+                raw_change: -1000_000000, // Pool vault (largest delta)
+                ui_change: -1000.0,
+                decimals: 6,
+                is_sol: false,
+                transfer_fee: 10.0,
+            },
+        ];
+        let account_keys = vec![mock_pubkey(0), mock_pubkey(1), mock_pubkey(2)];
+
+        let buy = extract_all_trades(&buy_sol, &buy_tokens, &account_keys);
+        assert_eq!(buy[0].token_amount, 990.0);
+
+        // Selling all 990 back: the pool receives 980.1
+        let sell_sol = vec![BalanceDelta {
+            raw_change: 980_000_000,
+            ui_change: 0.98,
+            ..buy_sol[0].clone()
+        }];
+        let sell_tokens = vec![
+            BalanceDelta {
+                raw_change: -990_000000,
+                ui_change: -990.0,
+                transfer_fee: 9.9,
+                ..buy_tokens[0].clone()
+            },
+            BalanceDelta {
+                raw_change: 980_100000,
+                ui_change: 980.1,
+                transfer_fee: 9.9,
+                ..buy_tokens[1].clone()
+            },
+        ];
+
+        let sell = extract_all_trades(&sell_sol, &sell_tokens, &account_keys);
+        assert!(matches!(sell[0].direction, TradeDirection::Sell));
+        assert_eq!(sell[0].token_amount, buy[0].token_amount, "Full exit, not a partial sell");
+    }
+
     #[test]
     fn test_extract_trade_info_backwards_compat() {
         // Test: extract_trade_info() returns first trade only
@@ -418,6 +616,7 @@ mod tests {
                 ui_change: -1.0,
                 decimals: 9,
                 is_sol: true,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -429,6 +628,7 @@ mod tests {
                 ui_change: 100.0,
                 decimals: 6,
                 is_sol: false,
+                transfer_fee: 0.0,
             },
             BalanceDelta {
                 account_index: 2,
@@ -437,6 +637,7 @@ mod tests {
                 ui_change: 200.0,
                 decimals: 6,
                 is_sol: false,
+                transfer_fee: 0.0,
             },
         ];
 
@@ -451,4 +652,72 @@ mod tests {
         let mint = &single_trade.unwrap().mint;
         assert!(mint == "MintA" || mint == "MintB");
     }
+
+    #[test]
+    fn test_extract_quoted_trades_from_wsol_account() {
+        // Test: user sells from a persistent WSOL account (no native SOL delta)
+        let token_deltas = vec![
+            BalanceDelta {
+                account_index: 1,
+                mint: "TokenMint".to_string(),
+                raw_change: -5000_000000,
+                ui_change: -5000.0,
+                decimals: 6,
+                is_sol: false,
+                transfer_fee: 0.0,
+            },
+            BalanceDelta {
+                account_index: 2,
+                mint: "So11111111111111111111111111111111111111112".to_string(),
+                raw_change: 2_500_000_000,
+                ui_change: 2.5,
+                decimals: 9,
+                is_sol: false,
+                transfer_fee: 0.0,
+            },
+        ];
+        let quote_changes = vec![QuoteChange {
+            currency: QuoteCurrency::Sol,
+            ui_change: 2.5,
+        }];
+
+        let trades = extract_quoted_trades(&quote_changes, &token_deltas, mock_pubkey(0), 0);
+        assert_eq!(trades.len(), 1, "WSOL is the quote, not a traded mint");
+        assert_eq!(trades[0].mint, "TokenMint");
+        assert!(matches!(trades[0].direction, TradeDirection::Sell));
+        assert!((trades[0].sol_amount - 2.5).abs() < 1e-9);
+        assert!((trades[0].token_amount - 5000.0).abs() < 1e-9);
+
+        // No quote movement: nothing to value the trade with
+        assert!(extract_quoted_trades(&[], &token_deltas, mock_pubkey(0), 0).is_empty());
+    }
+
+    #[test]
+    fn test_instruction_action_overrides_lone_trade_direction() {
+        let trade = |mint: &str, direction| TradeInfo {
+            mint: mint.to_string(),
+            sol_amount: 1.0,
+            token_amount: 1000.0,
+            token_decimals: 6,
+            direction,
+            user_account: Some(mock_pubkey(0)),
+            program_id: None,
+        };
+
+        // SOL flow said buy, the instruction was a sell
+        let mut trades = vec![trade("MintA", TradeDirection::Buy)];
+        assert_eq!(apply_instruction_action(&mut trades, Some(InstructionAction::Sell)), 1);
+        assert_eq!(trades[0].direction, TradeDirection::Sell);
+        assert_eq!(apply_instruction_action(&mut trades, Some(InstructionAction::Sell)), 0);
+
+        // Swaps and unknown instructions fall back to balance deltas
+        assert_eq!(apply_instruction_action(&mut trades, Some(InstructionAction::Swap)), 0);
+        assert_eq!(apply_instruction_action(&mut trades, None), 0);
+        assert_eq!(trades[0].direction, TradeDirection::Sell);
+
+        // Several mints: the instruction doesn't say which one it bought
+        let mut route = vec![trade("MintA", TradeDirection::Sell), trade("MintB", TradeDirection::Buy)];
+        assert_eq!(apply_instruction_action(&mut route, Some(InstructionAction::Buy)), 0);
+        assert_eq!(route[0].direction, TradeDirection::Sell);
+    }
 }
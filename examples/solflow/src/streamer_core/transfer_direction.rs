@@ -0,0 +1,342 @@
+//! Trade direction from SPL transfer topology
+//!
+//! `extract_all_trades` reads direction off the user's SOL balance delta and
+//! yields `TradeDirection::Unknown` when that delta doesn't say which way the
+//! trade went. Unknown trades are left out of net flow, so this secondary
+//! classifier looks at the transfers themselves (top-level and inner): the
+//! user receiving the traded mint while paying SOL/WSOL is a buy, the user
+//! sending the mint while receiving SOL/WSOL is a sell.
+//!
+//! Token account owners come from the transaction's pre/post token balances;
+//! a source account without a balance entry falls back to the transfer
+//! authority.
+
+use crate::streamer_core::trade_detector::{TradeDirection, TradeInfo};
+use carbon_core::transaction::TransactionMetadata;
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// SPL Token `Transfer` / `TransferChecked` instruction tags
+const TRANSFER_TAG: u8 = 3;
+const TRANSFER_CHECKED_TAG: u8 = 12;
+/// System program `Transfer` instruction index (u32 LE)
+const SYSTEM_TRANSFER_INDEX: u32 = 2;
+
+/// One value movement between two owners
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    /// Token mint, None for native SOL
+    pub mint: Option<String>,
+    pub from_owner: Option<Pubkey>,
+    pub to_owner: Option<Pubkey>,
+    pub amount: u64,
+}
+
+impl Transfer {
+    /// Native SOL or WSOL
+    pub fn is_sol(&self) -> bool {
+        self.mint.as_deref().is_none_or(|mint| mint == WSOL_MINT)
+    }
+}
+
+/// Decoded transfer instruction, before owner resolution (account indexes)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RawTransfer {
+    Token {
+        source: usize,
+        destination: usize,
+        authority: usize,
+        amount: u64,
+    },
+    System {
+        from: usize,
+        to: usize,
+        lamports: u64,
+    },
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+fn decode_token_transfer(accounts: &[u8], data: &[u8]) -> Option<RawTransfer> {
+    let account = |i: usize| accounts.get(i).map(|&index| index as usize);
+    match *data.first()? {
+        TRANSFER_TAG => Some(RawTransfer::Token {
+            source: account(0)?,
+            destination: account(1)?,
+            authority: account(2)?,
+            amount: read_u64(data, 1)?,
+        }),
+        // [source, mint, destination, authority]
+        TRANSFER_CHECKED_TAG => Some(RawTransfer::Token {
+            source: account(0)?,
+            destination: account(2)?,
+            authority: account(3)?,
+            amount: read_u64(data, 1)?,
+        }),
+        _ => None,
+    }
+}
+
+fn decode_system_transfer(accounts: &[u8], data: &[u8]) -> Option<RawTransfer> {
+    let index = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+    if index != SYSTEM_TRANSFER_INDEX {
+        return None;
+    }
+    Some(RawTransfer::System {
+        from: *accounts.first()? as usize,
+        to: *accounts.get(1)? as usize,
+        lamports: read_u64(data, 4)?,
+    })
+}
+
+/// Every SOL and SPL token transfer in the transaction, in execution order
+pub fn extract_transfers(metadata: &TransactionMetadata, account_keys: &[Pubkey]) -> Vec<Transfer> {
+    extract_transfer_groups(metadata, account_keys).into_iter().flatten().collect()
+}
+
+/// Transfers per top-level instruction (including its inner instructions)
+pub fn extract_transfer_groups(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+) -> Vec<Vec<Transfer>> {
+    let token_programs = [
+        Pubkey::from_str(TOKEN_PROGRAM_ID).ok(),
+        Pubkey::from_str(TOKEN_2022_PROGRAM_ID).ok(),
+    ];
+    let system_program = Pubkey::default();
+
+    // account index → (mint, owner) for every token account with a balance entry
+    let mut token_accounts: HashMap<usize, (String, Option<Pubkey>)> = HashMap::new();
+    let balances = [&metadata.meta.pre_token_balances, &metadata.meta.post_token_balances];
+    for balance in balances.into_iter().flatten().flatten() {
+        token_accounts.insert(
+            balance.account_index as usize,
+            (balance.mint.clone(), Pubkey::from_str(&balance.owner).ok()),
+        );
+    }
+
+    let decode = |program_id_index: u8, accounts: &[u8], data: &[u8]| -> Option<RawTransfer> {
+        let program = account_keys.get(program_id_index as usize)?;
+        if token_programs.contains(&Some(*program)) {
+            decode_token_transfer(accounts, data)
+        } else if *program == system_program {
+            decode_system_transfer(accounts, data)
+        } else {
+            None
+        }
+    };
+
+    let inner_groups = metadata.meta.inner_instructions.as_deref().unwrap_or_default();
+    metadata
+        .message
+        .instructions()
+        .iter()
+        .enumerate()
+        .map(|(index, ix)| {
+            let mut raw: Vec<RawTransfer> =
+                decode(ix.program_id_index, &ix.accounts, &ix.data).into_iter().collect();
+            for inner_group in inner_groups.iter().filter(|group| group.index as usize == index) {
+                for inner in &inner_group.instructions {
+                    let ix = &inner.instruction;
+                    raw.extend(decode(ix.program_id_index, &ix.accounts, &ix.data));
+                }
+            }
+            raw.into_iter()
+                .filter_map(|transfer| resolve_owners(transfer, &token_accounts, account_keys))
+                .collect()
+        })
+        .collect()
+}
+
+fn resolve_owners(
+    transfer: RawTransfer,
+    token_accounts: &HashMap<usize, (String, Option<Pubkey>)>,
+    account_keys: &[Pubkey],
+) -> Option<Transfer> {
+    match transfer {
+        RawTransfer::Token {
+            source,
+            destination,
+            authority,
+            amount,
+        } => {
+            let source_entry = token_accounts.get(&source);
+            let destination_entry = token_accounts.get(&destination);
+            let mint = source_entry.or(destination_entry)?.0.clone();
+            let from_owner = source_entry
+                .and_then(|(_, owner)| *owner)
+                .or_else(|| account_keys.get(authority).copied());
+            Some(Transfer {
+                mint: Some(mint),
+                from_owner,
+                to_owner: destination_entry.and_then(|(_, owner)| *owner),
+                amount,
+            })
+        }
+        RawTransfer::System { from, to, lamports } => Some(Transfer {
+            mint: None,
+            from_owner: account_keys.get(from).copied(),
+            to_owner: account_keys.get(to).copied(),
+            amount: lamports,
+        }),
+    }
+}
+
+/// Classify `user`'s trade of `mint` from the transfers alone
+///
+/// Requires the mint to move to or from the user; SOL/WSOL moving the same
+/// way as the token (both in or both out) is contradictory and stays Unknown.
+pub fn infer_direction(transfers: &[Transfer], user: &Pubkey, mint: &str) -> TradeDirection {
+    let mut token_net: i128 = 0;
+    let mut sol_net: i128 = 0;
+
+    for transfer in transfers {
+        let net = if transfer.mint.as_deref() == Some(mint) {
+            &mut token_net
+        } else if transfer.is_sol() {
+            &mut sol_net
+        } else {
+            continue;
+        };
+        if transfer.to_owner.as_ref() == Some(user) {
+            *net += transfer.amount as i128;
+        }
+        if transfer.from_owner.as_ref() == Some(user) {
+            *net -= transfer.amount as i128;
+        }
+    }
+
+    match (token_net.signum(), sol_net.signum()) {
+        (1, -1 | 0) => TradeDirection::Buy,
+        (-1, 1 | 0) => TradeDirection::Sell,
+        _ => TradeDirection::Unknown,
+    }
+}
+
+/// Fill in Unknown directions from transfer topology; returns how many were resolved
+///
+/// Transfers are only decoded when at least one trade needs them.
+pub fn resolve_unknown_directions(
+    trades: &mut [TradeInfo],
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+) -> usize {
+    if !trades
+        .iter()
+        .any(|t| matches!(t.direction, TradeDirection::Unknown) && t.user_account.is_some())
+    {
+        return 0;
+    }
+
+    let transfers = extract_transfers(metadata, account_keys);
+    let mut resolved = 0;
+    for trade in trades.iter_mut() {
+        let (TradeDirection::Unknown, Some(user)) = (trade.direction, trade.user_account) else {
+            continue;
+        };
+        trade.direction = infer_direction(&transfers, &user, &trade.mint);
+        if !matches!(trade.direction, TradeDirection::Unknown) {
+            log::debug!(
+                "🔀 Inferred {} for {} from transfers (signature: {})",
+                <&str>::from(trade.direction),
+                trade.mint,
+                metadata.signature
+            );
+            resolved += 1;
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(index: u8) -> Pubkey {
+        let mut bytes = [0u8; 32];
+        bytes[0] = index;
+        Pubkey::from(bytes)
+    }
+
+    fn transfer(mint: Option<&str>, from: u8, to: u8, amount: u64) -> Transfer {
+        Transfer {
+            mint: mint.map(str::to_string),
+            from_owner: Some(key(from)),
+            to_owner: Some(key(to)),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_decode_transfer_instructions() {
+        let mut data = vec![TRANSFER_CHECKED_TAG];
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.push(6);
+        assert_eq!(
+            decode_token_transfer(&[4, 5, 6, 7], &data),
+            Some(RawTransfer::Token {
+                source: 4,
+                destination: 6,
+                authority: 7,
+                amount: 500
+            })
+        );
+        // Other token instructions (e.g. CloseAccount) are ignored
+        assert_eq!(decode_token_transfer(&[4, 5, 6], &[9]), None);
+
+        let mut data = SYSTEM_TRANSFER_INDEX.to_le_bytes().to_vec();
+        data.extend_from_slice(&1_000_000u64.to_le_bytes());
+        assert_eq!(
+            decode_system_transfer(&[1, 2], &data),
+            Some(RawTransfer::System {
+                from: 1,
+                to: 2,
+                lamports: 1_000_000
+            })
+        );
+    }
+
+    #[test]
+    fn test_infer_direction_from_topology() {
+        let (user, pool) = (1, 2);
+        let user_key = key(user);
+
+        // User pays WSOL, receives the token
+        let buy = vec![
+            transfer(Some(WSOL_MINT), user, pool, 1_000_000_000),
+            transfer(Some("MintA"), pool, user, 5_000),
+        ];
+        assert!(matches!(infer_direction(&buy, &user_key, "MintA"), TradeDirection::Buy));
+
+        // User sends the token, receives native SOL
+        let sell = vec![
+            transfer(Some("MintA"), user, pool, 5_000),
+            transfer(None, pool, user, 900_000_000),
+        ];
+        assert!(matches!(infer_direction(&sell, &user_key, "MintA"), TradeDirection::Sell));
+
+        // Token and SOL both flowing in is contradictory
+        let contradictory = vec![
+            transfer(Some("MintA"), pool, user, 5_000),
+            transfer(None, pool, user, 900_000_000),
+        ];
+        assert!(matches!(
+            infer_direction(&contradictory, &user_key, "MintA"),
+            TradeDirection::Unknown
+        ));
+
+        // SOL alone (tips, fees) never decides the direction
+        let sol_only = vec![transfer(None, user, pool, 10_000)];
+        assert!(matches!(
+            infer_direction(&sol_only, &user_key, "MintA"),
+            TradeDirection::Unknown
+        ));
+    }
+}
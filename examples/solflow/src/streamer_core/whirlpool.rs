@@ -0,0 +1,123 @@
+//! Orca Whirlpool two-hop swap decoding
+//!
+//! A Whirlpool `two_hop_swap` (top-level or CPI'd by an aggregator) trades
+//! A → B in one pool and B → C in another. The user never holds B, but the
+//! two pool vaults do change by the B amount, so balance deltas alone report
+//! a phantom B trade next to the real A/C one. This module finds the
+//! intermediate mint of every two-hop swap so those trades can be dropped.
+
+use carbon_core::deserialize::CarbonDeserialize;
+use carbon_core::transaction::TransactionMetadata;
+use carbon_orca_whirlpool_decoder::instructions::two_hop_swap::TwoHopSwap;
+use carbon_orca_whirlpool_decoder::instructions::two_hop_swap_v2::TwoHopSwapV2;
+use carbon_orca_whirlpool_decoder::PROGRAM_ID as WHIRLPOOL_PROGRAM_ID;
+use solana_pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+
+/// `two_hop_swap` accounts holding pool one's A and B tokens
+const TWO_HOP_VAULT_ONE_A: usize = 5;
+const TWO_HOP_VAULT_ONE_B: usize = 7;
+
+/// `two_hop_swap_v2` account naming the intermediate mint
+const TWO_HOP_V2_INTERMEDIATE_MINT: usize = 3;
+
+/// Intermediate mint of one Whirlpool instruction (None if it isn't a two-hop swap)
+///
+/// `accounts` are the instruction's indices into `account_keys`;
+/// `token_account_mints` maps token account indices to their mint.
+pub fn intermediate_mint(
+    accounts: &[u8],
+    data: &[u8],
+    account_keys: &[Pubkey],
+    token_account_mints: &HashMap<usize, String>,
+) -> Option<String> {
+    if TwoHopSwapV2::deserialize(data).is_some() {
+        let index = *accounts.get(TWO_HOP_V2_INTERMEDIATE_MINT)? as usize;
+        return account_keys.get(index).map(|mint| mint.to_string());
+    }
+    let swap = TwoHopSwap::deserialize(data)?;
+    // Pool one pays out B when swapping A → B, otherwise A
+    let vault = if swap.a_to_b_one {
+        TWO_HOP_VAULT_ONE_B
+    } else {
+        TWO_HOP_VAULT_ONE_A
+    };
+    token_account_mints.get(&(*accounts.get(vault)? as usize)).cloned()
+}
+
+/// Mints that Whirlpool two-hop swaps in this transaction only passed through
+pub fn two_hop_intermediate_mints(
+    metadata: &TransactionMetadata,
+    account_keys: &[Pubkey],
+) -> HashSet<String> {
+    let token_account_mints: HashMap<usize, String> = [
+        &metadata.meta.pre_token_balances,
+        &metadata.meta.post_token_balances,
+    ]
+    .into_iter()
+    .flatten()
+    .flatten()
+    .map(|balance| (balance.account_index as usize, balance.mint.clone()))
+    .collect();
+
+    let mut mints = HashSet::new();
+    let mut check = |program_id_index: u8, accounts: &[u8], data: &[u8]| {
+        if account_keys.get(program_id_index as usize) == Some(&WHIRLPOOL_PROGRAM_ID) {
+            mints.extend(intermediate_mint(accounts, data, account_keys, &token_account_mints));
+        }
+    };
+    for ix in metadata.message.instructions() {
+        check(ix.program_id_index, &ix.accounts, &ix.data);
+    }
+    if let Some(inner_groups) = &metadata.meta.inner_instructions {
+        for inner_group in inner_groups {
+            for inner in &inner_group.instructions {
+                let ix = &inner.instruction;
+                check(ix.program_id_index, &ix.accounts, &ix.data);
+            }
+        }
+    }
+    mints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Borsh layout shared by both two-hop instructions (v2 adds an optional tail)
+    fn two_hop_data(discriminator: [u8; 8], a_to_b_one: bool) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        data.extend(1_000u64.to_le_bytes()); // amount
+        data.extend(900u64.to_le_bytes()); // other_amount_threshold
+        data.extend([1, a_to_b_one as u8, 0]);
+        data.extend(0u128.to_le_bytes());
+        data.extend(0u128.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_two_hop_intermediate_mint() {
+        let account_keys: Vec<Pubkey> = (0..30u8).map(|i| Pubkey::new_from_array([i; 32])).collect();
+        let accounts: Vec<u8> = (0..24).collect();
+        let mints = HashMap::from([(5, "usdc".to_string()), (7, "token".to_string())]);
+
+        let v1 = [0xc3, 0x60, 0xed, 0x6c, 0x44, 0xa2, 0xdb, 0xe6];
+        // A → B in pool one leaves B (vault one_b) as the intermediate
+        let data = two_hop_data(v1, true);
+        assert_eq!(intermediate_mint(&accounts, &data, &account_keys, &mints).as_deref(), Some("token"));
+        let data = two_hop_data(v1, false);
+        assert_eq!(intermediate_mint(&accounts, &data, &account_keys, &mints).as_deref(), Some("usdc"));
+
+        // v2 names the intermediate mint account directly
+        let mut data = two_hop_data([0xba, 0x8f, 0xd1, 0x1d, 0xfe, 0x02, 0xc2, 0x75], true);
+        data.push(0); // remaining_accounts_info: None
+        assert_eq!(
+            intermediate_mint(&accounts, &data, &account_keys, &mints),
+            Some(account_keys[3].to_string())
+        );
+
+        // A single-pool swap has no intermediate
+        let swap = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
+        assert!(intermediate_mint(&accounts, &two_hop_data(swap, true), &account_keys, &mints).is_none());
+    }
+}
@@ -6,6 +6,8 @@ pub enum WriterError {
     Io(std::io::Error),
     Serialization(serde_json::Error),
     Database(String),
+    /// Message broker (Kafka) client or delivery error
+    Publish(String),
 }
 
 impl From<std::io::Error> for WriterError {
@@ -26,18 +28,33 @@ impl From<rusqlite::Error> for WriterError {
     }
 }
 
+impl From<sqlx::Error> for WriterError {
+    fn from(err: sqlx::Error) -> Self {
+        WriterError::Database(err.to_string())
+    }
+}
+
 impl std::fmt::Display for WriterError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             WriterError::Io(e) => write!(f, "IO error: {}", e),
             WriterError::Serialization(e) => write!(f, "Serialization error: {}", e),
             WriterError::Database(e) => write!(f, "Database error: {}", e),
+            WriterError::Publish(e) => write!(f, "Publish error: {}", e),
         }
     }
 }
 
 impl std::error::Error for WriterError {}
 
+/// Read `KAFKA_BROKERS` (the Kafka backend's `output_path`)
+pub fn kafka_brokers_from_env() -> Result<String, WriterError> {
+    std::env::var("KAFKA_BROKERS")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| WriterError::Publish("KAFKA_BROKERS is not set".to_string()))
+}
+
 #[async_trait]
 pub trait WriterBackend: Send {
     /// Write a single trade event
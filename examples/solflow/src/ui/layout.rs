@@ -1,5 +1,5 @@
 use {
-    crate::state::State,
+    crate::{state::State, ui::signal_browser::SignalBrowser},
     ratatui::{
         layout::{Constraint, Layout as RatLayout, Rect},
         style::{Color, Modifier, Style},
@@ -33,6 +33,20 @@ pub fn render_layout(f: &mut Frame, area: Rect, state: &State) -> Result<(), Box
     Ok(())
 }
 
+/// Render the historical signal browser screen
+pub fn render_signals_layout(f: &mut Frame, area: Rect, browser: &SignalBrowser) {
+    let chunks = RatLayout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Header
+            Constraint::Min(0),    // Browser
+        ])
+        .split(area);
+
+    render_header(f, chunks[0]);
+    browser.render(f, chunks[1]);
+}
+
 fn render_header(f: &mut Frame, area: Rect) {
     let header = Block::default()
         .borders(Borders::ALL)
@@ -42,6 +56,7 @@ fn render_header(f: &mut Frame, area: Rect) {
         Line::from(vec![
             Span::styled("SolFlow", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
             Span::raw(" - Live Trade Monitor"),
+            Span::raw("  |  Tab: trades/signals  |  'q' or Esc to quit"),
         ]),
         Line::from(vec![
             Span::raw("Press 'q' or Esc to quit"),
@@ -1,5 +1,6 @@
 pub mod layout;
 pub mod renderer;
+pub mod signal_browser;
 pub mod terminal;
 
 pub use terminal::run_ui;
@@ -0,0 +1,555 @@
+//! Historical signal browser
+//!
+//! Pages through past `token_signals` rows in the pipeline database
+//! (`SOLFLOW_DB_PATH`), newest first, so what fired overnight can be reviewed
+//! without leaving the terminal. Blocklisted mints are left out.
+//!
+//! Keys on the Signals screen:
+//! - `↑`/`↓` select, `←`/`→` or `PgUp`/`PgDn` page
+//! - `t` cycle signal type, `s` cycle minimum severity, `r` cycle time range
+//! - `Enter` jump to the selected token (its aggregates and signals only),
+//!   `Backspace` back to all tokens
+//! - `R` reload
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row, Table},
+    Frame,
+};
+use rusqlite::{params_from_iter, types::Value, Connection, OpenFlags, OptionalExtension};
+use std::time::{Duration, Instant};
+
+/// Signals per page
+const PAGE_SIZE: i64 = 30;
+
+/// Time range presets: (label, seconds back from now; 0 = everything)
+const RANGES: &[(&str, i64)] = &[
+    ("1h", 3600),
+    ("12h", 43_200),
+    ("24h", 86_400),
+    ("7d", 604_800),
+    ("all", 0),
+];
+
+/// First page reloads this often so new signals show up
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Which signals to list
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalFilter {
+    /// Exact signal_type, None for all
+    pub signal_type: Option<String>,
+    pub min_severity: i32,
+    /// Index into `RANGES`
+    pub range: usize,
+    /// Only this token's signals (jump-to-token)
+    pub mint: Option<String>,
+}
+
+impl Default for SignalFilter {
+    fn default() -> Self {
+        Self {
+            signal_type: None,
+            min_severity: 1,
+            range: 1, // 12h covers a night
+            mint: None,
+        }
+    }
+}
+
+impl SignalFilter {
+    /// WHERE clause and its parameters
+    fn where_clause(&self, now: i64) -> (String, Vec<Value>) {
+        let mut conditions = vec![
+            "s.severity >= ?".to_string(),
+            "NOT EXISTS (SELECT 1 FROM mint_blocklist b
+                WHERE b.mint = s.mint AND (b.expires_at IS NULL OR b.expires_at > ?))"
+                .to_string(),
+        ];
+        let mut params = vec![Value::Integer(self.min_severity as i64), Value::Integer(now)];
+
+        if let Some(signal_type) = &self.signal_type {
+            conditions.push("s.signal_type = ?".to_string());
+            params.push(Value::Text(signal_type.clone()));
+        }
+        let (_, range_secs) = RANGES[self.range];
+        if range_secs > 0 {
+            conditions.push("s.created_at >= ?".to_string());
+            params.push(Value::Integer(now - range_secs));
+        }
+        if let Some(mint) = &self.mint {
+            conditions.push("s.mint = ?".to_string());
+            params.push(Value::Text(mint.clone()));
+        }
+        (conditions.join(" AND "), params)
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "type: {} | severity >= {} | range: {}{}",
+            self.signal_type.as_deref().unwrap_or("all"),
+            self.min_severity,
+            RANGES[self.range].0,
+            self.mint
+                .as_deref()
+                .map(|mint| format!(" | token: {}", mint))
+                .unwrap_or_default()
+        )
+    }
+}
+
+/// One past signal
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalRow {
+    pub id: i64,
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub signal_type: String,
+    pub severity: i32,
+    pub score: Option<f64>,
+    pub window_seconds: i32,
+    pub source: String,
+    pub created_at: i64,
+}
+
+/// Current aggregates of the token jumped to
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenSummary {
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub net_flow_300s_sol: Option<f64>,
+    pub net_flow_3600s_sol: Option<f64>,
+    pub unique_wallets_300s: Option<i64>,
+    pub market_cap_usd: Option<f64>,
+    pub updated_at: i64,
+}
+
+/// One page of signals matching `filter` (newest first) and the total match count
+pub fn query_signals(
+    conn: &Connection,
+    filter: &SignalFilter,
+    now: i64,
+    page: i64,
+) -> rusqlite::Result<(Vec<SignalRow>, i64)> {
+    let (where_clause, mut params) = filter.where_clause(now);
+
+    let total = conn.query_row(
+        &format!("SELECT COUNT(*) FROM token_signals s WHERE {where_clause}"),
+        params_from_iter(params.iter()),
+        |row| row.get(0),
+    )?;
+
+    params.push(Value::Integer(PAGE_SIZE));
+    params.push(Value::Integer(page * PAGE_SIZE));
+    let mut stmt = conn.prepare(&format!(
+        "SELECT s.id, s.mint, m.symbol, s.signal_type, s.severity, s.score,
+                s.window_seconds, s.source, s.created_at
+         FROM token_signals s
+         LEFT JOIN token_metadata m ON m.mint = s.mint
+         WHERE {where_clause}
+         ORDER BY s.created_at DESC, s.id DESC
+         LIMIT ? OFFSET ?"
+    ))?;
+    let rows = stmt
+        .query_map(params_from_iter(params.iter()), |row| {
+            Ok(SignalRow {
+                id: row.get(0)?,
+                mint: row.get(1)?,
+                symbol: row.get(2)?,
+                signal_type: row.get(3)?,
+                severity: row.get(4)?,
+                score: row.get(5)?,
+                window_seconds: row.get(6)?,
+                source: row.get(7)?,
+                created_at: row.get(8)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok((rows, total))
+}
+
+/// Signal types present in the table, for the type filter
+pub fn signal_types(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT DISTINCT signal_type FROM token_signals ORDER BY signal_type")?;
+    let types = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(types)
+}
+
+/// The token's current aggregates (None if it has none)
+pub fn token_summary(conn: &Connection, mint: &str) -> rusqlite::Result<Option<TokenSummary>> {
+    conn.query_row(
+        "SELECT t.mint, m.symbol, t.net_flow_300s_sol, t.net_flow_3600s_sol,
+                t.unique_wallets_300s, t.market_cap_usd, t.updated_at
+         FROM token_aggregates t
+         LEFT JOIN token_metadata m ON m.mint = t.mint
+         WHERE t.mint = ?1",
+        [mint],
+        |row| {
+            Ok(TokenSummary {
+                mint: row.get(0)?,
+                symbol: row.get(1)?,
+                net_flow_300s_sol: row.get(2)?,
+                net_flow_3600s_sol: row.get(3)?,
+                unique_wallets_300s: row.get(4)?,
+                market_cap_usd: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        },
+    )
+    .optional()
+}
+
+/// State of the Signals screen
+pub struct SignalBrowser {
+    db_path: String,
+    filter: SignalFilter,
+    page: i64,
+    selected: usize,
+    rows: Vec<SignalRow>,
+    total: i64,
+    types: Vec<String>,
+    token: Option<TokenSummary>,
+    error: Option<String>,
+    loaded_at: Option<Instant>,
+}
+
+impl SignalBrowser {
+    pub fn new(db_path: impl Into<String>) -> Self {
+        Self {
+            db_path: db_path.into(),
+            filter: SignalFilter::default(),
+            page: 0,
+            selected: 0,
+            rows: Vec::new(),
+            total: 0,
+            types: Vec::new(),
+            token: None,
+            error: None,
+            loaded_at: None,
+        }
+    }
+
+    /// Browser over `SOLFLOW_DB_PATH` (same default as the pipeline)
+    pub fn from_env() -> Self {
+        Self::new(
+            std::env::var("SOLFLOW_DB_PATH")
+                .unwrap_or_else(|_| "/var/lib/solflow/solflow.db".to_string()),
+        )
+    }
+
+    fn load(&mut self, now: i64) -> rusqlite::Result<()> {
+        let conn = Connection::open_with_flags(&self.db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        conn.busy_timeout(Duration::from_secs(1))?;
+
+        let (rows, total) = query_signals(&conn, &self.filter, now, self.page)?;
+        self.rows = rows;
+        self.total = total;
+        self.types = signal_types(&conn)?;
+        self.token = match &self.filter.mint {
+            Some(mint) => token_summary(&conn, mint)?,
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Re-run the query for the current filter and page
+    pub fn reload(&mut self, now: i64) {
+        self.error = self.load(now).err().map(|e| e.to_string());
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+        self.loaded_at = Some(Instant::now());
+    }
+
+    /// Reload when never loaded, or periodically while on the first page
+    pub fn refresh_if_stale(&mut self, now: i64) {
+        let stale = match self.loaded_at {
+            None => true,
+            Some(at) => self.page == 0 && at.elapsed() >= REFRESH_INTERVAL,
+        };
+        if stale {
+            self.reload(now);
+        }
+    }
+
+    fn page_count(&self) -> i64 {
+        ((self.total + PAGE_SIZE - 1) / PAGE_SIZE).max(1)
+    }
+
+    fn set_filter(&mut self, filter: SignalFilter, now: i64) {
+        self.filter = filter;
+        self.page = 0;
+        self.selected = 0;
+        self.reload(now);
+    }
+
+    /// Handle a key press; returns false for keys the browser doesn't use
+    pub fn handle_key(&mut self, code: KeyCode, now: i64) -> bool {
+        match code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.rows.len().saturating_sub(1))
+            }
+            KeyCode::Right | KeyCode::PageDown if self.page + 1 < self.page_count() => {
+                self.page += 1;
+                self.selected = 0;
+                self.reload(now);
+            }
+            KeyCode::Left | KeyCode::PageUp if self.page > 0 => {
+                self.page -= 1;
+                self.selected = 0;
+                self.reload(now);
+            }
+            KeyCode::Char('t') => {
+                // None → each known type → None
+                let next = match &self.filter.signal_type {
+                    None => self.types.first().cloned(),
+                    Some(current) => self
+                        .types
+                        .iter()
+                        .position(|t| t == current)
+                        .and_then(|i| self.types.get(i + 1))
+                        .cloned(),
+                };
+                let filter = SignalFilter {
+                    signal_type: next,
+                    ..self.filter.clone()
+                };
+                self.set_filter(filter, now);
+            }
+            KeyCode::Char('s') => {
+                let filter = SignalFilter {
+                    min_severity: self.filter.min_severity % 5 + 1,
+                    ..self.filter.clone()
+                };
+                self.set_filter(filter, now);
+            }
+            KeyCode::Char('r') => {
+                let filter = SignalFilter {
+                    range: (self.filter.range + 1) % RANGES.len(),
+                    ..self.filter.clone()
+                };
+                self.set_filter(filter, now);
+            }
+            KeyCode::Enter => {
+                if let Some(row) = self.rows.get(self.selected) {
+                    let filter = SignalFilter {
+                        mint: Some(row.mint.clone()),
+                        ..self.filter.clone()
+                    };
+                    self.set_filter(filter, now);
+                }
+            }
+            KeyCode::Backspace if self.filter.mint.is_some() => {
+                let filter = SignalFilter {
+                    mint: None,
+                    ..self.filter.clone()
+                };
+                self.set_filter(filter, now);
+            }
+            KeyCode::Char('R') => self.reload(now),
+            KeyCode::Right | KeyCode::PageDown | KeyCode::Left | KeyCode::PageUp => {}
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect) {
+        let token_height = if self.filter.mint.is_some() { 4 } else { 0 };
+        let chunks = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),            // Filters
+                Constraint::Length(token_height), // Jumped-to token
+                Constraint::Min(0),               // Signals
+            ])
+            .split(area);
+
+        let filters = vec![
+            Line::from(vec![
+                Span::styled("Filters: ", Style::default().fg(Color::Cyan)),
+                Span::raw(self.filter.describe()),
+            ]),
+        ];
+        f.render_widget(
+            Paragraph::new(filters).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("t type | s severity | r range | Enter token | Backspace all | ←/→ page"),
+            ),
+            chunks[0],
+        );
+
+        if self.filter.mint.is_some() {
+            self.render_token(f, chunks[1]);
+        }
+        self.render_table(f, chunks[2]);
+    }
+
+    fn render_token(&self, f: &mut Frame, area: Rect) {
+        let fmt_sol = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:+.3}", v));
+        let text = match &self.token {
+            Some(token) => vec![
+                Line::from(vec![
+                    Span::styled(
+                        token.symbol.clone().unwrap_or_else(|| "?".to_string()),
+                        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!("  {}", token.mint)),
+                ]),
+                Line::from(format!(
+                    "net 5m: {} SOL | net 1h: {} SOL | wallets 5m: {} | mcap: {} | updated {}",
+                    fmt_sol(token.net_flow_300s_sol),
+                    fmt_sol(token.net_flow_3600s_sol),
+                    token.unique_wallets_300s.map_or("-".to_string(), |w| w.to_string()),
+                    token.market_cap_usd.map_or("-".to_string(), |m| format!("${:.0}", m)),
+                    format_datetime(token.updated_at),
+                )),
+            ],
+            None => vec![Line::from("No current aggregates for this token")],
+        };
+        f.render_widget(
+            Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Token")),
+            area,
+        );
+    }
+
+    fn render_table(&self, f: &mut Frame, area: Rect) {
+        let title = match &self.error {
+            Some(e) => format!("Signals - error: {}", e),
+            None => format!(
+                "Signals - page {}/{} ({} matching)",
+                self.page + 1,
+                self.page_count(),
+                self.total
+            ),
+        };
+
+        let header = Row::new(vec!["Time", "Token", "Type", "Sev", "Score", "Window", "Source"])
+            .style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, signal)| {
+                let severity_color = match signal.severity {
+                    5.. => Color::Red,
+                    4 => Color::LightRed,
+                    3 => Color::Yellow,
+                    _ => Color::Gray,
+                };
+                let mut style = Style::default().fg(severity_color);
+                if i == self.selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                let token = signal
+                    .symbol
+                    .clone()
+                    .unwrap_or_else(|| signal.mint.chars().take(8).collect());
+                Row::new(vec![
+                    format_datetime(signal.created_at),
+                    token,
+                    signal.signal_type.clone(),
+                    signal.severity.to_string(),
+                    signal.score.map_or("-".to_string(), |s| format!("{:.2}", s)),
+                    format!("{}s", signal.window_seconds),
+                    signal.source.clone(),
+                ])
+                .style(style)
+            })
+            .collect();
+
+        let widths = [
+            Constraint::Length(12), // Time
+            Constraint::Length(12), // Token
+            Constraint::Length(20), // Type
+            Constraint::Length(4),  // Sev
+            Constraint::Length(8),  // Score
+            Constraint::Length(8),  // Window
+            Constraint::Min(8),     // Source
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::default().borders(Borders::ALL).title(title));
+        f.render_widget(table, area);
+    }
+}
+
+fn format_datetime(timestamp: i64) -> String {
+    chrono::DateTime::<chrono::Utc>::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::db::run_schema_migrations;
+
+    fn seeded_db() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        run_schema_migrations(&mut conn, "sql").unwrap();
+        for (mint, signal_type, severity, created_at) in [
+            ("alpha", "BREAKOUT", 4, 1000),
+            ("alpha", "SURGE", 2, 2000),
+            ("beta", "BREAKOUT", 3, 3000),
+            ("blocked", "BREAKOUT", 5, 3500),
+            ("beta", "SURGE", 5, 90_000),
+        ] {
+            conn.execute(
+                "INSERT INTO token_signals (mint, signal_type, window_seconds, severity, created_at)
+                 VALUES (?1, ?2, 60, ?3, ?4)",
+                rusqlite::params![mint, signal_type, severity, created_at],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO mint_blocklist (mint, reason, blocked_by, created_at) VALUES ('blocked', 'rug', 'test', 0)",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_query_signals_filters() {
+        let conn = seeded_db();
+        let now = 90_000;
+        let all_time = SignalFilter {
+            range: RANGES.len() - 1,
+            ..Default::default()
+        };
+
+        // Newest first, blocklisted mint left out
+        let (rows, total) = query_signals(&conn, &all_time, now, 0).unwrap();
+        assert_eq!(total, 4);
+        let order: Vec<i64> = rows.iter().map(|r| r.created_at).collect();
+        assert_eq!(order, vec![90_000, 3000, 2000, 1000]);
+
+        let filter = SignalFilter {
+            signal_type: Some("BREAKOUT".to_string()),
+            min_severity: 4,
+            ..all_time.clone()
+        };
+        let (rows, _) = query_signals(&conn, &filter, now, 0).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].mint.as_str(), rows[0].severity), ("alpha", 4));
+
+        // Jump to token, last 24h only
+        let filter = SignalFilter {
+            mint: Some("beta".to_string()),
+            range: 2,
+            ..Default::default()
+        };
+        let (rows, total) = query_signals(&conn, &filter, now, 0).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(rows[0].signal_type, "SURGE");
+
+        assert_eq!(signal_types(&conn).unwrap(), vec!["BREAKOUT", "SURGE"]);
+    }
+}
@@ -1,5 +1,6 @@
 use {
-    crate::state::State,
+    crate::{pipeline::shutdown::ShutdownSignal, state::State, ui::signal_browser::SignalBrowser},
+    crossterm::event::{KeyCode, KeyModifiers},
     ratatui::{
         backend::CrosstermBackend,
         Terminal,
@@ -11,11 +12,34 @@ use {
     tokio::sync::RwLock,
 };
 
+/// Which screen the TUI shows (Tab switches)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Screen {
+    Trades,
+    Signals,
+}
+
+/// Restores the terminal however the UI loop exits (quit, shutdown or error)
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(
+            std::io::stdout(),
+            crossterm::terminal::LeaveAlternateScreen,
+            crossterm::cursor::Show
+        );
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
 /// Run the TUI event loop
 /// 
-/// Handles keyboard input, terminal resize, and adaptive refresh throttling
+/// Handles keyboard input, terminal resize, and adaptive refresh throttling.
+/// Exits on q/Esc/Ctrl+C (raw mode swallows SIGINT) or when `shutdown` fires.
 pub async fn run_ui(
     state: Arc<RwLock<State>>,
+    mut shutdown: ShutdownSignal,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Setup terminal
     let stdout = std::io::stdout();
@@ -24,6 +48,7 @@ pub async fn run_ui(
     
     // Enable raw mode for keyboard input
     crossterm::terminal::enable_raw_mode()?;
+    let _guard = TerminalGuard;
     
     // Clear screen and enter alternate screen mode
     // This creates a separate screen buffer, isolating stdout from stderr logs
@@ -43,8 +68,15 @@ pub async fn run_ui(
     let mut last_trade_count = 0;
     let mut last_refresh = Instant::now();
     let mut trade_rate_samples = Vec::new();
+
+    let mut screen = Screen::Trades;
+    let mut browser = SignalBrowser::from_env();
     
     loop {
+        if shutdown.is_triggered() {
+            break;
+        }
+
         // Calculate adaptive refresh interval
         let current_trade_count = {
             let state = state.read().await;
@@ -79,10 +111,23 @@ pub async fn run_ui(
         // Check for keyboard input (non-blocking)
         if crossterm::event::poll(refresh_interval)? {
             if let crossterm::event::Event::Key(key) = crossterm::event::read()? {
+                let now = chrono::Utc::now().timestamp();
                 match key.code {
-                    crossterm::event::KeyCode::Char('q') | crossterm::event::KeyCode::Esc => {
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         break;
                     }
+                    KeyCode::Char('q') | KeyCode::Esc => {
+                        break;
+                    }
+                    KeyCode::Tab => {
+                        screen = match screen {
+                            Screen::Trades => Screen::Signals,
+                            Screen::Signals => Screen::Trades,
+                        };
+                    }
+                    code if screen == Screen::Signals => {
+                        browser.handle_key(code, now);
+                    }
                     _ => {
                         // Other keys can be handled here (scroll, pause, etc.)
                     }
@@ -91,7 +136,11 @@ pub async fn run_ui(
         }
         
         // Render UI
-        {
+        if screen == Screen::Signals {
+            browser.refresh_if_stale(chrono::Utc::now().timestamp());
+            let area = terminal.size()?;
+            terminal.draw(|f| crate::ui::layout::render_signals_layout(f, area, &browser))?;
+        } else {
             let state = state.read().await;
             let area = terminal.size()?;
             terminal.draw(|f| {
@@ -105,13 +154,7 @@ pub async fn run_ui(
         last_refresh = Instant::now();
     }
     
-    // Cleanup - restore terminal state
-    crossterm::execute!(
-        std::io::stdout(),
-        crossterm::terminal::LeaveAlternateScreen,
-        crossterm::cursor::Show
-    )?;
-    crossterm::terminal::disable_raw_mode()?;
+    // Cleanup - TerminalGuard restores terminal state
     Ok(())
 }
 
@@ -30,6 +30,7 @@ mod dual_channel_tests {
             token_decimals: 6,
             user_account: Some("test_wallet".to_string()),
             discriminator: "0123456789abcdef".to_string(),
+            ..Default::default()
         }
     }
 
@@ -79,6 +80,7 @@ mod dual_channel_tests {
             token_decimals: 6,
             user_account: "test_wallet".to_string(),
             source_program: "TestProgram".to_string(),
+            ..Default::default()
         };
 
         tx.send(trade.clone()).await.unwrap();
@@ -105,6 +107,7 @@ mod dual_channel_tests {
             token_decimals: 6,
             user_account: "wallet".to_string(),
             source_program: "Test".to_string(),
+            ..Default::default()
         };
 
         // Fill the channel
@@ -181,6 +184,8 @@ mod dual_channel_tests {
             token_decimals: streamer_event.token_decimals,
             user_account: streamer_event.user_account.clone().unwrap_or_default(),
             source_program: streamer_event.program_name.clone(),
+            signature: streamer_event.signature.clone(),
+            ..Default::default()
         };
 
         // Verify all fields preserved
@@ -209,6 +214,8 @@ mod dual_channel_tests {
             token_decimals: streamer_event.token_decimals,
             user_account: streamer_event.user_account.clone().unwrap_or_default(),
             source_program: streamer_event.program_name.clone(),
+            signature: streamer_event.signature.clone(),
+            ..Default::default()
         };
 
         assert_eq!(pipeline_event.user_account, ""); // Empty string when None
@@ -233,6 +240,7 @@ mod dual_channel_tests {
             token_decimals: 6,
             user_account: "wallet_1".to_string(),
             source_program: "PumpSwap".to_string(),
+            ..Default::default()
         };
         tx1.send(trade1).await.unwrap();
 
@@ -246,6 +254,7 @@ mod dual_channel_tests {
             token_decimals: 6,
             user_account: "wallet_2".to_string(),
             source_program: "BonkSwap".to_string(),
+            ..Default::default()
         };
         tx2.send(trade2).await.unwrap();
 
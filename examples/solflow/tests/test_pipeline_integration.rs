@@ -33,6 +33,7 @@ mod pipeline_integration_tests {
                     token_decimals: 6,
                     user_account: format!("test_wallet_{}", i),
                     source_program: "MockStreamer".to_string(),
+                    ..Default::default()
                 };
                 if tx.send(trade).await.is_err() {
                     break; // Channel closed
@@ -87,6 +88,7 @@ mod pipeline_integration_tests {
                         token_decimals: 6,
                         user_account: "test_wallet".to_string(),
                         source_program: source_name.clone(),
+                        ..Default::default()
                     };
                     let _ = tx_clone.send(trade).await;
                 }
@@ -134,6 +136,7 @@ mod pipeline_integration_tests {
                     token_decimals: 6,
                     user_account: "wallet".to_string(),
                     source_program: "Test".to_string(),
+                    ..Default::default()
                 };
                 
                 // try_send is non-blocking (used by streamers)
@@ -184,6 +187,7 @@ mod pipeline_integration_tests {
                     token_decimals: 6,
                     user_account: "wallet".to_string(),
                     source_program: "Test".to_string(),
+                    ..Default::default()
                 };
                 let _ = tx.send(trade).await;
             }
@@ -222,6 +226,7 @@ mod pipeline_integration_tests {
                     token_decimals: 6,
                     user_account: "wallet".to_string(),
                     source_program: "Test".to_string(),
+                    ..Default::default()
                 };
                 let _ = tx.send(trade).await;
             }
@@ -293,6 +298,7 @@ mod pipeline_integration_tests {
                     token_decimals: 6,
                     user_account: "test_wallet".to_string(),
                     source_program: "TestStreamer".to_string(),
+                    ..Default::default()
                 };
                 let _ = tx.send(trade).await;
             }
@@ -1,4 +1,4 @@
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use {
     async_trait::async_trait,
     carbon_core::{
@@ -16,6 +16,9 @@ pub struct PrometheusMetrics {
     pub gauges: RwLock<HashMap<String, metrics::Gauge>>,
     pub histograms: RwLock<HashMap<String, metrics::Histogram>>,
     pub listen_port: u16,
+    pub listen_ip: IpAddr,
+    /// Bucket bounds for all histograms; `None` exports them as summaries
+    pub histogram_buckets: Option<Vec<f64>>,
 }
 
 impl Default for PrometheusMetrics {
@@ -25,6 +28,8 @@ impl Default for PrometheusMetrics {
             gauges: RwLock::new(HashMap::new()),
             histograms: RwLock::new(HashMap::new()),
             listen_port: 9100,
+            listen_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            histogram_buckets: None,
         }
     }
 }
@@ -35,12 +40,25 @@ impl PrometheusMetrics {
 
     pub fn new_with_port(listen_port: u16) -> Self {
         Self {
-            gauges: RwLock::new(HashMap::new()),
-            counters: RwLock::new(HashMap::new()),
-            histograms: RwLock::new(HashMap::new()),
             listen_port,
+            ..Self::default()
         }
     }
+
+    /// Listen on `addr` instead of `127.0.0.1:9100` (e.g. `0.0.0.0:9100` for a remote scraper)
+    pub fn new_with_addr(addr: SocketAddr) -> Self {
+        Self {
+            listen_port: addr.port(),
+            listen_ip: addr.ip(),
+            ..Self::default()
+        }
+    }
+
+    /// Export histograms with these bucket bounds instead of as summaries
+    pub fn with_histogram_buckets(mut self, buckets: Vec<f64>) -> Self {
+        self.histogram_buckets = Some(buckets);
+        self
+    }
 }
 
 #[async_trait]
@@ -50,11 +68,21 @@ impl Metrics for PrometheusMetrics {
 
         let mut result = Ok(());
         INIT.call_once(|| {
-            let addr = format!("127.0.0.1:{}", self.listen_port)
-                .parse::<SocketAddr>()
-                .expect("Failed to parse address");
-
-            let builder = PrometheusBuilder::new().with_http_listener(addr);
+            let addr = SocketAddr::new(self.listen_ip, self.listen_port);
+
+            let mut builder = PrometheusBuilder::new().with_http_listener(addr);
+            if let Some(buckets) = &self.histogram_buckets {
+                builder = match builder.set_buckets(buckets) {
+                    Ok(builder) => builder,
+                    Err(e) => {
+                        result = Err(Error::Custom(format!(
+                            "Invalid Prometheus histogram buckets: {}",
+                            e
+                        )));
+                        return;
+                    }
+                };
+            }
 
             match builder.install() {
                 Ok(_handle) => {